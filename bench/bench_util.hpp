@@ -0,0 +1,86 @@
+///
+/// @file  bench_util.hpp
+/// @brief A minimal benchmark harness: runs a case several times after a warm-up, and reports the
+/// median time per operation.
+///
+
+#ifndef SYNC_CELL_BENCH_UTIL_HPP
+#define SYNC_CELL_BENCH_UTIL_HPP
+
+#include <algorithm>
+#include <atomic>
+#include <chrono>
+#include <cstdint>
+#include <iomanip>
+#include <iostream>
+#include <string_view>
+#include <thread>
+#include <vector>
+
+
+constexpr uint32_t SampleCount = 5;
+
+inline int64_t now_ns()
+{
+    using namespace std::chrono;
+    return duration_cast<nanoseconds>(steady_clock::now().time_since_epoch()).count();
+}
+
+/// @brief Prevents the compiler from optimizing out the computation of 'value'.
+template<typename T>
+void do_not_optimize(const T &value)
+{
+#if defined(_MSC_VER)
+    static const void *volatile sink;
+    sink = &value;
+#else
+    asm volatile("" : : "r,m"(value) : "memory");
+#endif
+}
+
+/// @brief Runs 'run' once for the warm-up and 'SampleCount' times for the measurement. Each run
+/// performs 'ops' operations and returns its elapsed time in nanoseconds.
+template<typename Func>
+void bench(std::string_view group, std::string_view name, uint64_t ops, Func &&run)
+{
+    run();
+
+    std::vector<double> samples;
+    samples.reserve(SampleCount);
+    for (uint32_t i = 0; i < SampleCount; ++i) {
+        samples.push_back(static_cast<double>(run()) / static_cast<double>(ops));
+    }
+    std::sort(samples.begin(), samples.end());
+
+    auto median = samples[samples.size() / 2];
+    std::cout << std::left << std::setw(24) << group << std::setw(36) << name << std::right << std::fixed
+              << std::setprecision(2) << std::setw(10) << median << " ns/op  [" << samples.front() << ", "
+              << samples.back() << "]  " << std::setprecision(0) << 1e9 / median << " ops/s" << std::endl;
+}
+
+/// @brief Starts 'count' threads running 'f(index)' at the same time, and returns the elapsed time
+/// until all of them finished.
+template<typename Func>
+int64_t run_threads(uint32_t count, Func &&f)
+{
+    std::atomic<bool> start{false};
+    std::vector<std::thread> threads;
+    threads.reserve(count);
+    for (uint32_t i = 0; i < count; ++i) {
+        threads.emplace_back([&start, &f, i] {
+            while (!start.load(std::memory_order_acquire)) {
+                std::this_thread::yield();
+            }
+            f(i);
+        });
+    }
+
+    auto begin = now_ns();
+    start.store(true, std::memory_order_release);
+    for (auto &t: threads) {
+        t.join();
+    }
+    return now_ns() - begin;
+}
+
+#endif //SYNC_CELL_BENCH_UTIL_HPP
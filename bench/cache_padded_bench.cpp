@@ -0,0 +1,69 @@
+///
+/// @file  cache_padded_bench.cpp
+/// @brief Benchmark of the per-thread counters with and without sc::util::CachePadded, which shows
+/// the cost of the false sharing.
+///
+
+#include "util/cache_padded.hpp"
+
+#include <array>
+#include <string>
+
+#include "bench_util.hpp"
+
+
+constexpr uint64_t OpCount = 10'000'000;
+constexpr uint32_t MaxThreadCount = 4;
+
+/// @brief The counters share the cache lines.
+struct PlainCounters
+{
+    std::array<std::atomic<uint64_t>, MaxThreadCount> counters{};
+
+    std::atomic<uint64_t> &operator[](uint32_t index) noexcept
+    {
+        return counters[index];
+    }
+};
+
+/// @brief Each counter has its own cache line.
+struct PaddedCounters
+{
+    std::array<sc::util::CachePadded<std::atomic<uint64_t>>, MaxThreadCount> counters{};
+
+    std::atomic<uint64_t> &operator[](uint32_t index) noexcept
+    {
+        return *counters[index];
+    }
+};
+
+/// @brief Each of the 'threads' threads increments its own counter, 'OpCount' times in total.
+template<typename Counters>
+void bench_counters(std::string_view name, uint32_t threads)
+{
+    auto group = std::to_string(threads) + " threads";
+    bench(group, name, OpCount, [threads] {
+        Counters counters;
+        auto elapsed = run_threads(threads, [&counters, threads](uint32_t index) {
+            auto &counter = counters[index];
+            for (uint64_t i = 0; i < OpCount / threads; ++i) {
+                counter.fetch_add(1, std::memory_order_relaxed);
+            }
+        });
+        do_not_optimize(counters);
+        return elapsed;
+    });
+}
+
+int main()
+{
+    std::cout << "CacheLineSize: " << sc::util::CacheLineSize << ", sizeof(CachePadded<std::atomic<uint64_t>>): "
+              << sizeof(sc::util::CachePadded<std::atomic<uint64_t>>) << std::endl;
+
+    for (uint32_t threads = 2; threads <= MaxThreadCount; threads *= 2) {
+        bench_counters<PlainCounters>("adjacent std::atomic<uint64_t>", threads);
+        bench_counters<PaddedCounters>("CachePadded<std::atomic<uint64_t>>", threads);
+    }
+
+    return 0;
+}
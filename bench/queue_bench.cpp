@@ -0,0 +1,119 @@
+///
+/// @file  queue_bench.cpp
+/// @brief Benchmark of the queues under varying producer/consumer counts, against a std::mutex
+/// guarded std::deque.
+///
+
+#include "pool/object_pool.hpp"
+#include "queue/mpmc_array_queue.hpp"
+#include "queue/mpmc_bounded_queue.hpp"
+#include "queue/mpmc_list_queue.hpp"
+#include "queue/mpsc_list_queue.hpp"
+#include "queue/spsc_ring_buffer.hpp"
+
+#include <deque>
+#include <memory>
+#include <mutex>
+#include <optional>
+#include <string>
+
+#include "bench_util.hpp"
+
+
+constexpr uint64_t OpCount = 1'000'000;
+constexpr size_t BoundedCapacity = 1024;
+
+/// @brief The baseline, a std::deque guarded by a std::mutex.
+template<typename T>
+class MutexQueue
+{
+public:
+    void enqueue(T value)
+    {
+        std::lock_guard guard(mtx_);
+        queue_.push_back(std::move(value));
+    }
+
+    std::optional<T> try_dequeue()
+    {
+        std::lock_guard guard(mtx_);
+        if (queue_.empty()) {
+            return std::nullopt;
+        }
+        auto value = std::move(queue_.front());
+        queue_.pop_front();
+        return value;
+    }
+
+private:
+    std::mutex mtx_;
+    std::deque<T> queue_;
+};
+
+/// @brief Runs 'producers' threads enqueuing 'OpCount' values in total, and 'consumers' threads
+/// dequeuing them until all values are consumed. Each run starts with a new queue constructed by
+/// 'args'.
+template<typename Queue, typename... Args>
+void bench_queue(std::string_view name, uint32_t producers, uint32_t consumers, Args... args)
+{
+    auto group = std::to_string(producers) + "P / " + std::to_string(consumers) + "C";
+    bench(group, name, OpCount, [&] {
+        auto queue = std::make_unique<Queue>(args...);
+        std::atomic<uint64_t> consumed{0};
+        return run_threads(producers + consumers, [&](uint32_t index) {
+            if (index < producers) {
+                for (uint64_t i = index; i < OpCount; i += producers) {
+                    queue->enqueue(i);
+                }
+                return;
+            }
+            while (consumed.load(std::memory_order_relaxed) < OpCount) {
+                if (auto v = queue->try_dequeue()) {
+                    do_not_optimize(*v);
+                    consumed.fetch_add(1, std::memory_order_relaxed);
+                }
+            }
+        });
+    });
+}
+
+int main()
+{
+    constexpr std::pair<uint32_t, uint32_t> Configs[] = {{1, 1}, {4, 1}, {1, 4}, {4, 4}};
+    for (auto [p, c]: Configs) {
+        bench_queue<sc::mpmc::ArrayListQueue<uint64_t>>("mpmc::ArrayListQueue", p, c);
+        bench_queue<sc::mpmc::BoundedQueue<uint64_t>>("mpmc::BoundedQueue", p, c, BoundedCapacity);
+        bench_queue<sc::mpmc::FixedBoundedQueue<uint64_t, BoundedCapacity>>("mpmc::FixedBoundedQueue", p, c);
+        bench_queue<sc::mpmc::LinkedListQueue<uint64_t>>("mpmc::LinkedListQueue", p, c);
+        bench_queue<sc::mpmc::LinkedListQueue<uint64_t, 0, sc::pool::PoolAllocator<uint64_t>>>(
+                "mpmc::LinkedListQueue + PoolAllocator", p, c);
+        if (c == 1) {
+            bench_queue<sc::mpsc::LinkedListQueue<uint64_t>>("mpsc::LinkedListQueue", p, c);
+            bench_queue<sc::mpsc::LinkedListQueue<uint64_t, 0, sc::pool::PoolAllocator<uint64_t>>>(
+                    "mpsc::LinkedListQueue + PoolAllocator", p, c);
+        }
+        bench_queue<MutexQueue<uint64_t>>("std::mutex + std::deque", p, c);
+    }
+
+    // The SPSC ring buffer is split into the producer and consumer handles.
+    bench("1P / 1C", "spsc::RingBuffer", OpCount, [] {
+        sc::spsc::RingBuffer<uint64_t> ring_buffer(BoundedCapacity);
+        auto split = ring_buffer.split();
+        return run_threads(2, [&split](uint32_t index) {
+            if (index == 0) {
+                for (uint64_t i = 0; i < OpCount; ++i) {
+                    split.first.enqueue(i);
+                }
+                return;
+            }
+            for (uint64_t n = 0; n < OpCount;) {
+                if (auto v = split.second.try_dequeue()) {
+                    do_not_optimize(*v);
+                    ++n;
+                }
+            }
+        });
+    });
+
+    return 0;
+}
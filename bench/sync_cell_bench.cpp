@@ -0,0 +1,142 @@
+///
+/// @file  sync_cell_bench.cpp
+/// @brief Benchmark of sc::SyncCell load/store against std::atomic and a std::mutex guarded value,
+/// and of the contended updates by the CAS loop, the lock and the flat combining.
+///
+
+#include "cell/sync_cell.hpp"
+
+#include <mutex>
+#include <string>
+
+#include "bench_util.hpp"
+
+
+constexpr uint64_t OpCount = 1'000'000;
+constexpr uint32_t ReaderCount = 3;
+constexpr uint32_t WriterCount = 8;
+
+/// @brief A value updated as a whole, e.g. the statistics of the samples.
+struct Stats
+{
+    uint32_t count;
+    uint32_t total;
+};
+
+/// @brief The baseline of the non-atomic types.
+template<typename T>
+class MutexCell
+{
+public:
+    explicit MutexCell(T value) : value_(std::move(value)) { }
+
+    T load() const
+    {
+        std::lock_guard guard(mtx_);
+        return value_;
+    }
+
+    void store(T value)
+    {
+        std::lock_guard guard(mtx_);
+        value_ = std::move(value);
+    }
+
+private:
+    mutable std::mutex mtx_;
+    T value_;
+};
+
+/// @brief A thin wrapper to give the std::atomic the same interface.
+template<typename T>
+class AtomicCell
+{
+public:
+    explicit AtomicCell(T value) : value_(value) { }
+
+    T load() const
+    {
+        return value_.load(std::memory_order_acquire);
+    }
+
+    void store(T value)
+    {
+        value_.store(value, std::memory_order_release);
+    }
+
+private:
+    std::atomic<T> value_;
+};
+
+template<typename Cell, typename T>
+void bench_cell(std::string_view name, T a, T b)
+{
+    bench("single-thread load", name, OpCount, [&] {
+        Cell cell(a);
+        auto begin = now_ns();
+        for (uint64_t i = 0; i < OpCount; ++i) {
+            auto v = cell.load();
+            do_not_optimize(v);
+        }
+        return now_ns() - begin;
+    });
+
+    bench("single-thread store", name, OpCount, [&] {
+        Cell cell(a);
+        auto begin = now_ns();
+        for (uint64_t i = 0; i < OpCount; ++i) {
+            cell.store((i & 1) ? a : b);
+        }
+        return now_ns() - begin;
+    });
+
+    bench("1 writer / 3 readers", name, OpCount * (ReaderCount + 1), [&] {
+        Cell cell(a);
+        return run_threads(ReaderCount + 1, [&](uint32_t index) {
+            for (uint64_t i = 0; i < OpCount; ++i) {
+                if (index == 0) {
+                    cell.store((i & 1) ? a : b);
+                } else {
+                    auto v = cell.load();
+                    do_not_optimize(v);
+                }
+            }
+        });
+    });
+}
+
+/// @brief All threads update the same cell. It compares the backends on the running machine only,
+/// the flat combining is not expected to win with few cores, see the Readme.
+template<typename Cell>
+void bench_update(std::string_view name)
+{
+    bench("8 writers update", name, OpCount * WriterCount, [&] {
+        Cell cell(Stats{0, 0});
+        return run_threads(WriterCount, [&](uint32_t index) {
+            for (uint64_t i = 0; i < OpCount; ++i) {
+                cell.with_mut([index](Stats &s) {
+                    ++s.count;
+                    s.total += index;
+                });
+            }
+        });
+    });
+}
+
+int main()
+{
+    bench_cell<sc::SyncCell<uint64_t>>("SyncCell<uint64_t>", uint64_t{1}, uint64_t{2});
+    bench_cell<AtomicCell<uint64_t>>("std::atomic<uint64_t>", uint64_t{1}, uint64_t{2});
+    bench_cell<MutexCell<uint64_t>>("std::mutex + uint64_t", uint64_t{1}, uint64_t{2});
+
+    std::string a(32, 'a');
+    std::string b(32, 'b');
+    bench_cell<sc::SyncCell<std::string>>("SyncCell<std::string>", a, b);
+    bench_cell<MutexCell<std::string>>("std::mutex + std::string", a, b);
+
+    bench_update<sc::SyncCell<Stats>>("SyncCell<Stats> CAS loop");
+    bench_update<sc::SyncCell<Stats, sc::ForceLock<>>>("SyncCell<Stats, ForceLock>");
+    bench_update<sc::SyncCell<Stats, sc::FlatCombining<>>>("SyncCell<Stats, FlatCombining>");
+
+    return 0;
+}
@@ -0,0 +1,257 @@
+///
+/// @file  arc_cell.hpp
+/// @brief A cell holding a std::shared_ptr which can be loaded and swapped atomically.
+///
+
+#ifndef SYNC_CELL_ARC_CELL_HPP
+#define SYNC_CELL_ARC_CELL_HPP
+
+#include <atomic>
+#include <cassert>
+#include <cstdint>
+#include <memory>
+#include <utility>
+
+
+namespace sc {
+
+namespace impl {
+
+/// @brief Packs a pointer and a 16-bit counter in one word. The pointer is stored in the low 48
+/// bits, which is the size of the user space addresses on the x86-64 and the AArch64.
+class PackedPtr
+{
+    static_assert(sizeof(void *) == sizeof(uint64_t), "The PackedPtr requires the 64-bit pointers.");
+
+public:
+    static constexpr uint32_t CountShift = 48;
+    static constexpr uint64_t CountOne = uint64_t(1) << CountShift;
+    static constexpr uint64_t PtrMask = CountOne - 1;
+
+    static uint64_t pack(void *ptr) noexcept
+    {
+        auto bits = reinterpret_cast<uint64_t>(ptr);
+        assert((bits & ~PtrMask) == 0);
+        return bits;
+    }
+
+    template<typename P>
+    static P *ptr(uint64_t packed) noexcept
+    {
+        return reinterpret_cast<P *>(packed & PtrMask);
+    }
+
+    static uint64_t count(uint64_t packed) noexcept
+    {
+        return packed >> CountShift;
+    }
+};
+
+}
+
+/// @brief A weak view of a value of an @c ArcCell, from @c ArcCell::downgrade.
+///
+/// The view does not keep the value alive, so an observer holding it for a long time does not
+/// keep a large snapshot after the writer has replaced it and the other readers have released it.
+/// It is immutable, so many threads can @c load it at the same time.
+/// @tparam T The value type.
+template<typename T>
+class WeakCell
+{
+public:
+    using value_type = T;
+    using pointer_type = std::shared_ptr<T>;
+
+    /// @brief Creates a view of no value.
+    WeakCell() noexcept = default;
+
+    explicit WeakCell(const pointer_type &ptr) noexcept : value_(ptr) { }
+
+    /// @brief Gets a strong pointer to the value.
+    /// @return The empty pointer if the value has been released by all strong owners.
+    [[nodiscard]] pointer_type load() const noexcept
+    {
+        return value_.lock();
+    }
+
+    /// @brief Returns true if the value has been released by all strong owners.
+    [[nodiscard]] bool expired() const noexcept
+    {
+        return value_.expired();
+    }
+
+private:
+    std::weak_ptr<T> value_;
+};
+
+/// @brief A cell holding a @c std::shared_ptr which can be loaded and swapped atomically.
+/// Inspired by [arc-swap](https://github.com/vorner/arc-swap).
+///
+/// Readers call @c load to get a copy of the current pointer, and writers publish a new pointer by
+/// @c store, @c swap or @c compare_and_swap. None of the operations takes a lock, so the hot read
+/// path is never blocked by the writers.
+///
+/// The pointer is stored in a node which is shared by the readers with a split reference count: a
+/// reader increases the counter packed with the node pointer (so the node can not be freed while
+/// the reader copies the @c shared_ptr), then gives the reference back. The writer which swaps the
+/// node out moves the packed counter to the node, and the last one who gives the reference back
+/// frees the node.
+///
+/// @note At most 65535 threads can load the value at the same time.
+/// @tparam T The value type.
+template<typename T>
+class ArcCell
+{
+    struct Node
+    {
+        explicit Node(std::shared_ptr<T> &&v) noexcept : value(std::move(v)) { }
+
+        /// @brief Immutable after the node is published.
+        const std::shared_ptr<T> value;
+        /// @brief The references not given back after the node is swapped out.
+        std::atomic<int64_t> internal_count{0};
+    };
+
+    using Packed = impl::PackedPtr;
+
+public:
+    using value_type = T;
+    using pointer_type = std::shared_ptr<T>;
+
+    /// @brief Creates a cell holding the empty pointer.
+    ArcCell() : ArcCell(pointer_type()) { }
+
+    explicit ArcCell(pointer_type ptr) : current_(Packed::pack(new Node(std::move(ptr)))) { }
+
+    ArcCell(const ArcCell &) = delete;
+
+    ArcCell &operator=(const ArcCell &) = delete;
+
+    ~ArcCell()
+    {
+        delete Packed::ptr<Node>(current_.load(std::memory_order_relaxed));
+    }
+
+    [[nodiscard]] bool is_lock_free() const noexcept
+    {
+        return current_.is_lock_free();
+    }
+
+    /// @brief Gets a copy of the current pointer.
+    [[nodiscard]] pointer_type load() const noexcept
+    {
+        auto *node = acquire();
+        pointer_type value = node->value;
+        release(node);
+        return value;
+    }
+
+    /// @brief Gets a weak view of the current value, which does not keep the value alive after it
+    /// is replaced in the cell.
+    [[nodiscard]] WeakCell<T> downgrade() const noexcept
+    {
+        auto *node = acquire();
+        WeakCell<T> weak(node->value);
+        release(node);
+        return weak;
+    }
+
+    /// @brief Replaces the current pointer with 'ptr'.
+    void store(pointer_type ptr)
+    {
+        swap(std::move(ptr));
+    }
+
+    /// @brief Replaces the current pointer with 'ptr'.
+    /// @return The previous pointer.
+    pointer_type swap(pointer_type ptr)
+    {
+        auto *node = new Node(std::move(ptr));
+        auto old = current_.exchange(Packed::pack(node), std::memory_order_acq_rel);
+        auto *old_node = Packed::ptr<Node>(old);
+
+        pointer_type prev = old_node->value;
+        retire(old_node, static_cast<int64_t>(Packed::count(old)));
+        return prev;
+    }
+
+    /// @brief Replaces the current pointer with 'desired' if the current pointer equals to
+    /// 'current' (compared by @c get).
+    /// @return The previous pointer. The pointer is replaced if the result equals to 'current'.
+    pointer_type compare_and_swap(const pointer_type &current, pointer_type desired)
+    {
+        Node *new_node = nullptr;
+        while (true) {
+            auto *node = acquire();
+            if (node->value.get() != current.get()) {
+                pointer_type prev = node->value;
+                release(node);
+                delete new_node;
+                return prev;
+            }
+
+            if (new_node == nullptr) {
+                new_node = new Node(std::move(desired));
+            }
+
+            auto expected = current_.load(std::memory_order_relaxed);
+            while (Packed::ptr<Node>(expected) == node) {
+                if (current_.compare_exchange_weak(
+                        expected, Packed::pack(new_node),
+                        std::memory_order_acq_rel,
+                        std::memory_order_relaxed)) {
+                    pointer_type prev = node->value;
+                    // The packed counter includes our own reference.
+                    retire(node, static_cast<int64_t>(Packed::count(expected)) - 1);
+                    return prev;
+                }
+            }
+
+            // Another writer has replaced the node, try again with the new one.
+            release(node);
+        }
+    }
+
+private:
+    /// @brief Gets a reference of the current node.
+    Node *acquire() const noexcept
+    {
+        auto packed = current_.fetch_add(Packed::CountOne, std::memory_order_acquire);
+        assert(Packed::count(packed) + 1 < (uint64_t(1) << (64 - Packed::CountShift)));
+        return Packed::ptr<Node>(packed);
+    }
+
+    /// @brief Gives a reference of 'node' back.
+    void release(Node *node) const noexcept
+    {
+        auto packed = current_.load(std::memory_order_relaxed);
+        while (Packed::ptr<Node>(packed) == node) {
+            if (current_.compare_exchange_weak(
+                    packed, packed - Packed::CountOne,
+                    std::memory_order_release,
+                    std::memory_order_relaxed)) {
+                return;
+            }
+        }
+
+        // The node has been swapped out, and the writer has moved our reference to the node.
+        if (node->internal_count.fetch_sub(1, std::memory_order_acq_rel) == 1) {
+            delete node;
+        }
+    }
+
+    /// @brief Moves the references of the swapped out 'node' to its internal counter, and frees
+    /// it if all references have been given back.
+    static void retire(Node *node, int64_t count) noexcept
+    {
+        if (node->internal_count.fetch_add(count, std::memory_order_acq_rel) + count == 0) {
+            delete node;
+        }
+    }
+
+    mutable std::atomic<uint64_t> current_;
+};
+
+}
+
+#endif //SYNC_CELL_ARC_CELL_HPP
@@ -0,0 +1,86 @@
+///
+/// @file  atomic_enum.hpp
+/// @brief An atomic cell of an enum type, stored as its underlying integer.
+///
+
+#ifndef SYNC_CELL_ATOMIC_ENUM_HPP
+#define SYNC_CELL_ATOMIC_ENUM_HPP
+
+#include <atomic>
+#include <type_traits>
+
+
+namespace sc {
+
+/// @brief An atomic enum, e.g. for the state of a state machine shared by the threads.
+///
+/// The value is stored as the underlying integer of the enum in a @c std::atomic, so it is
+/// lock-free on the common platforms, and the conversions between the enum and the integer are
+/// done by the cell instead of the callers. Unlike a derive macro generating a type per enum, the
+/// template works for any enum.
+///
+/// @example
+/// ``` cpp
+/// enum class State : uint8_t { Idle, Running, Stopped };
+/// constinit sc::AtomicEnum<State> state(State::Idle);
+/// auto expected = State::Idle;
+/// if (state.compare_exchange(expected, State::Running)) { run(); }
+/// ```
+/// @tparam E The enum type.
+template<typename E>
+class AtomicEnum
+{
+    static_assert(std::is_enum_v<E>, "The value type must be an enum.");
+
+    using underlying = std::underlying_type_t<E>;
+
+public:
+    using value_type = E;
+
+    constexpr explicit AtomicEnum(E value) noexcept : value_(static_cast<underlying>(value)) { }
+
+    AtomicEnum(const AtomicEnum &) = delete;
+
+    AtomicEnum &operator=(const AtomicEnum &) = delete;
+
+    [[nodiscard]] bool is_lock_free() const noexcept
+    {
+        return value_.is_lock_free();
+    }
+
+    [[nodiscard]] E load(std::memory_order order = std::memory_order_seq_cst) const noexcept
+    {
+        return static_cast<E>(value_.load(order));
+    }
+
+    void store(E value, std::memory_order order = std::memory_order_seq_cst) noexcept
+    {
+        value_.store(static_cast<underlying>(value), order);
+    }
+
+    /// @brief Stores the 'value', and returns the previous one.
+    E swap(E value, std::memory_order order = std::memory_order_seq_cst) noexcept
+    {
+        return static_cast<E>(value_.exchange(static_cast<underlying>(value), order));
+    }
+
+    /// @brief Stores the 'desired' if the current value is equal to 'expected'. Otherwise, the
+    /// current value is loaded into the 'expected'.
+    /// @return true if the current value is replaced by the 'desired'.
+    bool compare_exchange(E &expected, E desired,
+                          std::memory_order success = std::memory_order_seq_cst,
+                          std::memory_order failure = std::memory_order_seq_cst) noexcept
+    {
+        auto current = static_cast<underlying>(expected);
+        auto replaced = value_.compare_exchange_strong(current, static_cast<underlying>(desired), success, failure);
+        expected = static_cast<E>(current);
+        return replaced;
+    }
+
+private:
+    std::atomic<underlying> value_;
+};
+
+}
+
+#endif //SYNC_CELL_ATOMIC_ENUM_HPP
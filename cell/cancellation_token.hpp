@@ -0,0 +1,269 @@
+///
+/// @file  cancellation_token.hpp
+/// @brief A token which stops the work cooperatively, checked or waited by the threads and the
+/// C++20 coroutines, with the child tokens cancelled together with their parent. Inspired by the
+/// [CancellationToken](https://docs.rs/tokio-util/latest/tokio_util/sync/struct.CancellationToken.html)
+/// of tokio-util.
+///
+
+#ifndef SYNC_CELL_CANCELLATION_TOKEN_HPP
+#define SYNC_CELL_CANCELLATION_TOKEN_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The cancellation_token.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <algorithm>
+#include <atomic>
+#include <chrono>
+#include <condition_variable>
+#include <memory>
+#include <mutex>
+#include <utility>
+#include <vector>
+
+#if __cpp_impl_coroutine
+#include <coroutine>
+#endif
+
+
+namespace sc {
+
+namespace impl {
+
+/// @brief The state shared by the copies of a token. A parent holds its children weakly, so a
+/// dropped child token is released before the parent, and a child holds its parent, so a
+/// grandchild is still reached from the root after the child tokens between them are dropped.
+struct CancelState
+{
+#if __cpp_impl_coroutine
+    struct Waiter
+    {
+        std::coroutine_handle<> handle;
+        Waiter *next = nullptr;
+    };
+#endif
+
+    std::atomic<bool> cancelled{false};
+    std::shared_ptr<CancelState> parent;
+    std::mutex mtx;
+    std::condition_variable cond_var;
+    /// @brief Guarded by the 'mtx', and taken by the cancellation.
+    std::vector<std::weak_ptr<CancelState>> children;
+#if __cpp_impl_coroutine
+    /// @brief The suspended coroutines, guarded by the 'mtx'.
+    Waiter *head = nullptr;
+#endif
+};
+
+}
+
+/// @brief A cancellation flag shared by the copies of the token, which any of them can set once.
+///
+/// The work running on the pool or consuming the queues checks @c is_cancelled between its steps,
+/// or waits for the cancellation by @c wait_cancelled or @c co_await @c cancelled. A
+/// @c child_token is cancelled with its parent, or alone without affecting the parent, so a
+/// request and its sub-tasks are stopped together.
+///
+/// @example
+/// ``` cpp
+/// sc::CancellationToken shutdown;
+/// for (size_t i = 0; i < 4; ++i) {
+///     pool.spawn([token = shutdown.child_token(), &jobs] {
+///         while (!token.is_cancelled()) {
+///             if (auto job = jobs.try_dequeue()) { run(*job); }
+///         }
+///     });
+/// }
+/// shutdown.cancel();
+/// ```
+/// @note The cancellation only sets the flag and wakes up the waiters, the work must stop itself.
+/// A suspended coroutine is resumed on the cancelling thread inside the @c cancel call. If the
+/// coroutine must run on a specific executor, reschedule it after the @c co_await returns.
+class CancellationToken
+{
+public:
+#if __cpp_impl_coroutine
+    class CancelledAwaiter;
+#endif
+
+    /// @brief Creates a root token, not cancelled.
+    CancellationToken() : state_(std::make_shared<impl::CancelState>()) { }
+
+    /// @brief Cancels this token and all its descendants, and wakes up their waiters. Does nothing
+    /// if it is cancelled already.
+    void cancel() const
+    {
+        std::vector<std::shared_ptr<impl::CancelState>> pending{state_};
+        while (!pending.empty()) {
+            auto state = std::move(pending.back());
+            pending.pop_back();
+            for (auto &child: cancel_one(*state)) {
+                if (auto c = child.lock()) {
+                    pending.push_back(std::move(c));
+                }
+            }
+        }
+    }
+
+    /// @brief Returns true if the token, or one of its ancestors, has been cancelled.
+    [[nodiscard]] bool is_cancelled() const noexcept
+    {
+        return state_->cancelled.load(std::memory_order_acquire);
+    }
+
+    /// @brief Returns a new token, which is cancelled when this one is cancelled. Cancelling the
+    /// child does not cancel this one. The child of a cancelled token is cancelled already.
+    [[nodiscard]] CancellationToken child_token() const
+    {
+        CancellationToken child;
+        child.state_->parent = state_;
+        std::lock_guard guard(state_->mtx);
+        if (is_cancelled()) {
+            child.state_->cancelled.store(true, std::memory_order_release);
+            return child;
+        }
+        auto &children = state_->children;
+        // Drop the dead children, so a long-lived parent does not grow with the short-lived ones.
+        children.erase(std::remove_if(children.begin(), children.end(),
+                                      [](const auto &c) { return c.expired(); }),
+                       children.end());
+        children.push_back(child.state_);
+        return child;
+    }
+
+    /// @brief Blocks the current thread until the token is cancelled.
+    void wait_cancelled() const
+    {
+        if (is_cancelled()) {
+            return;
+        }
+        std::unique_lock lock(state_->mtx);
+        state_->cond_var.wait(lock, [this] { return is_cancelled(); });
+    }
+
+    /// @brief Blocks the current thread until the token is cancelled or the 'timeout' duration has
+    /// elapsed.
+    /// @return false if timeout.
+    template<typename Rep, typename Period>
+    bool wait_cancelled_for(const std::chrono::duration<Rep, Period> &timeout) const
+    {
+        return wait_cancelled_until(std::chrono::steady_clock::now() + timeout);
+    }
+
+    /// @brief Blocks the current thread until the token is cancelled or the 'deadline' has been
+    /// reached.
+    /// @return false if timeout.
+    template<typename Clock, typename Duration>
+    bool wait_cancelled_until(const std::chrono::time_point<Clock, Duration> &deadline) const
+    {
+        if (is_cancelled()) {
+            return true;
+        }
+        std::unique_lock lock(state_->mtx);
+        return state_->cond_var.wait_until(lock, deadline, [this] { return is_cancelled(); });
+    }
+
+#if __cpp_impl_coroutine
+    /// @brief Waits asynchronously until the token is cancelled.
+    /// @example
+    /// ``` cpp
+    /// co_await token.cancelled();
+    /// ```
+    [[nodiscard]] CancelledAwaiter cancelled() const noexcept;
+#endif
+
+    /// @brief Returns true if both tokens share the same flag, i.e. one is a copy of the other.
+    [[nodiscard]] bool operator==(const CancellationToken &other) const noexcept
+    {
+        return state_ == other.state_;
+    }
+
+private:
+    /// @brief Sets the flag of one state and wakes up its waiters.
+    /// @return The children to cancel, empty if it has been cancelled already.
+    static std::vector<std::weak_ptr<impl::CancelState>> cancel_one(impl::CancelState &state)
+    {
+        std::vector<std::weak_ptr<impl::CancelState>> children;
+#if __cpp_impl_coroutine
+        impl::CancelState::Waiter *waiters;
+#endif
+        {
+            // Set under the lock, so a waiter either sees the flag or is registered before.
+            std::lock_guard guard(state.mtx);
+            if (state.cancelled.exchange(true, std::memory_order_acq_rel)) {
+                return children;
+            }
+            children = std::move(state.children);
+#if __cpp_impl_coroutine
+            waiters = std::exchange(state.head, nullptr);
+#endif
+        }
+        state.cond_var.notify_all();
+#if __cpp_impl_coroutine
+        while (waiters != nullptr) {
+            // The coroutine may destroy the waiter after resumed.
+            auto handle = waiters->handle;
+            waiters = waiters->next;
+            handle.resume();
+        }
+#endif
+        return children;
+    }
+
+    std::shared_ptr<impl::CancelState> state_;
+};
+
+#if __cpp_impl_coroutine
+
+/// @brief The awaitable object returned by @c CancellationToken::cancelled, which keeps the state
+/// of the token alive while the coroutine is suspended.
+class CancellationToken::CancelledAwaiter
+{
+    friend class CancellationToken;
+
+    explicit CancelledAwaiter(std::shared_ptr<impl::CancelState> state) noexcept : state_(std::move(state)) { }
+
+public:
+    CancelledAwaiter(const CancelledAwaiter &) = delete;
+
+    CancelledAwaiter &operator=(const CancelledAwaiter &) = delete;
+
+    bool await_ready() const noexcept
+    {
+        return state_->cancelled.load(std::memory_order_acquire);
+    }
+
+    bool await_suspend(std::coroutine_handle<> handle) noexcept
+    {
+        waiter_.handle = handle;
+
+        std::lock_guard guard(state_->mtx);
+        // Check again under the lock to avoid missing the concurrent cancellation.
+        if (state_->cancelled.load(std::memory_order_acquire)) {
+            return false;
+        }
+        waiter_.next = state_->head;
+        state_->head = &waiter_;
+        return true;
+    }
+
+    void await_resume() const noexcept { }
+
+private:
+    std::shared_ptr<impl::CancelState> state_;
+    impl::CancelState::Waiter waiter_;
+};
+
+inline CancellationToken::CancelledAwaiter CancellationToken::cancelled() const noexcept
+{
+    return CancelledAwaiter(state_);
+}
+
+#endif
+
+}
+
+#endif //SYNC_CELL_CANCELLATION_TOKEN_HPP
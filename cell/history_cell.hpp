@@ -0,0 +1,166 @@
+///
+/// @file  history_cell.hpp
+/// @brief A thread-safe cell which keeps its last written values, with the time and the thread of
+/// each write.
+///
+
+#ifndef SYNC_CELL_HISTORY_CELL_HPP
+#define SYNC_CELL_HISTORY_CELL_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The history_cell.hpp requires the std clocks and threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <array>
+#include <chrono>
+#include <cstddef>
+#include <cstdint>
+#include <optional>
+#include <thread>
+#include <utility>
+#include <vector>
+
+#include "lock/spin_lock.hpp"
+#include "util/back_off.hpp"
+
+
+namespace sc {
+
+/// @brief A write recorded by a @c HistoryCell.
+template<typename T, typename Clock>
+struct HistoryEntry
+{
+    T value;
+    typename Clock::time_point at;
+    /// @brief The thread which has written the value.
+    std::thread::id thread;
+    /// @brief The count of the writes to the cell up to this one, starting at 1, so a gap between
+    /// two entries of different snapshots tells how many writes have been dropped from the history.
+    uint64_t sequence;
+};
+
+/// @brief A cell which records its last 'N' writes, to find out who has changed a value and when,
+/// in production without attaching a debugger:
+/// ``` cpp
+/// sc::HistoryCell<Mode, 16> mode(Mode::Idle);
+/// // On an unexpected mode.
+/// for (const auto &entry: mode.history()) {
+///     log("mode {} set at {} by thread {}", entry.value, entry.at, entry.thread);
+/// }
+/// ```
+///
+/// The value and the history are guarded by one spin lock, so each store copies the value into the
+/// ring, and reading the history copies the ring. It suits the values written rarely, e.g. the
+/// state or the config of a component, rather than the hot counters.
+/// @tparam T The value type. It must be copyable.
+/// @tparam N The count of the writes kept.
+/// @tparam Clock The clock of the timestamps, the wall clock by default to match the logs.
+/// @tparam Backoff The backoff of the spin lock.
+template<typename T, size_t N, typename Clock = std::chrono::system_clock, typename Backoff = util::Backoff>
+class HistoryCell
+{
+    static_assert(N > 0, "The history must keep at least one write.");
+
+public:
+    using value_type = T;
+    using entry_type = HistoryEntry<T, Clock>;
+
+    /// @brief A copy of the history taken at once, from the oldest write to the latest one.
+    class Snapshot
+    {
+        friend class HistoryCell;
+
+        explicit Snapshot(std::vector<entry_type> &&entries) noexcept : entries_(std::move(entries)) { }
+
+    public:
+        using const_iterator = typename std::vector<entry_type>::const_iterator;
+
+        [[nodiscard]] const_iterator begin() const noexcept
+        {
+            return entries_.begin();
+        }
+
+        [[nodiscard]] const_iterator end() const noexcept
+        {
+            return entries_.end();
+        }
+
+        [[nodiscard]] size_t size() const noexcept
+        {
+            return entries_.size();
+        }
+
+        [[nodiscard]] bool empty() const noexcept
+        {
+            return entries_.empty();
+        }
+
+        /// @brief Returns the i-th entry, the oldest one at 0.
+        [[nodiscard]] const entry_type &operator[](size_t i) const noexcept
+        {
+            return entries_[i];
+        }
+
+    private:
+        std::vector<entry_type> entries_;
+    };
+
+    /// @brief Creates the cell with the initial 'value', which is not recorded as a write.
+    explicit HistoryCell(T value) : state_(std::move(value)) { }
+
+    HistoryCell(const HistoryCell &) = delete;
+
+    HistoryCell &operator=(const HistoryCell &) = delete;
+
+    [[nodiscard]] T load() const
+    {
+        return state_.lock()->value;
+    }
+
+    /// @brief Stores the 'value', and records it in the history with the current time and thread.
+    void store(T value)
+    {
+        auto guard = state_.lock();
+        auto sequence = ++guard->writes;
+        guard->ring[sequence % N].emplace(entry_type{value, Clock::now(), std::this_thread::get_id(), sequence});
+        guard->value = std::move(value);
+    }
+
+    /// @brief Returns the count of the writes since the cell is created, including the ones dropped
+    /// from the history.
+    [[nodiscard]] uint64_t write_count() const
+    {
+        return state_.lock()->writes;
+    }
+
+    /// @brief Copies the last at most 'N' writes.
+    [[nodiscard]] Snapshot history() const
+    {
+        std::vector<entry_type> entries;
+        entries.reserve(N);
+        auto guard = state_.lock();
+        auto writes = guard->writes;
+        for (auto sequence = writes > N ? writes - N + 1 : 1; sequence <= writes; ++sequence) {
+            entries.push_back(*guard->ring[sequence % N]);
+        }
+        return Snapshot(std::move(entries));
+    }
+
+private:
+    struct State
+    {
+        explicit State(T &&value) : value(std::move(value)) { }
+
+        T value;
+        uint64_t writes = 0;
+        std::array<std::optional<entry_type>, N> ring;
+    };
+
+    mutable lock::SpinLock<State, Backoff> state_;
+};
+
+}
+
+#endif //SYNC_CELL_HISTORY_CELL_HPP
@@ -0,0 +1,85 @@
+///
+/// @file  lazy_sync_cell.hpp
+/// @brief A thread-safe value which is initialized on the first access.
+///
+
+#ifndef SYNC_CELL_LAZY_SYNC_CELL_HPP
+#define SYNC_CELL_LAZY_SYNC_CELL_HPP
+
+#include <memory>
+#include <type_traits>
+#include <utility>
+
+#include "cell/once_sync_cell.hpp"
+
+
+namespace sc {
+
+/// @brief A thread-safe value which is initialized on the first access.
+///
+/// The initialization function runs on the first dereference. The concurrent readers spin with
+/// backoff (and then park) until the initialization is completed. See @c OnceSyncCell.
+///
+/// @example
+/// ``` cpp
+/// static sc::LazySyncCell<std::vector<int>> Primes([] { return compute_primes(); });
+///
+/// for (auto p : *Primes) { ... }
+/// ```
+/// @tparam T The value type.
+/// @tparam F The type of the initialization function with the signature of 'T()'.
+template<typename T, typename F = T (*)()>
+class LazySyncCell
+{
+    static_assert(std::is_convertible_v<std::invoke_result_t<F &>, T>);
+
+public:
+    using value_type = T;
+
+    /// @brief Creates a new lazy value with the given initializing function.
+    constexpr explicit LazySyncCell(F f) noexcept(std::is_nothrow_move_constructible_v<F>)
+            : init_(std::move(f)) { }
+
+    LazySyncCell(const LazySyncCell &) = delete;
+
+    LazySyncCell &operator=(const LazySyncCell &) = delete;
+
+    /// @brief Returns true if the value has been initialized.
+    [[nodiscard]] bool is_initialized() const noexcept
+    {
+        return cell_.is_initialized();
+    }
+
+    /// @brief Forces the evaluation of this lazy value and returns a reference to the result.
+    const value_type &get() const
+    {
+        return cell_.get_or_init(init_);
+    }
+
+    const value_type &operator*() const
+    {
+        return get();
+    }
+
+    const value_type *operator->() const
+    {
+        return std::addressof(get());
+    }
+
+    /// @brief Forces the evaluation of this lazy value and moves the result out. The caller must
+    /// guarantee no other thread is accessing the value, and the value is not used after.
+    value_type into_inner() &&
+    {
+        get();
+        return *std::move(cell_).into_inner();
+    }
+
+private:
+    mutable OnceSyncCell<value_type> cell_;
+    /// @brief Only called by the thread which runs the initialization.
+    mutable F init_;
+};
+
+}
+
+#endif //SYNC_CELL_LAZY_SYNC_CELL_HPP
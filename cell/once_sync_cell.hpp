@@ -0,0 +1,246 @@
+///
+/// @file  once_sync_cell.hpp
+/// @brief A thread-safe cell which can be written to only once.
+///
+
+#ifndef SYNC_CELL_ONCE_SYNC_CELL_HPP
+#define SYNC_CELL_ONCE_SYNC_CELL_HPP
+
+#include <atomic>
+#include <cstdint>
+#include <memory>
+#include <optional>
+#include <type_traits>
+
+#include "shared/config.hpp"
+#include "shared/serde.hpp"
+#include "shared/tracing.hpp"
+#include "util/back_off.hpp"
+
+
+namespace sc {
+
+/// @brief A thread-safe cell which can be written to only once.
+///
+/// The value is initialized by @c set, @c get_or_init or @c get_or_try_init. Even if many threads
+/// call these methods concurrently, only one of them initializes the value, while the others wait
+/// until the initialization is completed (spinning at first, and then parking the thread).
+///
+/// If the initialization function throws an exception (or the @c get_or_try_init function returns
+/// an empty optional), the cell stays uninitialized and another waiting thread may try to
+/// initialize the cell.
+/// @tparam T The value type.
+template<typename T>
+class OnceSyncCell
+{
+    static_assert(!std::is_reference_v<T>);
+
+    enum State : uint8_t
+    {
+        Incomplete = 0,
+        Running = 1,
+        Complete = 2,
+    };
+
+    /// @brief Resets the state to 'Incomplete' if the initialization function throws.
+    struct InitGuard
+    {
+        OnceSyncCell *cell;
+
+        ~InitGuard()
+        {
+            if (cell != nullptr) {
+                cell->finish_init(Incomplete);
+            }
+        }
+    };
+
+public:
+    using value_type = T;
+
+    /// @brief Creates a new empty cell.
+    constexpr OnceSyncCell() noexcept = default;
+
+    OnceSyncCell(const OnceSyncCell &) = delete;
+
+    OnceSyncCell &operator=(const OnceSyncCell &) = delete;
+
+    /// @brief Returns true if the value has been initialized.
+    [[nodiscard]] bool is_initialized() const noexcept
+    {
+        return state_.load(std::memory_order_acquire) == Complete;
+    }
+
+    /// @brief Gets the pointer to the value.
+    /// @return nullptr if the cell is empty or being initialized.
+    [[nodiscard]] const value_type *get() const noexcept
+    {
+        return is_initialized() ? std::addressof(*value_) : nullptr;
+    }
+
+    /// @brief Sets the value of the cell.
+    /// @return false if the cell has been initialized. In this case, the 'value' is not touched.
+    template<typename = std::enable_if_t<std::is_copy_constructible_v<value_type>>>
+    bool set(const value_type &value)
+    {
+        bool initialized = false;
+        try_init([&value] { return std::optional<value_type>(value); }, &initialized);
+        return initialized;
+    }
+
+    /// @brief Sets the value of the cell.
+    /// @return false if the cell has been initialized. In this case, the 'value' is not moved.
+    template<typename = std::enable_if_t<std::is_move_constructible_v<value_type>>>
+    bool set(value_type &&value)
+    {
+        bool initialized = false;
+        try_init([&value] { return std::optional<value_type>(std::move(value)); }, &initialized);
+        return initialized;
+    }
+
+    /// @brief Gets the value of the cell, initializing it with 'f' if the cell is empty.
+    ///
+    /// Only one thread calls the 'f' even if many threads call this method concurrently. If 'f'
+    /// throws an exception, the exception is propagated to the caller and the cell stays empty.
+    /// @param f The function with the signature of 'T()'.
+    template<typename F>
+    const value_type &get_or_init(F &&f)
+    {
+        return *try_init([&f] { return std::optional<value_type>(f()); });
+    }
+
+    /// @brief Gets the value of the cell, initializing it with 'f' if the cell is empty.
+    ///
+    /// If 'f' returns an empty optional, the cell stays empty and another thread can try to
+    /// initialize it.
+    /// @param f The function with the signature of 'std::optional<T>()'.
+    /// @return The pointer to the value, or nullptr if 'f' failed.
+    template<typename F>
+    const value_type *get_or_try_init(F &&f)
+    {
+        return try_init(f);
+    }
+
+    /// @brief Gets the mutable pointer to the value without synchronization. The caller must
+    /// guarantee no other thread is accessing the cell.
+    /// @return nullptr if the cell is empty.
+    [[nodiscard]] value_type *get_unsafe() noexcept
+    {
+        return value_ ? std::addressof(*value_) : nullptr;
+    }
+
+    /// @brief Moves the value out of the cell without synchronization. The caller must guarantee no
+    /// other thread is accessing the cell, and the cell is not used after.
+    /// @return An empty optional if the cell is empty.
+    std::optional<value_type> into_inner() && noexcept(std::is_nothrow_move_constructible_v<value_type>)
+    {
+        return std::move(value_);
+    }
+
+private:
+    /// @brief Waits for the other initializing thread if needed, and runs 'f' if the cell
+    /// is still empty.
+    /// @param initialized Set to true if the value is initialized by this call.
+    template<typename F>
+    const value_type *try_init(F &&f, bool *initialized = nullptr)
+    {
+        if (!begin_init()) {
+            return std::addressof(*value_);
+        }
+
+        tracing::Span span(tracing::SpanKind::OnceInit, "sc::OnceSyncCell", this);
+        span.enter();
+        InitGuard guard{this};
+        std::optional<value_type> value = f();
+        if (!value) {
+            return nullptr;
+        }
+
+        value_.emplace(*std::move(value));
+        guard.cell = nullptr;
+        finish_init(Complete);
+        if (initialized != nullptr) {
+            *initialized = true;
+        }
+
+        return std::addressof(*value_);
+    }
+
+    /// @brief Returns true if the current thread should initialize the value. Returns false if the
+    /// value has been initialized.
+    bool begin_init() noexcept
+    {
+        util::Backoff backoff;
+        tracing::Span span(tracing::SpanKind::OnceWait, "sc::OnceSyncCell", this);
+        while (true) {
+            auto state = state_.load(std::memory_order_acquire);
+            if (state == Complete) {
+                return false;
+            }
+
+            if (state == Incomplete) {
+                if (state_.compare_exchange_weak(
+                        state, Running,
+                        std::memory_order_acquire,
+                        std::memory_order_acquire)) {
+                    return true;
+                }
+                continue;
+            }
+
+            // Another thread is running the initialization.
+            span.enter();
+#if SC_HAS_STD
+            if (backoff.is_completed()) {
+                state_.wait(Running, std::memory_order_acquire);
+                continue;
+            }
+#endif
+            backoff.snooze();
+        }
+    }
+
+    void finish_init(State state) noexcept
+    {
+        state_.store(state, std::memory_order_release);
+#if SC_HAS_STD
+        state_.notify_all();
+#endif
+    }
+
+    std::optional<value_type> value_;
+    std::atomic<uint8_t> state_{Incomplete};
+};
+
+}
+
+#if SC_HAS_SERDE
+
+namespace sc::serde {
+
+/// @brief Serializes the value of the cell, or an empty optional if it is not initialized. The value
+/// is not restored if the cell has been initialized.
+template<typename T>
+struct Codec<OnceSyncCell<T>>
+{
+    using value_type = std::optional<T>;
+
+    static value_type snapshot(const OnceSyncCell<T> &c)
+    {
+        auto *p = c.get();
+        return p != nullptr ? value_type(*p) : value_type();
+    }
+
+    static void restore(OnceSyncCell<T> &c, value_type &&value)
+    {
+        if (value) {
+            c.set(*std::move(value));
+        }
+    }
+};
+
+}
+
+#endif
+
+#endif //SYNC_CELL_ONCE_SYNC_CELL_HPP
@@ -0,0 +1,219 @@
+///
+/// @file  oneshot_cell.hpp
+/// @brief A single-producer single-consumer cell which passes one value, received by blocking or
+/// by a C++20 coroutine.
+///
+
+#ifndef SYNC_CELL_ONESHOT_CELL_HPP
+#define SYNC_CELL_ONESHOT_CELL_HPP
+
+#include <atomic>
+#include <cstdint>
+#include <optional>
+#include <type_traits>
+#include <utility>
+
+#include "shared/config.hpp"
+#include "shared/select_hook.hpp"
+#include "util/back_off.hpp"
+
+#if __cpp_impl_coroutine
+#include <coroutine>
+#endif
+
+
+namespace sc {
+
+/// @brief A one-shot rendezvous cell: the producer @c send one value, and the consumer receives it
+/// by @c try_recv, the blocking @c recv, or @c co_await @c recv_async.
+///
+/// The state is one atomic byte, and the value is stored in the cell, so no allocation is needed.
+/// A blocked consumer spins at first, and then parks the thread.
+///
+/// @example
+/// ``` cpp
+/// sc::OneshotCell<Response> response;
+/// std::thread worker([&response] { response.send(handle(request)); });
+/// auto r = response.recv();
+/// ```
+/// @note Only one thread sends and only one thread receives. A suspended coroutine is resumed on
+/// the producer thread inside the @c send call. If the coroutine must run on a specific executor,
+/// reschedule it after the @c co_await returns.
+/// @tparam T The value type.
+template<typename T>
+class OneshotCell
+{
+    static_assert(!std::is_reference_v<T>);
+
+    enum State : uint8_t
+    {
+        Empty = 0,
+        /// @brief A coroutine is suspended for the value.
+        Awaiting = 1,
+        Full = 2,
+        Taken = 3,
+    };
+
+public:
+    using value_type = T;
+
+#if __cpp_impl_coroutine
+    class RecvAwaiter;
+#endif
+
+    constexpr OneshotCell() noexcept = default;
+
+    OneshotCell(const OneshotCell &) = delete;
+
+    OneshotCell &operator=(const OneshotCell &) = delete;
+
+    /// @brief Returns true if the value is sent and not received yet.
+    [[nodiscard]] bool is_ready() const noexcept
+    {
+        return state_.load(std::memory_order_acquire) == Full;
+    }
+
+    /// @brief Returns true if the value has been sent, whether it is received or not.
+    [[nodiscard]] bool is_sent() const noexcept
+    {
+        return state_.load(std::memory_order_acquire) >= Full;
+    }
+
+    /// @brief Returns the hook notified when the value is sent, used by the @c sync::Select.
+    [[nodiscard]] impl::SelectHook &select_hook() noexcept
+    {
+        return select_hook_;
+    }
+
+    /// @brief Sends the value, and wakes up the waiting consumer.
+    /// @return false if a value has been sent, in this case, 'value' is dropped.
+    bool send(value_type value)
+    {
+        // Only the producer changes the state to the 'Full'.
+        if (state_.load(std::memory_order_relaxed) >= Full) {
+            return false;
+        }
+
+        value_.emplace(std::move(value));
+        if (state_.exchange(Full, std::memory_order_acq_rel) == Awaiting) {
+#if __cpp_impl_coroutine
+            handle_.resume();
+#endif
+            return true;
+        }
+#if SC_HAS_STD
+        state_.notify_one();
+#endif
+        // Pairs with the attach of a selector: either it sees the value, or we see its entry.
+        std::atomic_thread_fence(std::memory_order_seq_cst);
+        select_hook_.notify();
+        return true;
+    }
+
+    /// @brief Receives the value without blocking.
+    /// @return An empty optional if the value is not sent yet, or has been received.
+    std::optional<value_type> try_recv()
+    {
+        if (!is_ready()) {
+            return {};
+        }
+        return take();
+    }
+
+    /// @brief Receives the value, blocks the current thread until it is sent.
+    /// @note It must not be called after the value has been received, which never returns.
+    value_type recv()
+    {
+        util::Backoff backoff;
+        while (true) {
+            auto state = state_.load(std::memory_order_acquire);
+            if (state == Full) {
+                return *take();
+            }
+#if SC_HAS_STD
+            if (backoff.is_completed()) {
+                state_.wait(state, std::memory_order_acquire);
+                continue;
+            }
+#endif
+            backoff.snooze();
+        }
+    }
+
+#if __cpp_impl_coroutine
+    /// @brief Receives the value asynchronously.
+    /// @example
+    /// ``` cpp
+    /// auto value = co_await cell.recv_async();
+    /// ```
+    /// @note It must not be called after the value has been received, which never resumes.
+    [[nodiscard]] RecvAwaiter recv_async() noexcept
+    {
+        return RecvAwaiter(*this);
+    }
+#endif
+
+private:
+    /// @brief Moves out the sent value, the state must be 'Full'.
+    std::optional<value_type> take()
+    {
+        std::optional<value_type> value(std::move(value_));
+        value_.reset();
+        state_.store(Taken, std::memory_order_relaxed);
+        return value;
+    }
+
+    std::optional<value_type> value_;
+    std::atomic<uint8_t> state_{Empty};
+    impl::SelectHook select_hook_;
+#if __cpp_impl_coroutine
+    /// @brief The suspended consumer, published by the 'Awaiting' state.
+    std::coroutine_handle<> handle_;
+#endif
+};
+
+#if __cpp_impl_coroutine
+
+/// @brief The awaitable object returned by @c OneshotCell::recv_async.
+template<typename T>
+class OneshotCell<T>::RecvAwaiter
+{
+    friend class OneshotCell;
+
+    explicit RecvAwaiter(OneshotCell &cell) noexcept : cell_(cell) { }
+
+public:
+    RecvAwaiter(const RecvAwaiter &) = delete;
+
+    RecvAwaiter &operator=(const RecvAwaiter &) = delete;
+
+    bool await_ready() const noexcept
+    {
+        return cell_.is_ready();
+    }
+
+    bool await_suspend(std::coroutine_handle<> handle) noexcept
+    {
+        cell_.handle_ = handle;
+        uint8_t state = Empty;
+        // Fails if the value is sent meanwhile, then the coroutine goes on without suspending.
+        return cell_.state_.compare_exchange_strong(
+                state, Awaiting,
+                std::memory_order_acq_rel,
+                std::memory_order_acquire);
+    }
+
+    value_type await_resume()
+    {
+        return *cell_.take();
+    }
+
+private:
+    OneshotCell &cell_;
+};
+
+#endif
+
+}
+
+#endif //SYNC_CELL_ONESHOT_CELL_HPP
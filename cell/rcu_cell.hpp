@@ -0,0 +1,330 @@
+///
+/// @file  rcu_cell.hpp
+/// @brief A read-copy-update cell for the read-mostly data.
+///
+
+#ifndef SYNC_CELL_RCU_CELL_HPP
+#define SYNC_CELL_RCU_CELL_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The rcu_cell.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <atomic>
+#include <cstdint>
+#include <memory>
+#include <mutex>
+#include <type_traits>
+#include <utility>
+#include <vector>
+
+#include "util/cache_padded.hpp"
+
+
+namespace sc {
+
+namespace impl {
+
+template<typename T>
+struct IsAtomicLockFree : std::bool_constant<std::atomic<T>::is_always_lock_free> { };
+
+/// @brief Whether the @c RcuCell stores the value inline in an atomic word: the small trivially
+/// copyable values whose atomic operations are always lock-free, i.e. up to the size of a pointer,
+/// or of two pointers on the platforms with the double-width CAS.
+template<typename T>
+inline constexpr bool RcuInline = std::conjunction_v<
+        std::is_trivially_copyable<T>,
+        std::bool_constant<sizeof(T) <= 2 * sizeof(void *)>,
+        IsAtomicLockFree<T>>;
+
+}
+
+/// @brief A read-copy-update cell for the read-mostly data.
+///
+/// Readers call @c read to get a cheap guard pointing to the current snapshot of the value, which
+/// only costs an atomic increment/decrement of a reader counter. Writers call @c update to build
+/// a new version from the current one and publish it atomically. The old versions are retired and
+/// destroyed later, once no reader can still access them.
+///
+/// The reclamation follows an epoch scheme with two reader counters: a reader registers itself in
+/// the counter of the current epoch parity. Values replaced in the epoch @a e are reclaimed after
+/// all readers registered in the epoch @a e have left, which is detected when the epoch is advanced
+/// from @a e + 1 to @a e + 2. The epoch is only advanced if the counter of the previous epoch is
+/// drained, so writers never wait for readers.
+///
+/// Writers are serialized by a mutex, which matches the read-mostly usage.
+///
+/// The small values (see @c impl::RcuInline, e.g. an @c int or a pair of pointers) are not boxed:
+/// they are stored directly in an atomic word, and the guard holds a copy of the snapshot, so a
+/// read skips the reader counters, the allocation and the pointer chase.
+///
+/// @note A @c ReadGuard must not outlive the cell.
+/// @tparam T The value type.
+template<typename T, bool = impl::RcuInline<T>>
+class RcuCell
+{
+    static_assert(!std::is_reference_v<T>);
+
+public:
+    using value_type = T;
+
+    /// @brief A guard to access a snapshot of the value. The snapshot keeps alive until the guard
+    /// is destroyed, even if the cell has been updated in the meantime.
+    class ReadGuard
+    {
+        friend class RcuCell;
+
+        ReadGuard(const RcuCell *cell, const value_type *ptr, size_t slot) noexcept
+                : cell_(cell), ptr_(ptr), slot_(slot) { }
+
+    public:
+        ReadGuard(ReadGuard &&other) noexcept
+                : cell_(std::exchange(other.cell_, nullptr)), ptr_(other.ptr_), slot_(other.slot_) { }
+
+        ReadGuard(const ReadGuard &) = delete;
+
+        ReadGuard &operator=(const ReadGuard &) = delete;
+
+        ReadGuard &operator=(ReadGuard &&) = delete;
+
+        ~ReadGuard()
+        {
+            if (cell_ != nullptr) {
+                cell_->leave(slot_);
+            }
+        }
+
+        const value_type *get() const noexcept
+        {
+            return ptr_;
+        }
+
+        const value_type *operator->() const noexcept
+        {
+            return ptr_;
+        }
+
+        const value_type &operator*() const noexcept
+        {
+            return *ptr_;
+        }
+
+    private:
+        const RcuCell *cell_;
+        const value_type *ptr_;
+        size_t slot_;
+    };
+
+    template<typename... Args, std::enable_if_t<std::is_constructible_v<T, Args...>, bool> = false>
+    explicit RcuCell(Args &&... args) : current_(new value_type(std::forward<Args>(args)...))
+    {
+        for (auto &r : readers_) {
+            r->store(0, std::memory_order_relaxed);
+        }
+    }
+
+    RcuCell(const RcuCell &) = delete;
+
+    RcuCell &operator=(const RcuCell &) = delete;
+
+    ~RcuCell()
+    {
+        delete current_.load(std::memory_order_relaxed);
+        for (auto &list : retired_) {
+            for (auto *p : list) {
+                delete p;
+            }
+        }
+    }
+
+    /// @brief Gets a guard to the current snapshot.
+    [[nodiscard]] ReadGuard read() const noexcept
+    {
+        while (true) {
+            auto epoch = epoch_.load(std::memory_order_seq_cst);
+            auto slot = epoch & 1;
+            readers_[slot]->fetch_add(1, std::memory_order_seq_cst);
+
+            // Make sure that we are registered in the current epoch, otherwise the writer may have
+            // checked the counter before our increment.
+            if (epoch_.load(std::memory_order_seq_cst) == epoch) {
+                return ReadGuard(this, current_.load(std::memory_order_seq_cst), slot);
+            }
+
+            leave(slot);
+        }
+    }
+
+    /// @brief Publishes a new value built from the current one.
+    /// @param f The function with the signature of 'T(const T &)'.
+    template<typename F>
+    void update(F &&f)
+    {
+        std::lock_guard guard(writer_mtx_);
+        auto *old = current_.load(std::memory_order_relaxed);
+        publish_locked(old, new value_type(f(std::as_const(*old))));
+    }
+
+    /// @brief Publishes a new value.
+    void store(value_type value)
+    {
+        std::lock_guard guard(writer_mtx_);
+        auto *old = current_.load(std::memory_order_relaxed);
+        publish_locked(old, new value_type(std::move(value)));
+    }
+
+private:
+    void publish_locked(value_type *old, value_type *new_value)
+    {
+        current_.store(new_value, std::memory_order_seq_cst);
+        retired_[epoch_.load(std::memory_order_relaxed) & 1].push_back(old);
+        pending_.store(true, std::memory_order_relaxed);
+
+        reclaim_locked();
+    }
+
+    void leave(size_t slot) const noexcept
+    {
+        if (readers_[slot]->fetch_sub(1, std::memory_order_seq_cst) == 1 &&
+            pending_.load(std::memory_order_relaxed)) {
+            // The last reader of the epoch tries to advance the epoch and reclaim the old values.
+            // If the lock is held by a writer, the next writer or reader will do the work.
+            std::unique_lock lock(writer_mtx_, std::try_to_lock);
+            if (lock.owns_lock()) {
+                reclaim_locked();
+            }
+        }
+    }
+
+    /// @brief Advances the epoch as far as possible and reclaims the old values.
+    void reclaim_locked() const noexcept
+    {
+        // Each step advances the epoch from 'e' to 'e + 1', at most two steps are needed to
+        // reclaim all values retired before this call.
+        for (int step = 0; step < 2; ++step) {
+            auto epoch = epoch_.load(std::memory_order_relaxed);
+            auto prev_slot = (epoch + 1) & 1;
+
+            if (retired_[0].empty() && retired_[1].empty()) {
+                break;
+            }
+
+            // Readers registered in the epoch 'e - 1' are still active.
+            if (readers_[prev_slot]->load(std::memory_order_seq_cst) != 0) {
+                break;
+            }
+
+            // All readers registered before the epoch 'e' have left, so nobody can access the
+            // values replaced in the epoch 'e - 1'.
+            for (auto *p : retired_[prev_slot]) {
+                delete p;
+            }
+            retired_[prev_slot].clear();
+
+            epoch_.store(epoch + 1, std::memory_order_seq_cst);
+        }
+
+        pending_.store(!retired_[0].empty() || !retired_[1].empty(), std::memory_order_relaxed);
+    }
+
+    /// @brief The current value.
+    std::atomic<value_type *> current_;
+
+    /// @brief The current epoch. Only writers (with the lock held) change it.
+    mutable std::atomic<size_t> epoch_{0};
+    /// @brief Reader counters for the even epochs and the odd epochs.
+    mutable util::CachePadded<std::atomic<size_t>> readers_[2];
+
+    /// @brief Serializes writers and guards the retired lists.
+    mutable std::mutex writer_mtx_;
+    /// @brief Values replaced in the even epochs and the odd epochs.
+    mutable std::vector<value_type *> retired_[2];
+    /// @brief Whether the retired lists are not empty. Used to skip the lock of the last reader.
+    mutable std::atomic<bool> pending_{false};
+};
+
+/// @brief The @c RcuCell of a small value, which is stored inline in an atomic word.
+template<typename T>
+class RcuCell<T, true>
+{
+public:
+    using value_type = T;
+
+    /// @brief A guard holding a copy of the snapshot.
+    class ReadGuard
+    {
+        friend class RcuCell;
+
+        explicit ReadGuard(value_type value) noexcept : value_(value) { }
+
+    public:
+        ReadGuard(ReadGuard &&other) noexcept = default;
+
+        ReadGuard(const ReadGuard &) = delete;
+
+        ReadGuard &operator=(const ReadGuard &) = delete;
+
+        ReadGuard &operator=(ReadGuard &&) = delete;
+
+        const value_type *get() const noexcept
+        {
+            return std::addressof(value_);
+        }
+
+        const value_type *operator->() const noexcept
+        {
+            return std::addressof(value_);
+        }
+
+        const value_type &operator*() const noexcept
+        {
+            return value_;
+        }
+
+    private:
+        value_type value_;
+    };
+
+    template<typename... Args, std::enable_if_t<std::is_constructible_v<T, Args...>, bool> = false>
+    explicit RcuCell(Args &&... args) : current_(value_type(std::forward<Args>(args)...)) { }
+
+    RcuCell(const RcuCell &) = delete;
+
+    RcuCell &operator=(const RcuCell &) = delete;
+
+    /// @brief Gets a guard to the current snapshot.
+    [[nodiscard]] ReadGuard read() const noexcept
+    {
+        return ReadGuard(current_.load(std::memory_order_seq_cst));
+    }
+
+    /// @brief Publishes a new value built from the current one.
+    /// @param f The function with the signature of 'T(const T &)'.
+    template<typename F>
+    void update(F &&f)
+    {
+        std::lock_guard guard(writer_mtx_);
+        auto old = current_.load(std::memory_order_relaxed);
+        current_.store(f(std::as_const(old)), std::memory_order_seq_cst);
+    }
+
+    /// @brief Publishes a new value.
+    void store(value_type value)
+    {
+        std::lock_guard guard(writer_mtx_);
+        current_.store(value, std::memory_order_seq_cst);
+    }
+
+private:
+    /// @brief The current value.
+    std::atomic<value_type> current_;
+
+    /// @brief Serializes writers, so an @c update is not lost by a concurrent writer.
+    std::mutex writer_mtx_;
+};
+
+}
+
+#endif //SYNC_CELL_RCU_CELL_HPP
@@ -0,0 +1,246 @@
+///
+/// @file  seq_lock_cell.hpp
+/// @brief A cell for the small trivially copyable types guarded by a sequence lock.
+///
+
+#ifndef SYNC_CELL_SEQ_LOCK_CELL_HPP
+#define SYNC_CELL_SEQ_LOCK_CELL_HPP
+
+#include <array>
+#include <atomic>
+#include <bit>
+#include <cstddef>
+#include <cstdint>
+#include <cstring>
+#include <memory>
+#include <type_traits>
+
+#include "shared/compiler_workaround.hpp"
+#include "shared/metrics.hpp"
+#include "util/back_off.hpp"
+
+
+namespace sc {
+
+/// @brief A cell for the small trivially copyable types guarded by a sequence lock.
+///
+/// A writer increases the sequence number to an odd value before writing the value, and increases
+/// it to an even value again after writing. A reader copies the value without any lock, and
+/// retries the copy if the sequence number is odd or changed during the copy (the copy may be
+/// torn by a concurrent writer). So reading never blocks the writers, and readers never write any
+/// shared memory, which is much faster than a lock or a CAS loop on the read-mostly data.
+///
+/// The value is stored as an array of atomic words accessed with the relaxed ordering, so that
+/// the concurrent read and write of a torn value is not a data race.
+/// @tparam T The value type. It must be trivially copyable.
+/// @tparam Backoff The backoff of the readers and writers waiting for a writer.
+template<typename T, typename Backoff = util::Backoff>
+class SeqLockCell
+{
+    static_assert(std::is_trivially_copyable_v<T>, "The value type must be trivially copyable.");
+
+    using Word = std::uintptr_t;
+    static constexpr size_t WordCount = (sizeof(T) + sizeof(Word) - 1) / sizeof(Word);
+
+    using Bytes = std::array<std::byte, sizeof(T)>;
+
+public:
+    using value_type = T;
+
+    /// @brief A copy of the words read by @c read_optimistic, which is only a valid value after
+    /// the @c validate of the cell returns true for it.
+    class OptimisticRead
+    {
+        friend class SeqLockCell;
+
+        OptimisticRead(const Bytes &bytes, size_t seq) noexcept : bytes_(bytes), seq_(seq) { }
+
+    public:
+        /// @brief Returns the value read. It may be torn by a concurrent writer, so it must not be
+        /// used before validated.
+        [[nodiscard]] value_type value() const noexcept
+        {
+            return std::bit_cast<value_type>(bytes_);
+        }
+
+    private:
+        Bytes bytes_;
+        size_t seq_;
+    };
+
+    template<typename U = T, std::enable_if_t<std::is_default_constructible_v<U>, bool> = false>
+    SeqLockCell() noexcept : SeqLockCell(T{}) { }
+
+    explicit SeqLockCell(const value_type &value) noexcept
+    {
+        store_words(value);
+    }
+
+    SeqLockCell(const SeqLockCell &) = delete;
+
+    SeqLockCell &operator=(const SeqLockCell &) = delete;
+
+    /// @brief Returns the contention counters: the retried reads and the waits for a writer count
+    /// as the spin iterations, and the lost races between the writers as the CAS failures. Requires
+    /// @c SYNC_CELL_METRICS.
+    [[nodiscard]] metrics::Stats stats() const noexcept
+    {
+        return metrics_.snapshot();
+    }
+
+    /// @brief Reads the value. Retries if a writer is writing the value at the same time.
+    [[nodiscard]] value_type read() const noexcept
+    {
+        Backoff backoff;
+        while (true) {
+            auto seq = seq_.load(std::memory_order_acquire);
+            if ((seq & 1) == 0) {
+                Word words[WordCount];
+                for (size_t i = 0; i < WordCount; ++i) {
+                    words[i] = words_[i].load(std::memory_order_relaxed);
+                }
+
+                // Pairs with the fence in the writer: if any word written by a writer is read,
+                // the following load must see the odd sequence number (or a newer one).
+                std::atomic_thread_fence(std::memory_order_acquire);
+                if (seq_.load(std::memory_order_relaxed) == seq) {
+                    Bytes bytes;
+                    std::memcpy(bytes.data(), words, sizeof(T));
+                    return std::bit_cast<value_type>(bytes);
+                }
+
+                metrics_.spin();
+                backoff.spin();
+            } else {
+                // A writer is writing the value.
+                metrics_.spin();
+                backoff.snooze();
+            }
+        }
+    }
+
+    /// @brief Reads the words once without the retry, for the optimistic read paths which do the
+    /// work first (typically computing on a few fields) and check the conflict once at the end.
+    /// @example
+    /// ``` cpp
+    /// auto r = cell.read_optimistic();
+    /// auto area = r.value().width * r.value().height;
+    /// if (!cell.validate(r)) { area = compute_area(cell.read()); }
+    /// ```
+    [[nodiscard]] OptimisticRead read_optimistic() const noexcept
+    {
+        auto seq = seq_.load(std::memory_order_acquire);
+        Word words[WordCount];
+        for (size_t i = 0; i < WordCount; ++i) {
+            words[i] = words_[i].load(std::memory_order_relaxed);
+        }
+        Bytes bytes;
+        std::memcpy(bytes.data(), words, sizeof(T));
+        return OptimisticRead(bytes, seq);
+    }
+
+    /// @brief Returns true if no writer has written the value since the 'read' began, i.e. the
+    /// value of the 'read' is not torn and was the current value.
+    [[nodiscard]] bool validate(const OptimisticRead &read) const noexcept
+    {
+        // The same as the check in the @c read.
+        std::atomic_thread_fence(std::memory_order_acquire);
+        return (read.seq_ & 1) == 0 && seq_.load(std::memory_order_relaxed) == read.seq_;
+    }
+
+    /// @brief Writes the value. Concurrent writers are serialized.
+    void write(const value_type &value) noexcept
+    {
+        auto seq = lock();
+        store_words(value);
+        unlock(seq);
+    }
+
+    /// @brief Replaces the value with the result of 'f'. Concurrent writers are serialized, and
+    /// readers see either the old value or the new value.
+    /// @param f The function with the signature of 'T(const T &)'.
+    template<typename F>
+    void update(F &&f)
+    {
+        // Unlocks even if 'f' throws, the value is unchanged in that case.
+        struct UnlockGuard
+        {
+            SeqLockCell *cell;
+            size_t seq;
+
+            ~UnlockGuard() { cell->unlock(seq); }
+        } guard{this, lock()};
+
+        // No other writer can change the words now.
+        store_words(f(load_words()));
+    }
+
+    /// @brief Reads the value without the sequence check. The caller must guarantee no other
+    /// thread is accessing the cell, and the cell is not used after.
+    value_type into_inner() && noexcept
+    {
+        return load_words();
+    }
+
+private:
+    /// @brief Waits until the sequence number is even and increases it to an odd value.
+    /// @return The odd sequence number.
+    size_t lock() noexcept
+    {
+        Backoff backoff;
+        auto seq = seq_.load(std::memory_order_relaxed);
+        while (true) {
+            if ((seq & 1) != 0) {
+                metrics_.spin();
+            } else if (seq_.compare_exchange_weak(
+                    seq, seq + 1,
+                    std::memory_order_acquire,
+                    std::memory_order_relaxed)) {
+                break;
+            } else {
+                metrics_.cas_failure();
+            }
+
+            backoff.snooze();
+            seq = seq_.load(std::memory_order_relaxed);
+        }
+
+        // The odd sequence number must be visible before any word is written.
+        std::atomic_thread_fence(std::memory_order_release);
+        return seq + 1;
+    }
+
+    void unlock(size_t seq) noexcept
+    {
+        seq_.store(seq + 1, std::memory_order_release);
+    }
+
+    /// @brief Loads the words without the sequence check, only valid when no writer is writing.
+    value_type load_words() const noexcept
+    {
+        Word words[WordCount];
+        for (size_t i = 0; i < WordCount; ++i) {
+            words[i] = words_[i].load(std::memory_order_relaxed);
+        }
+        Bytes bytes;
+        std::memcpy(bytes.data(), words, sizeof(T));
+        return std::bit_cast<value_type>(bytes);
+    }
+
+    void store_words(const value_type &value) noexcept
+    {
+        Word words[WordCount]{};
+        std::memcpy(words, std::addressof(value), sizeof(T));
+        for (size_t i = 0; i < WordCount; ++i) {
+            words_[i].store(words[i], std::memory_order_relaxed);
+        }
+    }
+
+    std::atomic<size_t> seq_{0};
+    std::atomic<Word> words_[WordCount];
+    SC_NO_UNIQUE_ADDRESS metrics::Counters metrics_;
+};
+
+}
+
+#endif //SYNC_CELL_SEQ_LOCK_CELL_HPP
@@ -0,0 +1,209 @@
+///
+/// @file  shared_cell.hpp
+/// @brief The traits reading and writing any thread-safe cell by the same operations, so the
+/// generic code accepts any of them.
+///
+
+#ifndef SYNC_CELL_SHARED_CELL_HPP
+#define SYNC_CELL_SHARED_CELL_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The shared_cell.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <concepts>
+#include <memory>
+#include <type_traits>
+#include <utility>
+
+#include "cell/arc_cell.hpp"
+#include "cell/rcu_cell.hpp"
+#include "cell/seq_lock_cell.hpp"
+#include "cell/sync_cell.hpp"
+
+
+namespace sc {
+
+/// @brief The snapshot of the cells which return the value by copy, with the same accessors as
+/// the @c RcuCell::ReadGuard, so the generic code reads all snapshots by @c * and @c ->.
+template<typename T>
+class ValueSnapshot
+{
+public:
+    explicit ValueSnapshot(T value) noexcept(std::is_nothrow_move_constructible_v<T>) : value_(std::move(value)) { }
+
+    const T *get() const noexcept
+    {
+        return std::addressof(value_);
+    }
+
+    const T *operator->() const noexcept
+    {
+        return std::addressof(value_);
+    }
+
+    const T &operator*() const noexcept
+    {
+        return value_;
+    }
+
+private:
+    T value_;
+};
+
+/// @brief The operations of a thread-safe cell 'C', which the generic code calls instead of the
+/// members of 'C'. A specialization provides:
+/// * @c value_type: the value in the cell.
+/// * <tt>static snapshot_type load_snapshot(const C &)</tt>: reads the current value, which is
+///   accessed by @c * and @c -> of the snapshot, and stays valid until the snapshot is destroyed.
+/// * <tt>static void store(C &, value_type)</tt>: replaces the value.
+/// * <tt>static void update(C &, F &&f)</tt>: replaces the value with 'f(current)', where 'f' has
+///   the signature of <tt>value_type(const value_type &)</tt>. 'f' may be called several times by
+///   the lock-free cells, so it must not have side effects.
+///
+/// It is specialized for the @c SyncCell, the @c RcuCell, the @c SeqLockCell and the @c ArcCell,
+/// and a cell of another library can specialize it too.
+template<typename C>
+struct SharedCellTraits
+{
+};
+
+/// @brief The snapshot is a copy, and the update is the @c replace_with.
+template<typename T, typename Backoff>
+struct SharedCellTraits<SyncCell<T, Backoff>>
+{
+    using value_type = T;
+    using snapshot_type = ValueSnapshot<T>;
+
+    static snapshot_type load_snapshot(const SyncCell<T, Backoff> &cell)
+    {
+        return snapshot_type(cell.load());
+    }
+
+    static void store(SyncCell<T, Backoff> &cell, value_type value)
+    {
+        cell.store(std::move(value));
+    }
+
+    template<typename F>
+    static void update(SyncCell<T, Backoff> &cell, F &&f)
+    {
+        cell.replace_with(std::forward<F>(f));
+    }
+};
+
+/// @brief The snapshot is the @c ReadGuard, which delays the reclamation of the value while it lives.
+template<typename T, bool Inline>
+struct SharedCellTraits<RcuCell<T, Inline>>
+{
+    using value_type = T;
+    using snapshot_type = typename RcuCell<T, Inline>::ReadGuard;
+
+    static snapshot_type load_snapshot(const RcuCell<T, Inline> &cell) noexcept
+    {
+        return cell.read();
+    }
+
+    static void store(RcuCell<T, Inline> &cell, value_type value)
+    {
+        cell.store(std::move(value));
+    }
+
+    template<typename F>
+    static void update(RcuCell<T, Inline> &cell, F &&f)
+    {
+        cell.update(std::forward<F>(f));
+    }
+};
+
+template<typename T, typename Backoff>
+struct SharedCellTraits<SeqLockCell<T, Backoff>>
+{
+    using value_type = T;
+    using snapshot_type = ValueSnapshot<T>;
+
+    static snapshot_type load_snapshot(const SeqLockCell<T, Backoff> &cell) noexcept
+    {
+        return snapshot_type(cell.read());
+    }
+
+    static void store(SeqLockCell<T, Backoff> &cell, const value_type &value) noexcept
+    {
+        cell.write(value);
+    }
+
+    template<typename F>
+    static void update(SeqLockCell<T, Backoff> &cell, F &&f)
+    {
+        cell.update(std::forward<F>(f));
+    }
+};
+
+/// @brief The value is the pointee of the @c ArcCell, and the snapshot is the shared pointer to it.
+/// A stored value is moved into a new shared pointer, and the update retries the
+/// @c compare_and_swap until no other store has happened meanwhile.
+/// @note The operations require a cell which is not empty.
+template<typename T>
+struct SharedCellTraits<ArcCell<T>>
+{
+    using value_type = T;
+    using snapshot_type = std::shared_ptr<const T>;
+
+    static snapshot_type load_snapshot(const ArcCell<T> &cell) noexcept
+    {
+        return cell.load();
+    }
+
+    static void store(ArcCell<T> &cell, value_type value)
+    {
+        cell.store(std::make_shared<T>(std::move(value)));
+    }
+
+    template<typename F>
+    static void update(ArcCell<T> &cell, F &&f)
+    {
+        auto current = cell.load();
+        while (true) {
+            auto prev = cell.compare_and_swap(current, std::make_shared<T>(f(std::as_const(*current))));
+            if (prev == current) {
+                return;
+            }
+            current = std::move(prev);
+        }
+    }
+};
+
+/// @brief Any thread-safe cell, whose operations are provided by the @c SharedCellTraits. A library
+/// takes it as a parameter, and the user chooses the cell fitting the read and write pattern:
+/// ``` cpp
+/// template<sc::SharedCell Cell>
+/// class RateTracker
+/// {
+///     using Traits = sc::SharedCellTraits<Cell>;
+///
+/// public:
+///     double average() const { return Traits::load_snapshot(cell_)->average; }
+///     void add(double sample) { Traits::update(cell_, [=](const Stats &s) { return s.with(sample); }); }
+///
+/// private:
+///     Cell cell_;
+/// };
+///
+/// RateTracker<sc::SeqLockCell<Stats>> tracker;   // Or sc::SyncCell<Stats>, sc::RcuCell<Stats>, ...
+/// ```
+template<typename C>
+concept SharedCell = requires(C &cell, const C &const_cell, typename SharedCellTraits<C>::value_type value,
+                              typename SharedCellTraits<C>::value_type (*f)(
+                                      const typename SharedCellTraits<C>::value_type &)) {
+    typename SharedCellTraits<C>::snapshot_type;
+    { *SharedCellTraits<C>::load_snapshot(const_cell) }
+            -> std::convertible_to<const typename SharedCellTraits<C>::value_type &>;
+    SharedCellTraits<C>::store(cell, std::move(value));
+    SharedCellTraits<C>::update(cell, f);
+};
+
+}
+
+#endif //SYNC_CELL_SHARED_CELL_HPP
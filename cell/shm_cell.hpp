@@ -0,0 +1,232 @@
+///
+/// @file  shm_cell.hpp
+/// @brief A cell placed in a memory shared by the processes.
+///
+
+#ifndef SYNC_CELL_SHM_CELL_HPP
+#define SYNC_CELL_SHM_CELL_HPP
+
+#include <array>
+#include <atomic>
+#include <bit>
+#include <chrono>
+#include <cstddef>
+#include <cstdint>
+#include <cstring>
+#include <new>
+
+#include "shared/shm.hpp"
+#include "util/back_off.hpp"
+
+
+namespace sc::shm {
+
+/// @brief A cell of a @c Pod value guarded by a sequence lock, which is placed in a memory mapped by
+/// several processes (e.g. a @c Region), for the state published by one process to the others:
+/// ``` cpp
+/// // In the publisher.
+/// auto *status = sc::shm::ShmCell<Status>::create(region.data());
+/// status->store(Status{.healthy = true});
+/// // In the observers.
+/// auto *status = sc::shm::ShmCell<Status>::attach(region.data());
+/// auto version = status->version();
+/// auto current = status->load();
+/// version = status->wait_changed(version);
+/// ```
+///
+/// The cell holds no pointer, only its layout header, the sequence word, the count of the waiters
+/// and the value bytes, so it works at any address of each process. A reader retries while a
+/// writer is writing, and the waiters of @c wait_changed park on the sequence word by the shared
+/// futex, so the writer only makes a system call if a process waits.
+///
+/// @note A process dying while writing leaves the cell locked, and the others spin forever.
+/// @tparam T The value type.
+/// @tparam Backoff The backoff of the readers and writers waiting for a writer.
+template<Pod T, typename Backoff = util::Backoff>
+class ShmCell
+{
+    using Word = uint64_t;
+    static constexpr size_t WordCount = (sizeof(T) + sizeof(Word) - 1) / sizeof(Word);
+
+    explicit ShmCell(const T &value) noexcept
+    {
+        // The header is constructed first with a zero magic, which is written last, so a process
+        // attaching to a reused region meanwhile does not see the old cell as valid.
+        store_words(value);
+        header_.init(impl::Kind::Cell, sizeof(T), alignof(T));
+    }
+
+public:
+    using value_type = T;
+
+    /// @brief Returns the bytes of the memory taken by the cell.
+    static constexpr size_t required_size() noexcept
+    {
+        return sizeof(ShmCell);
+    }
+
+    /// @brief Constructs the cell at 'memory', which must be aligned to @c alignof(ShmCell) and hold
+    /// @c required_size() bytes. The other processes attach to it after the call returns.
+    static ShmCell *create(void *memory, const T &value = T{}) noexcept
+    {
+        return new(memory) ShmCell(value);
+    }
+
+    /// @brief Returns the cell created at 'memory' by another process (or this one).
+    /// @return nullptr if no cell has been created there yet, or the cell has been created for
+    /// another value type, or by another layout version of the project.
+    static ShmCell *attach(void *memory) noexcept
+    {
+        auto *cell = std::launder(static_cast<ShmCell *>(memory));
+        return cell->header_.matches(impl::Kind::Cell, sizeof(T), alignof(T)) ? cell : nullptr;
+    }
+
+    ShmCell(const ShmCell &) = delete;
+
+    ShmCell &operator=(const ShmCell &) = delete;
+
+    [[nodiscard]] value_type load() const noexcept
+    {
+        Backoff backoff;
+        while (true) {
+            auto seq = seq_.load(std::memory_order_acquire);
+            if ((seq & 1) == 0) {
+                Word words[WordCount];
+                for (size_t i = 0; i < WordCount; ++i) {
+                    words[i] = words_[i].load(std::memory_order_relaxed);
+                }
+                // The same pairing with the fence of the writer as the one of the SeqLockCell.
+                std::atomic_thread_fence(std::memory_order_acquire);
+                if (seq_.load(std::memory_order_relaxed) == seq) {
+                    std::array<std::byte, sizeof(T)> bytes;
+                    std::memcpy(bytes.data(), words, sizeof(T));
+                    return std::bit_cast<value_type>(bytes);
+                }
+                backoff.spin();
+            } else {
+                backoff.snooze();
+            }
+        }
+    }
+
+    /// @brief Returns the count of the stores to the cell since it is created, which wraps around.
+    [[nodiscard]] uint32_t version() const noexcept
+    {
+        return seq_.load(std::memory_order_acquire) >> 1;
+    }
+
+    void store(const value_type &value) noexcept
+    {
+        auto seq = lock();
+        store_words(value);
+        unlock(seq);
+    }
+
+    /// @brief Stores the result of 'f' called with the current value, with the other writers
+    /// excluded meanwhile.
+    /// @param f The function with the signature of 'T(const T &)', which must not throw.
+    template<typename F>
+    void update(F &&f) noexcept
+    {
+        auto seq = lock();
+        store_words(f(load_words()));
+        unlock(seq);
+    }
+
+    /// @brief Parks the thread until the version differs from 'seen'.
+    /// @return The new version.
+    uint32_t wait_changed(uint32_t seen) const noexcept
+    {
+        while (true) {
+            if (auto version = wait_once(seen, -1); version != seen) {
+                return version;
+            }
+        }
+    }
+
+    /// @brief Parks the thread until the version differs from 'seen', at most 'timeout'.
+    /// @return The version, which is still 'seen' if the timeout has expired.
+    template<typename Rep, typename Period>
+    uint32_t wait_changed_for(uint32_t seen, std::chrono::duration<Rep, Period> timeout) const noexcept
+    {
+        auto deadline = std::chrono::steady_clock::now() + timeout;
+        while (true) {
+            auto remaining = std::chrono::ceil<std::chrono::nanoseconds>(deadline - std::chrono::steady_clock::now());
+            if (remaining.count() <= 0) {
+                return version();
+            }
+            if (auto version = wait_once(seen, remaining.count()); version != seen) {
+                return version;
+            }
+        }
+    }
+
+private:
+    /// @brief Parks once unless the version has changed.
+    uint32_t wait_once(uint32_t seen, int64_t ns) const noexcept
+    {
+        waiters_.fetch_add(1, std::memory_order_seq_cst);
+        // Pairs with the store of the sequence and the load of the waiters in the writer: either
+        // the writer sees the waiter, or the waiter sees the new sequence.
+        auto seq = seq_.load(std::memory_order_seq_cst);
+        if (seq >> 1 == seen) {
+            impl::wait(seq_, seq, ns);
+            seq = seq_.load(std::memory_order_acquire);
+        }
+        waiters_.fetch_sub(1, std::memory_order_relaxed);
+        return seq >> 1;
+    }
+
+    uint32_t lock() noexcept
+    {
+        Backoff backoff;
+        auto seq = seq_.load(std::memory_order_relaxed);
+        while (true) {
+            if ((seq & 1) == 0 &&
+                seq_.compare_exchange_weak(seq, seq + 1, std::memory_order_acquire, std::memory_order_relaxed)) {
+                // The odd sequence is visible before any word is changed.
+                std::atomic_thread_fence(std::memory_order_release);
+                return seq;
+            }
+            backoff.snooze();
+            seq = seq_.load(std::memory_order_relaxed);
+        }
+    }
+
+    void unlock(uint32_t seq) noexcept
+    {
+        seq_.store(seq + 2, std::memory_order_seq_cst);
+        if (waiters_.load(std::memory_order_seq_cst) != 0) {
+            impl::wake(seq_, true);
+        }
+    }
+
+    void store_words(const value_type &value) noexcept
+    {
+        Word words[WordCount] = {};
+        std::memcpy(words, &value, sizeof(T));
+        for (size_t i = 0; i < WordCount; ++i) {
+            words_[i].store(words[i], std::memory_order_relaxed);
+        }
+    }
+
+    [[nodiscard]] value_type load_words() const noexcept
+    {
+        Word words[WordCount];
+        for (size_t i = 0; i < WordCount; ++i) {
+            words[i] = words_[i].load(std::memory_order_relaxed);
+        }
+        std::array<std::byte, sizeof(T)> bytes;
+        std::memcpy(bytes.data(), words, sizeof(T));
+        return std::bit_cast<value_type>(bytes);
+    }
+
+    impl::Header header_;
+    std::atomic<uint32_t> seq_{0};
+    mutable std::atomic<uint32_t> waiters_{0};
+    std::atomic<Word> words_[WordCount];
+};
+
+}
+
+#endif //SYNC_CELL_SHM_CELL_HPP
@@ -0,0 +1,1119 @@
+///
+/// @file  sync_cell.hpp
+/// @brief A thread-safe mutable memory location. Inspired by crossbeam-rs:
+/// [AtomicCell](https://github.com/crossbeam-rs/crossbeam/blob/master/crossbeam-utils/src/atomic/atomic_cell.rs).
+///
+
+#ifndef SYNC_CELL_SYNC_CELL_HPP
+#define SYNC_CELL_SYNC_CELL_HPP
+
+#include <atomic>
+#include <concepts>
+#include <cstring>
+#include <exception>
+#include <memory>
+#include <optional>
+#include <type_traits>
+#include <utility>
+
+#include "shared/compiler_workaround.hpp"
+#include "shared/config.hpp"
+#include "shared/metrics.hpp"
+#include "shared/serde.hpp"
+#include "util/back_off.hpp"
+#include "util/drop_queue.hpp"
+
+#if defined(SYNC_CELL_CRITICAL_SECTION)
+#include "shared/critical_section.hpp"
+#endif
+
+
+namespace sc {
+
+/// @brief The backend tag of the @c SyncCell forcing the lock-based storage, even if the value
+/// could be stored in a @c std::atomic<T>. A lock has a bounded and predictable latency, while a
+/// CAS loop (e.g. @c fetch_update or @c with_mut on a contended cell) may retry many times.
+///
+/// ``` cpp
+/// sc::SyncCell<uint64_t, sc::ForceLock<>> cell(0);
+/// ```
+///
+/// @tparam Backoff The backoff of the spin lock.
+template<typename Backoff = util::Backoff>
+struct ForceLock { };
+
+/// @brief The backend tag of the @c SyncCell applying the updates by flat combining, for the cells
+/// written by many threads at once.
+///
+/// The @c with_mut and @c fetch_update publish their function on a request list, and the thread
+/// which takes the lock of the cell (the combiner) applies all published functions in a row, while
+/// the others wait for their results. So a highly contended cell is updated by one thread holding
+/// the value in its cache, instead of the retry storm of a CAS loop, or the lock handed over for
+/// each update. Whether it is faster depends on the machine, measure it with the
+/// @c sync_cell_bench first. The other operations take the lock directly, as the @c ForceLock cell
+/// does.
+///
+/// ``` cpp
+/// sc::SyncCell<Stats, sc::FlatCombining<>> stats;
+/// stats.with_mut([&](Stats &s) { s.add(sample); });
+/// ```
+///
+/// @note The functions of the other threads are called on the combiner thread, so they must not
+/// depend on the thread-local state. An exception thrown by a function is rethrown to its caller.
+/// @tparam Backoff The backoff of the lock and of the waits for the combiner.
+template<typename Backoff = util::Backoff>
+struct FlatCombining { };
+
+/// @brief The backend tag of the @c SyncCell deferring the destruction of the replaced values to
+/// the @c util::DropQueue::global(), so a @c store or @c compare_exchange replacing a large value
+/// (e.g. a big map) does not run its destructor in the writer, nor make the other threads spin on
+/// the lock while it runs. The parked values are destroyed by the @c collect of the queue, or by a
+/// @c util::DropCollector thread.
+///
+/// ``` cpp
+/// sc::SyncCell<std::map<Key, Route>, sc::DeferredDrop<>> routes;
+/// ```
+///
+/// The trivially copyable values have no destructor to defer, so they keep the atomic storage.
+/// @tparam Backoff The backoff of the spin lock.
+template<typename Backoff = util::Backoff>
+struct DeferredDrop { };
+
+/// @brief The value types of the @c SyncCell supporting the arithmetic operations: the integer
+/// types except the @c bool, and the floating-point types. A concept can not be specialized, so
+/// the set of the types is closed.
+template<typename T>
+concept AtomicArith = (std::integral<T> && !std::same_as<std::remove_cv_t<T>, bool>) || std::floating_point<T>;
+
+namespace impl {
+
+/// @brief Returns 'a + b', which wraps around on the overflow of the integers like the
+/// @c std::atomic::fetch_add.
+template<AtomicArith T>
+constexpr T wrapping_add(T a, T b) noexcept
+{
+    if constexpr(std::integral<T>) {
+        using U = std::make_unsigned_t<T>;
+        return static_cast<T>(static_cast<U>(static_cast<U>(a) + static_cast<U>(b)));
+    } else {
+        return a + b;
+    }
+}
+
+/// @brief Returns 'a - b', which wraps around on the overflow of the integers.
+template<AtomicArith T>
+constexpr T wrapping_sub(T a, T b) noexcept
+{
+    if constexpr(std::integral<T>) {
+        using U = std::make_unsigned_t<T>;
+        return static_cast<T>(static_cast<U>(static_cast<U>(a) - static_cast<U>(b)));
+    } else {
+        return a - b;
+    }
+}
+
+#if defined(SYNC_CELL_CRITICAL_SECTION)
+
+/// @brief The lock of the lock-based cell storage, using the user-provided critical section.
+template<typename Backoff>
+class CellLock
+{
+public:
+    void lock() noexcept
+    {
+        state_ = critical_section::acquire();
+    }
+
+    bool try_lock() noexcept
+    {
+        lock();
+        return true;
+    }
+
+    void unlock() noexcept
+    {
+        critical_section::release(state_);
+    }
+
+    [[nodiscard]] metrics::Stats stats() const noexcept
+    {
+        return {};
+    }
+
+private:
+    /// @brief Only accessed in the critical section.
+    critical_section::RestoreState state_ = 0;
+};
+
+#else
+
+/// @brief A tiny spin lock used by the lock-based cell storage.
+template<typename Backoff>
+class CellLock
+{
+public:
+    void lock() noexcept
+    {
+        Backoff backoff;
+        while (flag_.test_and_set(std::memory_order_acquire)) {
+            while (flag_.test(std::memory_order_relaxed)) {
+                metrics_.spin();
+                backoff.snooze();
+            }
+        }
+    }
+
+    bool try_lock() noexcept
+    {
+        return !flag_.test(std::memory_order_relaxed) && !flag_.test_and_set(std::memory_order_acquire);
+    }
+
+    void unlock() noexcept
+    {
+        flag_.clear(std::memory_order_release);
+    }
+
+    [[nodiscard]] metrics::Stats stats() const noexcept
+    {
+        return metrics_.snapshot();
+    }
+
+private:
+    std::atomic_flag flag_ = ATOMIC_FLAG_INIT;
+    SC_NO_UNIQUE_ADDRESS metrics::Counters metrics_;
+};
+
+#endif
+
+/// @brief Holds the @c CellLock in a scope. The @c std::lock_guard is not used, so the cell does
+/// not depend on the @c <mutex> header.
+template<typename Lock>
+class CellLockGuard
+{
+public:
+    explicit CellLockGuard(Lock &lock) noexcept : lock_(lock)
+    {
+        lock_.lock();
+    }
+
+    CellLockGuard(const CellLockGuard &) = delete;
+
+    CellLockGuard &operator=(const CellLockGuard &) = delete;
+
+    ~CellLockGuard()
+    {
+        lock_.unlock();
+    }
+
+private:
+    Lock &lock_;
+};
+
+template<typename T>
+struct IsAlwaysLockFree : std::bool_constant<std::atomic<T>::is_always_lock_free> { };
+
+/// @brief Whether the @c std::atomic<T> is used as the cell storage.
+template<typename T>
+inline constexpr bool UseAtomicStorage = std::conjunction_v<
+        std::is_trivially_copyable<T>
+#if defined(SYNC_CELL_ATOMIC_FALLBACK)
+        , IsAlwaysLockFree<T>
+#endif
+>;
+
+/// @brief Cell storage for the trivially copyable types, backed by the @c std::atomic<T>.
+///
+/// Whether the storage is lock-free depends on the compiler and the platform (see the Readme).
+template<typename T, typename Backoff, bool = UseAtomicStorage<T>>
+class CellStorage
+{
+public:
+    template<typename... Args>
+    constexpr explicit CellStorage(Args &&... args) : value_(T(std::forward<Args>(args)...)) { }
+
+    [[nodiscard]] bool is_lock_free() const noexcept
+    {
+        return value_.is_lock_free();
+    }
+
+    [[nodiscard]] metrics::Stats stats() const noexcept
+    {
+        return metrics_.snapshot();
+    }
+
+    T load(std::memory_order order) const noexcept
+    {
+        return value_.load(order);
+    }
+
+    void store(T value, std::memory_order order) noexcept
+    {
+        value_.store(value, order);
+    }
+
+    T exchange(T value, std::memory_order order) noexcept
+    {
+        return value_.exchange(value, order);
+    }
+
+    bool compare_exchange_weak(T &expected, T desired,
+                               std::memory_order success, std::memory_order failure) noexcept
+    {
+        return value_.compare_exchange_weak(expected, desired, success, failure);
+    }
+
+    bool compare_exchange_strong(T &expected, T desired,
+                                 std::memory_order success, std::memory_order failure) noexcept
+    {
+        return value_.compare_exchange_strong(expected, desired, success, failure);
+    }
+
+    template<typename F>
+    std::optional<T> fetch_update(std::memory_order set_order, std::memory_order fetch_order, F &f)
+    {
+        auto prev = value_.load(fetch_order);
+        while (true) {
+            std::optional<T> next = f(std::as_const(prev));
+            if (!next) {
+                return {};
+            }
+            if (value_.compare_exchange_weak(prev, *next, set_order, fetch_order)) {
+                return prev;
+            }
+            metrics_.cas_failure();
+        }
+    }
+
+    template<typename F>
+    auto with(F &f) const
+    {
+        const T value = value_.load(std::memory_order_seq_cst);
+        return f(value);
+    }
+
+    template<typename F>
+    auto with_mut(F &f)
+    {
+        auto prev = value_.load(std::memory_order_seq_cst);
+        while (true) {
+            auto next = prev;
+            if constexpr(std::is_void_v<std::invoke_result_t<F &, T &>>) {
+                f(next);
+                if (value_.compare_exchange_weak(prev, next, std::memory_order_seq_cst)) {
+                    return;
+                }
+            } else {
+                auto result = f(next);
+                if (value_.compare_exchange_weak(prev, next, std::memory_order_seq_cst)) {
+                    return result;
+                }
+            }
+            metrics_.cas_failure();
+        }
+    }
+
+    T fetch_add(T value, std::memory_order order) noexcept
+    {
+        if constexpr(std::floating_point<T>) {
+            return fetch_compute(value, order, [](T prev, T v) { return prev + v; });
+        } else {
+            return value_.fetch_add(value, order);
+        }
+    }
+
+    T fetch_sub(T value, std::memory_order order) noexcept
+    {
+        if constexpr(std::floating_point<T>) {
+            return fetch_compute(value, order, [](T prev, T v) { return prev - v; });
+        } else {
+            return value_.fetch_sub(value, order);
+        }
+    }
+
+    T fetch_min(T value) noexcept
+    {
+#if defined(__cpp_lib_atomic_min_max)
+        if constexpr(std::integral<T>) {
+            return value_.fetch_min(value, std::memory_order_seq_cst);
+        }
+#endif
+        return fetch_replace_if(value, [](T prev, T v) { return v < prev; });
+    }
+
+    T fetch_max(T value) noexcept
+    {
+#if defined(__cpp_lib_atomic_min_max)
+        if constexpr(std::integral<T>) {
+            return value_.fetch_max(value, std::memory_order_seq_cst);
+        }
+#endif
+        return fetch_replace_if(value, [](T prev, T v) { return prev < v; });
+    }
+
+    T into_inner() noexcept
+    {
+        return value_.load(std::memory_order_relaxed);
+    }
+
+private:
+    /// @brief Stores 'op(prev, value)' by a CAS loop. The CAS compares the bit pattern, so a NaN
+    /// value, which is not equal to itself, does not make it loop forever.
+    template<typename Op>
+    T fetch_compute(T value, std::memory_order order, Op op) noexcept
+    {
+        auto prev = value_.load(std::memory_order_relaxed);
+        while (!value_.compare_exchange_weak(prev, op(prev, value), order, std::memory_order_relaxed)) {
+            metrics_.cas_failure();
+        }
+        return prev;
+    }
+
+    /// @brief Stores the 'value' by a CAS loop while the 'replace' returns true for the current
+    /// value, for the operations without the native atomic instruction.
+    template<typename Replace>
+    T fetch_replace_if(T value, Replace replace) noexcept
+    {
+        auto prev = value_.load(std::memory_order_seq_cst);
+        while (replace(prev, value)) {
+            if (value_.compare_exchange_weak(prev, value, std::memory_order_seq_cst)) {
+                break;
+            }
+            metrics_.cas_failure();
+        }
+        return prev;
+    }
+
+    std::atomic<T> value_;
+    SC_NO_UNIQUE_ADDRESS metrics::Counters metrics_;
+};
+
+/// @brief Cell storage for the other types. All accesses are guarded by a spin lock.
+template<typename T, typename Backoff>
+class CellStorage<T, Backoff, false>
+{
+public:
+    template<typename... Args>
+    constexpr explicit CellStorage(Args &&... args) : value_(std::forward<Args>(args)...) { }
+
+    [[nodiscard]] bool is_lock_free() const noexcept
+    {
+        return false;
+    }
+
+    [[nodiscard]] metrics::Stats stats() const noexcept
+    {
+        return lock_.stats();
+    }
+
+    T load(std::memory_order) const
+    {
+        CellLockGuard guard(lock_);
+        return value_;
+    }
+
+    void store(T value, std::memory_order)
+    {
+        CellLockGuard guard(lock_);
+        value_ = std::move(value);
+    }
+
+    T exchange(T value, std::memory_order)
+    {
+        CellLockGuard guard(lock_);
+        return std::exchange(value_, std::move(value));
+    }
+
+    bool compare_exchange_weak(T &expected, T desired, std::memory_order success, std::memory_order failure)
+    {
+        return compare_exchange_strong(expected, std::move(desired), success, failure);
+    }
+
+    bool compare_exchange_strong(T &expected, T desired, std::memory_order, std::memory_order)
+    {
+        CellLockGuard guard(lock_);
+        if (equals(value_, expected)) {
+            value_ = std::move(desired);
+            return true;
+        }
+
+        expected = value_;
+        return false;
+    }
+
+    template<typename F>
+    std::optional<T> fetch_update(std::memory_order, std::memory_order, F &f)
+    {
+        CellLockGuard guard(lock_);
+        std::optional<T> next = f(std::as_const(value_));
+        if (!next) {
+            return {};
+        }
+
+        return std::exchange(value_, *std::move(next));
+    }
+
+    template<typename F>
+    auto with(F &f) const
+    {
+        CellLockGuard guard(lock_);
+        return f(std::as_const(value_));
+    }
+
+    template<typename F>
+    auto with_mut(F &f)
+    {
+        CellLockGuard guard(lock_);
+        return f(value_);
+    }
+
+    T fetch_add(T value, std::memory_order)
+    {
+        CellLockGuard guard(lock_);
+        return std::exchange(value_, wrapping_add(value_, value));
+    }
+
+    T fetch_sub(T value, std::memory_order)
+    {
+        CellLockGuard guard(lock_);
+        return std::exchange(value_, wrapping_sub(value_, value));
+    }
+
+    T fetch_min(T value)
+    {
+        CellLockGuard guard(lock_);
+        auto prev = value_;
+        if (value < prev) {
+            value_ = value;
+        }
+        return prev;
+    }
+
+    T fetch_max(T value)
+    {
+        CellLockGuard guard(lock_);
+        auto prev = value_;
+        if (prev < value) {
+            value_ = value;
+        }
+        return prev;
+    }
+
+    T &get_mut() noexcept
+    {
+        return value_;
+    }
+
+    T into_inner() noexcept(std::is_nothrow_move_constructible_v<T>)
+    {
+        return std::move(value_);
+    }
+
+protected:
+    /// @brief Compares bitwise for the trivially copyable types (falling back from the atomic
+    /// storage), the same as the @c std::atomic.
+    static bool equals(const T &a, const T &b) noexcept
+    {
+        if constexpr(std::is_trivially_copyable_v<T>) {
+            return std::memcmp(std::addressof(a), std::addressof(b), sizeof(T)) == 0;
+        } else {
+            static_assert(std::equality_comparable<T>, "The compare-exchange operation requires an operator==.");
+            return a == b;
+        }
+    }
+
+    mutable CellLock<Backoff> lock_;
+    T value_;
+};
+
+/// @brief Cell storage of the @c FlatCombining backend: the lock-based storage, whose updates are
+/// applied by the combiner on behalf of the waiting threads.
+template<typename T, typename Backoff>
+class CombiningStorage : public CellStorage<T, Backoff, false>
+{
+    using Base = CellStorage<T, Backoff, false>;
+
+    /// @brief An update published by a thread, which lives on the stack of the thread until done.
+    struct Request
+    {
+        /// @brief Applies the function of the request to the value, and keeps the result or the
+        /// exception in the request.
+        void (*apply)(Request &request, T &value);
+        Request *next = nullptr;
+        std::atomic<bool> done{false};
+    };
+
+    template<typename F, typename R>
+    struct TypedRequest : Request
+    {
+        F *f;
+        std::optional<std::conditional_t<std::is_void_v<R>, bool, R>> result;
+        std::exception_ptr error;
+
+        explicit TypedRequest(F &f) noexcept : Request{&TypedRequest::apply_to}, f(&f) { }
+
+        static void apply_to(Request &request, T &value)
+        {
+            auto &self = static_cast<TypedRequest &>(request);
+            try {
+                if constexpr(std::is_void_v<R>) {
+                    (*self.f)(value);
+                    self.result.emplace(true);
+                } else {
+                    self.result.emplace((*self.f)(value));
+                }
+            } catch (...) {
+                self.error = std::current_exception();
+            }
+        }
+
+        R take()
+        {
+            if (error) {
+                std::rethrow_exception(error);
+            }
+            if constexpr(!std::is_void_v<R>) {
+                return *std::move(result);
+            }
+        }
+    };
+
+    /// @brief The rounds of a combiner taking the new requests before it releases the lock, which
+    /// bounds the time of a thread serving the others.
+    static constexpr uint32_t CombineRounds = 4;
+
+public:
+    using Base::Base;
+
+    template<typename F>
+    std::optional<T> fetch_update(std::memory_order, std::memory_order, F &f)
+    {
+        auto update = [&f](T &value) -> std::optional<T> {
+            std::optional<T> next = f(std::as_const(value));
+            if (!next) {
+                return {};
+            }
+            return std::exchange(value, *std::move(next));
+        };
+        return combine(update);
+    }
+
+    template<typename F>
+    auto with_mut(F &f)
+    {
+        return combine(f);
+    }
+
+private:
+    template<typename F>
+    auto combine(F &f)
+    {
+        using R = std::invoke_result_t<F &, T &>;
+        TypedRequest<F, R> request(f);
+        if (this->lock_.try_lock()) {
+            // Not contended: apply directly, and serve the requests published in the meantime.
+            request.apply(request, this->value_);
+            run_combiner();
+            this->lock_.unlock();
+            return request.take();
+        }
+
+        publish(request);
+        Backoff backoff;
+        while (!request.done.load(std::memory_order_acquire)) {
+            if (this->lock_.try_lock()) {
+                run_combiner();
+                this->lock_.unlock();
+                backoff.reset();
+                continue;
+            }
+            backoff.snooze();
+        }
+        return request.take();
+    }
+
+    void publish(Request &request) noexcept
+    {
+        request.next = pending_.load(std::memory_order_relaxed);
+        while (!pending_.compare_exchange_weak(
+                request.next, &request,
+                std::memory_order_release,
+                std::memory_order_relaxed)) { }
+    }
+
+    /// @brief Applies the published requests in their order. Must be called with the lock held.
+    void run_combiner()
+    {
+        for (uint32_t round = 0; round < CombineRounds; ++round) {
+            auto *head = pending_.exchange(nullptr, std::memory_order_acquire);
+            if (head == nullptr) {
+                return;
+            }
+
+            // The list is pushed in the reverse order of the publication.
+            Request *ordered = nullptr;
+            while (head != nullptr) {
+                ordered = std::exchange(head, std::exchange(head->next, ordered));
+            }
+            while (ordered != nullptr) {
+                // The waiter may return and destroy the request once it is done.
+                auto *next = ordered->next;
+                ordered->apply(*ordered, this->value_);
+                ordered->done.store(true, std::memory_order_release);
+                ordered = next;
+            }
+        }
+    }
+
+    /// @brief The stack of the published requests.
+    std::atomic<Request *> pending_{nullptr};
+};
+
+/// @brief Cell storage of the @c DeferredDrop backend: the lock-based storage, whose replaced values
+/// are moved out under the lock and parked in the drop queue after the unlock. The node of the drop
+/// queue is allocated before the lock, so a failed allocation leaves the cell unchanged.
+template<typename T, typename Backoff>
+class DeferredDropStorage : public CellStorage<T, Backoff, false>
+{
+    using Base = CellStorage<T, Backoff, false>;
+    using Reservation = util::DropQueue::Reservation<T>;
+
+public:
+    using Base::Base;
+
+    void store(T value, std::memory_order)
+    {
+        Reservation reservation(std::move(value));
+        {
+            CellLockGuard guard(this->lock_);
+            std::swap(this->value_, reservation.value());
+        }
+        util::DropQueue::global().defer(std::move(reservation));
+    }
+
+    bool compare_exchange_weak(T &expected, T desired, std::memory_order success, std::memory_order failure)
+    {
+        return compare_exchange_strong(expected, std::move(desired), success, failure);
+    }
+
+    bool compare_exchange_strong(T &expected, T desired, std::memory_order, std::memory_order)
+    {
+        Reservation reservation(std::move(desired));
+        {
+            CellLockGuard guard(this->lock_);
+            if (!Base::equals(this->value_, expected)) {
+                expected = this->value_;
+                return false;
+            }
+            std::swap(this->value_, reservation.value());
+        }
+        util::DropQueue::global().defer(std::move(reservation));
+        return true;
+    }
+};
+
+/// @brief Selects the storage of the @c SyncCell from its second template parameter, which is a
+/// backoff, a @c ForceLock, a @c FlatCombining or a @c DeferredDrop.
+template<typename T, typename Backoff>
+struct CellBackend
+{
+    static constexpr bool UseAtomic = UseAtomicStorage<T>;
+    using storage = CellStorage<T, Backoff, UseAtomic>;
+};
+
+template<typename T, typename Backoff>
+struct CellBackend<T, ForceLock<Backoff>>
+{
+    static constexpr bool UseAtomic = false;
+    using storage = CellStorage<T, Backoff, false>;
+};
+
+template<typename T, typename Backoff>
+struct CellBackend<T, FlatCombining<Backoff>>
+{
+    static constexpr bool UseAtomic = false;
+    using storage = CombiningStorage<T, Backoff>;
+};
+
+template<typename T, typename Backoff>
+struct CellBackend<T, DeferredDrop<Backoff>>
+{
+    static constexpr bool UseAtomic = UseAtomicStorage<T>;
+    using storage = std::conditional_t<UseAtomic, CellStorage<T, Backoff, true>, DeferredDropStorage<T, Backoff>>;
+};
+
+}
+
+/// @brief A thread-safe mutable memory location.
+///
+/// The @c SyncCell uses a @c std::atomic<T> for the trivially copyable types, which is lock-free if
+/// the platform supports the atomic operations on the size of @c T, otherwise it depends on the
+/// compiler (usually a global lock table in @c libatomic). For the other types, a spin lock in each
+/// cell is used to guard the value. With @c SYNC_CELL_ATOMIC_FALLBACK, the spin lock is also used
+/// for the trivially copyable types whose @c std::atomic<T> is not always lock-free. With the
+/// @c ForceLock backend, the spin lock is always used, the @c FlatCombining backend adds the
+/// combining of the updates to it, and the @c DeferredDrop backend moves the destruction of the
+/// replaced values out of the writers.
+///
+/// @tparam T The value type. It must be copyable.
+/// @tparam Backoff The backoff of the spin lock, e.g. @c util::BasicBackoff<util::backoff::SpinThenYield>
+/// for the oversubscribed machines, @c ForceLock<Backoff> to force the lock-based storage, or
+/// @c FlatCombining<Backoff> to combine the contended updates, or @c DeferredDrop<Backoff> to defer
+/// the destruction of the replaced values.
+template<typename T, typename Backoff = util::Backoff>
+class SyncCell
+{
+    static_assert(!std::is_reference_v<T> && std::is_copy_constructible_v<T>);
+
+    using Backend = impl::CellBackend<T, Backoff>;
+
+public:
+    using value_type = T;
+
+    /// @brief Whether the value is stored in a @c std::atomic<T>, otherwise it is guarded by a spin
+    /// lock.
+    static constexpr bool IsAtomic = Backend::UseAtomic;
+
+    /// @brief Whether the operations on the cell are always lock-free, i.e. @c IsAtomic and the
+    /// @c std::atomic<T> is always lock-free on the platform. The runtime @c is_lock_free may be true
+    /// even if this is false.
+    static constexpr bool IsAlwaysLockFree = std::conjunction_v<
+            std::bool_constant<IsAtomic>, impl::IsAlwaysLockFree<T>>;
+
+    template<typename U = T, std::enable_if_t<std::is_default_constructible_v<U>, bool> = false>
+    constexpr SyncCell() noexcept(std::is_nothrow_default_constructible_v<T>) : storage_() { }
+
+    constexpr explicit SyncCell(T value) noexcept(std::is_nothrow_move_constructible_v<T>)
+            : storage_(std::move(value)) { }
+
+    SyncCell(const SyncCell &) = delete;
+
+    SyncCell &operator=(const SyncCell &) = delete;
+
+    /// @brief Returns true if the operations on the cell are lock-free.
+    [[nodiscard]] bool is_lock_free() const noexcept
+    {
+        return storage_.is_lock_free();
+    }
+
+    /// @brief Returns the contention counters: the lock spins of the lock-based cell, and the CAS
+    /// retries of @c fetch_update on the atomic cell. Requires @c SYNC_CELL_METRICS.
+    [[nodiscard]] metrics::Stats stats() const noexcept
+    {
+        return storage_.stats();
+    }
+
+    /// @brief Loads a value from the cell.
+    [[nodiscard]] value_type load() const
+    {
+        return storage_.load(std::memory_order_seq_cst);
+    }
+
+    /// @brief Stores the 'value' into the cell.
+    void store(value_type value)
+    {
+        storage_.store(std::move(value), std::memory_order_seq_cst);
+    }
+
+    /// @brief Loads a value from the cell with the memory ordering, e.g. the relaxed one for a
+    /// statistic which does not order other memory accesses.
+    ///
+    /// The operations without the ordering parameter are sequentially consistent. The orderings
+    /// are the same as the ones of the @c std::atomic, and are only used by the atomic cell: the
+    /// lock of the lock-based cell always orders the operations as acquire and release.
+    [[nodiscard]] value_type load_with(std::memory_order order) const
+    {
+        return storage_.load(order);
+    }
+
+    /// @brief Stores the 'value' into the cell with the memory ordering, which must be relaxed,
+    /// release or seq_cst.
+    void store_with(value_type value, std::memory_order order)
+    {
+        storage_.store(std::move(value), order);
+    }
+
+    /// @brief Stores the 'value' into the cell, and returns the previous value, as one atomic step.
+    value_type swap(value_type value)
+    {
+        return storage_.exchange(std::move(value), std::memory_order_seq_cst);
+    }
+
+    /// @brief The @c swap with the memory ordering of the @c std::atomic::exchange.
+    value_type swap_with(value_type value, std::memory_order order)
+    {
+        return storage_.exchange(std::move(value), order);
+    }
+
+    /// @brief Replaces the value with the result of 'f' called with the current value, and returns
+    /// the previous value. Like @c fetch_update, the function may be called multiple times for the
+    /// atomic cell.
+    /// @param f The function with the signature of 'T(const T &)'.
+    template<typename F>
+    value_type replace_with(F &&f)
+    {
+        static_assert(std::is_convertible_v<std::invoke_result_t<F &, const value_type &>, value_type>);
+
+        auto next = [&f](const value_type &prev) { return std::optional<value_type>(f(prev)); };
+        return *storage_.fetch_update(std::memory_order_seq_cst, std::memory_order_seq_cst, next);
+    }
+
+    /// @brief Leaves the default value in the cell, and returns the previous value.
+    value_type take() requires std::is_default_constructible_v<T>
+    {
+        return swap(value_type());
+    }
+
+    /// @brief Stores the 'desired' into the cell if the current value is equal to 'expected'.
+    /// Otherwise, the current value is loaded into the 'expected'.
+    ///
+    /// The comparison is bitwise (the same as the @c std::atomic) for the trivially copyable
+    /// types, and uses the @c operator== for the other types.
+    /// @return true if the current value is replaced by the 'desired'.
+    bool compare_exchange(value_type &expected, value_type desired)
+    {
+        return storage_.compare_exchange_strong(
+                expected, std::move(desired),
+                std::memory_order_seq_cst,
+                std::memory_order_seq_cst);
+    }
+
+    /// @brief The @c compare_exchange with the memory orderings of the successful update and the
+    /// failed load. The 'failure' must not be release or acq_rel.
+    bool compare_exchange_with(value_type &expected, value_type desired,
+                               std::memory_order success, std::memory_order failure)
+    {
+        return storage_.compare_exchange_strong(expected, std::move(desired), success, failure);
+    }
+
+    /// @brief Stores the 'desired' into the cell if the 'predicate' accepts the current value, e.g.
+    /// one comparing only a field of the value, or a type without the @c operator==.
+    ///
+    /// Like @c fetch_update, the predicate may be called multiple times for the atomic cell, and is
+    /// called once under the lock for the lock-based cell.
+    /// @param predicate The function with the signature of 'bool(const T &)'.
+    /// @return The previous value if the cell is updated, otherwise an empty optional.
+    template<typename P>
+    std::optional<value_type> compare_exchange_if(P &&predicate, value_type desired)
+    {
+        static_assert(std::is_convertible_v<std::invoke_result_t<P &, const value_type &>, bool>);
+
+        // The atomic cell only holds the trivially copyable types, whose move is a copy, so the
+        // 'desired' is still there for a retry; the lock-based cell calls the function once.
+        auto next = [&](const value_type &current) -> std::optional<value_type> {
+            if (!predicate(current)) {
+                return {};
+            }
+            return std::move(desired);
+        };
+        return storage_.fetch_update(std::memory_order_seq_cst, std::memory_order_seq_cst, next);
+    }
+
+    /// @brief Fetches the value, and applies a function to it that returns an optional new value.
+    /// If the function returns a new value, it is stored into the cell, otherwise the cell is not
+    /// changed.
+    ///
+    /// The function may be called multiple times if the value has been changed by other threads
+    /// in the meantime, as long as the function returns a new value. For the lock-based cell, the
+    /// function is called only once and the lock is held during the call.
+    /// @param set_order The memory ordering of the successful store.
+    /// @param fetch_order The memory ordering of the loads.
+    /// @param f The function with the signature of 'std::optional<T>(const T &)'.
+    /// @return The previous value if the cell is updated, otherwise an empty optional.
+    template<typename F>
+    std::optional<value_type> fetch_update(std::memory_order set_order, std::memory_order fetch_order, F &&f)
+    {
+        static_assert(std::is_convertible_v<std::invoke_result_t<F &, const value_type &>, std::optional<value_type>>);
+
+        return storage_.fetch_update(set_order, fetch_order, f);
+    }
+
+    /// @brief Loads the value from the cell, and returns a field of it.
+    ///
+    /// With the @c update storing many fields by one CAS, a small struct in the atomic cell works as
+    /// an "atomic struct", without packing the fields into an integer by hand.
+    /// @example
+    /// ``` cpp
+    /// struct Range { uint32_t begin; uint32_t end; };
+    /// sc::SyncCell<Range> range(Range{0, 0});
+    /// range.update([](Range &r) { ++r.begin; ++r.end; });
+    /// auto end = range.get(&Range::end);
+    /// ```
+    template<typename M, typename C> requires (std::is_base_of_v<C, T> && !std::is_function_v<M>)
+    [[nodiscard]] M get(M C::*field) const
+    {
+        auto project = [field](const value_type &value) -> M { return value.*field; };
+        return storage_.with(project);
+    }
+
+    /// @brief Updates the fields of the value together, so no thread sees some of them updated and
+    /// the others not. 'f' modifies a copy of the current value, which is stored by a single
+    /// @c compare_exchange_weak of the whole value, and 'f' is called again with the new current
+    /// value if another thread has changed it in the meantime, so it must have no side effect.
+    ///
+    /// The atomic cell requires the value to fit in one 8- or 16-byte word, which is lock-free if
+    /// @c is_lock_free (16 bytes need the double-width CAS, e.g. @c -mcx16 on x86-64). The
+    /// lock-based cells, e.g. a @c ForceLock one for a bigger struct, call 'f' once under the lock.
+    /// @param f The function with the signature of 'void(T &)'.
+    /// @return The new value stored.
+    template<typename F>
+    value_type update(F &&f)
+    {
+        static_assert(std::is_invocable_v<F &, value_type &>);
+        static_assert(!IsAtomic || sizeof(T) <= 16,
+                      "An atomic struct must fit in 16 bytes, use a sc::ForceLock cell for a bigger one.");
+
+        if constexpr(IsAtomic) {
+            auto prev = storage_.load(std::memory_order_acquire);
+            while (true) {
+                auto next = prev;
+                f(next);
+                if (storage_.compare_exchange_weak(prev, next, std::memory_order_acq_rel, std::memory_order_acquire)) {
+                    return next;
+                }
+            }
+        } else {
+            auto apply = [&f](value_type &value) -> value_type {
+                f(value);
+                return value;
+            };
+            return storage_.with_mut(apply);
+        }
+    }
+
+    /// @brief Calls a function with the value of the cell, and returns its result. The reference
+    /// passed to the function must not escape it.
+    ///
+    /// For the lock-based cell, the lock is held during the call, and the value is not copied. For
+    /// the atomic cell, the function reads a loaded copy.
+    /// @param f The function with the signature of 'R(const T &)'.
+    template<typename F>
+    auto with(F &&f) const
+    {
+        static_assert(std::is_invocable_v<F &, const value_type &>);
+        static_assert(!std::is_reference_v<std::invoke_result_t<F &, const value_type &>>,
+                      "The result must not refer to the value.");
+
+        return storage_.with(f);
+    }
+
+    /// @brief Calls a function with the mutable value of the cell, and returns its result. The
+    /// modification made by the function is stored into the cell atomically, and the reference
+    /// passed to the function must not escape it.
+    ///
+    /// For the lock-based cell, the lock is held during the call, and the function is called only
+    /// once. For the atomic cell, the function modifies a copy, which is stored by a CAS, so the
+    /// function may be called multiple times if the value has been changed by other threads in the
+    /// meantime.
+    /// @param f The function with the signature of 'R(T &)'.
+    template<typename F>
+    auto with_mut(F &&f)
+    {
+        static_assert(std::is_invocable_v<F &, value_type &>);
+        static_assert(!std::is_reference_v<std::invoke_result_t<F &, value_type &>>,
+                      "The result must not refer to the value.");
+
+        return storage_.with_mut(f);
+    }
+
+    /// @brief Adds the 'value' to the cell, the integers wrap around on the overflow.
+    ///
+    /// The arithmetic operations are available for the @c AtomicArith types. They are the native
+    /// atomic instructions of the atomic cell if the platform has them (e.g. the integer addition),
+    /// otherwise a CAS loop, and are done under the lock for the lock-based cell.
+    /// @return The previous value.
+    value_type fetch_add(value_type value) requires AtomicArith<T>
+    {
+        return storage_.fetch_add(value, std::memory_order_seq_cst);
+    }
+
+    /// @brief Subtracts the 'value' from the cell, the integers wrap around on the overflow.
+    /// @return The previous value.
+    value_type fetch_sub(value_type value) requires AtomicArith<T>
+    {
+        return storage_.fetch_sub(value, std::memory_order_seq_cst);
+    }
+
+    /// @brief The @c fetch_add with the memory ordering, e.g. the relaxed one for a counter.
+    value_type fetch_add_with(value_type value, std::memory_order order) requires AtomicArith<T>
+    {
+        return storage_.fetch_add(value, order);
+    }
+
+    /// @brief The @c fetch_sub with the memory ordering.
+    value_type fetch_sub_with(value_type value, std::memory_order order) requires AtomicArith<T>
+    {
+        return storage_.fetch_sub(value, order);
+    }
+
+    /// @brief Stores the minimum of the current value and the 'value', compared by the
+    /// @c operator<, so a NaN 'value' is never stored.
+    /// @return The previous value.
+    value_type fetch_min(value_type value) requires AtomicArith<T>
+    {
+        return storage_.fetch_min(value);
+    }
+
+    /// @brief Stores the maximum of the current value and the 'value', compared by the
+    /// @c operator<, so a NaN 'value' is never stored.
+    /// @return The previous value.
+    value_type fetch_max(value_type value) requires AtomicArith<T>
+    {
+        return storage_.fetch_max(value);
+    }
+
+    /// @brief Returns the reference to the value without locking, like the @c get_unsafe of the
+    /// locks. The caller must guarantee no other thread is accessing the cell, e.g. before the cell
+    /// is shared.
+    ///
+    /// Only the lock-based cells have it, the atomic cell can not give a reference to the value in
+    /// its @c std::atomic<T>, use @c into_inner or the @c store there.
+    value_type &get_mut() & noexcept requires (!IsAtomic)
+    {
+        return storage_.get_mut();
+    }
+
+    /// @brief Moves the value out of the cell without synchronization. The caller must guarantee no
+    /// other thread is accessing the cell, and the cell is not used after.
+    value_type into_inner() && noexcept(std::is_nothrow_move_constructible_v<T>)
+    {
+        return storage_.into_inner();
+    }
+
+private:
+    typename Backend::storage storage_;
+};
+
+/// @brief The atomic floating-point cells, e.g. for the metrics accumulating the durations. The
+/// @c fetch_add and @c fetch_sub are a CAS loop on the bit pattern, whether or not the standard
+/// library has the atomic floating-point operations, which are a CAS loop on most targets too.
+using F32Cell = SyncCell<float>;
+using F64Cell = SyncCell<double>;
+
+}
+
+#if SC_HAS_SERDE
+
+namespace sc::serde {
+
+/// @brief Serializes the loaded value of the cell.
+template<typename T, typename Backoff>
+struct Codec<SyncCell<T, Backoff>>
+{
+    using value_type = T;
+
+    static value_type snapshot(const SyncCell<T, Backoff> &c)
+    {
+        return c.load();
+    }
+
+    static void restore(SyncCell<T, Backoff> &c, value_type &&value)
+    {
+        c.store(std::move(value));
+    }
+};
+
+}
+
+#endif
+
+#endif //SYNC_CELL_SYNC_CELL_HPP
@@ -0,0 +1,194 @@
+///
+/// @file  tagged_ptr_cell.hpp
+/// @brief A cell holding a pointer with a version tag, which are updated together by a double-word
+/// CAS to avoid the ABA problem.
+///
+
+#ifndef SYNC_CELL_TAGGED_PTR_CELL_HPP
+#define SYNC_CELL_TAGGED_PTR_CELL_HPP
+
+#include <atomic>
+#include <bit>
+#include <cstdint>
+
+#if defined(_MSC_VER) && defined(_M_X64)
+#include <intrin.h>
+#endif
+
+
+namespace sc {
+
+namespace impl {
+
+/// @brief Two words which are loaded and compare-exchanged atomically.
+///
+/// It uses the @c cmpxchg16b on the x86-64 (requires the @c -mcx16 or a @c -march which supports
+/// it) and the @c LDXP/STXP (or @c CASP) on the AArch64 by the compiler intrinsics. On the other
+/// targets it falls back to the @c std::atomic, which may use a lock.
+class DoubleWordAtomic
+{
+public:
+    struct alignas(2 * sizeof(uint64_t)) Words
+    {
+        uint64_t lo;
+        uint64_t hi;
+
+        bool operator==(const Words &) const = default;
+    };
+
+#if defined(__GCC_HAVE_SYNC_COMPARE_AND_SWAP_16) || (defined(_MSC_VER) && defined(_M_X64))
+    static constexpr bool IsLockFree = true;
+#else
+    static constexpr bool IsLockFree = false;
+#endif
+
+    explicit DoubleWordAtomic(Words words) noexcept
+    {
+#if defined(__GCC_HAVE_SYNC_COMPARE_AND_SWAP_16)
+        value_ = std::bit_cast<unsigned __int128>(words);
+#elif defined(_MSC_VER) && defined(_M_X64)
+        value_[0] = static_cast<int64_t>(words.lo);
+        value_[1] = static_cast<int64_t>(words.hi);
+#else
+        value_.store(words, std::memory_order_relaxed);
+#endif
+    }
+
+    DoubleWordAtomic(const DoubleWordAtomic &) = delete;
+
+    DoubleWordAtomic &operator=(const DoubleWordAtomic &) = delete;
+
+    /// @brief Loads the words with the sequentially-consistent ordering.
+    /// @note There is no double-word atomic load instruction on some targets, the load is a CAS
+    /// which writes the same value back, so it takes the cache line exclusively.
+    [[nodiscard]] Words load() const noexcept
+    {
+        Words expected{0, 0};
+        const_cast<DoubleWordAtomic *>(this)->compare_exchange(expected, expected);
+        return expected;
+    }
+
+    /// @brief Replaces the words with 'desired' if they are equal to 'expected', otherwise loads
+    /// them into 'expected'. It is sequentially-consistent.
+    /// @return true if replaced.
+    bool compare_exchange(Words &expected, Words desired) noexcept
+    {
+#if defined(__GCC_HAVE_SYNC_COMPARE_AND_SWAP_16)
+        // The '__atomic' builtins call the libatomic for the 16-byte types on GCC, the '__sync' ones
+        // are inlined.
+        auto exp = std::bit_cast<unsigned __int128>(expected);
+        auto prev = __sync_val_compare_and_swap(&value_, exp, std::bit_cast<unsigned __int128>(desired));
+        if (prev == exp) {
+            return true;
+        }
+        expected = std::bit_cast<Words>(prev);
+        return false;
+#elif defined(_MSC_VER) && defined(_M_X64)
+        int64_t comparand[2]{static_cast<int64_t>(expected.lo), static_cast<int64_t>(expected.hi)};
+        auto ok = _InterlockedCompareExchange128(
+                value_, static_cast<int64_t>(desired.hi), static_cast<int64_t>(desired.lo), comparand);
+        expected = Words{static_cast<uint64_t>(comparand[0]), static_cast<uint64_t>(comparand[1])};
+        return ok != 0;
+#else
+        return value_.compare_exchange_strong(expected, desired, std::memory_order_seq_cst);
+#endif
+    }
+
+private:
+#if defined(__GCC_HAVE_SYNC_COMPARE_AND_SWAP_16)
+    alignas(16) unsigned __int128 value_;
+#elif defined(_MSC_VER) && defined(_M_X64)
+    alignas(16) int64_t value_[2];
+#else
+    std::atomic<Words> value_;
+#endif
+};
+
+}
+
+/// @brief A pointer with a version tag.
+template<typename T>
+struct TaggedPtr
+{
+    T *ptr = nullptr;
+    uint64_t tag = 0;
+
+    bool operator==(const TaggedPtr &) const = default;
+};
+
+/// @brief A cell holding a pointer with a version tag. Every successful update increases the tag,
+/// so a CAS with a stale @c TaggedPtr fails even if the pointer has been changed back to the same
+/// address (the ABA problem), which gives the lock-free structures (e.g. a Treiber stack with a
+/// node pool) the ABA protection without any memory reclamation.
+///
+/// The pointer and the tag are updated together by a double-word CAS, see @c impl::DoubleWordAtomic.
+/// All operations are sequentially-consistent.
+/// @note The cell only manages the pointer value, not the lifetime of the pointed object.
+/// @tparam T The pointed type.
+template<typename T>
+class TaggedPtrCell
+{
+    static_assert(sizeof(T *) == sizeof(uint64_t), "The TaggedPtrCell requires the 64-bit pointers.");
+
+    using Words = impl::DoubleWordAtomic::Words;
+
+public:
+    using value_type = TaggedPtr<T>;
+
+    explicit TaggedPtrCell(T *ptr = nullptr, uint64_t tag = 0) noexcept : value_(to_words({ptr, tag})) { }
+
+    TaggedPtrCell(const TaggedPtrCell &) = delete;
+
+    TaggedPtrCell &operator=(const TaggedPtrCell &) = delete;
+
+    /// @brief Returns true if the double-word CAS is supported natively on the target.
+    [[nodiscard]] static constexpr bool is_lock_free() noexcept
+    {
+        return impl::DoubleWordAtomic::IsLockFree;
+    }
+
+    /// @brief Loads the pointer and its tag.
+    [[nodiscard]] value_type load() const noexcept
+    {
+        return from_words(value_.load());
+    }
+
+    /// @brief Stores the 'ptr' and increases the tag.
+    /// @return The previous value.
+    value_type store(T *ptr) noexcept
+    {
+        auto current = load();
+        while (!compare_exchange(current, ptr)) { }
+        return current;
+    }
+
+    /// @brief Stores the 'desired' pointer with the increased tag if the current pointer and tag are
+    /// equal to 'expected'. Otherwise, the current value is loaded into 'expected'.
+    /// @return true if replaced.
+    bool compare_exchange(value_type &expected, T *desired) noexcept
+    {
+        auto words = to_words(expected);
+        if (value_.compare_exchange(words, to_words({desired, expected.tag + 1}))) {
+            return true;
+        }
+        expected = from_words(words);
+        return false;
+    }
+
+private:
+    static Words to_words(value_type v) noexcept
+    {
+        return Words{reinterpret_cast<uint64_t>(v.ptr), v.tag};
+    }
+
+    static value_type from_words(Words w) noexcept
+    {
+        return value_type{reinterpret_cast<T *>(w.lo), w.hi};
+    }
+
+    impl::DoubleWordAtomic value_;
+};
+
+}
+
+#endif //SYNC_CELL_TAGGED_PTR_CELL_HPP
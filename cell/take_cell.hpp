@@ -0,0 +1,111 @@
+///
+/// @file  take_cell.hpp
+/// @brief A thread-safe cell whose value can be taken out only once.
+///
+
+#ifndef SYNC_CELL_TAKE_CELL_HPP
+#define SYNC_CELL_TAKE_CELL_HPP
+
+#include <atomic>
+#include <cstdint>
+#include <optional>
+#include <type_traits>
+#include <utility>
+
+
+namespace sc {
+
+/// @brief A thread-safe cell whose value is moved out by the first @c take, and the later takes get
+/// an empty optional, e.g. to hand a socket or a file handle over to exactly one of the threads:
+/// ``` cpp
+/// sc::TakeCell<Socket> pending;
+/// pending.try_put(accept());
+/// // In any of the workers.
+/// if (auto socket = pending.take()) { serve(*std::move(socket)); }
+/// ```
+///
+/// The value is only moved, so it suits the move-only types. A cell is filled once, by the
+/// constructor or by @c try_put, and is never refilled after the take. The operations are
+/// lock-free: a take racing with the put which has not completed sees the cell empty.
+/// @tparam T The value type, which must be move constructible.
+template<typename T>
+class TakeCell
+{
+    static_assert(!std::is_reference_v<T> && std::is_move_constructible_v<T>);
+
+    enum State : uint8_t
+    {
+        Empty = 0,
+        Putting = 1,
+        Full = 2,
+        Taken = 3,
+    };
+
+public:
+    using value_type = T;
+
+    /// @brief Creates an empty cell, which can be filled by @c try_put.
+    constexpr TakeCell() noexcept = default;
+
+    /// @brief Creates a cell holding the 'value'.
+    explicit TakeCell(T value) noexcept(std::is_nothrow_move_constructible_v<T>)
+            : state_(Full), value_(std::move(value)) { }
+
+    TakeCell(const TakeCell &) = delete;
+
+    TakeCell &operator=(const TakeCell &) = delete;
+
+    /// @brief Fills the empty cell with the 'value'.
+    /// @return false if the cell has been filled (or taken) before, the 'value' is not moved then.
+    bool try_put(T &&value)
+    {
+        auto expected = Empty;
+        if (!state_.compare_exchange_strong(expected, Putting, std::memory_order_relaxed,
+                                            std::memory_order_relaxed)) {
+            return false;
+        }
+        try {
+            value_.emplace(std::move(value));
+        } catch (...) {
+            state_.store(Empty, std::memory_order_relaxed);
+            throw;
+        }
+        state_.store(Full, std::memory_order_release);
+        return true;
+    }
+
+    /// @brief Moves the value out of the cell, if it is the first take since the cell is filled.
+    /// @return The value, or an empty optional if the cell is empty or has been taken.
+    [[nodiscard]] std::optional<T> take() noexcept(std::is_nothrow_move_constructible_v<T>)
+    {
+        auto expected = Full;
+        if (state_.load(std::memory_order_relaxed) != Full ||
+            !state_.compare_exchange_strong(expected, Taken, std::memory_order_acquire,
+                                            std::memory_order_relaxed)) {
+            return {};
+        }
+        std::optional<T> value(std::move(value_));
+        value_.reset();
+        return value;
+    }
+
+    /// @brief Returns true if the cell holds a value which has not been taken.
+    [[nodiscard]] bool has_value() const noexcept
+    {
+        return state_.load(std::memory_order_acquire) == Full;
+    }
+
+    /// @brief Returns true if the value has been taken.
+    [[nodiscard]] bool is_taken() const noexcept
+    {
+        return state_.load(std::memory_order_acquire) == Taken;
+    }
+
+private:
+    std::atomic<State> state_{Empty};
+    std::optional<T> value_;
+};
+
+}
+
+#endif //SYNC_CELL_TAKE_CELL_HPP
@@ -0,0 +1,201 @@
+///
+/// @file  thread_sharded_cell.hpp
+/// @brief A per-object thread-local value, whose values of all threads can be visited. Inspired by
+/// [thread_local-rs](https://github.com/Amanieu/thread_local-rs).
+///
+
+#ifndef SYNC_CELL_THREAD_SHARDED_CELL_HPP
+#define SYNC_CELL_THREAD_SHARDED_CELL_HPP
+
+#include <array>
+#include <atomic>
+#include <bit>
+#include <cstddef>
+#include <optional>
+#include <type_traits>
+#include <utility>
+
+#include "util/cache_padded.hpp"
+#include "util/thread_id.hpp"
+
+
+namespace sc {
+
+/// @brief Gives each thread its own value of type 'T', which is created on the first access of the
+/// thread. Unlike the @c thread_local variables, each cell has its own set of values, and all values
+/// can be visited by @c for_each or @c fold, e.g. to sum the per-thread counters.
+///
+/// The slots are indexed by the @c util::thread_id, in the buckets of 1, 1, 2, 4, 8... slots which
+/// are allocated on demand, so the access is lock-free and never moves a value. Each slot is padded
+/// to a cache line, the threads do not false share their values.
+///
+/// When a thread exits, its value is kept in the cell, and given to the next thread reusing its id,
+/// so the accumulated values are not lost. The values are destroyed with the cell.
+///
+/// @example
+/// ``` cpp
+/// sc::ThreadShardedCell<std::atomic<uint64_t>> hits;
+/// // In the worker threads.
+/// hits.get_or_default().fetch_add(1, std::memory_order_relaxed);
+/// // Anywhere.
+/// auto total = hits.fold(uint64_t{0}, [](uint64_t sum, auto &v) { return sum + v.load(); });
+/// ```
+/// @note The visiting thread reads the values while their owners may be modifying them, so either
+/// 'T' is made of the atomics (e.g. the relaxed counters above), or the owners are synchronized with
+/// the visiting thread first (e.g. joined).
+/// @tparam T The value type.
+template<typename T>
+class ThreadShardedCell
+{
+    struct Slot
+    {
+        std::atomic<bool> present{false};
+        std::optional<T> value;
+    };
+
+    using PaddedSlot = util::CachePadded<Slot>;
+
+    /// @brief Enough buckets for any 'size_t' id.
+    static constexpr size_t BucketCount = sizeof(size_t) * 8 + 1;
+
+public:
+    using value_type = T;
+
+    constexpr ThreadShardedCell() noexcept = default;
+
+    ThreadShardedCell(const ThreadShardedCell &) = delete;
+
+    ThreadShardedCell &operator=(const ThreadShardedCell &) = delete;
+
+    ~ThreadShardedCell()
+    {
+        for (size_t b = 0; b < BucketCount; ++b) {
+            delete[] buckets_[b].load(std::memory_order_relaxed);
+        }
+    }
+
+    /// @brief Gets the pointer to the value of the current thread.
+    /// @return nullptr if the current thread has no value yet.
+    [[nodiscard]] value_type *get()
+    {
+        auto [bucket, index] = locate(util::thread_id());
+        auto *slots = buckets_[bucket].load(std::memory_order_acquire);
+        if (slots == nullptr || !slots[index]->present.load(std::memory_order_relaxed)) {
+            return nullptr;
+        }
+        return std::addressof(*slots[index]->value);
+    }
+
+    /// @brief Gets the value of the current thread, initializing it with 'f' if the thread has no
+    /// value yet. If 'f' throws an exception, the thread still has no value.
+    /// @param f The function with the signature of 'T()'.
+    template<typename F>
+    value_type &get_or_init(F &&f)
+    {
+        auto [bucket, index] = locate(util::thread_id());
+        auto &slot = *slot_of(bucket, index);
+        if (!slot.present.load(std::memory_order_relaxed)) {
+            // Constructs from the result of 'f' in place, so 'T' needs not be movable.
+            struct Init
+            {
+                F &f;
+
+                operator T() const { return f(); }
+            };
+            slot.value.emplace(Init{f});
+            // Publish the value to the visiting threads.
+            slot.present.store(true, std::memory_order_release);
+        }
+        return *slot.value;
+    }
+
+    /// @brief Gets the value of the current thread, creating a default value if the thread has no
+    /// value yet.
+    template<typename U = T, std::enable_if_t<std::is_default_constructible_v<U>, bool> = false>
+    value_type &get_or_default()
+    {
+        return get_or_init([] { return T(); });
+    }
+
+    /// @brief Calls 'f' with each value created by any thread, including the exited ones.
+    /// @param f The function with the signature of 'void(T &)'.
+    template<typename F>
+    void for_each(F &&f)
+    {
+        visit([&f](Slot &slot) { f(*slot.value); });
+    }
+
+    /// @brief Calls 'f' with each value created by any thread, including the exited ones.
+    /// @param f The function with the signature of 'void(const T &)'.
+    template<typename F>
+    void for_each(F &&f) const
+    {
+        visit([&f](const Slot &slot) { f(*slot.value); });
+    }
+
+    /// @brief Folds the values of all threads into one value.
+    /// @param init The initial value.
+    /// @param f The function with the signature of 'A(A, const T &)'.
+    template<typename A, typename F>
+    A fold(A init, F &&f) const
+    {
+        for_each([&init, &f](const value_type &v) { init = f(std::move(init), v); });
+        return init;
+    }
+
+private:
+    /// @brief The bucket 'b' holds the ids in [2^(b - 1), 2^b), and the bucket 0 holds the id 0.
+    static std::pair<size_t, size_t> locate(size_t id) noexcept
+    {
+        auto bucket = static_cast<size_t>(std::bit_width(id));
+        auto index = bucket == 0 ? 0 : id - (size_t{1} << (bucket - 1));
+        return {bucket, index};
+    }
+
+    static size_t bucket_size(size_t bucket) noexcept
+    {
+        return bucket == 0 ? 1 : size_t{1} << (bucket - 1);
+    }
+
+    /// @brief Returns the slot, allocates the bucket if it is not allocated yet.
+    PaddedSlot &slot_of(size_t bucket, size_t index)
+    {
+        auto *slots = buckets_[bucket].load(std::memory_order_acquire);
+        if (slots == nullptr) {
+            auto *new_slots = new PaddedSlot[bucket_size(bucket)];
+            if (buckets_[bucket].compare_exchange_strong(
+                    slots, new_slots,
+                    std::memory_order_acq_rel,
+                    std::memory_order_acquire)) {
+                slots = new_slots;
+            } else {
+                // Another thread with an id in the same bucket allocated it.
+                delete[] new_slots;
+            }
+        }
+        return slots[index];
+    }
+
+    template<typename F>
+    void visit(F &&f) const
+    {
+        for (size_t b = 0; b < BucketCount; ++b) {
+            auto *slots = buckets_[b].load(std::memory_order_acquire);
+            if (slots == nullptr) {
+                continue;
+            }
+            for (size_t i = 0; i < bucket_size(b); ++i) {
+                auto &slot = *slots[i];
+                if (slot.present.load(std::memory_order_acquire)) {
+                    f(slot);
+                }
+            }
+        }
+    }
+
+    std::array<std::atomic<PaddedSlot *>, BucketCount> buckets_{};
+};
+
+}
+
+#endif //SYNC_CELL_THREAD_SHARDED_CELL_HPP
@@ -0,0 +1,173 @@
+///
+/// @file  time_cell.hpp
+/// @brief The thread-safe cells of the durations and the time points.
+///
+
+#ifndef SYNC_CELL_TIME_CELL_HPP
+#define SYNC_CELL_TIME_CELL_HPP
+
+#include <chrono>
+
+#include "cell/sync_cell.hpp"
+
+
+namespace sc {
+
+/// @brief A thread-safe @c std::chrono::duration, stored as the tick count in a @c SyncCell, e.g.
+/// for the max latency updated by many threads.
+///
+/// @example
+/// ``` cpp
+/// sc::DurationCell<> max_latency;
+/// max_latency.fetch_max(task.sent.elapsed());
+/// ```
+/// @tparam Duration The duration type, whose @c rep must be an @c AtomicArith type.
+/// @tparam Backoff The backoff of the @c SyncCell.
+template<typename Duration = std::chrono::nanoseconds, typename Backoff = util::Backoff>
+class DurationCell
+{
+    using rep = typename Duration::rep;
+
+    static_assert(AtomicArith<rep>, "The tick count of the duration must be an arithmetic type.");
+
+public:
+    using value_type = Duration;
+
+    constexpr DurationCell() noexcept : ticks_(rep{}) { }
+
+    constexpr explicit DurationCell(Duration value) noexcept : ticks_(value.count()) { }
+
+    DurationCell(const DurationCell &) = delete;
+
+    DurationCell &operator=(const DurationCell &) = delete;
+
+    [[nodiscard]] bool is_lock_free() const noexcept
+    {
+        return ticks_.is_lock_free();
+    }
+
+    [[nodiscard]] Duration load() const
+    {
+        return Duration(ticks_.load());
+    }
+
+    void store(Duration value)
+    {
+        ticks_.store(value.count());
+    }
+
+    /// @return The previous duration.
+    Duration fetch_add(Duration value)
+    {
+        return Duration(ticks_.fetch_add(value.count()));
+    }
+
+    /// @return The previous duration.
+    Duration fetch_sub(Duration value)
+    {
+        return Duration(ticks_.fetch_sub(value.count()));
+    }
+
+    /// @brief Stores the shorter one of the current duration and the 'value'.
+    /// @return The previous duration.
+    Duration fetch_min(Duration value)
+    {
+        return Duration(ticks_.fetch_min(value.count()));
+    }
+
+    /// @brief Stores the longer one of the current duration and the 'value'.
+    /// @return The previous duration.
+    Duration fetch_max(Duration value)
+    {
+        return Duration(ticks_.fetch_max(value.count()));
+    }
+
+private:
+    SyncCell<rep, Backoff> ticks_;
+};
+
+/// @brief A thread-safe @c std::chrono::time_point of the 'Clock', stored as the tick count since
+/// the epoch of the clock in a @c SyncCell, e.g. for the last activity time of a connection.
+///
+/// A default constructed cell holds the epoch of the clock.
+/// @example
+/// ``` cpp
+/// sc::InstantCell<> last_seen;
+/// last_seen.store_now();
+/// // In the watchdog thread.
+/// if (last_seen.elapsed() > timeout) { close(); }
+/// ```
+/// @tparam Clock The clock type, e.g. @c std::chrono::system_clock for the wall time.
+/// @tparam Backoff The backoff of the @c SyncCell.
+template<typename Clock = std::chrono::steady_clock, typename Backoff = util::Backoff>
+class InstantCell
+{
+    using rep = typename Clock::duration::rep;
+
+    static_assert(AtomicArith<rep>, "The tick count of the clock must be an arithmetic type.");
+
+public:
+    using value_type = typename Clock::time_point;
+    using duration = typename Clock::duration;
+
+    constexpr InstantCell() noexcept : ticks_(rep{}) { }
+
+    constexpr explicit InstantCell(value_type value) noexcept : ticks_(value.time_since_epoch().count()) { }
+
+    InstantCell(const InstantCell &) = delete;
+
+    InstantCell &operator=(const InstantCell &) = delete;
+
+    [[nodiscard]] bool is_lock_free() const noexcept
+    {
+        return ticks_.is_lock_free();
+    }
+
+    [[nodiscard]] value_type load() const
+    {
+        return value_type(duration(ticks_.load()));
+    }
+
+    void store(value_type value)
+    {
+        ticks_.store(value.time_since_epoch().count());
+    }
+
+    /// @brief Stores the current time of the clock.
+    /// @return The stored time.
+    value_type store_now()
+    {
+        auto now = Clock::now();
+        store(now);
+        return now;
+    }
+
+    /// @brief Returns the time passed since the stored time point, which is negative if it is in
+    /// the future.
+    [[nodiscard]] duration elapsed() const
+    {
+        return Clock::now() - load();
+    }
+
+    /// @brief Stores the later one of the current time point and the 'value', so the cell keeps the
+    /// latest time stored by the threads.
+    /// @return The previous time point.
+    value_type fetch_max(value_type value)
+    {
+        return value_type(duration(ticks_.fetch_max(value.time_since_epoch().count())));
+    }
+
+    /// @brief Stores the current time of the clock if it is later than the stored one.
+    /// @return The previous time point.
+    value_type fetch_max_now()
+    {
+        return fetch_max(Clock::now());
+    }
+
+private:
+    SyncCell<rep, Backoff> ticks_;
+};
+
+}
+
+#endif //SYNC_CELL_TIME_CELL_HPP
@@ -0,0 +1,108 @@
+///
+/// @file  versioned_cell.hpp
+/// @brief A thread-safe cell whose value is paired with a revision increased by each store.
+///
+
+#ifndef SYNC_CELL_VERSIONED_CELL_HPP
+#define SYNC_CELL_VERSIONED_CELL_HPP
+
+#include <cstdint>
+#include <optional>
+#include <utility>
+
+#include "cell/sync_cell.hpp"
+
+
+namespace sc {
+
+/// @brief A value and its revision loaded together from a @c VersionedCell.
+template<typename T>
+struct Versioned
+{
+    T value;
+    uint64_t version;
+};
+
+/// @brief A thread-safe cell whose value has a revision, which starts at 0 and is increased by one
+/// by each store, e.g. for a config whose readers reload their caches when the revision changes:
+/// ``` cpp
+/// sc::VersionedCell<Config> config;
+/// // In a reader.
+/// if (config.version() != cached.version) { cached = config.load(); }
+/// // In an optimistic writer, which fails if another writer has stored in the meantime.
+/// auto [current, version] = config.load();
+/// config.store_if_version(version, patch(current));
+/// ```
+///
+/// The value and the revision are kept in one @c SyncCell, so a small trivially copyable value is
+/// still lock-free where the platform has the atomic operations on its size plus 8 bytes (e.g. the
+/// 16-byte CAS for a 64-bit value).
+/// @tparam T The value type. It must be copyable.
+/// @tparam Backoff The backoff of the @c SyncCell.
+template<typename T, typename Backoff = util::Backoff>
+class VersionedCell
+{
+public:
+    using value_type = T;
+
+    template<typename U = T, std::enable_if_t<std::is_default_constructible_v<U>, bool> = false>
+    constexpr VersionedCell() noexcept(std::is_nothrow_default_constructible_v<T>) : cell_(Versioned<T>{T(), 0}) { }
+
+    constexpr explicit VersionedCell(T value) noexcept(std::is_nothrow_move_constructible_v<T>)
+            : cell_(Versioned<T>{std::move(value), 0}) { }
+
+    VersionedCell(const VersionedCell &) = delete;
+
+    VersionedCell &operator=(const VersionedCell &) = delete;
+
+    [[nodiscard]] bool is_lock_free() const noexcept
+    {
+        return cell_.is_lock_free();
+    }
+
+    /// @brief Loads the value and its revision together.
+    [[nodiscard]] Versioned<T> load() const
+    {
+        return cell_.load();
+    }
+
+    /// @brief Loads the revision only, which is cheaper than @c load for a large value, e.g. to check
+    /// whether a cached copy is still the latest one.
+    [[nodiscard]] uint64_t version() const
+    {
+        return cell_.get(&Versioned<T>::version);
+    }
+
+    /// @brief Stores the 'value' with the next revision.
+    /// @return The new revision.
+    uint64_t store(T value)
+    {
+        auto previous = cell_.fetch_update(std::memory_order_seq_cst, std::memory_order_seq_cst,
+                                           [&](const Versioned<T> &current) {
+                                               return std::optional(Versioned<T>{value, current.version + 1});
+                                           });
+        return previous->version + 1;
+    }
+
+    /// @brief Stores the 'value' with the next revision if the current revision is 'expected', so a
+    /// read-modify-write based on the revision 'expected' is not lost.
+    /// @return true if the value is stored, otherwise another store has happened since 'expected'.
+    bool store_if_version(uint64_t expected, T value)
+    {
+        auto previous = cell_.fetch_update(std::memory_order_seq_cst, std::memory_order_seq_cst,
+                                           [&](const Versioned<T> &current) -> std::optional<Versioned<T>> {
+                                               if (current.version != expected) {
+                                                   return {};
+                                               }
+                                               return Versioned<T>{value, expected + 1};
+                                           });
+        return previous.has_value();
+    }
+
+private:
+    SyncCell<Versioned<T>, Backoff> cell_;
+};
+
+}
+
+#endif //SYNC_CELL_VERSIONED_CELL_HPP
@@ -0,0 +1,310 @@
+///
+/// @file  watch_cell.hpp
+/// @brief A cell holding the latest value sent by the writers, whose subscribers can wait for the
+/// changes by blocking or by a C++20 coroutine. Inspired by the watch channel of
+/// [tokio](https://docs.rs/tokio/latest/tokio/sync/watch/index.html).
+///
+
+#ifndef SYNC_CELL_WATCH_CELL_HPP
+#define SYNC_CELL_WATCH_CELL_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The watch_cell.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <atomic>
+#include <chrono>
+#include <condition_variable>
+#include <cstddef>
+#include <cstdint>
+#include <mutex>
+#include <utility>
+
+#include "lock/rw_spin_lock.hpp"
+#include "util/back_off.hpp"
+
+#if __cpp_impl_coroutine
+#include <coroutine>
+#endif
+
+
+namespace sc {
+
+/// @brief A cell broadcasting the latest value to any number of subscribers, e.g. the reloaded
+/// config. The writers @c send the new values, and each @c Subscriber remembers the version it
+/// has seen, so it can check or wait for a newer value. The intermediate values may be skipped by
+/// a slow subscriber, it always sees the latest one.
+///
+/// The value is guarded by a @c lock::RwSpinLock, which is held by the guard returned by
+/// @c borrow. So keep the borrows short, the writers wait for them.
+///
+/// @example
+/// ``` cpp
+/// sc::WatchCell<Config> config(load_config());
+/// // In the worker threads.
+/// auto sub = config.subscribe();
+/// while (running) {
+///     sub.wait_changed();
+///     apply(*sub.borrow_and_update());
+/// }
+/// // In the reloading thread.
+/// config.send(load_config());
+/// ```
+/// @note A suspended coroutine is resumed on the sending thread inside the @c send call. If the
+/// coroutine must run on a specific executor, reschedule it after the @c co_await returns.
+/// @tparam T The value type.
+/// @tparam Backoff The backoff when the value lock is held by another thread.
+template<typename T, typename Backoff = util::Backoff>
+class WatchCell
+{
+    using Lock = lock::RwSpinLock<T, Backoff>;
+
+#if __cpp_impl_coroutine
+    struct Waiter
+    {
+        std::coroutine_handle<> handle;
+        Waiter *next = nullptr;
+    };
+#endif
+
+public:
+    using value_type = T;
+    /// @brief The guard of a borrowed value, holding the shared lock of the value.
+    using Ref = typename Lock::ReadGuard;
+
+    class Subscriber;
+
+    template<typename... Args>
+    explicit WatchCell(Args &&... args) : value_(std::forward<Args>(args)...) { }
+
+    WatchCell(const WatchCell &) = delete;
+
+    WatchCell &operator=(const WatchCell &) = delete;
+
+    /// @brief Returns a new subscriber, which has seen the current value.
+    [[nodiscard]] Subscriber subscribe() const noexcept
+    {
+        return Subscriber(*this, version());
+    }
+
+    /// @brief Borrows the current value.
+    [[nodiscard]] Ref borrow() const noexcept
+    {
+        return value_.read();
+    }
+
+    /// @brief Returns the count of the sent values.
+    [[nodiscard]] uint64_t version() const noexcept
+    {
+        return version_.load(std::memory_order_acquire);
+    }
+
+    /// @brief Replaces the value, and wakes up the subscribers waiting for a change.
+    void send(value_type value)
+    {
+        send_modify([&value](value_type &v) { v = std::move(value); });
+    }
+
+    /// @brief Modifies the value in place by 'f' holding the exclusive lock, and wakes up the
+    /// subscribers waiting for a change.
+    /// @param f The function with the signature of 'void(T &)'.
+    template<typename F>
+    void send_modify(F &&f)
+    {
+        {
+            auto guard = value_.write();
+            f(*guard);
+            // Changed with the value under the lock, so a borrowed value matches the version.
+            version_.fetch_add(1, std::memory_order_release);
+        }
+
+        // Pairs with the 'waiters_' increment: either the waiter sees the new version before
+        // waiting, or we see the waiter.
+        std::atomic_thread_fence(std::memory_order_seq_cst);
+        if (waiters_.load(std::memory_order_relaxed) == 0) {
+            return;
+        }
+
+#if __cpp_impl_coroutine
+        Waiter *waiters;
+        {
+            std::lock_guard guard(mtx_);
+            waiters = std::exchange(head_, nullptr);
+            for (auto *w = waiters; w != nullptr; w = w->next) {
+                waiters_.fetch_sub(1, std::memory_order_relaxed);
+            }
+        }
+        cond_var_.notify_all();
+        while (waiters != nullptr) {
+            // The coroutine may destroy the waiter after resumed.
+            auto handle = waiters->handle;
+            waiters = waiters->next;
+            handle.resume();
+        }
+#else
+        { std::lock_guard guard(mtx_); }
+        cond_var_.notify_all();
+#endif
+    }
+
+private:
+    mutable Lock value_;
+    std::atomic<uint64_t> version_{0};
+
+    /// @brief Count of the threads and coroutines that are (going to be) waiting.
+    mutable std::atomic<size_t> waiters_{0};
+    mutable std::mutex mtx_;
+    mutable std::condition_variable cond_var_;
+#if __cpp_impl_coroutine
+    /// @brief The suspended coroutines, guarded by 'mtx_'.
+    mutable Waiter *head_ = nullptr;
+#endif
+};
+
+/// @brief A subscriber of a @c WatchCell, which remembers the last seen version. It is used by one
+/// thread at a time, copy it for another thread. The cell must outlive its subscribers.
+template<typename T, typename Backoff>
+class WatchCell<T, Backoff>::Subscriber
+{
+    friend class WatchCell;
+
+    Subscriber(const WatchCell &cell, uint64_t seen) noexcept : cell_(&cell), seen_(seen) { }
+
+public:
+#if __cpp_impl_coroutine
+    class ChangedAwaiter;
+#endif
+
+    /// @brief Returns true if a value has been sent since the last seen one.
+    [[nodiscard]] bool has_changed() const noexcept
+    {
+        return cell_->version() != seen_;
+    }
+
+    /// @brief Borrows the current value, without marking it as seen.
+    [[nodiscard]] Ref borrow() const noexcept
+    {
+        return cell_->borrow();
+    }
+
+    /// @brief Borrows the current value, and marks it as seen.
+    [[nodiscard]] Ref borrow_and_update() noexcept
+    {
+        auto guard = cell_->borrow();
+        seen_ = cell_->version();
+        return guard;
+    }
+
+    /// @brief Blocks the current thread until a value has been sent since the last seen one, and
+    /// marks the version as seen. Returns immediately if it has been sent already.
+    void wait_changed()
+    {
+        if (!has_changed()) {
+            std::unique_lock lock(cell_->mtx_);
+            cell_->waiters_.fetch_add(1, std::memory_order_seq_cst);
+            cell_->cond_var_.wait(lock, [this] { return has_changed(); });
+            cell_->waiters_.fetch_sub(1, std::memory_order_relaxed);
+        }
+        seen_ = cell_->version();
+    }
+
+    /// @brief Blocks the current thread until a value has been sent since the last seen one or the
+    /// 'timeout' duration has elapsed.
+    /// @return false if timeout, in this case, the seen version is not changed.
+    template<typename Rep, typename Period>
+    bool wait_changed_timeout(const std::chrono::duration<Rep, Period> &timeout)
+    {
+        return wait_changed_until(std::chrono::steady_clock::now() + timeout);
+    }
+
+    /// @brief Blocks the current thread until a value has been sent since the last seen one or the
+    /// 'deadline' has been reached.
+    /// @return false if timeout, in this case, the seen version is not changed.
+    template<typename Clock, typename Duration>
+    bool wait_changed_until(const std::chrono::time_point<Clock, Duration> &deadline)
+    {
+        if (!has_changed()) {
+            std::unique_lock lock(cell_->mtx_);
+            cell_->waiters_.fetch_add(1, std::memory_order_seq_cst);
+            auto ok = cell_->cond_var_.wait_until(lock, deadline, [this] { return has_changed(); });
+            cell_->waiters_.fetch_sub(1, std::memory_order_relaxed);
+            if (!ok) {
+                return false;
+            }
+        }
+        seen_ = cell_->version();
+        return true;
+    }
+
+#if __cpp_impl_coroutine
+    /// @brief Waits asynchronously until a value has been sent since the last seen one, and marks
+    /// the version as seen.
+    /// @example
+    /// ``` cpp
+    /// co_await sub.changed();
+    /// ```
+    [[nodiscard]] ChangedAwaiter changed() noexcept
+    {
+        return ChangedAwaiter(*this);
+    }
+#endif
+
+private:
+    const WatchCell *cell_;
+    uint64_t seen_;
+};
+
+#if __cpp_impl_coroutine
+
+/// @brief The awaitable object returned by @c WatchCell::Subscriber::changed.
+template<typename T, typename Backoff>
+class WatchCell<T, Backoff>::Subscriber::ChangedAwaiter
+{
+    friend class Subscriber;
+
+    explicit ChangedAwaiter(Subscriber &subscriber) noexcept : subscriber_(subscriber) { }
+
+public:
+    ChangedAwaiter(const ChangedAwaiter &) = delete;
+
+    ChangedAwaiter &operator=(const ChangedAwaiter &) = delete;
+
+    bool await_ready() const noexcept
+    {
+        return subscriber_.has_changed();
+    }
+
+    bool await_suspend(std::coroutine_handle<> handle) noexcept
+    {
+        auto &cell = *subscriber_.cell_;
+        waiter_.handle = handle;
+
+        std::lock_guard guard(cell.mtx_);
+        cell.waiters_.fetch_add(1, std::memory_order_seq_cst);
+        // Check again after registration to avoid missing the value sent concurrently.
+        if (subscriber_.has_changed()) {
+            cell.waiters_.fetch_sub(1, std::memory_order_relaxed);
+            return false;
+        }
+        waiter_.next = cell.head_;
+        cell.head_ = &waiter_;
+        return true;
+    }
+
+    void await_resume() noexcept
+    {
+        subscriber_.seen_ = subscriber_.cell_->version();
+    }
+
+private:
+    Subscriber &subscriber_;
+    Waiter waiter_;
+};
+
+#endif
+
+}
+
+#endif //SYNC_CELL_WATCH_CELL_HPP
@@ -0,0 +1,688 @@
+///
+/// @file  work_stealing_deque.hpp
+/// @brief A work-stealing deque. Original rust implement:
+/// [Worker and Stealer](https://github.com/crossbeam-rs/crossbeam/blob/master/crossbeam-deque/src/deque.rs).
+///
+
+#ifndef SYNC_CELL_WORK_STEALING_DEQUE_HPP
+#define SYNC_CELL_WORK_STEALING_DEQUE_HPP
+
+#include <algorithm>
+#include <atomic>
+#include <cstdint>
+#include <memory>
+#include <optional>
+#include <type_traits>
+
+#include "epoch/epoch.hpp"
+#include "shared/compiler_workaround.hpp"
+#include "shared/metrics.hpp"
+#include "shared/numa.hpp"
+#include "util/cache_padded.hpp"
+
+
+namespace sc::deque {
+
+/// @brief The flavor of a @c Worker: how the tasks are popped by the owner thread.
+enum class Flavor
+{
+    /// @brief The first-in first-out flavor.
+    Fifo,
+    /// @brief The last-in first-out flavor.
+    Lifo,
+};
+
+/// @brief The state of a steal operation.
+enum class StealState
+{
+    /// @brief The deque was empty at the time of stealing.
+    Empty,
+    /// @brief At least one task was successfully stolen.
+    Success,
+    /// @brief The steal operation needs to be retried.
+    Retry,
+};
+
+/// @brief Possible outcomes of a steal operation.
+/// @tparam T The task type.
+template<typename T>
+class Steal
+{
+public:
+    constexpr Steal() noexcept = default;
+
+    static constexpr Steal empty() noexcept
+    {
+        return {};
+    }
+
+    static constexpr Steal retry() noexcept
+    {
+        Steal s;
+        s.state_ = StealState::Retry;
+        return s;
+    }
+
+    static constexpr Steal success(T task) noexcept(std::is_nothrow_move_constructible_v<T>)
+    {
+        Steal s;
+        s.state_ = StealState::Success;
+        s.task_.emplace(std::move(task));
+        return s;
+    }
+
+    [[nodiscard]] constexpr StealState state() const noexcept
+    {
+        return state_;
+    }
+
+    [[nodiscard]] constexpr bool is_empty() const noexcept
+    {
+        return state_ == StealState::Empty;
+    }
+
+    [[nodiscard]] constexpr bool is_success() const noexcept
+    {
+        return state_ == StealState::Success;
+    }
+
+    [[nodiscard]] constexpr bool is_retry() const noexcept
+    {
+        return state_ == StealState::Retry;
+    }
+
+    /// @brief Returns the stolen task if the steal operation succeeded.
+    constexpr std::optional<T> success() &&
+    {
+        return std::move(task_);
+    }
+
+private:
+    StealState state_ = StealState::Empty;
+    std::optional<T> task_;
+};
+
+namespace impl {
+
+/// @brief Minimum buffer capacity.
+constexpr size_t MinCap = 64;
+
+/// @brief The default maximum count of the tasks moved by one batch steal.
+constexpr size_t MaxBatch = 32;
+
+/// @brief If a buffer of at least this size is retired, thread-local garbage is flushed so that it
+/// gets deallocated as soon as possible.
+constexpr size_t FlushThresholdBytes = 1 << 10;
+
+/// @brief A buffer that holds tasks in a worker queue.
+///
+/// The buffer does not own the tasks: the tasks are bitwise copied in and out, which is the reason
+/// that the deque requires a trivially copyable task type.
+template<typename T>
+class Buffer
+{
+public:
+    /// @param cap The buffer capacity. It must be a power of two.
+    /// @param node The NUMA node hint of the storage.
+    Buffer(size_t cap, int node)
+            : ptr_(static_cast<T *>(numa::allocate(cap * sizeof(T), alignof(T), node))), cap_(cap), node_(node) { }
+
+    Buffer(const Buffer &) = delete;
+
+    Buffer &operator=(const Buffer &) = delete;
+
+    ~Buffer()
+    {
+        numa::deallocate(ptr_, cap_ * sizeof(T), alignof(T), node_);
+    }
+
+    [[nodiscard]] size_t capacity() const noexcept
+    {
+        return cap_;
+    }
+
+    [[nodiscard]] int node() const noexcept
+    {
+        return node_;
+    }
+
+    /// @brief Writes a task into the specified index.
+    void write(int64_t index, const T &task) noexcept
+    {
+        std::construct_at(at(index), task);
+    }
+
+    /// @brief Reads a task from the specified index.
+    ///
+    /// This method might be concurrently called with another @c write at the same index, and the
+    /// result task is invalid in this case. The caller must discard the result in that case
+    /// (checked by the CAS operation on the index later).
+    [[nodiscard]] T read(int64_t index) const noexcept
+    {
+        return *at(index);
+    }
+
+private:
+    [[nodiscard]] T *at(int64_t index) const noexcept
+    {
+        // 'cap_' is always a power of two.
+        return ptr_ + ((size_t)index & (cap_ - 1));
+    }
+
+    T *ptr_;
+    size_t cap_;
+    int node_;
+};
+
+/// @brief Internal queue data shared between the worker and stealers.
+///
+/// The implementation is based on the following work:
+/// 1. [Chase and Lev. Dynamic circular work-stealing deque. SPAA 2005.](https://dl.acm.org/citation.cfm?id=1073974)
+/// 2. [Le, Pop, Cohen, and Nardelli. Correct and efficient work-stealing for weak memory models.
+///    PPoPP 2013.](https://dl.acm.org/citation.cfm?id=2442524)
+template<typename T>
+struct Inner
+{
+    /// @brief The front index.
+    util::CachePadded<std::atomic<int64_t>> front;
+    /// @brief The back index.
+    util::CachePadded<std::atomic<int64_t>> back;
+    /// @brief The underlying buffer.
+    /// The buffers replaced by a resize are destroyed by the epoch-based reclamation, as the
+    /// stealers may still read them.
+    util::CachePadded<std::atomic<Buffer<T> *>> buffer;
+    /// @brief The contention counters of the worker and all stealers.
+    SC_NO_UNIQUE_ADDRESS metrics::Counters counters;
+    /// @brief The flavor of the worker, which decides how a batch is stolen.
+    Flavor flavor;
+
+    Inner(Flavor f, int node) : flavor(f)
+    {
+        front->store(0, std::memory_order_relaxed);
+        back->store(0, std::memory_order_relaxed);
+        buffer->store(new Buffer<T>(MinCap, node), std::memory_order_relaxed);
+    }
+
+    Inner(const Inner &) = delete;
+
+    Inner &operator=(const Inner &) = delete;
+
+    ~Inner()
+    {
+        delete buffer->load(std::memory_order_relaxed);
+    }
+};
+
+}
+
+template<typename T, typename Reclaimer>
+class Stealer;
+
+/// @brief A worker queue.
+///
+/// This is a FIFO or LIFO queue that is owned by a single thread, but other threads may steal
+/// tasks from it. Task schedulers typically create a single worker queue per thread.
+///
+/// The @c push and @c pop methods can only be called by the owner thread, while any number of
+/// @c Stealer objects created by the @c stealer method can be shared among threads.
+///
+/// @note The deque reads the task from its buffer speculatively on stealing (the same as the
+/// original rust implement), so the task type is required to be trivially copyable. Use a pointer
+/// or an index as the task type if the task object is not trivially copyable.
+/// @tparam T The task type.
+/// @tparam Reclaimer The reclamation backend of the replaced buffers: @c sc::epoch::Reclaimer or
+/// @c sc::hazard::Reclaimer (include "hazard/hazard.hpp" to use it).
+template<typename T, typename Reclaimer = epoch::Reclaimer>
+class Worker
+{
+    static_assert(std::is_trivially_copyable_v<T>, "The task type must be trivially copyable.");
+
+    friend class Stealer<T, Reclaimer>;
+
+    using Inner = impl::Inner<T>;
+    using Buffer = impl::Buffer<T>;
+
+public:
+    using value_type = T;
+
+    /// @brief Creates a worker queue.
+    /// @param flavor The order of tasks popped by the @c pop method. Tasks are always stolen from
+    /// the front of the queue.
+    /// @param node The NUMA node hint of the buffer, e.g. the node of the owner thread. It is kept
+    /// by the resized buffers, and ignored without @c SC_HAS_NUMA (see "shared/numa.hpp").
+    explicit Worker(Flavor flavor = Flavor::Lifo, int node = numa::AnyNode)
+            : inner_(std::make_shared<Inner>(flavor, node)),
+              buffer_(inner_->buffer->load(std::memory_order_relaxed)),
+              flavor_(flavor) { }
+
+    Worker(const Worker &) = delete;
+
+    Worker &operator=(const Worker &) = delete;
+
+    Worker(Worker &&) noexcept = default;
+
+    Worker &operator=(Worker &&) noexcept = default;
+
+    /// @brief Creates a stealer for this queue. The returned stealer can be shared among threads
+    /// and cloned.
+    [[nodiscard]] Stealer<T, Reclaimer> stealer() const
+    {
+        return Stealer<T, Reclaimer>(inner_);
+    }
+
+    [[nodiscard]] Flavor flavor() const noexcept
+    {
+        return flavor_;
+    }
+
+    [[nodiscard]] bool is_empty() const noexcept
+    {
+        auto b = inner_->back->load(std::memory_order_relaxed);
+        auto f = inner_->front->load(std::memory_order_seq_cst);
+        return b - f <= 0;
+    }
+
+    /// @brief Returns the contention counters shared with the stealers: the steals which must be
+    /// retried as the failed steals, and the last task taken by a stealer while popping it as the
+    /// CAS failures. Requires @c SYNC_CELL_METRICS.
+    [[nodiscard]] metrics::Stats stats() const noexcept
+    {
+        return inner_->counters.snapshot();
+    }
+
+    /// @brief Returns the number of tasks in the deque.
+    [[nodiscard]] size_t len() const noexcept
+    {
+        auto b = inner_->back->load(std::memory_order_relaxed);
+        auto f = inner_->front->load(std::memory_order_seq_cst);
+        return b - f > 0 ? (size_t)(b - f) : 0;
+    }
+
+    /// @brief Pushes a task into the queue.
+    void push(const value_type &task)
+    {
+        auto b = inner_->back->load(std::memory_order_relaxed);
+        auto f = inner_->front->load(std::memory_order_acquire);
+
+        // Is the queue full?
+        if (b - f >= (int64_t)buffer_->capacity()) {
+            // Yes. Grow the underlying buffer.
+            resize(2 * buffer_->capacity());
+        }
+
+        // Write 'task' into the slot.
+        buffer_->write(b, task);
+
+        std::atomic_thread_fence(std::memory_order_release);
+
+        // Increment the back index.
+        //
+        // This ordering could be 'relaxed', but then thread sanitizer would falsely report data
+        // races because it doesn't support fences.
+        inner_->back->store(b + 1, std::memory_order_release);
+    }
+
+    /// @brief Pops a task from the queue.
+    std::optional<value_type> pop()
+    {
+        auto b = inner_->back->load(std::memory_order_relaxed);
+        auto f = inner_->front->load(std::memory_order_relaxed);
+
+        // Calculate the length of the queue.
+        auto len = b - f;
+
+        // Is the queue empty?
+        if (len <= 0) {
+            return {};
+        }
+
+        if (flavor_ == Flavor::Fifo) {
+            // Try incrementing the front index to pop the task.
+            f = inner_->front->fetch_add(1, std::memory_order_seq_cst);
+            auto new_f = f + 1;
+
+            if (b - new_f < 0) {
+                // We overshot the back index and must restore the front index.
+                inner_->front->store(f, std::memory_order_relaxed);
+                return {};
+            }
+
+            // Read the task to be popped.
+            auto task = buffer_->read(f);
+
+            // Shrink the buffer if 'len - 1' is less than one fourth of the capacity.
+            if (buffer_->capacity() > impl::MinCap && len <= (int64_t)buffer_->capacity() / 4) {
+                resize(buffer_->capacity() / 2);
+            }
+
+            return task;
+        }
+
+        // Decrement the back index.
+        b -= 1;
+        inner_->back->store(b, std::memory_order_relaxed);
+
+        std::atomic_thread_fence(std::memory_order_seq_cst);
+
+        // Load the front index.
+        f = inner_->front->load(std::memory_order_relaxed);
+
+        // Compute the length after the back index was decremented.
+        len = b - f;
+
+        if (len < 0) {
+            // The queue is empty. Restore the back index to the original task.
+            inner_->back->store(b + 1, std::memory_order_relaxed);
+            return {};
+        }
+
+        // Read the task to be popped.
+        std::optional<value_type> task(buffer_->read(b));
+
+        // Are we popping the last task from the queue?
+        if (len == 0) {
+            // Try incrementing the front index.
+            if (!inner_->front->compare_exchange_strong(
+                    f, f + 1,
+                    std::memory_order_seq_cst,
+                    std::memory_order_relaxed)) {
+                // Failed. We didn't pop anything.
+                inner_->counters.cas_failure();
+                task.reset();
+            }
+
+            // Restore the back index to the original task.
+            inner_->back->store(b + 1, std::memory_order_relaxed);
+        } else if (buffer_->capacity() > impl::MinCap && len < (int64_t)buffer_->capacity() / 4) {
+            // Shrink the buffer if 'len' is less than one fourth of the capacity.
+            resize(buffer_->capacity() / 2);
+        }
+
+        return task;
+    }
+
+private:
+    /// @brief Grows the buffer, so at least 'reserve_cap' more tasks can be pushed without a resize.
+    void reserve(size_t reserve_cap)
+    {
+        auto b = inner_->back->load(std::memory_order_relaxed);
+        auto f = inner_->front->load(std::memory_order_seq_cst);
+        auto len = static_cast<size_t>(std::max<int64_t>(b - f, 0));
+        auto cap = buffer_->capacity();
+        if (cap - len < reserve_cap) {
+            auto new_cap = cap * 2;
+            while (new_cap - len < reserve_cap) {
+                new_cap *= 2;
+            }
+            resize(new_cap);
+        }
+    }
+
+    /// @brief Reverses the 'count' tasks before the index 'b', which are not published yet.
+    void reverse_unpublished(int64_t b, int64_t count) noexcept
+    {
+        for (int64_t i = 0; i < count / 2; ++i) {
+            auto first = buffer_->read(b - count + i);
+            auto last = buffer_->read(b - i - 1);
+            buffer_->write(b - count + i, last);
+            buffer_->write(b - i - 1, first);
+        }
+    }
+
+    /// @brief Publishes the tasks written up to the index 'b' by a batch steal.
+    void publish(int64_t b) noexcept
+    {
+        std::atomic_thread_fence(std::memory_order_release);
+        inner_->back->store(b, std::memory_order_release);
+    }
+
+    /// @brief Resizes the internal buffer to the new capacity.
+    void resize(size_t new_cap)
+    {
+        // Load the back index, front index, and buffer.
+        auto b = inner_->back->load(std::memory_order_relaxed);
+        auto f = inner_->front->load(std::memory_order_relaxed);
+        auto *old = buffer_;
+
+        // Allocate a new buffer and copy data from the old buffer to the new one.
+        auto *new_buffer = new Buffer(new_cap, old->node());
+        for (auto i = f; i != b; ++i) {
+            new_buffer->write(i, old->read(i));
+        }
+
+        auto guard = Reclaimer::guard();
+
+        // Replace the old buffer with the new one. The old buffer may still be read by stealers,
+        // so destroy it later.
+        buffer_ = new_buffer;
+        inner_->buffer->store(new_buffer, std::memory_order_release);
+        guard.retire(old);
+
+        // If the buffer is very large, then flush the thread-local garbage in order to deallocate
+        // it as soon as possible.
+        if (sizeof(T) * new_cap >= impl::FlushThresholdBytes) {
+            guard.flush();
+        }
+    }
+
+    std::shared_ptr<Inner> inner_;
+    /// @brief A copy of 'inner_->buffer' for quick access.
+    Buffer *buffer_;
+    Flavor flavor_;
+};
+
+/// @brief A stealer handle of a worker queue.
+///
+/// Stealers can be shared among threads. Task schedulers typically have a single worker queue per
+/// worker thread.
+/// @tparam T The task type.
+/// @tparam Reclaimer The reclamation backend, which is the same as the one of the @c Worker.
+template<typename T, typename Reclaimer = epoch::Reclaimer>
+class Stealer
+{
+    friend class Worker<T, Reclaimer>;
+
+    using Inner = impl::Inner<T>;
+
+    explicit Stealer(std::shared_ptr<Inner> inner) noexcept: inner_(std::move(inner)) { }
+
+public:
+    using value_type = T;
+
+    [[nodiscard]] bool is_empty() const noexcept
+    {
+        auto f = inner_->front->load(std::memory_order_acquire);
+        std::atomic_thread_fence(std::memory_order_seq_cst);
+        auto b = inner_->back->load(std::memory_order_acquire);
+        return b - f <= 0;
+    }
+
+    /// @brief Returns the number of tasks in the deque.
+    [[nodiscard]] size_t len() const noexcept
+    {
+        auto f = inner_->front->load(std::memory_order_acquire);
+        std::atomic_thread_fence(std::memory_order_seq_cst);
+        auto b = inner_->back->load(std::memory_order_acquire);
+        return b - f > 0 ? (size_t)(b - f) : 0;
+    }
+
+    /// @brief Returns the contention counters shared with the worker, see @c Worker::stats.
+    [[nodiscard]] metrics::Stats stats() const noexcept
+    {
+        return inner_->counters.snapshot();
+    }
+
+    /// @brief Steals a task from the queue.
+    Steal<value_type> steal() const
+    {
+        // Load the front index.
+        auto f = inner_->front->load(std::memory_order_acquire);
+
+        // A SeqCst fence is needed here, the creation of the guard acts as the fence.
+        auto guard = Reclaimer::guard();
+
+        // Load the back index.
+        auto b = inner_->back->load(std::memory_order_acquire);
+
+        // Is the queue empty?
+        if (b - f <= 0) {
+            return Steal<value_type>::empty();
+        }
+
+        // Load the buffer and read the task at the front.
+        auto *buffer = guard.protect(*inner_->buffer);
+        auto task = buffer->read(f);
+
+        // Try incrementing the front index to steal the task.
+        // If the buffer has been swapped or the increment fails, we retry.
+        if (inner_->buffer->load(std::memory_order_acquire) != buffer ||
+            !inner_->front->compare_exchange_strong(
+                    f, f + 1,
+                    std::memory_order_seq_cst,
+                    std::memory_order_relaxed)) {
+            // We didn't steal this task, forget it.
+            inner_->counters.failed_steal();
+            return Steal<value_type>::retry();
+        }
+
+        // Return the stolen task.
+        return Steal<value_type>::success(task);
+    }
+
+    /// @brief Steals about a half of the tasks (at most 'limit') and pushes them into the worker
+    /// queue 'dest', which must be owned by the calling thread. So a thief needs one steal for many
+    /// tasks, instead of contending on the front index for each task with the other thieves.
+    /// @return The count of the stolen tasks on success.
+    Steal<size_t> steal_batch(Worker<T, Reclaimer> &dest, size_t limit = impl::MaxBatch) const
+    {
+        auto stolen = steal_into(dest, limit, false);
+        if (stolen.state != StealState::Success) {
+            return stolen.state == StealState::Empty ? Steal<size_t>::empty() : Steal<size_t>::retry();
+        }
+        return Steal<size_t>::success(stolen.count);
+    }
+
+    /// @brief Steals about a half of the tasks (at most 'limit' plus one), pushes them into the
+    /// worker queue 'dest' except the first one, which is returned. The 'dest' must be owned by the
+    /// calling thread. A scheduler calls it when its own queue is empty: the returned task runs at
+    /// once, and the others are popped locally after.
+    Steal<value_type> steal_batch_and_pop(Worker<T, Reclaimer> &dest, size_t limit = impl::MaxBatch) const
+    {
+        auto stolen = steal_into(dest, limit + 1, true);
+        if (stolen.state != StealState::Success) {
+            return stolen.state == StealState::Empty ? Steal<value_type>::empty() : Steal<value_type>::retry();
+        }
+        return Steal<value_type>::success(*stolen.first);
+    }
+
+private:
+    struct Batch
+    {
+        StealState state = StealState::Empty;
+        size_t count = 0;
+        std::optional<value_type> first;
+    };
+
+    /// @brief Steals at most 'limit' tasks into 'dest', keeping the first one out if 'pop_first'.
+    Batch steal_into(Worker<T, Reclaimer> &dest, size_t limit, bool pop_first) const
+    {
+        Batch batch;
+        if (limit == 0 || dest.inner_ == inner_) {
+            return batch;
+        }
+
+        // Load the front index.
+        auto f = inner_->front->load(std::memory_order_acquire);
+
+        // A SeqCst fence is needed here, the creation of the guard acts as the fence.
+        auto guard = Reclaimer::guard();
+
+        // Load the back index.
+        auto b = inner_->back->load(std::memory_order_acquire);
+
+        // Is the queue empty?
+        auto len = b - f;
+        if (len <= 0) {
+            return batch;
+        }
+
+        // Steal a half of the tasks, but no more than the limit.
+        auto size = static_cast<int64_t>(std::min<size_t>(static_cast<size_t>(len + 1) / 2, limit));
+        auto skip = pop_first ? 1 : 0;
+        dest.reserve(static_cast<size_t>(size - skip));
+        auto dest_b = dest.inner_->back->load(std::memory_order_relaxed);
+
+        auto *buffer = guard.protect(*inner_->buffer);
+        if (inner_->flavor == Flavor::Fifo) {
+            // The worker pops from the front too, so all tasks are claimed by one CAS.
+            if (pop_first) {
+                batch.first = buffer->read(f);
+            }
+            for (int64_t i = skip; i < size; ++i) {
+                dest.buffer_->write(dest_b + i - skip, buffer->read(f + i));
+            }
+            if (inner_->buffer->load(std::memory_order_acquire) != buffer ||
+                !inner_->front->compare_exchange_strong(
+                        f, f + size,
+                        std::memory_order_seq_cst,
+                        std::memory_order_relaxed)) {
+                inner_->counters.failed_steal();
+                batch.state = StealState::Retry;
+                return batch;
+            }
+            dest_b += size - skip;
+            // The oldest stolen task must be popped first by a LIFO destination.
+            if (dest.flavor_ == Flavor::Lifo) {
+                dest.reverse_unpublished(dest_b, size - skip);
+            }
+        } else {
+            // The worker pops from the back, which may meet the thief, so the tasks are claimed one
+            // by one, and the batch stops at the first lost race.
+            int64_t taken = 0;
+            for (; taken < size; ++taken) {
+                if (taken > 0) {
+                    std::atomic_thread_fence(std::memory_order_seq_cst);
+                    if (inner_->back->load(std::memory_order_acquire) - f <= 0) {
+                        break;
+                    }
+                }
+                auto task = buffer->read(f);
+                if (inner_->buffer->load(std::memory_order_acquire) != buffer ||
+                    !inner_->front->compare_exchange_strong(
+                            f, f + 1,
+                            std::memory_order_seq_cst,
+                            std::memory_order_relaxed)) {
+                    inner_->counters.failed_steal();
+                    break;
+                }
+                if (pop_first && taken == 0) {
+                    batch.first = task;
+                } else {
+                    dest.buffer_->write(dest_b, task);
+                    ++dest_b;
+                }
+                ++f;
+            }
+            if (taken == 0) {
+                batch.state = StealState::Retry;
+                return batch;
+            }
+            size = taken;
+            // A FIFO destination pops the newest stolen task first, the order of the LIFO source.
+            if (dest.flavor_ == Flavor::Fifo) {
+                dest.reverse_unpublished(dest_b, size - skip);
+            }
+        }
+
+        dest.publish(dest_b);
+        batch.state = StealState::Success;
+        batch.count = static_cast<size_t>(size);
+        return batch;
+    }
+
+    std::shared_ptr<Inner> inner_;
+};
+
+}
+
+#endif //SYNC_CELL_WORK_STEALING_DEQUE_HPP
@@ -0,0 +1,455 @@
+///
+/// @file  epoch.hpp
+/// @brief Epoch-based memory reclamation. Inspired by
+/// [crossbeam-epoch](https://github.com/crossbeam-rs/crossbeam/tree/master/crossbeam-epoch).
+///
+
+#ifndef SYNC_CELL_EPOCH_HPP
+#define SYNC_CELL_EPOCH_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The epoch.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <atomic>
+#include <cstdint>
+#include <memory>
+#include <mutex>
+#include <type_traits>
+#include <utility>
+#include <vector>
+
+#include "util/cache_padded.hpp"
+
+
+namespace sc::epoch {
+
+namespace impl {
+
+/// @brief Number of pinnings after which a participant will execute some deferred functions from
+/// the global garbage.
+constexpr size_t PinningsBetweenCollect = 128;
+
+/// @brief Maximum number of deferred functions in a thread-local bag.
+constexpr size_t MaxBagSize = 64;
+
+/// @brief The epoch is increased by two on each advance, the lowest bit of a participant epoch
+/// marks whether the participant is pinned.
+constexpr size_t EpochStep = 2;
+
+/// @brief A deferred function which is called after no thread can access the data anymore.
+struct Deferred
+{
+    void (*call)(void *) noexcept;
+    void *data;
+
+    void operator()() const noexcept
+    {
+        call(data);
+    }
+};
+
+/// @brief A bag of deferred functions sealed with the global epoch at the time of sealing.
+struct SealedBag
+{
+    size_t epoch;
+    std::vector<Deferred> items;
+
+    /// @brief Two advances after the sealing, all threads pinned before the sealing have been
+    /// unpinned, so nobody can access the data in the bag.
+    [[nodiscard]] bool is_expired(size_t global_epoch) const noexcept
+    {
+        return global_epoch - epoch >= 2 * EpochStep;
+    }
+};
+
+/// @brief A participant of the garbage collection, which is the record of one thread.
+struct Local
+{
+    /// @brief The pinned global epoch with the lowest bit set, or 0 if the thread is not pinned.
+    util::CachePadded<std::atomic<size_t>> epoch;
+    /// @brief Whether the record is owned by a thread. The records are reused by the new threads
+    /// after the old ones exit, and released when the global data is destroyed.
+    std::atomic<bool> in_use{true};
+    Local *next = nullptr;
+
+    Local()
+    {
+        epoch->store(0, std::memory_order_relaxed);
+    }
+};
+
+/// @brief The global data of the garbage collection.
+class Global
+{
+public:
+    Global() = default;
+
+    Global(const Global &) = delete;
+
+    Global &operator=(const Global &) = delete;
+
+    ~Global()
+    {
+        // No thread is running now.
+        for (auto &bag : garbage_) {
+            for (auto &d : bag.items) {
+                d();
+            }
+        }
+
+        auto *local = head_.load(std::memory_order_relaxed);
+        while (local != nullptr) {
+            delete std::exchange(local, local->next);
+        }
+    }
+
+    /// @brief Registers a new participant, reusing a released record if possible.
+    Local *acquire_local()
+    {
+        for (auto *local = head_.load(std::memory_order_acquire); local != nullptr; local = local->next) {
+            bool in_use = false;
+            if (!local->in_use.load(std::memory_order_relaxed) &&
+                local->in_use.compare_exchange_strong(in_use, true, std::memory_order_acquire)) {
+                return local;
+            }
+        }
+
+        auto *local = new Local();
+        local->next = head_.load(std::memory_order_relaxed);
+        while (!head_.compare_exchange_weak(
+                local->next, local,
+                std::memory_order_release,
+                std::memory_order_relaxed)) { }
+        return local;
+    }
+
+    void release_local(Local *local) noexcept
+    {
+        local->in_use.store(false, std::memory_order_release);
+    }
+
+    [[nodiscard]] size_t epoch() const noexcept
+    {
+        return epoch_->load(std::memory_order_relaxed);
+    }
+
+    /// @brief Seals the deferred functions with the current global epoch and moves them to the
+    /// global garbage.
+    void push_bag(std::vector<Deferred> &&items)
+    {
+        // The data in the bag have been unlinked before this fence, so any thread which pins
+        // after the epoch loaded below can not access them.
+        std::atomic_thread_fence(std::memory_order_seq_cst);
+        SealedBag bag{epoch_->load(std::memory_order_relaxed), std::move(items)};
+
+        std::lock_guard guard(garbage_mtx_);
+        garbage_.push_back(std::move(bag));
+    }
+
+    /// @brief Tries to advance the global epoch and call the expired deferred functions.
+    void collect()
+    {
+        auto global_epoch = try_advance();
+
+        std::vector<SealedBag> expired;
+        {
+            std::unique_lock lock(garbage_mtx_, std::try_to_lock);
+            if (!lock.owns_lock()) {
+                // Another thread is collecting.
+                return;
+            }
+
+            for (auto it = garbage_.begin(); it != garbage_.end();) {
+                if (it->is_expired(global_epoch)) {
+                    expired.push_back(std::move(*it));
+                    it = garbage_.erase(it);
+                } else {
+                    ++it;
+                }
+            }
+        }
+
+        for (auto &bag : expired) {
+            for (auto &d : bag.items) {
+                d();
+            }
+        }
+    }
+
+private:
+    /// @brief Advances the global epoch if all pinned participants have been pinned in the
+    /// current epoch.
+    /// @return The current global epoch.
+    size_t try_advance() noexcept
+    {
+        // Pairs with the release CAS below: the accesses of the participants checked by the thread
+        // which advanced the epoch happen before the destruction by the current thread.
+        auto global_epoch = epoch_->load(std::memory_order_acquire);
+        std::atomic_thread_fence(std::memory_order_seq_cst);
+
+        for (auto *local = head_.load(std::memory_order_acquire); local != nullptr; local = local->next) {
+            // Pairs with the release stores of the participant epoch: the accesses in the previous
+            // pinned sections happen before the destruction.
+            auto local_epoch = local->epoch->load(std::memory_order_acquire);
+            if ((local_epoch & 1) != 0 && (local_epoch & ~size_t(1)) != global_epoch) {
+                // A participant is pinned in the previous epoch.
+                return global_epoch;
+            }
+        }
+
+        auto new_epoch = global_epoch + EpochStep;
+        if (epoch_->compare_exchange_strong(
+                global_epoch, new_epoch,
+                std::memory_order_release,
+                std::memory_order_relaxed)) {
+            return new_epoch;
+        }
+
+        // Another thread has advanced the epoch.
+        return global_epoch;
+    }
+
+    util::CachePadded<std::atomic<size_t>> epoch_{0};
+    /// @brief The list of all participants. A record is never removed before the destruction.
+    std::atomic<Local *> head_{nullptr};
+
+    std::mutex garbage_mtx_;
+    std::vector<SealedBag> garbage_;
+};
+
+inline Global &global() noexcept
+{
+    static Global g;
+    return g;
+}
+
+/// @brief The thread-local handle of a participant.
+class Handle
+{
+public:
+    Handle() : global_(global()), local_(global_.acquire_local()) { }
+
+    Handle(const Handle &) = delete;
+
+    Handle &operator=(const Handle &) = delete;
+
+    ~Handle()
+    {
+        if (!bag_.empty()) {
+            global_.push_bag(std::move(bag_));
+        }
+        global_.release_local(local_);
+    }
+
+    [[nodiscard]] bool is_pinned() const noexcept
+    {
+        return guard_count_ > 0;
+    }
+
+    void pin()
+    {
+        if (guard_count_++ == 0) {
+            auto global_epoch = global_.epoch();
+            local_->epoch->store(global_epoch | 1, std::memory_order_release);
+
+            // The pinned epoch must be visible before any shared data is loaded.
+            std::atomic_thread_fence(std::memory_order_seq_cst);
+
+            if (++pin_count_ % PinningsBetweenCollect == 0) {
+                global_.collect();
+            }
+        }
+    }
+
+    void unpin() noexcept
+    {
+        if (--guard_count_ == 0) {
+            local_->epoch->store(0, std::memory_order_release);
+        }
+    }
+
+    void defer(Deferred d)
+    {
+        bag_.push_back(d);
+        if (bag_.size() >= MaxBagSize) {
+            global_.push_bag(std::exchange(bag_, {}));
+        }
+    }
+
+    void flush()
+    {
+        if (!bag_.empty()) {
+            global_.push_bag(std::exchange(bag_, {}));
+        }
+        global_.collect();
+    }
+
+private:
+    Global &global_;
+    Local *local_;
+    size_t guard_count_ = 0;
+    size_t pin_count_ = 0;
+    std::vector<Deferred> bag_;
+};
+
+inline Handle &handle()
+{
+    thread_local Handle h;
+    return h;
+}
+
+}
+
+/// @brief A guard that keeps the current thread pinned.
+///
+/// While a thread is pinned, the data retired by @c defer_destroy (by any thread) after the
+/// pinning are not destroyed, so it is safe to access the shared data loaded in the pinned
+/// section. The guards can be nested: the thread is unpinned when the last guard is destroyed.
+///
+/// @note A guard can only be used by the thread which created it.
+class Guard
+{
+    friend Guard pin();
+
+    Guard() : handle_(&impl::handle())
+    {
+        handle_->pin();
+    }
+
+public:
+    Guard(Guard &&other) noexcept : handle_(std::exchange(other.handle_, nullptr)) { }
+
+    Guard(const Guard &) = delete;
+
+    Guard &operator=(const Guard &) = delete;
+
+    Guard &operator=(Guard &&) = delete;
+
+    ~Guard()
+    {
+        if (handle_ != nullptr) {
+            handle_->unpin();
+        }
+    }
+
+    /// @brief Destroys the object with @c delete after all threads pinned at the moment have been
+    /// unpinned.
+    ///
+    /// The object must have been unlinked from the shared data, so that no thread pinned after
+    /// this call can get it.
+    template<typename T>
+    void defer_destroy(T *ptr) const
+    {
+        static_assert(!std::is_void_v<T>);
+        handle_->defer(impl::Deferred{
+                [](void *p) noexcept { delete static_cast<T *>(p); },
+                const_cast<std::remove_cv_t<T> *>(ptr)});
+    }
+
+    /// @brief Destroys the object with the 'deleter' instead of @c delete, as @c defer_destroy, e.g.
+    /// for an object from a custom allocator.
+    template<typename T>
+    void defer_destroy(T *ptr, void (*deleter)(void *) noexcept) const
+    {
+        static_assert(!std::is_void_v<T>);
+        handle_->defer(impl::Deferred{deleter, const_cast<std::remove_cv_t<T> *>(ptr)});
+    }
+
+    /// @brief Calls the function after all threads pinned at the moment have been unpinned.
+    /// @param f The function with the signature of 'void()'. It must not throw.
+    template<typename F>
+    void defer(F &&f) const
+    {
+        using Func = std::decay_t<F>;
+        handle_->defer(impl::Deferred{
+                [](void *p) noexcept {
+                    std::unique_ptr<Func> func(static_cast<Func *>(p));
+                    (*func)();
+                },
+                new Func(std::forward<F>(f))});
+    }
+
+    /// @brief Moves the deferred functions of the current thread to the global garbage, and tries
+    /// to call the expired ones.
+    void flush() const
+    {
+        handle_->flush();
+    }
+
+private:
+    impl::Handle *handle_;
+};
+
+/// @brief Pins the current thread.
+[[nodiscard]] inline Guard pin()
+{
+    return Guard();
+}
+
+/// @brief Returns true if the current thread is pinned.
+[[nodiscard]] inline bool is_pinned()
+{
+    return impl::handle().is_pinned();
+}
+
+/// @brief The epoch-based reclamation backend of the lock-free containers.
+///
+/// A reclamation backend provides a static @c guard method which creates a guard and acts as a
+/// sequentially consistent fence. The guard can @c protect a pointer loaded from an atomic, and
+/// @c retire an unlinked object, which is destroyed by @c delete (or the given deleter) after no
+/// guard can access it. See also @c sc::hazard::Reclaimer.
+struct Reclaimer
+{
+    class Guard
+    {
+        friend struct Reclaimer;
+
+        explicit Guard(epoch::Guard &&guard) noexcept : guard_(std::move(guard)) { }
+
+    public:
+        /// @brief Loads the pointer from 'src'. It keeps valid until the guard is destroyed.
+        template<typename T>
+        T *protect(const std::atomic<T *> &src) const noexcept
+        {
+            return src.load(std::memory_order_acquire);
+        }
+
+        template<typename T>
+        void retire(T *ptr) const
+        {
+            guard_.defer_destroy(ptr);
+        }
+
+        /// @brief Retires an object destroyed by the 'deleter' which takes the 'ptr'.
+        template<typename T>
+        void retire(T *ptr, void (*deleter)(void *) noexcept) const
+        {
+            guard_.defer_destroy(ptr, deleter);
+        }
+
+        void flush() const
+        {
+            guard_.flush();
+        }
+
+    private:
+        epoch::Guard guard_;
+    };
+
+    static Guard guard()
+    {
+        // Pinning issues a SeqCst fence only if the current thread is not pinned yet.
+        if (is_pinned()) {
+            std::atomic_thread_fence(std::memory_order_seq_cst);
+        }
+
+        return Guard(pin());
+    }
+};
+
+}
+
+#endif //SYNC_CELL_EPOCH_HPP
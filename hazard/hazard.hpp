@@ -0,0 +1,385 @@
+///
+/// @file  hazard.hpp
+/// @brief Hazard pointer based memory reclamation.
+///
+
+#ifndef SYNC_CELL_HAZARD_HPP
+#define SYNC_CELL_HAZARD_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The hazard.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <algorithm>
+#include <atomic>
+#include <cstdint>
+#include <mutex>
+#include <type_traits>
+#include <utility>
+#include <vector>
+
+#include "util/cache_padded.hpp"
+
+
+namespace sc::hazard {
+
+namespace impl {
+
+/// @brief Minimum number of the retired objects in a thread before a scan.
+constexpr size_t ScanThreshold = 64;
+
+/// @brief Maximum number of the free hazard records cached by a thread.
+constexpr size_t MaxCachedRecords = 8;
+
+/// @brief A retired object which is destroyed after no hazard pointer protects it.
+struct Retired
+{
+    void (*deleter)(void *) noexcept;
+    void *ptr;
+};
+
+/// @brief A hazard slot. The records are reused after being released, and only destroyed with the
+/// global domain.
+struct Record
+{
+    /// @brief The protected pointer, or nullptr.
+    util::CachePadded<std::atomic<const void *>> ptr;
+    std::atomic<bool> in_use{true};
+    Record *next = nullptr;
+
+    Record()
+    {
+        ptr->store(nullptr, std::memory_order_relaxed);
+    }
+};
+
+/// @brief The global list of the hazard records and the orphan retired objects.
+class Domain
+{
+public:
+    Domain() = default;
+
+    Domain(const Domain &) = delete;
+
+    Domain &operator=(const Domain &) = delete;
+
+    ~Domain()
+    {
+        // No thread is running now.
+        for (auto &r : orphans_) {
+            r.deleter(r.ptr);
+        }
+
+        auto *record = head_.load(std::memory_order_relaxed);
+        while (record != nullptr) {
+            delete std::exchange(record, record->next);
+        }
+    }
+
+    Record *acquire_record()
+    {
+        for (auto *record = head_.load(std::memory_order_acquire); record != nullptr; record = record->next) {
+            bool in_use = false;
+            if (!record->in_use.load(std::memory_order_relaxed) &&
+                record->in_use.compare_exchange_strong(in_use, true, std::memory_order_acquire)) {
+                return record;
+            }
+        }
+
+        auto *record = new Record();
+        record->next = head_.load(std::memory_order_relaxed);
+        while (!head_.compare_exchange_weak(
+                record->next, record,
+                std::memory_order_release,
+                std::memory_order_relaxed)) { }
+        record_count_.fetch_add(1, std::memory_order_relaxed);
+        return record;
+    }
+
+    void release_record(Record *record) noexcept
+    {
+        record->in_use.store(false, std::memory_order_release);
+    }
+
+    [[nodiscard]] size_t record_count() const noexcept
+    {
+        return record_count_.load(std::memory_order_relaxed);
+    }
+
+    /// @brief Moves the retired objects of an exiting thread to the domain.
+    void push_orphans(std::vector<Retired> &&retired)
+    {
+        std::lock_guard guard(orphans_mtx_);
+        orphans_.insert(orphans_.end(), retired.begin(), retired.end());
+        retired.clear();
+    }
+
+    /// @brief Destroys the objects in 'retired' which are not protected. The protected ones are
+    /// kept in 'retired'. The orphan objects are adopted if no other thread is adopting them.
+    void scan(std::vector<Retired> &retired)
+    {
+        {
+            std::unique_lock lock(orphans_mtx_, std::try_to_lock);
+            if (lock.owns_lock() && !orphans_.empty()) {
+                retired.insert(retired.end(), orphans_.begin(), orphans_.end());
+                orphans_.clear();
+            }
+        }
+
+        // The retired objects have been unlinked before this fence, so a hazard pointer which is
+        // set after the fence can not protect them (the validation in 'protect' fails).
+        std::atomic_thread_fence(std::memory_order_seq_cst);
+
+        std::vector<const void *> hazards;
+        for (auto *record = head_.load(std::memory_order_acquire); record != nullptr; record = record->next) {
+            // Pairs with the release stores of the hazard pointer: the accesses before a reset
+            // happen before the destruction.
+            if (auto *p = record->ptr->load(std::memory_order_acquire); p != nullptr) {
+                hazards.push_back(p);
+            }
+        }
+        std::sort(hazards.begin(), hazards.end());
+
+        auto kept = std::partition(retired.begin(), retired.end(), [&hazards](const Retired &r) {
+            return std::binary_search(hazards.begin(), hazards.end(), r.ptr);
+        });
+        std::vector<Retired> reclaimable(kept, retired.end());
+        retired.erase(kept, retired.end());
+
+        for (auto &r : reclaimable) {
+            r.deleter(r.ptr);
+        }
+    }
+
+private:
+    /// @brief The list of all records. A record is never removed before the destruction.
+    std::atomic<Record *> head_{nullptr};
+    std::atomic<size_t> record_count_{0};
+
+    std::mutex orphans_mtx_;
+    std::vector<Retired> orphans_;
+};
+
+inline Domain &domain() noexcept
+{
+    static Domain d;
+    return d;
+}
+
+/// @brief The thread-local cache of the free records and the retired objects.
+class ThreadCache
+{
+public:
+    ThreadCache() : domain_(domain()) { }
+
+    ThreadCache(const ThreadCache &) = delete;
+
+    ThreadCache &operator=(const ThreadCache &) = delete;
+
+    ~ThreadCache()
+    {
+        for (auto *record : records_) {
+            domain_.release_record(record);
+        }
+        if (!retired_.empty()) {
+            domain_.scan(retired_);
+        }
+        if (!retired_.empty()) {
+            domain_.push_orphans(std::move(retired_));
+        }
+    }
+
+    Record *acquire_record()
+    {
+        if (records_.empty()) {
+            return domain_.acquire_record();
+        }
+
+        auto *record = records_.back();
+        records_.pop_back();
+        return record;
+    }
+
+    void release_record(Record *record)
+    {
+        if (records_.size() < MaxCachedRecords) {
+            records_.push_back(record);
+        } else {
+            domain_.release_record(record);
+        }
+    }
+
+    void retire(Retired r)
+    {
+        retired_.push_back(r);
+        if (retired_.size() >= std::max(ScanThreshold, 2 * domain_.record_count())) {
+            domain_.scan(retired_);
+        }
+    }
+
+    void flush()
+    {
+        domain_.scan(retired_);
+    }
+
+private:
+    Domain &domain_;
+    std::vector<Record *> records_;
+    std::vector<Retired> retired_;
+};
+
+inline ThreadCache &thread_cache()
+{
+    thread_local ThreadCache c;
+    return c;
+}
+
+}
+
+/// @brief A hazard pointer which protects one object from being destroyed.
+///
+/// An object protected by a hazard pointer is not destroyed after @c retire, until the hazard
+/// pointer is reset or destroyed. Unlike the epoch-based reclamation, a reader only blocks the
+/// objects it protects, so a slow reader never stalls the reclamation of the other objects.
+///
+/// @note A hazard pointer can only be used by the thread which created it.
+class HazardPointer
+{
+public:
+    HazardPointer() : record_(impl::thread_cache().acquire_record()) { }
+
+    HazardPointer(HazardPointer &&other) noexcept : record_(std::exchange(other.record_, nullptr)) { }
+
+    HazardPointer(const HazardPointer &) = delete;
+
+    HazardPointer &operator=(const HazardPointer &) = delete;
+
+    HazardPointer &operator=(HazardPointer &&) = delete;
+
+    ~HazardPointer()
+    {
+        if (record_ != nullptr) {
+            reset();
+            impl::thread_cache().release_record(record_);
+        }
+    }
+
+    /// @brief Loads the pointer from 'src' and protects it.
+    /// @return The protected pointer. It keeps valid until the hazard pointer is reset.
+    template<typename T>
+    T *protect(const std::atomic<T *> &src) noexcept
+    {
+        auto *ptr = src.load(std::memory_order_relaxed);
+        while (!try_protect(ptr, src)) { }
+        return ptr;
+    }
+
+    /// @brief Protects 'ptr' which is loaded from 'src'.
+    /// @return true if 'src' still points to 'ptr' after the protection. Otherwise 'ptr' is set
+    /// to the current value of 'src', and the protection is not valid.
+    template<typename T>
+    bool try_protect(T *&ptr, const std::atomic<T *> &src) noexcept
+    {
+        auto *p = ptr;
+        record_->ptr->store(p, std::memory_order_seq_cst);
+        ptr = src.load(std::memory_order_seq_cst);
+        if (ptr != p) {
+            reset();
+            return false;
+        }
+
+        return true;
+    }
+
+    /// @brief Stops protecting the current object.
+    void reset() noexcept
+    {
+        record_->ptr->store(nullptr, std::memory_order_release);
+    }
+
+private:
+    impl::Record *record_;
+};
+
+/// @brief Destroys the object with @c delete after no hazard pointer protects it.
+///
+/// The object must have been unlinked from the shared data, so that no hazard pointer can protect
+/// it again.
+template<typename T>
+void retire(T *ptr)
+{
+    static_assert(!std::is_void_v<T>);
+    impl::thread_cache().retire(impl::Retired{
+            [](void *p) noexcept { delete static_cast<T *>(p); },
+            const_cast<std::remove_cv_t<T> *>(ptr)});
+}
+
+/// @brief Destroys the object with the 'deleter' instead of @c delete, as @c retire, e.g. for an
+/// object from a custom allocator.
+template<typename T>
+void retire(T *ptr, void (*deleter)(void *) noexcept)
+{
+    static_assert(!std::is_void_v<T>);
+    impl::thread_cache().retire(impl::Retired{deleter, const_cast<std::remove_cv_t<T> *>(ptr)});
+}
+
+/// @brief Destroys the retired objects of the current thread which are not protected now.
+inline void flush()
+{
+    impl::thread_cache().flush();
+}
+
+/// @brief The hazard pointer based reclamation backend of the lock-free containers. See
+/// @c sc::epoch::Reclaimer for the requirements of a backend.
+///
+/// @note A guard owns one hazard pointer, so it only protects the latest pointer passed to
+/// @c protect.
+struct Reclaimer
+{
+    class Guard
+    {
+        friend struct Reclaimer;
+
+        Guard() = default;
+
+    public:
+        template<typename T>
+        T *protect(const std::atomic<T *> &src) noexcept
+        {
+            return hazard_.protect(src);
+        }
+
+        template<typename T>
+        void retire(T *ptr) const
+        {
+            hazard::retire(ptr);
+        }
+
+        template<typename T>
+        void retire(T *ptr, void (*deleter)(void *) noexcept) const
+        {
+            hazard::retire(ptr, deleter);
+        }
+
+        void flush() const
+        {
+            hazard::flush();
+        }
+
+    private:
+        HazardPointer hazard_;
+    };
+
+    static Guard guard()
+    {
+        Guard guard;
+        std::atomic_thread_fence(std::memory_order_seq_cst);
+        return guard;
+    }
+};
+
+}
+
+#endif //SYNC_CELL_HAZARD_HPP
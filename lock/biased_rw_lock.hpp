@@ -0,0 +1,362 @@
+///
+/// @file  biased_rw_lock.hpp
+/// @brief A reader-biased reader-writer lock for the read-mostly values, whose readers publish
+/// themselves in a table of distributed slots instead of a shared reader count. Based on
+/// [BRAVO](https://www.usenix.org/conference/atc19/presentation/dice) (Dice and Kogan, USENIX ATC 2019).
+///
+
+#ifndef SYNC_CELL_BIASED_RW_LOCK_HPP
+#define SYNC_CELL_BIASED_RW_LOCK_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The biased_rw_lock.hpp requires the std clocks, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <atomic>
+#include <chrono>
+#include <cstddef>
+#include <cstdint>
+#include <memory>
+#include <optional>
+#include <type_traits>
+#include <utility>
+
+#include "lock/rw_spin_lock.hpp"
+#include "shared/deadlock.hpp"
+#include "shared/metrics.hpp"
+#include "util/back_off.hpp"
+
+
+namespace sc::lock {
+
+namespace impl {
+
+/// @brief The count of the slots of the visible readers table, shared by all biased locks.
+constexpr size_t VisibleReaderSlots = 4096;
+
+/// @brief After the bias is revoked, the readers take the slow path for this many times the
+/// duration of the revocation, which bounds the cost of the revocations to about 10% of the time
+/// of the write-heavy phases.
+constexpr int64_t InhibitMultiplier = 9;
+
+/// @brief The value of the inner lock, which only guards the slow path.
+struct Unit { };
+
+/// @brief The visible readers table: a slot holds the lock its reader has entered by the fast path.
+inline std::atomic<const void *> *visible_readers() noexcept
+{
+    static std::atomic<const void *> table[VisibleReaderSlots];
+    return table;
+}
+
+/// @brief Returns the slot of the current thread for the 'lock'.
+inline std::atomic<const void *> &reader_slot(const void *lock) noexcept
+{
+    static std::atomic<size_t> next_thread{0};
+    thread_local const size_t thread_seed = next_thread.fetch_add(1, std::memory_order_relaxed) * 0x9e3779b97f4a7c15;
+    auto hash = (thread_seed ^ reinterpret_cast<uintptr_t>(lock)) * 0xff51afd7ed558ccd;
+    return visible_readers()[(hash >> 32) % VisibleReaderSlots];
+}
+
+inline int64_t now_ns() noexcept
+{
+    return std::chrono::duration_cast<std::chrono::nanoseconds>(
+            std::chrono::steady_clock::now().time_since_epoch()).count();
+}
+
+}
+
+/// @brief A reader-writer lock protecting a value of type 'T', biased to the readers.
+///
+/// While the lock is read-biased, a reader enters it by one CAS on its own slot of a process-wide
+/// table (hashed by the thread and the lock), so the readers on the different cores do not bounce
+/// a shared reader count, and the read acquisitions scale with the cores. A writer revokes the
+/// bias: it takes the inner @c RwSpinLock, clears the bias and waits for the readers found in the
+/// table. Then the readers take the inner lock as in a plain @c RwSpinLock, until a reader
+/// re-enables the bias after a while proportional to the cost of the revocation.
+///
+/// So the lock suits the values written rarely and read very often, e.g. a routing table or a
+/// configuration. A write to a biased lock scans the whole table, which takes microseconds.
+///
+/// @note A reader whose slot is taken (by another lock or thread hashed to it, or by its own outer
+/// read guard) takes the slow path, which is always correct.
+/// @tparam T The value type.
+/// @tparam Backoff The backoff when the lock is held by another thread.
+template<typename T, typename Backoff = util::Backoff>
+class BiasedRwLock
+{
+    using Inner = RwSpinLock<impl::Unit, Backoff>;
+
+public:
+    using value_type = T;
+
+    /// @brief The RAII guard holding the shared lock, which gives the const access to the value.
+    class ReadGuard
+    {
+        friend class BiasedRwLock;
+
+        ReadGuard(BiasedRwLock &lock, std::atomic<const void *> &slot) noexcept : lock_(&lock), slot_(&slot) { }
+
+        ReadGuard(BiasedRwLock &lock, typename Inner::ReadGuard &&inner) noexcept
+                : lock_(&lock), slot_(nullptr), inner_(std::move(inner)) { }
+
+    public:
+        ReadGuard(ReadGuard &&other) noexcept
+                : lock_(std::exchange(other.lock_, nullptr)),
+                  slot_(std::exchange(other.slot_, nullptr)),
+                  inner_(std::move(other.inner_))
+        {
+            other.inner_.reset();
+        }
+
+        ReadGuard &operator=(ReadGuard &&) = delete;
+
+        ~ReadGuard()
+        {
+            if (lock_ != nullptr) {
+                deadlock::on_unlocked(lock_);
+            }
+            if (slot_ != nullptr) {
+                slot_->store(nullptr, std::memory_order_release);
+            }
+        }
+
+        /// @brief Returns true if the guard has entered by the fast path of the biased lock.
+        [[nodiscard]] bool is_fast_path() const noexcept
+        {
+            return slot_ != nullptr;
+        }
+
+        const T &operator*() const noexcept
+        {
+            return lock_->value_;
+        }
+
+        const T *operator->() const noexcept
+        {
+            return std::addressof(lock_->value_);
+        }
+
+    private:
+        BiasedRwLock *lock_;
+        std::atomic<const void *> *slot_;
+        std::optional<typename Inner::ReadGuard> inner_;
+    };
+
+    /// @brief The RAII guard holding the exclusive lock, which gives the mutable access to the value.
+    class WriteGuard
+    {
+        friend class BiasedRwLock;
+
+        WriteGuard(BiasedRwLock &lock, typename Inner::WriteGuard &&inner) noexcept
+                : lock_(&lock), inner_(std::move(inner)) { }
+
+    public:
+        WriteGuard(WriteGuard &&other) noexcept
+                : lock_(std::exchange(other.lock_, nullptr)), inner_(std::move(other.inner_)) { }
+
+        WriteGuard &operator=(WriteGuard &&) = delete;
+
+        ~WriteGuard()
+        {
+            if (lock_ != nullptr) {
+                deadlock::on_unlocked(lock_);
+            }
+        }
+
+        T &operator*() const noexcept
+        {
+            return lock_->value_;
+        }
+
+        T *operator->() const noexcept
+        {
+            return std::addressof(lock_->value_);
+        }
+
+    private:
+        BiasedRwLock *lock_;
+        typename Inner::WriteGuard inner_;
+    };
+
+    template<typename... Args>
+    constexpr explicit BiasedRwLock(Args &&... args) noexcept(std::is_nothrow_constructible_v<T, Args...>)
+            : value_(std::forward<Args>(args)...)
+    {
+    }
+
+#if SC_HAS_DEADLOCK_DETECTION
+    ~BiasedRwLock()
+    {
+        deadlock::forget(this);
+    }
+#endif
+
+    BiasedRwLock(const BiasedRwLock &) = delete;
+
+    BiasedRwLock &operator=(const BiasedRwLock &) = delete;
+
+    /// @brief Acquires the shared lock. Takes the slot of the thread if the lock is read-biased,
+    /// otherwise spins with backoff while a writer holds or waits for the inner lock.
+    [[nodiscard]] ReadGuard read() noexcept
+    {
+        deadlock::before_lock(this, "sc::lock::BiasedRwLock");
+        if (auto *slot = try_enter_biased(); slot != nullptr) {
+            deadlock::on_locked(this, "sc::lock::BiasedRwLock");
+            return ReadGuard(*this, *slot);
+        }
+
+        auto inner = inner_.read();
+        rebias_if_expired();
+        deadlock::on_locked(this, "sc::lock::BiasedRwLock");
+        return ReadGuard(*this, std::move(inner));
+    }
+
+    /// @brief Acquires the shared lock if no writer holds or waits for the lock.
+    [[nodiscard]] std::optional<ReadGuard> try_read() noexcept
+    {
+        if (auto *slot = try_enter_biased(); slot != nullptr) {
+            deadlock::on_locked(this, "sc::lock::BiasedRwLock");
+            return ReadGuard(*this, *slot);
+        }
+
+        if (auto inner = inner_.try_read()) {
+            rebias_if_expired();
+            deadlock::on_locked(this, "sc::lock::BiasedRwLock");
+            return ReadGuard(*this, *std::move(inner));
+        }
+        return {};
+    }
+
+    /// @brief Acquires the exclusive lock, spins with backoff until all readers and the writer
+    /// release the lock. Revokes the bias of the lock first if it is read-biased.
+    [[nodiscard]] WriteGuard write() noexcept
+    {
+        deadlock::before_lock(this, "sc::lock::BiasedRwLock");
+        auto inner = inner_.write();
+        if (read_bias_.load(std::memory_order_relaxed)) {
+            revoke_bias();
+        }
+        deadlock::on_locked(this, "sc::lock::BiasedRwLock");
+        return WriteGuard(*this, std::move(inner));
+    }
+
+    /// @brief Acquires the exclusive lock if no reader or writer holds the lock. The bias is revoked
+    /// even if a fast-path reader makes it fail.
+    [[nodiscard]] std::optional<WriteGuard> try_write() noexcept
+    {
+        auto inner = inner_.try_write();
+        if (!inner) {
+            return {};
+        }
+        if (read_bias_.load(std::memory_order_relaxed)) {
+            read_bias_.store(false, std::memory_order_seq_cst);
+            inhibit_until_.store(impl::now_ns(), std::memory_order_relaxed);
+            if (has_biased_readers()) {
+                return {};
+            }
+        }
+        deadlock::on_locked(this, "sc::lock::BiasedRwLock");
+        return WriteGuard(*this, *std::move(inner));
+    }
+
+    /// @brief Returns true if the readers take the fast path now.
+    [[nodiscard]] bool is_read_biased() const noexcept
+    {
+        return read_bias_.load(std::memory_order_relaxed);
+    }
+
+    /// @brief Returns the contention counters of the inner lock, used by the writers and the
+    /// slow-path readers. Requires @c SYNC_CELL_METRICS.
+    [[nodiscard]] metrics::Stats stats() const noexcept
+    {
+        return inner_.stats();
+    }
+
+    /// @brief Returns the reference to the value without locking. The caller must guarantee no
+    /// other thread is accessing the value, e.g. before the lock is shared.
+    T &get_unsafe() noexcept
+    {
+        return value_;
+    }
+
+    /// @brief Moves the value out of the lock without locking. The caller must guarantee no other
+    /// thread is accessing the lock, and the lock is not used after.
+    T into_inner() && noexcept(std::is_nothrow_move_constructible_v<T>)
+    {
+        return std::move(value_);
+    }
+
+private:
+    /// @brief Takes the slot of the thread if the lock is read-biased.
+    /// @return The slot, or null for the slow path.
+    std::atomic<const void *> *try_enter_biased() noexcept
+    {
+        if (!read_bias_.load(std::memory_order_relaxed)) {
+            return nullptr;
+        }
+
+        auto &slot = impl::reader_slot(this);
+        const void *expected = nullptr;
+        if (!slot.compare_exchange_strong(expected, this, std::memory_order_seq_cst, std::memory_order_relaxed)) {
+            return nullptr;
+        }
+
+        // Pairs with the revocation of the writer: either the writer sees the slot in its scan, or
+        // we see the bias has been revoked.
+        if (read_bias_.load(std::memory_order_seq_cst)) {
+            return &slot;
+        }
+        slot.store(nullptr, std::memory_order_relaxed);
+        return nullptr;
+    }
+
+    /// @brief Re-enables the bias once the inhibition after the last revocation expires. Must be
+    /// called with the inner read lock held, so no writer is revoking at the same time.
+    void rebias_if_expired() noexcept
+    {
+        if (!read_bias_.load(std::memory_order_relaxed) &&
+            impl::now_ns() >= inhibit_until_.load(std::memory_order_relaxed)) {
+            read_bias_.store(true, std::memory_order_relaxed);
+        }
+    }
+
+    /// @brief Clears the bias and waits for the fast-path readers. Must be called with the inner
+    /// write lock held.
+    void revoke_bias() noexcept
+    {
+        read_bias_.store(false, std::memory_order_seq_cst);
+        auto start = impl::now_ns();
+        Backoff backoff;
+        while (has_biased_readers()) {
+            backoff.snooze();
+        }
+        auto now = impl::now_ns();
+        inhibit_until_.store(now + (now - start) * impl::InhibitMultiplier, std::memory_order_relaxed);
+    }
+
+    [[nodiscard]] bool has_biased_readers() const noexcept
+    {
+        auto *table = impl::visible_readers();
+        for (size_t i = 0; i < impl::VisibleReaderSlots; ++i) {
+            // Pairs with the release store of the leaving reader: its reads happen before the write.
+            if (table[i].load(std::memory_order_seq_cst) == this) {
+                return true;
+            }
+        }
+        return false;
+    }
+
+    Inner inner_;
+    std::atomic<bool> read_bias_{true};
+    /// @brief The time (in the nanoseconds of the steady clock) before which the bias is not
+    /// re-enabled.
+    std::atomic<int64_t> inhibit_until_{0};
+    T value_;
+};
+
+}
+
+#endif //SYNC_CELL_BIASED_RW_LOCK_HPP
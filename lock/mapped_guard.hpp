@@ -0,0 +1,89 @@
+///
+/// @file  mapped_guard.hpp
+/// @brief A lock guard projected to a part of the protected value.
+///
+
+#ifndef SYNC_CELL_MAPPED_GUARD_HPP
+#define SYNC_CELL_MAPPED_GUARD_HPP
+
+#include <memory>
+#include <type_traits>
+#include <utility>
+
+
+namespace sc::lock {
+
+template<typename Guard, typename U>
+class MappedGuard;
+
+namespace impl {
+
+/// @brief Applies 'f' to the 'value' protected by the 'guard', and makes a @c MappedGuard of the
+/// returned reference, which takes the 'guard'.
+template<typename Guard, typename V, typename F>
+auto map_guard(Guard &&guard, V &value, F &f)
+{
+    using R = std::invoke_result_t<F &, V &>;
+    static_assert(std::is_lvalue_reference_v<R>, "The function must return a reference to a part of the value.");
+
+    R part = f(value);
+    return MappedGuard<Guard, std::remove_reference_t<R>>(std::move(guard), part);
+}
+
+}
+
+/// @brief A guard which keeps holding the lock of the original guard, but gives the access to a
+/// part of the value only, e.g. a field. Like the @c parking_lot::MappedMutexGuard.
+///
+/// It is made by the @c map (the const access) or @c map_mut (the mutable access) of the guards,
+/// which consume the original guard:
+/// ``` cpp
+/// sc::lock::SpinLock<std::pair<int, std::string>> lock;
+/// auto name = lock.lock().map_mut([](auto &p) -> std::string & { return p.second; });
+/// name->append("x");
+/// ```
+/// @tparam Guard The original guard, which is movable.
+/// @tparam U The type of the part, const for the const access.
+template<typename Guard, typename U>
+class MappedGuard
+{
+public:
+    /// @brief Takes the 'guard', and refers to the 'part' of the value protected by it.
+    MappedGuard(Guard &&guard, U &part) noexcept : guard_(std::move(guard)), part_(std::addressof(part)) { }
+
+    MappedGuard(MappedGuard &&) noexcept = default;
+
+    MappedGuard &operator=(MappedGuard &&) = delete;
+
+    U &operator*() const noexcept
+    {
+        return *part_;
+    }
+
+    U *operator->() const noexcept
+    {
+        return part_;
+    }
+
+    /// @brief Projects to a part of this part, the function returns a reference into its argument.
+    template<typename F>
+    [[nodiscard]] auto map(F &&f) &&
+    {
+        return impl::map_guard(std::move(guard_), std::as_const(*part_), f);
+    }
+
+    /// @brief Projects to a part of this part with the mutable access.
+    template<typename F> requires (!std::is_const_v<U>)
+    [[nodiscard]] auto map_mut(F &&f) &&
+    {
+        return impl::map_guard(std::move(guard_), *part_, f);
+    }
+
+private:
+    Guard guard_;
+    U *part_;
+};
+
+}
+
+#endif //SYNC_CELL_MAPPED_GUARD_HPP
@@ -0,0 +1,264 @@
+///
+/// @file  mcs_lock.hpp
+/// @brief A fair (FIFO) queue spin lock protecting a value, each waiter spins on its own node
+/// (Mellor-Crummey and Scott lock).
+///
+
+#ifndef SYNC_CELL_MCS_LOCK_HPP
+#define SYNC_CELL_MCS_LOCK_HPP
+
+#include <atomic>
+#include <cassert>
+#include <memory>
+#include <type_traits>
+#include <utility>
+
+#include "shared/compiler_workaround.hpp"
+#include "shared/deadlock.hpp"
+#include "shared/metrics.hpp"
+#include "shared/serde.hpp"
+#include "shared/tracing.hpp"
+#include "util/back_off.hpp"
+#include "util/cache_padded.hpp"
+
+
+namespace sc::lock {
+
+/// @brief A fair queue spin lock protecting a value of type 'T' (MCS lock).
+///
+/// The waiting threads form a linked list of nodes, and each of them spins on the flag in its own
+/// node, which is set by its predecessor when unlocking. So an unlock only touches the cache line
+/// of the next waiter, instead of all waiting cores like the @c TicketLock.
+///
+/// The node lives in the guard, so the guard can not be moved, nor mapped like the other guards.
+/// Unlike the @c SpinLock, the @c try_lock returns a guard which may not own the lock, check it by @c owns_lock (like the
+/// @c std::unique_lock with @c std::try_to_lock):
+/// ``` cpp
+/// if (auto guard = lock.try_lock(); guard.owns_lock()) { guard->push_back(1); }
+/// ```
+/// @tparam T The value type.
+/// @tparam Backoff The backoff when waiting for the predecessor.
+template<typename T, typename Backoff = util::Backoff>
+class McsLock
+{
+    struct Node
+    {
+        std::atomic<Node *> next{nullptr};
+        std::atomic<bool> locked{true};
+    };
+
+public:
+    using value_type = T;
+
+    /// @brief The RAII guard holding (or failed to hold) the lock, the lock is released when the
+    /// guard is destroyed.
+    class Guard
+    {
+        friend class McsLock;
+
+        struct TryTag { };
+
+        explicit Guard(McsLock &lock) noexcept : lock_(&lock)
+        {
+            lock_->lock_node(*node_);
+        }
+
+        Guard(McsLock &lock, TryTag) noexcept : lock_(&lock)
+        {
+            if (!lock_->try_lock_node(*node_)) {
+                lock_ = nullptr;
+            }
+        }
+
+    public:
+        Guard(const Guard &) = delete;
+
+        Guard &operator=(const Guard &) = delete;
+
+        ~Guard()
+        {
+            if (lock_ != nullptr) {
+                lock_->unlock_node(*node_);
+            }
+        }
+
+        [[nodiscard]] bool owns_lock() const noexcept
+        {
+            return lock_ != nullptr;
+        }
+
+        explicit operator bool() const noexcept
+        {
+            return owns_lock();
+        }
+
+        T &operator*() const noexcept
+        {
+            assert(owns_lock());
+            return lock_->value_;
+        }
+
+        T *operator->() const noexcept
+        {
+            assert(owns_lock());
+            return std::addressof(lock_->value_);
+        }
+
+    private:
+        McsLock *lock_;
+        /// @brief Padded, so the spinning on the node does not disturb the caller's stack.
+        util::CachePadded<Node> node_;
+    };
+
+    template<typename... Args>
+    constexpr explicit McsLock(Args &&... args) noexcept(std::is_nothrow_constructible_v<T, Args...>)
+            : tail_(nullptr), value_(std::forward<Args>(args)...)
+    {
+    }
+
+#if SC_HAS_DEADLOCK_DETECTION
+    ~McsLock()
+    {
+        deadlock::forget(this);
+    }
+#endif
+
+    McsLock(const McsLock &) = delete;
+
+    McsLock &operator=(const McsLock &) = delete;
+
+    /// @brief Acquires the lock, spins with backoff until all threads arrived before have released
+    /// the lock.
+    [[nodiscard]] Guard lock() noexcept
+    {
+        return Guard(*this);
+    }
+
+    /// @brief Acquires the lock if no thread holds or waits for it.
+    /// @return A guard owns the lock, or not if the lock is held by another thread.
+    [[nodiscard]] Guard try_lock() noexcept
+    {
+        return Guard(*this, typename Guard::TryTag{});
+    }
+
+    /// @brief Returns true if the lock is held by any thread.
+    [[nodiscard]] bool is_locked() const noexcept
+    {
+        return tail_->load(std::memory_order_relaxed) != nullptr;
+    }
+
+    /// @brief Returns the contention counters: the waits on the own node and for the successor to
+    /// link as the spin iterations, and the lost races on the tail when unlocking as the CAS
+    /// failures. Requires @c SYNC_CELL_METRICS.
+    [[nodiscard]] metrics::Stats stats() const noexcept
+    {
+        return metrics_.snapshot();
+    }
+
+    /// @brief Returns the reference to the value without locking. The caller must guarantee no
+    /// other thread is accessing the value, e.g. before the lock is shared.
+    T &get_unsafe() noexcept
+    {
+        return value_;
+    }
+
+    /// @brief Moves the value out of the lock without locking. The caller must guarantee no other
+    /// thread is accessing the lock, and the lock is not used after.
+    T into_inner() && noexcept(std::is_nothrow_move_constructible_v<T>)
+    {
+        return std::move(value_);
+    }
+
+private:
+    void lock_node(Node &node) noexcept
+    {
+        deadlock::before_lock(this, "sc::lock::McsLock");
+        auto *prev = tail_->exchange(&node, std::memory_order_acq_rel);
+        if (prev == nullptr) {
+            deadlock::on_locked(this, "sc::lock::McsLock");
+            return;
+        }
+
+        prev->next.store(&node, std::memory_order_release);
+        Backoff backoff;
+        tracing::Span span(tracing::SpanKind::LockWait, "sc::lock::McsLock", this);
+        while (node.locked.load(std::memory_order_acquire)) {
+            span.enter();
+            metrics_.spin();
+            backoff.snooze();
+        }
+        deadlock::on_locked(this, "sc::lock::McsLock");
+    }
+
+    bool try_lock_node(Node &node) noexcept
+    {
+        Node *expected = nullptr;
+        if (tail_->compare_exchange_strong(
+                expected, &node,
+                std::memory_order_acquire,
+                std::memory_order_relaxed)) {
+            deadlock::on_locked(this, "sc::lock::McsLock");
+            return true;
+        }
+        return false;
+    }
+
+    void unlock_node(Node &node) noexcept
+    {
+        deadlock::on_unlocked(this);
+        auto *next = node.next.load(std::memory_order_acquire);
+        if (next == nullptr) {
+            // No successor: the lock is free after the tail is reset.
+            auto *expected = &node;
+            if (tail_->compare_exchange_strong(
+                    expected, nullptr,
+                    std::memory_order_release,
+                    std::memory_order_relaxed)) {
+                return;
+            }
+
+            // A successor has swapped the tail, but not linked to the node yet.
+            metrics_.cas_failure();
+            Backoff backoff;
+            while ((next = node.next.load(std::memory_order_acquire)) == nullptr) {
+                metrics_.spin();
+                backoff.spin();
+            }
+        }
+
+        next->locked.store(false, std::memory_order_release);
+    }
+
+    util::CachePadded<std::atomic<Node *>> tail_;
+    SC_NO_UNIQUE_ADDRESS metrics::Counters metrics_;
+    T value_;
+};
+
+}
+
+#if SC_HAS_SERDE
+
+namespace sc::serde {
+
+/// @brief Serializes a copy of the value under the lock.
+template<typename T, typename Backoff>
+struct Codec<lock::McsLock<T, Backoff>>
+{
+    using value_type = T;
+
+    static value_type snapshot(const lock::McsLock<T, Backoff> &c)
+    {
+        return *const_cast<lock::McsLock<T, Backoff> &>(c).lock();
+    }
+
+    static void restore(lock::McsLock<T, Backoff> &c, value_type &&value)
+    {
+        *c.lock() = std::move(value);
+    }
+};
+
+}
+
+#endif
+
+#endif //SYNC_CELL_MCS_LOCK_HPP
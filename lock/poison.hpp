@@ -0,0 +1,121 @@
+///
+/// @file  poison.hpp
+/// @brief The poisoning policies of the locks, which mark a lock poisoned if a writer exits by an
+/// exception.
+///
+
+#ifndef SYNC_CELL_POISON_HPP
+#define SYNC_CELL_POISON_HPP
+
+#include <atomic>
+#include <exception>
+#include <stdexcept>
+
+
+namespace sc::lock {
+
+/// @brief The default policy of the locks: a writer exiting by an exception releases the lock
+/// normally, and the next holder sees the value as it was left.
+struct NoPoison
+{
+    static constexpr bool Enabled = false;
+};
+
+/// @brief The opt-in policy of the locks: if a guard with the mutable access is destroyed by the
+/// stack unwinding of an exception, the lock is poisoned, and the later acquisitions throw the
+/// @c PoisonError instead of observing the value which may be half-updated.
+///
+/// @example
+/// ``` cpp
+/// sc::lock::SpinLock<Config, sc::util::Backoff, sc::lock::Poison> config;
+/// try {
+///     config.lock()->apply(update);
+/// } catch (const sc::lock::PoisonError &) {
+///     auto guard = config.lock_ignoring_poison();
+///     guard->reset();
+///     config.clear_poison();
+/// }
+/// ```
+struct Poison
+{
+    static constexpr bool Enabled = true;
+};
+
+/// @brief Thrown by the acquisitions of a poisoned lock.
+class PoisonError : public std::runtime_error
+{
+public:
+    PoisonError() : std::runtime_error("The lock is poisoned by a writer exited by an exception.") { }
+};
+
+namespace impl {
+
+/// @brief The poisoned flag of a lock, which is empty for the @c NoPoison policy.
+template<typename Policy>
+class PoisonFlag
+{
+public:
+    /// @brief Stored in a guard with the mutable access.
+    struct Scope
+    {
+    };
+
+    [[nodiscard]] bool is_poisoned() const noexcept
+    {
+        return false;
+    }
+
+    void check() const noexcept { }
+
+    void leave(const Scope &) noexcept { }
+
+    void clear() noexcept { }
+};
+
+template<>
+class PoisonFlag<Poison>
+{
+public:
+    /// @brief Records the count of the uncaught exceptions when the guard is created, so a guard
+    /// destroyed by the unwinding is detected, even if the guard is created in a destructor.
+    struct Scope
+    {
+        int exceptions = std::uncaught_exceptions();
+    };
+
+    [[nodiscard]] bool is_poisoned() const noexcept
+    {
+        return poisoned_.load(std::memory_order_acquire);
+    }
+
+    /// @brief Throws the @c PoisonError if the lock is poisoned, must be called while holding
+    /// the lock, by a guard which releases it.
+    void check() const
+    {
+        if (is_poisoned()) {
+            throw PoisonError();
+        }
+    }
+
+    /// @brief Called when a guard of the 'scope' is destroyed, before it releases the lock.
+    void leave(const Scope &scope) noexcept
+    {
+        if (std::uncaught_exceptions() > scope.exceptions) {
+            poisoned_.store(true, std::memory_order_relaxed);
+        }
+    }
+
+    void clear() noexcept
+    {
+        poisoned_.store(false, std::memory_order_relaxed);
+    }
+
+private:
+    std::atomic<bool> poisoned_{false};
+};
+
+}
+
+}
+
+#endif //SYNC_CELL_POISON_HPP
@@ -0,0 +1,233 @@
+///
+/// @file  reentrant_mutex.hpp
+/// @brief A reentrant spin lock protecting a value, which the owning thread can acquire again.
+///
+
+#ifndef SYNC_CELL_REENTRANT_MUTEX_HPP
+#define SYNC_CELL_REENTRANT_MUTEX_HPP
+
+#include <atomic>
+#include <cassert>
+#include <cstddef>
+#include <memory>
+#include <optional>
+#include <type_traits>
+#include <utility>
+
+#include "lock/mapped_guard.hpp"
+#include "shared/compiler_workaround.hpp"
+#include "shared/deadlock.hpp"
+#include "shared/metrics.hpp"
+#include "shared/tracing.hpp"
+#include "util/back_off.hpp"
+#include "util/thread_id.hpp"
+
+
+namespace sc::lock {
+
+/// @brief A reentrant lock protecting a value of type 'T': the thread holding the lock can acquire
+/// it again, e.g. when a callback called under the lock calls back into the locked object.
+///
+/// Only the outermost guard gives the mutable access by @c get_mut, the nested guards give the const
+/// access only, so a re-entered callback does not modify the value the outer caller is working on.
+/// The lock is released when the outermost guard is destroyed. The guards must be destroyed on the
+/// thread which acquired them.
+///
+/// @example
+/// ``` cpp
+/// sc::lock::ReentrantMutex<std::vector<Listener>> listeners;
+/// auto guard = listeners.lock();
+/// for (auto &l: *guard) { l.notify(); }   // notify() may call listeners.lock() again.
+/// ```
+/// @tparam T The value type.
+/// @tparam Backoff The backoff when the lock is held by another thread.
+template<typename T, typename Backoff = util::Backoff>
+class ReentrantMutex
+{
+    static constexpr size_t NoOwner = ~size_t(0);
+
+public:
+    using value_type = T;
+
+    /// @brief The RAII guard holding the lock once, the lock is released when the outermost guard
+    /// is destroyed.
+    class Guard
+    {
+        friend class ReentrantMutex;
+
+        Guard(ReentrantMutex &lock, bool nested) noexcept : lock_(&lock), nested_(nested) { }
+
+    public:
+        Guard(Guard &&other) noexcept : lock_(std::exchange(other.lock_, nullptr)), nested_(other.nested_) { }
+
+        Guard &operator=(Guard &&) = delete;
+
+        ~Guard()
+        {
+            if (lock_ != nullptr) {
+                lock_->unlock();
+            }
+        }
+
+        const T &operator*() const noexcept
+        {
+            return lock_->value_;
+        }
+
+        const T *operator->() const noexcept
+        {
+            return std::addressof(lock_->value_);
+        }
+
+        /// @brief Returns true if the lock was already held by this thread when the guard acquired it.
+        [[nodiscard]] bool is_nested() const noexcept
+        {
+            return nested_;
+        }
+
+        /// @brief Returns the mutable reference to the value, only for the outermost guard.
+        [[nodiscard]] T &get_mut() const noexcept
+        {
+            assert(!nested_);
+            return lock_->value_;
+        }
+
+        /// @brief Consumes the guard, and makes a guard of the part of the value returned by 'f',
+        /// which gives the const access to it. See @c MappedGuard.
+        template<typename F>
+        [[nodiscard]] auto map(F &&f) &&
+        {
+            return impl::map_guard(std::move(*this), std::as_const(lock_->value_), f);
+        }
+
+    private:
+        ReentrantMutex *lock_;
+        bool nested_;
+    };
+
+    template<typename... Args>
+    constexpr explicit ReentrantMutex(Args &&... args) noexcept(std::is_nothrow_constructible_v<T, Args...>)
+            : value_(std::forward<Args>(args)...)
+    {
+    }
+
+#if SC_HAS_DEADLOCK_DETECTION
+    ~ReentrantMutex()
+    {
+        deadlock::forget(this);
+    }
+#endif
+
+    ReentrantMutex(const ReentrantMutex &) = delete;
+
+    ReentrantMutex &operator=(const ReentrantMutex &) = delete;
+
+    /// @brief Acquires the lock, returns at once if this thread holds it, otherwise spins with
+    /// backoff until it is available.
+    [[nodiscard]] Guard lock()
+    {
+        auto id = util::thread_id();
+        if (owner_.load(std::memory_order_relaxed) == id) {
+            return relock();
+        }
+
+        deadlock::before_lock(this, "sc::lock::ReentrantMutex");
+        Backoff backoff;
+        tracing::Span span(tracing::SpanKind::LockWait, "sc::lock::ReentrantMutex", this);
+        while (!try_own(id)) {
+            metrics_.cas_failure();
+            span.enter();
+            while (owner_.load(std::memory_order_relaxed) != NoOwner) {
+                metrics_.spin();
+                backoff.snooze();
+            }
+        }
+        deadlock::on_locked(this, "sc::lock::ReentrantMutex");
+        return Guard(*this, false);
+    }
+
+    /// @brief Acquires the lock if it is available or held by this thread.
+    /// @return An empty optional if the lock is held by another thread.
+    [[nodiscard]] std::optional<Guard> try_lock()
+    {
+        auto id = util::thread_id();
+        if (owner_.load(std::memory_order_relaxed) == id) {
+            return relock();
+        }
+        if (try_own(id)) {
+            deadlock::on_locked(this, "sc::lock::ReentrantMutex");
+            return Guard(*this, false);
+        }
+        return {};
+    }
+
+    /// @brief Returns true if the lock is held by any thread.
+    [[nodiscard]] bool is_locked() const noexcept
+    {
+        return owner_.load(std::memory_order_relaxed) != NoOwner;
+    }
+
+    /// @brief Returns true if the lock is held by the current thread.
+    [[nodiscard]] bool is_owned_by_current_thread() const
+    {
+        return owner_.load(std::memory_order_relaxed) == util::thread_id();
+    }
+
+    /// @brief Returns the contention counters: the lost races for the lock as the CAS failures,
+    /// and the waits for the holder as the spin iterations. Requires @c SYNC_CELL_METRICS.
+    [[nodiscard]] metrics::Stats stats() const noexcept
+    {
+        return metrics_.snapshot();
+    }
+
+    /// @brief Returns the reference to the value without locking. The caller must guarantee no
+    /// other thread is accessing the value, e.g. before the lock is shared.
+    T &get_unsafe() noexcept
+    {
+        return value_;
+    }
+
+    /// @brief Moves the value out of the lock without locking. The caller must guarantee no other
+    /// thread is accessing the lock, and the lock is not used after.
+    T into_inner() && noexcept(std::is_nothrow_move_constructible_v<T>)
+    {
+        return std::move(value_);
+    }
+
+private:
+    bool try_own(size_t id) noexcept
+    {
+        auto expected = NoOwner;
+        if (owner_.compare_exchange_strong(expected, id, std::memory_order_acquire, std::memory_order_relaxed)) {
+            depth_ = 1;
+            return true;
+        }
+        return false;
+    }
+
+    /// @brief Acquires the lock again by the owner. Only the owner reads and writes the 'depth_'.
+    Guard relock() noexcept
+    {
+        ++depth_;
+        return Guard(*this, true);
+    }
+
+    void unlock() noexcept
+    {
+        if (--depth_ == 0) {
+            deadlock::on_unlocked(this);
+            owner_.store(NoOwner, std::memory_order_release);
+        }
+    }
+
+    /// @brief The thread id of the owner, only the owner itself can observe its own id here.
+    std::atomic<size_t> owner_{NoOwner};
+    /// @brief The count of the guards held by the owner.
+    size_t depth_ = 0;
+    SC_NO_UNIQUE_ADDRESS metrics::Counters metrics_;
+    T value_;
+};
+
+}
+
+#endif //SYNC_CELL_REENTRANT_MUTEX_HPP
@@ -0,0 +1,488 @@
+///
+/// @file  rw_spin_lock.hpp
+/// @brief A reader-writer spin lock protecting a value, whose guards give the shared or the
+/// exclusive access to the value.
+///
+
+#ifndef SYNC_CELL_RW_SPIN_LOCK_HPP
+#define SYNC_CELL_RW_SPIN_LOCK_HPP
+
+#include <atomic>
+#include <cstddef>
+#include <memory>
+#include <optional>
+#include <type_traits>
+#include <utility>
+
+#include "lock/mapped_guard.hpp"
+#include "lock/poison.hpp"
+#include "shared/compiler_workaround.hpp"
+#include "shared/deadlock.hpp"
+#include "shared/metrics.hpp"
+#include "shared/serde.hpp"
+#include "shared/tracing.hpp"
+#include "util/back_off.hpp"
+
+
+namespace sc::lock {
+
+/// @brief A reader-writer spin lock protecting a value of type 'T'. Multiple readers can hold the
+/// lock at the same time, while a writer holds it exclusively.
+///
+/// An upgradeable reader holds the shared lock along with the other readers, but at most one
+/// upgradeable reader at the same time, so it can be upgraded to the writer without releasing the
+/// lock in between: the value checked by the upgradeable reader is not changed before the write.
+///
+/// The state is one atomic word: the lowest bit is set when a writer holds the lock, the second bit
+/// is set when a writer is waiting, the third bit is set when an upgradeable reader holds the lock,
+/// and the other bits are the count of readers. A waiting writer blocks the new readers, so the
+/// writers are not starved by a stream of readers.
+/// @tparam T The value type.
+/// @tparam Backoff The backoff when the lock is held by another thread.
+/// @tparam PoisonPolicy @c NoPoison, or @c Poison to poison the lock when a @c WriteGuard is
+/// destroyed by an exception, then all acquisitions throw the @c PoisonError. See @c Poison.
+template<typename T, typename Backoff = util::Backoff, typename PoisonPolicy = NoPoison>
+class RwSpinLock
+{
+    static constexpr size_t Writer = 1;
+    static constexpr size_t Pending = 2;
+    static constexpr size_t Upgradeable = 4;
+    static constexpr size_t ReaderOne = 8;
+
+    static constexpr bool Poisoning = PoisonPolicy::Enabled;
+
+    using PoisonFlag = impl::PoisonFlag<PoisonPolicy>;
+
+public:
+    using value_type = T;
+
+    /// @brief The RAII guard holding the shared lock, which gives the const access to the value.
+    class ReadGuard
+    {
+        friend class RwSpinLock;
+
+        explicit ReadGuard(RwSpinLock &lock) noexcept : lock_(&lock) { }
+
+    public:
+        ReadGuard(ReadGuard &&other) noexcept : lock_(std::exchange(other.lock_, nullptr)) { }
+
+        ReadGuard &operator=(ReadGuard &&) = delete;
+
+        ~ReadGuard()
+        {
+            if (lock_ != nullptr) {
+                deadlock::on_unlocked(lock_);
+                lock_->state_.fetch_sub(ReaderOne, std::memory_order_release);
+            }
+        }
+
+        const T &operator*() const noexcept
+        {
+            return lock_->value_;
+        }
+
+        const T *operator->() const noexcept
+        {
+            return std::addressof(lock_->value_);
+        }
+
+        /// @brief Consumes the guard, and makes a guard of the part of the value returned by 'f',
+        /// which gives the const access to it. See @c MappedGuard.
+        template<typename F>
+        [[nodiscard]] auto map(F &&f) &&
+        {
+            return impl::map_guard(std::move(*this), std::as_const(lock_->value_), f);
+        }
+
+    private:
+        RwSpinLock *lock_;
+    };
+
+    /// @brief The RAII guard holding the exclusive lock, which gives the mutable access to the value.
+    class WriteGuard
+    {
+        friend class RwSpinLock;
+
+        explicit WriteGuard(RwSpinLock &lock) noexcept : lock_(&lock) { }
+
+    public:
+        WriteGuard(WriteGuard &&other) noexcept : lock_(std::exchange(other.lock_, nullptr)), scope_(other.scope_) { }
+
+        WriteGuard &operator=(WriteGuard &&) = delete;
+
+        ~WriteGuard()
+        {
+            if (lock_ != nullptr) {
+                lock_->poison_.leave(scope_);
+                deadlock::on_unlocked(lock_);
+                // Keep the 'Pending' bit set by the other waiting writers.
+                lock_->state_.fetch_and(~Writer, std::memory_order_release);
+            }
+        }
+
+        T &operator*() const noexcept
+        {
+            return lock_->value_;
+        }
+
+        T *operator->() const noexcept
+        {
+            return std::addressof(lock_->value_);
+        }
+
+        /// @brief Consumes the guard, and makes a guard of the part of the value returned by 'f',
+        /// which gives the const access to it. See @c MappedGuard.
+        template<typename F>
+        [[nodiscard]] auto map(F &&f) &&
+        {
+            return impl::map_guard(std::move(*this), std::as_const(lock_->value_), f);
+        }
+
+        /// @brief Consumes the guard, and makes a guard of the part of the value returned by 'f',
+        /// which gives the mutable access to it.
+        template<typename F>
+        [[nodiscard]] auto map_mut(F &&f) &&
+        {
+            return impl::map_guard(std::move(*this), lock_->value_, f);
+        }
+
+    private:
+        RwSpinLock *lock_;
+        SC_NO_UNIQUE_ADDRESS typename PoisonFlag::Scope scope_;
+    };
+
+    /// @brief The RAII guard holding the upgradeable shared lock, which gives the const access to the
+    /// value and can be upgraded to the @c WriteGuard.
+    class UpgradeableReadGuard
+    {
+        friend class RwSpinLock;
+
+        explicit UpgradeableReadGuard(RwSpinLock &lock) noexcept : lock_(&lock) { }
+
+    public:
+        UpgradeableReadGuard(UpgradeableReadGuard &&other) noexcept : lock_(std::exchange(other.lock_, nullptr)) { }
+
+        UpgradeableReadGuard &operator=(UpgradeableReadGuard &&) = delete;
+
+        ~UpgradeableReadGuard()
+        {
+            if (lock_ != nullptr) {
+                deadlock::on_unlocked(lock_);
+                lock_->state_.fetch_and(~Upgradeable, std::memory_order_release);
+            }
+        }
+
+        const T &operator*() const noexcept
+        {
+            return lock_->value_;
+        }
+
+        const T *operator->() const noexcept
+        {
+            return std::addressof(lock_->value_);
+        }
+
+        /// @brief Consumes the guard, and makes a guard of the part of the value returned by 'f',
+        /// which gives the const access to it. See @c MappedGuard.
+        template<typename F>
+        [[nodiscard]] auto map(F &&f) &&
+        {
+            return impl::map_guard(std::move(*this), std::as_const(lock_->value_), f);
+        }
+
+        /// @brief Upgrades to the exclusive lock, spins with backoff until the other readers release
+        /// the lock. The new readers are blocked while waiting.
+        [[nodiscard]] WriteGuard upgrade() &&
+        {
+            auto *lock = std::exchange(lock_, nullptr);
+            Backoff backoff;
+            tracing::Span span(tracing::SpanKind::LockWait, "sc::lock::RwSpinLock", lock);
+            while (!lock->try_upgrade_lock()) {
+                span.enter();
+                // Block the new readers.
+                lock->state_.fetch_or(Pending, std::memory_order_relaxed);
+                lock->metrics_.spin();
+                backoff.snooze();
+            }
+            return WriteGuard(*lock);
+        }
+
+        /// @brief Upgrades to the exclusive lock if no other reader holds the lock.
+        /// @return An empty optional if failed, and this guard still holds the upgradeable lock.
+        [[nodiscard]] std::optional<WriteGuard> try_upgrade() noexcept
+        {
+            if (lock_->try_upgrade_lock()) {
+                return WriteGuard(*std::exchange(lock_, nullptr));
+            }
+            return {};
+        }
+
+    private:
+        RwSpinLock *lock_;
+    };
+
+    template<typename... Args>
+    constexpr explicit RwSpinLock(Args &&... args) noexcept(std::is_nothrow_constructible_v<T, Args...>)
+            : value_(std::forward<Args>(args)...)
+    {
+    }
+
+#if SC_HAS_DEADLOCK_DETECTION
+    ~RwSpinLock()
+    {
+        deadlock::forget(this);
+    }
+#endif
+
+    RwSpinLock(const RwSpinLock &) = delete;
+
+    RwSpinLock &operator=(const RwSpinLock &) = delete;
+
+    /// @brief Acquires the shared lock, spins with backoff while a writer holds or waits for the
+    /// lock.
+    /// @note With the @c Poison policy, all acquisitions throw the @c PoisonError if the lock is
+    /// poisoned.
+    [[nodiscard]] ReadGuard read() noexcept(!Poisoning)
+    {
+        deadlock::before_lock(this, "sc::lock::RwSpinLock");
+        Backoff backoff;
+        tracing::Span span(tracing::SpanKind::LockWait, "sc::lock::RwSpinLock", this);
+        while (!try_lock_shared()) {
+            span.enter();
+            metrics_.spin();
+            backoff.snooze();
+        }
+        deadlock::on_locked(this, "sc::lock::RwSpinLock");
+        ReadGuard guard(*this);
+        poison_.check();
+        return guard;
+    }
+
+    /// @brief Acquires the shared lock if no writer holds or waits for the lock.
+    [[nodiscard]] std::optional<ReadGuard> try_read() noexcept(!Poisoning)
+    {
+        if (try_lock_shared()) {
+            deadlock::on_locked(this, "sc::lock::RwSpinLock");
+            std::optional<ReadGuard> guard(ReadGuard(*this));
+            poison_.check();
+            return guard;
+        }
+        return {};
+    }
+
+    /// @brief Acquires the upgradeable shared lock, spins with backoff while a writer or another
+    /// upgradeable reader holds the lock, or a writer waits for the lock.
+    [[nodiscard]] UpgradeableReadGuard read_upgradeable() noexcept(!Poisoning)
+    {
+        deadlock::before_lock(this, "sc::lock::RwSpinLock");
+        Backoff backoff;
+        tracing::Span span(tracing::SpanKind::LockWait, "sc::lock::RwSpinLock", this);
+        while (!try_lock_upgradeable()) {
+            span.enter();
+            metrics_.spin();
+            backoff.snooze();
+        }
+        deadlock::on_locked(this, "sc::lock::RwSpinLock");
+        UpgradeableReadGuard guard(*this);
+        poison_.check();
+        return guard;
+    }
+
+    /// @brief Acquires the upgradeable shared lock if no writer or other upgradeable reader holds
+    /// the lock, and no writer waits for the lock.
+    [[nodiscard]] std::optional<UpgradeableReadGuard> try_read_upgradeable() noexcept(!Poisoning)
+    {
+        if (try_lock_upgradeable()) {
+            deadlock::on_locked(this, "sc::lock::RwSpinLock");
+            std::optional<UpgradeableReadGuard> guard(UpgradeableReadGuard(*this));
+            poison_.check();
+            return guard;
+        }
+        return {};
+    }
+
+    /// @brief Acquires the exclusive lock, spins with backoff until all readers and the writer
+    /// release the lock.
+    [[nodiscard]] WriteGuard write() noexcept(!Poisoning)
+    {
+        auto guard = write_ignoring_poison();
+        poison_.check();
+        return guard;
+    }
+
+    /// @brief Acquires the exclusive lock even if it is poisoned, e.g. to recover the value before
+    /// @c clear_poison.
+    [[nodiscard]] WriteGuard write_ignoring_poison() noexcept
+    {
+        deadlock::before_lock(this, "sc::lock::RwSpinLock");
+        Backoff backoff;
+        tracing::Span span(tracing::SpanKind::LockWait, "sc::lock::RwSpinLock", this);
+        auto state = state_.load(std::memory_order_relaxed);
+        while (true) {
+            if ((state & ~Pending) == 0) {
+                if (state_.compare_exchange_weak(
+                        state, Writer,
+                        std::memory_order_acquire,
+                        std::memory_order_relaxed)) {
+                    deadlock::on_locked(this, "sc::lock::RwSpinLock");
+                    return WriteGuard(*this);
+                }
+                metrics_.cas_failure();
+                continue;
+            }
+
+            // Block the new readers.
+            if ((state & Pending) == 0) {
+                state_.fetch_or(Pending, std::memory_order_relaxed);
+            }
+            span.enter();
+            metrics_.spin();
+            backoff.snooze();
+            state = state_.load(std::memory_order_relaxed);
+        }
+    }
+
+    /// @brief Acquires the exclusive lock if no reader or writer holds the lock.
+    [[nodiscard]] std::optional<WriteGuard> try_write() noexcept(!Poisoning)
+    {
+        auto state = state_.load(std::memory_order_relaxed);
+        if ((state & ~Pending) == 0 &&
+            state_.compare_exchange_strong(
+                    state, Writer,
+                    std::memory_order_acquire,
+                    std::memory_order_relaxed)) {
+            deadlock::on_locked(this, "sc::lock::RwSpinLock");
+            std::optional<WriteGuard> guard(WriteGuard(*this));
+            poison_.check();
+            return guard;
+        }
+        return {};
+    }
+
+    /// @brief Returns true if the lock is poisoned, always false with the @c NoPoison policy.
+    [[nodiscard]] bool is_poisoned() const noexcept
+    {
+        return poison_.is_poisoned();
+    }
+
+    /// @brief Clears the poisoned state, after the value has been recovered to a consistent state.
+    void clear_poison() noexcept
+    {
+        poison_.clear();
+    }
+
+    /// @brief Returns the count of readers holding the lock.
+    [[nodiscard]] size_t reader_count() const noexcept
+    {
+        return state_.load(std::memory_order_relaxed) / ReaderOne;
+    }
+
+    /// @brief Returns true if a writer holds the lock.
+    [[nodiscard]] bool is_write_locked() const noexcept
+    {
+        return (state_.load(std::memory_order_relaxed) & Writer) != 0;
+    }
+
+    /// @brief Returns the contention counters: the lost races on the lock state as the CAS
+    /// failures, and the waits for the other holders as the spin iterations. Requires
+    /// @c SYNC_CELL_METRICS.
+    [[nodiscard]] metrics::Stats stats() const noexcept
+    {
+        return metrics_.snapshot();
+    }
+
+    /// @brief Returns the reference to the value without locking. The caller must guarantee no
+    /// other thread is accessing the value, e.g. before the lock is shared.
+    T &get_unsafe() noexcept
+    {
+        return value_;
+    }
+
+    /// @brief Moves the value out of the lock without locking. The caller must guarantee no other
+    /// thread is accessing the lock, and the lock is not used after.
+    T into_inner() && noexcept(std::is_nothrow_move_constructible_v<T>)
+    {
+        return std::move(value_);
+    }
+
+private:
+    bool try_lock_shared() noexcept
+    {
+        auto state = state_.load(std::memory_order_relaxed);
+        while ((state & (Writer | Pending)) == 0) {
+            if (state_.compare_exchange_weak(
+                    state, state + ReaderOne,
+                    std::memory_order_acquire,
+                    std::memory_order_relaxed)) {
+                return true;
+            }
+            metrics_.cas_failure();
+        }
+        return false;
+    }
+
+    bool try_lock_upgradeable() noexcept
+    {
+        auto state = state_.load(std::memory_order_relaxed);
+        while ((state & (Writer | Pending | Upgradeable)) == 0) {
+            if (state_.compare_exchange_weak(
+                    state, state | Upgradeable,
+                    std::memory_order_acquire,
+                    std::memory_order_relaxed)) {
+                return true;
+            }
+            metrics_.cas_failure();
+        }
+        return false;
+    }
+
+    /// @brief Replaces the upgradeable reader with the writer if no other reader holds the lock.
+    bool try_upgrade_lock() noexcept
+    {
+        auto state = state_.load(std::memory_order_relaxed);
+        while ((state & ~Pending) == Upgradeable) {
+            if (state_.compare_exchange_weak(
+                    state, Writer,
+                    std::memory_order_acquire,
+                    std::memory_order_relaxed)) {
+                return true;
+            }
+            metrics_.cas_failure();
+        }
+        return false;
+    }
+
+    std::atomic<size_t> state_{0};
+    SC_NO_UNIQUE_ADDRESS metrics::Counters metrics_;
+    SC_NO_UNIQUE_ADDRESS PoisonFlag poison_;
+    T value_;
+};
+
+}
+
+#if SC_HAS_SERDE
+
+namespace sc::serde {
+
+/// @brief Serializes a copy of the value under the read lock, and restores it under the write lock.
+template<typename T, typename Backoff, typename PoisonPolicy>
+struct Codec<lock::RwSpinLock<T, Backoff, PoisonPolicy>>
+{
+    using value_type = T;
+
+    static value_type snapshot(const lock::RwSpinLock<T, Backoff, PoisonPolicy> &c)
+    {
+        return *const_cast<lock::RwSpinLock<T, Backoff, PoisonPolicy> &>(c).read();
+    }
+
+    static void restore(lock::RwSpinLock<T, Backoff, PoisonPolicy> &c, value_type &&value)
+    {
+        *c.write() = std::move(value);
+    }
+};
+
+}
+
+#endif
+
+#endif //SYNC_CELL_RW_SPIN_LOCK_HPP
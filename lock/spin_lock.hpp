@@ -0,0 +1,245 @@
+///
+/// @file  spin_lock.hpp
+/// @brief A spin lock protecting a value, whose guard gives the access to the value.
+///
+
+#ifndef SYNC_CELL_SPIN_LOCK_HPP
+#define SYNC_CELL_SPIN_LOCK_HPP
+
+#include <atomic>
+#include <memory>
+#include <optional>
+#include <type_traits>
+#include <utility>
+
+#include "lock/mapped_guard.hpp"
+#include "lock/poison.hpp"
+#include "shared/compiler_workaround.hpp"
+#include "shared/deadlock.hpp"
+#include "shared/metrics.hpp"
+#include "shared/serde.hpp"
+#include "shared/tracing.hpp"
+#include "util/back_off.hpp"
+
+
+namespace sc::lock {
+
+/// @brief A spin lock protecting a value of type 'T', for the tiny critical sections where the
+/// overhead of the @c std::mutex dominates.
+///
+/// The value can only be accessed through the @c Guard returned by @c lock or @c try_lock, so it is
+/// never accessed without holding the lock. The lock does not park the thread, and does not use
+/// any OS primitive.
+///
+/// @example
+/// ``` cpp
+/// sc::lock::SpinLock<std::vector<int>> values;
+/// values.lock()->push_back(1);
+/// if (auto guard = values.try_lock()) { (*guard)->push_back(2); }
+/// ```
+/// @tparam T The value type.
+/// @tparam Backoff The backoff when the lock is held by another thread.
+/// @tparam PoisonPolicy @c NoPoison, or @c Poison to poison the lock when a guard is destroyed by
+/// an exception, see @c Poison.
+template<typename T, typename Backoff = util::Backoff, typename PoisonPolicy = NoPoison>
+class SpinLock
+{
+    static constexpr bool Poisoning = PoisonPolicy::Enabled;
+
+    using PoisonFlag = impl::PoisonFlag<PoisonPolicy>;
+
+public:
+    using value_type = T;
+
+    /// @brief The RAII guard holding the lock, the lock is released when the guard is destroyed.
+    class Guard
+    {
+        friend class SpinLock;
+
+        explicit Guard(SpinLock &lock) noexcept : lock_(&lock) { }
+
+    public:
+        Guard(Guard &&other) noexcept : lock_(std::exchange(other.lock_, nullptr)), scope_(other.scope_) { }
+
+        Guard &operator=(Guard &&) = delete;
+
+        ~Guard()
+        {
+            if (lock_ != nullptr) {
+                lock_->poison_.leave(scope_);
+                lock_->unlock();
+            }
+        }
+
+        T &operator*() const noexcept
+        {
+            return lock_->value_;
+        }
+
+        T *operator->() const noexcept
+        {
+            return std::addressof(lock_->value_);
+        }
+
+        /// @brief Consumes the guard, and makes a guard of the part of the value returned by 'f',
+        /// which gives the const access to it. See @c MappedGuard.
+        template<typename F>
+        [[nodiscard]] auto map(F &&f) &&
+        {
+            return impl::map_guard(std::move(*this), std::as_const(lock_->value_), f);
+        }
+
+        /// @brief Consumes the guard, and makes a guard of the part of the value returned by 'f',
+        /// which gives the mutable access to it.
+        template<typename F>
+        [[nodiscard]] auto map_mut(F &&f) &&
+        {
+            return impl::map_guard(std::move(*this), lock_->value_, f);
+        }
+
+    private:
+        SpinLock *lock_;
+        SC_NO_UNIQUE_ADDRESS typename PoisonFlag::Scope scope_;
+    };
+
+    template<typename... Args>
+    constexpr explicit SpinLock(Args &&... args) noexcept(std::is_nothrow_constructible_v<T, Args...>)
+            : value_(std::forward<Args>(args)...)
+    {
+    }
+
+#if SC_HAS_DEADLOCK_DETECTION
+    ~SpinLock()
+    {
+        deadlock::forget(this);
+    }
+#endif
+
+    SpinLock(const SpinLock &) = delete;
+
+    SpinLock &operator=(const SpinLock &) = delete;
+
+    /// @brief Acquires the lock, spins with backoff until it is available.
+    /// @note With the @c Poison policy, throws the @c PoisonError if the lock is poisoned.
+    [[nodiscard]] Guard lock() noexcept(!Poisoning)
+    {
+        auto guard = lock_ignoring_poison();
+        poison_.check();
+        return guard;
+    }
+
+    /// @brief Acquires the lock even if it is poisoned, e.g. to recover the value before
+    /// @c clear_poison.
+    [[nodiscard]] Guard lock_ignoring_poison() noexcept
+    {
+        deadlock::before_lock(this, "sc::lock::SpinLock");
+        Backoff backoff;
+        tracing::Span span(tracing::SpanKind::LockWait, "sc::lock::SpinLock", this);
+        while (locked_.exchange(true, std::memory_order_acquire)) {
+            metrics_.cas_failure();
+            span.enter();
+            // Spin on the load to avoid the writes to the shared cache line.
+            while (locked_.load(std::memory_order_relaxed)) {
+                metrics_.spin();
+                backoff.snooze();
+            }
+        }
+        deadlock::on_locked(this, "sc::lock::SpinLock");
+        return Guard(*this);
+    }
+
+    /// @brief Acquires the lock if it is available.
+    /// @return An empty optional if the lock is held by another thread.
+    [[nodiscard]] std::optional<Guard> try_lock() noexcept(!Poisoning)
+    {
+        if (!locked_.load(std::memory_order_relaxed) && !locked_.exchange(true, std::memory_order_acquire)) {
+            deadlock::on_locked(this, "sc::lock::SpinLock");
+            std::optional<Guard> guard(Guard(*this));
+            poison_.check();
+            return guard;
+        }
+        return {};
+    }
+
+    /// @brief Returns true if the lock is poisoned, always false with the @c NoPoison policy.
+    [[nodiscard]] bool is_poisoned() const noexcept
+    {
+        return poison_.is_poisoned();
+    }
+
+    /// @brief Clears the poisoned state, after the value has been recovered to a consistent state.
+    void clear_poison() noexcept
+    {
+        poison_.clear();
+    }
+
+    /// @brief Returns true if the lock is held by any thread.
+    [[nodiscard]] bool is_locked() const noexcept
+    {
+        return locked_.load(std::memory_order_relaxed);
+    }
+
+    /// @brief Returns the contention counters: the lost races for the lock as the CAS failures,
+    /// and the waits for the holder as the spin iterations. Requires @c SYNC_CELL_METRICS.
+    [[nodiscard]] metrics::Stats stats() const noexcept
+    {
+        return metrics_.snapshot();
+    }
+
+    /// @brief Returns the reference to the value without locking. The caller must guarantee no
+    /// other thread is accessing the value, e.g. before the lock is shared.
+    T &get_unsafe() noexcept
+    {
+        return value_;
+    }
+
+    /// @brief Moves the value out of the lock without locking. The caller must guarantee no other
+    /// thread is accessing the lock, and the lock is not used after.
+    T into_inner() && noexcept(std::is_nothrow_move_constructible_v<T>)
+    {
+        return std::move(value_);
+    }
+
+private:
+    void unlock() noexcept
+    {
+        deadlock::on_unlocked(this);
+        locked_.store(false, std::memory_order_release);
+    }
+
+    std::atomic<bool> locked_{false};
+    SC_NO_UNIQUE_ADDRESS metrics::Counters metrics_;
+    SC_NO_UNIQUE_ADDRESS PoisonFlag poison_;
+    T value_;
+};
+
+}
+
+#if SC_HAS_SERDE
+
+namespace sc::serde {
+
+/// @brief Serializes a copy of the value under the lock. With the @c Poison policy, throws the
+/// @c PoisonError if the lock is poisoned.
+template<typename T, typename Backoff, typename PoisonPolicy>
+struct Codec<lock::SpinLock<T, Backoff, PoisonPolicy>>
+{
+    using value_type = T;
+
+    static value_type snapshot(const lock::SpinLock<T, Backoff, PoisonPolicy> &c)
+    {
+        // Locking does not change the value, the lock is only const for the hook.
+        return *const_cast<lock::SpinLock<T, Backoff, PoisonPolicy> &>(c).lock();
+    }
+
+    static void restore(lock::SpinLock<T, Backoff, PoisonPolicy> &c, value_type &&value)
+    {
+        *c.lock() = std::move(value);
+    }
+};
+
+}
+
+#endif
+
+#endif //SYNC_CELL_SPIN_LOCK_HPP
@@ -0,0 +1,214 @@
+///
+/// @file  ticket_lock.hpp
+/// @brief A fair (FIFO) spin lock protecting a value, based on the tickets.
+///
+
+#ifndef SYNC_CELL_TICKET_LOCK_HPP
+#define SYNC_CELL_TICKET_LOCK_HPP
+
+#include <atomic>
+#include <cstddef>
+#include <memory>
+#include <optional>
+#include <type_traits>
+#include <utility>
+
+#include "lock/mapped_guard.hpp"
+#include "shared/compiler_workaround.hpp"
+#include "shared/deadlock.hpp"
+#include "shared/metrics.hpp"
+#include "shared/serde.hpp"
+#include "shared/tracing.hpp"
+#include "util/back_off.hpp"
+#include "util/cache_padded.hpp"
+
+
+namespace sc::lock {
+
+/// @brief A fair spin lock protecting a value of type 'T', the threads acquire the lock in the
+/// order they arrive, so no thread is starved under heavy contention.
+///
+/// Each locking thread takes a ticket by an increment on the 'next' counter, and waits until the
+/// 'serving' counter reaches its ticket, the unlocking thread increases the 'serving' counter. The
+/// two counters are padded to different cache lines, so taking a ticket does not disturb the
+/// waiting threads. It has the same API as the @c SpinLock.
+/// @note As all waiters spin on the same 'serving' counter, each unlock still invalidates the cache
+/// line in all waiting cores. Use the @c McsLock if the count of contending threads is large.
+/// @tparam T The value type.
+/// @tparam Backoff The backoff when waiting for the ticket.
+template<typename T, typename Backoff = util::Backoff>
+class TicketLock
+{
+public:
+    using value_type = T;
+
+    /// @brief The RAII guard holding the lock, the lock is released when the guard is destroyed.
+    class Guard
+    {
+        friend class TicketLock;
+
+        explicit Guard(TicketLock &lock) noexcept : lock_(&lock) { }
+
+    public:
+        Guard(Guard &&other) noexcept : lock_(std::exchange(other.lock_, nullptr)) { }
+
+        Guard &operator=(Guard &&) = delete;
+
+        ~Guard()
+        {
+            if (lock_ != nullptr) {
+                lock_->unlock();
+            }
+        }
+
+        T &operator*() const noexcept
+        {
+            return lock_->value_;
+        }
+
+        T *operator->() const noexcept
+        {
+            return std::addressof(lock_->value_);
+        }
+
+        /// @brief Consumes the guard, and makes a guard of the part of the value returned by 'f',
+        /// which gives the const access to it. See @c MappedGuard.
+        template<typename F>
+        [[nodiscard]] auto map(F &&f) &&
+        {
+            return impl::map_guard(std::move(*this), std::as_const(lock_->value_), f);
+        }
+
+        /// @brief Consumes the guard, and makes a guard of the part of the value returned by 'f',
+        /// which gives the mutable access to it.
+        template<typename F>
+        [[nodiscard]] auto map_mut(F &&f) &&
+        {
+            return impl::map_guard(std::move(*this), lock_->value_, f);
+        }
+
+    private:
+        TicketLock *lock_;
+    };
+
+    template<typename... Args>
+    constexpr explicit TicketLock(Args &&... args) noexcept(std::is_nothrow_constructible_v<T, Args...>)
+            : value_(std::forward<Args>(args)...)
+    {
+    }
+
+#if SC_HAS_DEADLOCK_DETECTION
+    ~TicketLock()
+    {
+        deadlock::forget(this);
+    }
+#endif
+
+    TicketLock(const TicketLock &) = delete;
+
+    TicketLock &operator=(const TicketLock &) = delete;
+
+    /// @brief Acquires the lock, spins with backoff until all threads arrived before have released
+    /// the lock.
+    [[nodiscard]] Guard lock() noexcept
+    {
+        deadlock::before_lock(this, "sc::lock::TicketLock");
+        auto ticket = next_->fetch_add(1, std::memory_order_relaxed);
+
+        Backoff backoff;
+        tracing::Span span(tracing::SpanKind::LockWait, "sc::lock::TicketLock", this);
+        while (serving_->load(std::memory_order_acquire) != ticket) {
+            span.enter();
+            metrics_.spin();
+            backoff.snooze();
+        }
+        deadlock::on_locked(this, "sc::lock::TicketLock");
+        return Guard(*this);
+    }
+
+    /// @brief Acquires the lock if no thread holds or waits for it.
+    /// @return An empty optional if the lock is held by another thread.
+    [[nodiscard]] std::optional<Guard> try_lock() noexcept
+    {
+        auto ticket = serving_->load(std::memory_order_acquire);
+        if (next_->compare_exchange_strong(
+                ticket, ticket + 1,
+                std::memory_order_acquire,
+                std::memory_order_relaxed)) {
+            deadlock::on_locked(this, "sc::lock::TicketLock");
+            return Guard(*this);
+        }
+        return {};
+    }
+
+    /// @brief Returns true if the lock is held by any thread.
+    [[nodiscard]] bool is_locked() const noexcept
+    {
+        return next_->load(std::memory_order_relaxed) != serving_->load(std::memory_order_relaxed);
+    }
+
+    /// @brief Returns the contention counters: the waits for the earlier tickets as the spin
+    /// iterations. Requires @c SYNC_CELL_METRICS.
+    [[nodiscard]] metrics::Stats stats() const noexcept
+    {
+        return metrics_.snapshot();
+    }
+
+    /// @brief Returns the reference to the value without locking. The caller must guarantee no
+    /// other thread is accessing the value, e.g. before the lock is shared.
+    T &get_unsafe() noexcept
+    {
+        return value_;
+    }
+
+    /// @brief Moves the value out of the lock without locking. The caller must guarantee no other
+    /// thread is accessing the lock, and the lock is not used after.
+    T into_inner() && noexcept(std::is_nothrow_move_constructible_v<T>)
+    {
+        return std::move(value_);
+    }
+
+private:
+    void unlock() noexcept
+    {
+        deadlock::on_unlocked(this);
+        // Only the lock holder writes the 'serving_'.
+        serving_->store(serving_->load(std::memory_order_relaxed) + 1, std::memory_order_release);
+    }
+
+    /// @brief The next ticket to take.
+    util::CachePadded<std::atomic<size_t>> next_;
+    /// @brief The ticket holding the lock.
+    util::CachePadded<std::atomic<size_t>> serving_;
+    SC_NO_UNIQUE_ADDRESS metrics::Counters metrics_;
+    T value_;
+};
+
+}
+
+#if SC_HAS_SERDE
+
+namespace sc::serde {
+
+/// @brief Serializes a copy of the value under the lock.
+template<typename T, typename Backoff>
+struct Codec<lock::TicketLock<T, Backoff>>
+{
+    using value_type = T;
+
+    static value_type snapshot(const lock::TicketLock<T, Backoff> &c)
+    {
+        return *const_cast<lock::TicketLock<T, Backoff> &>(c).lock();
+    }
+
+    static void restore(lock::TicketLock<T, Backoff> &c, value_type &&value)
+    {
+        *c.lock() = std::move(value);
+    }
+};
+
+}
+
+#endif
+
+#endif //SYNC_CELL_TICKET_LOCK_HPP
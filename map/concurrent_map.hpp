@@ -0,0 +1,361 @@
+///
+/// @file  concurrent_map.hpp
+/// @brief A hash map with the lock-free reads protected by the epoch-based reclamation, and the
+/// writes serialized by the shard locks.
+///
+
+#ifndef SYNC_CELL_CONCURRENT_MAP_HPP
+#define SYNC_CELL_CONCURRENT_MAP_HPP
+
+#include <algorithm>
+#include <atomic>
+#include <bit>
+#include <cstddef>
+#include <cstdint>
+#include <functional>
+#include <memory>
+#include <utility>
+#include <vector>
+
+#include "epoch/epoch.hpp"
+#include "lock/spin_lock.hpp"
+#include "shared/compiler_workaround.hpp"
+#include "util/back_off.hpp"
+#include "util/cache_padded.hpp"
+
+
+namespace sc::map {
+
+/// @brief A hash map whose readers never lock: @c get, @c contains and @c for_each only pin the
+/// current thread (see @c sc::epoch::pin) and traverse the buckets, so they are never blocked by
+/// the writers or by each other. The writers lock one of the shards, each owns a fixed subset of
+/// the buckets, so the writes of the keys of different shards do not contend.
+///
+/// The entries are immutable after being published: @c insert_or_assign replaces the entry of the
+/// key instead of assigning to it, and the replaced or removed entries are destroyed after no
+/// reader can access them. So a reader always sees a whole value, the old one or the new one.
+///
+/// The bucket array is doubled when the keys are more than twice the buckets. The growing locks
+/// all shards, links the entries into the new buckets, and retires the old buckets, the readers
+/// keep going on the old buckets meanwhile.
+///
+/// Compared to @c StripedHashMap, the reads are cheaper and scale with the readers, while the
+/// writes are more expensive (an allocation and a deferred destruction each).
+///
+/// @example
+/// ``` cpp
+/// sc::map::ConcurrentMap<std::string, uint64_t> ports;
+/// ports.insert("http", 80);
+/// ports.get("http", [](uint64_t port) { std::cout << port; });
+/// ports.remove("http");
+/// ```
+/// @tparam K The key type.
+/// @tparam V The value type.
+/// @tparam Hash The hash function of the keys, also used to select the bucket and the shard.
+/// @tparam KeyEqual The equality of the keys.
+/// @tparam Backoff The backoff when the shard lock is held by another thread.
+template<typename K, typename V, typename Hash = std::hash<K>, typename KeyEqual = std::equal_to<K>,
+        typename Backoff = util::Backoff>
+class ConcurrentMap
+{
+    struct Entry
+    {
+        K key;
+        V value;
+    };
+
+    /// @brief A node of the bucket list. The 'next' is only changed by the writers holding the lock
+    /// of the shard, and a link is only used by one bucket array, the growing creates new links.
+    struct Link
+    {
+        uint64_t hash;
+        std::atomic<Entry *> entry;
+        std::atomic<Link *> next;
+    };
+
+    /// @brief The bucket array, whose destruction destroys the links but not the entries, which
+    /// are shared with the next bucket array or retired separately.
+    struct Table
+    {
+        uint32_t bits;
+        std::unique_ptr<std::atomic<Link *>[]> buckets;
+
+        explicit Table(uint32_t bits)
+                : bits(bits), buckets(std::make_unique<std::atomic<Link *>[]>(size_t{1} << bits))
+        {
+        }
+
+        ~Table()
+        {
+            for (size_t i = 0; i < bucket_count(); ++i) {
+                auto *link = buckets[i].load(std::memory_order_relaxed);
+                while (link != nullptr) {
+                    delete std::exchange(link, link->next.load(std::memory_order_relaxed));
+                }
+            }
+        }
+
+        [[nodiscard]] size_t bucket_count() const noexcept
+        {
+            return size_t{1} << bits;
+        }
+
+        std::atomic<Link *> &bucket_of(uint64_t hash) const noexcept
+        {
+            return buckets[top_bits(hash, bits)];
+        }
+    };
+
+    /// @brief The lock of a shard, protecting the count of its keys.
+    using Shard = util::CachePadded<lock::SpinLock<size_t, Backoff>>;
+
+    static constexpr size_t MaxLoadFactor = 2;
+
+public:
+    using key_type = K;
+    using mapped_type = V;
+
+    static constexpr size_t DefaultShardCount = 16;
+
+    /// @brief Creates an empty map with at least 'shard_count' shards, rounded up to a power of 2.
+    /// The initial bucket count is the shard count.
+    explicit ConcurrentMap(size_t shard_count = DefaultShardCount)
+            : shard_bits_(static_cast<uint32_t>(std::countr_zero(std::bit_ceil(std::max<size_t>(shard_count, 1))))),
+              shards_(std::make_unique<Shard[]>(size_t{1} << shard_bits_)),
+              table_(new Table(shard_bits_))
+    {
+    }
+
+    ConcurrentMap(const ConcurrentMap &) = delete;
+
+    ConcurrentMap &operator=(const ConcurrentMap &) = delete;
+
+    ~ConcurrentMap()
+    {
+        std::unique_ptr<Table> table(table_->load(std::memory_order_relaxed));
+        for (size_t i = 0; i < table->bucket_count(); ++i) {
+            for (auto *link = table->buckets[i].load(std::memory_order_relaxed); link != nullptr;
+                 link = link->next.load(std::memory_order_relaxed)) {
+                delete link->entry.load(std::memory_order_relaxed);
+            }
+        }
+    }
+
+    [[nodiscard]] size_t shard_count() const noexcept
+    {
+        return size_t{1} << shard_bits_;
+    }
+
+    /// @brief Returns the current count of the buckets, which grows with the keys.
+    [[nodiscard]] size_t bucket_count() const
+    {
+        auto guard = epoch::pin();
+        return table_->load(std::memory_order_acquire)->bucket_count();
+    }
+
+    /// @brief Inserts the value if the key does not exist.
+    /// @return false if the key exists, in this case, the value is not touched.
+    bool insert(key_type key, mapped_type value)
+    {
+        return write(std::move(key), std::move(value), false);
+    }
+
+    /// @brief Inserts the value, or replaces the existing value of the key. The readers holding the
+    /// old value keep reading it, which is destroyed after they finish.
+    /// @return true if the key is inserted, false if replaced.
+    bool insert_or_assign(key_type key, mapped_type value)
+    {
+        return write(std::move(key), std::move(value), true);
+    }
+
+    /// @brief Calls 'f' with the value of the key, without any lock.
+    /// @param f The function with the signature of 'void(const V &)'. The value is valid only in the
+    /// call, as it may be replaced or removed by other threads meanwhile.
+    /// @return false if the key does not exist, in this case, 'f' is not called.
+    template<typename F>
+    bool get(const key_type &key, F &&f) const
+    {
+        auto hash = hash_of(key);
+        auto guard = epoch::pin();
+        auto *table = table_->load(std::memory_order_acquire);
+        auto *entry = find(table->bucket_of(hash), hash, key).second;
+        if (entry == nullptr) {
+            return false;
+        }
+        f(std::as_const(entry->value));
+        return true;
+    }
+
+    [[nodiscard]] bool contains(const key_type &key) const
+    {
+        return get(key, [](const mapped_type &) { });
+    }
+
+    /// @brief Removes the key. The readers holding its value keep reading it, which is destroyed
+    /// after they finish.
+    /// @return false if the key does not exist.
+    bool remove(const key_type &key)
+    {
+        auto hash = hash_of(key);
+        auto guard = epoch::pin();
+        auto lock = shard_of(hash).lock();
+        // The bucket array can not be grown while a shard is locked.
+        auto *table = table_->load(std::memory_order_relaxed);
+        auto *prev = &table->bucket_of(hash);
+        auto *link = prev->load(std::memory_order_relaxed);
+        while (link != nullptr) {
+            auto *entry = link->entry.load(std::memory_order_relaxed);
+            if (link->hash == hash && key_equal_(entry->key, key)) {
+                // The readers on the link can still go on to the next links.
+                prev->store(link->next.load(std::memory_order_relaxed), std::memory_order_release);
+                guard.defer_destroy(entry);
+                guard.defer_destroy(link);
+                --*lock;
+                return true;
+            }
+            prev = &link->next;
+            link = prev->load(std::memory_order_relaxed);
+        }
+        return false;
+    }
+
+    /// @brief Returns the count of the keys. The shards are counted one by one, so it is not
+    /// a snapshot if other threads are modifying the map.
+    [[nodiscard]] size_t size() const
+    {
+        size_t size = 0;
+        for (size_t i = 0; i < shard_count(); ++i) {
+            size += *shards_[i]->lock();
+        }
+        return size;
+    }
+
+    [[nodiscard]] bool empty() const
+    {
+        return size() == 0;
+    }
+
+    /// @brief Calls 'f' with each key and value, without any lock. The keys inserted or removed by
+    /// other threads meanwhile may or may not be visited, but no key is visited twice.
+    /// @param f The function with the signature of 'void(const K &, const V &)'.
+    template<typename F>
+    void for_each(F &&f) const
+    {
+        auto guard = epoch::pin();
+        auto *table = table_->load(std::memory_order_acquire);
+        for (size_t i = 0; i < table->bucket_count(); ++i) {
+            for (auto *link = table->buckets[i].load(std::memory_order_acquire); link != nullptr;
+                 link = link->next.load(std::memory_order_acquire)) {
+                auto *entry = link->entry.load(std::memory_order_acquire);
+                f(std::as_const(entry->key), std::as_const(entry->value));
+            }
+        }
+    }
+
+private:
+    /// @brief Takes the upper 'bits' bits, so the bucket of a hash in a larger bucket array is one
+    /// of the buckets split from its bucket in the smaller one, and the buckets of a shard are fixed.
+    static size_t top_bits(uint64_t hash, uint32_t bits) noexcept
+    {
+        return bits == 0 ? 0 : static_cast<size_t>(hash >> (64 - bits));
+    }
+
+    /// @brief Mixes the hash by the Fibonacci hashing, whose upper bits depend on all bits.
+    uint64_t hash_of(const key_type &key) const
+    {
+        return static_cast<uint64_t>(hash_(key)) * 0x9E3779B97F4A7C15ull;
+    }
+
+    lock::SpinLock<size_t, Backoff> &shard_of(uint64_t hash) const noexcept
+    {
+        return *shards_[top_bits(hash, shard_bits_)];
+    }
+
+    /// @brief Finds the link and the entry of the key in the bucket, the current thread must be
+    /// pinned or hold the lock of the shard.
+    std::pair<Link *, Entry *> find(const std::atomic<Link *> &bucket, uint64_t hash, const key_type &key) const
+    {
+        for (auto *link = bucket.load(std::memory_order_acquire); link != nullptr;
+             link = link->next.load(std::memory_order_acquire)) {
+            if (link->hash != hash) {
+                continue;
+            }
+            auto *entry = link->entry.load(std::memory_order_acquire);
+            if (key_equal_(entry->key, key)) {
+                return {link, entry};
+            }
+        }
+        return {nullptr, nullptr};
+    }
+
+    bool write(key_type &&key, mapped_type &&value, bool assign)
+    {
+        auto hash = hash_of(key);
+        auto guard = epoch::pin();
+        Table *table;
+        bool overloaded;
+        {
+            auto lock = shard_of(hash).lock();
+            table = table_->load(std::memory_order_relaxed);
+            auto &bucket = table->bucket_of(hash);
+            auto [link, entry] = find(bucket, hash, key);
+            if (link != nullptr) {
+                if (!assign) {
+                    return false;
+                }
+                auto *old = link->entry.exchange(new Entry{std::move(key), std::move(value)},
+                                                 std::memory_order_acq_rel);
+                guard.defer_destroy(old);
+                return false;
+            }
+
+            auto *new_link = new Link{hash, new Entry{std::move(key), std::move(value)},
+                                      bucket.load(std::memory_order_relaxed)};
+            bucket.store(new_link, std::memory_order_release);
+            overloaded = ++*lock > (table->bucket_count() >> shard_bits_) * MaxLoadFactor;
+        }
+        if (overloaded) {
+            // Grows without the shard lock, as the growing locks all shards in order.
+            grow(table, guard);
+        }
+        return true;
+    }
+
+    /// @brief Doubles the bucket array if it is still 'table'.
+    void grow(Table *table, const epoch::Guard &guard)
+    {
+        std::vector<typename lock::SpinLock<size_t, Backoff>::Guard> locks;
+        locks.reserve(shard_count());
+        for (size_t i = 0; i < shard_count(); ++i) {
+            locks.emplace_back(shards_[i]->lock());
+        }
+        if (table_->load(std::memory_order_relaxed) != table) {
+            // Another thread has grown it.
+            return;
+        }
+
+        auto *new_table = new Table(table->bits + 1);
+        for (size_t i = 0; i < table->bucket_count(); ++i) {
+            for (auto *link = table->buckets[i].load(std::memory_order_relaxed); link != nullptr;
+                 link = link->next.load(std::memory_order_relaxed)) {
+                auto &bucket = new_table->bucket_of(link->hash);
+                bucket.store(new Link{link->hash, link->entry.load(std::memory_order_relaxed),
+                                      bucket.load(std::memory_order_relaxed)},
+                             std::memory_order_relaxed);
+            }
+        }
+        // Publishes the new links, the readers on the old ones keep going until they unpin.
+        table_->store(new_table, std::memory_order_release);
+        guard.defer_destroy(table);
+    }
+
+    uint32_t shard_bits_;
+    std::unique_ptr<Shard[]> shards_;
+    util::CachePadded<std::atomic<Table *>> table_;
+    SC_NO_UNIQUE_ADDRESS Hash hash_;
+    SC_NO_UNIQUE_ADDRESS KeyEqual key_equal_;
+};
+
+}
+
+#endif //SYNC_CELL_CONCURRENT_MAP_HPP
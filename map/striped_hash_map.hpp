@@ -0,0 +1,220 @@
+///
+/// @file  striped_hash_map.hpp
+/// @brief A hash map split into the shards with their own locks, so the unrelated keys can be
+/// accessed concurrently. Inspired by [dashmap](https://github.com/xacrimon/dashmap).
+///
+
+#ifndef SYNC_CELL_STRIPED_HASH_MAP_HPP
+#define SYNC_CELL_STRIPED_HASH_MAP_HPP
+
+#include <algorithm>
+#include <bit>
+#include <cstddef>
+#include <cstdint>
+#include <functional>
+#include <memory>
+#include <optional>
+#include <unordered_map>
+#include <utility>
+
+#include "lock/rw_spin_lock.hpp"
+#include "shared/compiler_workaround.hpp"
+#include "util/back_off.hpp"
+#include "util/cache_padded.hpp"
+
+
+namespace sc::map {
+
+/// @brief A hash map whose keys are hashed to one of the shards, each is a @c std::unordered_map
+/// guarded by its own @c lock::RwSpinLock. The operations on the keys of different shards do not
+/// contend, unlike a @c SyncCell or a lock around the whole map.
+///
+/// The values are never referenced outside the lock: they are copied out by @c get, or accessed in
+/// the callbacks of @c with, @c with_mut and @c upsert, which run while holding the shard lock. So
+/// keep the callbacks short, and do not access the same map in them (it may deadlock on the lock of
+/// the same shard).
+///
+/// @example
+/// ``` cpp
+/// sc::map::StripedHashMap<std::string, uint64_t> hits;
+/// hits.upsert("/index", [](uint64_t &v) { ++v; });
+/// hits.with("/index", [](uint64_t v) { std::cout << v; });
+/// ```
+/// @tparam K The key type.
+/// @tparam V The value type.
+/// @tparam Hash The hash function of the keys, also used to select the shard.
+/// @tparam KeyEqual The equality of the keys.
+/// @tparam Backoff The backoff when the shard lock is held by another thread.
+template<typename K, typename V, typename Hash = std::hash<K>, typename KeyEqual = std::equal_to<K>,
+        typename Backoff = util::Backoff>
+class StripedHashMap
+{
+    using Map = std::unordered_map<K, V, Hash, KeyEqual>;
+    using Shard = util::CachePadded<lock::RwSpinLock<Map, Backoff>>;
+
+public:
+    using key_type = K;
+    using mapped_type = V;
+
+    static constexpr size_t DefaultShardCount = 64;
+
+    /// @brief Creates an empty map with at least 'shard_count' shards, rounded up to a power of 2.
+    explicit StripedHashMap(size_t shard_count = DefaultShardCount)
+            : shard_count_(std::bit_ceil(std::max<size_t>(shard_count, 1))),
+              shift_(static_cast<uint32_t>(64 - std::countr_zero(shard_count_))),
+              shards_(std::make_unique<Shard[]>(shard_count_))
+    {
+    }
+
+    StripedHashMap(const StripedHashMap &) = delete;
+
+    StripedHashMap &operator=(const StripedHashMap &) = delete;
+
+    [[nodiscard]] size_t shard_count() const noexcept
+    {
+        return shard_count_;
+    }
+
+    /// @brief Inserts the value if the key does not exist.
+    /// @return false if the key exists, in this case, the value is not touched.
+    bool insert(key_type key, mapped_type value)
+    {
+        auto guard = shard_of(key).write();
+        return guard->try_emplace(std::move(key), std::move(value)).second;
+    }
+
+    /// @brief Inserts the value, or assigns it to the existing value of the key.
+    /// @return true if the key is inserted, false if assigned.
+    bool insert_or_assign(key_type key, mapped_type value)
+    {
+        auto guard = shard_of(key).write();
+        return guard->insert_or_assign(std::move(key), std::move(value)).second;
+    }
+
+    /// @brief Returns a copy of the value of the key, or an empty optional if the key does not exist.
+    [[nodiscard]] std::optional<mapped_type> get(const key_type &key) const
+    {
+        auto guard = shard_of(key).read();
+        auto it = guard->find(key);
+        if (it == guard->end()) {
+            return {};
+        }
+        return it->second;
+    }
+
+    [[nodiscard]] bool contains(const key_type &key) const
+    {
+        return shard_of(key).read()->contains(key);
+    }
+
+    /// @brief Removes the key.
+    /// @return The removed value, or an empty optional if the key does not exist.
+    std::optional<mapped_type> erase(const key_type &key)
+    {
+        auto guard = shard_of(key).write();
+        auto it = guard->find(key);
+        if (it == guard->end()) {
+            return {};
+        }
+        std::optional<mapped_type> value(std::move(it->second));
+        guard->erase(it);
+        return value;
+    }
+
+    /// @brief Calls 'f' with the value of the key, holding the shared lock of the shard.
+    /// @param f The function with the signature of 'void(const V &)'.
+    /// @return false if the key does not exist, in this case, 'f' is not called.
+    template<typename F>
+    bool with(const key_type &key, F &&f) const
+    {
+        auto guard = shard_of(key).read();
+        auto it = guard->find(key);
+        if (it == guard->end()) {
+            return false;
+        }
+        f(std::as_const(it->second));
+        return true;
+    }
+
+    /// @brief Calls 'f' with the mutable value of the key, holding the exclusive lock of the shard.
+    /// @param f The function with the signature of 'void(V &)'.
+    /// @return false if the key does not exist, in this case, 'f' is not called.
+    template<typename F>
+    bool with_mut(const key_type &key, F &&f)
+    {
+        auto guard = shard_of(key).write();
+        auto it = guard->find(key);
+        if (it == guard->end()) {
+            return false;
+        }
+        f(it->second);
+        return true;
+    }
+
+    /// @brief Calls 'f' with the mutable value of the key, which is default constructed first if the
+    /// key does not exist, holding the exclusive lock of the shard.
+    /// @param f The function with the signature of 'void(V &)'.
+    template<typename F>
+    void upsert(key_type key, F &&f)
+    {
+        auto guard = shard_of(key).write();
+        f((*guard)[std::move(key)]);
+    }
+
+    /// @brief Returns the count of the keys. The shards are counted one by one, so it is not
+    /// a snapshot if other threads are modifying the map.
+    [[nodiscard]] size_t size() const
+    {
+        size_t size = 0;
+        for (size_t i = 0; i < shard_count_; ++i) {
+            size += shards_[i]->read()->size();
+        }
+        return size;
+    }
+
+    [[nodiscard]] bool empty() const
+    {
+        return size() == 0;
+    }
+
+    void clear()
+    {
+        for (size_t i = 0; i < shard_count_; ++i) {
+            shards_[i]->write()->clear();
+        }
+    }
+
+    /// @brief Calls 'f' with each key and value, holding the shared lock of one shard at a time. The
+    /// keys inserted or removed by other threads meanwhile may or may not be visited.
+    /// @param f The function with the signature of 'void(const K &, const V &)'.
+    template<typename F>
+    void for_each(F &&f) const
+    {
+        for (size_t i = 0; i < shard_count_; ++i) {
+            auto guard = shards_[i]->read();
+            for (auto &[k, v]: *guard) {
+                f(k, v);
+            }
+        }
+    }
+
+private:
+    /// @brief Selects the shard by the upper bits of the mixed hash (the Fibonacci hashing), which
+    /// are independent of the lower bits used by the buckets of the @c std::unordered_map.
+    lock::RwSpinLock<Map, Backoff> &shard_of(const key_type &key) const
+    {
+        auto h = static_cast<uint64_t>(hash_(key)) * 0x9E3779B97F4A7C15ull;
+        auto index = shard_count_ == 1 ? 0 : static_cast<size_t>(h >> shift_);
+        return *shards_[index];
+    }
+
+    size_t shard_count_;
+    /// @brief The shift to take the upper 'log2(shard_count_)' bits of a 64-bit hash.
+    uint32_t shift_;
+    std::unique_ptr<Shard[]> shards_;
+    SC_NO_UNIQUE_ADDRESS Hash hash_;
+};
+
+}
+
+#endif //SYNC_CELL_STRIPED_HASH_MAP_HPP
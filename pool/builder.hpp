@@ -0,0 +1,201 @@
+///
+/// @file  builder.hpp
+/// @brief The builder of the thread pool, which configures the workers and pins them to the cores.
+///
+
+#ifndef SYNC_CELL_POOL_BUILDER_HPP
+#define SYNC_CELL_POOL_BUILDER_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The builder.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <algorithm>
+#include <cstddef>
+#include <functional>
+#include <memory>
+#include <optional>
+#include <thread>
+#include <type_traits>
+#include <utility>
+#include <vector>
+
+#include "pool/thread_pool.hpp"
+#include "shared/affinity.hpp"
+#include "shared/numa.hpp"
+
+
+namespace sc::pool {
+
+/// @brief The cores each worker of the pool is pinned to. The worker 'i' takes the 'i % n'-th of
+/// the 'n' entries of the selection.
+class CoreSelection
+{
+public:
+    /// @brief Pins each worker to one core of the list.
+    static CoreSelection cores(const std::vector<int> &cores)
+    {
+        CoreSelection selection;
+        for (auto core: cores) {
+            selection.sets_.push_back({core});
+            selection.nodes_.push_back(numa::AnyNode);
+        }
+        return selection;
+    }
+
+    /// @brief Pins each worker to all cores of a NUMA node of the list, and gives the node to the
+    /// pool as the hint of the worker deque. The workers are placed on the nodes in turn.
+    static CoreSelection numa_nodes(const std::vector<int> &nodes)
+    {
+        CoreSelection selection;
+        for (auto node: nodes) {
+            selection.sets_.push_back(affinity::node_cores(node));
+            selection.nodes_.push_back(node);
+        }
+        return selection;
+    }
+
+    /// @brief Pins each worker to one of the cores allowed for the current thread, in the order
+    /// of the core IDs, so the workers are spread over the cores.
+    static CoreSelection spread()
+    {
+        return cores(affinity::allowed_cores());
+    }
+
+    /// @brief Returns the cores of the worker 'index', empty if it is not pinned.
+    [[nodiscard]] const std::vector<int> &cores_of(size_t index) const noexcept
+    {
+        static const std::vector<int> none;
+        return sets_.empty() ? none : sets_[index % sets_.size()];
+    }
+
+    /// @brief Returns the NUMA node of the worker 'index', @c numa::AnyNode if it is unknown.
+    [[nodiscard]] int node_of(size_t index) const noexcept
+    {
+        return nodes_.empty() ? numa::AnyNode : nodes_[index % nodes_.size()];
+    }
+
+    [[nodiscard]] bool is_empty() const noexcept
+    {
+        return sets_.empty();
+    }
+
+private:
+    std::vector<std::vector<int>> sets_;
+    std::vector<int> nodes_;
+};
+
+/// @brief Configures and starts a thread pool, instead of the long list of the constructor
+/// parameters:
+/// ``` cpp
+/// auto pool = sc::pool::Builder()
+///         .threads(16)
+///         .pin_workers(sc::pool::CoreSelection::numa_nodes({0, 1}))
+///         .build();
+/// pool->spawn([&] { process(request); });
+/// ```
+/// @tparam Pool The @c BasicThreadPool to build.
+template<typename Pool>
+class BasicBuilder
+{
+public:
+    using policy_type = typename Pool::policy_type;
+
+    /// @brief The count of the workers, the hardware concurrency by default.
+    BasicBuilder &threads(size_t count) noexcept
+    {
+        thread_count_ = count;
+        return *this;
+    }
+
+    /// @brief The NUMA node hints of the worker deques, instead of the nodes of the pinning.
+    BasicBuilder &numa_nodes(std::vector<int> nodes)
+    {
+        numa_nodes_ = std::move(nodes);
+        return *this;
+    }
+
+    BasicBuilder &lifo_slot(bool enabled) noexcept
+    {
+        lifo_slot_ = enabled;
+        return *this;
+    }
+
+    /// @brief The steal policy copied to each worker. Without it, a @c LocalitySteal pool is given
+    /// the first core of each worker of the @c pin_workers selection.
+    BasicBuilder &steal_policy(policy_type policy)
+    {
+        policy_ = std::move(policy);
+        return *this;
+    }
+
+    /// @brief Pins each worker to the cores of the 'selection' when it starts, so the workers stay
+    /// on the same cores from run to run.
+    ///
+    /// A worker whose cores can not be pinned, e.g. not allowed for the process, runs unpinned.
+    /// Without @c SC_HAS_AFFINITY no worker is pinned.
+    BasicBuilder &pin_workers(CoreSelection selection)
+    {
+        selection_ = std::move(selection);
+        return *this;
+    }
+
+    /// @brief Returns the steal policy given to the pool by @c build: the one set by
+    /// @c steal_policy, or the default one, which is a @c LocalitySteal knowing the cores of the
+    /// pinned workers for a @c LocalitySteal pool.
+    [[nodiscard]] policy_type resolved_steal_policy() const
+    {
+        if (policy_) {
+            return *policy_;
+        }
+        if constexpr(std::is_same_v<policy_type, LocalitySteal>) {
+            std::vector<int> cores;
+            for (size_t i = 0; i < thread_count(); ++i) {
+                auto &set = selection_.cores_of(i);
+                cores.push_back(set.empty() ? -1 : set.front());
+            }
+            return LocalitySteal(std::move(cores));
+        } else {
+            return policy_type();
+        }
+    }
+
+    /// @brief Starts the pool. The pool can not be moved, since its workers refer to it.
+    [[nodiscard]] std::unique_ptr<Pool> build() const
+    {
+        auto count = thread_count();
+        auto nodes = numa_nodes_;
+        if (nodes.empty() && !selection_.is_empty()) {
+            for (size_t i = 0; i < count; ++i) {
+                nodes.push_back(selection_.node_of(i));
+            }
+        }
+        std::function<void(size_t)> on_start;
+        if (!selection_.is_empty()) {
+            on_start = [selection = selection_](size_t index) {
+                affinity::pin_current_thread(selection.cores_of(index));
+            };
+        }
+        return std::make_unique<Pool>(count, nodes, lifo_slot_, resolved_steal_policy(), std::move(on_start));
+    }
+
+private:
+    [[nodiscard]] size_t thread_count() const noexcept
+    {
+        return std::max<size_t>(thread_count_.value_or(std::thread::hardware_concurrency()), 1);
+    }
+
+    std::optional<size_t> thread_count_;
+    std::vector<int> numa_nodes_;
+    bool lifo_slot_ = true;
+    std::optional<policy_type> policy_;
+    CoreSelection selection_;
+};
+
+using Builder = BasicBuilder<ThreadPool>;
+
+}
+
+#endif //SYNC_CELL_POOL_BUILDER_HPP
@@ -0,0 +1,275 @@
+///
+/// @file  object_pool.hpp
+/// @brief An object pool recycling the memory blocks through the per-thread caches.
+///
+
+#ifndef SYNC_CELL_OBJECT_POOL_HPP
+#define SYNC_CELL_OBJECT_POOL_HPP
+
+#include <cstddef>
+#include <memory>
+#include <new>
+#include <type_traits>
+#include <utility>
+#include <vector>
+
+#include "cell/thread_sharded_cell.hpp"
+#include "lock/spin_lock.hpp"
+#include "shared/compiler_workaround.hpp"
+
+
+namespace sc::pool {
+
+/// @brief Default count of the free blocks cached by each thread of an @c ObjectPool.
+inline constexpr size_t DefaultLocalCapacity = 64;
+
+/// @brief A pool of the memory blocks of 'T', e.g. for the nodes of a linked structure, or the
+/// payloads passed through a queue.
+///
+/// A released block is cached by the releasing thread, and the next allocation of the thread takes
+/// it without any synchronization. When the cache of a thread is full, its blocks are moved to the
+/// global list as one batch under a spin lock, and a thread with an empty cache takes a whole batch
+/// back. So a producer thread allocating the nodes and a consumer thread releasing them exchange
+/// the blocks by batches, and the allocator is only called when all caches are empty.
+///
+/// The blocks are returned to the allocator when the pool is destroyed, all objects must have been
+/// released by then. The pool keeps the peak count of the blocks until it is destroyed.
+///
+/// @example
+/// ``` cpp
+/// sc::pool::ObjectPool<Request> requests;
+/// auto request = requests.make(id, body);     // A std::unique_ptr releasing to the pool.
+/// queue.enqueue(std::move(request));
+/// ```
+/// @tparam T The object type.
+/// @tparam Allocator The allocator of the blocks, rebound to the block type.
+/// @tparam LocalCapacity The count of the free blocks cached by each thread.
+template<typename T, typename Allocator = std::allocator<T>, size_t LocalCapacity = DefaultLocalCapacity>
+class ObjectPool
+{
+    static_assert(!std::is_void_v<T> && !std::is_reference_v<T>);
+    static_assert(LocalCapacity > 0);
+
+    /// @brief The storage of an object, or the link of a free block.
+    union Block
+    {
+        Block *next;
+        alignas(T) std::byte storage[sizeof(T)];
+    };
+
+    using BlockAlloc = typename std::allocator_traits<Allocator>::template rebind_alloc<Block>;
+    using BlockTrait = std::allocator_traits<BlockAlloc>;
+    using ValueAlloc = typename std::allocator_traits<Allocator>::template rebind_alloc<T>;
+    using ValueTrait = std::allocator_traits<ValueAlloc>;
+
+    /// @brief A list of the free blocks.
+    struct FreeList
+    {
+        Block *head = nullptr;
+        size_t len = 0;
+
+        void push(Block *block) noexcept
+        {
+            block->next = head;
+            head = block;
+            ++len;
+        }
+
+        Block *pop() noexcept
+        {
+            auto *block = head;
+            head = block->next;
+            --len;
+            return block;
+        }
+    };
+
+public:
+    using value_type = T;
+    using allocator_type = Allocator;
+
+    /// @brief Releases the object to its pool, the deleter of the @c Ptr.
+    struct Deleter
+    {
+        ObjectPool *pool;
+
+        void operator()(T *p) const noexcept
+        {
+            pool->dealloc(p);
+        }
+    };
+
+    /// @brief An object owned by a @c std::unique_ptr, which releases it to the pool.
+    using Ptr = std::unique_ptr<T, Deleter>;
+
+    ObjectPool() = default;
+
+    explicit ObjectPool(const Allocator &alloc) : alloc_(alloc) { }
+
+    ObjectPool(const ObjectPool &) = delete;
+
+    ObjectPool &operator=(const ObjectPool &) = delete;
+
+    ~ObjectPool()
+    {
+        locals_.for_each([this](FreeList &list) { release(list); });
+        auto global = global_.lock();
+        for (auto &list: *global) {
+            release(list);
+        }
+    }
+
+    [[nodiscard]] allocator_type get_allocator() const noexcept
+    {
+        return allocator_type(alloc_);
+    }
+
+    /// @brief Gets an uninitialized block for one 'T'.
+    [[nodiscard]] T *allocate()
+    {
+        auto &local = locals_.get_or_default();
+        if (local.head == nullptr) {
+            refill(local);
+        }
+        auto *block = local.head != nullptr ? local.pop() : BlockTrait::allocate(alloc_, 1);
+        return reinterpret_cast<T *>(block->storage);
+    }
+
+    /// @brief Returns a block from @c allocate, its object must have been destroyed.
+    void deallocate(T *p) noexcept
+    {
+        auto *block = reinterpret_cast<Block *>(p);
+        FreeList *local;
+        try {
+            local = &locals_.get_or_default();
+        } catch (...) {
+            // The cache of the thread can not be created.
+            BlockTrait::deallocate(alloc_, block, 1);
+            return;
+        }
+
+        if (local->len >= LocalCapacity && !flush(*local)) {
+            BlockTrait::deallocate(alloc_, block, 1);
+            return;
+        }
+        local->push(block);
+    }
+
+    /// @brief Creates an object in a pooled block.
+    template<typename... Args>
+    [[nodiscard]] T *alloc(Args &&... args)
+    {
+        auto *p = allocate();
+        try {
+            ValueAlloc value_alloc(alloc_);
+            ValueTrait::construct(value_alloc, p, std::forward<Args>(args)...);
+        } catch (...) {
+            deallocate(p);
+            throw;
+        }
+        return p;
+    }
+
+    /// @brief Destroys an object from @c alloc, and returns its block to the pool.
+    void dealloc(T *p) noexcept
+    {
+        ValueAlloc value_alloc(alloc_);
+        ValueTrait::destroy(value_alloc, p);
+        deallocate(p);
+    }
+
+    /// @brief Creates an object owned by a @c Ptr.
+    template<typename... Args>
+    [[nodiscard]] Ptr make(Args &&... args)
+    {
+        return Ptr(alloc(std::forward<Args>(args)...), Deleter{this});
+    }
+
+private:
+    /// @brief Takes a batch from the global list into the empty cache.
+    void refill(FreeList &local)
+    {
+        auto global = global_.lock();
+        if (!global->empty()) {
+            local = global->back();
+            global->pop_back();
+        }
+    }
+
+    /// @brief Moves the blocks of the cache to the global list as one batch.
+    /// @return false if the global list can not grow.
+    bool flush(FreeList &local) noexcept
+    {
+        auto global = global_.lock();
+        try {
+            global->push_back(local);
+        } catch (...) {
+            return false;
+        }
+        local = FreeList();
+        return true;
+    }
+
+    void release(FreeList &list) noexcept
+    {
+        while (list.head != nullptr) {
+            BlockTrait::deallocate(alloc_, list.pop(), 1);
+        }
+    }
+
+    SC_NO_UNIQUE_ADDRESS BlockAlloc alloc_;
+    ThreadShardedCell<FreeList> locals_;
+    lock::SpinLock<std::vector<FreeList>> global_;
+};
+
+/// @brief A stateless allocator taking the single objects from a process-wide @c ObjectPool of each
+/// type, e.g. for the nodes of the containers:
+/// ``` cpp
+/// sc::mpmc::LinkedListQueue<Job, 0, sc::pool::PoolAllocator<Job>> jobs;
+/// sc::stack::LockFreeStack<Job, sc::epoch::Reclaimer, sc::pool::PoolAllocator<Job>> free_jobs;
+/// ```
+///
+/// The arrays are allocated by the @c std::allocator. The pools are never destroyed, so a block
+/// can be released at any time, e.g. by the reclamation backend after its container is destroyed.
+template<typename T>
+struct PoolAllocator
+{
+    using value_type = T;
+
+    constexpr PoolAllocator() noexcept = default;
+
+    template<typename U>
+    constexpr PoolAllocator(const PoolAllocator<U> &) noexcept { }
+
+    [[nodiscard]] T *allocate(size_t n)
+    {
+        return n == 1 ? shared_pool().allocate() : std::allocator<T>().allocate(n);
+    }
+
+    void deallocate(T *p, size_t n) noexcept
+    {
+        if (n == 1) {
+            shared_pool().deallocate(p);
+        } else {
+            std::allocator<T>().deallocate(p, n);
+        }
+    }
+
+    /// @brief Returns the process-wide pool of 'T'.
+    static ObjectPool<T> &shared_pool()
+    {
+        // Leaked on purpose, the blocks may be released during the static destruction.
+        static auto *pool = new ObjectPool<T>();
+        return *pool;
+    }
+
+    template<typename U>
+    constexpr bool operator==(const PoolAllocator<U> &) const noexcept
+    {
+        return true;
+    }
+};
+
+}
+
+#endif //SYNC_CELL_OBJECT_POOL_HPP
@@ -0,0 +1,142 @@
+///
+/// @file  steal_policy.hpp
+/// @brief The policies choosing the order in which an idle worker of the thread pool visits the
+/// other workers to steal from.
+///
+
+#ifndef SYNC_CELL_STEAL_POLICY_HPP
+#define SYNC_CELL_STEAL_POLICY_HPP
+
+#include <algorithm>
+#include <concepts>
+#include <cstddef>
+#include <cstdint>
+#include <cstdlib>
+#include <tuple>
+#include <utility>
+#include <vector>
+
+#include "shared/numa.hpp"
+
+
+namespace sc::pool {
+
+/// @brief The workers in the ring order after the 'index': 'index + 1', 'index + 2', ...
+inline std::vector<size_t> ring_victims(size_t index, size_t count)
+{
+    std::vector<size_t> victims;
+    victims.reserve(count > 0 ? count - 1 : 0);
+    for (size_t i = 1; i < count; ++i) {
+        victims.push_back((index + i) % count);
+    }
+    return victims;
+}
+
+/// @brief Visits the other workers in the ring order, always from the next one. It is the
+/// default, and fits a few workers on one socket.
+class RoundRobinSteal
+{
+public:
+    std::vector<size_t> victims(size_t index, const std::vector<int> &numa_nodes)
+    {
+        return ring_victims(index, numa_nodes.size());
+    }
+
+    size_t start(size_t) noexcept
+    {
+        return 0;
+    }
+};
+
+/// @brief Visits the other workers in the ring order from a random one in each sweep, as
+/// crossbeam and tokio do, so the thieves do not contend on the same victim.
+class RandomSteal
+{
+public:
+    std::vector<size_t> victims(size_t index, const std::vector<int> &numa_nodes)
+    {
+        state_ = (index + 1) * 0x9E3779B97F4A7C15ull | 1;
+        return ring_victims(index, numa_nodes.size());
+    }
+
+    size_t start(size_t count) noexcept
+    {
+        // xorshift64.
+        state_ ^= state_ << 13;
+        state_ ^= state_ >> 7;
+        state_ ^= state_ << 17;
+        return count > 0 ? static_cast<size_t>(state_ % count) : 0;
+    }
+
+private:
+    uint64_t state_ = 1;
+};
+
+/// @brief Visits the workers on the same NUMA node first, and among them the ones on the nearer
+/// cores first, so the stolen jobs and their data rarely cross the sockets.
+///
+/// The nodes are the NUMA node hints passed to the pool, and the cores are the IDs of the cores
+/// the workers are pinned to, which the @c BasicBuilder takes from its @c pin_workers selection
+/// unless a policy is given. The workers at the same distance are visited in the ring order.
+/// ``` cpp
+/// using Pool = sc::pool::BasicThreadPool<sc::deque::Worker<sc::pool::JobPtr>,
+///                                        sc::mpmc::ArrayListQueue<sc::pool::JobPtr>, sc::pool::LocalitySteal>;
+/// auto pool = sc::pool::BasicBuilder<Pool>()
+///         .threads(4)
+///         .pin_workers(sc::pool::CoreSelection::cores({0, 1, 32, 33}))
+///         .build();
+/// ```
+class LocalitySteal
+{
+public:
+    LocalitySteal() = default;
+
+    /// @param cores The core ID of each worker, a negative one is unknown.
+    explicit LocalitySteal(std::vector<int> cores) : cores_(std::move(cores)) { }
+
+    std::vector<size_t> victims(size_t index, const std::vector<int> &numa_nodes)
+    {
+        auto victims = ring_victims(index, numa_nodes.size());
+        auto distance = [&](size_t victim) {
+            bool same_node = numa_nodes[index] != numa::AnyNode && numa_nodes[index] == numa_nodes[victim];
+            return std::make_tuple(!same_node, core_distance(index, victim));
+        };
+        std::stable_sort(victims.begin(), victims.end(),
+                         [&](size_t a, size_t b) { return distance(a) < distance(b); });
+        return victims;
+    }
+
+    size_t start(size_t) noexcept
+    {
+        return 0;
+    }
+
+private:
+    [[nodiscard]] int core_distance(size_t a, size_t b) const noexcept
+    {
+        if (a >= cores_.size() || b >= cores_.size() || cores_[a] < 0 || cores_[b] < 0) {
+            return 0;
+        }
+        return std::abs(cores_[a] - cores_[b]);
+    }
+
+    std::vector<int> cores_;
+};
+
+/// @brief The order in which an idle worker of the thread pool visits the others to steal from.
+/// Each worker owns a copy of the policy, and calls:
+/// * <tt>std::vector<size_t> victims(size_t index, const std::vector<int> &numa_nodes)</tt>: once
+///   at the start, returns the indices of the other workers in the visiting order. The
+///   'numa_nodes' has the node hint of each worker, @c numa::AnyNode if it is unknown.
+/// * <tt>size_t start(size_t count)</tt>: before each sweep over the 'count' victims, returns the
+///   position in the victims to start from, the sweep wraps around to visit all of them.
+template<typename P>
+concept StealPolicy = std::copy_constructible<P> &&
+                      requires(P &policy, size_t index, const std::vector<int> &numa_nodes, size_t count) {
+    { policy.victims(index, numa_nodes) } -> std::same_as<std::vector<size_t>>;
+    { policy.start(count) } -> std::convertible_to<size_t>;
+};
+
+}
+
+#endif //SYNC_CELL_STEAL_POLICY_HPP
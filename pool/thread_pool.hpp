@@ -0,0 +1,722 @@
+///
+/// @file  thread_pool.hpp
+/// @brief A work-stealing thread pool built on the global injector queue and the per-worker deques.
+///
+
+#ifndef SYNC_CELL_THREAD_POOL_HPP
+#define SYNC_CELL_THREAD_POOL_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The thread_pool.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <algorithm>
+#include <atomic>
+#include <chrono>
+#include <condition_variable>
+#include <cstddef>
+#include <cstdint>
+#include <functional>
+#include <memory>
+#include <mutex>
+#include <optional>
+#include <thread>
+#include <type_traits>
+#include <utility>
+#include <vector>
+
+#include "deque/work_stealing_deque.hpp"
+#include "pool/steal_policy.hpp"
+#include "queue/mpmc_array_queue.hpp"
+#include "queue/queue_backend.hpp"
+#include "shared/numa.hpp"
+#include "util/back_off.hpp"
+#include "util/cache_padded.hpp"
+
+
+namespace sc::pool {
+
+namespace impl {
+
+/// @brief A type-erased job. The deques only hold the trivially copyable tasks, so the jobs are
+/// passed as the pointers, and deleted after they run.
+struct Job
+{
+    virtual ~Job() = default;
+
+    virtual void run() = 0;
+
+    /// @brief Called instead of the @c run when the job is dropped by an aborting shutdown.
+    virtual void drop() noexcept { }
+};
+
+template<typename F>
+struct FnJob final : Job
+{
+    F f;
+
+    template<typename G>
+    explicit FnJob(G &&g) : f(std::forward<G>(g)) { }
+
+    void run() override
+    {
+        std::move(f)();
+    }
+};
+
+/// @brief A job of a scope, whose 'f' takes whether to run the job body, and completes the scope
+/// either way, so a dropped job does not block the scope.
+template<typename F>
+struct ScopeJob final : Job
+{
+    F f;
+
+    template<typename G>
+    explicit ScopeJob(G &&g) : f(std::forward<G>(g)) { }
+
+    void run() override
+    {
+        std::move(f)(true);
+    }
+
+    void drop() noexcept override
+    {
+        std::move(f)(false);
+    }
+};
+
+}
+
+/// @brief How the @c ThreadPool::shutdown treats the jobs not started yet.
+enum class Grace
+{
+    /// @brief Runs all spawned jobs, including the ones they spawn.
+    Drain,
+    /// @brief Drops the jobs not started yet, only the running ones complete.
+    Abort,
+};
+
+/// @brief The counts of the jobs of a pool, returned by the @c ThreadPool::shutdown.
+struct ShutdownReport
+{
+    /// @brief The jobs which have run, over the lifetime of the pool.
+    size_t completed;
+    /// @brief The jobs dropped without running by an aborting shutdown.
+    size_t dropped;
+};
+
+/// @brief The item type of the queue backends of a pool, a pointer to a type-erased job.
+using JobPtr = impl::Job *;
+
+template<typename Pool>
+class BasicScope;
+
+/// @brief A fixed-size thread pool, whose workers run the spawned jobs.
+///
+/// The jobs spawned by a non-worker thread are pushed to the global injector queue
+/// (@c sc::mpmc::ArrayListQueue), and the jobs spawned by a running job are pushed to the deque of
+/// its worker (@c sc::deque::Worker). An idle worker pops its own deque first, then the injector,
+/// and at last steals from the deques of the other workers. The workers found nothing to run are
+/// parked by a condition variable.
+///
+/// @example
+/// ``` cpp
+/// sc::pool::ThreadPool pool(4);
+/// pool.spawn([&] { process(request); });
+/// pool.join();
+/// ```
+///
+/// The queues are the @c sc::QueueBackend parameters, e.g. to feed the workers from a bounded
+/// injector, or from a third-party queue:
+/// ``` cpp
+/// sc::pool::BasicThreadPool<sc::deque::Worker<sc::pool::JobPtr>,
+///                           sc::mpmc::FixedBoundedQueue<sc::pool::JobPtr, 4096>> pool(4);
+/// ```
+/// A job pushed to a full local queue goes to the injector, and a spawn is rejected if the
+/// injector is full too.
+///
+/// Each worker has a LIFO slot, which holds the job spawned last by its running job, and which the
+/// worker runs next, while it is hot in the cache. A job spawned later moves the previous one to
+/// the queue. The owner runs at most @c MaxLifoRuns jobs in a row from the slot, so a job
+/// respawning itself does not starve the queue, and the idle workers steal from the slot only
+/// after the queues.
+///
+/// An idle worker visits the others in the order of the @c StealPolicy: the next ones in the ring
+/// by default, from a random one by the @c RandomSteal, or the nearer ones first on a NUMA machine
+/// by the @c LocalitySteal.
+/// @note A job must not throw, an exception escaping from it terminates the program.
+/// @tparam Local The queue of each worker, whose stealers are used by the other workers.
+/// @tparam Injector The queue of the jobs spawned by the non-worker threads.
+/// @tparam Policy The order of the victims of the stealing workers.
+template<PoolBackend Local = deque::Worker<JobPtr>, PoolBackend Injector = mpmc::ArrayListQueue<JobPtr>,
+        StealPolicy Policy = RoundRobinSteal>
+class BasicThreadPool
+{
+    static_assert(std::is_same_v<typename QueueBackendTraits<Local>::value_type, JobPtr> &&
+                  std::is_same_v<typename QueueBackendTraits<Injector>::value_type, JobPtr>,
+                  "The queue backends of the pool must hold the sc::pool::JobPtr.");
+
+    friend class BasicScope<BasicThreadPool>;
+
+    using LocalBackend = QueueBackendTraits<Local>;
+    using InjectorBackend = QueueBackendTraits<Injector>;
+
+    /// @brief The lowest bit of the 'state_' is set after @c shutdown, the others count the
+    /// external spawns in progress.
+    static constexpr size_t Closed = 1;
+    static constexpr size_t InFlightOne = 2;
+    /// @brief The most jobs run in a row from the LIFO slot of a worker, the same as tokio.
+    static constexpr uint32_t MaxLifoRuns = 3;
+
+    /// @brief The job is taken by the owner or the thieves by an exchange, and only the owner puts
+    /// a job into the empty slot. The 'runs' is accessed by the owner only.
+    struct LifoSlot
+    {
+        std::atomic<JobPtr> job{nullptr};
+        uint32_t runs = 0;
+    };
+
+    /// @brief The stealing state of a worker, accessed by the owner only.
+    struct Thief
+    {
+        Policy policy;
+        std::vector<size_t> victims;
+    };
+
+    struct Context
+    {
+        const BasicThreadPool *pool;
+        size_t index;
+    };
+
+public:
+    using Scope = BasicScope<BasicThreadPool>;
+    using policy_type = Policy;
+
+    /// @brief Starts the worker threads.
+    /// @param thread_count The count of the workers, at least one.
+    /// @param numa_nodes The NUMA node hints of the worker deques, the worker 'i' takes the
+    /// 'numa_nodes[i % numa_nodes.size()]'. The pool does not pin the threads, so the hints are
+    /// useful together with an affinity set by the caller, or by the @c Builder::pin_workers.
+    /// Ignored without @c SC_HAS_NUMA.
+    /// @param lifo_slot Whether the nested spawns go through the LIFO slots, false pushes them to
+    /// the local queues in order.
+    /// @param policy The steal policy, copied to each worker.
+    /// @param on_worker_start Called by each worker thread with its index before it runs any job,
+    /// e.g. to pin the thread.
+    explicit BasicThreadPool(size_t thread_count = std::thread::hardware_concurrency(),
+                             const std::vector<int> &numa_nodes = {},
+                             bool lifo_slot = true,
+                             const Policy &policy = Policy(),
+                             std::function<void(size_t)> on_worker_start = {})
+            : injector_(InjectorBackend::create(numa::AnyNode)),
+              lifo_slot_(lifo_slot),
+              on_worker_start_(std::move(on_worker_start))
+    {
+        thread_count = std::max<size_t>(thread_count, 1);
+        slots_ = std::make_unique<util::CachePadded<LifoSlot>[]>(thread_count);
+        locals_.reserve(thread_count);
+        stealers_.reserve(thread_count);
+        std::vector<int> nodes(thread_count);
+        for (size_t i = 0; i < thread_count; ++i) {
+            nodes[i] = numa_nodes.empty() ? numa::AnyNode : numa_nodes[i % numa_nodes.size()];
+            locals_.push_back(LocalBackend::create(nodes[i]));
+            stealers_.push_back(LocalBackend::stealer(*locals_.back()));
+        }
+        thieves_.reserve(thread_count);
+        for (size_t i = 0; i < thread_count; ++i) {
+            Thief thief{policy, {}};
+            thief.victims = thief.policy.victims(i, nodes);
+            thieves_.emplace_back(std::move(thief));
+        }
+        live_workers_ = thread_count;
+        threads_.reserve(thread_count);
+        for (size_t i = 0; i < thread_count; ++i) {
+            threads_.emplace_back([this, i] { run_worker(i); });
+        }
+    }
+
+    BasicThreadPool(const BasicThreadPool &) = delete;
+
+    BasicThreadPool &operator=(const BasicThreadPool &) = delete;
+
+    /// @brief Shuts down the pool gracefully and joins the workers.
+    ~BasicThreadPool()
+    {
+        join();
+    }
+
+    [[nodiscard]] size_t thread_count() const noexcept
+    {
+        return locals_.size();
+    }
+
+    /// @brief Returns true if @c shutdown has been called.
+    [[nodiscard]] bool is_shutdown() const noexcept
+    {
+        return (state_->load(std::memory_order_acquire) & Closed) != 0;
+    }
+
+    /// @brief Spawns a job, which is called once by a worker thread.
+    ///
+    /// After @c shutdown, the jobs spawned by the non-worker threads are rejected, while a running
+    /// job can still spawn, so a tree of jobs is always completed.
+    /// @return False if the job is rejected, after shutdown or by the full queues.
+    template<typename F>
+    bool spawn(F &&f)
+    {
+        static_assert(std::is_invocable_v<std::decay_t<F> &&>, "The job must be callable without arguments.");
+
+        std::unique_ptr<impl::Job> job = std::make_unique<impl::FnJob<std::decay_t<F>>>(std::forward<F>(f));
+        return push_job(job);
+    }
+
+    /// @brief Calls the 'f' with a @c Scope, and returns after all jobs spawned by the scope have
+    /// run, so the jobs can borrow the data on the stack of the caller.
+    ///
+    /// If it is called by a job of the pool, the worker runs the other jobs while waiting.
+    /// @example
+    /// ``` cpp
+    /// std::vector<int> results(inputs.size());
+    /// pool.scope([&](sc::pool::Scope &s) {
+    ///     for (size_t i = 0; i < inputs.size(); ++i) {
+    ///         s.spawn([&, i] { results[i] = compute(inputs[i]); });
+    ///     }
+    /// });
+    /// ```
+    /// @return The result of the 'f'.
+    template<typename F>
+    std::invoke_result_t<F &&, Scope &> scope(F &&f);
+
+    /// @brief Stops accepting the external jobs. The workers exit after all spawned jobs have run.
+    void shutdown()
+    {
+        state_->fetch_or(Closed, std::memory_order_seq_cst);
+        notify(true);
+    }
+
+    /// @brief Shuts down the pool, and blocks until the workers have exited.
+    ///
+    /// With the @c Grace::Drain it waits for all jobs as the @c join, with the @c Grace::Abort the
+    /// jobs not started yet are dropped. A dropped job of a @c Scope still completes the scope, so
+    /// the scope returns without running it.
+    /// @note It must not be called by a job of the pool.
+    ShutdownReport shutdown(Grace grace)
+    {
+        if (grace == Grace::Abort) {
+            abort();
+        }
+        join();
+        return report();
+    }
+
+    /// @brief Shuts down the pool as the @c shutdown_until, with the deadline after the 'timeout'.
+    template<typename Rep, typename Period>
+    ShutdownReport shutdown_for(Grace grace, const std::chrono::duration<Rep, Period> &timeout)
+    {
+        return shutdown_until(grace, std::chrono::steady_clock::now() + timeout);
+    }
+
+    /// @brief Shuts down the pool by the 'grace', and blocks until the workers have exited. A
+    /// draining shutdown drops the jobs left when the 'deadline' is reached.
+    ///
+    /// The running jobs can not be interrupted, so it returns after the deadline if a job runs
+    /// past it.
+    /// @note It must not be called by a job of the pool.
+    template<typename Clock, typename Duration>
+    ShutdownReport shutdown_until(Grace grace, const std::chrono::time_point<Clock, Duration> &deadline)
+    {
+        shutdown();
+        if (grace == Grace::Drain) {
+            std::unique_lock lock(mtx_);
+            if (!exited_.wait_until(lock, deadline, [this] { return live_workers_ == 0; })) {
+                lock.unlock();
+                abort();
+            }
+        }
+        return shutdown(grace);
+    }
+
+    /// @brief Shuts down the pool, and blocks until all jobs have run and the workers have exited.
+    /// @note It must not be called by a job of the pool.
+    void join()
+    {
+        shutdown();
+        for (auto &t: threads_) {
+            if (t.joinable()) {
+                t.join();
+            }
+        }
+    }
+
+private:
+    void abort()
+    {
+        aborting_.store(true, std::memory_order_seq_cst);
+        shutdown();
+    }
+
+    [[nodiscard]] ShutdownReport report() const noexcept
+    {
+        return {completed_.load(std::memory_order_acquire), dropped_.load(std::memory_order_acquire)};
+    }
+
+    /// @brief Returns the index of the current thread if it is a worker of this pool.
+    [[nodiscard]] std::optional<size_t> worker_index() const noexcept
+    {
+        auto *context = current_;
+        if (context != nullptr && context->pool == this) {
+            return context->index;
+        }
+        return {};
+    }
+
+    /// @brief Pushes the job to the LIFO slot or the queue of the current worker, or to the injector.
+    /// @return False if the job is rejected, which is still owned by the 'job' then.
+    bool push_job(std::unique_ptr<impl::Job> &job)
+    {
+        if (auto index = worker_index()) {
+            pending_->fetch_add(1, std::memory_order_seq_cst);
+            if (!(lifo_slot_ ? push_lifo(*index, job.get()) : push_local(*index, job.get()))) {
+                pending_->fetch_sub(1, std::memory_order_seq_cst);
+                return false;
+            }
+            job.release();
+            notify(false);
+            return true;
+        }
+
+        if ((state_->fetch_add(InFlightOne, std::memory_order_seq_cst) & Closed) != 0) {
+            end_external_spawn();
+            return false;
+        }
+        pending_->fetch_add(1, std::memory_order_seq_cst);
+        bool pushed = InjectorBackend::push(*injector_, job.get());
+        if (pushed) {
+            job.release();
+            notify(false);
+        } else {
+            pending_->fetch_sub(1, std::memory_order_seq_cst);
+        }
+        end_external_spawn();
+        return pushed;
+    }
+
+    bool push_local(size_t index, JobPtr job)
+    {
+        return LocalBackend::push(*locals_[index], std::move(job)) || InjectorBackend::push(*injector_, std::move(job));
+    }
+
+    /// @brief Puts the job into the LIFO slot of the worker, after moving the previous one to the queue.
+    bool push_lifo(size_t index, JobPtr job)
+    {
+        auto &slot = slots_[index]->job;
+        // Taken out first, so a thief can not take the previous job while it is in the queue too.
+        auto *prev = slot.exchange(nullptr, std::memory_order_acq_rel);
+        if (prev != nullptr && !push_local(index, prev)) {
+            // The slot is empty, since only the owner fills it.
+            slot.store(prev, std::memory_order_release);
+            return false;
+        }
+        slot.store(job, std::memory_order_release);
+        return true;
+    }
+
+    JobPtr take_lifo(size_t index)
+    {
+        auto &slot = slots_[index]->job;
+        if (slot.load(std::memory_order_relaxed) == nullptr) {
+            return nullptr;
+        }
+        return slot.exchange(nullptr, std::memory_order_acq_rel);
+    }
+
+    void end_external_spawn()
+    {
+        // The last spawn in progress after shutdown may complete the draining.
+        if (state_->fetch_sub(InFlightOne, std::memory_order_seq_cst) == (Closed | InFlightOne)) {
+            notify(true);
+        }
+    }
+
+    /// @brief Returns true if the pool is shut down and no job is left, so the workers can exit.
+    [[nodiscard]] bool is_drained() const noexcept
+    {
+        // No external spawn can start after the state is seen as closed and idle, and the completed
+        // ones have counted their jobs before leaving.
+        return state_->load(std::memory_order_seq_cst) == Closed && pending_->load(std::memory_order_seq_cst) == 0;
+    }
+
+    void notify(bool all)
+    {
+        if (sleepers_.load(std::memory_order_seq_cst) == 0) {
+            return;
+        }
+        // Lock to not notify between the check of a parking worker and its wait.
+        { std::lock_guard guard(mtx_); }
+        if (all) {
+            cond_var_.notify_all();
+        } else {
+            cond_var_.notify_one();
+        }
+    }
+
+    JobPtr find_job(size_t index)
+    {
+        auto &slot = *slots_[index];
+        if (slot.runs < MaxLifoRuns) {
+            if (auto *job = take_lifo(index)) {
+                ++slot.runs;
+                return job;
+            }
+        }
+        slot.runs = 0;
+        if (auto job = LocalBackend::pop(*locals_[index])) {
+            return *job;
+        }
+        if (auto job = InjectorBackend::pop(*injector_)) {
+            return *job;
+        }
+        if (auto *job = take_lifo(index)) {
+            return job;
+        }
+
+        auto &thief = *thieves_[index];
+        auto count = thief.victims.size();
+        while (true) {
+            bool retry = false;
+            auto start = thief.policy.start(count);
+            for (size_t i = 0; i < count; ++i) {
+                auto steal = steal_from(stealers_[thief.victims[(start + i) % count]], *locals_[index]);
+                if (steal.is_success()) {
+                    return *std::move(steal).success();
+                }
+                retry = retry || steal.is_retry();
+            }
+            if (!retry) {
+                break;
+            }
+        }
+        for (auto victim: thief.victims) {
+            if (auto *job = take_lifo(victim)) {
+                return job;
+            }
+        }
+        return nullptr;
+    }
+
+    /// @brief Steals a batch of jobs into the 'local' queue if the backend supports it, so an idle
+    /// worker takes many jobs from a busy one by one steal.
+    static deque::Steal<JobPtr> steal_from(const typename LocalBackend::stealer_type &stealer, Local &local)
+    {
+        if constexpr(requires { LocalBackend::steal_batch_and_pop(stealer, local); }) {
+            return LocalBackend::steal_batch_and_pop(stealer, local);
+        } else {
+            return LocalBackend::steal(stealer);
+        }
+    }
+
+    /// @brief Runs a job found by the worker of the 'index'.
+    /// @return False if no job is found.
+    bool run_one(size_t index)
+    {
+        auto *job = find_job(index);
+        if (job == nullptr) {
+            return false;
+        }
+        if (pending_->fetch_sub(1, std::memory_order_seq_cst) == 1 && is_shutdown()) {
+            // Wake the parked workers to exit.
+            notify(true);
+        }
+        std::unique_ptr<impl::Job> owned(job);
+        if (aborting_.load(std::memory_order_acquire)) {
+            owned->drop();
+            dropped_.fetch_add(1, std::memory_order_release);
+        } else {
+            owned->run();
+            completed_.fetch_add(1, std::memory_order_release);
+        }
+        return true;
+    }
+
+    void run_worker(size_t index)
+    {
+        if (on_worker_start_) {
+            on_worker_start_(index);
+        }
+        Context context{this, index};
+        current_ = &context;
+
+        util::Backoff backoff;
+        while (true) {
+            if (run_one(index)) {
+                backoff.reset();
+                continue;
+            }
+            if (pending_->load(std::memory_order_seq_cst) != 0) {
+                // A job is being pushed, or being taken by another worker.
+                backoff.snooze();
+                continue;
+            }
+            if (is_drained()) {
+                break;
+            }
+
+            std::unique_lock lock(mtx_);
+            sleepers_.fetch_add(1, std::memory_order_seq_cst);
+            cond_var_.wait(lock, [this] {
+                return pending_->load(std::memory_order_seq_cst) != 0 || is_drained();
+            });
+            sleepers_.fetch_sub(1, std::memory_order_relaxed);
+        }
+
+        current_ = nullptr;
+        {
+            std::lock_guard guard(mtx_);
+            --live_workers_;
+        }
+        exited_.notify_all();
+    }
+
+    /// @brief The worker context of the current thread, used to push the nested spawns locally.
+    static inline thread_local const Context *current_ = nullptr;
+
+    std::unique_ptr<Injector> injector_;
+    std::vector<std::unique_ptr<Local>> locals_;
+    std::vector<typename LocalBackend::stealer_type> stealers_;
+    std::unique_ptr<util::CachePadded<LifoSlot>[]> slots_;
+    std::vector<util::CachePadded<Thief>> thieves_;
+    bool lifo_slot_;
+    std::function<void(size_t)> on_worker_start_;
+    /// @brief Count of the spawned jobs not taken by a worker yet.
+    util::CachePadded<std::atomic<size_t>> pending_;
+    util::CachePadded<std::atomic<size_t>> state_;
+
+    std::atomic<size_t> sleepers_{0};
+    std::atomic<bool> aborting_{false};
+    std::atomic<size_t> completed_{0};
+    std::atomic<size_t> dropped_{0};
+    std::mutex mtx_;
+    std::condition_variable cond_var_;
+    /// @brief Notified when a worker exits, the 'live_workers_' is guarded by the 'mtx_'.
+    std::condition_variable exited_;
+    size_t live_workers_ = 0;
+    std::vector<std::thread> threads_;
+};
+
+/// @brief The handle passed to the function of @c ThreadPool::scope, which spawns the jobs that
+/// must complete before the scope returns.
+template<typename Pool>
+class BasicScope
+{
+    friend Pool;
+
+    explicit BasicScope(Pool &pool) noexcept : pool_(pool) { }
+
+public:
+    BasicScope(const BasicScope &) = delete;
+
+    BasicScope &operator=(const BasicScope &) = delete;
+
+    /// @brief Spawns a job of the scope. The job is called with no argument, or with this scope to
+    /// spawn more jobs of it.
+    ///
+    /// If the pool rejects it after shutdown, the job is run on the current thread.
+    template<typename F>
+    void spawn(F &&f)
+    {
+        static_assert(std::is_invocable_v<std::decay_t<F> &, BasicScope &> || std::is_invocable_v<std::decay_t<F> &>,
+                      "The job must be callable without arguments or with the scope.");
+
+        auto body = [this, f = std::forward<F>(f)](bool run) mutable {
+            {
+                // The captures are destroyed before the scope is completed.
+                auto g = std::move(f);
+                if (run) {
+                    if constexpr(std::is_invocable_v<decltype(g) &, BasicScope &>) {
+                        g(*this);
+                    } else {
+                        g();
+                    }
+                }
+            }
+            complete();
+        };
+        std::unique_ptr<impl::Job> job = std::make_unique<impl::ScopeJob<decltype(body)>>(std::move(body));
+        {
+            std::lock_guard guard(mtx_);
+            ++remaining_;
+        }
+        if (!pool_.push_job(job)) {
+            job->run();
+        }
+    }
+
+private:
+    void complete()
+    {
+        // Decreased and notified under the lock, so the waiter can not destroy the scope before
+        // the notification is done.
+        std::lock_guard guard(mtx_);
+        if (--remaining_ == 0) {
+            cond_var_.notify_all();
+        }
+    }
+
+    /// @brief Waits for all jobs of the scope, a worker of the pool runs the other jobs meanwhile.
+    void wait()
+    {
+        auto index = pool_.worker_index();
+        util::Backoff backoff;
+        while (true) {
+            {
+                std::unique_lock lock(mtx_);
+                if (remaining_ == 0) {
+                    return;
+                }
+                if (!index) {
+                    cond_var_.wait(lock, [this] { return remaining_ == 0; });
+                    return;
+                }
+            }
+            if (pool_.run_one(*index)) {
+                backoff.reset();
+            } else {
+                backoff.snooze();
+            }
+        }
+    }
+
+    Pool &pool_;
+    size_t remaining_ = 0;
+    std::mutex mtx_;
+    std::condition_variable cond_var_;
+};
+
+template<PoolBackend Local, PoolBackend Injector, StealPolicy Policy>
+template<typename F>
+auto BasicThreadPool<Local, Injector, Policy>::scope(F &&f) -> std::invoke_result_t<F &&, Scope &>
+{
+    Scope scope(*this);
+    // The spawned jobs may borrow the stack of the caller, so wait for them even if the 'f' throws.
+    struct WaitOnExit
+    {
+        Scope &scope;
+
+        ~WaitOnExit()
+        {
+            scope.wait();
+        }
+    } wait_on_exit{scope};
+    return std::forward<F>(f)(scope);
+}
+
+using ThreadPool = BasicThreadPool<>;
+
+using Scope = ThreadPool::Scope;
+
+}
+
+#endif //SYNC_CELL_THREAD_POOL_HPP
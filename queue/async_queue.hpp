@@ -0,0 +1,216 @@
+///
+/// @file  async_queue.hpp
+/// @brief An adapter queue that adds an awaitable dequeue operation (C++20 coroutine) for the
+/// inner non-blocking queue.
+///
+
+#ifndef SYNC_CELL_ASYNC_QUEUE_HPP
+#define SYNC_CELL_ASYNC_QUEUE_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The async_queue.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <atomic>
+#include <mutex>
+#include <optional>
+#include <type_traits>
+
+#if __cpp_impl_coroutine
+#include <coroutine>
+#endif
+
+
+#if __cpp_impl_coroutine
+
+namespace sc {
+
+/// @brief A queue adapter whose consumers can @c co_await an item instead of spinning or blocking
+/// the thread. When the queue is empty, the awaiting coroutine is suspended and registered in a
+/// waiter list, and it is resumed when a value is enqueued.
+///
+/// @note A suspended coroutine is resumed on the producer thread inside the @c enqueue call. If
+/// the coroutine must run on a specific executor, reschedule it after the @c co_await returns.
+/// @tparam Queue The inner queue type, which provides the @c enqueue and @c try_dequeue methods.
+template<typename Queue>
+class AsyncQueue
+{
+    template<typename>
+    inline static constexpr bool dependent_false_v = false;
+
+public:
+    using value_type = typename Queue::value_type;
+    using reference = typename Queue::reference;
+    using const_reference = typename Queue::reference;
+
+    class DequeueAwaiter;
+
+    template<typename... Args>
+    explicit AsyncQueue(Args &&... args) noexcept(std::is_nothrow_constructible_v<Queue, Args...>)
+            : queue_(std::forward<Args>(args)...)
+    {
+    }
+
+    AsyncQueue(const AsyncQueue &) = delete;
+
+    AsyncQueue &operator=(const AsyncQueue &) = delete;
+
+    template<typename V>
+    void enqueue(V &&v)
+    {
+        if constexpr(std::is_same_v<value_type, std::remove_cvref_t<V>>) {
+            queue_.enqueue(std::forward<V>(v));
+        } else if constexpr(std::is_constructible_v<V, value_type>) {
+            queue_.enqueue(value_type(std::forward<V>(v)));
+        } else {
+            static_assert(dependent_false_v<V>, "Type is not expected.");
+        }
+
+        wake_waiter();
+    }
+
+    auto try_dequeue()
+    {
+        return queue_.try_dequeue();
+    }
+
+    /// @brief Returns the racy snapshot of the count of the items in the inner queue.
+    [[nodiscard]] size_t len() const
+    {
+        return queue_.len();
+    }
+
+    [[nodiscard]] bool is_empty() const
+    {
+        return queue_.is_empty();
+    }
+
+    [[nodiscard]] size_t capacity() const
+    {
+        return queue_.capacity();
+    }
+
+    [[nodiscard]] size_t remaining() const
+    {
+        return queue_.remaining();
+    }
+
+    /// @brief Dequeue an item asynchronously.
+    /// @example
+    /// ``` cpp
+    /// auto value = co_await queue.dequeue();
+    /// ```
+    [[nodiscard]] DequeueAwaiter dequeue() noexcept
+    {
+        return DequeueAwaiter(*this);
+    }
+
+private:
+    /// @brief Resumes the first waiter with an item dequeued from the inner queue.
+    void wake_waiter()
+    {
+        // Pairs with the waiter registration: either the waiter sees the new item in its
+        // 'await_suspend', or we see the waiter in the list.
+        std::atomic_thread_fence(std::memory_order_seq_cst);
+        if (waiter_count_.load(std::memory_order_relaxed) == 0) {
+            return;
+        }
+
+        DequeueAwaiter *waiter;
+        {
+            std::lock_guard guard(mtx_);
+            waiter = head_;
+            if (waiter == nullptr) {
+                return;
+            }
+
+            // Hand off the item to the waiter. If another consumer took the item, keep the waiter
+            // in the list, a later enqueue will wake it.
+            auto v = queue_.try_dequeue();
+            if (!v) {
+                return;
+            }
+            waiter->value_.emplace(*std::move(v));
+
+            head_ = waiter->next_;
+            if (head_ == nullptr) {
+                tail_ = nullptr;
+            }
+            waiter_count_.fetch_sub(1, std::memory_order_relaxed);
+        }
+
+        waiter->handle_.resume();
+    }
+
+    Queue queue_;
+
+    /// @brief Count of the waiters in the list. It is used to skip the lock in 'enqueue'.
+    std::atomic<size_t> waiter_count_{0};
+    /// @brief Guards the FIFO waiter list.
+    std::mutex mtx_;
+    DequeueAwaiter *head_ = nullptr;
+    DequeueAwaiter *tail_ = nullptr;
+};
+
+/// @brief The awaitable object returned by @c AsyncQueue::dequeue.
+template<typename Queue>
+class AsyncQueue<Queue>::DequeueAwaiter
+{
+    friend class AsyncQueue;
+
+    explicit DequeueAwaiter(AsyncQueue &queue) noexcept: queue_(queue) { }
+
+public:
+    DequeueAwaiter(const DequeueAwaiter &) = delete;
+
+    DequeueAwaiter &operator=(const DequeueAwaiter &) = delete;
+
+    bool await_ready()
+    {
+        value_ = queue_.queue_.try_dequeue();
+        return value_.has_value();
+    }
+
+    bool await_suspend(std::coroutine_handle<> handle)
+    {
+        handle_ = handle;
+
+        std::lock_guard guard(queue_.mtx_);
+        queue_.waiter_count_.fetch_add(1, std::memory_order_seq_cst);
+
+        // Check again after registration to avoid missing an item enqueued concurrently.
+        if (auto v = queue_.queue_.try_dequeue(); v) {
+            queue_.waiter_count_.fetch_sub(1, std::memory_order_relaxed);
+            value_.emplace(*std::move(v));
+            return false;
+        }
+
+        if (queue_.tail_ == nullptr) {
+            queue_.head_ = this;
+        } else {
+            queue_.tail_->next_ = this;
+        }
+        queue_.tail_ = this;
+
+        return true;
+    }
+
+    value_type await_resume()
+    {
+        return *std::move(value_);
+    }
+
+private:
+    AsyncQueue &queue_;
+    std::optional<value_type> value_;
+    std::coroutine_handle<> handle_;
+    DequeueAwaiter *next_ = nullptr;
+};
+
+}
+
+#endif
+
+#endif //SYNC_CELL_ASYNC_QUEUE_HPP
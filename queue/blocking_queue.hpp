@@ -8,9 +8,28 @@
 #ifndef SYNC_CELL_BLOCKING_QUEUE_HPP
 #define SYNC_CELL_BLOCKING_QUEUE_HPP
 
-#include <condition_variable>
-#include <mutex>
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The blocking_queue.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <atomic>
+#include <chrono>
+#include <cstdint>
+#include <iterator>
+#include <optional>
 #include <type_traits>
+#include <utility>
+
+#include "shared/compiler_workaround.hpp"
+#include "shared/drain.hpp"
+#include "shared/extend.hpp"
+#include "shared/futex.hpp"
+#include "shared/metrics.hpp"
+#include "shared/select_hook.hpp"
+#include "shared/tracing.hpp"
+#include "util/back_off.hpp"
 
 
 namespace sc {
@@ -37,12 +56,29 @@ public:
 
 }
 
-template<typename Queue, bool = impl::HasDequeue<Queue>::value>
+/// @brief An adapter queue that adds blocking dequeue operation for the inner non-blocking queue.
+///
+/// The queue can be closed by @c close to signal that no more items will be enqueued: the
+/// following @c enqueue fails, and the blocking dequeue returns an empty optional once the queue is
+/// drained, so a pipeline can be shut down without any external flag.
+/// @tparam Queue The inner queue type.
+///
+/// The consumers found the queue empty spin with backoff first, and then are parked on the futex of
+/// a sequence word increased by the wake-ups (see "shared/futex.hpp"), so the producers take no
+/// lock.
+/// @tparam Backoff The backoff of the spinning dequeue before parking the thread. A strategy which
+/// never completes (e.g. @c util::backoff::SpinOnly) makes the consumers spin instead of parking.
+template<typename Queue, typename Backoff = util::Backoff, bool = impl::HasDequeue<Queue>::value>
 class BlockingQueue
 {
     template<typename>
     inline static constexpr bool dependent_false_v = false;
 
+    /// @brief The lowest bit of 'state_': whether the queue is closed.
+    static constexpr size_t Closed = 1;
+    /// @brief The other bits of 'state_': count of the producers which are enqueuing the items.
+    static constexpr size_t InFlightOne = 2;
+
 public:
     using value_type = typename Queue::value_type;
     using reference = typename Queue::reference;
@@ -62,18 +98,50 @@ public:
 
     BlockingQueue &operator=(BlockingQueue &&) noexcept(std::is_nothrow_move_assignable_v<Queue>) = default;
 
+    /// @brief Enqueue an item.
+    /// @return false if the queue has been closed, in this case, the 'v' is not touched. Or if the
+    /// inner queue returns false, e.g. a full @c mpmc::BoundedQueue rejects or drops the 'v' by its
+    /// overflow policy.
     template<typename V>
-    void enqueue(V &&v)
+    bool enqueue(V &&v)
     {
+        if ((state_.fetch_add(InFlightOne, std::memory_order_acquire) & Closed) != 0) {
+            leave_producer();
+            return false;
+        }
+
+        bool enqueued;
         if constexpr(std::is_same_v<value_type, std::remove_cvref_t<V>>) {
-            queue_.enqueue(std::forward<V>(v));
+            enqueued = enqueue_inner(std::forward<V>(v));
         } else if constexpr(std::is_constructible_v<V, value_type>) {
-            queue_.enqueue(value_type(std::forward<V>(v)));
+            enqueued = enqueue_inner(value_type(std::forward<V>(v)));
         } else {
             static_assert(dependent_false_v<V>, "Type is not expected.");
         }
 
-        cond_var_.notify_all();
+        if (!leave_producer() && enqueued) {
+            notify_waiter();
+        }
+
+        return enqueued;
+    }
+
+    /// @brief Enqueues the items of [first, last) in order.
+    /// @return The count of the enqueued items, which stops at the first item failed to enqueue,
+    /// e.g. the queue is closed.
+    template<std::input_iterator It, typename Sentinel>
+    size_t extend(It first, Sentinel last)
+    {
+        return sc::impl::extend(std::move(first), std::move(last), [this](auto &&v) {
+            return enqueue(std::forward<decltype(v)>(v));
+        });
+    }
+
+    /// @brief Enqueues the items of the 'range' in order, e.g. a container or a view.
+    template<typename Range>
+    size_t extend(Range &&range)
+    {
+        return extend(std::begin(range), std::end(range));
     }
 
     auto try_dequeue()
@@ -81,30 +149,257 @@ public:
         return queue_.try_dequeue();
     }
 
-    value_type dequeue()
+    /// @brief Forwards to the inner queue, only available if the inner queue supports it.
+    template<typename F>
+    bool peek(F &&f)
+    {
+        return queue_.peek(std::forward<F>(f));
+    }
+
+    template<typename Pred>
+    auto pop_if(Pred &&pred)
+    {
+        return queue_.pop_if(std::forward<Pred>(pred));
+    }
+
+    /// @brief Forwards to the inner queue, only available if the inner queue supports it. The
+    /// drain does not wait for the items, it stops once the queue is empty.
+    auto drain(size_t chunk = DefaultDrainChunk)
+    {
+        return queue_.drain(chunk);
+    }
+
+    /// @brief Returns the racy snapshot of the count of the items in the inner queue.
+    [[nodiscard]] size_t len() const
+    {
+        return queue_.len();
+    }
+
+    [[nodiscard]] bool is_empty() const
+    {
+        return queue_.is_empty();
+    }
+
+    [[nodiscard]] size_t capacity() const
+    {
+        return queue_.capacity();
+    }
+
+    [[nodiscard]] size_t remaining() const
+    {
+        return queue_.remaining();
+    }
+
+    /// @brief Closes the queue. The following @c enqueue fails, and the blocked consumers are woken
+    /// up once the queue is drained. Closing a closed queue has no effect.
+    void close()
+    {
+        state_.fetch_or(Closed, std::memory_order_seq_cst);
+        notify_all_waiters();
+    }
+
+    /// @brief Returns true if the queue has been closed. There may still be items in the queue.
+    [[nodiscard]] bool is_closed() const noexcept
+    {
+        return (state_.load(std::memory_order_acquire) & Closed) != 0;
+    }
+
+    /// @brief Returns true if the queue is closed and no producer is enqueuing. If the queue is
+    /// empty after this returns true, no item will be enqueued anymore.
+    [[nodiscard]] bool is_drained() const noexcept
+    {
+        // Pairs with the release decrement of the producers: the items enqueued by them are visible.
+        return state_.load(std::memory_order_acquire) == Closed;
+    }
+
+    /// @brief Returns the hook notified when an item is enqueued or the queue is drained, used by
+    /// the @c sync::Select.
+    [[nodiscard]] impl::SelectHook &select_hook() noexcept
+    {
+        return select_hook_;
+    }
+
+    /// @brief Returns the contention counters: the spinning dequeues as the spin iterations and the
+    /// parked consumers as the park events, plus the counters of the inner queue if it has them.
+    /// Requires @c SYNC_CELL_METRICS.
+    [[nodiscard]] metrics::Stats stats() const noexcept
+    {
+        auto stats = metrics_.snapshot();
+        if constexpr(requires { queue_.stats(); }) {
+            stats += queue_.stats();
+        }
+        return stats;
+    }
+
+    /// @brief Dequeue an item. If the queue is empty, spins for a while and then parks the current
+    /// thread until an item is enqueued or the queue is closed.
+    /// @return The dequeue value, or an empty optional if the queue is closed and drained.
+    std::optional<value_type> dequeue()
     {
         static_assert(std::is_convertible_v<decltype(*(queue_.try_dequeue())), value_type>);
 
+        if (auto v = spin_dequeue(); v) {
+            return v;
+        }
+
+        std::optional<value_type> v;
+        metrics_.park();
+        tracing::Span span(tracing::SpanKind::QueueBlock, "sc::BlockingQueue", this);
+        span.enter();
+        waiters_.fetch_add(1, std::memory_order_seq_cst);
         while (true) {
-            auto v = queue_.try_dequeue();
-            if (v) {
-                return *std::move(v);
+            auto seq = seq_.load(std::memory_order_seq_cst);
+            if (dequeue_or_drained(v)) {
+                break;
             }
+            impl::futex_wait(seq_, seq);
+        }
+        waiters_.fetch_sub(1, std::memory_order_relaxed);
 
-            std::unique_lock lock(mtx_);
-            cond_var_.wait(lock);
+        return v;
+    }
+
+    /// @brief Dequeue an item. If the queue is empty, blocks the current thread until an item is
+    /// enqueued or the 'timeout' duration has elapsed.
+    /// @return The dequeue value, or an empty optional if timeout or the queue is closed and
+    /// drained.
+    template<typename Rep, typename Period>
+    std::optional<value_type> try_dequeue_for(const std::chrono::duration<Rep, Period> &timeout)
+    {
+        return try_dequeue_until(std::chrono::steady_clock::now() + timeout);
+    }
+
+    /// @brief Dequeue an item. If the queue is empty, blocks the current thread until an item is
+    /// enqueued or the 'deadline' has been reached.
+    /// @return The dequeue value, or an empty optional if timeout or the queue is closed and
+    /// drained.
+    template<typename Clock, typename Duration>
+    std::optional<value_type> try_dequeue_until(const std::chrono::time_point<Clock, Duration> &deadline)
+    {
+        if (auto v = spin_dequeue(); v) {
+            return v;
         }
+
+        std::optional<value_type> v;
+        metrics_.park();
+        tracing::Span span(tracing::SpanKind::QueueBlock, "sc::BlockingQueue", this);
+        span.enter();
+        waiters_.fetch_add(1, std::memory_order_seq_cst);
+        while (true) {
+            auto seq = seq_.load(std::memory_order_seq_cst);
+            if (dequeue_or_drained(v)) {
+                break;
+            }
+            if (!impl::futex_wait_until(seq_, seq, deadline)) {
+                dequeue_or_drained(v);
+                break;
+            }
+        }
+        waiters_.fetch_sub(1, std::memory_order_relaxed);
+
+        return v;
     }
 
 private:
+    /// @brief Enqueues to the inner queue, whose @c enqueue may return void or a bool.
+    template<typename V>
+    bool enqueue_inner(V &&v)
+    {
+        if constexpr(std::is_same_v<decltype(queue_.enqueue(std::forward<V>(v))), bool>) {
+            return queue_.enqueue(std::forward<V>(v));
+        } else {
+            queue_.enqueue(std::forward<V>(v));
+            return true;
+        }
+    }
+
+private:
+    /// @brief Try dequeue with backoff until the backoff is completed or the queue is drained.
+    std::optional<value_type> spin_dequeue()
+    {
+        Backoff backoff;
+        while (true) {
+            auto drained = is_drained();
+            auto v = queue_.try_dequeue();
+            if (v || drained || backoff.is_completed()) {
+                return v;
+            }
+            metrics_.spin();
+            backoff.snooze();
+        }
+    }
+
+    /// @brief Decreases the count of in-flight producers.
+    /// @return true if the queue is drained now, and all consumers have been woken up.
+    bool leave_producer()
+    {
+        if (state_.fetch_sub(InFlightOne, std::memory_order_release) == (Closed | InFlightOne)) {
+            // The last in-flight producer after the queue is closed: all consumers waiting for the
+            // drain must check the queue again.
+            notify_all_waiters();
+            return true;
+        }
+
+        return false;
+    }
+
+    /// @brief Returns true if an item is dequeued, or the queue is closed and drained.
+    bool dequeue_or_drained(std::optional<value_type> &v)
+    {
+        // Check the state before the dequeue, so the empty queue means drained.
+        auto drained = is_drained();
+        return dequeue_to(v) || drained;
+    }
+
+    bool dequeue_to(std::optional<value_type> &v)
+    {
+        if (auto r = queue_.try_dequeue(); r) {
+            v.emplace(*std::move(r));
+            return true;
+        }
+
+        return false;
+    }
+
+    /// @brief Wakes up a parked consumer thread, if any.
+    void notify_waiter()
+    {
+        // Pairs with the 'waiters_' increment of the consumer: either the consumer sees the new
+        // item before parking, or we see the consumer is (going to be) waiting.
+        std::atomic_thread_fence(std::memory_order_seq_cst);
+        select_hook_.notify();
+        if (waiters_.load(std::memory_order_relaxed) == 0) {
+            return;
+        }
+
+        // A consumer between its check and its park sees the new sequence, and does not park.
+        seq_.fetch_add(1, std::memory_order_release);
+        impl::futex_wake_one(seq_);
+    }
+
+    /// @brief Wakes up all parked consumer threads.
+    void notify_all_waiters()
+    {
+        std::atomic_thread_fence(std::memory_order_seq_cst);
+        select_hook_.notify();
+        seq_.fetch_add(1, std::memory_order_release);
+        impl::futex_wake_all(seq_);
+    }
+
     Queue queue_;
 
-    std::mutex mtx_;
-    std::condition_variable cond_var_;
+    /// @brief The closed flag and the count of in-flight producers.
+    std::atomic<size_t> state_{0};
+    /// @brief Count of the consumer threads that are (going to be) parked.
+    std::atomic<size_t> waiters_{0};
+    /// @brief Increased by each wake-up, the futex word of the parked consumers.
+    std::atomic<uint32_t> seq_{0};
+    impl::SelectHook select_hook_;
+    SC_NO_UNIQUE_ADDRESS metrics::Counters metrics_;
 };
 
-template<typename Queue>
-class BlockingQueue<Queue, true>
+template<typename Queue, typename Backoff>
+class BlockingQueue<Queue, Backoff, true>
 {
     template<typename>
     inline static constexpr bool dependent_false_v = false;
@@ -129,12 +424,12 @@ public:
     BlockingQueue &operator=(BlockingQueue &&) noexcept(std::is_nothrow_move_assignable_v<Queue>) = default;
 
     template<typename V>
-    void enqueue(V &&v)
+    decltype(auto) enqueue(V &&v)
     {
         if constexpr(std::is_same_v<value_type, std::remove_cvref_t<V>>) {
-            queue_.enqueue(std::forward<V>(v));
+            return queue_.enqueue(std::forward<V>(v));
         } else if constexpr(std::is_constructible_v<V, value_type>) {
-            queue_.enqueue(value_type(std::forward<V>(v)));
+            return queue_.enqueue(value_type(std::forward<V>(v)));
         } else {
             static_assert(dependent_false_v<V>, "Type is not expected.");
         }
@@ -145,11 +440,57 @@ public:
         return queue_.try_dequeue();
     }
 
+    /// @brief Returns the racy snapshot of the count of the items in the inner queue.
+    [[nodiscard]] size_t len() const
+    {
+        return queue_.len();
+    }
+
+    [[nodiscard]] bool is_empty() const
+    {
+        return queue_.is_empty();
+    }
+
+    [[nodiscard]] size_t capacity() const
+    {
+        return queue_.capacity();
+    }
+
+    [[nodiscard]] size_t remaining() const
+    {
+        return queue_.remaining();
+    }
+
     auto dequeue()
     {
         return queue_.dequeue();
     }
 
+    template<typename Rep, typename Period>
+    auto try_dequeue_for(const std::chrono::duration<Rep, Period> &timeout)
+    {
+        return queue_.try_dequeue_for(timeout);
+    }
+
+    template<typename Clock, typename Duration>
+    auto try_dequeue_until(const std::chrono::time_point<Clock, Duration> &deadline)
+    {
+        return queue_.try_dequeue_until(deadline);
+    }
+
+    /// @brief Forwards to the inner queue, only available if the inner queue supports closing.
+    template<typename Q = Queue>
+    auto close() -> decltype(std::declval<Q &>().close())
+    {
+        return queue_.close();
+    }
+
+    template<typename Q = Queue>
+    [[nodiscard]] auto is_closed() const noexcept -> decltype(std::declval<const Q &>().is_closed())
+    {
+        return queue_.is_closed();
+    }
+
 private:
     Queue queue_;
 };
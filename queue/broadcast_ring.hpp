@@ -0,0 +1,419 @@
+///
+/// @file  broadcast_ring.hpp
+/// @brief A bounded multi-producer broadcast ring, whose every consumer receives every value.
+/// Inspired by the [LMAX Disruptor](https://lmax-exchange.github.io/disruptor/) and the broadcast
+/// channel of [tokio](https://docs.rs/tokio/latest/tokio/sync/broadcast/index.html).
+///
+
+#ifndef SYNC_CELL_BROADCAST_RING_HPP
+#define SYNC_CELL_BROADCAST_RING_HPP
+
+#include <algorithm>
+#include <atomic>
+#include <bit>
+#include <cstddef>
+#include <cstdint>
+#include <memory>
+#include <optional>
+#include <type_traits>
+#include <utility>
+
+#include "lock/rw_spin_lock.hpp"
+#include "shared/config.hpp"
+#include "util/back_off.hpp"
+#include "util/cache_padded.hpp"
+
+
+namespace sc::broadcast {
+
+/// @brief What the producers do when the slowest consumer is a whole ring behind.
+enum class Overflow
+{
+    /// @brief The producers wait until the slowest consumer receives the oldest value, so no
+    /// consumer misses any value.
+    Block,
+    /// @brief The producers overwrite the oldest value, and the slow consumers skip the overwritten
+    /// values, counted by @c Ring::Consumer::lagged.
+    Lag,
+};
+
+/// @brief A bounded ring for the fan-out event distribution: any number of producers send the
+/// values, and each @c Consumer receives every value sent after it subscribed, in the order of
+/// sending.
+///
+/// The producers claim the sequences from one atomic counter, and each consumer has its own cursor
+/// in a cache line, so the consumers do not contend with each other. Each slot is guarded by
+/// a @c lock::RwSpinLock, the consumers copy the value out under the shared lock, which keeps
+/// the slot valid even if a lagging consumer races with the producer overwriting it.
+///
+/// @example
+/// ``` cpp
+/// sc::broadcast::Ring<Event> events(1024, 4);
+/// auto consumer = *events.subscribe();
+/// // In the producer threads.
+/// events.send(Event{...});
+/// // In the consumer thread.
+/// auto event = consumer.recv();
+/// ```
+/// @note With @c Overflow::Block, a consumer which stops receiving blocks all producers, destroy
+/// it to unsubscribe. The @c Ring object must outlive its consumers.
+/// @tparam T The value type, which is copied to each consumer.
+/// @tparam Backoff The backoff of the waiting producers and consumers.
+template<typename T, typename Backoff = util::Backoff>
+class Ring
+{
+    static_assert(std::is_copy_constructible_v<T>, "The values are copied to each consumer.");
+
+    struct Slot
+    {
+        /// @brief The sequence of the value plus 1, or 0 if never written. Only increased, under
+        /// the write lock of the value.
+        std::atomic<uint64_t> stamp{0};
+        lock::RwSpinLock<std::optional<T>, Backoff> value;
+    };
+
+    struct Cursor
+    {
+        /// @brief Set when the cursor is owned by a consumer.
+        std::atomic<bool> claimed{false};
+        /// @brief Set when the 'next' gates the producers.
+        std::atomic<bool> active{false};
+        /// @brief The next sequence to receive.
+        std::atomic<uint64_t> next{0};
+    };
+
+public:
+    using value_type = T;
+    using reference = value_type &;
+    using const_reference = const value_type &;
+
+    class Consumer;
+
+    /// @brief Creates a new ring.
+    /// @param capacity The count of values kept in the ring, rounded up to a power of 2.
+    /// @param max_consumers The max count of the consumers at the same time.
+    /// @param overflow What the producers do when the ring is full.
+    Ring(size_t capacity, size_t max_consumers, Overflow overflow = Overflow::Block)
+            : capacity_(std::bit_ceil(std::max<size_t>(capacity, 1))),
+              max_consumers_(max_consumers),
+              overflow_(overflow),
+              slots_(std::make_unique<Slot[]>(capacity_)),
+              cursors_(std::make_unique<util::CachePadded<Cursor>[]>(max_consumers))
+    {
+        tail_->store(0, std::memory_order_relaxed);
+    }
+
+    Ring(const Ring &) = delete;
+
+    Ring &operator=(const Ring &) = delete;
+
+    [[nodiscard]] size_t capacity() const noexcept
+    {
+        return capacity_;
+    }
+
+    /// @brief Returns the count of the values not received by the slowest consumer, or 0 if there
+    /// is no consumer. It is a racy snapshot: the count may be stale once returned if other threads
+    /// are sending or receiving.
+    [[nodiscard]] size_t len() const noexcept
+    {
+        auto tail = tail_->load(std::memory_order_seq_cst);
+        return static_cast<size_t>(std::min<uint64_t>(tail - min_cursor(tail), capacity_));
+    }
+
+    /// @brief Returns true if all consumers have received all values, a racy snapshot as @c len.
+    [[nodiscard]] bool is_empty() const noexcept
+    {
+        return len() == 0;
+    }
+
+    /// @brief Returns the count of the values which can be sent before the slowest consumer is
+    /// a whole ring behind, a racy snapshot as @c len.
+    [[nodiscard]] size_t remaining() const noexcept
+    {
+        return capacity_ - len();
+    }
+
+    [[nodiscard]] size_t max_consumers() const noexcept
+    {
+        return max_consumers_;
+    }
+
+    [[nodiscard]] Overflow overflow() const noexcept
+    {
+        return overflow_;
+    }
+
+    /// @brief Subscribes a new consumer, which receives the values sent after this call.
+    /// @return An empty optional if there are 'max_consumers' consumers already.
+    std::optional<Consumer> subscribe() noexcept
+    {
+        for (size_t i = 0; i < max_consumers_; ++i) {
+            auto &cursor = *cursors_[i];
+            bool claimed = false;
+            if (cursor.claimed.load(std::memory_order_relaxed) ||
+                !cursor.claimed.compare_exchange_strong(claimed, true, std::memory_order_acquire)) {
+                continue;
+            }
+
+            // Gate the producers at an old sequence first, then move to the current one. A
+            // producer missing the 'active' has claimed a sequence before the current one, whose
+            // slot is never received by this consumer.
+            cursor.next.store(tail_->load(std::memory_order_relaxed), std::memory_order_relaxed);
+            cursor.active.store(true, std::memory_order_seq_cst);
+            auto next = tail_->load(std::memory_order_seq_cst);
+            cursor.next.store(next, std::memory_order_release);
+            return Consumer(this, i, next);
+        }
+        return {};
+    }
+
+    /// @brief Sends the value to all consumers. With @c Overflow::Block, waits until the slowest
+    /// consumer receives the oldest value if the ring is full.
+    void send(const_reference value)
+    {
+        publish(claim(), value);
+    }
+
+    void send(value_type &&value)
+    {
+        publish(claim(), std::move(value));
+    }
+
+    /// @brief Sends the value to all consumers. With @c Overflow::Block, the value is not sent if
+    /// the ring is full.
+    /// @return false if the ring is full, in this case, the 'value' is not moved.
+    bool try_send(const_reference value)
+    {
+        auto seq = try_claim();
+        if (seq) {
+            publish(*seq, value);
+        }
+        return seq.has_value();
+    }
+
+    bool try_send(value_type &&value)
+    {
+        auto seq = try_claim();
+        if (seq) {
+            publish(*seq, std::move(value));
+        }
+        return seq.has_value();
+    }
+
+private:
+    [[nodiscard]] Slot &slot(uint64_t seq) const noexcept
+    {
+        return slots_[static_cast<size_t>(seq) & (capacity_ - 1)];
+    }
+
+    /// @brief Returns the min 'next' of the active consumers, or 'seq' if no consumer is active.
+    uint64_t min_cursor(uint64_t seq) const noexcept
+    {
+        auto min = seq;
+        for (size_t i = 0; i < max_consumers_; ++i) {
+            auto &cursor = *cursors_[i];
+            if (cursor.active.load(std::memory_order_seq_cst)) {
+                min = std::min(min, cursor.next.load(std::memory_order_acquire));
+            }
+        }
+        return min;
+    }
+
+    [[nodiscard]] bool is_full(uint64_t seq) const noexcept
+    {
+        return overflow_ == Overflow::Block && seq - min_cursor(seq) >= capacity_;
+    }
+
+    uint64_t claim() noexcept
+    {
+        auto seq = tail_->fetch_add(1, std::memory_order_seq_cst);
+        wait_slot(seq);
+        return seq;
+    }
+
+    std::optional<uint64_t> try_claim() noexcept
+    {
+        auto seq = tail_->load(std::memory_order_relaxed);
+        do {
+            if (is_full(seq)) {
+                return {};
+            }
+        } while (!tail_->compare_exchange_weak(
+                seq, seq + 1,
+                std::memory_order_seq_cst,
+                std::memory_order_relaxed));
+        // The consumer subscribed after the check may still gate the sequence for a moment.
+        wait_slot(seq);
+        return seq;
+    }
+
+    /// @brief Waits until the slot of the claimed 'seq' is received by all consumers, the check is
+    /// after the claim, so a consumer subscribing concurrently is either seen or starts after 'seq'.
+    void wait_slot(uint64_t seq) const noexcept
+    {
+        Backoff backoff;
+        while (is_full(seq)) {
+            backoff.snooze();
+        }
+    }
+
+    template<typename U>
+    void publish(uint64_t seq, U &&value)
+    {
+        auto &s = slot(seq);
+        {
+            auto guard = s.value.write();
+            // With 'Overflow::Lag', a newer value of the next lap may have been written.
+            if (s.stamp.load(std::memory_order_relaxed) > seq) {
+                return;
+            }
+            guard->emplace(std::forward<U>(value));
+            s.stamp.store(seq + 1, std::memory_order_release);
+        }
+#if SC_HAS_STD
+        s.stamp.notify_all();
+#endif
+    }
+
+    /// @brief The next sequence to claim.
+    util::CachePadded<std::atomic<uint64_t>> tail_;
+
+    size_t capacity_;
+    size_t max_consumers_;
+    Overflow overflow_;
+    std::unique_ptr<Slot[]> slots_;
+    std::unique_ptr<util::CachePadded<Cursor>[]> cursors_;
+};
+
+/// @brief A consumer of the @c Ring, which receives every value sent after it subscribed. It is
+/// move-only, and unsubscribes when destroyed.
+template<typename T, typename Backoff>
+class Ring<T, Backoff>::Consumer
+{
+    friend class Ring;
+
+    Consumer(Ring *ring, size_t index, uint64_t next) noexcept : ring_(ring), index_(index), next_(next) { }
+
+public:
+    Consumer(Consumer &&other) noexcept
+            : ring_(std::exchange(other.ring_, nullptr)), index_(other.index_), next_(other.next_),
+              lagged_(other.lagged_)
+    {
+    }
+
+    Consumer &operator=(Consumer &&other) noexcept
+    {
+        if (this != &other) {
+            unsubscribe();
+            ring_ = std::exchange(other.ring_, nullptr);
+            index_ = other.index_;
+            next_ = other.next_;
+            lagged_ = other.lagged_;
+        }
+        return *this;
+    }
+
+    ~Consumer()
+    {
+        unsubscribe();
+    }
+
+    /// @brief Returns the count of the values skipped because they were overwritten before being
+    /// received, only with @c Overflow::Lag.
+    [[nodiscard]] uint64_t lagged() const noexcept
+    {
+        return lagged_;
+    }
+
+    /// @brief Returns the count of the values sent but not received by this consumer yet, at most
+    /// the capacity of the ring. It is a racy snapshot if other threads are sending.
+    [[nodiscard]] size_t len() const noexcept
+    {
+        auto tail = ring_->tail_->load(std::memory_order_acquire);
+        return static_cast<size_t>(std::min<uint64_t>(tail - next_, ring_->capacity_));
+    }
+
+    /// @brief Returns true if no new value is sent, a racy snapshot as @c len.
+    [[nodiscard]] bool is_empty() const noexcept
+    {
+        return len() == 0;
+    }
+
+    /// @brief Receives the next value without blocking.
+    /// @return An empty optional if no new value is sent.
+    std::optional<value_type> try_recv()
+    {
+        while (true) {
+            auto &slot = ring_->slot(next_);
+            auto guard = slot.value.read();
+            auto stamp = slot.stamp.load(std::memory_order_relaxed);
+            if (stamp <= next_) {
+                return {};
+            }
+            if (stamp == next_ + 1) {
+                std::optional<value_type> value(**guard);
+                advance(next_ + 1);
+                return value;
+            }
+            skip();
+        }
+    }
+
+    /// @brief Receives the next value, blocks the current thread until it is sent.
+    value_type recv()
+    {
+        Backoff backoff;
+        while (true) {
+            if (auto value = try_recv()) {
+                return *std::move(value);
+            }
+#if SC_HAS_STD
+            if (backoff.is_completed()) {
+                auto &slot = ring_->slot(next_);
+                auto stamp = slot.stamp.load(std::memory_order_acquire);
+                if (stamp <= next_) {
+                    slot.stamp.wait(stamp, std::memory_order_acquire);
+                }
+                continue;
+            }
+#endif
+            backoff.snooze();
+        }
+    }
+
+private:
+    void advance(uint64_t next) noexcept
+    {
+        next_ = next;
+        // Releases the slot to the producers after the value is copied.
+        ring_->cursors_[index_]->next.store(next, std::memory_order_release);
+    }
+
+    /// @brief Skips to the oldest value which may not be overwritten yet.
+    void skip() noexcept
+    {
+        auto tail = ring_->tail_->load(std::memory_order_acquire);
+        auto next = std::max(next_ + 1, tail >= ring_->capacity_ ? tail - ring_->capacity_ : 0);
+        lagged_ += next - next_;
+        advance(next);
+    }
+
+    void unsubscribe() noexcept
+    {
+        if (ring_ != nullptr) {
+            auto &cursor = *ring_->cursors_[index_];
+            cursor.active.store(false, std::memory_order_release);
+            cursor.claimed.store(false, std::memory_order_release);
+        }
+    }
+
+    Ring *ring_;
+    size_t index_;
+    uint64_t next_;
+    uint64_t lagged_ = 0;
+};
+
+}
+
+#endif //SYNC_CELL_BROADCAST_RING_HPP
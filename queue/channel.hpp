@@ -0,0 +1,431 @@
+///
+/// @file  channel.hpp
+/// @brief The reference counted sender and receiver handles of a blocking queue, which detect the
+/// disconnection when all handles of the other side are dropped.
+///
+
+#ifndef SYNC_CELL_CHANNEL_HPP
+#define SYNC_CELL_CHANNEL_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The channel.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <atomic>
+#include <chrono>
+#include <cstddef>
+#include <iterator>
+#include <optional>
+#include <type_traits>
+#include <utility>
+
+#include "queue/blocking_queue.hpp"
+#include "shared/extend.hpp"
+
+
+namespace sc {
+
+/// @brief The state of a receive operation.
+enum class RecvState
+{
+    /// @brief A value was received.
+    Success,
+    /// @brief The channel was empty, but the senders may still send.
+    Empty,
+    /// @brief The channel is empty, and all senders have been dropped.
+    Disconnected,
+};
+
+/// @brief Possible outcomes of a non-blocking or timed receive.
+/// @tparam T The value type.
+template<typename T>
+class TryRecv
+{
+public:
+    constexpr TryRecv() noexcept = default;
+
+    static constexpr TryRecv empty() noexcept
+    {
+        return {};
+    }
+
+    static constexpr TryRecv disconnected() noexcept
+    {
+        TryRecv r;
+        r.state_ = RecvState::Disconnected;
+        return r;
+    }
+
+    static constexpr TryRecv success(T value) noexcept(std::is_nothrow_move_constructible_v<T>)
+    {
+        TryRecv r;
+        r.state_ = RecvState::Success;
+        r.value_.emplace(std::move(value));
+        return r;
+    }
+
+    [[nodiscard]] constexpr RecvState state() const noexcept
+    {
+        return state_;
+    }
+
+    [[nodiscard]] constexpr bool is_success() const noexcept
+    {
+        return state_ == RecvState::Success;
+    }
+
+    [[nodiscard]] constexpr bool is_empty() const noexcept
+    {
+        return state_ == RecvState::Empty;
+    }
+
+    [[nodiscard]] constexpr bool is_disconnected() const noexcept
+    {
+        return state_ == RecvState::Disconnected;
+    }
+
+    /// @brief Returns the received value if the receive operation succeeded.
+    constexpr std::optional<T> success() &&
+    {
+        return std::move(value_);
+    }
+
+private:
+    RecvState state_ = RecvState::Empty;
+    std::optional<T> value_;
+};
+
+template<typename Queue>
+class Sender;
+
+template<typename Queue>
+class Receiver;
+
+template<typename Queue, typename... Args>
+std::pair<Sender<Queue>, Receiver<Queue>> make_channel(Args &&... args);
+
+namespace impl {
+
+/// @brief The state shared by all handles of a channel, freed with the last handle.
+template<typename Queue>
+struct Channel
+{
+    template<typename... Args>
+    explicit Channel(Args &&... args) : queue(std::forward<Args>(args)...) { }
+
+    /// @brief Drops a handle, the last one frees the channel.
+    void release() noexcept
+    {
+        if (handles.fetch_sub(1, std::memory_order_acq_rel) == 1) {
+            delete this;
+        }
+    }
+
+    BlockingQueue<Queue> queue;
+    std::atomic<size_t> senders{1};
+    std::atomic<size_t> receivers{1};
+    std::atomic<size_t> handles{2};
+};
+
+}
+
+/// @brief A sending handle of a channel made by @c make_channel. The handle is copyable: each copy
+/// is counted, and the channel is closed when the last sender is destroyed, so the receivers see
+/// the disconnection once the sent values are drained.
+/// @tparam Queue The inner non-blocking queue type.
+template<typename Queue>
+class Sender
+{
+    using Channel = impl::Channel<Queue>;
+
+    template<typename Q, typename... Args>
+    friend std::pair<Sender<Q>, Receiver<Q>> make_channel(Args &&... args);
+
+    explicit Sender(Channel *channel) noexcept : channel_(channel) { }
+
+public:
+    using value_type = typename Queue::value_type;
+
+    Sender(const Sender &other) noexcept : channel_(other.channel_)
+    {
+        acquire();
+    }
+
+    Sender(Sender &&other) noexcept : channel_(std::exchange(other.channel_, nullptr)) { }
+
+    Sender &operator=(const Sender &other) noexcept
+    {
+        if (this != &other) {
+            release();
+            channel_ = other.channel_;
+            acquire();
+        }
+        return *this;
+    }
+
+    Sender &operator=(Sender &&other) noexcept
+    {
+        if (this != &other) {
+            release();
+            channel_ = std::exchange(other.channel_, nullptr);
+        }
+        return *this;
+    }
+
+    ~Sender()
+    {
+        release();
+    }
+
+    /// @brief Sends a value.
+    /// @return false if all receivers have been dropped, in this case, the 'v' is not touched. Or if
+    /// the inner queue rejects or drops the 'v', see @c BlockingQueue::enqueue.
+    template<typename V>
+    bool send(V &&v)
+    {
+        return channel_->queue.enqueue(std::forward<V>(v));
+    }
+
+    /// @brief Sends the items of [first, last) in order.
+    /// @return The count of the sent items, which stops at the first item failed to send.
+    template<std::input_iterator It, typename Sentinel>
+    size_t extend(It first, Sentinel last)
+    {
+        return sc::impl::extend(std::move(first), std::move(last), [this](auto &&v) {
+            return send(std::forward<decltype(v)>(v));
+        });
+    }
+
+    /// @brief Sends the items of the 'range' in order, e.g. a container or a view.
+    template<typename Range>
+    size_t extend(Range &&range)
+    {
+        return extend(std::begin(range), std::end(range));
+    }
+
+    /// @brief Returns true if all receivers have been dropped.
+    [[nodiscard]] bool is_disconnected() const noexcept
+    {
+        return channel_->receivers.load(std::memory_order_acquire) == 0;
+    }
+
+    /// @brief Returns the count of the live senders, including this one.
+    [[nodiscard]] size_t sender_count() const noexcept
+    {
+        return channel_->senders.load(std::memory_order_relaxed);
+    }
+
+    /// @brief Returns the racy snapshot of the count of the values in the channel.
+    [[nodiscard]] size_t len() const
+    {
+        return channel_->queue.len();
+    }
+
+    [[nodiscard]] bool is_empty() const
+    {
+        return channel_->queue.is_empty();
+    }
+
+    [[nodiscard]] size_t capacity() const
+    {
+        return channel_->queue.capacity();
+    }
+
+    [[nodiscard]] size_t remaining() const
+    {
+        return channel_->queue.remaining();
+    }
+
+private:
+    void acquire() noexcept
+    {
+        if (channel_ != nullptr) {
+            channel_->handles.fetch_add(1, std::memory_order_relaxed);
+            channel_->senders.fetch_add(1, std::memory_order_relaxed);
+        }
+    }
+
+    void release() noexcept
+    {
+        if (channel_ == nullptr) {
+            return;
+        }
+        if (channel_->senders.fetch_sub(1, std::memory_order_acq_rel) == 1) {
+            channel_->queue.close();
+        }
+        std::exchange(channel_, nullptr)->release();
+    }
+
+    Channel *channel_;
+};
+
+/// @brief A receiving handle of a channel made by @c make_channel. The handle is copyable for the
+/// multiple consumers if the inner queue supports them. When the last receiver is destroyed, the
+/// channel is closed, and the following sends fail.
+///
+/// @example
+/// ``` cpp
+/// auto [tx, rx] = sc::make_channel<sc::mpmc::ArrayListQueue<Job>>();
+/// std::thread worker([rx = std::move(rx)]() mutable {
+///     while (auto job = rx.recv()) { job->run(); }   // Ends when all senders are dropped.
+/// });
+/// tx.send(Job{...});
+/// ```
+/// @tparam Queue The inner non-blocking queue type.
+template<typename Queue>
+class Receiver
+{
+    using Channel = impl::Channel<Queue>;
+
+    template<typename Q, typename... Args>
+    friend std::pair<Sender<Q>, Receiver<Q>> make_channel(Args &&... args);
+
+    explicit Receiver(Channel *channel) noexcept : channel_(channel) { }
+
+public:
+    using value_type = typename Queue::value_type;
+
+    Receiver(const Receiver &other) noexcept : channel_(other.channel_)
+    {
+        acquire();
+    }
+
+    Receiver(Receiver &&other) noexcept : channel_(std::exchange(other.channel_, nullptr)) { }
+
+    Receiver &operator=(const Receiver &other) noexcept
+    {
+        if (this != &other) {
+            release();
+            channel_ = other.channel_;
+            acquire();
+        }
+        return *this;
+    }
+
+    Receiver &operator=(Receiver &&other) noexcept
+    {
+        if (this != &other) {
+            release();
+            channel_ = std::exchange(other.channel_, nullptr);
+        }
+        return *this;
+    }
+
+    ~Receiver()
+    {
+        release();
+    }
+
+    /// @brief Receives a value without blocking.
+    TryRecv<value_type> try_recv()
+    {
+        // Check the state before the dequeue, so the empty queue means disconnected.
+        auto drained = channel_->queue.is_drained();
+        if (auto v = channel_->queue.try_dequeue(); v) {
+            return TryRecv<value_type>::success(*std::move(v));
+        }
+        return drained ? TryRecv<value_type>::disconnected() : TryRecv<value_type>::empty();
+    }
+
+    /// @brief Receives a value, blocks the current thread until a value is sent or all senders are
+    /// dropped.
+    /// @return The received value, or an empty optional if disconnected.
+    std::optional<value_type> recv()
+    {
+        return channel_->queue.dequeue();
+    }
+
+    /// @brief Receives a value, blocks the current thread until a value is sent, all senders are
+    /// dropped, or the 'timeout' duration has elapsed.
+    /// @return The empty state if timeout.
+    template<typename Rep, typename Period>
+    TryRecv<value_type> recv_for(const std::chrono::duration<Rep, Period> &timeout)
+    {
+        return recv_until(std::chrono::steady_clock::now() + timeout);
+    }
+
+    /// @brief Receives a value, blocks the current thread until a value is sent, all senders are
+    /// dropped, or the 'deadline' has been reached.
+    /// @return The empty state if timeout.
+    template<typename Clock, typename Duration>
+    TryRecv<value_type> recv_until(const std::chrono::time_point<Clock, Duration> &deadline)
+    {
+        if (auto v = channel_->queue.try_dequeue_until(deadline); v) {
+            return TryRecv<value_type>::success(*std::move(v));
+        }
+        return try_recv();
+    }
+
+    /// @brief Returns true if all senders have been dropped. There may still be values to receive.
+    [[nodiscard]] bool is_disconnected() const noexcept
+    {
+        return channel_->senders.load(std::memory_order_acquire) == 0;
+    }
+
+    /// @brief Returns the count of the live receivers, including this one.
+    [[nodiscard]] size_t receiver_count() const noexcept
+    {
+        return channel_->receivers.load(std::memory_order_relaxed);
+    }
+
+    /// @brief Returns the racy snapshot of the count of the values in the channel.
+    [[nodiscard]] size_t len() const
+    {
+        return channel_->queue.len();
+    }
+
+    [[nodiscard]] bool is_empty() const
+    {
+        return channel_->queue.is_empty();
+    }
+
+    [[nodiscard]] size_t capacity() const
+    {
+        return channel_->queue.capacity();
+    }
+
+    [[nodiscard]] size_t remaining() const
+    {
+        return channel_->queue.remaining();
+    }
+
+private:
+    void acquire() noexcept
+    {
+        if (channel_ != nullptr) {
+            channel_->handles.fetch_add(1, std::memory_order_relaxed);
+            channel_->receivers.fetch_add(1, std::memory_order_relaxed);
+        }
+    }
+
+    void release() noexcept
+    {
+        if (channel_ == nullptr) {
+            return;
+        }
+        if (channel_->receivers.fetch_sub(1, std::memory_order_acq_rel) == 1) {
+            channel_->queue.close();
+        }
+        std::exchange(channel_, nullptr)->release();
+    }
+
+    Channel *channel_;
+};
+
+/// @brief Makes a channel on a new 'Queue' constructed by the 'args', and returns its first sender
+/// and receiver.
+/// @tparam Queue The inner non-blocking queue type, which must not have a blocking @c dequeue.
+template<typename Queue, typename... Args>
+std::pair<Sender<Queue>, Receiver<Queue>> make_channel(Args &&... args)
+{
+    static_assert(!impl::HasDequeue<Queue>::value, "The queue must be a non-blocking queue.");
+
+    auto *channel = new impl::Channel<Queue>(std::forward<Args>(args)...);
+    return {Sender<Queue>(channel), Receiver<Queue>(channel)};
+}
+
+}
+
+#endif //SYNC_CELL_CHANNEL_HPP
@@ -0,0 +1,424 @@
+///
+/// @file  delay_queue.hpp
+/// @brief A queue whose items can be popped only after their deadlines, backed by a hashed timer
+/// wheel.
+///
+
+#ifndef SYNC_CELL_DELAY_QUEUE_HPP
+#define SYNC_CELL_DELAY_QUEUE_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The delay_queue.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <algorithm>
+#include <atomic>
+#include <bit>
+#include <chrono>
+#include <condition_variable>
+#include <cstddef>
+#include <cstdint>
+#include <deque>
+#include <memory>
+#include <mutex>
+#include <optional>
+#include <utility>
+#include <vector>
+
+#if __cpp_impl_coroutine
+#include <coroutine>
+#endif
+
+#include "shared/queue_len.hpp"
+
+
+namespace sc {
+
+/// @brief A queue for the retry and timeout scheduling: each item is pushed with a deadline, and
+/// can be popped once the deadline has passed. The items expiring in the same tick are popped in
+/// the order of pushing.
+///
+/// The pending items are kept in a hashed timer wheel: the time is split into the ticks, and an
+/// item is put in the slot of its deadline tick modulo the slot count, so the push is O(1), and
+/// advancing the wheel only visits the slots of the passed ticks. An item is never popped before
+/// its deadline, but may be popped up to one tick after it.
+///
+/// The expired items are popped by @c try_pop_expired, the blocking @c pop_expired which sleeps
+/// until the next deadline, or @c co_await @c pop_expired_async.
+///
+/// @example
+/// ``` cpp
+/// sc::DelayQueue<Request> retries;
+/// retries.push_after(request, std::chrono::seconds(1));
+/// // In the retrying thread.
+/// auto request = retries.pop_expired();
+/// ```
+/// @note Nothing wakes the awaiting coroutines by itself: they are resumed inside @c poll (or any
+/// other call finding the expired items), so a driver thread must call @c poll periodically, e.g.
+/// once per tick. A coroutine is resumed on the calling thread, reschedule it after the
+/// @c co_await returns if it must run on a specific executor.
+/// @tparam T The value type.
+template<typename T>
+class DelayQueue
+{
+public:
+    using value_type = T;
+    using clock_type = std::chrono::steady_clock;
+    using time_point = clock_type::time_point;
+    using duration = clock_type::duration;
+
+private:
+    struct Entry
+    {
+        uint64_t tick;
+        T value;
+    };
+
+#if __cpp_impl_coroutine
+    struct Waiter
+    {
+        std::coroutine_handle<> handle;
+        std::optional<T> value;
+        Waiter *next = nullptr;
+    };
+#else
+    struct Waiter
+    {
+        Waiter *next = nullptr;
+    };
+#endif
+
+public:
+#if __cpp_impl_coroutine
+    class PopAwaiter;
+#endif
+
+    static constexpr size_t DefaultSlotCount = 256;
+
+    /// @brief Creates an empty queue.
+    /// @param tick The time resolution of the deadlines.
+    /// @param slot_count The count of the wheel slots, rounded up to a power of 2. The items more
+    /// than 'tick * slot_count' away stay in their slots for more rounds.
+    explicit DelayQueue(duration tick = std::chrono::milliseconds(1), size_t slot_count = DefaultSlotCount)
+            : tick_(std::max(tick, duration(1))),
+              slot_count_(std::bit_ceil(std::max<size_t>(slot_count, 1))),
+              slots_(std::make_unique<std::vector<Entry>[]>(slot_count_)),
+              start_(clock_type::now())
+    {
+    }
+
+    DelayQueue(const DelayQueue &) = delete;
+
+    DelayQueue &operator=(const DelayQueue &) = delete;
+
+    /// @brief Returns the count of the items, expired or not. It is a snapshot under the lock,
+    /// which may be stale once returned if other threads are pushing or popping.
+    [[nodiscard]] size_t len() const
+    {
+        std::lock_guard guard(mtx_);
+        return pending_ + ready_.size();
+    }
+
+    /// @brief Same as @c len.
+    [[nodiscard]] size_t size() const
+    {
+        return len();
+    }
+
+    [[nodiscard]] bool is_empty() const
+    {
+        return len() == 0;
+    }
+
+    /// @brief Returns @c UnboundedCapacity, the wheel slots grow for the items.
+    [[nodiscard]] static constexpr size_t capacity() noexcept
+    {
+        return UnboundedCapacity;
+    }
+
+    /// @brief Returns @c UnboundedCapacity, a push never fails for a full queue.
+    [[nodiscard]] static constexpr size_t remaining() noexcept
+    {
+        return UnboundedCapacity;
+    }
+
+    /// @brief Pushes the item, which expires at the 'deadline'.
+    void push_at(value_type value, time_point deadline)
+    {
+        Waiter *granted;
+        {
+            std::lock_guard guard(mtx_);
+            // Rounds up, so the item is never popped before the deadline.
+            auto since = std::max(deadline - start_, duration::zero());
+            auto tick = static_cast<uint64_t>((since + tick_ - duration(1)) / tick_);
+            if (tick <= current_tick_) {
+                ready_.push_back(std::move(value));
+            } else {
+                slots_[tick & (slot_count_ - 1)].push_back(Entry{tick, std::move(value)});
+                ++pending_;
+            }
+            granted = collect(clock_type::now());
+        }
+        // The new deadline may be earlier than the one the blocked threads are waiting for.
+        if (waiters_.load(std::memory_order_relaxed) != 0) {
+            cond_var_.notify_all();
+        }
+        wake(granted);
+    }
+
+    /// @brief Pushes the item, which expires after the 'delay'.
+    template<typename Rep, typename Period>
+    void push_after(value_type value, const std::chrono::duration<Rep, Period> &delay)
+    {
+        push_at(std::move(value), clock_type::now() + std::chrono::duration_cast<duration>(delay));
+    }
+
+    /// @brief Pops an expired item without blocking.
+    /// @return An empty optional if no item has expired.
+    std::optional<value_type> try_pop_expired()
+    {
+        std::optional<value_type> value;
+        Waiter *granted;
+        {
+            std::lock_guard guard(mtx_);
+            granted = collect(clock_type::now());
+            value = take_ready();
+        }
+        wake(granted);
+        return value;
+    }
+
+    /// @brief Pops an expired item, blocks the current thread until an item expires.
+    value_type pop_expired()
+    {
+        std::unique_lock lock(mtx_);
+        while (true) {
+            auto *granted = collect(clock_type::now());
+            if (granted != nullptr) {
+                lock.unlock();
+                wake(granted);
+                lock.lock();
+                continue;
+            }
+            if (auto value = take_ready()) {
+                return *std::move(value);
+            }
+
+            waiters_.fetch_add(1, std::memory_order_relaxed);
+            if (pending_ == 0) {
+                cond_var_.wait(lock);
+            } else {
+                cond_var_.wait_until(lock, next_deadline());
+            }
+            waiters_.fetch_sub(1, std::memory_order_relaxed);
+        }
+    }
+
+#if __cpp_impl_coroutine
+    /// @brief Pops an expired item asynchronously, see the note of the class about the driver.
+    /// @example
+    /// ``` cpp
+    /// auto request = co_await retries.pop_expired_async();
+    /// ```
+    [[nodiscard]] PopAwaiter pop_expired_async() noexcept
+    {
+        return PopAwaiter(*this);
+    }
+#endif
+
+    /// @brief Advances the wheel to now, and resumes the coroutines awaiting the expired items.
+    /// @return The count of the resumed coroutines.
+    size_t poll()
+    {
+        Waiter *granted;
+        {
+            std::lock_guard guard(mtx_);
+            granted = collect(clock_type::now());
+        }
+        return wake(granted);
+    }
+
+private:
+    [[nodiscard]] uint64_t tick_of(time_point now) const noexcept
+    {
+        return static_cast<uint64_t>(std::max(now - start_, duration::zero()) / tick_);
+    }
+
+    /// @brief Moves the items from the slot expired at the 'tick' to the ready items, keeping the
+    /// pushing order. Must be called with 'mtx_' locked.
+    void expire_slot(size_t index, uint64_t tick)
+    {
+        auto &slot = slots_[index];
+        auto kept = slot.begin();
+        for (auto &entry: slot) {
+            if (entry.tick <= tick) {
+                ready_.push_back(std::move(entry.value));
+                --pending_;
+            } else {
+                if (&*kept != &entry) {
+                    *kept = std::move(entry);
+                }
+                ++kept;
+            }
+        }
+        slot.erase(kept, slot.end());
+    }
+
+    /// @brief Advances the wheel to 'now', and hands the ready items to the awaiting coroutines.
+    /// Must be called with 'mtx_' locked.
+    /// @return The granted coroutine waiters to resume, linked by their 'next'.
+    Waiter *collect(time_point now)
+    {
+        auto tick = tick_of(now);
+        if (tick > current_tick_ && pending_ > 0) {
+            if (tick - current_tick_ >= slot_count_) {
+                // A whole round has passed, visit each slot once.
+                for (size_t i = 0; i < slot_count_; ++i) {
+                    expire_slot(i, tick);
+                }
+            } else {
+                for (auto t = current_tick_ + 1; t <= tick; ++t) {
+                    expire_slot(t & (slot_count_ - 1), tick);
+                }
+            }
+        }
+        current_tick_ = std::max(current_tick_, tick);
+
+        Waiter *granted = nullptr;
+#if __cpp_impl_coroutine
+        Waiter **tail = &granted;
+        while (head_ != nullptr && !ready_.empty()) {
+            auto *waiter = head_;
+            head_ = waiter->next;
+            waiter->value.emplace(std::move(ready_.front()));
+            ready_.pop_front();
+            waiter->next = nullptr;
+            *tail = waiter;
+            tail = &waiter->next;
+        }
+        if (head_ == nullptr) {
+            tail_ = &head_;
+        }
+#endif
+        return granted;
+    }
+
+    /// @brief Must be called with 'mtx_' locked.
+    std::optional<value_type> take_ready()
+    {
+        if (ready_.empty()) {
+            return {};
+        }
+        std::optional<value_type> value(std::move(ready_.front()));
+        ready_.pop_front();
+        return value;
+    }
+
+    /// @brief Returns the time of the earliest pending deadline tick in the next round, or the end
+    /// of the round if all pending items are in the later rounds. Must be called with 'mtx_' locked.
+    [[nodiscard]] time_point next_deadline() const
+    {
+        for (auto t = current_tick_ + 1; t <= current_tick_ + slot_count_; ++t) {
+            auto &slot = slots_[t & (slot_count_ - 1)];
+            if (std::any_of(slot.begin(), slot.end(), [t](const Entry &e) { return e.tick == t; })) {
+                return start_ + tick_ * t;
+            }
+        }
+        return start_ + tick_ * (current_tick_ + slot_count_);
+    }
+
+    /// @brief Resumes the granted coroutines. Must be called without 'mtx_' locked.
+    size_t wake(Waiter *granted)
+    {
+        size_t count = 0;
+#if __cpp_impl_coroutine
+        while (granted != nullptr) {
+            // The coroutine may destroy the waiter after resumed.
+            auto handle = granted->handle;
+            granted = granted->next;
+            handle.resume();
+            ++count;
+        }
+#else
+        (void) granted;
+#endif
+        return count;
+    }
+
+    duration tick_;
+    size_t slot_count_;
+    std::unique_ptr<std::vector<Entry>[]> slots_;
+    time_point start_;
+    /// @brief The last tick whose slot has been expired.
+    uint64_t current_tick_ = 0;
+    /// @brief Count of the items in the wheel.
+    size_t pending_ = 0;
+    /// @brief The expired items not popped yet.
+    std::deque<T> ready_;
+
+    /// @brief Count of the threads blocked in 'pop_expired', to skip the notification.
+    std::atomic<size_t> waiters_{0};
+    mutable std::mutex mtx_;
+    std::condition_variable cond_var_;
+#if __cpp_impl_coroutine
+    /// @brief The FIFO list of the awaiting coroutines.
+    Waiter *head_ = nullptr;
+    Waiter **tail_ = &head_;
+#endif
+};
+
+#if __cpp_impl_coroutine
+
+/// @brief The awaitable object returned by @c DelayQueue::pop_expired_async.
+template<typename T>
+class DelayQueue<T>::PopAwaiter
+{
+    friend class DelayQueue;
+
+    explicit PopAwaiter(DelayQueue &queue) noexcept : queue_(queue) { }
+
+public:
+    PopAwaiter(const PopAwaiter &) = delete;
+
+    PopAwaiter &operator=(const PopAwaiter &) = delete;
+
+    bool await_ready()
+    {
+        waiter_.value = queue_.try_pop_expired();
+        return waiter_.value.has_value();
+    }
+
+    bool await_suspend(std::coroutine_handle<> handle)
+    {
+        waiter_.handle = handle;
+
+        std::lock_guard guard(queue_.mtx_);
+        // Check again under the lock to avoid missing the items expired meanwhile.
+        if (queue_.head_ == nullptr) {
+            waiter_.value = queue_.take_ready();
+            if (waiter_.value) {
+                return false;
+            }
+        }
+        *queue_.tail_ = &waiter_;
+        queue_.tail_ = &waiter_.next;
+        return true;
+    }
+
+    value_type await_resume()
+    {
+        return *std::move(waiter_.value);
+    }
+
+private:
+    DelayQueue &queue_;
+    Waiter waiter_;
+};
+
+#endif
+
+}
+
+#endif //SYNC_CELL_DELAY_QUEUE_HPP
@@ -7,12 +7,19 @@
 #ifndef SYNC_CELL_MPMC_ARRAY_QUEUE_HPP
 #define SYNC_CELL_MPMC_ARRAY_QUEUE_HPP
 
+#include <algorithm>
 #include <atomic>
+#include <iterator>
 #include <memory>
 #include <optional>
+#include <vector>
 
 #include "shared/compiler_workaround.hpp"
+#include "shared/drain.hpp"
+#include "shared/extend.hpp"
+#include "shared/metrics.hpp"
 #include "shared/object_cache_pool.hpp"
+#include "shared/queue_len.hpp"
 #include "util/back_off.hpp"
 #include "util/cache_padded.hpp"
 #include "util/copy_move_selector.hpp"
@@ -24,7 +31,9 @@ namespace sc::mpmc {
 /// it may wait for another thread to complete progress by using the @c YIELD or @c PAUSE instruction
 /// and the current thread may yield by giving up the time slice to the OS scheduler.
 /// @tparam T The value type.
-template<typename T>
+/// @tparam Backoff The backoff when waiting for another thread to complete its operation.
+/// @tparam Allocator The allocator of the blocks.
+template<typename T, typename Backoff = util::Backoff, typename Allocator = std::allocator<T>>
 class ArrayListQueue
 {
     // Bits indicating the state of a slot:
@@ -58,7 +67,7 @@ class ArrayListQueue
         /// @brief Waits until a task is written into the slot.
         void wait_write() const noexcept
         {
-            util::Backoff backoff;
+            Backoff backoff;
             while ((state.load(std::memory_order_acquire) & Write) == 0) {
                 backoff.snooze();
             }
@@ -81,7 +90,7 @@ class ArrayListQueue
         /// @return The next pointer value.
         Block *wait_next() const noexcept
         {
-            util::Backoff backoff;
+            Backoff backoff;
             while (true) {
                 auto *n = next.load(std::memory_order_acquire);
                 if (n) {
@@ -99,7 +108,7 @@ class ArrayListQueue
     /// on a concurrent @c ArrayListQueue::enqueue call.
     /// @tparam N The pool size.
     template<uint32_t N = DefaultPoolSize>
-    using BlockCachePool = ObjectCachePool<Block, N>;
+    using BlockCachePool = ObjectCachePool<Block, N, Allocator>;
 
     template<uint32_t N = DefaultPoolSize>
     struct PoolBlockDeleter
@@ -156,9 +165,13 @@ public:
     using value_type = T;
     using reference = value_type &;
     using const_reference = const value_type &;
+    using allocator_type = Allocator;
 
     /// @brief Creates a new queue.
-    ArrayListQueue()
+    ArrayListQueue() : ArrayListQueue(Allocator()) { }
+
+    /// @brief Creates a new queue allocating the blocks from 'alloc'.
+    explicit ArrayListQueue(const Allocator &alloc) : pool_(alloc)
     {
         auto *block = pool_.TEMPLATE_CALL alloc();
         (*head_).block.store(block);
@@ -167,10 +180,95 @@ public:
         (*tail_).index.store(0);
     }
 
+    /// @brief Creates a queue with the items of [first, last) in order.
+    template<std::input_iterator It, typename Sentinel>
+    ArrayListQueue(It first, Sentinel last, const Allocator &alloc = Allocator()) : ArrayListQueue(alloc)
+    {
+        extend(std::move(first), std::move(last));
+    }
+
     ArrayListQueue(const ArrayListQueue &) = delete;
 
     ArrayListQueue &operator=(const ArrayListQueue &) = delete;
 
+    /// @brief Releases the blocks from the head to the tail, with the values not dequeued.
+    ~ArrayListQueue()
+    {
+        auto head = (*head_).index.load(std::memory_order_relaxed) & ~((1u << Shift) - 1);
+        auto tail = (*tail_).index.load(std::memory_order_relaxed) & ~((1u << Shift) - 1);
+        auto *block = (*head_).block.load(std::memory_order_relaxed);
+        for (; head != tail; head += 1u << Shift) {
+            if ((head >> Shift) % Lap == BlockCap) {
+                pool_.dealloc(std::exchange(block, block->next.load(std::memory_order_relaxed)));
+            }
+        }
+        pool_.dealloc(block);
+    }
+
+    /// @brief Returns the count of the items in the queue. It is a racy snapshot: the count may be
+    /// stale once returned if other threads are enqueuing or dequeuing.
+    [[nodiscard]] size_t len() const noexcept
+    {
+        while (true) {
+            // Load the tail, then the head, and retry if the tail moved, so they are consistent.
+            auto tail = (*tail_).index.load(std::memory_order_seq_cst);
+            auto head = (*head_).index.load(std::memory_order_seq_cst);
+            if ((*tail_).index.load(std::memory_order_seq_cst) != tail) {
+                continue;
+            }
+
+            tail >>= Shift;
+            head >>= Shift;
+            // An index at the end of a block is the first one of the next block.
+            if (tail % Lap == BlockCap) {
+                ++tail;
+            }
+            if (head % Lap == BlockCap) {
+                ++head;
+            }
+
+            // Rebase both indices to the block of the head, and skip the unused last index of each
+            // block passed by the tail.
+            auto base = head / Lap * Lap;
+            tail -= base;
+            head -= base;
+            return tail - head - tail / Lap;
+        }
+    }
+
+    /// @brief Returns true if the queue is empty, a racy snapshot as @c len.
+    [[nodiscard]] bool is_empty() const noexcept
+    {
+        auto head = (*head_).index.load(std::memory_order_seq_cst);
+        auto tail = (*tail_).index.load(std::memory_order_seq_cst);
+        return (head >> Shift) == (tail >> Shift);
+    }
+
+    /// @brief Returns @c UnboundedCapacity, the queue grows by blocks.
+    [[nodiscard]] static constexpr size_t capacity() noexcept
+    {
+        return UnboundedCapacity;
+    }
+
+    /// @brief Returns @c UnboundedCapacity, an enqueue never fails for a full queue.
+    [[nodiscard]] static constexpr size_t remaining() noexcept
+    {
+        return UnboundedCapacity;
+    }
+
+    /// @brief Returns the contention counters: the lost races on the head and tail indices as the
+    /// CAS failures, and the waits for the next block to be installed as the spin iterations.
+    /// Requires @c SYNC_CELL_METRICS.
+    [[nodiscard]] metrics::Stats stats() const noexcept
+    {
+        return metrics_.snapshot();
+    }
+
+    [[nodiscard]] allocator_type get_allocator() const noexcept
+    {
+        return allocator_type(pool_.get_allocator());
+    }
+
     template<typename = std::enable_if_t<std::is_copy_constructible_v<value_type>>>
     void enqueue(const_reference value)
     {
@@ -187,11 +285,51 @@ public:
         });
     }
 
+    /// @brief Enqueues the items of [first, last) in order, by @c enqueue_batch for a forward range.
+    template<std::input_iterator It, typename Sentinel>
+    void extend(It first, Sentinel last)
+    {
+        if constexpr(std::forward_iterator<It> && std::is_same_v<It, Sentinel>) {
+            enqueue_batch(std::move(first), std::move(last));
+        } else {
+            sc::impl::extend(std::move(first), std::move(last), [this](auto &&v) {
+                enqueue(std::forward<decltype(v)>(v));
+            });
+        }
+    }
+
+    /// @brief Enqueues the items of the 'range' in order, e.g. a container or a view.
+    template<typename Range>
+    void extend(Range &&range)
+    {
+        extend(std::begin(range), std::end(range));
+    }
+
+    /// @brief Enqueue all items in the range [first, last). The slots of a block are reserved with
+    /// one atomic operation, so the synchronization cost is amortized over many items.
+    ///
+    /// The items enqueued by one call are adjacent in the queue, unless the range spans over more
+    /// than one block.
+    template<typename ForwardIt>
+    void enqueue_batch(ForwardIt first, ForwardIt last)
+    {
+        static_assert(std::forward_iterator<ForwardIt>);
+
+        auto count = (size_t)std::distance(first, last);
+        while (count > 0) {
+            auto n = enqueue_values(count, [&first](std::optional<value_type> &o) {
+                o.TEMPLATE_CALL emplace(*first);
+                ++first;
+            });
+            count -= n;
+        }
+    }
+
     /// @brief Try dequeue an item from the queue.
     /// @return If success, the optional takes the dequeue value, otherwise the optional is empty.
     std::optional<value_type> try_dequeue()
     {
-        util::Backoff backoff;
+        Backoff backoff;
         size_t head;
         Block *block;
         size_t offset;
@@ -205,6 +343,7 @@ public:
 
             // If we reached the end of the block
             if (offset == BlockCap) {
+                metrics_.spin();
                 backoff.snooze();
             } else {
                 break;
@@ -232,6 +371,7 @@ public:
                 head, new_head,
                 std::memory_order_seq_cst,
                 std::memory_order_relaxed)) {
+            metrics_.cas_failure();
             return {};
         }
 
@@ -262,11 +402,161 @@ public:
         return ret;
     }
 
+    /// @brief Try dequeue at most 'max' items from the queue, with one atomic operation on the
+    /// head index. The items are appended to 'out' in the FIFO order.
+    ///
+    /// Only the items in the current head block are dequeued, so the result may be less than
+    /// 'max' even if the queue has more items.
+    /// @return The count of the dequeued items. Returns 0 if the queue is empty or a concurrent
+    /// dequeue operation wins the race.
+    size_t try_dequeue_batch(std::vector<value_type> &out, size_t max)
+    {
+        if (max == 0) {
+            return 0;
+        }
+
+        Backoff backoff;
+        size_t head;
+        Block *block;
+        size_t offset;
+
+        while (true) {
+            head = (*head_).index.load(std::memory_order_acquire);
+            block = (*head_).block.load(std::memory_order_acquire);
+
+            // Calculate the offset of the index into the block.
+            offset = (head >> Shift) % Lap;
+
+            // If we reached the end of the block
+            if (offset == BlockCap) {
+                metrics_.spin();
+                backoff.snooze();
+            } else {
+                break;
+            }
+        }
+
+        auto new_head = head;
+        size_t advance;
+        if ((new_head & HasNext) == 0) {
+            std::atomic_thread_fence(std::memory_order_seq_cst);
+            auto tail = (*tail_).index.load(std::memory_order_relaxed);
+
+            // If the tail equals the head, that means the queue is empty.
+            if ((head >> Shift) == (tail >> Shift)) {
+                return 0;
+            }
+
+            // If head and tail are not in the same block, set 'HasNext' in head and dequeue all
+            // remaining items of the block. Otherwise, dequeue the items until the tail.
+            if ((head >> Shift) / Lap != (tail >> Shift) / Lap) {
+                new_head |= HasNext;
+                advance = std::min(BlockCap - offset, max);
+            } else {
+                advance = std::min(((tail - head) >> Shift), max);
+            }
+        } else {
+            advance = std::min(BlockCap - offset, max);
+        }
+
+        new_head += advance << Shift;
+        auto new_offset = offset + advance;
+
+        // Try moving the head index forward.
+        if (!(*head_).index.compare_exchange_weak(
+                head, new_head,
+                std::memory_order_seq_cst,
+                std::memory_order_relaxed)) {
+            metrics_.cas_failure();
+            return 0;
+        }
+
+        // If we've reached the end of the block, move to the next one.
+        if (new_offset == BlockCap) {
+            auto next = block->wait_next();
+            auto next_index = (new_head & ~HasNext) + (1u << Shift);
+            if (next->next.load(std::memory_order_relaxed) != nullptr) {
+                next_index |= HasNext;
+            }
+
+            (*head_).block.store(next, std::memory_order_release);
+            (*head_).index.store(next_index, std::memory_order_release);
+        }
+
+        // Read the slots
+        for (auto i = offset; i < new_offset; ++i) {
+            auto &slot = block->slots[i];
+            slot.wait_write();
+            out.emplace_back(util::cast_ctor_ref(*slot.value));
+        }
+
+        // Destroy the block if we've reached the end, or if another thread wanted to destroy
+        // but couldn't because we were busy reading from the slots.
+        if (new_offset == BlockCap) {
+            destroy_block(block, (uint32_t)offset, pool_);
+        } else {
+            for (auto i = offset; i < new_offset; ++i) {
+                if ((block->slots[i].state.fetch_or(Read, std::memory_order_acq_rel) & Destroy) != 0) {
+                    destroy_block(block, (uint32_t)offset, pool_);
+                    break;
+                }
+            }
+        }
+
+        return advance;
+    }
+
+    /// @brief Takes the items in the queue by chunks, each chunk is a @c try_dequeue_batch, which
+    /// is retried if a concurrent dequeue operation wins the race.
+    /// @param chunk The max count of the items in one chunk, at most one block is taken at a time.
+    /// @return A @c Drain range, which stops after the items in the queue when it is called.
+    auto drain(size_t chunk = DefaultDrainChunk)
+    {
+        auto take = [this](std::vector<value_type> &out, size_t max) {
+            size_t count;
+            while ((count = try_dequeue_batch(out, max)) == 0 && !is_empty()) { }
+            return count;
+        };
+        return Drain<value_type, decltype(take)>(take, chunk, len());
+    }
+
+    /// @brief Moves the items out of the queue in the FIFO order, without synchronization. The
+    /// caller must guarantee no other thread is accessing the queue, and the queue is not used
+    /// after. The blocks are released by the destructor.
+    std::vector<value_type> into_inner() &&
+    {
+        auto head = (*head_).index.load(std::memory_order_relaxed) & ~((1u << Shift) - 1);
+        auto tail = (*tail_).index.load(std::memory_order_relaxed) & ~((1u << Shift) - 1);
+        auto *block = (*head_).block.load(std::memory_order_relaxed);
+        std::vector<value_type> values;
+        values.reserve(len());
+        for (; head != tail; head += 1u << Shift) {
+            auto offset = (head >> Shift) % Lap;
+            if (offset == BlockCap) {
+                block = block->next.load(std::memory_order_relaxed);
+            } else {
+                auto &slot = block->slots[offset];
+                values.emplace_back(util::cast_ctor_ref(*slot.value));
+                slot.value.reset();
+            }
+        }
+        return values;
+    }
+
 private:
     template<typename Func>
     void enqueue_value(Func value_set)
     {
-        util::Backoff backoff;
+        enqueue_values(1, value_set);
+    }
+
+    /// @brief Reserves at most 'max' slots in the tail block, and sets the values of them.
+    /// @param value_set The function to set a value, which is called in the order of the slots.
+    /// @return The count of the reserved slots, which is at least 1.
+    template<typename Func>
+    size_t enqueue_values(size_t max, Func &&value_set)
+    {
+        Backoff backoff;
         auto tail = (*tail_).index.load(std::memory_order_acquire);
         auto *block = (*tail_).block.load(std::memory_order_acquire);
         PoolBlockPtr next_block;
@@ -279,19 +569,23 @@ private:
             // thread whose offset is equal to the 'BlockCap - 1', and that thread will
             // set the next block.
             if (offset == BlockCap) {
+                metrics_.spin();
                 backoff.snooze();
                 tail = (*tail_).index.load(std::memory_order_acquire);
                 block = (*tail_).block.load(std::memory_order_acquire);
                 continue;
             }
 
+            auto count = std::min(BlockCap - offset, max);
+            auto new_offset = offset + count;
+
             // If we're going to have to install the next block, allocate it in advance
             // in order to make the wait for other threads as short as possible.
-            if (offset + 1 == BlockCap && !next_block) {
+            if (new_offset == BlockCap && !next_block) {
                 next_block = new_block(pool_);
             }
 
-            auto new_tail = tail + (1u << Shift);
+            auto new_tail = tail + (count << Shift);
 
             // Try advancing the tail forward.
             if ((*tail_).index.compare_exchange_weak(
@@ -299,7 +593,7 @@ private:
                     std::memory_order_seq_cst,
                     std::memory_order_acquire)) {
                 // If we've reached the end of the block, install the next one.
-                if (offset + 1 == BlockCap) {
+                if (new_offset == BlockCap) {
                     // this progress is excluded.
                     auto *b = next_block.release();
                     auto next_index = new_tail + (1u << Shift);
@@ -309,14 +603,17 @@ private:
                     block->next.store(b, std::memory_order_release);
                 }
 
-                // Write the task into the slot.
-                auto &slot = block->slots[offset];
-                value_set(slot.value);
-                slot.state.fetch_or(Write, std::memory_order_release);
+                // Write the tasks into the slots.
+                for (auto i = offset; i < new_offset; ++i) {
+                    auto &slot = block->slots[i];
+                    value_set(slot.value);
+                    slot.state.fetch_or(Write, std::memory_order_release);
+                }
 
-                return;
+                return count;
             } else {
                 block = (*tail_).block.load(std::memory_order_acquire);
+                metrics_.cas_failure();
                 backoff.spin();
             }
         }
@@ -329,6 +626,7 @@ private:
 
     /// @brief Default cache pool.
     BlockCachePool<> pool_;
+    SC_NO_UNIQUE_ADDRESS metrics::Counters metrics_;
 };
 
 }
@@ -0,0 +1,783 @@
+///
+/// @file  mpmc_bounded_queue.hpp
+/// @brief A bounded mpmc queue. Original rust implement:
+/// [ArrayQueue](https://github.com/crossbeam-rs/crossbeam/blob/master/crossbeam-queue/src/array_queue.rs).
+///
+
+#ifndef SYNC_CELL_MPMC_BOUNDED_QUEUE_HPP
+#define SYNC_CELL_MPMC_BOUNDED_QUEUE_HPP
+
+#include <algorithm>
+#include <array>
+#include <atomic>
+#include <bit>
+#include <cassert>
+#include <cstddef>
+#include <functional>
+#include <iterator>
+#include <memory>
+#include <optional>
+#include <type_traits>
+#include <utility>
+#include <vector>
+
+#include "shared/alloc_array.hpp"
+#include "shared/compiler_workaround.hpp"
+#include "shared/extend.hpp"
+#include "shared/metrics.hpp"
+#include "util/back_off.hpp"
+#include "util/cache_padded.hpp"
+#include "util/copy_move_selector.hpp"
+#include "util/thread_id.hpp"
+
+
+namespace sc::mpmc {
+
+/// @brief What @c BoundedQueue::enqueue does when the queue is full.
+enum class Overflow
+{
+    /// @brief Waits until a slot is released by a consumer.
+    Block,
+    /// @brief Fails without touching the value.
+    Reject,
+    /// @brief Dequeues the oldest value to make room, which is passed to the drop handler.
+    DropOldest,
+    /// @brief Passes the new value to the drop handler instead of enqueuing it.
+    DropNewest,
+};
+
+namespace bounded_detail {
+
+/// @brief Excludes the operations of a @c BoundedQueue while its buffer is replaced. Each operation
+/// is counted in the shard of its thread, so the operations do not contend on one counter.
+template<typename Backoff>
+class ResizeGate
+{
+    static constexpr size_t ShardCount = 16;
+
+public:
+    /// @brief Enters an operation, waits if the buffer is being replaced.
+    /// @return The shard to leave.
+    size_t enter() noexcept
+    {
+        auto shard = util::thread_id() & (ShardCount - 1);
+        while (true) {
+            // Pairs with the 'lock': either the resizer sees this operation, or this operation
+            // sees the resizer.
+            active_[shard]->fetch_add(1, std::memory_order_seq_cst);
+            if (!resizing_->load(std::memory_order_seq_cst)) {
+                return shard;
+            }
+            active_[shard]->fetch_sub(1, std::memory_order_release);
+
+            Backoff backoff;
+            while (resizing_->load(std::memory_order_acquire)) {
+                backoff.snooze();
+            }
+        }
+    }
+
+    void leave(size_t shard) noexcept
+    {
+        active_[shard]->fetch_sub(1, std::memory_order_release);
+    }
+
+    /// @brief Stops the new operations, and waits until the running ones leave.
+    void lock() noexcept
+    {
+        Backoff backoff;
+        while (resizing_->exchange(true, std::memory_order_seq_cst)) {
+            backoff.snooze();
+        }
+        backoff.reset();
+        while (!is_idle()) {
+            backoff.snooze();
+        }
+    }
+
+    void unlock() noexcept
+    {
+        resizing_->store(false, std::memory_order_release);
+    }
+
+private:
+    [[nodiscard]] bool is_idle() const noexcept
+    {
+        size_t active = 0;
+        for (auto &shard: active_) {
+            active += shard->load(std::memory_order_seq_cst);
+        }
+        return active == 0;
+    }
+
+    std::array<util::CachePadded<std::atomic<size_t>>, ShardCount> active_{};
+    util::CachePadded<std::atomic<bool>> resizing_;
+};
+
+/// @brief The drop handler of the compile-time capacity, which is not constexpr constructible.
+struct NoDropHandler
+{
+};
+
+/// @brief The gate of a queue whose buffer is never replaced, which costs nothing.
+struct NoResizeGate
+{
+    static constexpr size_t enter() noexcept
+    {
+        return 0;
+    }
+
+    static constexpr void leave(size_t) noexcept { }
+};
+
+/// @brief Enters the gate for the scope.
+template<typename Gate>
+class GateScope
+{
+public:
+    explicit GateScope(Gate &gate) noexcept : gate_(gate), shard_(gate.enter()) { }
+
+    GateScope(const GateScope &) = delete;
+
+    GateScope &operator=(const GateScope &) = delete;
+
+    ~GateScope()
+    {
+        gate_.leave(shard_);
+    }
+
+private:
+    Gate &gate_;
+    size_t shard_;
+};
+
+/// @brief Locks the gate for the scope.
+template<typename Backoff>
+class GateLock
+{
+public:
+    explicit GateLock(ResizeGate<Backoff> &gate) noexcept : gate_(gate)
+    {
+        gate_.lock();
+    }
+
+    GateLock(const GateLock &) = delete;
+
+    GateLock &operator=(const GateLock &) = delete;
+
+    ~GateLock()
+    {
+        gate_.unlock();
+    }
+
+private:
+    ResizeGate<Backoff> &gate_;
+};
+
+}
+
+/// @brief A bounded FIFO queue that can be shared among multiple threads.
+///
+/// The queue allocates a fixed-capacity buffer on construction, which is used to store the
+/// enqueued values. The @c try_enqueue and @c try_dequeue operations are lock-free, and the
+/// @c try_enqueue reports a full queue instead of growing the buffer, so it can be used for
+/// the backpressure-sensitive cases.
+///
+/// Each slot in the buffer has a @a stamp, which is a combination of a @a lap and an @a index,
+/// the head and tail indices of the queue are also composed in the same way. The stamp tells
+/// whether the slot is ready to be written (stamp == tail) or to be read (stamp == head + 1).
+///
+/// With a non-zero 'Capacity', the buffer is held inline and the queue has a constexpr default
+/// constructor, so it can be a @c constinit static (see @c FixedBoundedQueue). With 'Resizable'
+/// (see @c ResizableBoundedQueue), the capacity can be changed by @c reserve and
+/// @c shrink_to_fit, which migrate the values to a new buffer: the operations are counted in a
+/// sharded counter to be excluded during the migration, so they are not lock-free any more. The
+/// queues which are not resizable pay nothing for it.
+///
+/// The @c Overflow policy given on construction decides what @c enqueue does with a full queue,
+/// e.g. @c Overflow::DropOldest for the telemetry which prefers the new data to blocking the
+/// producers. The @c try_enqueue always fails on a full queue.
+///
+/// @example
+/// ``` cpp
+/// sc::mpmc::BoundedQueue<Sample> samples(1024, sc::mpmc::Overflow::DropOldest,
+///                                        [&](Sample &&) { dropped.add(); });
+/// samples.enqueue(Sample{...});   // Never blocks.
+/// ```
+/// @tparam T The value type.
+/// @tparam Backoff The backoff when waiting for another thread to complete its operation.
+/// @tparam Capacity The compile-time capacity, or 0 for the capacity given on construction.
+/// @tparam Allocator The allocator of the buffer, unused with the inline buffer of the compile-time
+/// capacity.
+/// @tparam Resizable Enables @c reserve and @c shrink_to_fit for the capacity given on construction.
+template<typename T, typename Backoff = util::Backoff, size_t Capacity = 0, typename Allocator = std::allocator<T>,
+        bool Resizable = false>
+class BoundedQueue
+{
+    static constexpr bool IsFixed = Capacity != 0;
+    static_assert(!(IsFixed && Resizable), "The compile-time capacity can not be resized.");
+
+    /// @brief A slot in the buffer.
+    struct Slot
+    {
+        /// @brief The current stamp.
+        std::atomic<size_t> stamp{0};
+        /// @brief The value saved.
+        std::optional<T> value;
+
+        constexpr Slot() noexcept = default;
+
+        constexpr explicit Slot(size_t stamp) noexcept : stamp(stamp) { }
+    };
+
+    using Buffer = std::conditional_t<IsFixed, std::array<Slot, Capacity>, impl::AllocArray<Slot, Allocator>>;
+
+    /// @brief The capacity and the stamp with the value of '{ lap: 1, index: 0 }', only stored for
+    /// the capacity given on construction, they are constants for the compile-time capacity.
+    struct DynamicBounds
+    {
+        size_t cap;
+        size_t one_lap;
+    };
+
+    struct FixedBounds
+    {
+    };
+
+    using Gate = std::conditional_t<Resizable, bounded_detail::ResizeGate<Backoff>, bounded_detail::NoResizeGate>;
+    using GateScope = bounded_detail::GateScope<Gate>;
+
+public:
+    using value_type = T;
+    using reference = value_type &;
+    using const_reference = const value_type &;
+    using allocator_type = Allocator;
+    /// @brief Called with each value dropped by the @c Overflow policy, on the enqueuing thread.
+    using DropHandler = std::function<void(value_type &&)>;
+
+    /// @brief Creates a new bounded queue with the given capacity.
+    /// @param capacity The max count of values the queue can hold. It must be greater than 0.
+    /// @param overflow What @c enqueue does when the queue is full.
+    /// @param on_drop The handler of the dropped values, may be empty.
+    /// @param alloc The allocator of the buffer, also used by the migrations.
+    explicit BoundedQueue(size_t capacity, Overflow overflow = Overflow::Block, DropHandler on_drop = {},
+                          const Allocator &alloc = Allocator())
+        requires (!IsFixed)
+            : buffer_(capacity, alloc),
+              bounds_{capacity, std::bit_ceil(capacity + 1)},
+              overflow_(overflow),
+              on_drop_(std::move(on_drop))
+    {
+        assert(capacity > 0);
+
+        // Head is initialized to '{ lap: 0, index: 0 }'.
+        // Tail is initialized to '{ lap: 0, index: 0 }'.
+        head_->store(0, std::memory_order_relaxed);
+        tail_->store(0, std::memory_order_relaxed);
+
+        // Slot 'i' is initialized to the stamp '{ lap: 0, index: i }'.
+        for (size_t i = 0; i < capacity; ++i) {
+            buffer_[i].stamp.store(i, std::memory_order_relaxed);
+        }
+    }
+
+    /// @brief Creates a new bounded queue with the compile-time capacity. The slots are initialized
+    /// by a pack expansion, so keep the 'Capacity' moderate (e.g. up to a few thousands).
+    constexpr BoundedQueue() noexcept requires IsFixed
+            : head_(0),
+              tail_(0),
+              buffer_(make_slots(std::make_index_sequence<Capacity>{}))
+    {
+    }
+
+    /// @brief Creates a new bounded queue with the compile-time capacity and the overflow policy.
+    /// The dropped values are destroyed, as the drop handler is not constexpr constructible.
+    constexpr explicit BoundedQueue(Overflow overflow) noexcept requires IsFixed
+            : head_(0),
+              tail_(0),
+              buffer_(make_slots(std::make_index_sequence<Capacity>{})),
+              overflow_(overflow)
+    {
+    }
+
+    BoundedQueue(const BoundedQueue &) = delete;
+
+    BoundedQueue &operator=(const BoundedQueue &) = delete;
+
+    [[nodiscard]] bool is_lock_free() const noexcept
+    {
+        return head_->is_lock_free() && tail_->is_lock_free();
+    }
+
+    /// @brief Returns the capacity of the queue.
+    [[nodiscard]] size_t capacity() const noexcept
+    {
+        GateScope scope(gate_);
+        return cap();
+    }
+
+    [[nodiscard]] Overflow overflow() const noexcept
+    {
+        return overflow_;
+    }
+
+    /// @brief Returns the count of the values in the queue. It is a racy snapshot: the count may
+    /// be stale once returned if other threads are enqueuing or dequeuing.
+    [[nodiscard]] size_t len() const noexcept
+    {
+        GateScope scope(gate_);
+        return count();
+    }
+
+    /// @brief Returns true if the queue is empty, a racy snapshot as @c len.
+    [[nodiscard]] bool is_empty() const noexcept
+    {
+        GateScope scope(gate_);
+        auto head = head_->load(std::memory_order_seq_cst);
+        auto tail = tail_->load(std::memory_order_seq_cst);
+        return head == tail;
+    }
+
+    /// @brief Returns the count of the values which can be enqueued without blocking, a racy
+    /// snapshot as @c len.
+    [[nodiscard]] size_t remaining() const noexcept
+    {
+        GateScope scope(gate_);
+        return cap() - count();
+    }
+
+    /// @brief Grows the capacity to hold at least 'additional' more values than the current ones.
+    /// The values are moved to a new buffer in order, the operations of other threads wait until
+    /// the migration completes. Does nothing if the capacity is enough.
+    void reserve(size_t additional) requires Resizable
+    {
+        bounded_detail::GateLock<Backoff> lock(gate_);
+        auto len = count();
+        if (len + additional > bounds_.cap) {
+            migrate(len, len + additional);
+        }
+    }
+
+    /// @brief Shrinks the capacity to the count of the current values (at least 1), the values are
+    /// moved to a new buffer as @c reserve.
+    void shrink_to_fit() requires Resizable
+    {
+        bounded_detail::GateLock<Backoff> lock(gate_);
+        auto len = count();
+        auto capacity = std::max<size_t>(len, 1);
+        if (capacity != bounds_.cap) {
+            migrate(len, capacity);
+        }
+    }
+
+    /// @brief Returns the contention counters: the lost races on the head and tail as the CAS
+    /// failures, and the waits for a full queue or a slot being written as the spin iterations.
+    /// Requires @c SYNC_CELL_METRICS.
+    [[nodiscard]] metrics::Stats stats() const noexcept
+    {
+        return metrics_.snapshot();
+    }
+
+    [[nodiscard]] allocator_type get_allocator() const noexcept requires (!IsFixed)
+    {
+        return allocator_type(buffer_.get_allocator());
+    }
+
+    /// @brief Try enqueue a value to the queue.
+    /// @return false if the queue is full. In this case, the 'value' is not touched.
+    template<typename = std::enable_if_t<std::is_copy_constructible_v<value_type>>>
+    bool try_enqueue(const_reference value)
+    {
+        return enqueue_value([&value](std::optional<value_type> &o) {
+            o.TEMPLATE_CALL emplace(value);
+        });
+    }
+
+    /// @brief Try enqueue a value to the queue.
+    /// @return false if the queue is full. In this case, the 'value' is not moved.
+    template<typename = std::enable_if_t<std::is_move_constructible_v<value_type>>>
+    bool try_enqueue(value_type &&value)
+    {
+        return enqueue_value([&value](std::optional<value_type> &o) {
+            o.TEMPLATE_CALL emplace(std::move(value));
+        });
+    }
+
+    /// @brief Try enqueue a value constructed in place from the 'args'.
+    /// @return false if the queue is full. In this case, the 'args' are not touched.
+    template<typename... Args>
+    bool try_emplace(Args &&... args)
+    {
+        static_assert(std::is_constructible_v<value_type, Args &&...>);
+
+        return enqueue_value([&args...](std::optional<value_type> &o) {
+            o.TEMPLATE_CALL emplace(std::forward<Args>(args)...);
+        });
+    }
+
+    /// @brief Try enqueue a value written by 'f' directly in the slot, so a large value is not
+    /// copied from the stack of the caller.
+    /// @param f The function with the signature of 'void(std::optional<T> &)', which must emplace
+    /// the value into the empty optional. It must not throw, the slot has been claimed when it is
+    /// called.
+    /// @return false if the queue is full. In this case, 'f' is not called.
+    template<typename F>
+    bool try_enqueue_with(F &&f)
+    {
+        return enqueue_value([&f](std::optional<value_type> &o) {
+            std::forward<F>(f)(o);
+            assert(o.has_value());
+        });
+    }
+
+    /// @brief Enqueue a value to the queue. If the queue is full, follows the @c Overflow policy:
+    /// waits until a slot is released by the consumer thread, fails, drops the oldest value, or
+    /// drops the 'value'.
+    /// @return false if the 'value' is rejected or dropped.
+    template<typename = std::enable_if_t<std::is_copy_constructible_v<value_type>>>
+    bool enqueue(const_reference value)
+    {
+        return enqueue_with_policy([&value, this] {
+            return try_enqueue(value);
+        }, [&value] {
+            return value_type(value);
+        });
+    }
+
+    /// @brief Enqueue a value to the queue. If the queue is full, follows the @c Overflow policy.
+    /// @return false if the 'value' is rejected or dropped. The 'value' is not moved if rejected.
+    template<typename = std::enable_if_t<std::is_move_constructible_v<value_type>>>
+    bool enqueue(value_type &&value)
+    {
+        return enqueue_with_policy([&value, this] {
+            return try_enqueue(std::move(value));
+        }, [&value] {
+            return std::move(value);
+        });
+    }
+
+    /// @brief Enqueues the items of [first, last) in order, each item follows the @c Overflow
+    /// policy as @c enqueue.
+    /// @return The count of the enqueued items, the rejected and the dropped items are not counted.
+    template<std::input_iterator It, typename Sentinel>
+    size_t extend(It first, Sentinel last)
+    {
+        size_t count = 0;
+        sc::impl::extend(std::move(first), std::move(last), [this, &count](auto &&v) {
+            count += enqueue(std::forward<decltype(v)>(v));
+        });
+        return count;
+    }
+
+    /// @brief Enqueues the items of the 'range' in order, e.g. a container or a view.
+    template<typename Range>
+    size_t extend(Range &&range)
+    {
+        return extend(std::begin(range), std::end(range));
+    }
+
+    /// @brief Try dequeue an item from the queue.
+    /// @return If success, the optional takes the dequeue value, otherwise the optional is empty.
+    std::optional<value_type> try_dequeue()
+    {
+        std::optional<value_type> ret;
+        dequeue_value([&ret](value_type &value) {
+            ret.emplace(util::cast_ctor_ref(value));
+        });
+        return ret;
+    }
+
+    /// @brief Try dequeue an item, and calls 'f' with it in the slot, so a large value is not
+    /// moved out. The item is destroyed after the call.
+    /// @param f The function with the signature of 'void(T &)'. It must not throw, the slot is
+    /// released after it returns.
+    /// @return false if the queue is empty, in this case, 'f' is not called.
+    template<typename F>
+    bool try_dequeue_with(F &&f)
+    {
+        return dequeue_value(std::forward<F>(f));
+    }
+
+    /// @brief Moves the values out of the queue in the FIFO order, without synchronization. The
+    /// caller must guarantee no other thread is accessing the queue, and the queue is not used
+    /// after.
+    std::vector<value_type> into_inner() &&
+    {
+        const auto one_lap = this->one_lap();
+        auto len = count();
+        auto head = head_->load(std::memory_order_relaxed);
+        std::vector<value_type> values;
+        values.reserve(len);
+        for (size_t i = 0; i < len; ++i) {
+            auto index = head & (one_lap - 1);
+            auto lap = head & ~(one_lap - 1);
+            auto &slot = buffer_[index];
+            values.emplace_back(util::cast_ctor_ref(*slot.value));
+            slot.value.reset();
+            head = index + 1 < cap() ? head + 1 : lap + one_lap;
+        }
+        return values;
+    }
+
+private:
+    /// @param try_enqueue Tries to enqueue the value.
+    /// @param take Returns the value to drop.
+    template<typename TryEnqueue, typename Take>
+    bool enqueue_with_policy(TryEnqueue try_enqueue, Take take)
+    {
+        Backoff backoff;
+        while (!try_enqueue()) {
+            switch (overflow_) {
+            case Overflow::Block:
+                metrics_.spin();
+                backoff.snooze();
+                break;
+            case Overflow::Reject:
+                return false;
+            case Overflow::DropOldest:
+                // The slot may be taken by another producer again, retry until the value fits.
+                if (auto oldest = try_dequeue(); oldest) {
+                    drop(*std::move(oldest));
+                }
+                break;
+            case Overflow::DropNewest:
+                drop(take());
+                return false;
+            }
+        }
+        return true;
+    }
+
+    void drop(value_type &&value)
+    {
+        if constexpr(!IsFixed) {
+            if (on_drop_) {
+                on_drop_(std::move(value));
+            }
+        }
+    }
+
+    [[nodiscard]] size_t cap() const noexcept
+    {
+        if constexpr(IsFixed) {
+            return Capacity;
+        } else {
+            return bounds_.cap;
+        }
+    }
+
+    /// @brief Returns the count of the values, the current thread must be in the gate.
+    [[nodiscard]] size_t count() const noexcept
+    {
+        const auto one_lap = this->one_lap();
+        while (true) {
+            // Load the tail, then the head, and retry if the tail moved, so they are consistent.
+            auto tail = tail_->load(std::memory_order_seq_cst);
+            auto head = head_->load(std::memory_order_seq_cst);
+            if (tail_->load(std::memory_order_seq_cst) == tail) {
+                auto head_index = head & (one_lap - 1);
+                auto tail_index = tail & (one_lap - 1);
+                if (head_index < tail_index) {
+                    return tail_index - head_index;
+                } else if (head_index > tail_index) {
+                    return cap() - head_index + tail_index;
+                } else {
+                    // The same index in the same lap is empty, in the next lap is full.
+                    return tail == head ? 0 : cap();
+                }
+            }
+        }
+    }
+
+    /// @brief Moves the 'len' values to a new buffer of the 'capacity', the gate must be locked.
+    void migrate(size_t len, size_t capacity)
+    {
+        static_assert(std::is_nothrow_move_constructible_v<T>, "The values are moved to the new buffer.");
+
+        // Allocate first, the queue is not changed if it throws.
+        Buffer buffer(capacity, buffer_.get_allocator());
+        const auto one_lap = bounds_.one_lap;
+        auto head = head_->load(std::memory_order_relaxed);
+        for (size_t i = 0; i < len; ++i) {
+            auto index = head & (one_lap - 1);
+            auto lap = head & ~(one_lap - 1);
+            auto &slot = buffer_[index];
+            buffer[i].value.emplace(std::move(*slot.value));
+            slot.value.reset();
+            head = index + 1 < bounds_.cap ? head + 1 : lap + one_lap;
+        }
+
+        // The moved values are ready to be read in the lap 0, and the rest slots to be written.
+        const auto new_one_lap = std::bit_ceil(capacity + 1);
+        for (size_t i = 0; i < capacity; ++i) {
+            buffer[i].stamp.store(i < len ? i + 1 : i, std::memory_order_relaxed);
+        }
+        head_->store(0, std::memory_order_relaxed);
+        tail_->store(len < capacity ? len : new_one_lap, std::memory_order_relaxed);
+        buffer_.swap(buffer);
+        bounds_ = {capacity, new_one_lap};
+    }
+
+    [[nodiscard]] size_t one_lap() const noexcept
+    {
+        if constexpr(IsFixed) {
+            return std::bit_ceil(Capacity + 1);
+        } else {
+            return bounds_.one_lap;
+        }
+    }
+
+    /// @brief Slot 'i' is initialized to the stamp '{ lap: 0, index: i }'.
+    template<size_t... Is>
+    static constexpr std::array<Slot, Capacity> make_slots(std::index_sequence<Is...>) noexcept
+    {
+        return {Slot(Is)...};
+    }
+
+    /// @brief Calls 'value_get' with the value in the head slot, then releases the slot.
+    /// @return false if the queue is empty.
+    template<typename Func>
+    bool dequeue_value(Func &&value_get)
+    {
+        GateScope scope(gate_);
+        Backoff backoff;
+        const auto one_lap = this->one_lap();
+        auto head = head_->load(std::memory_order_relaxed);
+
+        while (true) {
+            // Deconstruct the head.
+            auto index = head & (one_lap - 1);
+            auto lap = head & ~(one_lap - 1);
+
+            auto &slot = buffer_[index];
+            auto stamp = slot.stamp.load(std::memory_order_acquire);
+
+            // If the stamp is ahead of the head by 1, we may attempt to pop.
+            if (head + 1 == stamp) {
+                // If the head does not reach the end of the buffer, move forward by 1, otherwise
+                // move to the next lap.
+                auto new_head = index + 1 < cap() ? head + 1 : lap + one_lap;
+
+                // Try moving the head.
+                if (head_->compare_exchange_weak(
+                        head, new_head,
+                        std::memory_order_seq_cst,
+                        std::memory_order_relaxed)) {
+                    // Read the value from the slot and update the stamp.
+                    value_get(*slot.value);
+                    slot.value.reset();
+                    slot.stamp.store(head + one_lap, std::memory_order_release);
+                    return true;
+                }
+
+                metrics_.cas_failure();
+                backoff.spin();
+            } else if (stamp == head) {
+                std::atomic_thread_fence(std::memory_order_seq_cst);
+                auto tail = tail_->load(std::memory_order_relaxed);
+
+                // If the tail equals the head, that means the queue is empty.
+                if (tail == head) {
+                    return false;
+                }
+
+                backoff.spin();
+                head = head_->load(std::memory_order_relaxed);
+            } else {
+                // Snooze because we need to wait for the stamp to update.
+                metrics_.spin();
+                backoff.snooze();
+                head = head_->load(std::memory_order_relaxed);
+            }
+        }
+    }
+
+    template<typename Func>
+    bool enqueue_value(Func value_set)
+    {
+        GateScope scope(gate_);
+        Backoff backoff;
+        const auto one_lap = this->one_lap();
+        auto tail = tail_->load(std::memory_order_relaxed);
+
+        while (true) {
+            // Deconstruct the tail.
+            auto index = tail & (one_lap - 1);
+            auto lap = tail & ~(one_lap - 1);
+
+            auto &slot = buffer_[index];
+            auto stamp = slot.stamp.load(std::memory_order_acquire);
+
+            // If the tail and the stamp match, we may attempt to push.
+            if (tail == stamp) {
+                // If the tail does not reach the end of the buffer, move forward by 1, otherwise
+                // move to the next lap.
+                auto new_tail = index + 1 < cap() ? tail + 1 : lap + one_lap;
+
+                // Try moving the tail.
+                if (tail_->compare_exchange_weak(
+                        tail, new_tail,
+                        std::memory_order_seq_cst,
+                        std::memory_order_relaxed)) {
+                    // Write the value into the slot and update the stamp.
+                    value_set(slot.value);
+                    slot.stamp.store(tail + 1, std::memory_order_release);
+                    return true;
+                }
+
+                metrics_.cas_failure();
+                backoff.spin();
+            } else if (stamp + one_lap == tail + 1) {
+                std::atomic_thread_fence(std::memory_order_seq_cst);
+                auto head = head_->load(std::memory_order_relaxed);
+
+                // If the head lags one lap behind the tail as well, that means the queue is full.
+                if (head + one_lap == tail) {
+                    return false;
+                }
+
+                backoff.spin();
+                tail = tail_->load(std::memory_order_relaxed);
+            } else {
+                // Snooze because we need to wait for the stamp to update.
+                metrics_.spin();
+                backoff.snooze();
+                tail = tail_->load(std::memory_order_relaxed);
+            }
+        }
+    }
+
+    /// @brief The head of the queue. Bits lower than the 'one_lap()' are the index into the
+    /// buffer, and the upper bits are the lap.
+    util::CachePadded<std::atomic<size_t>> head_;
+    /// @brief The tail of the queue, composed in the same way as the head.
+    util::CachePadded<std::atomic<size_t>> tail_;
+
+    /// @brief The buffer holding slots.
+    Buffer buffer_;
+    SC_NO_UNIQUE_ADDRESS std::conditional_t<IsFixed, FixedBounds, DynamicBounds> bounds_;
+    Overflow overflow_ = Overflow::Block;
+    SC_NO_UNIQUE_ADDRESS std::conditional_t<IsFixed, bounded_detail::NoDropHandler, DropHandler> on_drop_;
+    SC_NO_UNIQUE_ADDRESS mutable Gate gate_;
+    SC_NO_UNIQUE_ADDRESS metrics::Counters metrics_;
+};
+
+/// @brief A @c BoundedQueue with the compile-time capacity and the inline buffer, which can be
+/// constant-initialized:
+/// ``` cpp
+/// constinit sc::mpmc::FixedBoundedQueue<Event, 256> Events;
+/// ```
+template<typename T, size_t Capacity, typename Backoff = util::Backoff>
+using FixedBoundedQueue = BoundedQueue<T, Backoff, Capacity>;
+
+/// @brief A @c BoundedQueue whose capacity can be changed by @c reserve and @c shrink_to_fit:
+/// ``` cpp
+/// sc::mpmc::ResizableBoundedQueue<Request> requests(64);
+/// requests.reserve(1024);     // The waiting requests are kept.
+/// ```
+template<typename T, typename Backoff = util::Backoff, typename Allocator = std::allocator<T>>
+using ResizableBoundedQueue = BoundedQueue<T, Backoff, 0, Allocator, true>;
+
+}
+
+#endif //SYNC_CELL_MPMC_BOUNDED_QUEUE_HPP
@@ -7,19 +7,30 @@
 #define SYNC_CELL_MPMC_LIST_QUEUE_HPP
 
 #include <atomic>
+#include <iterator>
 #include <memory>
 #include <optional>
 #include <type_traits>
+#include <utility>
+#include <vector>
 
 #include "shared/compiler_workaround.hpp"
+#include "shared/drain.hpp"
+#include "shared/extend.hpp"
 #include "shared/object_cache_pool.hpp"
+#include "shared/queue_len.hpp"
+#include "util/back_off.hpp"
 #include "util/cache_padded.hpp"
 #include "util/copy_move_selector.hpp"
 
 
 namespace sc::mpmc {
 
-template<typename T, uint32_t PoolSize = 0>
+/// @brief An unbounded mpmc queue implemented with the linked-list.
+/// @tparam T The value type.
+/// @tparam PoolSize The count of the cached nodes.
+/// @tparam Allocator The allocator of the nodes, e.g. an arena or a NUMA-local allocator.
+template<typename T, uint32_t PoolSize = 0, typename Allocator = std::allocator<T>>
 class LinkedListQueue
 {
     static constexpr size_t PointerSize = sizeof(void *);
@@ -42,16 +53,27 @@ public:
     using value_type = T;
     using reference = value_type &;
     using const_reference = const value_type &;
+    using allocator_type = Allocator;
 
     static constexpr uint32_t PoolCacheSize = PoolSize;
 
-    LinkedListQueue()
+    LinkedListQueue() : LinkedListQueue(Allocator()) { }
+
+    /// @brief Creates a queue allocating the nodes from 'alloc'.
+    explicit LinkedListQueue(const Allocator &alloc) : pool_(alloc)
     {
         Node *p = pool_.TEMPLATE_CALL alloc();   // construct a default empty node
         head_->store(p);
         tail_->store(p);
     }
 
+    /// @brief Creates a queue with the items of [first, last) in order.
+    template<std::input_iterator It, typename Sentinel>
+    LinkedListQueue(It first, Sentinel last, const Allocator &alloc = Allocator()) : LinkedListQueue(alloc)
+    {
+        extend(std::move(first), std::move(last));
+    }
+
     LinkedListQueue(const LinkedListQueue &) = delete;
 
     LinkedListQueue &operator=(const LinkedListQueue &) = delete;
@@ -79,6 +101,36 @@ public:
         return head_->is_lock_free() && tail_->is_lock_free();
     }
 
+    /// @brief Returns the count of the items in the queue. It is a racy snapshot: the count may be
+    /// stale once returned if other threads are enqueuing or dequeuing.
+    [[nodiscard]] size_t len() const noexcept
+    {
+        return len_.len();
+    }
+
+    /// @brief Returns true if the queue is empty, a racy snapshot as @c len.
+    [[nodiscard]] bool is_empty() const noexcept
+    {
+        return len_.len() == 0;
+    }
+
+    [[nodiscard]] allocator_type get_allocator() const noexcept
+    {
+        return allocator_type(pool_.get_allocator());
+    }
+
+    /// @brief Returns @c UnboundedCapacity, the queue allocates a node for each item.
+    [[nodiscard]] static constexpr size_t capacity() noexcept
+    {
+        return UnboundedCapacity;
+    }
+
+    /// @brief Returns @c UnboundedCapacity, an enqueue never fails for a full queue.
+    [[nodiscard]] static constexpr size_t remaining() noexcept
+    {
+        return UnboundedCapacity;
+    }
+
     template<typename = std::enable_if_t<std::is_copy_constructible_v<value_type>>>
     void enqueue(const_reference value)
     {
@@ -95,46 +147,169 @@ public:
         enqueue_node(p);
     }
 
+    /// @brief Enqueues the items of [first, last) in order.
+    template<std::input_iterator It, typename Sentinel>
+    void extend(It first, Sentinel last)
+    {
+        sc::impl::extend(std::move(first), std::move(last), [this](auto &&v) {
+            enqueue(std::forward<decltype(v)>(v));
+        });
+    }
+
+    /// @brief Enqueues the items of the 'range' in order, e.g. a container or a view.
+    template<typename Range>
+    void extend(Range &&range)
+    {
+        extend(std::begin(range), std::end(range));
+    }
+
     // todo: pop(wait, spin first, sleep if spin long time)
     std::optional<value_type> try_dequeue()
     {
-        Node *ptr = head_->load(std::memory_order_acquire);
-        Node *locked_ptr;
-        do {
-            // Expected: unlock, normal pointer value.
-            ptr = (Node *)((uintptr_t)ptr & ~0x01);
-            // Pointer value with the lock tag: set the least significant bit to 1.
-            // Because the Node is aligned at least sizeof(void*), so the Node object address
-            // must be a multiple of 4(on a 32-bits OS) or 8(on a 64-bits OS), so the least
-            // significant bits can be used to save tag value.
-            locked_ptr = (Node *)((uintptr_t)ptr | 0x01);
-        } while (!head_->compare_exchange_weak(
-                ptr, locked_ptr,
-                std::memory_order_acq_rel,
-                std::memory_order_acquire));
-
-        // When the CAS succeeds, 'ptr' points to the current head node, 'locked_ptr' = 'ptr' | 0x01.
+        return pop_if([](const_reference) { return true; });
+    }
 
-        auto *next = ptr->next.load(std::memory_order_acquire);
-        if (next == nullptr) {
-            head_->store(ptr, std::memory_order_release);
-            return {};
+    /// @brief Calls 'f' with the first item without removing it. The other consumers wait while
+    /// 'f' is running, so keep it short. If 'f' throws, the head is unlocked before the exception
+    /// leaves the call.
+    /// @return false if the queue is empty, in this case, 'f' is not called.
+    template<typename F>
+    bool peek(F &&f)
+    {
+        HeadLock lock(*head_, lock_head());
+        auto *next = lock.node->next.load(std::memory_order_acquire);
+        if (next != nullptr) {
+            std::forward<F>(f)(std::as_const(*next->value));
         }
+        return next != nullptr;
+    }
 
-        // retrieve the value before set 'head_' to avoid another thread releases the 'next' node.
-        std::optional<value_type> ret(util::cast_ctor_ref(next->value));
-        head_->store(next, std::memory_order_release);
+    /// @brief Dequeues the first item only if 'pred' returns true for it. The other consumers wait
+    /// while 'pred' is running, and a throwing 'pred' leaves the item in the queue, as @c peek.
+    /// @return An empty optional if the queue is empty or 'pred' returns false.
+    template<typename Pred>
+    std::optional<value_type> pop_if(Pred &&pred)
+    {
+        auto *ptr = lock_head();
+        std::optional<value_type> ret;
+        {
+            // Unlocked at the end of the scope, also if 'pred' or the copy of the value throws.
+            HeadLock lock(*head_, ptr);
+
+            // While the head is locked, 'ptr' points to the current head node.
+            auto *next = ptr->next.load(std::memory_order_acquire);
+            if (next == nullptr || !std::forward<Pred>(pred)(std::as_const(*next->value))) {
+                return {};
+            }
+
+            // retrieve the value before set 'head_' to avoid another thread releases the 'next' node.
+            ret.emplace(util::cast_ctor_ref(*next->value));
+            lock.node = next;
+        }
+        len_.popped();
 
         release_node(ptr);
         return ret;
     }
 
+    /// @brief Try dequeue at most 'max' items from the queue, with one lock of the head. The items
+    /// are appended to 'out' in the FIFO order.
+    /// @return The count of the dequeued items.
+    size_t try_dequeue_batch(std::vector<value_type> &out, size_t max)
+    {
+        auto *ptr = lock_head();
+        auto *head = ptr;
+        size_t count = 0;
+        for (; count < max; ++count) {
+            auto *next = head->next.load(std::memory_order_acquire);
+            if (next == nullptr) {
+                break;
+            }
+            out.emplace_back(util::cast_ctor_ref(*next->value));
+            head = next;
+        }
+        head_->store(head, std::memory_order_release);
+        len_.popped(count);
+
+        // The skipped nodes are only reachable from 'ptr' now.
+        while (ptr != head) {
+            release_node(std::exchange(ptr, ptr->next.load(std::memory_order_relaxed)));
+        }
+        return count;
+    }
+
+    /// @brief Takes the items in the queue by chunks, each chunk locks the head once.
+    /// @param chunk The max count of the items in one chunk.
+    /// @return A @c Drain range, which stops after the items in the queue when it is called.
+    auto drain(size_t chunk = DefaultDrainChunk)
+    {
+        auto take = [this](std::vector<value_type> &out, size_t max) { return try_dequeue_batch(out, max); };
+        return Drain<value_type, decltype(take)>(take, chunk, len());
+    }
+
     void clear()
     {
         while (try_dequeue()) { }
     }
 
+    /// @brief Moves the items out of the queue in the FIFO order, without locking the head. The
+    /// caller must guarantee no other thread is accessing the queue, and the queue is not used
+    /// after. The nodes are released by the destructor.
+    std::vector<value_type> into_inner() &&
+    {
+        std::vector<value_type> values;
+        values.reserve(len_.len());
+        auto *node = head_->load(std::memory_order_relaxed)->next.load(std::memory_order_relaxed);
+        for (; node != nullptr; node = node->next.load(std::memory_order_relaxed)) {
+            values.emplace_back(util::cast_ctor_ref(*node->value));
+        }
+        return values;
+    }
+
 private:
+    /// @brief Unlocks the head locked by @c lock_head when the scope exits, by storing the 'node'.
+    struct HeadLock
+    {
+        std::atomic<Node *> &head;
+        Node *node;
+
+        HeadLock(std::atomic<Node *> &head, Node *node) noexcept : head(head), node(node) { }
+
+        HeadLock(const HeadLock &) = delete;
+
+        HeadLock &operator=(const HeadLock &) = delete;
+
+        ~HeadLock()
+        {
+            head.store(node, std::memory_order_release);
+        }
+    };
+
+    /// @brief Locks the head by the tag, which is cleared by storing the head again.
+    /// @return The head node.
+    Node *lock_head() noexcept
+    {
+        util::Backoff backoff;
+        Node *ptr = head_->load(std::memory_order_acquire);
+        while (true) {
+            // Expected: unlock, normal pointer value.
+            ptr = (Node *)((uintptr_t)ptr & ~0x01);
+            // Pointer value with the lock tag: set the least significant bit to 1.
+            // Because the Node is aligned at least sizeof(void*), so the Node object address
+            // must be a multiple of 4(on a 32-bits OS) or 8(on a 64-bits OS), so the least
+            // significant bits can be used to save tag value.
+            auto *locked_ptr = (Node *)((uintptr_t)ptr | 0x01);
+            if (head_->compare_exchange_weak(
+                    ptr, locked_ptr,
+                    std::memory_order_acq_rel,
+                    std::memory_order_acquire)) {
+                return ptr;
+            }
+            // The holder may run a 'peek' or 'pop_if' callback, yield to it after spinning.
+            backoff.snooze();
+        }
+    }
+
     void enqueue_node(Node *node)
     {
         Node *queue_tail = tail_->load(std::memory_order_acquire);
@@ -169,6 +344,7 @@ private:
         // the "queue tail" to the current node.
 
         queue_tail->next.store(node, std::memory_order_release);
+        len_.pushed();
     }
 
     void release_node(Node *node)
@@ -182,7 +358,8 @@ private:
     util::CachePadded<std::atomic<Node *>> tail_;
 
     // allocator
-    ObjectCachePool<Node, PoolSize> pool_;
+    ObjectCachePool<Node, PoolSize, Allocator> pool_;
+    impl::LenCounter len_;
 };
 
 }
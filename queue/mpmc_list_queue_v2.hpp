@@ -7,9 +7,12 @@
 #define SYNC_CELL_MPMC_LIST_QUEUE_V2_HPP
 
 #include <atomic>
+#include <iterator>
 #include <memory>
 #include <optional>
 
+#include "shared/extend.hpp"
+#include "shared/queue_len.hpp"
 #include "util/cache_padded.hpp"
 
 
@@ -46,6 +49,13 @@ public:
         (*tail_).store(dummy.get());
     }
 
+    /// @brief Creates a queue with the items of [first, last) in order.
+    template<std::input_iterator It, typename Sentinel>
+    LinkedListQueueV2(It first, Sentinel last) : LinkedListQueueV2()
+    {
+        extend(std::move(first), std::move(last));
+    }
+
     LinkedListQueueV2(const LinkedListQueueV2 &) = delete;
 
     LinkedListQueueV2 &operator=(const LinkedListQueueV2 &) = delete;
@@ -73,6 +83,31 @@ public:
         return (*head_).is_lock_free();
     }
 
+    /// @brief Returns the count of the items in the queue. It is a racy snapshot: the count may be
+    /// stale once returned if other threads are enqueuing or dequeuing.
+    [[nodiscard]] size_t len() const noexcept
+    {
+        return len_.len();
+    }
+
+    /// @brief Returns true if the queue is empty, a racy snapshot as @c len.
+    [[nodiscard]] bool is_empty() const noexcept
+    {
+        return len_.len() == 0;
+    }
+
+    /// @brief Returns @c UnboundedCapacity, the queue allocates a node for each item.
+    [[nodiscard]] static constexpr size_t capacity() noexcept
+    {
+        return UnboundedCapacity;
+    }
+
+    /// @brief Returns @c UnboundedCapacity, an enqueue never fails for a full queue.
+    [[nodiscard]] static constexpr size_t remaining() noexcept
+    {
+        return UnboundedCapacity;
+    }
+
     template<typename = std::enable_if_t<std::is_copy_constructible_v<value_type>>>
     void enqueue(const_reference value)
     {
@@ -89,6 +124,22 @@ public:
         enqueue_node(node);
     }
 
+    /// @brief Enqueues the items of [first, last) in order.
+    template<std::input_iterator It, typename Sentinel>
+    void extend(It first, Sentinel last)
+    {
+        sc::impl::extend(std::move(first), std::move(last), [this](auto &&v) {
+            enqueue(std::forward<decltype(v)>(v));
+        });
+    }
+
+    /// @brief Enqueues the items of the 'range' in order, e.g. a container or a view.
+    template<typename Range>
+    void extend(Range &&range)
+    {
+        extend(std::begin(range), std::end(range));
+    }
+
     std::optional<value_type> try_dequeue()
     {
         auto ptr = (*head_).load(std::memory_order_acquire);
@@ -99,6 +150,7 @@ public:
                 return {};
             }
         } while (!(*head_).compare_exchange_weak(ptr, next, std::memory_order_acq_rel, std::memory_order_acquire));
+        len_.popped();
 
         return std::move(next->value);
     }
@@ -125,10 +177,12 @@ private:
                 std::memory_order_acquire));
 
         queue_tail->next.store(node, std::memory_order_release);
+        len_.pushed();
     }
 
     util::CachePadded<std::atomic<std::shared_ptr<Node>>> head_;
     util::CachePadded<std::atomic<Node *>> tail_;
+    impl::LenCounter len_;
 };
 
 }
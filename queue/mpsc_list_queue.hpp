@@ -7,18 +7,28 @@
 #define SYNC_CELL_MPSC_LIST_QUEUE_HPP
 
 #include <atomic>
+#include <iterator>
 #include <memory>
 #include <optional>
+#include <utility>
+#include <vector>
 
 #include "shared/compiler_workaround.hpp"
+#include "shared/drain.hpp"
+#include "shared/extend.hpp"
 #include "shared/object_cache_pool.hpp"
+#include "shared/queue_len.hpp"
 #include "util/cache_padded.hpp"
 #include "util/copy_move_selector.hpp"
 
 
 namespace sc::mpsc {
 
-template<typename T, uint32_t PoolSize = 0>
+/// @brief An unbounded mpsc queue.
+/// @tparam T The value type.
+/// @tparam PoolSize The count of the cached nodes.
+/// @tparam Allocator The allocator of the nodes, e.g. an arena or a NUMA-local allocator.
+template<typename T, uint32_t PoolSize = 0, typename Allocator = std::allocator<T>>
 class LinkedListQueue
 {
     struct Node
@@ -39,16 +49,27 @@ public:
     using value_type = T;
     using reference = value_type &;
     using const_reference = const value_type &;
+    using allocator_type = Allocator;
 
     static constexpr uint32_t PoolCacheSize = PoolSize;
 
-    LinkedListQueue()
+    LinkedListQueue() : LinkedListQueue(Allocator()) { }
+
+    /// @brief Creates a queue allocating the nodes from 'alloc'.
+    explicit LinkedListQueue(const Allocator &alloc) : pool_(alloc)
     {
         Node *p = pool_.TEMPLATE_CALL alloc();   // construct a default empty node
         head_ = p;
         tail_->store(p);
     }
 
+    /// @brief Creates a queue with the items of [first, last) in order.
+    template<std::input_iterator It, typename Sentinel>
+    LinkedListQueue(It first, Sentinel last, const Allocator &alloc = Allocator()) : LinkedListQueue(alloc)
+    {
+        extend(std::move(first), std::move(last));
+    }
+
     LinkedListQueue(const LinkedListQueue &) = delete;
 
     LinkedListQueue &operator=(const LinkedListQueue &) = delete;
@@ -76,6 +97,36 @@ public:
         return tail_->is_lock_free();
     }
 
+    /// @brief Returns the count of the items in the queue. It is a racy snapshot: the count may be
+    /// stale once returned if other threads are enqueuing or dequeuing.
+    [[nodiscard]] size_t len() const noexcept
+    {
+        return len_.len();
+    }
+
+    /// @brief Returns true if the queue is empty, a racy snapshot as @c len.
+    [[nodiscard]] bool is_empty() const noexcept
+    {
+        return len_.len() == 0;
+    }
+
+    [[nodiscard]] allocator_type get_allocator() const noexcept
+    {
+        return allocator_type(pool_.get_allocator());
+    }
+
+    /// @brief Returns @c UnboundedCapacity, the queue allocates a node for each item.
+    [[nodiscard]] static constexpr size_t capacity() noexcept
+    {
+        return UnboundedCapacity;
+    }
+
+    /// @brief Returns @c UnboundedCapacity, an enqueue never fails for a full queue.
+    [[nodiscard]] static constexpr size_t remaining() noexcept
+    {
+        return UnboundedCapacity;
+    }
+
     template<typename = std::enable_if_t<std::is_copy_constructible_v<value_type>>>
     void enqueue(const_reference value)
     {
@@ -92,22 +143,94 @@ public:
         enqueue_node(p);
     }
 
+    /// @brief Enqueues the items of [first, last) in order.
+    template<std::input_iterator It, typename Sentinel>
+    void extend(It first, Sentinel last)
+    {
+        sc::impl::extend(std::move(first), std::move(last), [this](auto &&v) {
+            enqueue(std::forward<decltype(v)>(v));
+        });
+    }
+
+    /// @brief Enqueues the items of the 'range' in order, e.g. a container or a view.
+    template<typename Range>
+    void extend(Range &&range)
+    {
+        extend(std::begin(range), std::end(range));
+    }
+
     /// @brief Try dequeue an item.
     /// @note Do not call this method in multiple threads, even if the calls are not concurrent.
     std::optional<value_type> try_dequeue()
+    {
+        return pop_if([](const_reference) { return true; });
+    }
+
+    /// @brief Calls 'f' with the first item without removing it.
+    /// @return false if the queue is empty, in this case, 'f' is not called.
+    /// @note Only called by the consumer thread, as @c try_dequeue.
+    template<typename F>
+    bool peek(F &&f)
+    {
+        auto *next = head_->next.load(std::memory_order_acquire);
+        if (next == nullptr) {
+            return false;
+        }
+
+        std::forward<F>(f)(std::as_const(*next->value));
+        return true;
+    }
+
+    /// @brief Dequeues the first item only if 'pred' returns true for it.
+    /// @return An empty optional if the queue is empty or 'pred' returns false.
+    /// @note Only called by the consumer thread, as @c try_dequeue.
+    template<typename Pred>
+    std::optional<value_type> pop_if(Pred &&pred)
     {
         auto *h = head_;
         auto *next = h->next.load(std::memory_order_acquire);
-        if (next == nullptr) {
+        if (next == nullptr || !std::forward<Pred>(pred)(std::as_const(*next->value))) {
             return {};
         }
 
         head_ = next;
         release_node(h);
+        len_.popped();
 
         return util::cast_ctor_ref(next->value);
     }
 
+    /// @brief Try dequeue at most 'max' items from the queue. The items are appended to 'out' in the
+    /// FIFO order.
+    /// @return The count of the dequeued items.
+    /// @note Only called by the consumer thread, as @c try_dequeue.
+    size_t try_dequeue_batch(std::vector<value_type> &out, size_t max)
+    {
+        size_t count = 0;
+        for (; count < max; ++count) {
+            auto *h = head_;
+            auto *next = h->next.load(std::memory_order_acquire);
+            if (next == nullptr) {
+                break;
+            }
+            out.emplace_back(util::cast_ctor_ref(*next->value));
+            head_ = next;
+            release_node(h);
+        }
+        len_.popped(count);
+        return count;
+    }
+
+    /// @brief Takes the items in the queue by chunks.
+    /// @param chunk The max count of the items in one chunk.
+    /// @return A @c Drain range, which stops after the items in the queue when it is called.
+    /// @note Only called by the consumer thread, as @c try_dequeue.
+    auto drain(size_t chunk = DefaultDrainChunk)
+    {
+        auto take = [this](std::vector<value_type> &out, size_t max) { return try_dequeue_batch(out, max); };
+        return Drain<value_type, decltype(take)>(take, chunk, len());
+    }
+
     void clear()
     {
         while (try_dequeue()) { }
@@ -148,6 +271,7 @@ private:
         // the "queue tail" to the current node.
 
         queue_tail->next.store(node, std::memory_order_release);
+        len_.pushed();
     }
 
     void release_node(Node *node)
@@ -161,7 +285,8 @@ private:
     Node *head_;
 
     // allocator
-    ObjectCachePool<Node, PoolSize> pool_;
+    ObjectCachePool<Node, PoolSize, Allocator> pool_;
+    impl::LenCounter len_;
 };
 
 }
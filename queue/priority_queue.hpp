@@ -0,0 +1,368 @@
+///
+/// @file  priority_queue.hpp
+/// @brief An unbounded lock-free mpmc priority queue implemented with the skip list.
+///
+
+#ifndef SYNC_CELL_PRIORITY_QUEUE_HPP
+#define SYNC_CELL_PRIORITY_QUEUE_HPP
+
+#include <algorithm>
+#include <array>
+#include <atomic>
+#include <bit>
+#include <cstdint>
+#include <functional>
+#include <iterator>
+#include <memory>
+#include <optional>
+#include <type_traits>
+#include <utility>
+
+#include "epoch/epoch.hpp"
+#include "shared/compiler_workaround.hpp"
+#include "shared/extend.hpp"
+#include "shared/queue_len.hpp"
+#include "util/cache_padded.hpp"
+
+
+namespace sc::mpmc {
+
+/// @brief An unbounded priority queue, whose @c push and @c pop_max can be called by any threads
+/// concurrently. The values of the same priority are popped in the order of pushing.
+///
+/// It is a lock-free skip list sorted from the max value (Lotan and Shavit), with the marked links
+/// of Fraser: @c pop_max claims the first node not claimed yet by marking its links, and the marked
+/// nodes are unlinked by any thread passing them, whose memory is reclaimed by the epoch.
+///
+/// @example
+/// ``` cpp
+/// sc::mpmc::PriorityQueue<Job, ByPriority> jobs;
+/// jobs.push(Job{.priority = 2, ...});
+/// if (auto job = jobs.pop_max()) { run(*job); }
+/// ```
+/// @note The values are read by the concurrent traversals until being reclaimed, so @c pop_max
+/// returns a copy of the value.
+/// @tparam T The value type.
+/// @tparam Compare The "less" order of the values, the max one is popped first.
+template<typename T, typename Compare = std::less<T>>
+class PriorityQueue
+{
+    static_assert(std::is_copy_constructible_v<T>, "The popped values are copied out.");
+
+    static constexpr uint32_t MaxLevel = 32;
+    /// @brief The lowest bit of a link is set when the node owning the link is being removed.
+    static constexpr uintptr_t Mark = 1;
+
+    using Link = std::atomic<uintptr_t>;
+
+    struct Node
+    {
+        /// @brief Immutable after the node is pushed, read by the traversals.
+        T value;
+        /// @brief The pushing order, which orders the values of the same priority.
+        uint64_t seq;
+        uint32_t level;
+        /// @brief One for the pushing thread linking the upper levels, and one for the popping
+        /// thread. The last one releasing it unlinks and retires the node.
+        std::atomic<uint32_t> refs{2};
+        std::unique_ptr<Link[]> next;
+
+        template<typename V>
+        Node(V &&value, uint64_t seq, uint32_t level)
+                : value(std::forward<V>(value)), seq(seq), level(level), next(std::make_unique<Link[]>(level))
+        {
+        }
+    };
+
+public:
+    using value_type = T;
+    using reference = value_type &;
+    using const_reference = const value_type &;
+
+    PriorityQueue() = default;
+
+    /// @brief Creates a queue with the items of [first, last).
+    template<std::input_iterator It, typename Sentinel>
+    PriorityQueue(It first, Sentinel last) : PriorityQueue()
+    {
+        extend(std::move(first), std::move(last));
+    }
+
+    PriorityQueue(const PriorityQueue &) = delete;
+
+    PriorityQueue &operator=(const PriorityQueue &) = delete;
+
+    ~PriorityQueue()
+    {
+        auto *node = ptr(head_[0].load(std::memory_order_relaxed));
+        while (node != nullptr) {
+            delete std::exchange(node, ptr(node->next[0].load(std::memory_order_relaxed)));
+        }
+    }
+
+    /// @brief Returns true if no value can be popped. It is not a snapshot if other threads are
+    /// pushing or popping.
+    [[nodiscard]] bool is_empty() const
+    {
+        auto guard = epoch::pin();
+        return first_unmarked() == nullptr;
+    }
+
+    /// @brief Returns the count of the values in the queue. It is a racy snapshot: the count may be
+    /// stale once returned if other threads are pushing or popping.
+    [[nodiscard]] size_t len() const noexcept
+    {
+        return len_.len();
+    }
+
+    /// @brief Returns @c UnboundedCapacity, the queue allocates a node for each value.
+    [[nodiscard]] static constexpr size_t capacity() noexcept
+    {
+        return UnboundedCapacity;
+    }
+
+    /// @brief Returns @c UnboundedCapacity, a push never fails for a full queue.
+    [[nodiscard]] static constexpr size_t remaining() noexcept
+    {
+        return UnboundedCapacity;
+    }
+
+    void push(const_reference value)
+    {
+        push_node(new Node(value, seq_->fetch_add(1, std::memory_order_relaxed), random_level()));
+    }
+
+    void push(value_type &&value)
+    {
+        push_node(new Node(std::move(value), seq_->fetch_add(1, std::memory_order_relaxed), random_level()));
+    }
+
+    /// @brief Pushes the items of [first, last) in order.
+    template<std::input_iterator It, typename Sentinel>
+    void extend(It first, Sentinel last)
+    {
+        sc::impl::extend(std::move(first), std::move(last), [this](auto &&v) {
+            push(std::forward<decltype(v)>(v));
+        });
+    }
+
+    /// @brief Pushes the items of the 'range' in order, e.g. a container or a view.
+    template<typename Range>
+    void extend(Range &&range)
+    {
+        extend(std::begin(range), std::end(range));
+    }
+
+    /// @brief Pops the max value.
+    /// @return An empty optional if the queue is empty.
+    std::optional<value_type> pop_max()
+    {
+        return pop_if([](const_reference) { return true; });
+    }
+
+    /// @brief Calls 'f' with the max value without removing it. The value may be popped by other
+    /// threads meanwhile, but it is kept valid until 'f' returns.
+    /// @return false if the queue is empty, in this case, 'f' is not called.
+    template<typename F>
+    bool peek(F &&f) const
+    {
+        auto guard = epoch::pin();
+        auto *node = first_unmarked();
+        if (node == nullptr) {
+            return false;
+        }
+
+        std::forward<F>(f)(std::as_const(node->value));
+        return true;
+    }
+
+    /// @brief Pops the max value only if 'pred' returns true for it. If another thread pops the
+    /// value first, 'pred' is called again with the next max value.
+    /// @return An empty optional if the queue is empty or 'pred' returns false.
+    template<typename Pred>
+    std::optional<value_type> pop_if(Pred &&pred)
+    {
+        auto guard = epoch::pin();
+        while (true) {
+            auto *node = first_unmarked();
+            if (node == nullptr || !pred(std::as_const(node->value))) {
+                return {};
+            }
+
+            // Freeze the upper levels first, so no node is linked after it, and claim it by the
+            // mark of the bottom level.
+            for (auto l = node->level - 1; l > 0; --l) {
+                node->next[l].fetch_or(Mark, std::memory_order_acq_rel);
+            }
+            if (marked(node->next[0].fetch_or(Mark, std::memory_order_acq_rel))) {
+                // Claimed by another thread.
+                continue;
+            }
+
+            len_.popped();
+            std::optional<value_type> value(node->value);
+            release(node, guard);
+            return value;
+        }
+    }
+
+private:
+    static Node *ptr(uintptr_t link) noexcept
+    {
+        return reinterpret_cast<Node *>(link & ~Mark);
+    }
+
+    static uintptr_t link_of(Node *node) noexcept
+    {
+        return reinterpret_cast<uintptr_t>(node);
+    }
+
+    static bool marked(uintptr_t link) noexcept
+    {
+        return (link & Mark) != 0;
+    }
+
+    /// @brief Returns a level in [1, MaxLevel], the level 'n' with a probability of 1/2^n.
+    static uint32_t random_level() noexcept
+    {
+        // xorshift64, seeded by the address of the thread-local state.
+        thread_local uint64_t state = reinterpret_cast<uintptr_t>(&state) * 0x9E3779B97F4A7C15ull | 1;
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        return std::min<uint32_t>(static_cast<uint32_t>(std::countr_zero(state)) + 1, MaxLevel);
+    }
+
+    /// @brief Returns true if the node is before the position of the 'value' of the 'seq'.
+    [[nodiscard]] bool before(const Node *node, const_reference value, uint64_t seq) const
+    {
+        if (compare_(value, node->value)) {
+            return true;
+        }
+        if (compare_(node->value, value)) {
+            return false;
+        }
+        return node->seq < seq;
+    }
+
+    /// @brief Returns the first node not claimed by a pop, the current thread must be pinned.
+    Node *first_unmarked() const noexcept
+    {
+        auto *node = ptr(head_[0].load(std::memory_order_acquire));
+        while (node != nullptr) {
+            auto next = node->next[0].load(std::memory_order_acquire);
+            if (!marked(next)) {
+                break;
+            }
+            node = ptr(next);
+        }
+        return node;
+    }
+
+    /// @brief Finds the links before the position of the 'value' of the 'seq' on each level, and
+    /// the nodes after them, unlinking the marked nodes on the way. The current thread must be
+    /// pinned.
+    void find(const_reference value, uint64_t seq, std::array<Link *, MaxLevel> &preds,
+              std::array<uintptr_t, MaxLevel> &succs)
+    {
+    retry:
+        Link *links = head_.data();
+        for (auto l = static_cast<int32_t>(MaxLevel) - 1; l >= 0; --l) {
+            auto *pred = &links[l];
+            auto *curr = ptr(pred->load(std::memory_order_acquire));
+            while (curr != nullptr) {
+                auto succ = curr->next[l].load(std::memory_order_acquire);
+                while (marked(succ)) {
+                    // Unlink the marked 'curr', it fails if the 'pred' is marked or changed.
+                    auto expected = link_of(curr);
+                    if (!pred->compare_exchange_strong(
+                            expected, succ & ~Mark,
+                            std::memory_order_acq_rel,
+                            std::memory_order_acquire)) {
+                        goto retry;
+                    }
+                    curr = ptr(succ);
+                    if (curr == nullptr) {
+                        break;
+                    }
+                    succ = curr->next[l].load(std::memory_order_acquire);
+                }
+                if (curr == nullptr || !before(curr, value, seq)) {
+                    break;
+                }
+                links = curr->next.get();
+                pred = &links[l];
+                curr = ptr(succ);
+            }
+            preds[l] = pred;
+            succs[l] = link_of(curr);
+        }
+    }
+
+    void push_node(Node *node)
+    {
+        auto guard = epoch::pin();
+        std::array<Link *, MaxLevel> preds;
+        std::array<uintptr_t, MaxLevel> succs;
+        while (true) {
+            find(node->value, node->seq, preds, succs);
+            node->next[0].store(succs[0], std::memory_order_relaxed);
+            // The node is pushed once it is linked on the bottom level.
+            if (preds[0]->compare_exchange_strong(
+                    succs[0], link_of(node),
+                    std::memory_order_release,
+                    std::memory_order_relaxed)) {
+                break;
+            }
+        }
+        len_.pushed();
+
+        for (uint32_t l = 1; l < node->level; ++l) {
+            while (true) {
+                auto next = node->next[l].load(std::memory_order_acquire);
+                if (next != succs[l] && (marked(next) || !node->next[l].compare_exchange_strong(
+                        next, succs[l],
+                        std::memory_order_acq_rel,
+                        std::memory_order_acquire))) {
+                    // Frozen by a pop, stop linking the upper levels.
+                    release(node, guard);
+                    return;
+                }
+                auto expected = succs[l];
+                if (preds[l]->compare_exchange_strong(
+                        expected, link_of(node),
+                        std::memory_order_release,
+                        std::memory_order_relaxed)) {
+                    break;
+                }
+                find(node->value, node->seq, preds, succs);
+            }
+        }
+        release(node, guard);
+    }
+
+    /// @brief Releases a reference of the node, the last one unlinks it from all levels and retires
+    /// it. The node is marked on all levels then.
+    void release(Node *node, const epoch::Guard &guard)
+    {
+        if (node->refs.fetch_sub(1, std::memory_order_acq_rel) != 1) {
+            return;
+        }
+
+        std::array<Link *, MaxLevel> preds;
+        std::array<uintptr_t, MaxLevel> succs;
+        find(node->value, node->seq, preds, succs);
+        guard.defer_destroy(node);
+    }
+
+    /// @brief The links of the head sentinel on each level.
+    std::array<Link, MaxLevel> head_{};
+    /// @brief The next pushing order.
+    util::CachePadded<std::atomic<uint64_t>> seq_;
+    impl::LenCounter len_;
+    SC_NO_UNIQUE_ADDRESS Compare compare_;
+};
+
+}
+
+#endif //SYNC_CELL_PRIORITY_QUEUE_HPP
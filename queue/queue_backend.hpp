@@ -0,0 +1,181 @@
+///
+/// @file  queue_backend.hpp
+/// @brief The traits abstracting the push, pop and steal operations of the queues and the deques,
+/// for the generic code and the @c ThreadPool over any of them.
+///
+
+#ifndef SYNC_CELL_QUEUE_BACKEND_HPP
+#define SYNC_CELL_QUEUE_BACKEND_HPP
+
+#include <concepts>
+#include <memory>
+#include <optional>
+#include <type_traits>
+#include <utility>
+
+#include "deque/work_stealing_deque.hpp"
+#include "shared/numa.hpp"
+
+
+namespace sc {
+
+namespace impl {
+
+template<typename Q>
+concept HasTryEnqueue = requires(Q &q, typename Q::value_type &&v) {
+    { q.try_enqueue(std::move(v)) } -> std::same_as<bool>;
+};
+
+template<typename Q>
+concept SharedQueueShape = requires(Q &q, typename Q::value_type &&v) {
+    q.enqueue(std::move(v));
+    { q.try_dequeue() } -> std::same_as<std::optional<typename Q::value_type>>;
+};
+
+}
+
+/// @brief The operations of a queue backend 'Q', which the generic code calls instead of the
+/// members of 'Q'. A specialization provides:
+/// * @c value_type and @c stealer_type: the item type, and the handle stealing from the queue by
+///   the other threads, which must be copyable.
+/// * <tt>static bool push(Q &, value_type &&)</tt>: pushes an item without blocking, returns false
+///   if the queue is full.
+/// * <tt>static std::optional<value_type> pop(Q &)</tt>: pops an item, by the owner thread of a
+///   deque, or by any thread of a shared queue.
+/// * <tt>static stealer_type stealer(Q &)</tt> and
+///   <tt>static deque::Steal<value_type> steal(const stealer_type &)</tt>: steals an item.
+/// * <tt>static deque::Steal<value_type> steal_batch_and_pop(const stealer_type &, Q &dest)</tt>:
+///   steals a batch of items into the queue 'dest' of the calling thread, and returns one of them.
+///   It is optional, the @c ThreadPool steals one item at a time without it.
+/// * <tt>static std::unique_ptr<Q> create(int numa_node)</tt>: creates the queue of a worker of
+///   the thread pool, with the NUMA node hint of the worker. It is optional for the generic code.
+///
+/// The primary template covers the shared queues, such as @c mpmc::ArrayListQueue,
+/// @c mpmc::LinkedListQueue and @c mpmc::BoundedQueue: any type with the @c value_type, an
+/// @c enqueue and a @c try_dequeue. Their stealer is a pointer to the queue, and a steal is a
+/// dequeue. The @c try_enqueue is preferred for the push if the queue has one, so a full bounded
+/// queue never blocks. A third-party queue of the same shape works as is, the other ones
+/// specialize the traits:
+/// ``` cpp
+/// template<>
+/// struct sc::QueueBackendTraits<folly::MPMCQueue<Job>> { ... };
+/// ```
+template<typename Q>
+struct QueueBackendTraits
+{
+};
+
+template<impl::SharedQueueShape Q>
+struct QueueBackendTraits<Q>
+{
+    using value_type = typename Q::value_type;
+    using stealer_type = Q *;
+
+    static bool push(Q &q, value_type &&value)
+    {
+        if constexpr(impl::HasTryEnqueue<Q>) {
+            return q.try_enqueue(std::move(value));
+        } else if constexpr(std::is_same_v<decltype(q.enqueue(std::move(value))), bool>) {
+            return q.enqueue(std::move(value));
+        } else {
+            q.enqueue(std::move(value));
+            return true;
+        }
+    }
+
+    static std::optional<value_type> pop(Q &q)
+    {
+        return q.try_dequeue();
+    }
+
+    static stealer_type stealer(Q &q) noexcept
+    {
+        return &q;
+    }
+
+    static deque::Steal<value_type> steal(const stealer_type &stealer)
+    {
+        if (auto value = stealer->try_dequeue()) {
+            return deque::Steal<value_type>::success(*std::move(value));
+        }
+        return deque::Steal<value_type>::empty();
+    }
+
+    /// @brief Creates a default constructed queue, the NUMA node hint is ignored.
+    static std::unique_ptr<Q> create(int) requires std::is_default_constructible_v<Q>
+    {
+        return std::make_unique<Q>();
+    }
+};
+
+/// @brief The work-stealing deque: the owner pushes and pops, the other threads steal by the
+/// @c deque::Stealer. It is never full.
+template<typename T, typename Reclaimer>
+struct QueueBackendTraits<deque::Worker<T, Reclaimer>>
+{
+    using value_type = T;
+    using stealer_type = deque::Stealer<T, Reclaimer>;
+
+    static bool push(deque::Worker<T, Reclaimer> &q, value_type &&value)
+    {
+        q.push(value);
+        return true;
+    }
+
+    static std::optional<value_type> pop(deque::Worker<T, Reclaimer> &q)
+    {
+        return q.pop();
+    }
+
+    static stealer_type stealer(deque::Worker<T, Reclaimer> &q)
+    {
+        return q.stealer();
+    }
+
+    static deque::Steal<value_type> steal(const stealer_type &stealer)
+    {
+        return stealer.steal();
+    }
+
+    static deque::Steal<value_type> steal_batch_and_pop(const stealer_type &stealer,
+                                                        deque::Worker<T, Reclaimer> &dest)
+    {
+        return stealer.steal_batch_and_pop(dest);
+    }
+
+    /// @brief Creates the LIFO deque whose buffer is placed on the 'numa_node'.
+    static std::unique_ptr<deque::Worker<T, Reclaimer>> create(int numa_node)
+    {
+        return std::make_unique<deque::Worker<T, Reclaimer>>(deque::Flavor::Lifo, numa_node);
+    }
+};
+
+/// @brief A queue backend, whose operations are provided by the @c QueueBackendTraits:
+/// ``` cpp
+/// template<sc::QueueBackend Q>
+/// size_t drain_into(Q &q, std::vector<typename sc::QueueBackendTraits<Q>::value_type> &out)
+/// {
+///     using Backend = sc::QueueBackendTraits<Q>;
+///     size_t count = 0;
+///     for (; auto value = Backend::pop(q); ++count) { out.push_back(*std::move(value)); }
+///     return count;
+/// }
+/// ```
+template<typename Q>
+concept QueueBackend = requires(Q &q, typename QueueBackendTraits<Q>::value_type &&v,
+                                const typename QueueBackendTraits<Q>::stealer_type &s) {
+    { QueueBackendTraits<Q>::push(q, std::move(v)) } -> std::same_as<bool>;
+    { QueueBackendTraits<Q>::pop(q) } -> std::same_as<std::optional<typename QueueBackendTraits<Q>::value_type>>;
+    { QueueBackendTraits<Q>::stealer(q) } -> std::convertible_to<typename QueueBackendTraits<Q>::stealer_type>;
+    { QueueBackendTraits<Q>::steal(s) } -> std::same_as<deque::Steal<typename QueueBackendTraits<Q>::value_type>>;
+};
+
+/// @brief A queue backend which can be created by the @c ThreadPool for its workers.
+template<typename Q>
+concept PoolBackend = QueueBackend<Q> && requires(int node) {
+    { QueueBackendTraits<Q>::create(node) } -> std::same_as<std::unique_ptr<Q>>;
+};
+
+}
+
+#endif //SYNC_CELL_QUEUE_BACKEND_HPP
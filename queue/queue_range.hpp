@@ -0,0 +1,186 @@
+///
+/// @file  queue_range.hpp
+/// @brief Adapts the queue endpoints to the C++20 ranges: a consumer range which dequeues the items
+/// until the queue is closed, and a producer output iterator.
+///
+
+#ifndef SYNC_CELL_QUEUE_RANGE_HPP
+#define SYNC_CELL_QUEUE_RANGE_HPP
+
+#include <cstddef>
+#include <iterator>
+#include <memory>
+#include <optional>
+#include <ranges>
+#include <utility>
+
+
+#if __cpp_lib_ranges
+
+namespace sc {
+
+/// @brief An input view which dequeues the items from a queue, like a stream.
+///
+/// Each increment of the iterator calls the blocking @c dequeue of the queue, and the range ends
+/// when the @c dequeue returns an empty optional (the @c BlockingQueue is closed and drained). So
+/// it can be used with the range adaptors in a pipeline:
+/// ``` cpp
+/// sc::BlockingQueue<sc::mpmc::ArrayListQueue<Task>> queue;
+/// for (auto &task : sc::dequeue_view(queue) | std::views::filter(is_valid)) { ... }
+/// ```
+/// @note The iterator refers to the view, so the view must not be moved after @c begin is called.
+/// @tparam Queue The queue type, whose @c dequeue returns a @c std::optional.
+template<typename Queue>
+class DequeueView : public std::ranges::view_interface<DequeueView<Queue>>
+{
+public:
+    using value_type = typename Queue::value_type;
+
+    class iterator
+    {
+        friend class DequeueView;
+
+        explicit iterator(DequeueView *view) noexcept : view_(view) { }
+
+    public:
+        using iterator_concept = std::input_iterator_tag;
+        using difference_type = std::ptrdiff_t;
+        using value_type = DequeueView::value_type;
+
+        iterator(iterator &&) noexcept = default;
+
+        iterator &operator=(iterator &&) noexcept = default;
+
+        value_type &operator*() const noexcept
+        {
+            return *view_->current_;
+        }
+
+        value_type *operator->() const noexcept
+        {
+            return std::addressof(*view_->current_);
+        }
+
+        iterator &operator++()
+        {
+            view_->next();
+            return *this;
+        }
+
+        void operator++(int)
+        {
+            ++*this;
+        }
+
+        friend bool operator==(const iterator &it, std::default_sentinel_t) noexcept
+        {
+            return it.at_end();
+        }
+
+    private:
+        [[nodiscard]] bool at_end() const noexcept
+        {
+            return !view_->current_.has_value();
+        }
+
+        DequeueView *view_;
+    };
+
+    DequeueView() noexcept = default;
+
+    explicit DequeueView(Queue &queue) noexcept : queue_(std::addressof(queue)) { }
+
+    /// @brief Dequeues the first item. Must be called only once.
+    iterator begin()
+    {
+        next();
+        return iterator(this);
+    }
+
+    [[nodiscard]] std::default_sentinel_t end() const noexcept
+    {
+        return std::default_sentinel;
+    }
+
+private:
+    void next()
+    {
+        current_ = queue_->dequeue();
+    }
+
+    Queue *queue_ = nullptr;
+    std::optional<value_type> current_;
+};
+
+/// @brief An output iterator which enqueues the assigned items to a queue, like a sink.
+///
+/// ``` cpp
+/// std::ranges::copy(tasks | std::views::transform(make_task), sc::enqueue_iterator(queue));
+/// ```
+/// @note The result of the @c enqueue is ignored, so the items assigned after the
+/// @c BlockingQueue is closed are dropped.
+/// @tparam Queue The queue type.
+template<typename Queue>
+class EnqueueIterator
+{
+public:
+    using iterator_category = std::output_iterator_tag;
+    using difference_type = std::ptrdiff_t;
+    using value_type = void;
+    using pointer = void;
+    using reference = void;
+
+    EnqueueIterator() noexcept = default;
+
+    explicit EnqueueIterator(Queue &queue) noexcept : queue_(std::addressof(queue)) { }
+
+    EnqueueIterator &operator=(const typename Queue::value_type &value)
+    {
+        queue_->enqueue(value);
+        return *this;
+    }
+
+    EnqueueIterator &operator=(typename Queue::value_type &&value)
+    {
+        queue_->enqueue(std::move(value));
+        return *this;
+    }
+
+    EnqueueIterator &operator*() noexcept
+    {
+        return *this;
+    }
+
+    EnqueueIterator &operator++() noexcept
+    {
+        return *this;
+    }
+
+    EnqueueIterator &operator++(int) noexcept
+    {
+        return *this;
+    }
+
+private:
+    Queue *queue_ = nullptr;
+};
+
+/// @brief Creates a @c DequeueView of the 'queue'.
+template<typename Queue>
+DequeueView<Queue> dequeue_view(Queue &queue) noexcept
+{
+    return DequeueView<Queue>(queue);
+}
+
+/// @brief Creates an @c EnqueueIterator of the 'queue'.
+template<typename Queue>
+EnqueueIterator<Queue> enqueue_iterator(Queue &queue) noexcept
+{
+    return EnqueueIterator<Queue>(queue);
+}
+
+}
+
+#endif
+
+#endif //SYNC_CELL_QUEUE_RANGE_HPP
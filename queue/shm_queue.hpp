@@ -0,0 +1,248 @@
+///
+/// @file  shm_queue.hpp
+/// @brief A bounded mpmc queue placed in a memory shared by the processes.
+///
+
+#ifndef SYNC_CELL_SHM_QUEUE_HPP
+#define SYNC_CELL_SHM_QUEUE_HPP
+
+#include <algorithm>
+#include <array>
+#include <atomic>
+#include <bit>
+#include <cstddef>
+#include <cstdint>
+#include <cstring>
+#include <new>
+#include <optional>
+
+#include "shared/shm.hpp"
+#include "util/back_off.hpp"
+#include "util/cache_padded.hpp"
+
+
+namespace sc::shm {
+
+/// @brief A bounded MPMC queue of @c Pod values in a ring of slots, which is placed in a memory mapped
+/// by several processes (e.g. a @c Region), whose producers and consumers may live in any of them:
+/// ``` cpp
+/// // In the server, which creates the queue before the clients are started.
+/// auto *orders = sc::shm::ShmQueue<Order>::create(region.data(), 1024);
+/// while (true) { handle(orders->dequeue()); }
+/// // In the clients.
+/// auto *orders = sc::shm::ShmQueue<Order>::attach(region.data());
+/// orders->enqueue(Order{.id = 42});
+/// ```
+///
+/// Each slot holds a sequence number telling whether it is free for the producer of a round, or
+/// holds the value for the consumer of that round (the algorithm of D. Vyukov, as the
+/// @c mpmc::BoundedQueue). The slots follow the queue in the memory, and nothing refers to an
+/// address, so the processes may map the region anywhere.
+///
+/// @c enqueue and @c dequeue park on the shared futex words when the queue is full or empty. The
+/// opposite side only makes a system call when the count of the waiters is not zero.
+///
+/// @note A process dying between claiming a slot and publishing it stalls the consumers of that
+/// slot, the queue is not robust against the crashing processes.
+/// @tparam T The value type.
+/// @tparam Backoff The backoff of the operations retrying after losing a race.
+template<Pod T, typename Backoff = util::Backoff>
+class ShmQueue
+{
+    struct Slot
+    {
+        std::atomic<uint64_t> seq;
+        alignas(T) std::byte value[sizeof(T)];
+    };
+
+    explicit ShmQueue(size_t capacity) noexcept : capacity_(capacity)
+    {
+        auto *slots = slot_array();
+        for (size_t i = 0; i < capacity; ++i) {
+            auto *slot = new(&slots[i]) Slot;
+            slot->seq.store(i, std::memory_order_relaxed);
+        }
+        header_.init(impl::Kind::Queue, sizeof(T), alignof(T));
+    }
+
+public:
+    using value_type = T;
+
+    /// @brief Returns the bytes of the memory taken by the queue of 'capacity' values, which is
+    /// rounded up to a power of two.
+    static constexpr size_t required_size(size_t capacity) noexcept
+    {
+        return slots_offset() + round_capacity(capacity) * sizeof(Slot);
+    }
+
+    /// @brief Constructs the empty queue at 'memory', which must be aligned to @c alignof(ShmQueue),
+    /// and hold @c required_size(capacity) bytes. The other processes attach to it after the call
+    /// returns.
+    /// @param capacity The count of the slots, rounded up to a power of two.
+    static ShmQueue *create(void *memory, size_t capacity) noexcept
+    {
+        return new(memory) ShmQueue(round_capacity(capacity));
+    }
+
+    /// @brief Returns the queue created at 'memory' by another process (or this one).
+    /// @return nullptr if no queue has been created there yet, or the queue has been created for
+    /// another value type, or by another layout version of the project.
+    static ShmQueue *attach(void *memory) noexcept
+    {
+        auto *queue = std::launder(static_cast<ShmQueue *>(memory));
+        return queue->header_.matches(impl::Kind::Queue, sizeof(T), alignof(T)) ? queue : nullptr;
+    }
+
+    ShmQueue(const ShmQueue &) = delete;
+
+    ShmQueue &operator=(const ShmQueue &) = delete;
+
+    /// @brief Enqueues the 'value' if the queue is not full.
+    /// @return false if the queue is full.
+    bool try_enqueue(const value_type &value) noexcept
+    {
+        Backoff backoff;
+        auto pos = tail_->load(std::memory_order_relaxed);
+        while (true) {
+            auto &slot = slot_array()[pos & (capacity_ - 1)];
+            auto seq = slot.seq.load(std::memory_order_acquire);
+            auto diff = static_cast<int64_t>(seq - pos);
+            if (diff == 0) {
+                if (tail_->compare_exchange_weak(pos, pos + 1, std::memory_order_relaxed)) {
+                    std::memcpy(slot.value, &value, sizeof(T));
+                    slot.seq.store(pos + 1, std::memory_order_release);
+                    notify(not_empty_, waiting_consumers_);
+                    return true;
+                }
+                backoff.spin();
+            } else if (diff < 0) {
+                return false;
+            } else {
+                backoff.spin();
+                pos = tail_->load(std::memory_order_relaxed);
+            }
+        }
+    }
+
+    /// @brief Dequeues the first value if the queue is not empty.
+    [[nodiscard]] std::optional<value_type> try_dequeue() noexcept
+    {
+        Backoff backoff;
+        auto pos = head_->load(std::memory_order_relaxed);
+        while (true) {
+            auto &slot = slot_array()[pos & (capacity_ - 1)];
+            auto seq = slot.seq.load(std::memory_order_acquire);
+            auto diff = static_cast<int64_t>(seq - (pos + 1));
+            if (diff == 0) {
+                if (head_->compare_exchange_weak(pos, pos + 1, std::memory_order_relaxed)) {
+                    std::array<std::byte, sizeof(T)> bytes;
+                    std::memcpy(bytes.data(), slot.value, sizeof(T));
+                    slot.seq.store(pos + capacity_, std::memory_order_release);
+                    notify(not_full_, waiting_producers_);
+                    return std::bit_cast<value_type>(bytes);
+                }
+                backoff.spin();
+            } else if (diff < 0) {
+                return {};
+            } else {
+                backoff.spin();
+                pos = head_->load(std::memory_order_relaxed);
+            }
+        }
+    }
+
+    /// @brief Enqueues the 'value', parks the thread while the queue is full.
+    void enqueue(const value_type &value) noexcept
+    {
+        wait_until(not_full_, waiting_producers_, [&] { return try_enqueue(value); });
+    }
+
+    /// @brief Dequeues the first value, parks the thread while the queue is empty.
+    [[nodiscard]] value_type dequeue() noexcept
+    {
+        std::optional<value_type> value;
+        wait_until(not_empty_, waiting_consumers_, [&] { return (value = try_dequeue()).has_value(); });
+        return *value;
+    }
+
+    [[nodiscard]] size_t capacity() const noexcept
+    {
+        return capacity_;
+    }
+
+    /// @brief Returns the count of the values in the queue, which may be stale once returned if
+    /// other threads (or processes) are enqueuing or dequeuing.
+    [[nodiscard]] size_t len() const noexcept
+    {
+        auto head = head_->load(std::memory_order_acquire);
+        auto tail = tail_->load(std::memory_order_acquire);
+        return tail > head ? std::min<size_t>(tail - head, capacity_) : 0;
+    }
+
+    [[nodiscard]] bool is_empty() const noexcept
+    {
+        return len() == 0;
+    }
+
+private:
+    static constexpr size_t round_capacity(size_t capacity) noexcept
+    {
+        return std::bit_ceil(std::max<size_t>(capacity, 1));
+    }
+
+    /// @brief The slots start after the queue, aligned for a slot.
+    static constexpr size_t slots_offset() noexcept
+    {
+        return (sizeof(ShmQueue) + alignof(Slot) - 1) / alignof(Slot) * alignof(Slot);
+    }
+
+    [[nodiscard]] Slot *slot_array() const noexcept
+    {
+        auto *bytes = reinterpret_cast<std::byte *>(const_cast<ShmQueue *>(this));
+        return std::launder(reinterpret_cast<Slot *>(bytes + slots_offset()));
+    }
+
+    /// @brief Wakes up a waiter of the other side after a slot is published or released.
+    static void notify(std::atomic<uint32_t> &word, std::atomic<uint32_t> &waiting) noexcept
+    {
+        // Pairs with the increase of the waiters and the retry in 'wait_until': either this side
+        // sees the waiter, or the waiter sees the slot.
+        std::atomic_thread_fence(std::memory_order_seq_cst);
+        if (waiting.load(std::memory_order_relaxed) != 0) {
+            word.fetch_add(1, std::memory_order_release);
+            impl::wake(word, false);
+        }
+    }
+
+    /// @brief Retries 'op' until it succeeds, parking on the 'word' between the retries.
+    template<typename F>
+    static void wait_until(std::atomic<uint32_t> &word, std::atomic<uint32_t> &waiting, F &&op) noexcept
+    {
+        while (!op()) {
+            waiting.fetch_add(1, std::memory_order_relaxed);
+            std::atomic_thread_fence(std::memory_order_seq_cst);
+            auto seen = word.load(std::memory_order_acquire);
+            if (op()) {
+                waiting.fetch_sub(1, std::memory_order_relaxed);
+                return;
+            }
+            impl::wait(word, seen, -1);
+            waiting.fetch_sub(1, std::memory_order_relaxed);
+        }
+    }
+
+    impl::Header header_;
+    uint64_t capacity_;
+    util::CachePadded<std::atomic<uint64_t>> tail_{0};
+    util::CachePadded<std::atomic<uint64_t>> head_{0};
+    /// @brief The futex words increased when a value is published, or a slot is released, while
+    /// some threads wait for it.
+    std::atomic<uint32_t> not_empty_{0};
+    std::atomic<uint32_t> not_full_{0};
+    std::atomic<uint32_t> waiting_consumers_{0};
+    std::atomic<uint32_t> waiting_producers_{0};
+};
+
+}
+
+#endif //SYNC_CELL_SHM_QUEUE_HPP
@@ -0,0 +1,402 @@
+///
+/// @file  spill_queue.hpp
+/// @brief An unbounded mpmc queue which spills the items beyond a memory threshold to a file, and
+/// recovers them after a restart.
+///
+
+#ifndef SYNC_CELL_SPILL_QUEUE_HPP
+#define SYNC_CELL_SPILL_QUEUE_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The spill_queue.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <algorithm>
+#include <array>
+#include <atomic>
+#include <bit>
+#include <cstddef>
+#include <cstdint>
+#include <cstring>
+#include <filesystem>
+#include <fstream>
+#include <iterator>
+#include <mutex>
+#include <optional>
+#include <string>
+#include <string_view>
+#include <system_error>
+#include <type_traits>
+#include <utility>
+
+#include "queue/mpmc_list_queue.hpp"
+#include "shared/extend.hpp"
+#include "shared/queue_len.hpp"
+
+
+namespace sc {
+
+/// @brief Converts the items of a @c mpmc::SpillQueue to the bytes of its file and back. The
+/// trivially copyable types are copied as their bytes and @c std::string as its characters. The
+/// other types specialize it, e.g. by the binary archive of a serialization library, providing:
+/// * <tt>static void encode(const T &, std::string &out)</tt>: appends the bytes of the value.
+/// * <tt>static T decode(std::string_view bytes)</tt>: rebuilds the value from the bytes.
+template<typename T>
+struct SpillCodec
+{
+    static_assert(std::is_trivially_copyable_v<T>,
+                  "Specialize sc::SpillCodec to spill the types which are not trivially copyable.");
+
+    static void encode(const T &value, std::string &out)
+    {
+        out.append(reinterpret_cast<const char *>(&value), sizeof(T));
+    }
+
+    static T decode(std::string_view bytes)
+    {
+        std::array<std::byte, sizeof(T)> raw{};
+        std::memcpy(raw.data(), bytes.data(), std::min(bytes.size(), sizeof(T)));
+        return std::bit_cast<T>(raw);
+    }
+};
+
+template<>
+struct SpillCodec<std::string>
+{
+    static void encode(const std::string &value, std::string &out)
+    {
+        out.append(value);
+    }
+
+    static std::string decode(std::string_view bytes)
+    {
+        return std::string(bytes);
+    }
+};
+
+namespace mpmc {
+
+/// @brief An unbounded MPMC queue which holds about 'memory_limit' items in a @c LinkedListQueue,
+/// and appends the later ones to a file, e.g. for a pipeline whose backlog must survive a crash or
+/// outgrow the memory:
+/// ``` cpp
+/// // At the start, the items spilled before the last exit are dequeued first.
+/// auto events = sc::mpmc::SpillQueue<Event>::recover("/var/lib/app/events.spill", 10000);
+/// events.enqueue(event);
+/// if (auto next = events.try_dequeue()) { handle(*next); }
+/// ```
+///
+/// Once an item is spilled, the later items are spilled too until the file is drained, so the
+/// items keep the FIFO order. The consumers refill the memory from the file by the chunks of at
+/// most 'memory_limit' items when the memory is empty, and the file is truncated once it is
+/// drained. The file operations are done under a mutex, the items in the memory are enqueued and
+/// dequeued lock-free.
+///
+/// The file starts with a header holding the offset of the first unread record, which is updated
+/// after each refill, and is followed by the records, each one with its length and a checksum. So
+/// @c recover skips the records which have been read, and drops a torn record at the end of a file
+/// written by a crashed process.
+///
+/// @note Only the spilled items are durable: the items in the memory (including the ones refilled
+/// from the file) are lost by a crash. Each record is flushed to the system before @c enqueue
+/// returns, which survives a crash of the process, but not a power loss.
+/// @tparam T The value type.
+/// @tparam Codec The conversion of the items to the bytes, see @c sc::SpillCodec.
+template<typename T, typename Codec = SpillCodec<T>>
+class SpillQueue
+{
+    static constexpr uint64_t Magic = 0x314c4c4950534353;  // "SCSPILL1" in the little-endian bytes.
+    static constexpr std::streamoff HeaderSize = 16;
+    static constexpr std::streamoff RecordHeaderSize = 8;
+
+    struct RecoverTag
+    {
+    };
+
+public:
+    using value_type = T;
+
+    /// @brief Creates an empty queue spilling to the file at 'path', which is replaced if it exists.
+    /// @param memory_limit The count of the items held in the memory before the later ones are
+    /// spilled, at least 1.
+    /// @throw std::system_error If the file can not be created.
+    SpillQueue(std::filesystem::path path, size_t memory_limit)
+            : path_(std::move(path)), memory_limit_(std::max<size_t>(memory_limit, 1))
+    {
+        open(std::ios::trunc);
+        write_header();
+    }
+
+    /// @brief Creates a queue holding the unread items of the file at 'path', written by the queue of
+    /// an earlier run, which is spilled to after. An empty queue is created if the file does not
+    /// exist.
+    /// @throw std::system_error If the file can not be read or is not a spill file.
+    static SpillQueue recover(std::filesystem::path path, size_t memory_limit)
+    {
+        return SpillQueue(RecoverTag{}, std::move(path), memory_limit);
+    }
+
+    SpillQueue(const SpillQueue &) = delete;
+
+    SpillQueue &operator=(const SpillQueue &) = delete;
+
+    /// @brief Returns the count of the items in the memory and in the file, a racy snapshot.
+    [[nodiscard]] size_t len() const noexcept
+    {
+        return memory_.len() + spilled_.load(std::memory_order_relaxed);
+    }
+
+    [[nodiscard]] bool is_empty() const noexcept
+    {
+        return len() == 0;
+    }
+
+    /// @brief Returns the count of the items in the file, a racy snapshot.
+    [[nodiscard]] size_t spilled() const noexcept
+    {
+        return spilled_.load(std::memory_order_relaxed);
+    }
+
+    [[nodiscard]] static constexpr size_t capacity() noexcept
+    {
+        return UnboundedCapacity;
+    }
+
+    [[nodiscard]] static constexpr size_t remaining() noexcept
+    {
+        return UnboundedCapacity;
+    }
+
+    [[nodiscard]] const std::filesystem::path &path() const noexcept
+    {
+        return path_;
+    }
+
+    /// @throw std::system_error If the item must be spilled, and can not be written.
+    void enqueue(value_type value)
+    {
+        if (fits_in_memory()) {
+            memory_.enqueue(std::move(value));
+            return;
+        }
+        std::lock_guard guard(mtx_);
+        // A consumer may have drained the file meanwhile.
+        if (fits_in_memory()) {
+            memory_.enqueue(std::move(value));
+            return;
+        }
+        append(value);
+        spilled_.fetch_add(1, std::memory_order_release);
+    }
+
+    /// @brief Enqueues the items of [first, last) in order.
+    template<std::input_iterator It, typename Sentinel>
+    void extend(It first, Sentinel last)
+    {
+        sc::impl::extend(std::move(first), std::move(last), [this](auto &&v) {
+            enqueue(std::forward<decltype(v)>(v));
+        });
+    }
+
+    /// @brief Enqueues the items of the 'range' in order, e.g. a container or a view.
+    template<typename Range>
+    void extend(Range &&range)
+    {
+        extend(std::begin(range), std::end(range));
+    }
+
+    /// @brief Dequeues the first item, refills the memory from the file if it is empty.
+    /// @throw std::system_error If the file can not be read.
+    std::optional<value_type> try_dequeue()
+    {
+        if (auto value = memory_.try_dequeue()) {
+            return value;
+        }
+        if (spilled_.load(std::memory_order_acquire) == 0) {
+            return {};
+        }
+        std::lock_guard guard(mtx_);
+        if (auto value = memory_.try_dequeue()) {
+            return value;
+        }
+        refill();
+        return memory_.try_dequeue();
+    }
+
+private:
+    SpillQueue(RecoverTag, std::filesystem::path path, size_t memory_limit)
+            : path_(std::move(path)), memory_limit_(std::max<size_t>(memory_limit, 1))
+    {
+        std::error_code ec;
+        if (!std::filesystem::exists(path_, ec)) {
+            open(std::ios::trunc);
+            write_header();
+            return;
+        }
+        open({});
+        file_.seekg(0);
+        std::array<char, HeaderSize> header{};
+        if (!file_.read(header.data(), HeaderSize) || load_u64(header.data()) != Magic) {
+            throw std::system_error(std::make_error_code(std::errc::invalid_argument), "Not a spill file");
+        }
+        read_offset_ = static_cast<std::streamoff>(load_u64(header.data() + 8));
+        write_offset_ = read_offset_;
+        // Counts the valid records, a torn or corrupted one ends the file.
+        auto end = static_cast<std::streamoff>(std::filesystem::file_size(path_));
+        std::string payload;
+        size_t count = 0;
+        while (read_record(write_offset_, end, payload)) {
+            write_offset_ += RecordHeaderSize + static_cast<std::streamoff>(payload.size());
+            ++count;
+        }
+        file_.clear();
+        std::filesystem::resize_file(path_, static_cast<uintmax_t>(write_offset_), ec);
+        spilled_.store(count, std::memory_order_release);
+    }
+
+    [[nodiscard]] bool fits_in_memory() const noexcept
+    {
+        return spilled_.load(std::memory_order_acquire) == 0 && memory_.len() < memory_limit_;
+    }
+
+    void open(std::ios::openmode mode)
+    {
+        file_.open(path_, std::ios::in | std::ios::out | std::ios::binary | mode);
+        if (!file_) {
+            throw std::system_error(std::make_error_code(std::errc::io_error), "Can not open the spill file");
+        }
+    }
+
+    /// @brief Moves the next records to the memory, called under the lock.
+    void refill()
+    {
+        std::string payload;
+        size_t count = 0;
+        while (count < memory_limit_ && read_record(read_offset_, write_offset_, payload)) {
+            read_offset_ += RecordHeaderSize + static_cast<std::streamoff>(payload.size());
+            memory_.enqueue(Codec::decode(payload));
+            ++count;
+        }
+        file_.clear();
+        if (count == 0) {
+            // A record failing the checksum, it is dropped with the rest of the file.
+            read_offset_ = write_offset_;
+            spilled_.store(0, std::memory_order_release);
+        } else {
+            spilled_.fetch_sub(count, std::memory_order_release);
+        }
+        if (read_offset_ == write_offset_) {
+            read_offset_ = write_offset_ = HeaderSize;
+            std::error_code ec;
+            std::filesystem::resize_file(path_, static_cast<uintmax_t>(HeaderSize), ec);
+        }
+        write_header();
+    }
+
+    /// @brief Appends the record of 'value', called under the lock.
+    void append(const value_type &value)
+    {
+        buffer_.assign(RecordHeaderSize, '\0');
+        Codec::encode(value, buffer_);
+        auto size = static_cast<uint32_t>(buffer_.size() - RecordHeaderSize);
+        store_u32(buffer_.data(), size);
+        store_u32(buffer_.data() + 4, checksum(std::string_view(buffer_).substr(RecordHeaderSize)));
+        file_.seekp(write_offset_);
+        if (!file_.write(buffer_.data(), static_cast<std::streamsize>(buffer_.size())) || !file_.flush()) {
+            file_.clear();
+            throw std::system_error(std::make_error_code(std::errc::io_error), "Can not write the spill file");
+        }
+        write_offset_ += static_cast<std::streamoff>(buffer_.size());
+    }
+
+    /// @brief Reads the record at 'offset' into 'payload', which must end before 'end'.
+    /// @return false at the end of the records, or if the record is torn or corrupted.
+    bool read_record(std::streamoff offset, std::streamoff end, std::string &payload)
+    {
+        std::array<char, RecordHeaderSize> header{};
+        file_.seekg(offset);
+        if (end - offset < RecordHeaderSize || !file_.read(header.data(), RecordHeaderSize)) {
+            return false;
+        }
+        auto size = load_u32(header.data());
+        if (end - offset - RecordHeaderSize < static_cast<std::streamoff>(size)) {
+            return false;
+        }
+        payload.resize(size);
+        if (!file_.read(payload.data(), static_cast<std::streamsize>(payload.size()))) {
+            return false;
+        }
+        return checksum(payload) == load_u32(header.data() + 4);
+    }
+
+    void write_header()
+    {
+        std::array<char, HeaderSize> header{};
+        store_u64(header.data(), Magic);
+        store_u64(header.data() + 8, static_cast<uint64_t>(read_offset_));
+        file_.seekp(0);
+        if (!file_.write(header.data(), HeaderSize) || !file_.flush()) {
+            file_.clear();
+            throw std::system_error(std::make_error_code(std::errc::io_error), "Can not write the spill file");
+        }
+    }
+
+    /// @brief The FNV-1a hash of the payload, to detect a torn record.
+    static uint32_t checksum(std::string_view bytes) noexcept
+    {
+        uint32_t hash = 2166136261u;
+        for (auto c: bytes) {
+            hash = (hash ^ static_cast<uint8_t>(c)) * 16777619u;
+        }
+        return hash;
+    }
+
+    // The integers of the file are little-endian, whatever the machine.
+    static void store_u32(char *p, uint32_t v) noexcept
+    {
+        for (int i = 0; i < 4; ++i) {
+            p[i] = static_cast<char>(v >> (8 * i));
+        }
+    }
+
+    static void store_u64(char *p, uint64_t v) noexcept
+    {
+        store_u32(p, static_cast<uint32_t>(v));
+        store_u32(p + 4, static_cast<uint32_t>(v >> 32));
+    }
+
+    static uint32_t load_u32(const char *p) noexcept
+    {
+        uint32_t v = 0;
+        for (int i = 0; i < 4; ++i) {
+            v |= static_cast<uint32_t>(static_cast<uint8_t>(p[i])) << (8 * i);
+        }
+        return v;
+    }
+
+    static uint64_t load_u64(const char *p) noexcept
+    {
+        return load_u32(p) | static_cast<uint64_t>(load_u32(p + 4)) << 32;
+    }
+
+    std::filesystem::path path_;
+    size_t memory_limit_;
+    LinkedListQueue<T> memory_;
+    /// @brief The count of the records in the file, increased after a record is written.
+    std::atomic<size_t> spilled_{0};
+
+    std::mutex mtx_;
+    /// @brief The file and its offsets, guarded by 'mtx_'.
+    std::fstream file_;
+    std::streamoff read_offset_ = HeaderSize;
+    std::streamoff write_offset_ = HeaderSize;
+    std::string buffer_;
+};
+
+}
+
+}
+
+#endif //SYNC_CELL_SPILL_QUEUE_HPP
@@ -0,0 +1,424 @@
+///
+/// @file  spsc_ring_buffer.hpp
+/// @brief A bounded spsc queue implemented with the ring buffer.
+///
+
+#ifndef SYNC_CELL_SPSC_RING_BUFFER_HPP
+#define SYNC_CELL_SPSC_RING_BUFFER_HPP
+
+#include <atomic>
+#include <cassert>
+#include <iterator>
+#include <memory>
+#include <optional>
+#include <utility>
+#include <vector>
+
+#include "shared/alloc_array.hpp"
+#include "shared/compiler_workaround.hpp"
+#include "shared/drain.hpp"
+#include "shared/extend.hpp"
+#include "shared/metrics.hpp"
+#include "util/back_off.hpp"
+#include "util/cache_padded.hpp"
+#include "util/copy_move_selector.hpp"
+
+
+namespace sc::spsc {
+
+/// @brief A bounded FIFO queue for exactly one producer thread and one consumer thread.
+///
+/// The buffer itself provides no enqueue/dequeue method, the @c split method must be called to
+/// get the @c Producer handle and the @c Consumer handle. Both handles are move-only, so each
+/// of them can be owned by only one thread at the same time, which satisfies the single-producer
+/// and single-consumer access convention.
+///
+/// Both the @c Producer::try_enqueue and the @c Consumer::try_dequeue are wait-free: only an
+/// acquire load of the opposite index and a release store of the self-owned index are needed,
+/// no CAS loop is involved.
+///
+/// @note The @c RingBuffer object must outlive the handles returned by @c split.
+/// @tparam T The value type.
+/// @tparam Backoff The backoff of the blocking enqueue and dequeue.
+/// @tparam Allocator The allocator of the buffer.
+template<typename T, typename Backoff = util::Backoff, typename Allocator = std::allocator<T>>
+class RingBuffer
+{
+public:
+    using value_type = T;
+    using reference = value_type &;
+    using const_reference = const value_type &;
+    using allocator_type = Allocator;
+
+    class Producer;
+
+    class Consumer;
+
+    /// @brief Creates a new ring buffer with the given capacity.
+    /// @param capacity The max count of values the buffer can hold. It must be greater than 0.
+    /// @param alloc The allocator of the buffer.
+    explicit RingBuffer(size_t capacity, const Allocator &alloc = Allocator())
+            : buffer_(capacity, alloc), cap_(capacity)
+    {
+        assert(capacity > 0);
+
+        head_->store(0, std::memory_order_relaxed);
+        tail_->store(0, std::memory_order_relaxed);
+    }
+
+    RingBuffer(const RingBuffer &) = delete;
+
+    RingBuffer &operator=(const RingBuffer &) = delete;
+
+    [[nodiscard]] bool is_lock_free() const noexcept
+    {
+        return head_->is_lock_free() && tail_->is_lock_free();
+    }
+
+    /// @brief Returns the capacity of the buffer.
+    [[nodiscard]] size_t capacity() const noexcept
+    {
+        return cap_;
+    }
+
+    /// @brief Returns the count of the values in the buffer. It is a racy snapshot unless called by
+    /// the producer (an upper bound) or the consumer (a lower bound).
+    [[nodiscard]] size_t len() const noexcept
+    {
+        auto head = head_->load(std::memory_order_acquire);
+        auto tail = tail_->load(std::memory_order_acquire);
+        return distance(head, tail);
+    }
+
+    /// @brief Returns true if the buffer is empty, a racy snapshot as @c len.
+    [[nodiscard]] bool is_empty() const noexcept
+    {
+        return len() == 0;
+    }
+
+    /// @brief Returns the count of the values which can be enqueued without blocking, a racy
+    /// snapshot as @c len.
+    [[nodiscard]] size_t remaining() const noexcept
+    {
+        return cap_ - len();
+    }
+
+    /// @brief Returns the contention counters: the waits of the producer on a full buffer as the
+    /// spin iterations. Requires @c SYNC_CELL_METRICS.
+    [[nodiscard]] metrics::Stats stats() const noexcept
+    {
+        return metrics_.snapshot();
+    }
+
+    [[nodiscard]] allocator_type get_allocator() const noexcept
+    {
+        return allocator_type(buffer_.get_allocator());
+    }
+
+    /// @brief Splits the buffer into the producer handle and the consumer handle.
+    /// @note This method can be called only once.
+    std::pair<Producer, Consumer> split() noexcept
+    {
+        [[maybe_unused]] auto split = split_.test_and_set(std::memory_order_relaxed);
+        assert(!split);
+
+        auto tail = tail_->load(std::memory_order_relaxed);
+        auto head = head_->load(std::memory_order_relaxed);
+        return {Producer(this, head), Consumer(this, tail)};
+    }
+
+private:
+    /// @brief Indices are in the range of [0, 2 * cap_), so that the full state (distance == cap_)
+    /// can be distinguished from the empty state (distance == 0).
+    [[nodiscard]] size_t next_index(size_t index) const noexcept
+    {
+        return index + 1 < 2 * cap_ ? index + 1 : 0;
+    }
+
+    [[nodiscard]] size_t distance(size_t head, size_t tail) const noexcept
+    {
+        return tail >= head ? tail - head : tail + 2 * cap_ - head;
+    }
+
+    [[nodiscard]] std::optional<value_type> &slot(size_t index) const noexcept
+    {
+        return buffer_[index < cap_ ? index : index - cap_];
+    }
+
+    /// @brief The read position. Only the consumer writes it.
+    util::CachePadded<std::atomic<size_t>> head_;
+    /// @brief The write position. Only the producer writes it.
+    util::CachePadded<std::atomic<size_t>> tail_;
+
+    impl::AllocArray<std::optional<value_type>, Allocator> buffer_;
+    size_t cap_;
+    SC_NO_UNIQUE_ADDRESS metrics::Counters metrics_;
+
+    std::atomic_flag split_ = ATOMIC_FLAG_INIT;
+};
+
+/// @brief The producer handle of the @c RingBuffer.
+template<typename T, typename Backoff, typename Allocator>
+class RingBuffer<T, Backoff, Allocator>::Producer
+{
+    friend class RingBuffer;
+
+    Producer(RingBuffer *buffer, size_t head) noexcept: buffer_(buffer), cached_head_(head) { }
+
+public:
+    Producer(Producer &&other) noexcept
+            : buffer_(std::exchange(other.buffer_, nullptr)), cached_head_(other.cached_head_) { }
+
+    Producer &operator=(Producer &&other) noexcept
+    {
+        buffer_ = std::exchange(other.buffer_, nullptr);
+        cached_head_ = other.cached_head_;
+        return *this;
+    }
+
+    Producer(const Producer &) = delete;
+
+    Producer &operator=(const Producer &) = delete;
+
+    /// @brief Try enqueue a value to the buffer.
+    /// @return false if the buffer is full. In this case, the 'value' is not touched.
+    template<typename = std::enable_if_t<std::is_copy_constructible_v<value_type>>>
+    bool try_enqueue(const_reference value)
+    {
+        return enqueue_value([&value](std::optional<value_type> &o) {
+            o.TEMPLATE_CALL emplace(value);
+        });
+    }
+
+    /// @brief Try enqueue a value to the buffer.
+    /// @return false if the buffer is full. In this case, the 'value' is not moved.
+    template<typename = std::enable_if_t<std::is_move_constructible_v<value_type>>>
+    bool try_enqueue(value_type &&value)
+    {
+        return enqueue_value([&value](std::optional<value_type> &o) {
+            o.TEMPLATE_CALL emplace(std::move(value));
+        });
+    }
+
+    /// @brief Try enqueue a value constructed in place from the 'args'.
+    /// @return false if the buffer is full. In this case, the 'args' are not touched.
+    template<typename... Args>
+    bool try_emplace(Args &&... args)
+    {
+        static_assert(std::is_constructible_v<value_type, Args &&...>);
+
+        return enqueue_value([&args...](std::optional<value_type> &o) {
+            o.TEMPLATE_CALL emplace(std::forward<Args>(args)...);
+        });
+    }
+
+    /// @brief Try enqueue a value written by 'f' directly in the slot, so a large value is not
+    /// copied from the stack of the caller.
+    /// @param f The function with the signature of 'void(std::optional<T> &)', which must emplace
+    /// the value into the empty optional. If it throws, the slot is left empty and not published.
+    /// @return false if the buffer is full. In this case, 'f' is not called.
+    template<typename F>
+    bool try_enqueue_with(F &&f)
+    {
+        return enqueue_value([&f](std::optional<value_type> &o) {
+            std::forward<F>(f)(o);
+            assert(o.has_value());
+        });
+    }
+
+    /// @brief Enqueue a value to the buffer. If the buffer is full, waits until a slot is released
+    /// by the consumer thread.
+    template<typename = std::enable_if_t<std::is_copy_constructible_v<value_type>>>
+    void enqueue(const_reference value)
+    {
+        Backoff backoff;
+        while (!try_enqueue(value)) {
+            buffer_->metrics_.spin();
+            backoff.snooze();
+        }
+    }
+
+    /// @brief Enqueue a value to the buffer. If the buffer is full, waits until a slot is released
+    /// by the consumer thread.
+    template<typename = std::enable_if_t<std::is_move_constructible_v<value_type>>>
+    void enqueue(value_type &&value)
+    {
+        Backoff backoff;
+        while (!try_enqueue(std::move(value))) {
+            buffer_->metrics_.spin();
+            backoff.snooze();
+        }
+    }
+
+    /// @brief Enqueues the items of [first, last) in order, waits for the consumer if the buffer
+    /// is full, as @c enqueue.
+    template<std::input_iterator It, typename Sentinel>
+    void extend(It first, Sentinel last)
+    {
+        sc::impl::extend(std::move(first), std::move(last), [this](auto &&v) {
+            enqueue(std::forward<decltype(v)>(v));
+        });
+    }
+
+    /// @brief Enqueues the items of the 'range' in order, e.g. a container or a view.
+    template<typename Range>
+    void extend(Range &&range)
+    {
+        extend(std::begin(range), std::end(range));
+    }
+
+private:
+    template<typename Func>
+    bool enqueue_value(Func value_set)
+    {
+        auto &rb = *buffer_;
+        auto tail = rb.tail_->load(std::memory_order_relaxed);
+
+        // Only reload the head index to check if the space is really exhausted.
+        if (rb.distance(cached_head_, tail) == rb.cap_) {
+            cached_head_ = rb.head_->load(std::memory_order_acquire);
+            if (rb.distance(cached_head_, tail) == rb.cap_) {
+                return false;
+            }
+        }
+
+        value_set(rb.slot(tail));
+        rb.tail_->store(rb.next_index(tail), std::memory_order_release);
+        return true;
+    }
+
+    RingBuffer *buffer_;
+    /// @brief A copy of the 'head_' index, it is always in or behind the real position.
+    size_t cached_head_;
+};
+
+/// @brief The consumer handle of the @c RingBuffer.
+template<typename T, typename Backoff, typename Allocator>
+class RingBuffer<T, Backoff, Allocator>::Consumer
+{
+    friend class RingBuffer;
+
+    Consumer(RingBuffer *buffer, size_t tail) noexcept: buffer_(buffer), cached_tail_(tail) { }
+
+public:
+    Consumer(Consumer &&other) noexcept
+            : buffer_(std::exchange(other.buffer_, nullptr)), cached_tail_(other.cached_tail_) { }
+
+    Consumer &operator=(Consumer &&other) noexcept
+    {
+        buffer_ = std::exchange(other.buffer_, nullptr);
+        cached_tail_ = other.cached_tail_;
+        return *this;
+    }
+
+    Consumer(const Consumer &) = delete;
+
+    Consumer &operator=(const Consumer &) = delete;
+
+    /// @brief Try dequeue an item from the buffer.
+    /// @return If success, the optional takes the dequeue value, otherwise the optional is empty.
+    std::optional<value_type> try_dequeue()
+    {
+        return pop_if([](const_reference) { return true; });
+    }
+
+    /// @brief Try dequeue an item, and calls 'f' with it in the slot, so a large value is not moved
+    /// out. The item is destroyed after the call.
+    /// @param f The function with the signature of 'void(T &)'. If it throws, the item stays in the
+    /// buffer.
+    /// @return false if the buffer is empty, in this case, 'f' is not called.
+    template<typename F>
+    bool try_dequeue_with(F &&f)
+    {
+        auto &rb = *buffer_;
+        auto head = rb.head_->load(std::memory_order_relaxed);
+        if (!has_item(head)) {
+            return false;
+        }
+
+        auto &slot = rb.slot(head);
+        std::forward<F>(f)(*slot);
+        slot.reset();
+        rb.head_->store(rb.next_index(head), std::memory_order_release);
+        return true;
+    }
+
+    /// @brief Calls 'f' with the first item without removing it.
+    /// @return false if the buffer is empty, in this case, 'f' is not called.
+    template<typename F>
+    bool peek(F &&f)
+    {
+        auto head = buffer_->head_->load(std::memory_order_relaxed);
+        if (!has_item(head)) {
+            return false;
+        }
+
+        std::forward<F>(f)(std::as_const(*buffer_->slot(head)));
+        return true;
+    }
+
+    /// @brief Dequeues the first item only if 'pred' returns true for it.
+    /// @return An empty optional if the buffer is empty or 'pred' returns false.
+    template<typename Pred>
+    std::optional<value_type> pop_if(Pred &&pred)
+    {
+        auto &rb = *buffer_;
+        auto head = rb.head_->load(std::memory_order_relaxed);
+        if (!has_item(head) || !std::forward<Pred>(pred)(std::as_const(*rb.slot(head)))) {
+            return {};
+        }
+
+        auto &slot = rb.slot(head);
+        std::optional<value_type> ret(util::cast_ctor_ref(slot));
+        slot.reset();
+        rb.head_->store(rb.next_index(head), std::memory_order_release);
+        return ret;
+    }
+
+    /// @brief Try dequeue at most 'max' items from the buffer, with one store of the head index.
+    /// The items are appended to 'out' in the FIFO order.
+    /// @return The count of the dequeued items.
+    size_t try_dequeue_batch(std::vector<value_type> &out, size_t max)
+    {
+        auto &rb = *buffer_;
+        auto head = rb.head_->load(std::memory_order_relaxed);
+        cached_tail_ = rb.tail_->load(std::memory_order_acquire);
+
+        size_t count = 0;
+        for (; count < max && head != cached_tail_; ++count) {
+            auto &slot = rb.slot(head);
+            out.emplace_back(util::cast_ctor_ref(*slot));
+            slot.reset();
+            head = rb.next_index(head);
+        }
+        rb.head_->store(head, std::memory_order_release);
+        return count;
+    }
+
+    /// @brief Takes the values in the buffer by chunks.
+    /// @param chunk The max count of the values in one chunk.
+    /// @return A @c Drain range, which stops after the values in the buffer when it is called.
+    auto drain(size_t chunk = DefaultDrainChunk)
+    {
+        auto take = [this](std::vector<value_type> &out, size_t max) { return try_dequeue_batch(out, max); };
+        return Drain<value_type, decltype(take)>(take, chunk, buffer_->len());
+    }
+
+private:
+    [[nodiscard]] bool has_item(size_t head) noexcept
+    {
+        // Only reload the tail index to check if the buffer is really empty.
+        if (cached_tail_ == head) {
+            cached_tail_ = buffer_->tail_->load(std::memory_order_acquire);
+        }
+        return cached_tail_ != head;
+    }
+
+    RingBuffer *buffer_;
+    /// @brief A copy of the 'tail_' index, it is always in or behind the real position.
+    size_t cached_tail_;
+};
+
+}
+
+#endif //SYNC_CELL_SPSC_RING_BUFFER_HPP
@@ -0,0 +1,153 @@
+///
+/// @file  affinity.hpp
+/// @brief Pins the threads to the cores, to keep the workers of the thread pool on the same cores
+/// from run to run.
+///
+
+#ifndef SYNC_CELL_AFFINITY_HPP
+#define SYNC_CELL_AFFINITY_HPP
+
+#include "shared/config.hpp"
+
+#include <cstddef>
+#include <string>
+#include <vector>
+
+#if SC_HAS_AFFINITY
+#include <algorithm>
+#include <charconv>
+#include <fstream>
+#include <sched.h>
+#endif
+
+
+namespace sc::affinity {
+
+namespace impl {
+
+#if SC_HAS_AFFINITY
+/// @brief The count of the cores in the CPU sets passed to the kernel.
+constexpr int MaxCores = 4096;
+
+/// @brief Parses a CPU list of the sysfs, such as "0-3,8,10-11". The malformed items are skipped,
+/// and the cores from @c MaxCores on are dropped.
+inline std::vector<int> parse_cpu_list(const std::string &list)
+{
+    std::vector<int> cores;
+    size_t pos = 0;
+    while (pos < list.size()) {
+        auto end = list.find(',', pos);
+        auto item = list.substr(pos, end == std::string::npos ? std::string::npos : end - pos);
+        pos = end == std::string::npos ? list.size() : end + 1;
+        // A trailing newline of the sysfs file is not a part of the item.
+        while (!item.empty() && (item.back() == '\n' || item.back() == ' ')) {
+            item.pop_back();
+        }
+
+        const char *item_end = item.data() + item.size();
+        int first = 0;
+        auto [first_end, first_ec] = std::from_chars(item.data(), item_end, first);
+        if (first_ec != std::errc() || first < 0) {
+            continue;
+        }
+        int last = first;
+        if (first_end != item_end) {
+            if (*first_end != '-') {
+                continue;
+            }
+            auto [last_end, last_ec] = std::from_chars(first_end + 1, item_end, last);
+            if (last_ec != std::errc() || last_end != item_end) {
+                continue;
+            }
+        }
+        for (int core = first; core <= std::min(last, MaxCores - 1); ++core) {
+            cores.push_back(core);
+        }
+    }
+    return cores;
+}
+#endif
+
+}
+
+/// @brief Returns the cores the current thread may run on, or empty if it is unknown.
+[[nodiscard]] inline std::vector<int> allowed_cores()
+{
+    std::vector<int> cores;
+#if SC_HAS_AFFINITY
+    auto *set = CPU_ALLOC(impl::MaxCores);
+    if (set == nullptr) {
+        return cores;
+    }
+    auto size = CPU_ALLOC_SIZE(impl::MaxCores);
+    CPU_ZERO_S(size, set);
+    if (::sched_getaffinity(0, size, set) == 0) {
+        for (int core = 0; core < impl::MaxCores; ++core) {
+            if (CPU_ISSET_S(core, size, set)) {
+                cores.push_back(core);
+            }
+        }
+    }
+    CPU_FREE(set);
+#endif
+    return cores;
+}
+
+/// @brief Returns the cores of the NUMA 'node' read from the sysfs, or empty if it is unknown.
+[[nodiscard]] inline std::vector<int> node_cores(int node)
+{
+#if SC_HAS_AFFINITY
+    if (node >= 0) {
+        std::ifstream in("/sys/devices/system/node/node" + std::to_string(node) + "/cpulist");
+        std::string list;
+        if (std::getline(in, list)) {
+            return impl::parse_cpu_list(list);
+        }
+    }
+#else
+    (void) node;
+#endif
+    return {};
+}
+
+/// @brief Pins the current thread to the 'cores', it may run on any of them after the call.
+/// @return False if the thread is not pinned: the 'cores' is empty, or not allowed for the
+/// process, or the pinning is not supported without @c SC_HAS_AFFINITY.
+inline bool pin_current_thread(const std::vector<int> &cores) noexcept
+{
+#if SC_HAS_AFFINITY
+    auto *set = CPU_ALLOC(impl::MaxCores);
+    if (set == nullptr) {
+        return false;
+    }
+    auto size = CPU_ALLOC_SIZE(impl::MaxCores);
+    CPU_ZERO_S(size, set);
+    bool any = false;
+    for (auto core: cores) {
+        if (core >= 0 && core < impl::MaxCores) {
+            CPU_SET_S(core, size, set);
+            any = true;
+        }
+    }
+    bool pinned = any && ::sched_setaffinity(0, size, set) == 0;
+    CPU_FREE(set);
+    return pinned;
+#else
+    (void) cores;
+    return false;
+#endif
+}
+
+/// @brief Returns the core running the current thread, or -1 if it is unknown.
+[[nodiscard]] inline int current_core() noexcept
+{
+#if SC_HAS_AFFINITY
+    return ::sched_getcpu();
+#else
+    return -1;
+#endif
+}
+
+}
+
+#endif //SYNC_CELL_AFFINITY_HPP
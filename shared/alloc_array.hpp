@@ -0,0 +1,89 @@
+///
+/// @file  alloc_array.hpp
+/// @brief A fixed-length array allocated from a user allocator, the buffer of the ring queues.
+///
+
+#ifndef SYNC_CELL_ALLOC_ARRAY_HPP
+#define SYNC_CELL_ALLOC_ARRAY_HPP
+
+#include <cstddef>
+#include <memory>
+#include <utility>
+
+#include "shared/compiler_workaround.hpp"
+
+
+namespace sc::impl {
+
+/// @brief An owned array of the value-initialized elements, like the @c std::unique_ptr<T[]> made
+/// by @c std::make_unique, but allocated from 'Allocator' (rebound to 'T').
+template<typename T, typename Allocator>
+class AllocArray
+{
+    using Alloc = typename std::allocator_traits<Allocator>::template rebind_alloc<T>;
+    using AllocTrait = std::allocator_traits<Alloc>;
+
+public:
+    using allocator_type = Alloc;
+
+    AllocArray(size_t len, const Allocator &alloc)
+            : alloc_(alloc), data_(AllocTrait::allocate(alloc_, len)), len_(len)
+    {
+        size_t i = 0;
+        try {
+            for (; i < len; ++i) {
+                AllocTrait::construct(alloc_, data_ + i);
+            }
+        } catch (...) {
+            destroy(i);
+            throw;
+        }
+    }
+
+    AllocArray(const AllocArray &) = delete;
+
+    AllocArray &operator=(const AllocArray &) = delete;
+
+    ~AllocArray()
+    {
+        destroy(len_);
+    }
+
+    /// @brief Exchanges the elements and the allocators.
+    void swap(AllocArray &other) noexcept
+    {
+        using std::swap;
+        swap(alloc_, other.alloc_);
+        swap(data_, other.data_);
+        swap(len_, other.len_);
+    }
+
+    [[nodiscard]] allocator_type get_allocator() const noexcept
+    {
+        return alloc_;
+    }
+
+    /// @brief Accesses an element, not const-propagating as the @c std::unique_ptr<T[]>.
+    T &operator[](size_t i) const noexcept
+    {
+        return data_[i];
+    }
+
+private:
+    /// @brief Destroys the first 'count' elements, and releases the memory.
+    void destroy(size_t count) noexcept
+    {
+        for (size_t i = count; i > 0; --i) {
+            AllocTrait::destroy(alloc_, data_ + i - 1);
+        }
+        AllocTrait::deallocate(alloc_, data_, len_);
+    }
+
+    SC_NO_UNIQUE_ADDRESS Alloc alloc_;
+    T *data_;
+    size_t len_;
+};
+
+}
+
+#endif //SYNC_CELL_ALLOC_ARRAY_HPP
@@ -16,4 +16,12 @@
 #define TEMPLATE_CALL template
 #endif
 
+// MSVC accepts but ignores the standard '[[no_unique_address]]' for the ABI compatibility, and
+// provides its own attribute instead.
+#if defined(_MSC_VER)
+#define SC_NO_UNIQUE_ADDRESS [[msvc::no_unique_address]]
+#else
+#define SC_NO_UNIQUE_ADDRESS [[no_unique_address]]
+#endif
+
 #endif //SYNC_CELL_COMPILER_WORKAROUND_HPP
@@ -0,0 +1,129 @@
+///
+/// @file  config.hpp
+/// @brief The build configuration of the project.
+///
+/// Define @c SYNC_CELL_NO_STD (e.g. @c -DSYNC_CELL_NO_STD) to build without the OS-dependent
+/// facilities of the standard library: the threads, the mutexes, the condition variables and the
+/// clocks. Only the atomics, the memory allocation and the other freestanding-friendly headers are
+/// used then, so the core cells, the spin locks and the non-blocking queues can be used in the
+/// embedded or kernel contexts. The headers requiring the OS facilities (the blocking adapters,
+/// the synchronization primitives and the memory reclamation) report an error if included.
+///
+/// Define @c SYNC_CELL_SINGLE_THREADED for the targets without threads, which is the default on the
+/// WebAssembly without the atomics feature (e.g. @c wasm32-unknown-unknown). It implies the
+/// @c SYNC_CELL_NO_STD mode, and a blocking wait, which could only be ended by another thread, calls
+/// the handler of "shared/single_thread.hpp" instead of spinning forever. Use the @c try_ variants
+/// of the operations on such a target.
+///
+/// Define @c SYNC_CELL_ATOMIC_FALLBACK for the targets without the native atomic operations on the
+/// wide types (e.g. no 64-bit atomics, or even no CAS). The @c SyncCell then guards the value by its
+/// own lock if the @c std::atomic<T> is not always lock-free, instead of relying on the @c libatomic
+/// of the toolchain which may be missing. Define @c SYNC_CELL_CRITICAL_SECTION additionally if the
+/// target has no CAS for the spin lock either: the lock is a critical section provided by the user
+/// (see "shared/critical_section.hpp"). The other containers still use the @c std::atomic of the
+/// @c size_t and the pointers.
+///
+/// Define @c SYNC_CELL_METRICS to let the cells, locks and queues count their contention events (the
+/// CAS failures, the spin iterations, the park events and the failed steals), which are read by
+/// their @c stats() method (see "shared/metrics.hpp"). The counters are relaxed atomics updated on
+/// the contended paths, and take no space and no time if the macro is not defined.
+///
+/// Define @c SYNC_CELL_TRACING to emit the tracing spans around the contended lock acquisitions,
+/// the parked consumers of the blocking queues and the once-cell initializations. The spans are
+/// reported to the hooks defined by the user (see "shared/tracing.hpp").
+///
+/// Define @c SYNC_CELL_DEADLOCK_DETECTION in the debug builds to track the locks of @c sc::lock held
+/// by each thread, and report the self-deadlocks and the lock order inversions with the cycle of the
+/// locks (see "shared/deadlock.hpp"). The tracking takes a global mutex on each blocking acquisition,
+/// so it is not meant for the release builds. It requires the std threads.
+///
+/// Define @c SYNC_CELL_SERDE to serialize the @c SyncCell, the @c OnceSyncCell and the value locks
+/// of @c sc::lock as their inner value, through the ADL hooks of the cereal-like archives and the
+/// nlohmann::json (see "shared/serde.hpp"). The library itself is not included by the project.
+///
+/// Define @c SYNC_CELL_NUMA on Linux to honor the NUMA node hints of the queue rings and the worker
+/// deques, whose memory is then bound to the node by the @c mbind system call (see
+/// "shared/numa.hpp"). No @c libnuma is linked. Without the macro, or on the other platforms, the
+/// hints are ignored, so the code passing them stays portable.
+///
+/// Define @c SYNC_CELL_SHM on Linux or macOS to use the @c sc::shm::ShmCell and
+/// @c sc::shm::ShmQueue, which are placed in a memory mapped by several processes, and park their
+/// waiters on the shared futex words (see "shared/shm.hpp"). The POSIX shared memory may require
+/// linking @c -lrt on the older glibc.
+///
+/// Define @c SYNC_CELL_AFFINITY on Linux to pin the workers of the thread pool to the cores by the
+/// @c sched_setaffinity system call (see "shared/affinity.hpp"), as selected by the
+/// @c sc::pool::Builder::pin_workers. No @c hwloc is linked, the cores of a NUMA node are read from
+/// the sysfs. Without the macro, or on the other platforms, the workers are not pinned.
+///
+/// The blocking waits of the @c Event, the @c Semaphore and the @c BlockingQueue park the thread on
+/// the futex of Linux, the @c WaitOnAddress of Windows (linking "Synchronization.lib") or the
+/// @c __ulock_wait of macOS, see "shared/futex.hpp". Define @c SYNC_CELL_NO_FUTEX to park them on a
+/// process-wide table of the mutexes and the condition variables instead, which is also the
+/// fallback on the other platforms.
+///
+
+#ifndef SYNC_CELL_CONFIG_HPP
+#define SYNC_CELL_CONFIG_HPP
+
+#if defined(SYNC_CELL_SINGLE_THREADED) || (defined(__wasm__) && !defined(__wasm_atomics__))
+#define SC_HAS_THREADS 0
+#else
+#define SC_HAS_THREADS 1
+#endif
+
+#if defined(SYNC_CELL_NO_STD) || !SC_HAS_THREADS
+#define SC_HAS_STD 0
+#else
+#define SC_HAS_STD 1
+#endif
+
+#if defined(SYNC_CELL_METRICS)
+#define SC_HAS_METRICS 1
+#else
+#define SC_HAS_METRICS 0
+#endif
+
+#if defined(SYNC_CELL_TRACING)
+#define SC_HAS_TRACING 1
+#else
+#define SC_HAS_TRACING 0
+#endif
+
+#if defined(SYNC_CELL_DEADLOCK_DETECTION)
+#define SC_HAS_DEADLOCK_DETECTION 1
+#else
+#define SC_HAS_DEADLOCK_DETECTION 0
+#endif
+
+#if defined(SYNC_CELL_SERDE)
+#define SC_HAS_SERDE 1
+#else
+#define SC_HAS_SERDE 0
+#endif
+
+#if defined(SYNC_CELL_NUMA) && defined(__linux__)
+#define SC_HAS_NUMA 1
+#else
+#define SC_HAS_NUMA 0
+#endif
+
+#if defined(SYNC_CELL_SHM) && (defined(__linux__) || defined(__APPLE__))
+#define SC_HAS_SHM 1
+#else
+#define SC_HAS_SHM 0
+#endif
+
+#if defined(SYNC_CELL_AFFINITY) && defined(__linux__)
+#define SC_HAS_AFFINITY 1
+#else
+#define SC_HAS_AFFINITY 0
+#endif
+
+#if !defined(SYNC_CELL_NO_FUTEX) && (defined(__linux__) || defined(_WIN32) || defined(__APPLE__))
+#define SC_HAS_FUTEX 1
+#else
+#define SC_HAS_FUTEX 0
+#endif
+
+#endif //SYNC_CELL_CONFIG_HPP
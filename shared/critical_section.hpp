@@ -0,0 +1,30 @@
+///
+/// @file  critical_section.hpp
+/// @brief The user-provided critical section, used as the lock of the atomic fallback on the
+/// targets without the native atomic operations. Only used if @c SYNC_CELL_CRITICAL_SECTION is
+/// defined, see "shared/config.hpp".
+///
+
+#ifndef SYNC_CELL_CRITICAL_SECTION_HPP
+#define SYNC_CELL_CRITICAL_SECTION_HPP
+
+#include <cstdint>
+
+
+namespace sc::critical_section {
+
+/// @brief The state saved by @c acquire and restored by @c release, e.g. the previous interrupt
+/// mask, so the critical sections can be nested.
+using RestoreState = std::uintptr_t;
+
+/// @brief Enters the critical section, e.g. disables the interrupts on a single-core MCU.
+/// @note Declared only, the user must define it in one translation unit.
+RestoreState acquire() noexcept;
+
+/// @brief Leaves the critical section entered by the paired @c acquire.
+/// @note Declared only, the user must define it in one translation unit.
+void release(RestoreState state) noexcept;
+
+}
+
+#endif //SYNC_CELL_CRITICAL_SECTION_HPP
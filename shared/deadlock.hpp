@@ -0,0 +1,196 @@
+///
+/// @file  deadlock.hpp
+/// @brief The lock ownership tracking of the locks in @c sc::lock, enabled by
+/// @c SYNC_CELL_DEADLOCK_DETECTION for the debug builds.
+///
+
+#ifndef SYNC_CELL_DEADLOCK_HPP
+#define SYNC_CELL_DEADLOCK_HPP
+
+#include "shared/config.hpp"
+
+#if SC_HAS_DEADLOCK_DETECTION
+
+#if !SC_HAS_STD
+#error "The SYNC_CELL_DEADLOCK_DETECTION requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <algorithm>
+#include <cstdio>
+#include <cstdlib>
+#include <mutex>
+#include <string>
+#include <unordered_map>
+#include <utility>
+#include <vector>
+
+#endif
+
+
+namespace sc::deadlock {
+
+#if SC_HAS_DEADLOCK_DETECTION
+
+/// @brief Called with the report of a detected deadlock. The default handler prints the report to
+/// the stderr and aborts. If a handler returns, the acquisition continues.
+using Handler = void (*)(const char *report);
+
+namespace impl {
+
+inline void default_handler(const char *report)
+{
+    std::fputs(report, stderr);
+    std::fputc('\n', stderr);
+    std::abort();
+}
+
+/// @brief The lock order graph of all threads: an edge 'a -> b' is recorded when a thread blocks
+/// to acquire 'b' while holding 'a'.
+struct Registry
+{
+    std::mutex mtx;
+    std::unordered_map<const void *, std::vector<const void *>> edges;
+    std::unordered_map<const void *, const char *> names;
+    Handler handler = default_handler;
+
+    static Registry &instance()
+    {
+        static Registry registry;
+        return registry;
+    }
+
+    /// @brief Finds a path from 'from' to 'to' by the DFS. Must be called with 'mtx' locked.
+    bool find_path(const void *from, const void *to, std::vector<const void *> &path)
+    {
+        path.push_back(from);
+        if (from == to) {
+            return true;
+        }
+        if (auto it = edges.find(from); it != edges.end()) {
+            for (auto *next: it->second) {
+                if (std::find(path.begin(), path.end(), next) == path.end() && find_path(next, to, path)) {
+                    return true;
+                }
+            }
+        }
+        path.pop_back();
+        return false;
+    }
+
+    std::string describe(const void *lock)
+    {
+        char address[32];
+        std::snprintf(address, sizeof(address), "%p", lock);
+        auto it = names.find(lock);
+        return std::string(it != names.end() ? it->second : "lock") + " (" + address + ")";
+    }
+};
+
+struct HeldLock
+{
+    const void *lock;
+    const char *name;
+};
+
+/// @brief The locks held by the current thread, in the acquisition order.
+inline std::vector<HeldLock> &held_locks()
+{
+    thread_local std::vector<HeldLock> held;
+    return held;
+}
+
+}
+
+/// @brief Replaces the handler of the reports.
+/// @return The previous handler.
+inline Handler set_handler(Handler handler) noexcept
+{
+    auto &registry = impl::Registry::instance();
+    std::lock_guard guard(registry.mtx);
+    return std::exchange(registry.handler, handler);
+}
+
+/// @brief Called before a thread blocks to acquire the 'lock'. Reports the self-deadlock if the
+/// thread already holds it, or the lock order inversion if another thread has acquired the locks
+/// held by this thread while holding the 'lock' (directly or through other locks).
+inline void before_lock(const void *lock, const char *name) noexcept
+{
+    auto &held = impl::held_locks();
+    auto &registry = impl::Registry::instance();
+    std::string report;
+    Handler handler;
+    {
+        std::lock_guard guard(registry.mtx);
+        registry.names[lock] = name;
+        handler = registry.handler;
+        for (auto &h: held) {
+            if (h.lock == lock) {
+                report = "sc::deadlock: self-deadlock, the thread acquires " + registry.describe(lock) +
+                         " which it already holds.";
+                break;
+            }
+            auto &out = registry.edges[h.lock];
+            if (std::find(out.begin(), out.end(), lock) != out.end()) {
+                continue;
+            }
+            std::vector<const void *> path;
+            if (registry.find_path(lock, h.lock, path)) {
+                report = "sc::deadlock: lock order inversion, the cycle is ";
+                for (auto *p: path) {
+                    report += registry.describe(p) + " -> ";
+                }
+                report += registry.describe(lock) + ".";
+                break;
+            }
+            out.push_back(lock);
+        }
+    }
+    if (!report.empty()) {
+        handler(report.c_str());
+    }
+}
+
+/// @brief Called after the current thread has acquired the 'lock'.
+inline void on_locked(const void *lock, const char *name) noexcept
+{
+    impl::held_locks().push_back(impl::HeldLock{lock, name});
+}
+
+/// @brief Called before the current thread releases the 'lock'.
+inline void on_unlocked(const void *lock) noexcept
+{
+    auto &held = impl::held_locks();
+    // Usually the last one, a guard moved to another thread is not found.
+    for (auto it = held.rbegin(); it != held.rend(); ++it) {
+        if (it->lock == lock) {
+            held.erase(std::next(it).base());
+            return;
+        }
+    }
+}
+
+/// @brief Called when the 'lock' is destroyed, so its address can be reused by another lock.
+inline void forget(const void *lock) noexcept
+{
+    auto &registry = impl::Registry::instance();
+    std::lock_guard guard(registry.mtx);
+    registry.edges.erase(lock);
+    registry.names.erase(lock);
+    for (auto &[from, out]: registry.edges) {
+        out.erase(std::remove(out.begin(), out.end(), lock), out.end());
+    }
+}
+
+#else
+
+inline void before_lock(const void *, const char *) noexcept { }
+
+inline void on_locked(const void *, const char *) noexcept { }
+
+inline void on_unlocked(const void *) noexcept { }
+
+#endif
+
+}
+
+#endif //SYNC_CELL_DEADLOCK_HPP
@@ -0,0 +1,140 @@
+///
+/// @file  drain.hpp
+/// @brief The input range returned by the @c drain of the queues and the stack, which takes the
+/// items in chunks.
+///
+
+#ifndef SYNC_CELL_DRAIN_HPP
+#define SYNC_CELL_DRAIN_HPP
+
+#include <algorithm>
+#include <cstddef>
+#include <iterator>
+#include <limits>
+#include <memory>
+#include <utility>
+#include <vector>
+
+
+namespace sc {
+
+/// @brief The default count of the items taken by one chunk of the @c Drain.
+inline constexpr size_t DefaultDrainChunk = 64;
+
+/// @brief An input range which takes the ownership of the items of a container in bulk.
+///
+/// Each chunk is taken from the container by one synchronization (e.g. one lock of the head), then
+/// the items are yielded from a local buffer. The range ends when the container returns an empty
+/// chunk, or after the count of the items which were in the container when the drain was created,
+/// so the concurrent producers can not keep a shutdown flushing running forever:
+/// ``` cpp
+/// for (auto &task : queue.drain()) { run(task); }
+/// ```
+/// @note The iterator refers to the drain, so the drain must not be moved after @c begin is called.
+/// If the iteration stops early, the rest items of the current chunk are destroyed with the drain.
+/// @tparam T The value type.
+/// @tparam Take The callable of <tt>size_t(std::vector<T> &out, size_t max)</tt>, which appends at
+/// most 'max' items to 'out' and returns the count of them.
+template<typename T, typename Take>
+class Drain
+{
+public:
+    using value_type = T;
+
+    class iterator
+    {
+        friend class Drain;
+
+        explicit iterator(Drain *drain) noexcept : drain_(drain) { }
+
+    public:
+        using iterator_concept = std::input_iterator_tag;
+        using difference_type = std::ptrdiff_t;
+        using value_type = Drain::value_type;
+
+        iterator(iterator &&) noexcept = default;
+
+        iterator &operator=(iterator &&) noexcept = default;
+
+        value_type &operator*() const noexcept
+        {
+            return drain_->buffer_[drain_->pos_];
+        }
+
+        value_type *operator->() const noexcept
+        {
+            return std::addressof(**this);
+        }
+
+        iterator &operator++()
+        {
+            drain_->next();
+            return *this;
+        }
+
+        void operator++(int)
+        {
+            ++*this;
+        }
+
+        friend bool operator==(const iterator &it, std::default_sentinel_t) noexcept
+        {
+            return it.at_end();
+        }
+
+    private:
+        [[nodiscard]] bool at_end() const noexcept
+        {
+            return drain_->pos_ >= drain_->buffer_.size();
+        }
+
+        Drain *drain_;
+    };
+
+    /// @param take Takes a chunk from the container.
+    /// @param chunk The max count of the items taken by one chunk, it must be greater than 0.
+    /// @param limit The max count of the items taken by the whole drain.
+    Drain(Take take, size_t chunk, size_t limit = std::numeric_limits<size_t>::max())
+            : take_(std::move(take)), chunk_(chunk), limit_(limit)
+    {
+    }
+
+    /// @brief Takes the first chunk. Must be called only once.
+    iterator begin()
+    {
+        fill();
+        return iterator(this);
+    }
+
+    [[nodiscard]] std::default_sentinel_t end() const noexcept
+    {
+        return std::default_sentinel;
+    }
+
+private:
+    void next()
+    {
+        if (++pos_ == buffer_.size()) {
+            fill();
+        }
+    }
+
+    void fill()
+    {
+        buffer_.clear();
+        pos_ = 0;
+        if (limit_ > 0) {
+            limit_ -= take_(buffer_, std::min(chunk_, limit_));
+        }
+    }
+
+    Take take_;
+    size_t chunk_;
+    size_t limit_;
+    std::vector<value_type> buffer_;
+    size_t pos_ = 0;
+};
+
+}
+
+#endif //SYNC_CELL_DRAIN_HPP
@@ -0,0 +1,55 @@
+///
+/// @file  extend.hpp
+/// @brief The helper of the @c extend of the queues, the stack and the producer handles, and
+/// @c collect which builds a container from a range.
+///
+
+#ifndef SYNC_CELL_EXTEND_HPP
+#define SYNC_CELL_EXTEND_HPP
+
+#include <cstddef>
+#include <iterator>
+#include <type_traits>
+#include <utility>
+
+
+namespace sc {
+
+namespace impl {
+
+/// @brief Calls 'push' with each item of [first, last) in order. If 'push' returns a bool, stops
+/// at the first item it rejects.
+/// @return The count of the pushed items.
+template<typename It, typename Sentinel, typename Push>
+size_t extend(It first, Sentinel last, Push &&push)
+{
+    size_t count = 0;
+    for (; first != last; ++first, ++count) {
+        if constexpr(std::is_same_v<decltype(push(*first)), bool>) {
+            if (!push(*first)) {
+                break;
+            }
+        } else {
+            push(*first);
+        }
+    }
+    return count;
+}
+
+}
+
+/// @brief Builds a container by its iterator constructor, with the items of the 'range'.
+///
+/// ``` cpp
+/// auto worklist = sc::collect<sc::mpmc::LinkedListQueue<Job>>(jobs | std::views::filter(is_ready));
+/// ```
+/// @tparam Container The queue or the stack type, the items are enqueued in the order of the range.
+template<typename Container, typename Range>
+Container collect(Range &&range)
+{
+    return Container(std::begin(range), std::end(range));
+}
+
+}
+
+#endif //SYNC_CELL_EXTEND_HPP
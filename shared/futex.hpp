@@ -0,0 +1,172 @@
+///
+/// @file  futex.hpp
+/// @brief Parks the threads on the address of a 32-bit atomic word, by the futex-like system call of
+/// the platform.
+///
+
+#ifndef SYNC_CELL_FUTEX_HPP
+#define SYNC_CELL_FUTEX_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_FUTEX && !SC_HAS_STD
+#error "The futex.hpp requires the std threads on this platform, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <algorithm>
+#include <atomic>
+#include <chrono>
+#include <climits>
+#include <cstddef>
+#include <cstdint>
+
+#if !SC_HAS_FUTEX
+#include <condition_variable>
+#include <mutex>
+#elif defined(__linux__)
+#include <linux/futex.h>
+#include <sys/syscall.h>
+#include <time.h>
+#include <unistd.h>
+#elif defined(_WIN32)
+#pragma comment(lib, "Synchronization.lib")
+#endif
+
+
+#if SC_HAS_FUTEX && defined(_WIN32)
+// Declared here rather than including the <windows.h>, whose macros leak into the user code.
+extern "C" __declspec(dllimport) int __stdcall WaitOnAddress(volatile void *, void *, size_t, unsigned long);
+extern "C" __declspec(dllimport) void __stdcall WakeByAddressSingle(void *);
+extern "C" __declspec(dllimport) void __stdcall WakeByAddressAll(void *);
+#elif SC_HAS_FUTEX && defined(__APPLE__)
+// The private but stable interface under the std::atomic::wait of libc++.
+extern "C" int __ulock_wait(uint32_t operation, void *addr, uint64_t value, uint32_t timeout_us);
+extern "C" int __ulock_wake(uint32_t operation, void *addr, uint64_t wake_value);
+#endif
+
+namespace sc::impl {
+
+static_assert(sizeof(std::atomic<uint32_t>) == sizeof(uint32_t) && std::atomic<uint32_t>::is_always_lock_free);
+
+#if !SC_HAS_FUTEX
+/// @brief The count of the buckets of the parking table.
+constexpr size_t ParkBucketCount = 64;
+
+/// @brief A bucket of the parking table, shared by the addresses hashed to it.
+struct ParkBucket
+{
+    std::mutex mtx;
+    std::condition_variable cond_var;
+};
+
+inline ParkBucket &park_bucket(const void *addr) noexcept
+{
+    static ParkBucket buckets[ParkBucketCount];
+    auto hash = reinterpret_cast<uintptr_t>(addr);
+    return buckets[(hash >> 4 ^ hash >> 10) % ParkBucketCount];
+}
+#endif
+
+/// @brief Parks the thread while the 'word' equals the 'expected', at most 'ns' nanoseconds, or
+/// without limit if 'ns' is negative. It may return spuriously.
+inline void futex_wait_ns(const std::atomic<uint32_t> &word, uint32_t expected, int64_t ns) noexcept
+{
+    auto *addr = const_cast<std::atomic<uint32_t> *>(&word);
+#if !SC_HAS_FUTEX
+    auto &bucket = park_bucket(addr);
+    std::unique_lock lock(bucket.mtx);
+    // The waker changes the word before taking the lock of the bucket, so the change is not missed.
+    if (word.load(std::memory_order_seq_cst) != expected) {
+        return;
+    }
+    if (ns < 0) {
+        bucket.cond_var.wait(lock);
+    } else {
+        bucket.cond_var.wait_for(lock, std::chrono::nanoseconds(ns));
+    }
+#elif defined(__linux__)
+    timespec ts{static_cast<time_t>(ns / 1000000000), static_cast<long>(ns % 1000000000)};
+    // The timeout of FUTEX_WAIT is relative, measured by the CLOCK_MONOTONIC.
+    ::syscall(SYS_futex, addr, FUTEX_WAIT_PRIVATE, expected, ns < 0 ? nullptr : &ts, nullptr, 0);
+#elif defined(_WIN32)
+    // INFINITE is 0xffffffff.
+    auto ms = ns < 0 ? 0xffffffffUL
+                     : static_cast<unsigned long>(std::min<int64_t>((ns + 999999) / 1000000, 0xfffffffe));
+    ::WaitOnAddress(addr, &expected, sizeof(expected), ms);
+#elif defined(__APPLE__)
+    // UL_COMPARE_AND_WAIT with ULF_NO_ERRNO, a zero timeout waits without limit.
+    auto us = ns < 0 ? 0u : static_cast<uint32_t>(std::clamp<int64_t>((ns + 999) / 1000, 1, UINT32_MAX));
+    ::__ulock_wait(0x01000001, addr, expected, us);
+#endif
+}
+
+/// @brief Parks the thread while the 'word' equals the 'expected'. It may return spuriously, so the
+/// caller checks its condition in a loop.
+inline void futex_wait(const std::atomic<uint32_t> &word, uint32_t expected) noexcept
+{
+    futex_wait_ns(word, expected, -1);
+}
+
+/// @brief Parks the thread while the 'word' equals the 'expected', until the 'deadline'.
+/// @return false if the deadline has been reached, the caller checks its condition once more then.
+template<typename Clock, typename Duration>
+bool futex_wait_until(const std::atomic<uint32_t> &word, uint32_t expected,
+                      const std::chrono::time_point<Clock, Duration> &deadline)
+{
+    auto now = Clock::now();
+    if (now >= deadline) {
+        return false;
+    }
+
+    // A longer wait is split into several ones by the loop of the caller, which also prevents the
+    // overflow of a far deadline.
+    constexpr auto MaxWait = std::chrono::hours(1);
+    auto remaining = deadline - now;
+    auto ns = remaining > MaxWait ? std::chrono::nanoseconds(MaxWait)
+                                  : std::chrono::ceil<std::chrono::nanoseconds>(remaining);
+    futex_wait_ns(word, expected, ns.count());
+    return Clock::now() < deadline;
+}
+
+/// @brief Wakes up one thread parked on the 'word', which has been changed by the caller.
+///
+/// The 'word' is not accessed, so it is fine that it has been destroyed by a waiter which saw the
+/// change and returned.
+inline void futex_wake_one(const std::atomic<uint32_t> &word) noexcept
+{
+    auto *addr = const_cast<std::atomic<uint32_t> *>(&word);
+#if !SC_HAS_FUTEX
+    // The bucket is shared by the other addresses, so all threads in it check their words again.
+    auto &bucket = park_bucket(addr);
+    { std::lock_guard guard(bucket.mtx); }
+    bucket.cond_var.notify_all();
+#elif defined(__linux__)
+    ::syscall(SYS_futex, addr, FUTEX_WAKE_PRIVATE, 1, nullptr, nullptr, 0);
+#elif defined(_WIN32)
+    ::WakeByAddressSingle(addr);
+#elif defined(__APPLE__)
+    ::__ulock_wake(0x01000001, addr, 0);
+#endif
+}
+
+/// @brief Wakes up all threads parked on the 'word', which has been changed by the caller.
+inline void futex_wake_all(const std::atomic<uint32_t> &word) noexcept
+{
+    auto *addr = const_cast<std::atomic<uint32_t> *>(&word);
+#if !SC_HAS_FUTEX
+    auto &bucket = park_bucket(addr);
+    { std::lock_guard guard(bucket.mtx); }
+    bucket.cond_var.notify_all();
+#elif defined(__linux__)
+    ::syscall(SYS_futex, addr, FUTEX_WAKE_PRIVATE, INT_MAX, nullptr, nullptr, 0);
+#elif defined(_WIN32)
+    ::WakeByAddressAll(addr);
+#elif defined(__APPLE__)
+    // ULF_WAKE_ALL
+    ::__ulock_wake(0x01000101, addr, 0);
+#endif
+}
+
+}
+
+#endif //SYNC_CELL_FUTEX_HPP
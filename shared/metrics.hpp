@@ -0,0 +1,108 @@
+///
+/// @file  metrics.hpp
+/// @brief The contention counters of the primitives, enabled by @c SYNC_CELL_METRICS.
+///
+
+#ifndef SYNC_CELL_METRICS_HPP
+#define SYNC_CELL_METRICS_HPP
+
+#include <cstdint>
+
+#include "shared/compiler_workaround.hpp"
+#include "shared/config.hpp"
+
+#if SC_HAS_METRICS
+#include <atomic>
+#endif
+
+
+namespace sc::metrics {
+
+/// @brief A snapshot of the contention counters of a primitive. All counters are zero if
+/// @c SYNC_CELL_METRICS is not defined.
+struct Stats
+{
+    /// @brief Count of the CAS operations which failed because of another thread, and are retried.
+    uint64_t cas_failures = 0;
+    /// @brief Count of the backoff steps while waiting for another thread.
+    uint64_t spin_iterations = 0;
+    /// @brief Count of the times a thread is parked by the OS (condition variable, atomic wait).
+    uint64_t park_events = 0;
+    /// @brief Count of the steals which failed because of another stealer or the owner.
+    uint64_t failed_steals = 0;
+
+    bool operator==(const Stats &) const = default;
+
+    Stats &operator+=(const Stats &other) noexcept
+    {
+        cas_failures += other.cas_failures;
+        spin_iterations += other.spin_iterations;
+        park_events += other.park_events;
+        failed_steals += other.failed_steals;
+        return *this;
+    }
+};
+
+/// @brief The contention counters held by a primitive, which is an empty class if
+/// @c SYNC_CELL_METRICS is not defined (declare the member with @c SC_NO_UNIQUE_ADDRESS).
+///
+/// The counters are updated with the relaxed ordering, so a snapshot taken during the operations
+/// may be inconsistent between the counters.
+class Counters
+{
+public:
+    void cas_failure() const noexcept
+    {
+#if SC_HAS_METRICS
+        cas_failures_.fetch_add(1, std::memory_order_relaxed);
+#endif
+    }
+
+    void spin() const noexcept
+    {
+#if SC_HAS_METRICS
+        spin_iterations_.fetch_add(1, std::memory_order_relaxed);
+#endif
+    }
+
+    void park() const noexcept
+    {
+#if SC_HAS_METRICS
+        park_events_.fetch_add(1, std::memory_order_relaxed);
+#endif
+    }
+
+    void failed_steal() const noexcept
+    {
+#if SC_HAS_METRICS
+        failed_steals_.fetch_add(1, std::memory_order_relaxed);
+#endif
+    }
+
+    [[nodiscard]] Stats snapshot() const noexcept
+    {
+#if SC_HAS_METRICS
+        return Stats{
+                cas_failures_.load(std::memory_order_relaxed),
+                spin_iterations_.load(std::memory_order_relaxed),
+                park_events_.load(std::memory_order_relaxed),
+                failed_steals_.load(std::memory_order_relaxed),
+        };
+#else
+        return {};
+#endif
+    }
+
+private:
+#if SC_HAS_METRICS
+    // Mutable: the const operations (e.g. a load waiting for the lock) are counted as well.
+    mutable std::atomic<uint64_t> cas_failures_{0};
+    mutable std::atomic<uint64_t> spin_iterations_{0};
+    mutable std::atomic<uint64_t> park_events_{0};
+    mutable std::atomic<uint64_t> failed_steals_{0};
+#endif
+};
+
+}
+
+#endif //SYNC_CELL_METRICS_HPP
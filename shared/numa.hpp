@@ -0,0 +1,173 @@
+///
+/// @file  numa.hpp
+/// @brief The NUMA node hints of the memory of the queue rings and the worker deques.
+///
+
+#ifndef SYNC_CELL_NUMA_HPP
+#define SYNC_CELL_NUMA_HPP
+
+#include "shared/config.hpp"
+
+#include <cstddef>
+#include <limits>
+#include <new>
+
+#if SC_HAS_NUMA
+#include <sys/mman.h>
+#include <sys/syscall.h>
+#include <unistd.h>
+#endif
+
+
+namespace sc::numa {
+
+/// @brief The node hint of no preference: the memory is placed by the default policy of the system.
+inline constexpr int AnyNode = -1;
+
+namespace impl {
+
+#if SC_HAS_NUMA
+/// @brief The @c MPOL_PREFERRED of the kernel: the pages are taken from the other nodes if the
+/// preferred one is out of memory, instead of failing the allocation.
+constexpr int MpolPreferred = 1;
+
+/// @brief The count of the nodes in the node mask passed to the kernel.
+constexpr size_t MaxNodes = 1024;
+
+inline size_t page_size() noexcept
+{
+    static const auto size = static_cast<size_t>(::sysconf(_SC_PAGESIZE));
+    return size;
+}
+
+/// @brief Returns true if the block is mapped and bound to the node, rather than taken from the heap.
+inline bool is_mapped(size_t align, int node) noexcept
+{
+    return node >= 0 && static_cast<size_t>(node) < MaxNodes && align <= page_size();
+}
+
+inline size_t mapped_len(size_t bytes) noexcept
+{
+    auto page = page_size();
+    return bytes == 0 ? page : (bytes + page - 1) / page * page;
+}
+#endif
+
+}
+
+/// @brief Returns the node of the CPU running the current thread, or @c AnyNode if it is unknown.
+///
+/// The thread may be moved to another node right after the call, unless it is pinned to the CPUs
+/// of one node.
+[[nodiscard]] inline int current_node() noexcept
+{
+#if SC_HAS_NUMA && defined(SYS_getcpu)
+    unsigned cpu = 0;
+    unsigned node = 0;
+    if (::syscall(SYS_getcpu, &cpu, &node, nullptr) == 0) {
+        return static_cast<int>(node);
+    }
+#endif
+    return AnyNode;
+}
+
+/// @brief Allocates 'bytes' of memory aligned to 'align', whose pages are preferably placed on the
+/// 'node'.
+///
+/// With @c SC_HAS_NUMA and a valid node, the memory is mapped by pages and bound to the node by
+/// @c mbind, so it suits the large blocks such as the rings, rather than the small nodes. A failed
+/// binding (e.g. a node not present on the machine) leaves the memory to the default policy.
+/// Otherwise the memory is allocated by the @c operator @c new.
+/// @throw std::bad_alloc If the memory can not be allocated.
+[[nodiscard]] inline void *allocate(size_t bytes, size_t align, int node)
+{
+#if SC_HAS_NUMA
+    if (impl::is_mapped(align, node)) {
+        auto len = impl::mapped_len(bytes);
+        auto *p = ::mmap(nullptr, len, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+        if (p == MAP_FAILED) {
+            throw std::bad_alloc();
+        }
+#ifdef SYS_mbind
+        constexpr size_t Bits = std::numeric_limits<unsigned long>::digits;
+        unsigned long mask[impl::MaxNodes / Bits] = {};
+        mask[node / Bits] = 1ul << (node % Bits);
+        // The kernel takes one more than the count of the nodes in the mask.
+        ::syscall(SYS_mbind, p, len, impl::MpolPreferred, mask, impl::MaxNodes + 1, 0u);
+#endif
+        return p;
+    }
+#else
+    (void) node;
+#endif
+    return ::operator new(bytes, std::align_val_t(align));
+}
+
+/// @brief Releases the memory from @c allocate with the same arguments.
+inline void deallocate(void *p, size_t bytes, size_t align, int node) noexcept
+{
+#if SC_HAS_NUMA
+    if (impl::is_mapped(align, node)) {
+        ::munmap(p, impl::mapped_len(bytes));
+        return;
+    }
+#else
+    (void) node;
+#endif
+    ::operator delete(p, bytes, std::align_val_t(align));
+}
+
+/// @brief A stateful allocator placing the memory on a NUMA node, e.g. for the rings of the queues:
+/// ``` cpp
+/// // For the threads running on the node 1.
+/// using Alloc = sc::numa::NodeAllocator<Job>;
+/// sc::mpmc::BoundedQueue<Job, sc::util::Backoff, 0, Alloc> jobs(4096, sc::mpmc::Overflow::Block, {}, Alloc(1));
+/// sc::spsc::RingBuffer<Job, sc::util::Backoff, Alloc> events(4096, Alloc(1));
+/// ```
+///
+/// Each allocation takes whole pages with @c SC_HAS_NUMA, so it is not meant for the containers
+/// allocating one node per element, e.g. the linked list queues.
+/// @tparam T The value type.
+template<typename T>
+class NodeAllocator
+{
+public:
+    using value_type = T;
+
+    /// @param node The node index, or @c AnyNode for the default placement.
+    constexpr explicit NodeAllocator(int node = AnyNode) noexcept : node_(node) { }
+
+    template<typename U>
+    constexpr NodeAllocator(const NodeAllocator<U> &other) noexcept : node_(other.node()) { }
+
+    [[nodiscard]] constexpr int node() const noexcept
+    {
+        return node_;
+    }
+
+    [[nodiscard]] T *allocate(size_t n)
+    {
+        if (n > std::numeric_limits<size_t>::max() / sizeof(T)) {
+            throw std::bad_array_new_length();
+        }
+        return static_cast<T *>(numa::allocate(n * sizeof(T), alignof(T), node_));
+    }
+
+    void deallocate(T *p, size_t n) noexcept
+    {
+        numa::deallocate(p, n * sizeof(T), alignof(T), node_);
+    }
+
+    template<typename U>
+    constexpr bool operator==(const NodeAllocator<U> &other) const noexcept
+    {
+        return node_ == other.node();
+    }
+
+private:
+    int node_;
+};
+
+}
+
+#endif //SYNC_CELL_NUMA_HPP
@@ -14,19 +14,26 @@
 
 namespace sc {
 
-template<typename T, uint32_t N>
+/// @tparam T The object type.
+/// @tparam N The count of the cached objects.
+/// @tparam Allocator The allocator of the objects, rebound to 'T'. So the containers can pass the
+/// allocator of their value type to the pool of their nodes.
+template<typename T, uint32_t N, typename Allocator = std::allocator<T>>
 class ObjectCachePool
 {
     static_assert(!std::is_void_v<T> && !std::is_reference_v<T>);
 
-    using Alloc = std::allocator<T>;    // using a template param?
+    using Alloc = typename std::allocator_traits<Allocator>::template rebind_alloc<T>;
     using AllocTrait = std::allocator_traits<Alloc>;
 
 public:
     using pointer_type = T *;
+    using allocator_type = Alloc;
 
     constexpr ObjectCachePool() noexcept = default;
 
+    explicit ObjectCachePool(const Allocator &alloc) noexcept : allocator_(alloc) { }
+
     ~ObjectCachePool()
     {
         for (uint32_t i = 0; i < N; ++i) {
@@ -41,6 +48,11 @@ public:
         }
     }
 
+    [[nodiscard]] allocator_type get_allocator() const noexcept
+    {
+        return allocator_;
+    }
+
     template<typename... Args>
     pointer_type alloc(Args &&... args)
     {
@@ -89,17 +101,25 @@ private:
 };
 
 // Specialization for cache size == 0.
-template<typename T>
-class ObjectCachePool<T, 0>
+template<typename T, typename Allocator>
+class ObjectCachePool<T, 0, Allocator>
 {
-    using Alloc = std::allocator<T>;    // using a template param?
+    using Alloc = typename std::allocator_traits<Allocator>::template rebind_alloc<T>;
     using AllocTrait = std::allocator_traits<Alloc>;
 
 public:
     using pointer_type = T *;
+    using allocator_type = Alloc;
 
     constexpr ObjectCachePool() noexcept = default;
 
+    explicit ObjectCachePool(const Allocator &alloc) noexcept : allocator_(alloc) { }
+
+    [[nodiscard]] allocator_type get_allocator() const noexcept
+    {
+        return allocator_;
+    }
+
     template<typename... Args>
     pointer_type alloc(Args &&... args)
     {
@@ -0,0 +1,59 @@
+///
+/// @file  queue_len.hpp
+/// @brief The capacity constant of the unbounded queues, and the counter of the queues which can
+/// not compute their length from the head and tail indices.
+///
+
+#ifndef SYNC_CELL_QUEUE_LEN_HPP
+#define SYNC_CELL_QUEUE_LEN_HPP
+
+#include <atomic>
+#include <cstddef>
+#include <limits>
+
+#include "util/cache_padded.hpp"
+
+
+namespace sc {
+
+/// @brief The @c capacity() and @c remaining() of the unbounded queues.
+inline constexpr size_t UnboundedCapacity = std::numeric_limits<size_t>::max();
+
+namespace impl {
+
+/// @brief Counts the pushed and popped items of a linked queue. The two counters are in their own
+/// cache lines, so the producers and the consumers do not contend on one counter.
+class LenCounter
+{
+public:
+    constexpr LenCounter() noexcept = default;
+
+    void pushed(size_t n = 1) noexcept
+    {
+        pushed_->fetch_add(n, std::memory_order_relaxed);
+    }
+
+    void popped(size_t n = 1) noexcept
+    {
+        popped_->fetch_add(n, std::memory_order_relaxed);
+    }
+
+    /// @brief Returns the count of the items in the queue. An item may be popped before its push
+    /// is counted, which is clamped to 0.
+    [[nodiscard]] size_t len() const noexcept
+    {
+        auto popped = popped_->load(std::memory_order_relaxed);
+        auto pushed = pushed_->load(std::memory_order_relaxed);
+        return pushed > popped ? pushed - popped : 0;
+    }
+
+private:
+    util::CachePadded<std::atomic<size_t>> pushed_;
+    util::CachePadded<std::atomic<size_t>> popped_;
+};
+
+}
+
+}
+
+#endif //SYNC_CELL_QUEUE_LEN_HPP
@@ -0,0 +1,159 @@
+///
+/// @file  select_hook.hpp
+/// @brief The registration point of a select source (a queue, a oneshot cell or an event), which
+/// notifies the selectors waiting on it.
+///
+
+#ifndef SYNC_CELL_SELECT_HOOK_HPP
+#define SYNC_CELL_SELECT_HOOK_HPP
+
+#include <atomic>
+#include <cstddef>
+
+#include "util/back_off.hpp"
+
+
+namespace sc::impl {
+
+/// @brief The wake-up signal of a waiting selector, which is fired at most once by the first
+/// notifying source, until it is reset.
+class SelectSignal
+{
+public:
+    /// @brief Claims the wake-up. Only the one succeeded calls @c wake later.
+    bool try_fire() noexcept
+    {
+        return !fired_.exchange(true, std::memory_order_acq_rel);
+    }
+
+    /// @brief Rearms the signal, after the selector has handled the wake-up.
+    void reset() noexcept
+    {
+        fired_.store(false, std::memory_order_relaxed);
+    }
+
+    /// @brief Called by the source which fired the signal, without its hook locked.
+    virtual void wake() noexcept = 0;
+
+protected:
+    SelectSignal() = default;
+
+    ~SelectSignal() = default;
+
+private:
+    std::atomic<bool> fired_{false};
+};
+
+/// @brief The node of a selector in the list of a @c SelectHook, one for each selected source.
+struct SelectEntry
+{
+    SelectSignal *signal = nullptr;
+    SelectEntry *prev = nullptr;
+    SelectEntry *next = nullptr;
+    /// @brief Whether the entry is in the list, guarded by the lock of the hook.
+    bool linked = false;
+};
+
+/// @brief The list of the selectors waiting on a source. The source calls @c notify after each
+/// change which may make it ready, the empty list costs a relaxed load only.
+///
+/// The list is guarded by a spin lock, which is held only to link or unlink the entries, so the
+/// hook works without the std threads as well.
+class SelectHook
+{
+public:
+    constexpr SelectHook() noexcept = default;
+
+    SelectHook(const SelectHook &) = delete;
+
+    SelectHook &operator=(const SelectHook &) = delete;
+
+    /// @brief Links the 'entry', the selector must check the source again after this.
+    void attach(SelectEntry &entry) noexcept
+    {
+        // Pairs with the fence of the source before 'notify': either the selector sees the change
+        // in its check after this, or the source sees the entry.
+        count_.fetch_add(1, std::memory_order_seq_cst);
+        lock();
+        entry.prev = nullptr;
+        entry.next = head_;
+        if (head_ != nullptr) {
+            head_->prev = &entry;
+        }
+        head_ = &entry;
+        entry.linked = true;
+        unlock();
+    }
+
+    /// @brief Unlinks the 'entry' if it is not unlinked by a notification yet.
+    void detach(SelectEntry &entry) noexcept
+    {
+        lock();
+        if (entry.linked) {
+            unlink(entry);
+        }
+        unlock();
+    }
+
+    /// @brief Fires the signals of all linked selectors. Must be called after a seq_cst fence
+    /// following the change of the source.
+    void notify() noexcept
+    {
+        if (count_.load(std::memory_order_relaxed) == 0) {
+            return;
+        }
+
+        // The fired selectors wait for the 'wake', so their entries stay valid after the unlock.
+        SelectEntry *fired = nullptr;
+        lock();
+        for (auto *entry = head_; entry != nullptr;) {
+            auto *next = entry->next;
+            if (entry->signal->try_fire()) {
+                unlink(*entry);
+                entry->next = fired;
+                fired = entry;
+            }
+            entry = next;
+        }
+        unlock();
+
+        while (fired != nullptr) {
+            // The selector may destroy the entry after woken.
+            auto *signal = fired->signal;
+            fired = fired->next;
+            signal->wake();
+        }
+    }
+
+private:
+    void unlink(SelectEntry &entry) noexcept
+    {
+        (entry.prev == nullptr ? head_ : entry.prev->next) = entry.next;
+        if (entry.next != nullptr) {
+            entry.next->prev = entry.prev;
+        }
+        entry.linked = false;
+        count_.fetch_sub(1, std::memory_order_relaxed);
+    }
+
+    void lock() noexcept
+    {
+        util::Backoff backoff;
+        while (locked_.test_and_set(std::memory_order_acquire)) {
+            backoff.snooze();
+        }
+    }
+
+    void unlock() noexcept
+    {
+        locked_.clear(std::memory_order_release);
+    }
+
+    std::atomic<size_t> count_{0};
+    std::atomic_flag locked_ = ATOMIC_FLAG_INIT;
+    SelectEntry *head_ = nullptr;
+};
+
+}
+
+#endif //SYNC_CELL_SELECT_HOOK_HPP
@@ -0,0 +1,112 @@
+///
+/// @file  serde.hpp
+/// @brief The serialization hooks of the cells and the locks, enabled by @c SYNC_CELL_SERDE.
+///
+/// A cell or a lock is serialized as its inner value, which is read by a snapshot (a load of the
+/// cell, or a copy under the lock), and is deserialized by storing the value back. So a struct
+/// containing them can round-trip through a config or a state file with the serialization library
+/// of the user. The hooks are found by the ADL, without including the headers of the library:
+/// * @c save and @c load for the archives called as <tt>ar(value)</tt>, e.g. the @b cereal.
+/// * @c to_json and @c from_json for the @b nlohmann::json.
+///
+/// A type is supported by specializing @c sc::serde::Codec, see the end of "cell/sync_cell.hpp".
+///
+
+#ifndef SYNC_CELL_SERDE_HPP
+#define SYNC_CELL_SERDE_HPP
+
+#include "shared/config.hpp"
+
+#if SC_HAS_SERDE
+
+#include <optional>
+#include <type_traits>
+#include <utility>
+
+
+namespace sc::serde {
+
+/// @brief Reads and writes the inner value of a cell or a lock 'C'. A specialization provides:
+/// * @c value_type: the serialized type, which must be default constructible.
+/// * <tt>static value_type snapshot(const C &)</tt>: reads the current value.
+/// * <tt>static void restore(C &, value_type &&)</tt>: stores the deserialized value.
+template<typename C>
+struct Codec;
+
+template<typename C>
+concept Serializable = requires { typename Codec<C>::value_type; };
+
+namespace impl {
+
+template<typename T>
+inline constexpr bool IsOptional = false;
+
+template<typename T>
+inline constexpr bool IsOptional<std::optional<T>> = true;
+
+}
+
+/// @brief Saves the snapshot of 'c' to the output archive.
+template<typename Archive, Serializable C>
+void save(Archive &ar, const C &c)
+{
+    ar(Codec<C>::snapshot(c));
+}
+
+/// @brief Loads a value from the input archive, and stores it into 'c'.
+template<typename Archive, Serializable C>
+void load(Archive &ar, C &c)
+{
+    typename Codec<C>::value_type value{};
+    ar(value);
+    Codec<C>::restore(c, std::move(value));
+}
+
+/// @brief Converts the snapshot of 'c' to the json, an empty optional is the @c null.
+template<typename Json, Serializable C>
+void to_json(Json &j, const C &c)
+{
+    auto value = Codec<C>::snapshot(c);
+    if constexpr(impl::IsOptional<decltype(value)>) {
+        if (value) {
+            j = *std::move(value);
+        } else {
+            j = nullptr;
+        }
+    } else {
+        j = std::move(value);
+    }
+}
+
+/// @brief Converts the json to the value, and stores it into 'c'.
+template<typename Json, Serializable C>
+void from_json(const Json &j, C &c)
+{
+    using Value = typename Codec<C>::value_type;
+    if constexpr(impl::IsOptional<Value>) {
+        Codec<C>::restore(c, j.is_null() ? Value() : Value(j.template get<typename Value::value_type>()));
+    } else {
+        Codec<C>::restore(c, j.template get<Value>());
+    }
+}
+
+}
+
+// The ADL looks up the hooks in the namespaces of the cells and the locks.
+namespace sc {
+using serde::save;
+using serde::load;
+using serde::to_json;
+using serde::from_json;
+}
+
+namespace sc::lock {
+using serde::save;
+using serde::load;
+using serde::to_json;
+using serde::from_json;
+}
+
+#endif
+
+#endif //SYNC_CELL_SERDE_HPP
@@ -0,0 +1,234 @@
+///
+/// @file  shm.hpp
+/// @brief The support of the cells and the queues placed in a memory shared by the processes: the
+/// layout header, the waits on the shared futex words, and the named shared memory regions.
+///
+
+#ifndef SYNC_CELL_SHM_HPP
+#define SYNC_CELL_SHM_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_SHM
+#error "The shared memory types require SYNC_CELL_SHM, on Linux or macOS."
+#endif
+
+#include <algorithm>
+#include <atomic>
+#include <cerrno>
+#include <climits>
+#include <cstddef>
+#include <cstdint>
+#include <string>
+#include <system_error>
+#include <type_traits>
+#include <utility>
+
+#include <fcntl.h>
+#include <sys/mman.h>
+#include <sys/stat.h>
+#include <unistd.h>
+
+#if defined(__linux__)
+#include <linux/futex.h>
+#include <sys/syscall.h>
+#include <time.h>
+#endif
+
+
+#if defined(__APPLE__)
+extern "C" int __ulock_wait(uint32_t operation, void *addr, uint64_t value, uint32_t timeout_us);
+extern "C" int __ulock_wake(uint32_t operation, void *addr, uint64_t wake_value);
+#endif
+
+namespace sc::shm {
+
+/// @brief The value types which can be shared by the processes: trivially copyable, so the bytes
+/// written by a process are the value read by another one, and without the pointers in practice,
+/// because the region is mapped at different addresses.
+template<typename T>
+concept Pod = std::is_trivially_copyable_v<T> && std::is_standard_layout_v<T>;
+
+namespace impl {
+
+/// @brief "SC_SHM01", written last by the creator, so an attaching process does not see a half
+/// initialized object.
+constexpr uint64_t Magic = 0x53435f53484d3031;
+
+/// @brief Increased when the layout of any shared type changes, so the processes built from the
+/// different versions of the project refuse to attach instead of corrupting the memory.
+constexpr uint32_t LayoutVersion = 1;
+
+enum class Kind : uint32_t
+{
+    Cell = 1,
+    Queue = 2,
+};
+
+static_assert(std::atomic<uint32_t>::is_always_lock_free && std::atomic<uint64_t>::is_always_lock_free,
+              "The atomics in the shared memory must be lock-free, so they are address-free.");
+
+/// @brief The header at the start of each shared object, checked by the attaching processes.
+struct Header
+{
+    std::atomic<uint64_t> magic;
+    uint32_t version;
+    Kind kind;
+    uint64_t value_size;
+    uint64_t value_align;
+
+    void init(Kind k, size_t size, size_t align) noexcept
+    {
+        version = LayoutVersion;
+        kind = k;
+        value_size = size;
+        value_align = align;
+        magic.store(Magic, std::memory_order_release);
+    }
+
+    [[nodiscard]] bool matches(Kind k, size_t size, size_t align) const noexcept
+    {
+        return magic.load(std::memory_order_acquire) == Magic && version == LayoutVersion && kind == k &&
+               value_size == size && value_align == align;
+    }
+};
+
+/// @brief Parks the thread while the shared 'word' equals the 'expected', at most 'ns' nanoseconds,
+/// or without limit if 'ns' is negative. It may return spuriously.
+///
+/// Unlike the process-private waits of "shared/futex.hpp", the kernel keys the wait by the physical
+/// page, so a thread of another process mapping the same page wakes it up.
+inline void wait(const std::atomic<uint32_t> &word, uint32_t expected, int64_t ns) noexcept
+{
+    auto *addr = const_cast<std::atomic<uint32_t> *>(&word);
+#if defined(__linux__)
+    timespec ts{static_cast<time_t>(ns / 1000000000), static_cast<long>(ns % 1000000000)};
+    ::syscall(SYS_futex, addr, FUTEX_WAIT, expected, ns < 0 ? nullptr : &ts, nullptr, 0);
+#else
+    // UL_COMPARE_AND_WAIT_SHARED with ULF_NO_ERRNO, a zero timeout waits without limit.
+    auto us = ns < 0 ? 0u : static_cast<uint32_t>(std::clamp<int64_t>((ns + 999) / 1000, 1, UINT32_MAX));
+    ::__ulock_wait(0x01000003, addr, expected, us);
+#endif
+}
+
+/// @brief Wakes up one or all threads of any process parked on the shared 'word'.
+inline void wake(const std::atomic<uint32_t> &word, bool all) noexcept
+{
+    auto *addr = const_cast<std::atomic<uint32_t> *>(&word);
+#if defined(__linux__)
+    ::syscall(SYS_futex, addr, FUTEX_WAKE, all ? INT_MAX : 1, nullptr, nullptr, 0);
+#else
+    // ULF_WAKE_ALL is 0x100.
+    ::__ulock_wake(all ? 0x01000103 : 0x01000003, addr, 0);
+#endif
+}
+
+}
+
+/// @brief A named POSIX shared memory region mapped into the process, to place the shared cells and
+/// queues in:
+/// ``` cpp
+/// // In the server.
+/// auto region = sc::shm::Region::create("/orders", sc::shm::ShmQueue<Order>::required_size(1024));
+/// auto *orders = sc::shm::ShmQueue<Order>::create(region.data(), 1024);
+/// // In the clients.
+/// auto region = sc::shm::Region::open("/orders");
+/// auto *orders = sc::shm::ShmQueue<Order>::attach(region.data());
+/// ```
+///
+/// The mapping is released by the destructor, and the name is removed by @c unlink, after which the
+/// processes already mapping it keep the memory.
+class Region
+{
+public:
+    /// @brief Creates the region of 'size' bytes (zeroed by the system), which must not exist yet.
+    /// @throw std::system_error If the region exists or can not be created.
+    static Region create(const std::string &name, size_t size)
+    {
+        int fd = ::shm_open(name.c_str(), O_CREAT | O_EXCL | O_RDWR, 0600);
+        if (fd < 0) {
+            throw_errno("shm_open");
+        }
+        if (::ftruncate(fd, static_cast<off_t>(size)) != 0) {
+            auto error = errno;
+            ::close(fd);
+            ::shm_unlink(name.c_str());
+            throw std::system_error(error, std::generic_category(), "ftruncate");
+        }
+        return map(fd, size);
+    }
+
+    /// @brief Maps the existing region.
+    /// @throw std::system_error If the region does not exist or can not be mapped.
+    static Region open(const std::string &name)
+    {
+        int fd = ::shm_open(name.c_str(), O_RDWR, 0);
+        if (fd < 0) {
+            throw_errno("shm_open");
+        }
+        struct stat st{};
+        if (::fstat(fd, &st) != 0) {
+            auto error = errno;
+            ::close(fd);
+            throw std::system_error(error, std::generic_category(), "fstat");
+        }
+        return map(fd, static_cast<size_t>(st.st_size));
+    }
+
+    /// @brief Removes the name of the region.
+    /// @return false if the region does not exist.
+    static bool unlink(const std::string &name) noexcept
+    {
+        return ::shm_unlink(name.c_str()) == 0;
+    }
+
+    Region(Region &&other) noexcept
+            : data_(std::exchange(other.data_, nullptr)), size_(std::exchange(other.size_, 0)) { }
+
+    Region &operator=(Region &&) = delete;
+
+    ~Region()
+    {
+        if (data_ != nullptr) {
+            ::munmap(data_, size_);
+        }
+    }
+
+    /// @brief Returns the start of the mapping, which is aligned to a page.
+    [[nodiscard]] void *data() const noexcept
+    {
+        return data_;
+    }
+
+    [[nodiscard]] size_t size() const noexcept
+    {
+        return size_;
+    }
+
+private:
+    Region(void *data, size_t size) noexcept : data_(data), size_(size) { }
+
+    [[noreturn]] static void throw_errno(const char *what)
+    {
+        throw std::system_error(errno, std::generic_category(), what);
+    }
+
+    static Region map(int fd, size_t size)
+    {
+        auto *data = ::mmap(nullptr, size, PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0);
+        auto error = errno;
+        // The mapping keeps the memory object, the descriptor is not needed after.
+        ::close(fd);
+        if (data == MAP_FAILED) {
+            throw std::system_error(error, std::generic_category(), "mmap");
+        }
+        return Region(data, size);
+    }
+
+    void *data_;
+    size_t size_;
+};
+
+}
+
+#endif //SYNC_CELL_SHM_HPP
@@ -0,0 +1,52 @@
+///
+/// @file  single_thread.hpp
+/// @brief The report of the blocking waits on the targets without threads, see
+/// @c SYNC_CELL_SINGLE_THREADED in "shared/config.hpp".
+///
+
+#ifndef SYNC_CELL_SINGLE_THREAD_HPP
+#define SYNC_CELL_SINGLE_THREAD_HPP
+
+#include <cstdlib>
+
+#include "shared/config.hpp"
+
+
+namespace sc::single_thread {
+
+/// @brief Called when a wait can never complete, as no other thread exists to make progress: e.g.
+/// locking a held spin lock, or the blocking enqueue to a full queue. The handler must not return,
+/// e.g. it reports the error to the host and aborts.
+using Handler = void (*)(const char *what) noexcept;
+
+namespace impl {
+
+inline Handler &handler() noexcept
+{
+    static Handler h = nullptr;
+    return h;
+}
+
+}
+
+/// @brief Replaces the handler of the blocked waits, the default one aborts.
+/// @return The previous handler.
+inline Handler set_handler(Handler handler) noexcept
+{
+    auto previous = impl::handler();
+    impl::handler() = handler;
+    return previous;
+}
+
+/// @brief Reports a wait which blocks forever on a single-threaded target.
+[[noreturn]] inline void would_block(const char *what) noexcept
+{
+    if (auto handler = impl::handler(); handler != nullptr) {
+        handler(what);
+    }
+    std::abort();
+}
+
+}
+
+#endif //SYNC_CELL_SINGLE_THREAD_HPP
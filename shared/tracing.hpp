@@ -0,0 +1,93 @@
+///
+/// @file  tracing.hpp
+/// @brief The tracing hooks around the contended waits and the once-cell initialization, enabled
+/// by @c SYNC_CELL_TRACING. The user forwards them to the tracer (e.g. OpenTelemetry spans).
+///
+
+#ifndef SYNC_CELL_TRACING_HPP
+#define SYNC_CELL_TRACING_HPP
+
+#include <cstdint>
+
+#include "shared/config.hpp"
+
+
+namespace sc::tracing {
+
+/// @brief What a span covers.
+enum class SpanKind : uint8_t
+{
+    /// @brief Waiting for a lock held by another thread. Not emitted if the lock is acquired at once.
+    LockWait,
+    /// @brief A consumer of a blocking queue parked on the empty queue.
+    QueueBlock,
+    /// @brief Running the initialization function of a once cell.
+    OnceInit,
+    /// @brief Waiting for another thread initializing a once cell.
+    OnceWait,
+};
+
+#if SC_HAS_TRACING
+
+/// @brief Called when a span is entered.
+/// @param kind What the span covers.
+/// @param name The static name of the primitive type, e.g. "sc::lock::SpinLock".
+/// @param object The address of the primitive, to tell the instances apart.
+/// @note Declared only, the user must define it in one translation unit. It may be called
+/// concurrently by many threads.
+void span_enter(SpanKind kind, const char *name, const void *object) noexcept;
+
+/// @brief Called when the span entered by the paired @c span_enter on the same thread is exited.
+/// @note Declared only, the user must define it in one translation unit.
+void span_exit(SpanKind kind, const char *name, const void *object) noexcept;
+
+#endif
+
+/// @brief A span in a scope, which is entered by @c enter (at most once) and exited when the
+/// scope ends. It does nothing if @c SYNC_CELL_TRACING is not defined.
+class Span
+{
+public:
+#if SC_HAS_TRACING
+    Span(SpanKind kind, const char *name, const void *object) noexcept : kind_(kind), name_(name), object_(object) { }
+#else
+    Span(SpanKind, const char *, const void *) noexcept { }
+#endif
+
+    Span(const Span &) = delete;
+
+    Span &operator=(const Span &) = delete;
+
+    ~Span()
+    {
+#if SC_HAS_TRACING
+        if (entered_) {
+            span_exit(kind_, name_, object_);
+        }
+#endif
+    }
+
+    /// @brief Enters the span if not entered yet, so it can be called in a wait loop and the span
+    /// covers only the contended path.
+    void enter() noexcept
+    {
+#if SC_HAS_TRACING
+        if (!entered_) {
+            entered_ = true;
+            span_enter(kind_, name_, object_);
+        }
+#endif
+    }
+
+private:
+#if SC_HAS_TRACING
+    SpanKind kind_;
+    bool entered_ = false;
+    const char *name_;
+    const void *object_;
+#endif
+};
+
+}
+
+#endif //SYNC_CELL_TRACING_HPP
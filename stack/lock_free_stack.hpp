@@ -0,0 +1,232 @@
+///
+/// @file  lock_free_stack.hpp
+/// @brief An unbounded lock-free stack (Treiber stack).
+///
+
+#ifndef SYNC_CELL_LOCK_FREE_STACK_HPP
+#define SYNC_CELL_LOCK_FREE_STACK_HPP
+
+#include <atomic>
+#include <iterator>
+#include <memory>
+#include <optional>
+#include <type_traits>
+#include <utility>
+#include <vector>
+
+#include "epoch/epoch.hpp"
+#include "shared/compiler_workaround.hpp"
+#include "shared/drain.hpp"
+#include "shared/extend.hpp"
+#include "util/cache_padded.hpp"
+#include "util/copy_move_selector.hpp"
+
+
+namespace sc::stack {
+
+/// @brief An unbounded lock-free stack (Treiber stack).
+///
+/// The popped nodes may still be read by the concurrent pop operations, so they are destroyed by
+/// the reclamation backend, which also avoids the ABA problem of the CAS on the top node.
+/// @tparam T The value type.
+/// @tparam Reclaimer The reclamation backend of the popped nodes: @c sc::epoch::Reclaimer or
+/// @c sc::hazard::Reclaimer (include "hazard/hazard.hpp" to use it).
+/// @tparam Allocator The allocator of the nodes. Each node keeps a copy of it to be destroyed by
+/// the reclamation backend, maybe after the stack is destroyed, so the memory resource of a
+/// stateful allocator (e.g. an arena) must outlive the reclamation.
+template<typename T, typename Reclaimer = epoch::Reclaimer, typename Allocator = std::allocator<T>>
+class LockFreeStack
+{
+    struct Node;
+
+    using NodeAlloc = typename std::allocator_traits<Allocator>::template rebind_alloc<Node>;
+    using NodeTrait = std::allocator_traits<NodeAlloc>;
+
+    struct Node
+    {
+        std::optional<T> value;
+        /// @brief Immutable after the node is pushed.
+        Node *next = nullptr;
+        SC_NO_UNIQUE_ADDRESS NodeAlloc alloc;
+
+        template<typename... Args>
+        explicit Node(const NodeAlloc &alloc, std::in_place_t, Args &&... args)
+                : value(std::in_place, std::forward<Args>(args)...), alloc(alloc)
+        {
+            static_assert(std::is_constructible_v<T, Args &&...>);
+        }
+    };
+
+public:
+    using value_type = T;
+    using reference = value_type &;
+    using const_reference = const value_type &;
+    using allocator_type = Allocator;
+
+    LockFreeStack() : LockFreeStack(Allocator()) { }
+
+    /// @brief Creates a stack allocating the nodes from 'alloc'.
+    explicit LockFreeStack(const Allocator &alloc) : alloc_(alloc)
+    {
+        head_->store(nullptr, std::memory_order_relaxed);
+    }
+
+    /// @brief Creates a stack with the items of [first, last), the last item is on the top.
+    template<std::input_iterator It, typename Sentinel>
+    LockFreeStack(It first, Sentinel last, const Allocator &alloc = Allocator()) : LockFreeStack(alloc)
+    {
+        extend(std::move(first), std::move(last));
+    }
+
+    LockFreeStack(const LockFreeStack &) = delete;
+
+    LockFreeStack &operator=(const LockFreeStack &) = delete;
+
+    ~LockFreeStack()
+    {
+        auto *node = head_->load(std::memory_order_relaxed);
+        while (node != nullptr) {
+            delete_node(std::exchange(node, node->next));
+        }
+    }
+
+    [[nodiscard]] bool is_lock_free() const noexcept
+    {
+        return head_->is_lock_free();
+    }
+
+    [[nodiscard]] bool is_empty() const noexcept
+    {
+        return head_->load(std::memory_order_acquire) == nullptr;
+    }
+
+    [[nodiscard]] allocator_type get_allocator() const noexcept
+    {
+        return allocator_type(alloc_);
+    }
+
+    template<typename = std::enable_if_t<std::is_copy_constructible_v<value_type>>>
+    void push(const_reference value)
+    {
+        push_node(new_node(value));
+    }
+
+    template<typename = std::enable_if_t<std::is_move_constructible_v<value_type>>>
+    void push(value_type &&value)
+    {
+        push_node(new_node(std::move(value)));
+    }
+
+    /// @brief Pushes the items of [first, last) in order.
+    template<std::input_iterator It, typename Sentinel>
+    void extend(It first, Sentinel last)
+    {
+        sc::impl::extend(std::move(first), std::move(last), [this](auto &&v) {
+            push(std::forward<decltype(v)>(v));
+        });
+    }
+
+    /// @brief Pushes the items of the 'range' in order, e.g. a container or a view.
+    template<typename Range>
+    void extend(Range &&range)
+    {
+        extend(std::begin(range), std::end(range));
+    }
+
+    /// @brief Pops the top value.
+    /// @return An empty optional if the stack is empty.
+    std::optional<value_type> pop()
+    {
+        auto guard = Reclaimer::guard();
+        while (true) {
+            auto *head = guard.protect(*head_);
+            if (head == nullptr) {
+                return {};
+            }
+
+            // 'head' can not be destroyed now, so its 'next' is valid even if it has been popped
+            // by another thread (the CAS will fail in that case).
+            if (head_->compare_exchange_weak(
+                    head, head->next,
+                    std::memory_order_acquire,
+                    std::memory_order_relaxed)) {
+                std::optional<value_type> ret(util::cast_ctor_ref(head->value));
+                guard.retire(head, &delete_node);
+                return ret;
+            }
+        }
+    }
+
+    /// @brief Pops all values with one atomic operation.
+    /// @return The popped values, from the top to the bottom.
+    std::vector<value_type> pop_all()
+    {
+        auto guard = Reclaimer::guard();
+        auto *node = head_->exchange(nullptr, std::memory_order_acquire);
+
+        std::vector<value_type> values;
+        while (node != nullptr) {
+            values.emplace_back(util::cast_ctor_ref(*node->value));
+            // The concurrent pop operations may still read the 'next' of the node.
+            guard.retire(std::exchange(node, node->next), &delete_node);
+        }
+
+        return values;
+    }
+
+    /// @brief Takes all values as @c pop_all when the iteration begins, then yields them from the
+    /// top to the bottom. The values pushed later are not taken.
+    /// @return A @c Drain range of one chunk.
+    auto drain()
+    {
+        auto take = [this, taken = false](std::vector<value_type> &out, size_t) mutable -> size_t {
+            if (std::exchange(taken, true)) {
+                return 0;
+            }
+            out = pop_all();
+            return out.size();
+        };
+        return Drain<value_type, decltype(take)>(take, DefaultDrainChunk);
+    }
+
+private:
+    template<typename... Args>
+    Node *new_node(Args &&... args)
+    {
+        NodeAlloc alloc(alloc_);
+        auto *node = NodeTrait::allocate(alloc, 1);
+        try {
+            NodeTrait::construct(alloc, node, alloc_, std::in_place, std::forward<Args>(args)...);
+        } catch (...) {
+            NodeTrait::deallocate(alloc, node, 1);
+            throw;
+        }
+        return node;
+    }
+
+    /// @brief Destroys the node with its own copy of the allocator, called by the reclamation
+    /// backend.
+    static void delete_node(void *p) noexcept
+    {
+        auto *node = static_cast<Node *>(p);
+        NodeAlloc alloc(std::move(node->alloc));
+        NodeTrait::destroy(alloc, node);
+        NodeTrait::deallocate(alloc, node, 1);
+    }
+
+    void push_node(Node *node)
+    {
+        node->next = head_->load(std::memory_order_relaxed);
+        while (!head_->compare_exchange_weak(
+                node->next, node,
+                std::memory_order_release,
+                std::memory_order_relaxed)) { }
+    }
+
+    util::CachePadded<std::atomic<Node *>> head_;
+    SC_NO_UNIQUE_ADDRESS NodeAlloc alloc_;
+};
+
+}
+
+#endif //SYNC_CELL_LOCK_FREE_STACK_HPP
@@ -0,0 +1,244 @@
+///
+/// @file  async_mutex.hpp
+/// @brief A mutex protecting a value, whose lock is acquired by a C++20 coroutine without blocking
+/// the thread.
+///
+
+#ifndef SYNC_CELL_ASYNC_MUTEX_HPP
+#define SYNC_CELL_ASYNC_MUTEX_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The async_mutex.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <atomic>
+#include <cstdint>
+#include <memory>
+#include <mutex>
+#include <optional>
+#include <type_traits>
+#include <utility>
+
+#if __cpp_impl_coroutine
+#include <coroutine>
+#endif
+
+
+#if __cpp_impl_coroutine
+
+namespace sc::sync {
+
+/// @brief A mutex protecting a value of type 'T' for the coroutines: @c co_await on @c lock
+/// suspends the coroutine instead of blocking the thread when the lock is held, and the coroutine
+/// is resumed when the lock is handed off to it.
+///
+/// The awaiters are linked into a FIFO waiter list, without any allocation. When the holder
+/// unlocks, the lock is handed off to the first waiter directly, so a waiter is never overtaken by
+/// the later waiters. The lock state is one atomic word, and the uncontended lock and unlock take
+/// no mutex.
+///
+/// @note A suspended coroutine is resumed on the unlocking thread inside the destructor of the
+/// @c Guard. If the coroutine must run on a specific executor, reschedule it after the @c co_await
+/// returns.
+///
+/// @example
+/// ``` cpp
+/// sc::sync::AsyncMutex<std::vector<int>> values;
+/// auto guard = co_await values.lock();
+/// guard->push_back(1);
+/// ```
+/// @tparam T The value type.
+template<typename T>
+class AsyncMutex
+{
+    static constexpr uintptr_t Locked = 1;
+    static constexpr uintptr_t HasWaiters = 2;
+
+public:
+    using value_type = T;
+
+    class LockAwaiter;
+
+    /// @brief The RAII guard holding the lock, the lock is released (or handed off to the first
+    /// waiter) when the guard is destroyed.
+    class Guard
+    {
+        friend class AsyncMutex;
+
+        explicit Guard(AsyncMutex &mutex) noexcept : mutex_(&mutex) { }
+
+    public:
+        Guard(Guard &&other) noexcept : mutex_(std::exchange(other.mutex_, nullptr)) { }
+
+        Guard &operator=(Guard &&) = delete;
+
+        ~Guard()
+        {
+            if (mutex_ != nullptr) {
+                mutex_->unlock();
+            }
+        }
+
+        T &operator*() const noexcept
+        {
+            return mutex_->value_;
+        }
+
+        T *operator->() const noexcept
+        {
+            return std::addressof(mutex_->value_);
+        }
+
+    private:
+        AsyncMutex *mutex_;
+    };
+
+    template<typename... Args>
+    explicit AsyncMutex(Args &&... args) noexcept(std::is_nothrow_constructible_v<T, Args...>)
+            : value_(std::forward<Args>(args)...)
+    {
+    }
+
+    AsyncMutex(const AsyncMutex &) = delete;
+
+    AsyncMutex &operator=(const AsyncMutex &) = delete;
+
+    /// @brief Acquires the lock asynchronously.
+    /// @example
+    /// ``` cpp
+    /// auto guard = co_await mutex.lock();
+    /// ```
+    [[nodiscard]] LockAwaiter lock() noexcept
+    {
+        return LockAwaiter(*this);
+    }
+
+    /// @brief Acquires the lock if it is available.
+    /// @return An empty optional if the lock is held.
+    [[nodiscard]] std::optional<Guard> try_lock() noexcept
+    {
+        if (try_set_locked()) {
+            return Guard(*this);
+        }
+        return {};
+    }
+
+    /// @brief Returns true if the lock is held.
+    [[nodiscard]] bool is_locked() const noexcept
+    {
+        return (state_.load(std::memory_order_relaxed) & Locked) != 0;
+    }
+
+private:
+    bool try_set_locked() noexcept
+    {
+        auto state = state_.load(std::memory_order_relaxed);
+        while ((state & Locked) == 0) {
+            if (state_.compare_exchange_weak(state, state | Locked, std::memory_order_acquire,
+                                             std::memory_order_relaxed)) {
+                return true;
+            }
+        }
+        return false;
+    }
+
+    /// @brief Takes the lock, or appends the 'waiter' to the list.
+    /// @return false if the lock is taken, and the 'waiter' is not registered.
+    bool register_waiter(LockAwaiter &waiter) noexcept
+    {
+        std::lock_guard guard(mtx_);
+        auto state = state_.load(std::memory_order_relaxed);
+        while (true) {
+            if ((state & Locked) == 0) {
+                if (state_.compare_exchange_weak(state, state | Locked, std::memory_order_acquire,
+                                                 std::memory_order_relaxed)) {
+                    return false;
+                }
+            } else if ((state & HasWaiters) != 0 ||
+                       state_.compare_exchange_weak(state, state | HasWaiters, std::memory_order_relaxed)) {
+                // The unlocker sees the 'HasWaiters', and takes the 'mtx_' to hand off the lock.
+                break;
+            }
+        }
+
+        if (tail_ == nullptr) {
+            head_ = &waiter;
+        } else {
+            tail_->next_ = &waiter;
+        }
+        tail_ = &waiter;
+        return true;
+    }
+
+    void unlock() noexcept
+    {
+        auto state = Locked;
+        if (state_.compare_exchange_strong(state, 0, std::memory_order_release, std::memory_order_relaxed)) {
+            return;
+        }
+
+        LockAwaiter *waiter;
+        {
+            std::lock_guard guard(mtx_);
+            waiter = head_;
+            head_ = waiter->next_;
+            if (head_ == nullptr) {
+                tail_ = nullptr;
+                // Keep the 'Locked', it is handed off to the waiter.
+                state_.store(Locked, std::memory_order_relaxed);
+            }
+        }
+        // The waiter is resumed on this thread, so it sees the writes of the value by this holder.
+        waiter->handle_.resume();
+    }
+
+    std::atomic<uintptr_t> state_{0};
+    /// @brief Guards the FIFO waiter list.
+    std::mutex mtx_;
+    LockAwaiter *head_ = nullptr;
+    LockAwaiter *tail_ = nullptr;
+    T value_;
+};
+
+/// @brief The awaitable object returned by @c AsyncMutex::lock.
+template<typename T>
+class AsyncMutex<T>::LockAwaiter
+{
+    friend class AsyncMutex;
+
+    explicit LockAwaiter(AsyncMutex &mutex) noexcept : mutex_(mutex) { }
+
+public:
+    LockAwaiter(const LockAwaiter &) = delete;
+
+    LockAwaiter &operator=(const LockAwaiter &) = delete;
+
+    bool await_ready() noexcept
+    {
+        return mutex_.try_set_locked();
+    }
+
+    bool await_suspend(std::coroutine_handle<> handle) noexcept
+    {
+        handle_ = handle;
+        return mutex_.register_waiter(*this);
+    }
+
+    Guard await_resume() noexcept
+    {
+        return Guard(mutex_);
+    }
+
+private:
+    AsyncMutex &mutex_;
+    std::coroutine_handle<> handle_;
+    LockAwaiter *next_ = nullptr;
+};
+
+}
+
+#endif
+
+#endif //SYNC_CELL_ASYNC_MUTEX_HPP
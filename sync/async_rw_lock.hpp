@@ -0,0 +1,330 @@
+///
+/// @file  async_rw_lock.hpp
+/// @brief A reader-writer lock protecting a value, whose shared or exclusive lock is acquired by a
+/// C++20 coroutine without blocking the thread.
+///
+
+#ifndef SYNC_CELL_ASYNC_RW_LOCK_HPP
+#define SYNC_CELL_ASYNC_RW_LOCK_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The async_rw_lock.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <cstddef>
+#include <memory>
+#include <mutex>
+#include <optional>
+#include <type_traits>
+#include <utility>
+
+#if __cpp_impl_coroutine
+#include <coroutine>
+#endif
+
+
+#if __cpp_impl_coroutine
+
+namespace sc::sync {
+
+/// @brief A reader-writer lock protecting a value of type 'T' for the coroutines: @c co_await on
+/// @c read or @c write suspends the coroutine while the lock is held incompatibly, and the
+/// coroutine is resumed when the lock is granted to it.
+///
+/// The awaiters are linked into a FIFO waiter list, without any allocation. A new reader waits
+/// behind a waiting writer, so the writers are not starved by a stream of readers. When the lock is
+/// released, it is granted to the waiters in order: either the first writer, or all readers before
+/// the next writer. The state is guarded by a @c std::mutex, which is only held for the bookkeeping.
+///
+/// @note A suspended coroutine is resumed on the releasing thread inside the destructor of the
+/// guard. If the coroutine must run on a specific executor, reschedule it after the @c co_await
+/// returns.
+///
+/// @example
+/// ``` cpp
+/// sc::sync::AsyncRwLock<Config> config;
+/// auto reader = co_await config.read();
+/// use(reader->timeout);
+/// ```
+/// @tparam T The value type.
+template<typename T>
+class AsyncRwLock
+{
+    static constexpr size_t Writer = ~size_t(0);
+
+public:
+    using value_type = T;
+
+    class Waiter;
+
+    class ReadAwaiter;
+
+    class WriteAwaiter;
+
+    /// @brief The RAII guard holding the shared lock, which gives the const access to the value.
+    class ReadGuard
+    {
+        friend class AsyncRwLock;
+
+        explicit ReadGuard(AsyncRwLock &lock) noexcept : lock_(&lock) { }
+
+    public:
+        ReadGuard(ReadGuard &&other) noexcept : lock_(std::exchange(other.lock_, nullptr)) { }
+
+        ReadGuard &operator=(ReadGuard &&) = delete;
+
+        ~ReadGuard()
+        {
+            if (lock_ != nullptr) {
+                lock_->unlock();
+            }
+        }
+
+        const T &operator*() const noexcept
+        {
+            return lock_->value_;
+        }
+
+        const T *operator->() const noexcept
+        {
+            return std::addressof(lock_->value_);
+        }
+
+    private:
+        AsyncRwLock *lock_;
+    };
+
+    /// @brief The RAII guard holding the exclusive lock, which gives the mutable access to the value.
+    class WriteGuard
+    {
+        friend class AsyncRwLock;
+
+        explicit WriteGuard(AsyncRwLock &lock) noexcept : lock_(&lock) { }
+
+    public:
+        WriteGuard(WriteGuard &&other) noexcept : lock_(std::exchange(other.lock_, nullptr)) { }
+
+        WriteGuard &operator=(WriteGuard &&) = delete;
+
+        ~WriteGuard()
+        {
+            if (lock_ != nullptr) {
+                lock_->unlock();
+            }
+        }
+
+        T &operator*() const noexcept
+        {
+            return lock_->value_;
+        }
+
+        T *operator->() const noexcept
+        {
+            return std::addressof(lock_->value_);
+        }
+
+    private:
+        AsyncRwLock *lock_;
+    };
+
+    template<typename... Args>
+    explicit AsyncRwLock(Args &&... args) noexcept(std::is_nothrow_constructible_v<T, Args...>)
+            : value_(std::forward<Args>(args)...)
+    {
+    }
+
+    AsyncRwLock(const AsyncRwLock &) = delete;
+
+    AsyncRwLock &operator=(const AsyncRwLock &) = delete;
+
+    /// @brief Acquires the shared lock asynchronously.
+    /// @example
+    /// ``` cpp
+    /// auto guard = co_await lock.read();
+    /// ```
+    [[nodiscard]] ReadAwaiter read() noexcept
+    {
+        return ReadAwaiter(*this);
+    }
+
+    /// @brief Acquires the exclusive lock asynchronously.
+    [[nodiscard]] WriteAwaiter write() noexcept
+    {
+        return WriteAwaiter(*this);
+    }
+
+    /// @brief Acquires the shared lock if no writer holds or waits for the lock.
+    [[nodiscard]] std::optional<ReadGuard> try_read() noexcept
+    {
+        std::lock_guard guard(mtx_);
+        if (try_take(false)) {
+            return ReadGuard(*this);
+        }
+        return {};
+    }
+
+    /// @brief Acquires the exclusive lock if no reader or writer holds or waits for the lock.
+    [[nodiscard]] std::optional<WriteGuard> try_write() noexcept
+    {
+        std::lock_guard guard(mtx_);
+        if (try_take(true)) {
+            return WriteGuard(*this);
+        }
+        return {};
+    }
+
+    /// @brief Returns the count of readers holding the lock.
+    [[nodiscard]] size_t reader_count() noexcept
+    {
+        std::lock_guard guard(mtx_);
+        return holders_ == Writer ? 0 : holders_;
+    }
+
+    /// @brief Returns true if a writer holds the lock.
+    [[nodiscard]] bool is_write_locked() noexcept
+    {
+        std::lock_guard guard(mtx_);
+        return holders_ == Writer;
+    }
+
+private:
+    /// @brief Takes the lock if no one waits before, and the holders are compatible. Must be called
+    /// with 'mtx_' locked.
+    bool try_take(bool write) noexcept
+    {
+        if (head_ != nullptr) {
+            return false;
+        }
+        if (write ? holders_ != 0 : holders_ == Writer) {
+            return false;
+        }
+        holders_ = write ? Writer : holders_ + 1;
+        return true;
+    }
+
+    /// @brief Takes the lock, or appends the 'waiter' to the list.
+    /// @return false if the lock is taken, and the 'waiter' is not registered.
+    bool register_waiter(Waiter &waiter) noexcept
+    {
+        std::lock_guard guard(mtx_);
+        if (try_take(waiter.write_)) {
+            return false;
+        }
+        if (tail_ == nullptr) {
+            head_ = &waiter;
+        } else {
+            tail_->next_ = &waiter;
+        }
+        tail_ = &waiter;
+        return true;
+    }
+
+    /// @brief Releases a reader or the writer, and grants the lock to the waiters in order.
+    void unlock() noexcept
+    {
+        Waiter *granted = nullptr;
+        {
+            std::lock_guard guard(mtx_);
+            holders_ = holders_ == Writer ? 0 : holders_ - 1;
+            Waiter **last = &granted;
+            while (head_ != nullptr && (head_->write_ ? holders_ == 0 : holders_ != Writer)) {
+                auto *waiter = head_;
+                head_ = waiter->next_;
+                holders_ = waiter->write_ ? Writer : holders_ + 1;
+                waiter->next_ = nullptr;
+                *last = waiter;
+                last = &waiter->next_;
+            }
+            if (head_ == nullptr) {
+                tail_ = nullptr;
+            }
+        }
+        for (auto *waiter = granted; waiter != nullptr;) {
+            // The coroutine may destroy the waiter after resumed.
+            auto handle = waiter->handle_;
+            waiter = waiter->next_;
+            handle.resume();
+        }
+    }
+
+    /// @brief Guards the holders and the FIFO waiter list.
+    std::mutex mtx_;
+    /// @brief The count of the readers, or 'Writer' when a writer holds the lock.
+    size_t holders_ = 0;
+    Waiter *head_ = nullptr;
+    Waiter *tail_ = nullptr;
+    T value_;
+};
+
+/// @brief The waiter list node of the awaiters.
+template<typename T>
+class AsyncRwLock<T>::Waiter
+{
+    friend class AsyncRwLock;
+
+protected:
+    Waiter(AsyncRwLock &lock, bool write) noexcept : lock_(lock), write_(write) { }
+
+public:
+    Waiter(const Waiter &) = delete;
+
+    Waiter &operator=(const Waiter &) = delete;
+
+    bool await_ready() noexcept
+    {
+        std::lock_guard guard(lock_.mtx_);
+        return lock_.try_take(write_);
+    }
+
+    bool await_suspend(std::coroutine_handle<> handle) noexcept
+    {
+        handle_ = handle;
+        return lock_.register_waiter(*this);
+    }
+
+protected:
+    AsyncRwLock &lock_;
+
+private:
+    const bool write_;
+    std::coroutine_handle<> handle_;
+    Waiter *next_ = nullptr;
+};
+
+/// @brief The awaitable object returned by @c AsyncRwLock::read.
+template<typename T>
+class AsyncRwLock<T>::ReadAwaiter : public Waiter
+{
+    friend class AsyncRwLock;
+
+    explicit ReadAwaiter(AsyncRwLock &lock) noexcept : Waiter(lock, false) { }
+
+public:
+    ReadGuard await_resume() noexcept
+    {
+        return ReadGuard(this->lock_);
+    }
+};
+
+/// @brief The awaitable object returned by @c AsyncRwLock::write.
+template<typename T>
+class AsyncRwLock<T>::WriteAwaiter : public Waiter
+{
+    friend class AsyncRwLock;
+
+    explicit WriteAwaiter(AsyncRwLock &lock) noexcept : Waiter(lock, true) { }
+
+public:
+    WriteGuard await_resume() noexcept
+    {
+        return WriteGuard(this->lock_);
+    }
+};
+
+}
+
+#endif
+
+#endif //SYNC_CELL_ASYNC_RW_LOCK_HPP
@@ -0,0 +1,217 @@
+///
+/// @file  barrier.hpp
+/// @brief A reusable barrier which spins for a while before parking the thread, and supports the
+/// timed wait.
+///
+
+#ifndef SYNC_CELL_BARRIER_HPP
+#define SYNC_CELL_BARRIER_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The barrier.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <atomic>
+#include <chrono>
+#include <condition_variable>
+#include <cstdint>
+#include <mutex>
+#include <optional>
+
+#include "util/back_off.hpp"
+
+
+namespace sc::sync {
+
+/// @brief The result of the @c Barrier::wait.
+class BarrierWaitResult
+{
+    friend class Barrier;
+
+    explicit BarrierWaitResult(bool is_leader) noexcept : is_leader_(is_leader) { }
+
+public:
+    /// @brief Returns true for exactly one thread of each generation: the last arrived one.
+    [[nodiscard]] bool is_leader() const noexcept
+    {
+        return is_leader_;
+    }
+
+private:
+    bool is_leader_;
+};
+
+/// @brief A reusable barrier for a fixed count of threads.
+///
+/// The generation and the arrived count are packed into one atomic word. The last arrived thread of
+/// a generation (the leader) starts the next generation, so the barrier can be reused right after
+/// the @c wait returns. The waiting threads spin with backoff first, and then are parked by the
+/// @c std::atomic::wait (a futex on Linux), or a condition variable for the timed wait.
+class Barrier
+{
+    static constexpr uint64_t GenerationShift = 32;
+    static constexpr uint64_t CountMask = (uint64_t(1) << GenerationShift) - 1;
+
+public:
+    /// @param count The count of threads to wait for in each generation, 0 is treated as 1.
+    explicit Barrier(uint32_t count) noexcept : count_(count == 0 ? 1 : count) { }
+
+    Barrier(const Barrier &) = delete;
+
+    Barrier &operator=(const Barrier &) = delete;
+
+    /// @brief Blocks the current thread until all threads have arrived at the barrier.
+    BarrierWaitResult wait()
+    {
+        auto generation = arrive();
+        if (!generation) {
+            return BarrierWaitResult(true);
+        }
+
+        auto state = spin_wait(*generation);
+        while (generation_of(state) == *generation) {
+            state_.wait(state, std::memory_order_acquire);
+            state = state_.load(std::memory_order_acquire);
+        }
+
+        return BarrierWaitResult(false);
+    }
+
+    /// @brief Blocks the current thread until all threads have arrived at the barrier, or the
+    /// 'timeout' duration has elapsed.
+    /// @return An empty optional if timeout. In this case, the current thread's arrival is
+    /// withdrawn, the other threads still wait for it (or another thread) in this generation.
+    template<typename Rep, typename Period>
+    std::optional<BarrierWaitResult> wait_timeout(const std::chrono::duration<Rep, Period> &timeout)
+    {
+        return wait_until(std::chrono::steady_clock::now() + timeout);
+    }
+
+    /// @brief Blocks the current thread until all threads have arrived at the barrier, or the
+    /// 'deadline' has been reached.
+    /// @return An empty optional if timeout. See @c wait_timeout.
+    template<typename Clock, typename Duration>
+    std::optional<BarrierWaitResult> wait_until(const std::chrono::time_point<Clock, Duration> &deadline)
+    {
+        auto generation = arrive();
+        if (!generation) {
+            return BarrierWaitResult(true);
+        }
+
+        auto passed = [this, &generation] {
+            return generation_of(state_.load(std::memory_order_acquire)) != *generation;
+        };
+        if (generation_of(spin_wait(*generation)) != *generation) {
+            return BarrierWaitResult(false);
+        }
+
+        {
+            std::unique_lock lock(mtx_);
+            timed_waiters_.fetch_add(1, std::memory_order_seq_cst);
+            auto ok = cond_var_.wait_until(lock, deadline, passed);
+            timed_waiters_.fetch_sub(1, std::memory_order_relaxed);
+            if (ok) {
+                return BarrierWaitResult(false);
+            }
+        }
+
+        return withdraw(*generation) ? std::nullopt : std::optional(BarrierWaitResult(false));
+    }
+
+    /// @brief Returns the count of threads to wait for in each generation.
+    [[nodiscard]] uint32_t count() const noexcept
+    {
+        return count_;
+    }
+
+private:
+    static uint32_t generation_of(uint64_t state) noexcept
+    {
+        return static_cast<uint32_t>(state >> GenerationShift);
+    }
+
+    /// @brief Arrives at the barrier.
+    /// @return The current generation to wait for, or an empty optional if the current thread is
+    /// the leader and the next generation has been started.
+    std::optional<uint32_t> arrive()
+    {
+        // CAS instead of an increment, so the leader resets the count within the same operation,
+        // and a concurrent withdraw can not be lost.
+        auto state = state_.load(std::memory_order_relaxed);
+        while (true) {
+            auto generation = generation_of(state);
+            if ((state & CountMask) + 1 < count_) {
+                if (state_.compare_exchange_weak(
+                        state, state + 1,
+                        std::memory_order_acq_rel,
+                        std::memory_order_relaxed)) {
+                    return generation;
+                }
+                continue;
+            }
+
+            // The leader: reset the count and start the next generation.
+            if (state_.compare_exchange_weak(
+                    state, uint64_t(generation + 1) << GenerationShift,
+                    std::memory_order_seq_cst,
+                    std::memory_order_relaxed)) {
+                break;
+            }
+        }
+        state_.notify_all();
+
+        // Pairs with the 'timed_waiters_' increment: either the timed waiter sees the new
+        // generation before parking, or we see the waiter.
+        if (timed_waiters_.load(std::memory_order_seq_cst) != 0) {
+            { std::lock_guard guard(mtx_); }
+            cond_var_.notify_all();
+        }
+
+        return {};
+    }
+
+    /// @brief Withdraws the arrival of the current thread after timeout.
+    /// @return false if the 'generation' has passed, and the arrival can not be withdrawn.
+    bool withdraw(uint32_t generation) noexcept
+    {
+        // Acquire: pairs with the leader if the generation has passed.
+        auto state = state_.load(std::memory_order_acquire);
+        while (generation_of(state) == generation) {
+            if (state_.compare_exchange_weak(
+                    state, state - 1,
+                    std::memory_order_acquire,
+                    std::memory_order_acquire)) {
+                return true;
+            }
+        }
+        return false;
+    }
+
+    /// @brief Spins with backoff until the 'generation' has passed or the backoff is completed.
+    /// @return The last loaded state.
+    uint64_t spin_wait(uint32_t generation) const noexcept
+    {
+        util::Backoff backoff;
+        auto state = state_.load(std::memory_order_acquire);
+        while (generation_of(state) == generation && !backoff.is_completed()) {
+            backoff.snooze();
+            state = state_.load(std::memory_order_acquire);
+        }
+        return state;
+    }
+
+    const uint32_t count_;
+
+    /// @brief The generation in the high 32 bits and the arrived count in the low 32 bits.
+    std::atomic<uint64_t> state_{0};
+    /// @brief Count of the threads parked by the timed wait.
+    std::atomic<size_t> timed_waiters_{0};
+    std::mutex mtx_;
+    std::condition_variable cond_var_;
+};
+
+}
+
+#endif //SYNC_CELL_BARRIER_HPP
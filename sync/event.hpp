@@ -0,0 +1,183 @@
+///
+/// @file  event.hpp
+/// @brief A manual-reset or auto-reset event for the signaling between threads.
+///
+
+#ifndef SYNC_CELL_EVENT_HPP
+#define SYNC_CELL_EVENT_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The event.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <atomic>
+#include <chrono>
+#include <cstddef>
+#include <cstdint>
+
+#include "shared/futex.hpp"
+#include "shared/select_hook.hpp"
+#include "util/back_off.hpp"
+
+
+namespace sc::sync {
+
+/// @brief How the @c Event is reset after it is set.
+enum class ResetMode
+{
+    /// @brief The event stays set until @c reset is called, and all waiters are released.
+    Manual,
+    /// @brief The event is reset automatically when a waiter is released, so each @c set releases
+    /// only one waiter.
+    Auto,
+};
+
+/// @brief An event which can be set and reset to signal the waiting threads.
+///
+/// The state is an atomic flag, so @c set and the waits on a set event do not take any lock. The
+/// waiters spin with backoff first, and then are parked on the futex of a sequence word, which each
+/// @c set increases (see "shared/futex.hpp").
+class Event
+{
+public:
+    explicit Event(ResetMode mode = ResetMode::Manual, bool set = false) noexcept : mode_(mode), set_(set) { }
+
+    Event(const Event &) = delete;
+
+    Event &operator=(const Event &) = delete;
+
+    [[nodiscard]] ResetMode mode() const noexcept
+    {
+        return mode_;
+    }
+
+    /// @brief Returns true if the event is set.
+    [[nodiscard]] bool is_set() const noexcept
+    {
+        return set_.load(std::memory_order_acquire);
+    }
+
+    /// @brief Returns the hook notified when the event is set, used by the @c sync::Select.
+    [[nodiscard]] sc::impl::SelectHook &select_hook() noexcept
+    {
+        return select_hook_;
+    }
+
+    /// @brief Sets the event. For the manual-reset event all waiters are released, and for the
+    /// auto-reset event one waiter (or the next waiting thread) is released.
+    void set()
+    {
+        set_.store(true, std::memory_order_release);
+
+        // Pairs with the 'waiters_' increment: either the waiter sees the new sequence before
+        // parking, or we see the waiter.
+        seq_.fetch_add(1, std::memory_order_seq_cst);
+        select_hook_.notify();
+        if (waiters_.load(std::memory_order_seq_cst) == 0) {
+            return;
+        }
+
+        if (mode_ == ResetMode::Manual) {
+            sc::impl::futex_wake_all(seq_);
+        } else {
+            sc::impl::futex_wake_one(seq_);
+        }
+    }
+
+    /// @brief Resets the event, the following waits are blocked until it is set again.
+    void reset() noexcept
+    {
+        set_.store(false, std::memory_order_relaxed);
+    }
+
+    /// @brief Returns true if the event is set without blocking. For the auto-reset event, the event
+    /// is reset if this returns true.
+    [[nodiscard]] bool try_wait() noexcept
+    {
+        if (mode_ == ResetMode::Manual) {
+            return is_set();
+        }
+
+        // Check before the exchange to avoid the write to the shared cache line.
+        return is_set() && set_.exchange(false, std::memory_order_acquire);
+    }
+
+    /// @brief Blocks the current thread until the event is set.
+    void wait()
+    {
+        if (spin_wait()) {
+            return;
+        }
+
+        waiters_.fetch_add(1, std::memory_order_seq_cst);
+        while (true) {
+            auto seq = seq_.load(std::memory_order_seq_cst);
+            if (try_wait()) {
+                break;
+            }
+            sc::impl::futex_wait(seq_, seq);
+        }
+        waiters_.fetch_sub(1, std::memory_order_relaxed);
+    }
+
+    /// @brief Blocks the current thread until the event is set or the 'timeout' duration has
+    /// elapsed.
+    /// @return false if timeout.
+    template<typename Rep, typename Period>
+    bool wait_timeout(const std::chrono::duration<Rep, Period> &timeout)
+    {
+        return wait_until(std::chrono::steady_clock::now() + timeout);
+    }
+
+    /// @brief Blocks the current thread until the event is set or the 'deadline' has been reached.
+    /// @return false if timeout.
+    template<typename Clock, typename Duration>
+    bool wait_until(const std::chrono::time_point<Clock, Duration> &deadline)
+    {
+        if (spin_wait()) {
+            return true;
+        }
+
+        waiters_.fetch_add(1, std::memory_order_seq_cst);
+        bool ok;
+        while (true) {
+            auto seq = seq_.load(std::memory_order_seq_cst);
+            ok = try_wait();
+            if (ok || !sc::impl::futex_wait_until(seq_, seq, deadline)) {
+                break;
+            }
+        }
+        waiters_.fetch_sub(1, std::memory_order_relaxed);
+
+        return ok || try_wait();
+    }
+
+private:
+    /// @brief Spins with backoff until the event is set or the backoff is completed.
+    bool spin_wait() noexcept
+    {
+        util::Backoff backoff;
+        while (!try_wait()) {
+            if (backoff.is_completed()) {
+                return false;
+            }
+            backoff.snooze();
+        }
+        return true;
+    }
+
+    const ResetMode mode_;
+    std::atomic<bool> set_;
+
+    /// @brief Increased by each @c set, the futex word of the parked threads.
+    std::atomic<uint32_t> seq_{0};
+    /// @brief Count of the threads that are (going to be) parked.
+    std::atomic<size_t> waiters_{0};
+    sc::impl::SelectHook select_hook_;
+};
+
+}
+
+#endif //SYNC_CELL_EVENT_HPP
@@ -0,0 +1,260 @@
+///
+/// @file  rate_limiter.hpp
+/// @brief A token bucket rate limiter, whose tokens are taken by one CAS, by blocking, or
+/// asynchronously by a C++20 coroutine.
+///
+
+#ifndef SYNC_CELL_RATE_LIMITER_HPP
+#define SYNC_CELL_RATE_LIMITER_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The rate_limiter.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <algorithm>
+#include <atomic>
+#include <chrono>
+#include <cstdint>
+#include <mutex>
+#include <thread>
+
+#if __cpp_impl_coroutine
+#include <coroutine>
+#endif
+
+
+namespace sc::sync {
+
+/// @brief A token bucket holding at most 'burst' tokens, refilled with 'rate' tokens per period,
+/// e.g. to throttle the producers feeding a queue:
+/// ``` cpp
+/// // 1000 jobs per second, at most 100 at once.
+/// sc::sync::RateLimiter limiter(1000, std::chrono::seconds(1), 100);
+/// // In the producers.
+/// limiter.acquire();
+/// queue.enqueue(job);
+/// ```
+///
+/// The tokens and the time of the last refill are packed in one atomic 64-bit word: the time at
+/// which the bucket is full again (in the nanoseconds since the limiter is created), each token
+/// taken moving it one refill interval later. So @c try_acquire is one CAS, no thread refills the
+/// bucket, and the count of the tokens is derived from the word and the clock (the generic cell
+/// rate algorithm).
+///
+/// @c acquire reserves the tokens even if the bucket does not hold them yet, and sleeps until they
+/// are refilled, so the waiters are served in the order of their reservations, and a count of
+/// tokens larger than the burst can be acquired. The reserved tokens are taken from the later
+/// acquisitions, e.g. a @c try_acquire fails until the reservations are refilled.
+///
+/// @note Nothing wakes the coroutines of @c acquire_async by itself: they are resumed inside
+/// @c poll, so a driver thread must call @c poll periodically. A coroutine is resumed on the
+/// calling thread, reschedule it after the @c co_await returns if it must run on a specific
+/// executor.
+class RateLimiter
+{
+public:
+    using clock_type = std::chrono::steady_clock;
+    using time_point = clock_type::time_point;
+    using duration = clock_type::duration;
+
+#if __cpp_impl_coroutine
+    class AcquireAwaiter;
+#endif
+
+    /// @param rate The count of the tokens refilled per 'period'.
+    /// @param period The period of the 'rate', one second by default.
+    /// @param burst The capacity of the bucket, or the 'rate' if it is 0. The bucket is full at first.
+    /// @note The refill interval of one token is rounded to the nanoseconds, so a rate of more than
+    /// one token per nanosecond is not supported.
+    explicit RateLimiter(uint64_t rate, duration period = std::chrono::seconds(1), uint64_t burst = 0)
+            : interval_(std::max<int64_t>(
+                      std::chrono::duration_cast<std::chrono::nanoseconds>(period).count()
+                      / static_cast<int64_t>(std::max<uint64_t>(rate, 1)), 1)),
+              burst_(burst == 0 ? std::max<uint64_t>(rate, 1) : burst),
+              start_(clock_type::now())
+    {
+    }
+
+    RateLimiter(const RateLimiter &) = delete;
+
+    RateLimiter &operator=(const RateLimiter &) = delete;
+
+    /// @brief Returns the capacity of the bucket.
+    [[nodiscard]] uint64_t burst() const noexcept
+    {
+        return burst_;
+    }
+
+    /// @brief Returns the count of the tokens in the bucket, which may be stale once returned if
+    /// other threads are acquiring.
+    [[nodiscard]] uint64_t available() const noexcept
+    {
+        auto now = elapsed();
+        auto full_at = std::max(full_at_.load(std::memory_order_relaxed), now);
+        auto capacity = burst_ * interval_;
+        return full_at - now >= capacity ? 0 : (capacity - (full_at - now)) / interval_;
+    }
+
+    /// @brief Takes 'count' tokens if the bucket holds them.
+    /// @return false if the bucket holds less tokens, nothing is taken then.
+    bool try_acquire(uint64_t count = 1) noexcept
+    {
+        auto now = elapsed();
+        auto full_at = full_at_.load(std::memory_order_relaxed);
+        while (true) {
+            auto next = std::max(full_at, now) + count * interval_;
+            if (next > now + burst_ * interval_) {
+                return false;
+            }
+            if (full_at_.compare_exchange_weak(full_at, next, std::memory_order_relaxed)) {
+                return true;
+            }
+        }
+    }
+
+    /// @brief Takes 'count' tokens, sleeps until the bucket is refilled with them if it does not
+    /// hold them.
+    void acquire(uint64_t count = 1)
+    {
+        auto ready_at = reserve(count);
+        if (ready_at > elapsed()) {
+            std::this_thread::sleep_until(start_ + std::chrono::nanoseconds(ready_at));
+        }
+    }
+
+#if __cpp_impl_coroutine
+    /// @brief Takes 'count' tokens asynchronously, see the note of the class about the driver.
+    /// ``` cpp
+    /// co_await limiter.acquire_async();
+    /// ```
+    [[nodiscard]] inline AcquireAwaiter acquire_async(uint64_t count = 1) noexcept;
+#endif
+
+    /// @brief Resumes the coroutines whose tokens have been refilled.
+    /// @return The count of the resumed coroutines.
+    size_t poll()
+    {
+        Waiter *ready = nullptr;
+#if __cpp_impl_coroutine
+        {
+            auto now = elapsed();
+            std::lock_guard guard(mtx_);
+            for (auto **link = &waiters_; *link != nullptr;) {
+                auto *waiter = *link;
+                if (waiter->ready_at <= now) {
+                    *link = waiter->next;
+                    waiter->next = ready;
+                    ready = waiter;
+                } else {
+                    link = &waiter->next;
+                }
+            }
+        }
+#endif
+        size_t count = 0;
+        while (ready != nullptr) {
+#if __cpp_impl_coroutine
+            // The coroutine may destroy the waiter after resumed.
+            auto handle = ready->handle;
+            ready = ready->next;
+            handle.resume();
+#endif
+            ++count;
+        }
+        return count;
+    }
+
+private:
+    struct Waiter
+    {
+        uint64_t ready_at;
+#if __cpp_impl_coroutine
+        std::coroutine_handle<> handle;
+#endif
+        Waiter *next = nullptr;
+    };
+
+    /// @brief Returns the nanoseconds since the limiter is created.
+    [[nodiscard]] uint64_t elapsed() const noexcept
+    {
+        return static_cast<uint64_t>(
+                std::chrono::duration_cast<std::chrono::nanoseconds>(clock_type::now() - start_).count());
+    }
+
+    /// @brief Takes 'count' tokens, whether the bucket holds them or not.
+    /// @return The time (in the nanoseconds since the limiter is created) when the tokens are
+    /// refilled, no later than now if the bucket holds them.
+    uint64_t reserve(uint64_t count) noexcept
+    {
+        auto now = elapsed();
+        auto full_at = full_at_.load(std::memory_order_relaxed);
+        while (true) {
+            auto next = std::max(full_at, now) + count * interval_;
+            if (full_at_.compare_exchange_weak(full_at, next, std::memory_order_relaxed)) {
+                auto capacity = burst_ * interval_;
+                return next > capacity ? next - capacity : 0;
+            }
+        }
+    }
+
+    /// @brief The refill interval of one token, in nanoseconds.
+    uint64_t interval_;
+    uint64_t burst_;
+    time_point start_;
+    /// @brief The time when the bucket is full again, in the nanoseconds since 'start_'.
+    std::atomic<uint64_t> full_at_{0};
+
+    std::mutex mtx_;
+    /// @brief The coroutines waiting for their tokens to be refilled, guarded by 'mtx_'.
+    Waiter *waiters_ = nullptr;
+};
+
+#if __cpp_impl_coroutine
+
+/// @brief The awaitable object returned by @c RateLimiter::acquire_async. The tokens are reserved
+/// when it is awaited.
+class RateLimiter::AcquireAwaiter
+{
+    friend class RateLimiter;
+
+    AcquireAwaiter(RateLimiter &limiter, uint64_t count) noexcept : limiter_(limiter), count_(count) { }
+
+public:
+    AcquireAwaiter(const AcquireAwaiter &) = delete;
+
+    AcquireAwaiter &operator=(const AcquireAwaiter &) = delete;
+
+    bool await_ready() noexcept
+    {
+        waiter_.ready_at = limiter_.reserve(count_);
+        return waiter_.ready_at <= limiter_.elapsed();
+    }
+
+    void await_suspend(std::coroutine_handle<> handle)
+    {
+        waiter_.handle = handle;
+        std::lock_guard guard(limiter_.mtx_);
+        waiter_.next = limiter_.waiters_;
+        limiter_.waiters_ = &waiter_;
+    }
+
+    void await_resume() noexcept { }
+
+private:
+    RateLimiter &limiter_;
+    uint64_t count_;
+    Waiter waiter_{};
+};
+
+inline RateLimiter::AcquireAwaiter RateLimiter::acquire_async(uint64_t count) noexcept
+{
+    return AcquireAwaiter(*this, count);
+}
+
+#endif
+
+}
+
+#endif //SYNC_CELL_RATE_LIMITER_HPP
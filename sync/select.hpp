@@ -0,0 +1,501 @@
+///
+/// @file  select.hpp
+/// @brief Waits on several queues, oneshot cells and events at the same time, and handles the
+/// first ready one, by blocking the thread or by a C++20 coroutine.
+///
+
+#ifndef SYNC_CELL_SELECT_HPP
+#define SYNC_CELL_SELECT_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The select.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <atomic>
+#include <cassert>
+#include <chrono>
+#include <condition_variable>
+#include <cstddef>
+#include <cstdint>
+#include <memory>
+#include <mutex>
+#include <optional>
+#include <type_traits>
+#include <utility>
+#include <vector>
+
+#if __cpp_impl_coroutine
+#include <coroutine>
+#endif
+
+#include "cell/oneshot_cell.hpp"
+#include "queue/blocking_queue.hpp"
+#include "shared/select_hook.hpp"
+#include "sync/event.hpp"
+
+
+namespace sc::sync {
+
+namespace impl {
+
+/// @brief A branch of a @c Select: a source and the handler of its value.
+class SelectBranch
+{
+public:
+    virtual ~SelectBranch() = default;
+
+    /// @brief Takes the value of the source if it is ready, and keeps it for @c invoke.
+    virtual bool try_take() = 0;
+
+    /// @brief Calls the handler with the taken value.
+    virtual void invoke() = 0;
+
+    [[nodiscard]] sc::impl::SelectHook &hook() const noexcept
+    {
+        return hook_;
+    }
+
+    /// @brief The node in the hook of the source while the select is waiting.
+    sc::impl::SelectEntry entry;
+
+protected:
+    explicit SelectBranch(sc::impl::SelectHook &hook) noexcept : hook_(hook) { }
+
+private:
+    sc::impl::SelectHook &hook_;
+};
+
+template<typename Queue, typename F>
+class QueueBranch final : public SelectBranch
+{
+    using value_type = typename Queue::value_type;
+
+public:
+    template<typename G>
+    QueueBranch(Queue &queue, G &&handler) : SelectBranch(queue.select_hook()), queue_(queue),
+                                             handler_(std::forward<G>(handler))
+    {
+    }
+
+    bool try_take() override
+    {
+        // Check the state before the dequeue, so the empty queue means drained.
+        auto drained = queue_.is_drained();
+        if (auto v = queue_.try_dequeue(); v) {
+            value_.emplace(std::in_place, *std::move(v));
+            return true;
+        }
+        if (drained) {
+            value_.emplace();
+            return true;
+        }
+        return false;
+    }
+
+    void invoke() override
+    {
+        auto v = *std::move(value_);
+        value_.reset();
+        handler_(std::move(v));
+    }
+
+private:
+    Queue &queue_;
+    F handler_;
+    /// @brief The dequeued item, or an empty one if the queue is drained.
+    std::optional<std::optional<value_type>> value_;
+};
+
+template<typename T, typename F>
+class OneshotBranch final : public SelectBranch
+{
+public:
+    template<typename G>
+    OneshotBranch(OneshotCell<T> &cell, G &&handler) : SelectBranch(cell.select_hook()), cell_(cell),
+                                                       handler_(std::forward<G>(handler))
+    {
+    }
+
+    bool try_take() override
+    {
+        value_ = cell_.try_recv();
+        return value_.has_value();
+    }
+
+    void invoke() override
+    {
+        auto v = *std::move(value_);
+        value_.reset();
+        handler_(std::move(v));
+    }
+
+private:
+    OneshotCell<T> &cell_;
+    F handler_;
+    std::optional<T> value_;
+};
+
+template<typename F>
+class EventBranch final : public SelectBranch
+{
+public:
+    template<typename G>
+    EventBranch(Event &event, G &&handler) : SelectBranch(event.select_hook()), event_(event),
+                                             handler_(std::forward<G>(handler))
+    {
+    }
+
+    bool try_take() override
+    {
+        return event_.try_wait();
+    }
+
+    void invoke() override
+    {
+        handler_();
+    }
+
+private:
+    Event &event_;
+    F handler_;
+};
+
+/// @brief The signal of a blocked thread.
+class ThreadSignal final : public sc::impl::SelectSignal
+{
+public:
+    void wake() noexcept override
+    {
+        // Notify under the lock, the selector may destroy the signal once it sees the 'woken_'.
+        std::lock_guard guard(mtx_);
+        woken_ = true;
+        cond_var_.notify_one();
+    }
+
+    void wait()
+    {
+        std::unique_lock lock(mtx_);
+        cond_var_.wait(lock, [this] { return woken_; });
+    }
+
+    /// @return false if timeout.
+    template<typename Clock, typename Duration>
+    bool wait_until(const std::chrono::time_point<Clock, Duration> &deadline)
+    {
+        std::unique_lock lock(mtx_);
+        return cond_var_.wait_until(lock, deadline, [this] { return woken_; });
+    }
+
+private:
+    std::mutex mtx_;
+    std::condition_variable cond_var_;
+    bool woken_ = false;
+};
+
+}
+
+/// @brief Waits on several sources at the same time, and runs the handler of the first ready one,
+/// like the @c select! of crossbeam-channel or tokio.
+///
+/// The branches are added by the builder methods: @c recv of a @c BlockingQueue or a
+/// @c OneshotCell, and @c wait of an @c Event. Then @c run blocks the thread (or @c co_await
+/// @c run_async suspends the coroutine) until a source is ready, takes its value, and calls the
+/// handler of the branch with it. When several sources are ready, the checks start from a rotating
+/// branch, so no branch is starved.
+///
+/// A waiting select links its nodes into the hooks of the sources, which are notified by the
+/// enqueue, the send and the set, so the select does not poll.
+///
+/// @example
+/// ``` cpp
+/// sc::sync::Select select;
+/// select.recv(jobs, [](std::optional<Job> job) { if (job) { job->run(); } })
+///       .wait(stop, [&] { running = false; });
+/// while (running) { select.run(); }
+/// ```
+/// @note A queue branch gets an empty optional when the queue is closed and drained, which is
+/// always ready from then on. A select is used by one thread or coroutine at a time, and a oneshot
+/// cell must not be received by other means while it is selected. A suspended coroutine is resumed
+/// on the notifying thread.
+class Select
+{
+    using BranchPtr = std::unique_ptr<impl::SelectBranch>;
+
+public:
+#if __cpp_impl_coroutine
+    class RunAwaiter;
+#endif
+
+    Select() = default;
+
+    Select(const Select &) = delete;
+
+    Select &operator=(const Select &) = delete;
+
+    /// @brief Adds a branch receiving from the 'queue', the 'handler' is called with a
+    /// @c std::optional of the item, which is empty if the queue is closed and drained.
+    template<typename Queue, typename Backoff, typename F>
+    Select &recv(BlockingQueue<Queue, Backoff, false> &queue, F &&handler)
+    {
+        using Branch = impl::QueueBranch<BlockingQueue<Queue, Backoff, false>, std::decay_t<F>>;
+        branches_.push_back(std::make_unique<Branch>(queue, std::forward<F>(handler)));
+        return *this;
+    }
+
+    /// @brief Adds a branch receiving the value of the 'cell', the 'handler' is called with it.
+    template<typename T, typename F>
+    Select &recv(OneshotCell<T> &cell, F &&handler)
+    {
+        using Branch = impl::OneshotBranch<T, std::decay_t<F>>;
+        branches_.push_back(std::make_unique<Branch>(cell, std::forward<F>(handler)));
+        return *this;
+    }
+
+    /// @brief Adds a branch waiting for the 'event' to be set, the 'handler' is called without
+    /// argument. An auto-reset event is reset when the branch is taken.
+    template<typename F>
+    Select &wait(Event &event, F &&handler)
+    {
+        using Branch = impl::EventBranch<std::decay_t<F>>;
+        branches_.push_back(std::make_unique<Branch>(event, std::forward<F>(handler)));
+        return *this;
+    }
+
+    /// @brief Returns the count of the branches.
+    [[nodiscard]] size_t size() const noexcept
+    {
+        return branches_.size();
+    }
+
+    /// @brief Runs the handler of a ready branch without blocking.
+    /// @return The index of the branch in the adding order, or an empty optional if none is ready.
+    std::optional<size_t> try_run()
+    {
+        if (auto index = take_any(); index) {
+            return invoke(*index);
+        }
+        return {};
+    }
+
+    /// @brief Blocks the current thread until a branch is ready, and runs its handler.
+    /// @return The index of the branch in the adding order.
+    size_t run()
+    {
+        assert(!branches_.empty());
+        while (true) {
+            if (auto index = take_any(); index) {
+                return invoke(*index);
+            }
+
+            impl::ThreadSignal signal;
+            attach(signal);
+            auto index = take_any();
+            if (!index) {
+                signal.wait();
+            } else if (!signal.try_fire()) {
+                // A source is waking the signal, wait for it before the signal is destroyed.
+                signal.wait();
+            }
+            detach();
+            if (index) {
+                return invoke(*index);
+            }
+        }
+    }
+
+    /// @brief Blocks the current thread until a branch is ready or the 'timeout' duration has
+    /// elapsed, and runs the handler of the ready branch.
+    /// @return The index of the branch, or an empty optional if timeout.
+    template<typename Rep, typename Period>
+    std::optional<size_t> run_for(const std::chrono::duration<Rep, Period> &timeout)
+    {
+        return run_until(std::chrono::steady_clock::now() + timeout);
+    }
+
+    /// @brief Blocks the current thread until a branch is ready or the 'deadline' has been reached,
+    /// and runs the handler of the ready branch.
+    /// @return The index of the branch, or an empty optional if timeout.
+    template<typename Clock, typename Duration>
+    std::optional<size_t> run_until(const std::chrono::time_point<Clock, Duration> &deadline)
+    {
+        while (true) {
+            if (auto index = take_any(); index) {
+                return invoke(*index);
+            }
+
+            impl::ThreadSignal signal;
+            attach(signal);
+            auto index = take_any();
+            auto woken = !index && signal.wait_until(deadline);
+            if (!woken && !signal.try_fire()) {
+                signal.wait();
+            }
+            detach();
+            if (index) {
+                return invoke(*index);
+            }
+            if (!woken) {
+                return try_run();
+            }
+        }
+    }
+
+#if __cpp_impl_coroutine
+    /// @brief Waits asynchronously until a branch is ready, and runs its handler.
+    /// @example
+    /// ``` cpp
+    /// auto index = co_await select.run_async();
+    /// ```
+    /// @return The index of the branch in the adding order.
+    [[nodiscard]] inline RunAwaiter run_async() noexcept;
+#endif
+
+private:
+    /// @brief Takes the value of a ready branch, the checks start from a rotating branch.
+    std::optional<size_t> take_any()
+    {
+        auto count = branches_.size();
+        auto start = start_++;
+        for (size_t i = 0; i < count; ++i) {
+            auto index = (start + i) % count;
+            if (branches_[index]->try_take()) {
+                return index;
+            }
+        }
+        return {};
+    }
+
+    size_t invoke(size_t index)
+    {
+        branches_[index]->invoke();
+        return index;
+    }
+
+    /// @brief Links the 'signal' into the hooks of all sources, the sources must be checked again
+    /// after this.
+    void attach(sc::impl::SelectSignal &signal) noexcept
+    {
+        for (auto &branch: branches_) {
+            branch->entry.signal = &signal;
+            branch->hook().attach(branch->entry);
+        }
+    }
+
+    void detach() noexcept
+    {
+        for (auto &branch: branches_) {
+            branch->hook().detach(branch->entry);
+        }
+    }
+
+    std::vector<BranchPtr> branches_;
+    size_t start_ = 0;
+};
+
+#if __cpp_impl_coroutine
+
+/// @brief The awaitable object returned by @c Select::run_async.
+///
+/// A notification may arrive while the coroutine is being suspended, so the suspension and the
+/// wake-up hand over the select by the 'state_': the one which comes last goes on checking the
+/// sources.
+class Select::RunAwaiter final : public sc::impl::SelectSignal
+{
+    friend class Select;
+
+    enum State : uint8_t
+    {
+        Suspending,
+        Suspended,
+        Woken,
+    };
+
+    explicit RunAwaiter(Select &select) noexcept : select_(select) { }
+
+public:
+    RunAwaiter(const RunAwaiter &) = delete;
+
+    RunAwaiter &operator=(const RunAwaiter &) = delete;
+
+    bool await_ready()
+    {
+        index_ = select_.take_any();
+        return index_.has_value();
+    }
+
+    bool await_suspend(std::coroutine_handle<> handle)
+    {
+        handle_ = handle;
+        return suspend();
+    }
+
+    size_t await_resume()
+    {
+        return select_.invoke(*index_);
+    }
+
+    void wake() noexcept override
+    {
+        if (state_.exchange(Woken, std::memory_order_acq_rel) == Suspending) {
+            // The 'suspend' is still running, it sees the 'Woken' and goes on.
+            return;
+        }
+
+        select_.detach();
+        if (index_ || (index_ = select_.take_any())) {
+            handle_.resume();
+            return;
+        }
+        reset();
+        if (!suspend()) {
+            handle_.resume();
+        }
+    }
+
+private:
+    /// @brief Links the signal into the sources and checks them again.
+    /// @return false if a branch is taken, and the coroutine goes on.
+    bool suspend()
+    {
+        while (true) {
+            state_.store(Suspending, std::memory_order_relaxed);
+            select_.attach(*this);
+            if ((index_ = select_.take_any())) {
+                if (try_fire() || state_.exchange(Suspended, std::memory_order_acq_rel) == Woken) {
+                    select_.detach();
+                    return false;
+                }
+                // The firing source resumes the coroutine by the 'wake'.
+                return true;
+            }
+            if (state_.exchange(Suspended, std::memory_order_acq_rel) != Woken) {
+                return true;
+            }
+
+            // Fired while attaching or checking.
+            select_.detach();
+            if ((index_ = select_.take_any())) {
+                return false;
+            }
+            reset();
+        }
+    }
+
+    Select &select_;
+    std::optional<size_t> index_;
+    std::coroutine_handle<> handle_;
+    std::atomic<uint8_t> state_{Suspending};
+};
+
+Select::RunAwaiter Select::run_async() noexcept
+{
+    return RunAwaiter(*this);
+}
+
+#endif
+
+}
+
+#endif //SYNC_CELL_SELECT_HPP
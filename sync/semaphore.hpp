@@ -0,0 +1,414 @@
+///
+/// @file  semaphore.hpp
+/// @brief A counting semaphore whose permits can be acquired by blocking the thread, with a timeout,
+/// or asynchronously by a C++20 coroutine.
+///
+
+#ifndef SYNC_CELL_SEMAPHORE_HPP
+#define SYNC_CELL_SEMAPHORE_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The semaphore.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <atomic>
+#include <chrono>
+#include <cstddef>
+#include <cstdint>
+#include <mutex>
+#include <optional>
+#include <utility>
+
+#if __cpp_impl_coroutine
+#include <coroutine>
+#endif
+
+#include "shared/futex.hpp"
+
+
+namespace sc::sync {
+
+class Semaphore;
+
+/// @brief The RAII guard of the permits acquired from a @c Semaphore, the permits are released
+/// when the guard is destroyed.
+class SemaphorePermit
+{
+    friend class Semaphore;
+
+    SemaphorePermit(Semaphore &semaphore, size_t count) noexcept : semaphore_(&semaphore), count_(count) { }
+
+public:
+    SemaphorePermit(SemaphorePermit &&other) noexcept
+            : semaphore_(std::exchange(other.semaphore_, nullptr)), count_(std::exchange(other.count_, 0))
+    {
+    }
+
+    SemaphorePermit &operator=(SemaphorePermit &&other) noexcept
+    {
+        if (this != &other) {
+            release();
+            semaphore_ = std::exchange(other.semaphore_, nullptr);
+            count_ = std::exchange(other.count_, 0);
+        }
+        return *this;
+    }
+
+    ~SemaphorePermit()
+    {
+        release();
+    }
+
+    /// @brief Returns the count of the permits held by the guard.
+    [[nodiscard]] size_t count() const noexcept
+    {
+        return count_;
+    }
+
+    /// @brief Forgets the permits without releasing them, so the semaphore loses the permits.
+    void forget() noexcept
+    {
+        semaphore_ = nullptr;
+        count_ = 0;
+    }
+
+private:
+    inline void release() noexcept;
+
+    Semaphore *semaphore_;
+    size_t count_;
+};
+
+/// @brief A counting semaphore, which can be used to bound the concurrency around a queue.
+///
+/// The permits are taken by a CAS on the counter if available. Otherwise, the acquirer (a thread or
+/// a coroutine) is registered in a FIFO waiter list, and the released permits are handed off to the
+/// waiters in order. A new acquirer may still take the released permits before the waiters (the
+/// semaphore is not strictly fair), but a waiter never misses the permits released after it is
+/// registered. A blocked thread is parked on the futex of its own waiter, so a release wakes up
+/// exactly the granted threads (see "shared/futex.hpp").
+///
+/// @note A suspended coroutine is resumed on the releasing thread inside the @c release call (or
+/// the destructor of the @c SemaphorePermit). If the coroutine must run on a specific executor,
+/// reschedule it after the @c co_await returns.
+class Semaphore
+{
+    struct Waiter
+    {
+        size_t count;
+        Waiter *next = nullptr;
+        /// @brief Set to 1 when the permits are handed off to the waiter, with 'mtx_' locked. It is
+        /// the futex word of a blocked thread.
+        std::atomic<uint32_t> granted{0};
+#if __cpp_impl_coroutine
+        /// @brief The suspended coroutine, or null for a blocked thread.
+        std::coroutine_handle<> handle;
+#endif
+    };
+
+    /// @brief The result of handing off the permits to the waiters.
+    struct Granted
+    {
+        /// @brief The granted coroutine waiters to resume, linked by their 'next'.
+        Waiter *coroutines = nullptr;
+    };
+
+public:
+#if __cpp_impl_coroutine
+    class AcquireAwaiter;
+#endif
+
+    explicit Semaphore(size_t permits) noexcept : permits_(permits) { }
+
+    Semaphore(const Semaphore &) = delete;
+
+    Semaphore &operator=(const Semaphore &) = delete;
+
+    /// @brief Returns the count of the currently available permits.
+    [[nodiscard]] size_t available_permits() const noexcept
+    {
+        return permits_.load(std::memory_order_relaxed);
+    }
+
+    /// @brief Acquires the 'count' permits without blocking.
+    /// @return An empty optional if the permits are not available.
+    std::optional<SemaphorePermit> try_acquire(size_t count = 1) noexcept
+    {
+        if (try_take(count)) {
+            return SemaphorePermit(*this, count);
+        }
+        return {};
+    }
+
+    /// @brief Acquires the 'count' permits, blocks the current thread until they are available.
+    SemaphorePermit acquire(size_t count = 1)
+    {
+        if (try_take(count)) {
+            return {*this, count};
+        }
+
+        Waiter waiter{count};
+        bool registered;
+        {
+            std::lock_guard guard(mtx_);
+            registered = register_waiter(waiter);
+        }
+        if (registered) {
+            while (waiter.granted.load(std::memory_order_acquire) == 0) {
+                sc::impl::futex_wait(waiter.granted, 0);
+            }
+        }
+
+        return {*this, count};
+    }
+
+    /// @brief Acquires the 'count' permits, blocks the current thread until they are available or
+    /// the 'timeout' duration has elapsed.
+    /// @return An empty optional if timeout.
+    template<typename Rep, typename Period>
+    std::optional<SemaphorePermit> acquire_timeout(const std::chrono::duration<Rep, Period> &timeout, size_t count = 1)
+    {
+        return acquire_until(std::chrono::steady_clock::now() + timeout, count);
+    }
+
+    /// @brief Acquires the 'count' permits, blocks the current thread until they are available or
+    /// the 'deadline' has been reached.
+    /// @return An empty optional if timeout.
+    template<typename Clock, typename Duration>
+    std::optional<SemaphorePermit> acquire_until(const std::chrono::time_point<Clock, Duration> &deadline, size_t count = 1)
+    {
+        if (try_take(count)) {
+            return SemaphorePermit(*this, count);
+        }
+
+        Waiter waiter{count};
+        {
+            std::lock_guard guard(mtx_);
+            if (!register_waiter(waiter)) {
+                return SemaphorePermit(*this, count);
+            }
+        }
+        while (waiter.granted.load(std::memory_order_acquire) == 0) {
+            if (!sc::impl::futex_wait_until(waiter.granted, 0, deadline)) {
+                break;
+            }
+        }
+
+        Granted granted;
+        {
+            std::lock_guard guard(mtx_);
+            // The permits may have been handed off after the timeout.
+            if (waiter.granted.load(std::memory_order_relaxed) != 0) {
+                return SemaphorePermit(*this, count);
+            }
+
+            // Timeout: the waiters queued behind may be satisfied by the available permits now.
+            unregister_waiter(waiter);
+            granted = grant_waiters();
+        }
+        wake(granted);
+
+        return {};
+    }
+
+#if __cpp_impl_coroutine
+    /// @brief Acquires the 'count' permits asynchronously.
+    /// @example
+    /// ``` cpp
+    /// auto permit = co_await semaphore.acquire_async();
+    /// ```
+    [[nodiscard]] inline AcquireAwaiter acquire_async(size_t count = 1) noexcept;
+#endif
+
+    /// @brief Adds the 'count' permits to the semaphore, and hands them off to the waiters.
+    void release(size_t count = 1)
+    {
+        permits_.fetch_add(count, std::memory_order_release);
+
+        // Pairs with the waiter registration: either the waiter takes the permits in its
+        // 'register_waiter', or we see the waiter in the list.
+        std::atomic_thread_fence(std::memory_order_seq_cst);
+        if (waiter_count_.load(std::memory_order_relaxed) == 0) {
+            return;
+        }
+
+        Granted granted;
+        {
+            std::lock_guard guard(mtx_);
+            granted = grant_waiters();
+        }
+        wake(granted);
+    }
+
+private:
+    bool try_take(size_t count) noexcept
+    {
+        auto permits = permits_.load(std::memory_order_relaxed);
+        while (permits >= count) {
+            if (permits_.compare_exchange_weak(
+                    permits, permits - count,
+                    std::memory_order_acquire,
+                    std::memory_order_relaxed)) {
+                return true;
+            }
+        }
+        return false;
+    }
+
+    /// @brief Appends the 'waiter' to the list. Must be called with 'mtx_' locked.
+    /// @return false if the permits are taken, and the 'waiter' is not registered.
+    bool register_waiter(Waiter &waiter) noexcept
+    {
+        waiter_count_.fetch_add(1, std::memory_order_seq_cst);
+
+        // Check again after registration to avoid missing the permits released concurrently. The
+        // waiters behind others wait in order.
+        if (head_ == nullptr && try_take(waiter.count)) {
+            waiter_count_.fetch_sub(1, std::memory_order_relaxed);
+            return false;
+        }
+
+        // The 'waiter' on the stack of a blocked thread is always removed from the list before
+        // the thread returns, but GCC can not see it.
+#if defined(__GNUC__) && !defined(__clang__) && __GNUC__ >= 12
+#pragma GCC diagnostic push
+#pragma GCC diagnostic ignored "-Wdangling-pointer"
+#endif
+        if (tail_ == nullptr) {
+            head_ = &waiter;
+        } else {
+            tail_->next = &waiter;
+        }
+        tail_ = &waiter;
+#if defined(__GNUC__) && !defined(__clang__) && __GNUC__ >= 12
+#pragma GCC diagnostic pop
+#endif
+
+        return true;
+    }
+
+    /// @brief Removes a not granted 'waiter' from the list. Must be called with 'mtx_' locked.
+    void unregister_waiter(Waiter &waiter) noexcept
+    {
+        Waiter *prev = nullptr;
+        for (auto *w = head_; w != &waiter; w = w->next) {
+            prev = w;
+        }
+
+        (prev == nullptr ? head_ : prev->next) = waiter.next;
+        if (tail_ == &waiter) {
+            tail_ = prev;
+        }
+        waiter_count_.fetch_sub(1, std::memory_order_relaxed);
+    }
+
+    /// @brief Hands off the available permits to the waiters in order. Must be called with 'mtx_'
+    /// locked.
+    Granted grant_waiters() noexcept
+    {
+        Granted granted;
+        while (head_ != nullptr && try_take(head_->count)) {
+            auto *waiter = head_;
+            head_ = waiter->next;
+            if (head_ == nullptr) {
+                tail_ = nullptr;
+            }
+            waiter_count_.fetch_sub(1, std::memory_order_relaxed);
+
+#if __cpp_impl_coroutine
+            if (waiter->handle) {
+                waiter->granted.store(1, std::memory_order_relaxed);
+                waiter->next = granted.coroutines;
+                granted.coroutines = waiter;
+                continue;
+            }
+#endif
+            // A blocked thread may return as soon as it sees the grant, the wake-up does not touch
+            // its waiter.
+            waiter->granted.store(1, std::memory_order_release);
+            sc::impl::futex_wake_one(waiter->granted);
+        }
+
+        return granted;
+    }
+
+    /// @brief Resumes the granted coroutines, the threads have been woken up by @c grant_waiters.
+    /// Must be called without 'mtx_' locked.
+    void wake([[maybe_unused]] const Granted &granted)
+    {
+#if __cpp_impl_coroutine
+        for (auto *waiter = granted.coroutines; waiter != nullptr;) {
+            // The coroutine may destroy the waiter after resumed.
+            auto handle = waiter->handle;
+            waiter = waiter->next;
+            handle.resume();
+        }
+#endif
+    }
+
+    std::atomic<size_t> permits_;
+
+    /// @brief Count of the waiters in the list. It is used to skip the lock in 'release'.
+    std::atomic<size_t> waiter_count_{0};
+    /// @brief Guards the FIFO waiter list.
+    std::mutex mtx_;
+    Waiter *head_ = nullptr;
+    Waiter *tail_ = nullptr;
+};
+
+void SemaphorePermit::release() noexcept
+{
+    if (semaphore_ != nullptr && count_ != 0) {
+        semaphore_->release(count_);
+    }
+}
+
+#if __cpp_impl_coroutine
+
+/// @brief The awaitable object returned by @c Semaphore::acquire_async.
+class Semaphore::AcquireAwaiter
+{
+    friend class Semaphore;
+
+    AcquireAwaiter(Semaphore &semaphore, size_t count) noexcept : semaphore_(semaphore), waiter_{count} { }
+
+public:
+    AcquireAwaiter(const AcquireAwaiter &) = delete;
+
+    AcquireAwaiter &operator=(const AcquireAwaiter &) = delete;
+
+    bool await_ready() noexcept
+    {
+        return semaphore_.try_take(waiter_.count);
+    }
+
+    bool await_suspend(std::coroutine_handle<> handle) noexcept
+    {
+        waiter_.handle = handle;
+
+        std::lock_guard guard(semaphore_.mtx_);
+        return semaphore_.register_waiter(waiter_);
+    }
+
+    SemaphorePermit await_resume() noexcept
+    {
+        return {semaphore_, waiter_.count};
+    }
+
+private:
+    Semaphore &semaphore_;
+    Waiter waiter_;
+};
+
+Semaphore::AcquireAwaiter Semaphore::acquire_async(size_t count) noexcept
+{
+    return {*this, count};
+}
+
+#endif
+
+}
+
+#endif //SYNC_CELL_SEMAPHORE_HPP
@@ -0,0 +1,169 @@
+///
+/// @file  wait_group.hpp
+/// @brief Wait for a count of tasks to finish: a Go-style @c WaitGroup and a one-shot
+/// @c CountdownLatch.
+///
+
+#ifndef SYNC_CELL_WAIT_GROUP_HPP
+#define SYNC_CELL_WAIT_GROUP_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The wait_group.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <atomic>
+#include <cassert>
+#include <cstddef>
+
+#include "util/back_off.hpp"
+
+
+namespace sc::sync {
+
+namespace impl {
+
+/// @brief A counter whose waiters are woken up when it reaches zero.
+class ZeroWaitCounter
+{
+public:
+    explicit ZeroWaitCounter(size_t count) noexcept : count_(count) { }
+
+    ZeroWaitCounter(const ZeroWaitCounter &) = delete;
+
+    ZeroWaitCounter &operator=(const ZeroWaitCounter &) = delete;
+
+    void add(size_t n) noexcept
+    {
+        count_.fetch_add(n, std::memory_order_relaxed);
+    }
+
+    void sub(size_t n) noexcept
+    {
+        // Release: the work before the decrement is visible to the waiters.
+        auto prev = count_.fetch_sub(n, std::memory_order_acq_rel);
+        assert(prev >= n && "the counter is decreased below zero");
+        if (prev == n) {
+            count_.notify_all();
+        }
+    }
+
+    [[nodiscard]] size_t count() const noexcept
+    {
+        return count_.load(std::memory_order_acquire);
+    }
+
+    /// @brief Spins with backoff, and then parks the current thread until the count is zero.
+    void wait() const noexcept
+    {
+        util::Backoff backoff;
+        auto count = count_.load(std::memory_order_acquire);
+        while (count != 0) {
+            if (backoff.is_completed()) {
+                count_.wait(count, std::memory_order_acquire);
+            } else {
+                backoff.snooze();
+            }
+            count = count_.load(std::memory_order_acquire);
+        }
+    }
+
+private:
+    std::atomic<size_t> count_;
+};
+
+}
+
+/// @brief Waits for a collection of tasks to finish, like the Go @c sync.WaitGroup.
+///
+/// The coordinator calls @c add before starting the tasks, each task calls @c done when finished,
+/// and @c wait blocks until all added tasks are done. The group can be reused once @c wait returns.
+/// @note @c add must happen before the @c wait which is expected to wait for the added tasks, and
+/// calling @c done more times than @c add is an undefined behavior.
+class WaitGroup
+{
+public:
+    WaitGroup() noexcept = default;
+
+    WaitGroup(const WaitGroup &) = delete;
+
+    WaitGroup &operator=(const WaitGroup &) = delete;
+
+    /// @brief Adds 'n' tasks to wait for.
+    void add(size_t n = 1) noexcept
+    {
+        counter_.add(n);
+    }
+
+    /// @brief Marks one task as done.
+    void done() noexcept
+    {
+        counter_.sub(1);
+    }
+
+    /// @brief Blocks the current thread until all added tasks are done.
+    void wait() const noexcept
+    {
+        counter_.wait();
+    }
+
+    /// @brief Returns the count of tasks not done yet.
+    [[nodiscard]] size_t count() const noexcept
+    {
+        return counter_.count();
+    }
+
+private:
+    impl::ZeroWaitCounter counter_{0};
+};
+
+/// @brief A one-shot latch which is opened after it has been counted down 'n' times. Unlike the
+/// @c WaitGroup, the count can not be increased.
+class CountdownLatch
+{
+public:
+    explicit CountdownLatch(size_t n) noexcept : counter_(n) { }
+
+    CountdownLatch(const CountdownLatch &) = delete;
+
+    CountdownLatch &operator=(const CountdownLatch &) = delete;
+
+    /// @brief Decreases the count by 'n', opens the latch when the count reaches zero.
+    void count_down(size_t n = 1) noexcept
+    {
+        counter_.sub(n);
+    }
+
+    /// @brief Returns true if the latch has been opened.
+    [[nodiscard]] bool try_wait() const noexcept
+    {
+        return counter_.count() == 0;
+    }
+
+    /// @brief Blocks the current thread until the latch is opened.
+    void wait() const noexcept
+    {
+        counter_.wait();
+    }
+
+    /// @brief Counts down, and then waits for the latch to be opened.
+    void arrive_and_wait(size_t n = 1) noexcept
+    {
+        count_down(n);
+        wait();
+    }
+
+    /// @brief Returns the remaining count.
+    [[nodiscard]] size_t count() const noexcept
+    {
+        return counter_.count();
+    }
+
+private:
+    impl::ZeroWaitCounter counter_;
+};
+
+}
+
+#endif //SYNC_CELL_WAIT_GROUP_HPP
@@ -0,0 +1,154 @@
+///
+/// @file  allocator_test.cpp
+/// @brief Test for the allocator parameter of the queues, the stack and the object cache pool.
+///
+
+#include "hazard/hazard.hpp"
+#include "queue/mpmc_array_queue.hpp"
+#include "queue/mpmc_bounded_queue.hpp"
+#include "queue/mpmc_list_queue.hpp"
+#include "queue/mpsc_list_queue.hpp"
+#include "queue/spsc_ring_buffer.hpp"
+#include "stack/lock_free_stack.hpp"
+
+#include <atomic>
+#include <memory>
+#include <new>
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+/// @brief The bookkeeping of an arena, shared by the copies of the allocator.
+struct Arena
+{
+    std::atomic<int64_t> allocations{0};
+    std::atomic<int64_t> live{0};
+};
+
+/// @brief A stateful allocator counting the allocations in its arena.
+template<typename T>
+struct ArenaAllocator
+{
+    using value_type = T;
+
+    Arena *arena;
+
+    explicit ArenaAllocator(Arena *arena) noexcept : arena(arena) { }
+
+    template<typename U>
+    ArenaAllocator(const ArenaAllocator<U> &other) noexcept : arena(other.arena) { }
+
+    T *allocate(size_t n)
+    {
+        arena->allocations.fetch_add(1, std::memory_order_relaxed);
+        arena->live.fetch_add(1, std::memory_order_relaxed);
+        return static_cast<T *>(::operator new(n * sizeof(T), std::align_val_t(alignof(T))));
+    }
+
+    void deallocate(T *p, size_t n) noexcept
+    {
+        arena->live.fetch_sub(1, std::memory_order_relaxed);
+        ::operator delete(p, n * sizeof(T), std::align_val_t(alignof(T)));
+    }
+
+    template<typename U>
+    bool operator==(const ArenaAllocator<U> &other) const noexcept
+    {
+        return arena == other.arena;
+    }
+};
+
+/// @brief Enqueues and dequeues some values, then prints the allocations in the arena.
+template<typename Queue>
+void run_queue(const std::string &name)
+{
+    Arena arena;
+    {
+        Queue queue{ArenaAllocator<uint64_t>(&arena)};
+        uint64_t sum = 0;
+        for (uint64_t i = 0; i < 100; ++i) {
+            queue.enqueue(i);
+        }
+        while (auto v = queue.try_dequeue()) {
+            sum += *v;
+        }
+        std::cout << name << ": sum: " << sum << ", same arena: "
+                  << (queue.get_allocator() == ArenaAllocator<uint64_t>(&arena)) << ", allocated: "
+                  << (arena.allocations.load() > 0);
+    }
+    std::cout << ", live after destroyed: " << arena.live.load() << std::endl;
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    run_queue<sc::mpmc::LinkedListQueue<uint64_t, 0, ArenaAllocator<uint64_t>>>("mpmc::LinkedListQueue");
+    run_queue<sc::mpmc::LinkedListQueue<uint64_t, 4, ArenaAllocator<uint64_t>>>("mpmc::LinkedListQueue pooled");
+    run_queue<sc::mpsc::LinkedListQueue<uint64_t, 0, ArenaAllocator<uint64_t>>>("mpsc::LinkedListQueue");
+    run_queue<sc::mpmc::ArrayListQueue<uint64_t, sc::util::Backoff, ArenaAllocator<uint64_t>>>("ArrayListQueue");
+
+    {
+        Arena arena;
+        {
+            using Queue = sc::mpmc::ResizableBoundedQueue<uint64_t, sc::util::Backoff, ArenaAllocator<uint64_t>>;
+            Queue queue(4, sc::mpmc::Overflow::Block, {}, ArenaAllocator<uint64_t>(&arena));
+            queue.try_enqueue(1);
+            queue.reserve(16);
+            std::cout << "BoundedQueue: value: " << *queue.try_dequeue() << ", allocations: "
+                      << arena.allocations.load();
+        }
+        std::cout << ", live after destroyed: " << arena.live.load() << std::endl;
+    }
+
+    {
+        Arena arena;
+        {
+            sc::spsc::RingBuffer<uint64_t, sc::util::Backoff, ArenaAllocator<uint64_t>> buffer(
+                    4, ArenaAllocator<uint64_t>(&arena));
+            auto [producer, consumer] = buffer.split();
+            producer.try_enqueue(7);
+            std::cout << "RingBuffer: value: " << *consumer.try_dequeue() << ", allocations: "
+                      << arena.allocations.load();
+        }
+        std::cout << ", live after destroyed: " << arena.live.load() << std::endl;
+    }
+
+    // The popped nodes are destroyed by the reclamation backend with their own allocator copies.
+    {
+        constexpr uint32_t ThreadCount = 2;
+        Arena arena;
+        {
+            sc::stack::LockFreeStack<uint64_t, sc::hazard::Reclaimer, ArenaAllocator<uint64_t>> stack{
+                    ArenaAllocator<uint64_t>(&arena)};
+            std::atomic<uint64_t> popped{0};
+            std::vector<std::thread> threads;
+            for (uint32_t i = 0; i < ThreadCount; ++i) {
+                threads.emplace_back([&] {
+                    for (uint64_t n = 0; n < 1000; ++n) {
+                        stack.push(n);
+                        if (stack.pop()) {
+                            popped.fetch_add(1, std::memory_order_relaxed);
+                        }
+                    }
+                    sc::hazard::flush();
+                });
+            }
+            for (auto &t: threads) {
+                t.join();
+            }
+            stack.push(1);
+            std::cout << "LockFreeStack: popped: " << popped.load() << ", allocations: "
+                      << arena.allocations.load();
+        }
+        sc::hazard::flush();
+        std::cout << ", live after destroyed: " << arena.live.load() << std::endl;
+    }
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
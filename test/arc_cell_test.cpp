@@ -0,0 +1,126 @@
+///
+/// @file  arc_cell_test.cpp
+/// @brief Test for sc::ArcCell.
+///
+
+#include "cell/arc_cell.hpp"
+
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ReaderCount = 3;
+constexpr uint32_t WriterCount = 2;
+constexpr uint64_t UpdateCount = 100'000;
+
+static std::atomic<int64_t> LiveConfigs{0};
+
+struct Config
+{
+    uint64_t version;
+    uint64_t checksum;      // always equals to 'version * 2'
+
+    explicit Config(uint64_t v) : version(v), checksum(v * 2)
+    {
+        LiveConfigs.fetch_add(1, std::memory_order_relaxed);
+    }
+
+    ~Config()
+    {
+        // a freed value must not be read again.
+        version = checksum = 1;
+        LiveConfigs.fetch_sub(1, std::memory_order_relaxed);
+    }
+};
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        sc::ArcCell<Config> cell(std::make_shared<Config>(0));
+        std::cout << "Is lock free: " << cell.is_lock_free() << std::endl;
+
+        std::atomic_flag barrier = ATOMIC_FLAG_INIT;
+        std::atomic<bool> stop{false};
+        std::atomic<uint64_t> invalid_reads{0};
+
+        std::vector<std::thread> readers;
+        readers.reserve(ReaderCount);
+        for (uint32_t i = 0; i < ReaderCount; ++i) {
+            readers.emplace_back([&] {
+                barrier.wait(false);
+                while (!stop.load(std::memory_order_relaxed)) {
+                    auto config = cell.load();
+                    if (config->checksum != config->version * 2) {
+                        invalid_reads.fetch_add(1, std::memory_order_relaxed);
+                    }
+                }
+            });
+        }
+
+        // each writer increases the version by a CAS loop.
+        std::vector<std::thread> writers;
+        writers.reserve(WriterCount);
+        for (uint32_t i = 0; i < WriterCount; ++i) {
+            writers.emplace_back([&] {
+                barrier.wait(false);
+                for (uint64_t n = 0; n < UpdateCount; ++n) {
+                    auto current = cell.load();
+                    while (true) {
+                        auto prev = cell.compare_and_swap(current, std::make_shared<Config>(current->version + 1));
+                        if (prev == current) {
+                            break;
+                        }
+                        current = std::move(prev);
+                    }
+                }
+            });
+        }
+
+        auto begin = get_current_time();
+        barrier.test_and_set();
+        barrier.notify_all();
+        for (auto &t: writers) {
+            t.join();
+        }
+        auto elapsed = get_current_time() - begin;
+
+        stop.store(true, std::memory_order_relaxed);
+        for (auto &t: readers) {
+            t.join();
+        }
+
+        std::cout << "Final version: " << cell.load()->version << ", expected: " << WriterCount * UpdateCount
+                  << ", time: " << elapsed << "ns, invalid reads: " << invalid_reads.load() << std::endl;
+
+        auto prev = cell.swap(nullptr);
+        std::cout << "Swapped out version: " << prev->version << ", is empty now: " << (cell.load() == nullptr)
+                  << std::endl;
+        prev.reset();
+        std::cout << "Live values: " << LiveConfigs.load() << std::endl;
+
+        cell.store(std::make_shared<Config>(1));
+    }
+
+    // A weak view does not keep the replaced value alive, unlike a strong snapshot.
+    {
+        sc::ArcCell<Config> cell(std::make_shared<Config>(10));
+        auto weak = cell.downgrade();
+        auto held = weak.load();
+        std::cout << "Weak load: " << held->version << std::endl;
+        cell.store(std::make_shared<Config>(11));
+        std::cout << "Weak expired while held: " << weak.expired() << std::endl;
+        held.reset();
+        std::cout << "Weak expired after replaced: " << weak.expired() << ", load is empty: "
+                  << (weak.load() == nullptr) << ", live values: " << LiveConfigs.load() << std::endl;
+    }
+
+    std::cout << "Live values after the cell destroyed: " << LiveConfigs.load() << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,134 @@
+///
+/// @file  async_lock_test.cpp
+/// @brief Test for sc::sync::AsyncMutex and sc::sync::AsyncRwLock.
+///
+
+#include "sync/async_mutex.hpp"
+#include "sync/async_rw_lock.hpp"
+
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "coro_util.hpp"
+#include "test_util.hpp"
+
+
+#if __cpp_impl_coroutine
+
+constexpr uint32_t ThreadCount = 4;
+
+DetachedTask async_push(sc::sync::AsyncMutex<std::vector<uint32_t>> &mutex, uint32_t index)
+{
+    auto guard = co_await mutex.lock();
+    guard->push_back(index);
+}
+
+DetachedTask async_increment(sc::sync::AsyncMutex<uint64_t> &mutex, std::atomic<uint32_t> &done)
+{
+    for (uint64_t n = 0; n < LoopCount / 100; ++n) {
+        auto guard = co_await mutex.lock();
+        ++*guard;
+    }
+    done.fetch_add(1, std::memory_order_release);
+}
+
+DetachedTask async_read(sc::sync::AsyncRwLock<int> &lock, std::vector<std::string> &events, uint32_t index)
+{
+    auto guard = co_await lock.read();
+    events.push_back("r" + std::to_string(index) + " (readers: " + std::to_string(lock.reader_count()) + ")");
+}
+
+DetachedTask async_write(sc::sync::AsyncRwLock<int> &lock, std::vector<std::string> &events, uint32_t index)
+{
+    auto guard = co_await lock.write();
+    ++*guard;
+    auto locked = lock.is_write_locked() ? "true" : "false";
+    events.push_back("w" + std::to_string(index) + " (write locked: " + locked + ")");
+}
+
+void run_mutex()
+{
+    sc::sync::AsyncMutex<std::vector<uint32_t>> mutex;
+    bool suspended;
+    {
+        // All coroutines are suspended, and the lock is handed off to them in order.
+        auto guard = mutex.try_lock();
+        for (uint32_t i = 0; i < 4; ++i) {
+            async_push(mutex, i);
+        }
+        suspended = (*guard)->empty() && !mutex.try_lock();
+    }
+    auto guard = mutex.try_lock();
+    std::cout << "Mutex suspended: " << suspended << ", resumed order:";
+    for (auto i: **guard) {
+        std::cout << " " << i;
+    }
+    std::cout << std::endl;
+}
+
+void run_mutex_threads()
+{
+    // Each thread starts a coroutine, which may continue on the thread which hands off the lock.
+    sc::sync::AsyncMutex<uint64_t> mutex(0);
+    std::atomic<uint32_t> done{0};
+    std::vector<std::thread> threads;
+    for (uint32_t i = 0; i < ThreadCount; ++i) {
+        threads.emplace_back([&] { async_increment(mutex, done); });
+    }
+    for (auto &t: threads) {
+        t.join();
+    }
+    while (done.load(std::memory_order_acquire) != ThreadCount) {
+        std::this_thread::yield();
+    }
+    std::cout << "Mutex value: " << **mutex.try_lock() << ", expected: " << ThreadCount * (LoopCount / 100)
+              << std::endl;
+}
+
+void run_rw_lock()
+{
+    sc::sync::AsyncRwLock<int> lock(0);
+    std::vector<std::string> events;
+    {
+        // The readers queued behind the writer 2 wait for it, though the readers hold the lock.
+        auto guard = lock.try_write();
+        async_read(lock, events, 0);
+        async_read(lock, events, 1);
+        async_write(lock, events, 2);
+        async_read(lock, events, 3);
+        std::cout << "Rw lock suspended: " << events.empty() << ", try read: " << lock.try_read().has_value()
+                  << std::endl;
+    }
+    std::cout << "Rw lock resumed:";
+    for (auto &e: events) {
+        std::cout << " " << e;
+    }
+    std::cout << ", value: " << **lock.try_read() << ", try write while reading: " << lock.try_write().has_value()
+              << std::endl;
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    run_mutex();
+    run_mutex_threads();
+    run_rw_lock();
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
+
+#else
+
+int main()
+{
+    std::cout << "Skip the async lock test: built without cpp coroutine support." << std::endl;
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
+
+#endif
@@ -0,0 +1,93 @@
+///
+/// @file  async_queue_test.cpp
+/// @brief Test for sc::AsyncQueue.
+///
+
+#include "queue/async_queue.hpp"
+#include "queue/mpmc_array_queue.hpp"
+
+#include "coro_util.hpp"
+#include "queue_thread_run.hpp"
+
+
+#if __cpp_impl_coroutine
+
+constexpr uint32_t ProducerCount = 4;
+constexpr uint32_t ConsumerCount = 2;
+
+using Queue = sc::AsyncQueue<sc::mpmc::ArrayListQueue<Task>>;
+
+DetachedTask async_consume(
+        Queue &task_queue,
+        std::vector<Task> &result,
+        uint64_t count,
+        std::atomic<uint32_t> &finished)
+{
+    for (uint64_t i = 0; i < count; ++i) {
+        auto task = co_await task_queue.dequeue();
+        // the coroutine may be resumed on any producer thread.
+        auto tid = std::this_thread::get_id();
+        task.consume_tid = (int64_t)*(ThreadIdType *)(&tid);     // hack
+        task.out_time = get_current_time();
+        result.push_back(task);
+    }
+
+    finished.fetch_add(1, std::memory_order_release);
+    finished.notify_all();
+}
+
+int main()
+{
+    Queue queue;
+    std::atomic_flag barrier = ATOMIC_FLAG_INIT;
+    std::atomic<uint32_t> finished{0};
+
+    constexpr uint64_t Total = ProducerCount * LoopCount;
+    constexpr uint64_t ConsumerResultSize = Total / ConsumerCount;
+    static_assert(ConsumerResultSize * ConsumerCount == Total);
+
+    // Start the consumer coroutines first, they are suspended on the empty queue.
+    std::vector<std::vector<Task>> result(ConsumerCount);
+    for (auto &r: result) {
+        r.reserve(ConsumerResultSize);
+        async_consume(queue, r, ConsumerResultSize, finished);
+    }
+
+    std::vector<std::thread> produce_threads;
+    produce_threads.reserve(ProducerCount);
+    for (uint32_t i = 0; i < ProducerCount; ++i) {
+        produce_threads.emplace_back(produce<Queue>, std::ref(queue), std::ref(barrier));
+    }
+
+    std::this_thread::sleep_for(std::chrono::seconds(2));
+    // begin
+    auto begin = get_current_time();
+    barrier.test_and_set();
+    barrier.notify_all();
+
+    for (auto &t: produce_threads) {
+        t.join();
+    }
+    for (auto f = finished.load(std::memory_order_acquire); f != ConsumerCount;
+         f = finished.load(std::memory_order_acquire)) {
+        finished.wait(f);
+    }
+
+    std::cout << "[Consume] All consumer coroutines finished. total time: "
+              << get_current_time() - begin << "ns" << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
+
+#else
+
+int main()
+{
+    std::cout << "Error: Built the test without cpp coroutine support." << std::endl;
+
+    return 0;
+}
+
+#endif
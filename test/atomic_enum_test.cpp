@@ -0,0 +1,72 @@
+///
+/// @file  atomic_enum_test.cpp
+/// @brief Test for sc::AtomicEnum.
+///
+
+#include "cell/atomic_enum.hpp"
+
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+
+enum class State : uint8_t
+{
+    Idle,
+    Running,
+    Stopped,
+};
+
+const char *to_string(State state)
+{
+    switch (state) {
+        case State::Idle:
+            return "Idle";
+        case State::Running:
+            return "Running";
+        case State::Stopped:
+            return "Stopped";
+    }
+    return "?";
+}
+
+constinit sc::AtomicEnum<State> GlobalState(State::Idle);
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    auto prev = GlobalState.swap(State::Running);
+    auto expected = State::Idle;
+    auto replaced = GlobalState.compare_exchange(expected, State::Stopped);
+    std::cout << "Lock free: " << GlobalState.is_lock_free() << ", swapped: " << to_string(prev) << ", replaced: "
+              << replaced << ", loaded: " << to_string(expected) << std::endl;
+
+    // Only one thread wins the transition from Idle to Running in each round.
+    sc::AtomicEnum<State> state(State::Idle);
+    std::atomic<uint64_t> wins{0};
+    std::vector<std::thread> threads;
+    for (uint32_t i = 0; i < ThreadCount; ++i) {
+        threads.emplace_back([&] {
+            for (uint64_t n = 0; n < LoopCount / 10; ++n) {
+                auto idle = State::Idle;
+                if (state.compare_exchange(idle, State::Running, std::memory_order_acquire,
+                                           std::memory_order_relaxed)) {
+                    wins.fetch_add(1, std::memory_order_relaxed);
+                    state.store(State::Idle, std::memory_order_release);
+                }
+            }
+        });
+    }
+    for (auto &t: threads) {
+        t.join();
+    }
+    std::cout << "Final: " << to_string(state.load()) << ", wins > 0: " << (wins.load() > 0) << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,95 @@
+///
+/// @file  atomic_fallback_test.cpp
+/// @brief Test for sc::SyncCell with SYNC_CELL_ATOMIC_FALLBACK and SYNC_CELL_CRITICAL_SECTION.
+///
+
+#define SYNC_CELL_ATOMIC_FALLBACK
+#define SYNC_CELL_CRITICAL_SECTION
+
+#include "cell/sync_cell.hpp"
+
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+
+/// @brief A 32-byte value, whose std::atomic is not lock-free.
+struct Wide
+{
+    uint64_t a;
+    uint64_t b;
+    uint64_t c;
+    uint64_t d;
+};
+
+/// @brief A simulated critical section for the test. On a single-core MCU, it would disable the
+/// interrupts and return the previous interrupt mask.
+std::atomic_flag CriticalSectionFlag = ATOMIC_FLAG_INIT;
+std::atomic<uint64_t> CriticalSectionCount{0};
+
+sc::critical_section::RestoreState sc::critical_section::acquire() noexcept
+{
+    while (CriticalSectionFlag.test_and_set(std::memory_order_acquire)) {
+        std::this_thread::yield();
+    }
+    CriticalSectionCount.fetch_add(1, std::memory_order_relaxed);
+    return 1;
+}
+
+void sc::critical_section::release(RestoreState state) noexcept
+{
+    if (state == 1) {
+        CriticalSectionFlag.clear(std::memory_order_release);
+    }
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    sc::SyncCell<uint32_t> small(0);
+    sc::SyncCell<Wide> wide(Wide{0, 0, 0, 0});
+    std::cout << "Small is lock free: " << small.is_lock_free() << ", wide is lock free: "
+              << wide.is_lock_free() << std::endl;
+
+    // bitwise comparison, the same as the atomic storage.
+    Wide expected{1, 0, 0, 0};
+    auto replaced = wide.compare_exchange(expected, Wide{2, 2, 2, 2});
+    std::cout << "Compare exchange with a wrong value: " << replaced << ", loaded: " << expected.a << std::endl;
+
+    auto begin = get_current_time();
+    std::vector<std::thread> threads;
+    threads.reserve(ThreadCount);
+    for (uint32_t i = 0; i < ThreadCount; ++i) {
+        threads.emplace_back([&wide] {
+            for (uint64_t n = 0; n < LoopCount / 10; ++n) {
+                wide.fetch_update(std::memory_order_seq_cst, std::memory_order_seq_cst, [](const Wide &w) {
+                    return std::optional(Wide{w.a + 1, w.b + 1, w.c + 1, w.d + 1});
+                });
+            }
+        });
+    }
+    for (auto &t: threads) {
+        t.join();
+    }
+
+    auto w = wide.load();
+    std::cout << "Wide value: " << w.a << " / " << w.b << " / " << w.c << " / " << w.d << ", expected: "
+              << ThreadCount * (LoopCount / 10) << ", critical sections: " << CriticalSectionCount.load()
+              << ", time: " << get_current_time() - begin << "ns" << std::endl;
+
+    // The arithmetic operations of the lock-based cell.
+    sc::SyncCell<long double> real(1.5L);
+    real.fetch_add(2.0L);
+    real.fetch_sub(0.5L);
+    auto prev = real.fetch_min(1.0L);
+    std::cout << "Long double is lock free: " << real.is_lock_free() << ", previous: " << (double) prev
+              << ", value: " << (double) real.load() << ", expected: 3 / 1" << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,92 @@
+///
+/// @file  back_off_test.cpp
+/// @brief Test for the sc::util::BasicBackoff strategies, and the primitives using them on an
+/// oversubscribed machine.
+///
+
+#include "cell/seq_lock_cell.hpp"
+#include "cell/sync_cell.hpp"
+#include "queue/blocking_queue.hpp"
+#include "queue/mpmc_bounded_queue.hpp"
+
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint64_t OpCount = LoopCount / 10000;
+
+/// @brief A non-trivially copyable value, so the @c SyncCell uses the spin lock.
+struct Counter
+{
+    uint64_t value = 0;
+    std::string name = "counter";
+
+    bool operator==(const Counter &) const = default;
+};
+
+template<typename Strategy>
+void run_strategy(const char *name)
+{
+    using Backoff = sc::util::BasicBackoff<Strategy>;
+
+    // Steps of 'snooze' until the parking is advised, or 0 if never.
+    Backoff backoff;
+    uint32_t steps = 0;
+    while (!backoff.is_completed() && steps < 100) {
+        backoff.snooze();
+        ++steps;
+    }
+
+    // More threads than the cores.
+    auto thread_count = std::max(2u, std::thread::hardware_concurrency()) * 2;
+
+    sc::SyncCell<Counter, Backoff> cell;
+    sc::SeqLockCell<uint64_t, Backoff> seq_cell(0);
+    sc::BlockingQueue<sc::mpmc::BoundedQueue<uint64_t, Backoff>, Backoff> queue(64);
+    std::atomic<uint64_t> dequeued{0};
+
+    auto begin = get_current_time();
+    std::vector<std::thread> threads;
+    threads.reserve(thread_count);
+    for (uint32_t i = 0; i < thread_count; ++i) {
+        threads.emplace_back([&, i] {
+            for (uint64_t n = 0; n < OpCount; ++n) {
+                cell.fetch_update(std::memory_order_seq_cst, std::memory_order_seq_cst, [](const Counter &c) {
+                    return std::optional(Counter{c.value + 1, c.name});
+                });
+                seq_cell.update([](uint64_t v) { return v + 1; });
+
+                // half of the threads are producers, and the others are consumers.
+                if (i % 2 == 0) {
+                    queue.enqueue(n);
+                } else if (queue.dequeue()) {
+                    dequeued.fetch_add(1, std::memory_order_relaxed);
+                }
+            }
+        });
+    }
+    for (auto &t: threads) {
+        t.join();
+    }
+
+    std::cout << "[" << name << "] steps to park: " << (steps == 100 ? 0 : steps) << ", cell: "
+              << cell.load().value << ", seq cell: " << seq_cell.read() << ", dequeued: "
+              << dequeued.load() << ", expected: " << thread_count * OpCount << " / "
+              << thread_count * OpCount << " / " << thread_count / 2 * OpCount << ", time: "
+              << get_current_time() - begin << "ns" << std::endl;
+}
+
+int main()
+{
+    run_strategy<sc::util::backoff::Exponential>("Exponential");
+    run_strategy<sc::util::backoff::SpinOnly>("SpinOnly");
+    run_strategy<sc::util::backoff::SpinThenYield>("SpinThenYield");
+    run_strategy<sc::util::backoff::SpinThenPark>("SpinThenPark");
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
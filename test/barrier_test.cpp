@@ -0,0 +1,89 @@
+///
+/// @file  barrier_test.cpp
+/// @brief Test for sc::sync::Barrier.
+///
+
+#include "sync/barrier.hpp"
+
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+constexpr uint64_t Rounds = LoopCount / 1000;
+
+/// @brief Each round, all threads add to the round's counter before the barrier, and check it
+/// after the barrier.
+void run_rounds(
+        sc::sync::Barrier &barrier,
+        std::vector<std::atomic<uint32_t>> &counters,
+        std::atomic<uint64_t> &leaders,
+        std::atomic<uint64_t> &errors)
+{
+    for (uint64_t i = 0; i < Rounds; ++i) {
+        counters[i].fetch_add(1, std::memory_order_relaxed);
+
+        // use the timed wait in some rounds, it never times out here.
+        bool is_leader;
+        if (i % 4 == 0) {
+            auto result = barrier.wait_timeout(std::chrono::seconds(10));
+            if (!result) {
+                errors.fetch_add(1, std::memory_order_relaxed);
+                continue;
+            }
+            is_leader = result->is_leader();
+        } else {
+            is_leader = barrier.wait().is_leader();
+        }
+
+        if (is_leader) {
+            leaders.fetch_add(1, std::memory_order_relaxed);
+        }
+        if (counters[i].load(std::memory_order_relaxed) != ThreadCount) {
+            errors.fetch_add(1, std::memory_order_relaxed);
+        }
+    }
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        // the only waiter times out, and its arrival is withdrawn.
+        sc::sync::Barrier barrier(2);
+        auto begin = get_current_time();
+        auto timeout = barrier.wait_timeout(std::chrono::milliseconds(100));
+        std::cout << "Wait timeout: " << !timeout.has_value() << ", wait time: "
+                  << get_current_time() - begin << "ns" << std::endl;
+
+        std::thread t([&barrier] { barrier.wait(); });
+        auto result = barrier.wait_timeout(std::chrono::seconds(10));
+        t.join();
+        std::cout << "Wait after timeout: " << result.has_value() << std::endl;
+    }
+
+    sc::sync::Barrier barrier(ThreadCount);
+    std::vector<std::atomic<uint32_t>> counters(Rounds);
+    std::atomic<uint64_t> leaders{0};
+    std::atomic<uint64_t> errors{0};
+
+    auto begin = get_current_time();
+    std::vector<std::thread> threads;
+    threads.reserve(ThreadCount);
+    for (uint32_t i = 0; i < ThreadCount; ++i) {
+        threads.emplace_back(run_rounds, std::ref(barrier), std::ref(counters), std::ref(leaders), std::ref(errors));
+    }
+    for (auto &t: threads) {
+        t.join();
+    }
+
+    std::cout << "Rounds: " << Rounds << ", leaders: " << leaders.load() << ", errors: "
+              << errors.load() << ", time: " << get_current_time() - begin << "ns" << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
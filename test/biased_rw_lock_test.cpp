@@ -0,0 +1,107 @@
+///
+/// @file  biased_rw_lock_test.cpp
+/// @brief Test for sc::lock::BiasedRwLock.
+///
+
+#include "lock/biased_rw_lock.hpp"
+
+#include <atomic>
+#include <chrono>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ReaderCount = 3;
+constexpr uint32_t WriterCount = 2;
+
+/// @brief Both fields are always the same value when the lock is not held by a writer.
+struct Pair
+{
+    uint64_t a = 0;
+    uint64_t b = 0;
+};
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    // The bias is revoked by a write, and re-enabled by a slow-path reader after the inhibition.
+    {
+        sc::lock::BiasedRwLock<Pair> lock;
+        {
+            auto first = lock.read();
+            auto second = lock.read();
+            std::cout << "Read biased: " << lock.is_read_biased() << ", fast path: " << first.is_fast_path()
+                      << ", nested read fast path: " << second.is_fast_path()
+                      << ", try write when read locked: " << lock.try_write().has_value() << std::endl;
+        }
+        std::cout << "Read biased after the failed try write: " << lock.is_read_biased() << std::endl;
+        {
+            auto guard = lock.write();
+            guard->a = guard->b = 1;
+            std::cout << "Try read when write locked: " << lock.try_read().has_value() << std::endl;
+        }
+        std::this_thread::sleep_for(std::chrono::milliseconds(10));
+        {
+            auto guard = lock.read();
+            std::cout << "Slow path after the write: " << !guard.is_fast_path() << ", value: " << guard->a
+                      << ", read biased again: " << lock.is_read_biased() << std::endl;
+        }
+        std::cout << "Fast path then: " << lock.read().is_fast_path() << std::endl;
+    }
+
+    // The readers never see a half-written pair, whichever path they take.
+    {
+        sc::lock::BiasedRwLock<Pair> lock;
+        std::atomic<bool> done{false};
+        std::atomic<uint64_t> torn{0};
+        std::atomic<uint64_t> fast_reads{0};
+        std::vector<std::thread> threads;
+        for (uint32_t t = 0; t < ReaderCount; ++t) {
+            threads.emplace_back([&] {
+                uint64_t fast = 0;
+                while (!done.load(std::memory_order_acquire)) {
+                    auto guard = lock.read();
+                    if (guard->a != guard->b) {
+                        torn.fetch_add(1, std::memory_order_relaxed);
+                    }
+                    fast += guard.is_fast_path();
+                }
+                fast_reads.fetch_add(fast, std::memory_order_relaxed);
+            });
+        }
+        std::vector<std::thread> writers;
+        for (uint32_t t = 0; t < WriterCount; ++t) {
+            writers.emplace_back([&] {
+                for (uint64_t i = 0; i < LoopCount / 1000; ++i) {
+                    auto guard = lock.write();
+                    ++guard->a;
+                    ++guard->b;
+                }
+            });
+        }
+        for (auto &t: writers) {
+            t.join();
+        }
+        done.store(true, std::memory_order_release);
+        for (auto &t: threads) {
+            t.join();
+        }
+        auto guard = lock.read();
+        std::cout << "Value: " << guard->a << ", expected: " << WriterCount * (LoopCount / 1000)
+                  << ", torn reads: " << torn.load() << ", some fast reads: " << (fast_reads.load() > 0) << std::endl;
+    }
+
+    {
+        sc::lock::BiasedRwLock<std::vector<int>> lock(3, 7);
+        lock.write()->push_back(8);
+        auto values = std::move(lock).into_inner();
+        std::cout << "into_inner: size: " << values.size() << ", last: " << values.back() << std::endl;
+    }
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -23,17 +23,99 @@ struct ObjNoDequeue
 {
 };
 
+/// @brief Consumes until the queue is closed and drained.
+template<typename Queue>
+void blocking_consume(
+        Queue &task_queue,
+        std::atomic_flag &barrier,
+        std::vector<Task> &result)
+{
+    auto tid = std::this_thread::get_id();
+
+    sync_io([&tid] { std::cout << "[Consume] Thread [" << tid << "] waiting..." << std::endl; });
+    barrier.wait(false);
+
+    auto begin = get_current_time();
+
+    auto c_tid = (int64_t)*(ThreadIdType *)(&tid);     // hack
+    while (auto task = task_queue.dequeue()) {
+        task->consume_tid = c_tid;
+        task->out_time = get_current_time();
+        result.push_back(*task);
+    }
+
+    auto end = get_current_time();
+    auto elapsed = end - begin;
+    sync_io([&tid, elapsed] {
+        std::cout << "[Consume] Blocking Consumer Thread [" << tid << "] finished. count time: "
+                  << elapsed << "ns" << std::endl;
+    });
+}
+
 int main()
 {
     std::cout << std::boolalpha;
     std::cout << "ObjHasDequeue check: " << sc::impl::HasDequeue<ObjHasDequeue>::value << std::endl;
     std::cout << "ObjNoDequeue check: " << sc::impl::HasDequeue<ObjNoDequeue>::value << std::endl;
 
-    sc::BlockingQueue<sc::mpmc::ArrayListQueue<Task>> queue;
+    using Queue = sc::BlockingQueue<sc::mpmc::ArrayListQueue<Task>>;
+    Queue queue;
 
     queue.enqueue(Task{});
     queue.dequeue();
 
+    auto begin = get_current_time();
+    auto timeout = queue.try_dequeue_for(std::chrono::milliseconds(100));
+    std::cout << "Dequeue timeout: " << !timeout.has_value() << ", wait time: "
+              << get_current_time() - begin << "ns" << std::endl;
+
+    std::atomic_flag barrier = ATOMIC_FLAG_INIT;
+
+    std::vector<std::thread> produce_threads;
+    produce_threads.reserve(ProducerCount);
+    for (uint32_t i = 0; i < ProducerCount; ++i) {
+        produce_threads.emplace_back(produce<Queue>, std::ref(queue), std::ref(barrier));
+    }
+
+    constexpr uint64_t Total = ProducerCount * LoopCount;
+    constexpr uint64_t ConsumerResultSize = Total / ConsumerCount;
+    static_assert(ConsumerResultSize * ConsumerCount == Total);
+
+    std::vector<std::thread> consume_threads;
+    consume_threads.reserve(ConsumerCount);
+    std::vector<std::vector<Task>> result(ConsumerCount);
+    for (auto &r: result) {
+        r.reserve(ConsumerResultSize);
+    }
+    for (uint32_t i = 0; i < ConsumerCount; ++i) {
+        consume_threads.emplace_back(
+                blocking_consume<Queue>,
+                std::ref(queue), std::ref(barrier),
+                std::ref(result[i]));
+    }
+
+    std::this_thread::sleep_for(std::chrono::seconds(2));
+    // begin
+    barrier.test_and_set();
+    barrier.notify_all();
+
+    for (auto &t: produce_threads) {
+        t.join();
+    }
+    // no more items, the consumers exit after the queue is drained.
+    queue.close();
+    for (auto &t: consume_threads) {
+        t.join();
+    }
+
+    uint64_t consumed = 0;
+    for (auto &r: result) {
+        consumed += r.size();
+    }
+    std::cout << "Consumed after close: " << consumed << ", expected: " << Total
+              << ", enqueue after close: " << queue.enqueue(Task{})
+              << ", dequeue after close: " << queue.dequeue().has_value() << std::endl;
+
     std::cout << "hello world" << std::endl;
 
     return 0;
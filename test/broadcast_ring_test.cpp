@@ -0,0 +1,114 @@
+///
+/// @file  broadcast_ring_test.cpp
+/// @brief Test for sc::broadcast::Ring.
+///
+
+#include "queue/broadcast_ring.hpp"
+
+#include <array>
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ProducerCount = 2;
+constexpr uint32_t ConsumerCount = 3;
+constexpr uint64_t SendCount = LoopCount / 100;
+
+void run_block()
+{
+    sc::broadcast::Ring<uint64_t> ring(64, ConsumerCount);
+    std::vector<sc::broadcast::Ring<uint64_t>::Consumer> consumers;
+    for (uint32_t i = 0; i < ConsumerCount; ++i) {
+        consumers.push_back(*ring.subscribe());
+    }
+
+    std::vector<std::thread> threads;
+    std::array<bool, ConsumerCount> ordered{};
+    std::vector<uint64_t> received(ConsumerCount, 0);
+    auto begin = get_current_time();
+    for (uint32_t i = 0; i < ConsumerCount; ++i) {
+        threads.emplace_back([&, i] {
+            // The values of each producer are received in its sending order.
+            std::vector<uint64_t> last(ProducerCount, 0);
+            bool ok = true;
+            for (uint64_t n = 0; n < ProducerCount * SendCount; ++n) {
+                auto v = consumers[i].recv();
+                auto producer = v % ProducerCount;
+                ok = ok && v / ProducerCount == last[producer];
+                last[producer] = v / ProducerCount + 1;
+                ++received[i];
+            }
+            ordered[i] = ok;
+        });
+    }
+    for (uint32_t p = 0; p < ProducerCount; ++p) {
+        threads.emplace_back([&ring, p] {
+            for (uint64_t n = 0; n < SendCount; ++n) {
+                ring.send(n * ProducerCount + p);
+            }
+        });
+    }
+    for (auto &t: threads) {
+        t.join();
+    }
+    auto elapsed = get_current_time() - begin;
+
+    for (uint32_t i = 0; i < ConsumerCount; ++i) {
+        std::cout << "Consumer " << i << " received: " << received[i] << ", expected: " << ProducerCount * SendCount
+                  << ", ordered: " << ordered[i] << ", lagged: " << consumers[i].lagged() << std::endl;
+    }
+    std::cout << "Block time: " << elapsed << "ns" << std::endl;
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        sc::broadcast::Ring<std::string> ring(3, 2);
+        auto a = ring.subscribe();
+        auto b = ring.subscribe();
+        std::cout << "Capacity: " << ring.capacity() << ", subscribe when full: " << !ring.subscribe().has_value();
+        for (int i = 0; i < 4; ++i) {
+            ring.send(std::to_string(i));
+        }
+        std::cout << ", try send when full: " << ring.try_send("4") << ", a received:";
+        while (auto v = a->try_recv()) {
+            std::cout << " " << *v;
+        }
+        std::cout << ", try send when b is behind: " << ring.try_send("4") << ", b received:";
+        while (auto v = b->try_recv()) {
+            std::cout << " " << *v;
+        }
+        std::cout << ", try send: " << ring.try_send("4") << std::endl;
+
+        // A new consumer only receives the values sent after it subscribed.
+        b.reset();
+        auto c = ring.subscribe();
+        ring.send("5");
+        std::cout << "New consumer received: " << *c->try_recv() << ", a received: " << *a->try_recv() << " "
+                  << *a->try_recv() << std::endl;
+    }
+
+    {
+        sc::broadcast::Ring<uint64_t> ring(4, 1, sc::broadcast::Overflow::Lag);
+        auto consumer = ring.subscribe();
+        for (uint64_t i = 0; i < 10; ++i) {
+            ring.send(i);
+        }
+        std::cout << "Lag received:";
+        while (auto v = consumer->try_recv()) {
+            std::cout << " " << *v;
+        }
+        std::cout << ", lagged: " << consumer->lagged() << ", expected: 6" << std::endl;
+    }
+
+    run_block();
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,129 @@
+///
+/// @file  cancellation_token_test.cpp
+/// @brief Test for sc::CancellationToken.
+///
+
+#include "cell/cancellation_token.hpp"
+
+#include <atomic>
+#include <chrono>
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "pool/thread_pool.hpp"
+#include "queue/mpmc_list_queue.hpp"
+
+#include "coro_util.hpp"
+#include "test_util.hpp"
+
+
+constexpr uint32_t WorkerCount = 4;
+
+#if __cpp_impl_coroutine
+
+DetachedTask async_wait(sc::CancellationToken token, std::vector<std::string> &log, std::string name)
+{
+    co_await token.cancelled();
+    log.push_back(std::move(name));
+}
+
+void run_async()
+{
+    sc::CancellationToken root;
+    auto child = root.child_token();
+    std::vector<std::string> log;
+    async_wait(root, log, "root");
+    async_wait(child, log, "child");
+    auto suspended = log.empty();
+    root.cancel();
+    async_wait(child, log, "ready");
+    std::cout << "Async: suspended: " << suspended << ", resumed:";
+    for (auto &name: log) {
+        std::cout << " " << name;
+    }
+    std::cout << std::endl;
+}
+
+#endif
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        sc::CancellationToken token;
+        auto copy = token;
+        auto before = token.is_cancelled();
+        copy.cancel();
+        copy.cancel();
+        std::cout << "Copy shares the flag: " << (copy == token) << ", before: " << before << ", after: "
+                  << token.is_cancelled() << std::endl;
+    }
+
+    {
+        // A child is cancelled with its parent, and alone without the parent.
+        sc::CancellationToken root;
+        auto child = root.child_token();
+        auto grandchild = child.child_token();
+        auto sibling = root.child_token();
+        sibling.cancel();
+        std::cout << "Sibling cancelled alone: root " << root.is_cancelled() << ", child " << child.is_cancelled()
+                  << ", sibling " << sibling.is_cancelled() << std::endl;
+        // The grandchild is still reached from the root after the child token is dropped.
+        child = sc::CancellationToken();
+        root.cancel();
+        std::cout << "Root cancelled: grandchild " << grandchild.is_cancelled() << ", replaced child "
+                  << child.is_cancelled() << ", late child " << root.child_token().is_cancelled() << std::endl;
+    }
+
+    {
+        sc::CancellationToken token;
+        auto timed_out = !token.wait_cancelled_for(std::chrono::milliseconds(5));
+        std::thread canceller([parent = token] {
+            std::this_thread::sleep_for(std::chrono::milliseconds(5));
+            parent.cancel();
+        });
+        token.child_token().wait_cancelled();
+        canceller.join();
+        std::cout << "Wait: timed out before: " << timed_out << ", returned after: "
+                  << token.wait_cancelled_for(std::chrono::seconds(1)) << std::endl;
+    }
+
+    {
+        // The jobs on the pool consume the queue until the request is cancelled.
+        sc::CancellationToken request;
+        sc::mpmc::LinkedListQueue<uint64_t> jobs;
+        std::atomic<uint64_t> consumed{0};
+        std::atomic<uint32_t> stopped{0};
+        sc::pool::ThreadPool pool(WorkerCount);
+        for (uint32_t i = 0; i < WorkerCount; ++i) {
+            pool.spawn([token = request.child_token(), &jobs, &consumed, &stopped] {
+                while (!token.is_cancelled()) {
+                    if (jobs.try_dequeue()) {
+                        consumed.fetch_add(1, std::memory_order_relaxed);
+                    } else {
+                        std::this_thread::yield();
+                    }
+                }
+                stopped.fetch_add(1);
+            });
+        }
+        for (uint64_t i = 0; i < LoopCount / 1000; ++i) {
+            jobs.enqueue(i);
+        }
+        while (consumed.load() < LoopCount / 1000) {
+            std::this_thread::yield();
+        }
+        request.cancel();
+        pool.join();
+        std::cout << "Pool: consumed " << consumed.load() << ", stopped workers " << stopped.load() << std::endl;
+    }
+
+#if __cpp_impl_coroutine
+    run_async();
+#endif
+
+    std::cout << "hello world" << std::endl;
+    return 0;
+}
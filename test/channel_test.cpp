@@ -0,0 +1,88 @@
+///
+/// @file  channel_test.cpp
+/// @brief Test for sc::Sender and sc::Receiver.
+///
+
+#include "queue/channel.hpp"
+#include "queue/mpmc_array_queue.hpp"
+
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ProducerCount = 4;
+constexpr uint32_t ConsumerCount = 2;
+constexpr uint64_t ItemCount = LoopCount / 10;
+
+using Queue = sc::mpmc::ArrayListQueue<uint64_t>;
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        auto [tx, rx] = sc::make_channel<Queue>();
+        auto empty = rx.try_recv();
+        auto timeout = rx.recv_for(std::chrono::milliseconds(10));
+        tx.send(1);
+        auto copy = tx;
+        auto senders = tx.sender_count();
+        {
+            auto dropped = std::move(tx);
+        }
+        auto connected = !rx.is_disconnected();
+        copy.send(2);
+        { auto last = std::move(copy); }
+        auto first = rx.try_recv();
+        auto second = rx.recv();
+        auto end = rx.try_recv();
+        std::cout << "Empty: " << empty.is_empty() << ", timeout empty: " << timeout.is_empty() << ", senders: "
+                  << senders << ", connected after a sender dropped: " << connected << ", values: "
+                  << *std::move(first).success() << " / " << *second << ", disconnected: " << end.is_disconnected()
+                  << " / " << rx.is_disconnected() << ", blocking recv: " << rx.recv().has_value() << std::endl;
+    }
+
+    {
+        auto [tx, rx] = sc::make_channel<Queue>();
+        { auto dropped = std::move(rx); }
+        std::cout << "Send without receivers: " << tx.send(1) << ", disconnected: " << tx.is_disconnected()
+                  << std::endl;
+    }
+
+    // The consumers stop when all producers have dropped their senders, without counting the items.
+    std::vector<uint64_t> sums(ConsumerCount, 0);
+    std::vector<std::thread> threads;
+    auto begin = get_current_time();
+    {
+        auto [tx, rx] = sc::make_channel<Queue>();
+        for (uint32_t i = 0; i < ConsumerCount; ++i) {
+            threads.emplace_back([rx = rx, &sum = sums[i]]() mutable {
+                while (auto v = rx.recv()) {
+                    sum += *v;
+                }
+            });
+        }
+        for (uint32_t i = 0; i < ProducerCount; ++i) {
+            threads.emplace_back([tx = tx, i]() mutable {
+                for (uint64_t n = i; n < ItemCount; n += ProducerCount) {
+                    tx.send(n);
+                }
+            });
+        }
+    }
+    for (auto &t: threads) {
+        t.join();
+    }
+    uint64_t sum = 0;
+    for (auto s: sums) {
+        sum += s;
+    }
+    std::cout << "Sum: " << sum << ", expected: " << ItemCount * (ItemCount - 1) / 2 << ", time: "
+              << get_current_time() - begin << "ns" << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,73 @@
+///
+/// @file  concurrent_map_test.cpp
+/// @brief Test for sc::map::ConcurrentMap.
+///
+
+#include "map/concurrent_map.hpp"
+
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    sc::map::ConcurrentMap<std::string, std::string> map(3);
+    std::cout << "Shard count: " << map.shard_count() << ", insert: " << map.insert("a", "1")
+              << ", insert again: " << map.insert("a", "2") << ", insert or assign: "
+              << map.insert_or_assign("a", "3") << ", contains missing: " << map.contains("b") << std::endl;
+    map.get("a", [](const std::string &v) { std::cout << "Value: " << v << ", expected: 3" << std::endl; });
+    std::cout << "Remove: " << map.remove("a") << ", remove again: " << map.remove("a") << ", empty: "
+              << map.empty() << std::endl;
+
+    // The writers insert and remove their own keys, growing the buckets, while the readers look
+    // up all keys.
+    constexpr uint64_t KeyCount = LoopCount / 10;
+    sc::map::ConcurrentMap<uint64_t, uint64_t> owned;
+    auto initial_buckets = owned.bucket_count();
+    std::atomic<uint64_t> bad_reads{0};
+    std::atomic<uint32_t> writers{ThreadCount / 2};
+    auto elapsed = run_threads(ThreadCount, [&](uint32_t i) {
+        if (i % 2 == 0) {
+            for (uint64_t n = 0; n < KeyCount; ++n) {
+                auto key = n * ThreadCount + i;
+                owned.insert(key, key * 2);
+                if (n % 2 == 0) {
+                    owned.insert_or_assign(key, key * 3);
+                    owned.remove(key);
+                }
+            }
+            writers.fetch_sub(1, std::memory_order_release);
+        } else {
+            while (writers.load(std::memory_order_acquire) > 0) {
+                for (uint64_t key = 0; key < 1000; ++key) {
+                    owned.get(key, [&bad_reads, key](uint64_t v) {
+                        if (v != key * 2 && v != key * 3) {
+                            bad_reads.fetch_add(1, std::memory_order_relaxed);
+                        }
+                    });
+                }
+            }
+        }
+    });
+    uint64_t visited = 0;
+    owned.for_each([&](uint64_t k, uint64_t v) {
+        ++visited;
+        if (v != k * 2) {
+            bad_reads.fetch_add(1, std::memory_order_relaxed);
+        }
+    });
+    std::cout << "Owned keys: " << owned.size() << ", visited: " << visited << ", expected: "
+              << ThreadCount / 2 * (KeyCount / 2) << ", grown: " << (owned.bucket_count() > initial_buckets)
+              << ", bad reads: " << bad_reads.load() << ", time: " << elapsed << "ns" << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
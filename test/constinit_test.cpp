@@ -0,0 +1,72 @@
+///
+/// @file  constinit_test.cpp
+/// @brief Test for the constant-initialized statics of the cells, locks and the fixed bounded queue.
+///
+
+#include "cell/once_sync_cell.hpp"
+#include "cell/sync_cell.hpp"
+#include "lock/mcs_lock.hpp"
+#include "lock/rw_spin_lock.hpp"
+#include "lock/spin_lock.hpp"
+#include "lock/ticket_lock.hpp"
+#include "queue/mpmc_bounded_queue.hpp"
+
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+
+// All statics are initialized at compile time, no dynamic initialization or lazy wrapper.
+constinit sc::SyncCell<uint64_t> Counter(0);
+constinit sc::OnceSyncCell<uint64_t> Config;
+constinit sc::lock::SpinLock<uint64_t> SpinTotal(0);
+constinit sc::lock::RwSpinLock<uint64_t> RwTotal(0);
+constinit sc::lock::TicketLock<uint64_t> TicketTotal(0);
+constinit sc::lock::McsLock<uint64_t> McsTotal(0);
+constinit sc::mpmc::FixedBoundedQueue<uint64_t, 64> Queue;
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    std::vector<std::thread> threads;
+    threads.reserve(ThreadCount);
+    for (uint32_t i = 0; i < ThreadCount; ++i) {
+        threads.emplace_back([] {
+            auto step = Config.get_or_init([] { return uint64_t{1}; });
+            for (uint64_t n = 0; n < LoopCount / 10; ++n) {
+                Counter.fetch_update(std::memory_order_seq_cst, std::memory_order_seq_cst,
+                                     [step](uint64_t v) { return std::optional(v + step); });
+                *SpinTotal.lock() += step;
+                *RwTotal.write() += step;
+                *TicketTotal.lock() += step;
+                *McsTotal.lock() += step;
+            }
+        });
+    }
+    for (auto &t: threads) {
+        t.join();
+    }
+
+    std::cout << "Values: " << Counter.load() << " / " << *SpinTotal.lock() << " / " << *RwTotal.read() << " / "
+              << *TicketTotal.lock() << " / " << *McsTotal.lock() << ", expected: " << ThreadCount * (LoopCount / 10)
+              << std::endl;
+
+    std::cout << "Fixed queue capacity: " << Queue.capacity() << std::endl;
+    uint64_t sum = 0;
+    for (uint64_t i = 0; i < 100; ++i) {
+        Queue.enqueue(i);
+        sum += *Queue.try_dequeue();
+    }
+    for (uint64_t i = 0; i < Queue.capacity(); ++i) {
+        Queue.enqueue(i);
+    }
+    std::cout << "Fixed queue sum: " << sum << ", full: " << !Queue.try_enqueue(0) << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,34 @@
+///
+/// @file  coro_util.hpp
+/// @brief Common coroutine types for test.
+///
+
+#ifndef SYNC_CELL_CORO_UTIL_HPP
+#define SYNC_CELL_CORO_UTIL_HPP
+
+#if __cpp_impl_coroutine
+
+#include <coroutine>
+#include <exception>
+
+
+/// @brief A fire-and-forget coroutine type, only used for the test.
+struct DetachedTask
+{
+    struct promise_type
+    {
+        DetachedTask get_return_object() noexcept { return {}; }
+
+        std::suspend_never initial_suspend() noexcept { return {}; }
+
+        std::suspend_never final_suspend() noexcept { return {}; }
+
+        void return_void() noexcept { }
+
+        void unhandled_exception() noexcept { std::terminate(); }
+    };
+};
+
+#endif
+
+#endif //SYNC_CELL_CORO_UTIL_HPP
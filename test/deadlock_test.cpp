@@ -0,0 +1,137 @@
+///
+/// @file  deadlock_test.cpp
+/// @brief Test for the deadlock detection enabled by SYNC_CELL_DEADLOCK_DETECTION.
+///
+
+#define SYNC_CELL_DEADLOCK_DETECTION
+
+#include "lock/mcs_lock.hpp"
+#include "lock/rw_spin_lock.hpp"
+#include "lock/spin_lock.hpp"
+#include "lock/ticket_lock.hpp"
+
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+
+std::vector<std::string> Reports;
+
+/// @brief Records the reports instead of aborting, the test never blocks on the reported locks.
+void record_report(const char *report)
+{
+    Reports.emplace_back(report);
+}
+
+/// @brief Prints and clears the recorded reports.
+void print_reports(const char *title)
+{
+    std::cout << title << ": " << Reports.size() << " reports" << std::endl;
+    for (auto &r: Reports) {
+        std::cout << "  " << r.substr(0, r.find(',')) << std::endl;
+    }
+    Reports.clear();
+}
+
+int main()
+{
+    sc::deadlock::set_handler(record_report);
+
+    {
+        // The same order on all threads forms no cycle.
+        sc::lock::SpinLock<uint64_t> a(0);
+        sc::lock::TicketLock<uint64_t> b(0);
+        std::vector<std::thread> threads;
+        for (uint32_t i = 0; i < ThreadCount; ++i) {
+            threads.emplace_back([&] {
+                for (uint64_t n = 0; n < LoopCount / 100; ++n) {
+                    auto ga = a.lock();
+                    auto gb = b.lock();
+                    ++*ga;
+                    ++*gb;
+                }
+            });
+        }
+        for (auto &t: threads) {
+            t.join();
+        }
+        std::cout << "Counts: " << *a.lock() << " / " << *b.lock() << ", expected: " << ThreadCount * (LoopCount / 100)
+                  << std::endl;
+        print_reports("Same order");
+    }
+
+    {
+        // A -> B on a thread, then B -> A on another thread is reported, though they never overlap.
+        sc::lock::SpinLock<int> a(0);
+        sc::lock::McsLock<int> b(0);
+        std::thread([&] {
+            auto ga = a.lock();
+            auto gb = b.lock();
+        }).join();
+        std::thread([&] {
+            auto gb = b.lock();
+            auto ga = a.lock();
+        }).join();
+        print_reports("Inversion");
+    }
+
+    {
+        // A -> B -> C, then C -> A closes the cycle through B.
+        sc::lock::SpinLock<int> a(0);
+        sc::lock::SpinLock<int> b(0);
+        sc::lock::SpinLock<int> c(0);
+        {
+            auto ga = a.lock();
+            auto gb = b.lock();
+        }
+        {
+            auto gb = b.lock();
+            auto gc = c.lock();
+        }
+        {
+            auto gc = c.lock();
+            auto ga = a.lock();
+        }
+        print_reports("Transitive inversion");
+    }
+
+    {
+        // A reader taking the read lock again deadlocks once a writer is waiting. No writer is
+        // waiting here, so the second read succeeds after the report.
+        sc::lock::RwSpinLock<int> lock(0);
+        auto g1 = lock.read();
+        auto g2 = lock.read();
+        print_reports("Recursive read");
+    }
+
+    {
+        // The try locks do not block, so they order no lock, while the held ones still count.
+        sc::lock::SpinLock<int> a(0);
+        sc::lock::RwSpinLock<int> b(0);
+        {
+            auto ga = a.lock();
+            auto gb = b.try_write();
+        }
+        {
+            auto gb = b.write();
+            auto ga = a.try_lock();
+        }
+        {
+            auto upgradeable = b.read_upgradeable();
+            auto gw = std::move(upgradeable).upgrade();
+            auto ga = a.lock();
+        }
+        {
+            auto ga = a.lock();
+        }
+        print_reports("Try locks and upgrade");
+    }
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,122 @@
+///
+/// @file  delay_queue_test.cpp
+/// @brief Test for sc::DelayQueue.
+///
+
+#include "queue/delay_queue.hpp"
+
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "coro_util.hpp"
+#include "test_util.hpp"
+
+
+constexpr uint32_t ProducerCount = 4;
+constexpr uint64_t PushCount = LoopCount / 10000;
+
+#if __cpp_impl_coroutine
+
+DetachedTask async_pop(sc::DelayQueue<std::string> &queue, std::vector<std::string> &popped)
+{
+    for (int i = 0; i < 2; ++i) {
+        popped.push_back(co_await queue.pop_expired_async());
+    }
+}
+
+void run_async()
+{
+    sc::DelayQueue<std::string> queue;
+    std::vector<std::string> popped;
+    queue.push_after("later", std::chrono::milliseconds(20));
+    queue.push_after("sooner", std::chrono::milliseconds(10));
+    async_pop(queue, popped);
+    auto suspended = popped.empty();
+
+    // The driver loop resumes the coroutine when the items expire.
+    size_t resumed = 0;
+    while (popped.size() < 2) {
+        resumed += queue.poll();
+        std::this_thread::sleep_for(std::chrono::milliseconds(1));
+    }
+    std::cout << "Async suspended: " << suspended << ", resumed: " << resumed << ", popped: " << popped[0]
+              << " " << popped[1] << std::endl;
+}
+
+#else
+
+void run_async()
+{
+    std::cout << "Skip the async test: built without cpp coroutine support." << std::endl;
+}
+
+#endif
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        sc::DelayQueue<int> queue;
+        auto begin = sc::DelayQueue<int>::clock_type::now();
+        for (int ms: {30, 10, 20, 0}) {
+            queue.push_at(ms, begin + std::chrono::milliseconds(ms));
+        }
+        // The item of a passed deadline can be popped from the next tick.
+        std::this_thread::sleep_for(std::chrono::milliseconds(2));
+        auto first = queue.try_pop_expired();
+        std::cout << "Size: " << queue.size() << ", first: " << *first << ", try pop: "
+                  << !queue.try_pop_expired().has_value() << ", popped:";
+        bool early = false;
+        for (int i = 0; i < 3; ++i) {
+            auto ms = queue.pop_expired();
+            early = early || sc::DelayQueue<int>::clock_type::now() < begin + std::chrono::milliseconds(ms);
+            std::cout << " " << ms;
+        }
+        std::cout << ", popped early: " << early << ", empty: " << queue.is_empty() << std::endl;
+    }
+
+    {
+        // The deadlines beyond a whole round of the wheel.
+        sc::DelayQueue<int> queue(std::chrono::milliseconds(1), 4);
+        queue.push_after(2, std::chrono::milliseconds(25));
+        queue.push_after(1, std::chrono::milliseconds(6));
+        auto a = queue.pop_expired();
+        auto b = queue.pop_expired();
+        std::cout << "Multi-round popped: " << a << " " << b << std::endl;
+    }
+
+    // The producers push the items with the short delays, the consumers pop them all.
+    sc::DelayQueue<uint64_t> queue;
+    std::vector<std::thread> threads;
+    std::atomic<uint64_t> popped_sum{0};
+    auto begin = get_current_time();
+    for (uint32_t i = 0; i < ProducerCount; ++i) {
+        threads.emplace_back([&queue, i] {
+            for (uint64_t n = 0; n < PushCount; ++n) {
+                auto value = n * ProducerCount + i;
+                queue.push_after(value, std::chrono::microseconds(value % 7 * 500));
+            }
+        });
+        threads.emplace_back([&queue, &popped_sum] {
+            uint64_t sum = 0;
+            for (uint64_t n = 0; n < PushCount; ++n) {
+                sum += queue.pop_expired();
+            }
+            popped_sum.fetch_add(sum);
+        });
+    }
+    for (auto &t: threads) {
+        t.join();
+    }
+    auto total = ProducerCount * PushCount;
+    std::cout << "Popped sum matched: " << (popped_sum.load() == total * (total - 1) / 2) << ", empty: "
+              << queue.is_empty() << ", time: " << get_current_time() - begin << "ns" << std::endl;
+
+    run_async();
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,158 @@
+///
+/// @file  drop_queue_test.cpp
+/// @brief Test for sc::util::DropQueue and the sc::DeferredDrop backend of sc::SyncCell.
+///
+
+#include "cell/sync_cell.hpp"
+#include "util/drop_queue.hpp"
+
+#include <atomic>
+#include <chrono>
+#include <cstdlib>
+#include <new>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+
+static std::atomic<int64_t> LiveTables{0};
+static std::atomic<bool> DroppedOnWriter{false};
+
+thread_local bool IsWriter = false;
+/// @brief Fails the next allocation of the thread, to check the cell is unchanged.
+thread_local bool FailAllocation = false;
+
+void *operator new(size_t size)
+{
+    if (std::exchange(FailAllocation, false)) {
+        throw std::bad_alloc();
+    }
+    if (auto *p = std::malloc(size == 0 ? 1 : size)) {
+        return p;
+    }
+    throw std::bad_alloc();
+}
+
+// The replaced pair of the global new and delete is mistaken for a mismatch when inlined.
+#pragma GCC diagnostic push
+#pragma GCC diagnostic ignored "-Wmismatched-new-delete"
+
+void operator delete(void *p) noexcept
+{
+    std::free(p);
+}
+
+void operator delete(void *p, size_t) noexcept
+{
+    std::free(p);
+}
+
+#pragma GCC diagnostic pop
+
+/// @brief A value whose destructor is expensive, which must not run on the writers.
+struct Table
+{
+    std::vector<uint64_t> rows;
+
+    explicit Table(uint64_t n = 0) : rows(n, n)
+    {
+        LiveTables.fetch_add(1, std::memory_order_relaxed);
+    }
+
+    Table(const Table &other) : rows(other.rows)
+    {
+        LiveTables.fetch_add(1, std::memory_order_relaxed);
+    }
+
+    Table(Table &&other) noexcept : rows(std::move(other.rows))
+    {
+        LiveTables.fetch_add(1, std::memory_order_relaxed);
+    }
+
+    Table &operator=(const Table &other) = default;
+
+    Table &operator=(Table &&other) noexcept = default;
+
+    ~Table()
+    {
+        if (IsWriter && !rows.empty()) {
+            DroppedOnWriter.store(true, std::memory_order_relaxed);
+        }
+        LiveTables.fetch_sub(1, std::memory_order_relaxed);
+    }
+
+    bool operator==(const Table &) const = default;
+};
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    auto &queue = sc::util::DropQueue::global();
+    {
+        sc::SyncCell<Table, sc::DeferredDrop<>> cell(Table(10));
+        IsWriter = true;
+        cell.store(Table(20));
+        auto expected = Table(20);
+        auto replaced = cell.compare_exchange(expected, Table(30));
+        IsWriter = false;
+        std::cout << "Parked: " << queue.parked() << ", replaced: " << replaced << ", value: "
+                  << cell.load().rows.size() << ", dropped on the writer: " << DroppedOnWriter.load() << std::endl;
+        std::cout << "Collected: " << queue.collect() << ", parked: " << queue.parked() << std::endl;
+
+        static_assert(sc::SyncCell<uint64_t, sc::DeferredDrop<>>::IsAtomic);
+    }
+    std::cout << "Live after the cell destroyed: " << LiveTables.load() << std::endl;
+
+    {
+        // The node is allocated before the value is replaced, a failed allocation changes nothing.
+        sc::SyncCell<Table, sc::DeferredDrop<>> cell(Table(10));
+        Table next(20);
+        Table expected(10);
+        uint32_t failed = 0;
+        for (auto cas: {false, true}) {
+            FailAllocation = true;
+            try {
+                if (cas) {
+                    cell.compare_exchange(expected, std::move(next));
+                } else {
+                    cell.store(std::move(next));
+                }
+            } catch (const std::bad_alloc &) {
+                ++failed;
+            }
+            next = Table(20);
+        }
+        std::cout << "Failed allocations: " << failed << ", value kept: " << cell.load().rows.size()
+                  << ", parked: " << queue.parked() << std::endl;
+    }
+    std::cout << "Live after the cell destroyed: " << LiveTables.load() << std::endl;
+
+    // The writers only park, and the collector destroys on its own thread.
+    {
+        sc::util::DropCollector collector(std::chrono::milliseconds(1));
+        sc::SyncCell<Table, sc::DeferredDrop<>> cell(Table(1));
+        std::vector<std::thread> threads;
+        for (uint32_t t = 0; t < ThreadCount; ++t) {
+            threads.emplace_back([&] {
+                IsWriter = true;
+                for (uint64_t i = 0; i < LoopCount / 1000; ++i) {
+                    cell.store(Table(i % 64 + 1));
+                }
+            });
+        }
+        for (auto &t: threads) {
+            t.join();
+        }
+        std::cout << "Concurrent: dropped on the writers: " << DroppedOnWriter.load() << std::endl;
+    }
+    queue.collect();
+    std::cout << "Live after the collector stopped: " << LiveTables.load() << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
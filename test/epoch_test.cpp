@@ -0,0 +1,101 @@
+///
+/// @file  epoch_test.cpp
+/// @brief Test for the epoch-based memory reclamation.
+///
+
+#include "epoch/epoch.hpp"
+
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ReaderCount = 3;
+constexpr uint64_t UpdateCount = 100'000;
+
+static std::atomic<int64_t> LiveNodes{0};
+
+struct Node
+{
+    uint64_t version;
+    uint64_t checksum;      // always equals to 'version * 2'
+
+    explicit Node(uint64_t v) : version(v), checksum(v * 2)
+    {
+        LiveNodes.fetch_add(1, std::memory_order_relaxed);
+    }
+
+    ~Node()
+    {
+        // a destroyed node must not be read again.
+        version = checksum = 1;
+        LiveNodes.fetch_sub(1, std::memory_order_relaxed);
+    }
+};
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    std::atomic<Node *> shared{new Node(0)};
+    std::atomic_flag barrier = ATOMIC_FLAG_INIT;
+    std::atomic<bool> stop{false};
+    std::atomic<uint64_t> invalid_reads{0};
+
+    std::vector<std::thread> readers;
+    readers.reserve(ReaderCount);
+    for (uint32_t i = 0; i < ReaderCount; ++i) {
+        readers.emplace_back([&] {
+            barrier.wait(false);
+            while (!stop.load(std::memory_order_relaxed)) {
+                auto guard = sc::epoch::pin();
+                auto *node = shared.load(std::memory_order_acquire);
+                if (node->checksum != node->version * 2) {
+                    invalid_reads.fetch_add(1, std::memory_order_relaxed);
+                }
+            }
+        });
+    }
+
+    barrier.test_and_set();
+    barrier.notify_all();
+
+    auto begin = get_current_time();
+    uint64_t max_live = 0;
+    for (uint64_t i = 1; i <= UpdateCount; ++i) {
+        auto guard = sc::epoch::pin();
+        auto *old = shared.exchange(new Node(i), std::memory_order_acq_rel);
+        guard.defer_destroy(old);
+        max_live = std::max(max_live, (uint64_t)LiveNodes.load(std::memory_order_relaxed));
+    }
+    auto elapsed = get_current_time() - begin;
+
+    stop.store(true, std::memory_order_relaxed);
+    for (auto &t: readers) {
+        t.join();
+    }
+
+    std::cout << "Updates: " << UpdateCount << ", time: " << elapsed << "ns, invalid reads: "
+              << invalid_reads.load() << ", max live nodes: " << max_live << std::endl;
+
+    // no thread is pinned now, all retired nodes can be destroyed after two advances.
+    std::cout << "Is pinned: " << sc::epoch::is_pinned() << std::endl;
+    bool deferred_called = false;
+    {
+        auto guard = sc::epoch::pin();
+        guard.defer([&deferred_called] { deferred_called = true; });
+        std::cout << "Is pinned in a guard: " << sc::epoch::is_pinned() << std::endl;
+    }
+    for (int i = 0; i < 3; ++i) {
+        sc::epoch::pin().flush();
+    }
+    std::cout << "Live nodes after flush: " << LiveNodes.load() << ", expected: 1, deferred function called: "
+              << deferred_called << std::endl;
+
+    delete shared.load();
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
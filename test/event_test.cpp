@@ -0,0 +1,93 @@
+///
+/// @file  event_test.cpp
+/// @brief Test for sc::sync::Event.
+///
+
+#include "sync/event.hpp"
+
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t WaiterCount = 4;
+constexpr uint64_t PingPongCount = LoopCount / 100;
+
+void run_manual()
+{
+    sc::sync::Event event(sc::sync::ResetMode::Manual);
+    std::atomic<uint32_t> released{0};
+
+    std::vector<std::thread> threads;
+    threads.reserve(WaiterCount);
+    for (uint32_t i = 0; i < WaiterCount; ++i) {
+        threads.emplace_back([&event, &released] {
+            event.wait();
+            released.fetch_add(1, std::memory_order_relaxed);
+        });
+    }
+
+    std::this_thread::sleep_for(std::chrono::milliseconds(100));
+    auto released_before_set = released.load();
+    event.set();
+    for (auto &t: threads) {
+        t.join();
+    }
+
+    auto still_set = event.is_set();
+    event.reset();
+    auto timeout = !event.wait_timeout(std::chrono::milliseconds(100));
+    std::cout << "Manual released before set: " << released_before_set << ", after set: "
+              << released.load() << ", expected: " << WaiterCount << ", still set: " << still_set
+              << ", timeout after reset: " << timeout << std::endl;
+}
+
+/// @brief Two threads signal each other by turns with two auto-reset events.
+void run_auto()
+{
+    sc::sync::Event ping(sc::sync::ResetMode::Auto);
+    sc::sync::Event pong(sc::sync::ResetMode::Auto);
+    uint64_t value = 0;
+
+    auto begin = get_current_time();
+    std::thread t([&] {
+        for (uint64_t i = 0; i < PingPongCount; ++i) {
+            ping.wait();
+            ++value;
+            pong.set();
+        }
+    });
+
+    uint64_t errors = 0;
+    for (uint64_t i = 0; i < PingPongCount; ++i) {
+        ping.set();
+        pong.wait();
+        if (value != i + 1) {
+            ++errors;
+        }
+    }
+    t.join();
+
+    std::cout << "Auto ping-pong: " << value << ", expected: " << PingPongCount << ", errors: "
+              << errors << ", reset after wait: " << !pong.is_set() << ", time: "
+              << get_current_time() - begin << "ns" << std::endl;
+
+    // each set releases only one wait.
+    ping.set();
+    auto first = ping.try_wait();
+    auto second = ping.wait_timeout(std::chrono::milliseconds(100));
+    std::cout << "Auto first wait: " << first << ", second wait: " << second << std::endl;
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    run_manual();
+    run_auto();
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
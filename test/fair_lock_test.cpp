@@ -0,0 +1,94 @@
+///
+/// @file  fair_lock_test.cpp
+/// @brief Test for sc::lock::TicketLock and sc::lock::McsLock, compared with sc::lock::SpinLock.
+///
+
+#include "lock/mcs_lock.hpp"
+#include "lock/spin_lock.hpp"
+#include "lock/ticket_lock.hpp"
+
+#include <algorithm>
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+
+struct Pair
+{
+    uint64_t a = 0;
+    uint64_t b = 0;
+};
+
+/// @brief Each thread increments the value under the lock for a fixed time. The fair locks should
+/// have a similar acquisition count on each thread.
+template<typename Lock>
+void run_lock(const char *name)
+{
+    Lock lock;
+    {
+        auto guard = lock.lock();
+        std::cout << "[" << name << "] try lock when locked: " << bool(lock.try_lock()) << std::endl;
+    }
+
+    std::atomic_flag barrier = ATOMIC_FLAG_INIT;
+    std::atomic<bool> stop{false};
+    std::vector<uint64_t> counts(ThreadCount, 0);
+
+    std::vector<std::thread> threads;
+    threads.reserve(ThreadCount);
+    for (uint32_t i = 0; i < ThreadCount; ++i) {
+        threads.emplace_back([&, i] {
+            barrier.wait(false);
+            uint64_t count = 0;
+            while (!stop.load(std::memory_order_relaxed)) {
+                auto guard = lock.lock();
+                ++guard->a;
+                ++guard->b;
+                ++count;
+            }
+            counts[i] = count;
+        });
+    }
+
+    barrier.test_and_set();
+    barrier.notify_all();
+    std::this_thread::sleep_for(std::chrono::milliseconds(200));
+    stop.store(true, std::memory_order_relaxed);
+    for (auto &t: threads) {
+        t.join();
+    }
+
+    uint64_t total = 0;
+    for (auto c: counts) {
+        total += c;
+    }
+    auto [min, max] = std::minmax_element(counts.begin(), counts.end());
+    auto guard = lock.lock();
+    std::cout << "[" << name << "] value: " << guard->a << " / " << guard->b << ", expected: " << total
+              << ", min / max acquisitions per thread: " << *min << " / " << *max << std::endl;
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    run_lock<sc::lock::SpinLock<Pair>>("SpinLock");
+    run_lock<sc::lock::TicketLock<Pair>>("TicketLock");
+    run_lock<sc::lock::McsLock<Pair>>("McsLock");
+
+    // Without any other thread, the value is accessed without locking.
+    sc::lock::TicketLock<std::string> ticket_lock("a");
+    ticket_lock.get_unsafe() += "b";
+    sc::lock::McsLock<std::string> mcs_lock("c");
+    mcs_lock.get_unsafe() += "d";
+    std::cout << "Into inner: " << std::move(ticket_lock).into_inner() << " / " << std::move(mcs_lock).into_inner()
+              << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
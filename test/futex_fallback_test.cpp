@@ -0,0 +1,22 @@
+///
+/// @file  futex_fallback_test.cpp
+/// @brief Test for the parking table used with SYNC_CELL_NO_FUTEX.
+///
+
+#define SYNC_CELL_NO_FUTEX
+
+#include "futex_run.hpp"
+
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    std::cout << "SC_HAS_FUTEX: " << (SC_HAS_FUTEX == 1) << std::endl;
+    run_word();
+    run_primitives();
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
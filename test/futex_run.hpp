@@ -0,0 +1,159 @@
+///
+/// @file  futex_run.hpp
+/// @brief The futex parking checks shared by the native and the fallback test.
+///
+
+#ifndef SYNC_CELL_FUTEX_RUN_HPP
+#define SYNC_CELL_FUTEX_RUN_HPP
+
+#include "queue/blocking_queue.hpp"
+#include "queue/mpmc_list_queue.hpp"
+#include "shared/futex.hpp"
+#include "sync/event.hpp"
+#include "sync/semaphore.hpp"
+
+#include <atomic>
+#include <chrono>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t WaiterCount = 4;
+constexpr uint64_t RoundCount = LoopCount / 100;
+
+/// @brief The raw wait and wake on a word.
+inline void run_word()
+{
+    std::atomic<uint32_t> word{1};
+
+    // The word differs, so the wait returns at once.
+    auto start = std::chrono::steady_clock::now();
+    sc::impl::futex_wait(word, 0);
+    auto deadline = std::chrono::steady_clock::now() + std::chrono::milliseconds(50);
+    auto in_time = sc::impl::futex_wait_until(word, 1, deadline);
+    while (in_time) {
+        in_time = sc::impl::futex_wait_until(word, 1, deadline);
+    }
+    auto elapsed = std::chrono::steady_clock::now() - start;
+    std::cout << "Word: timed out after the deadline: " << (elapsed >= std::chrono::milliseconds(50)) << std::endl;
+
+    // Ping-pong: each side parks until the other one changes the word.
+    start = std::chrono::steady_clock::now();
+    std::thread other([&word] {
+        for (uint64_t i = 0; i < RoundCount; ++i) {
+            uint32_t v;
+            while ((v = word.load(std::memory_order_acquire)) % 2 == 1) {
+                sc::impl::futex_wait(word, v);
+            }
+            word.store(v + 1, std::memory_order_release);
+            sc::impl::futex_wake_one(word);
+        }
+    });
+    for (uint64_t i = 0; i < RoundCount; ++i) {
+        uint32_t v;
+        while ((v = word.load(std::memory_order_acquire)) % 2 == 0) {
+            sc::impl::futex_wait(word, v);
+        }
+        word.store(v + 1, std::memory_order_release);
+        sc::impl::futex_wake_one(word);
+    }
+    other.join();
+    std::cout << "Word ping-pong: rounds: " << (word.load() - 1) / 2 << ", time: "
+              << (std::chrono::steady_clock::now() - start).count() << "ns" << std::endl;
+
+    // Wake all.
+    std::atomic<uint32_t> gate{0};
+    std::atomic<uint32_t> released{0};
+    std::vector<std::thread> threads;
+    for (uint32_t i = 0; i < WaiterCount; ++i) {
+        threads.emplace_back([&] {
+            while (gate.load(std::memory_order_acquire) == 0) {
+                sc::impl::futex_wait(gate, 0);
+            }
+            released.fetch_add(1, std::memory_order_relaxed);
+        });
+    }
+    std::this_thread::sleep_for(std::chrono::milliseconds(20));
+    gate.store(1, std::memory_order_release);
+    sc::impl::futex_wake_all(gate);
+    for (auto &t: threads) {
+        t.join();
+    }
+    std::cout << "Word wake all: released: " << released.load() << std::endl;
+}
+
+/// @brief The primitives parked on the futex words.
+inline void run_primitives()
+{
+    {
+        sc::sync::Event ping(sc::sync::ResetMode::Auto);
+        sc::sync::Event pong(sc::sync::ResetMode::Auto);
+        std::thread other([&] {
+            for (uint64_t i = 0; i < RoundCount; ++i) {
+                ping.wait();
+                pong.set();
+            }
+        });
+        for (uint64_t i = 0; i < RoundCount; ++i) {
+            ping.set();
+            pong.wait();
+        }
+        other.join();
+        std::cout << "Event ping-pong done, timeout: " << !ping.wait_timeout(std::chrono::milliseconds(20))
+                  << std::endl;
+    }
+
+    {
+        sc::sync::Semaphore semaphore(1);
+        std::atomic<uint64_t> inside{0};
+        std::atomic<bool> exclusive{true};
+        std::vector<std::thread> threads;
+        for (uint32_t t = 0; t < WaiterCount; ++t) {
+            threads.emplace_back([&] {
+                for (uint64_t i = 0; i < RoundCount / 10; ++i) {
+                    auto permit = semaphore.acquire();
+                    if (inside.fetch_add(1) != 0) {
+                        exclusive = false;
+                    }
+                    std::this_thread::yield();
+                    inside.fetch_sub(1);
+                }
+            });
+        }
+        for (auto &t: threads) {
+            t.join();
+        }
+        auto held = semaphore.acquire();
+        std::cout << "Semaphore: exclusive: " << exclusive.load() << ", timeout: "
+                  << !semaphore.acquire_timeout(std::chrono::milliseconds(20)) << std::endl;
+    }
+
+    {
+        sc::BlockingQueue<sc::mpmc::LinkedListQueue<uint64_t>> queue;
+        std::atomic<uint64_t> sum{0};
+        std::vector<std::thread> consumers;
+        for (uint32_t t = 0; t < WaiterCount; ++t) {
+            consumers.emplace_back([&] {
+                while (auto v = queue.dequeue()) {
+                    sum.fetch_add(*v, std::memory_order_relaxed);
+                }
+            });
+        }
+        for (uint64_t i = 0; i < RoundCount; ++i) {
+            queue.enqueue(i);
+            if (i % 64 == 0) {
+                std::this_thread::sleep_for(std::chrono::microseconds(100));
+            }
+        }
+        queue.close();
+        for (auto &t: consumers) {
+            t.join();
+        }
+        std::cout << "BlockingQueue: sum: " << sum.load() << ", expected: " << RoundCount * (RoundCount - 1) / 2
+                  << ", timeout: " << !queue.try_dequeue_for(std::chrono::milliseconds(20)) << std::endl;
+    }
+}
+
+#endif //SYNC_CELL_FUTEX_RUN_HPP
@@ -0,0 +1,20 @@
+///
+/// @file  futex_test.cpp
+/// @brief Test for the futex parking of the platform.
+///
+
+#include "futex_run.hpp"
+
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    std::cout << "SC_HAS_FUTEX: " << (SC_HAS_FUTEX == 1) << std::endl;
+    run_word();
+    run_primitives();
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
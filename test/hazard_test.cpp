@@ -0,0 +1,101 @@
+///
+/// @file  hazard_test.cpp
+/// @brief Test for the hazard pointer based memory reclamation.
+///
+
+#include "hazard/hazard.hpp"
+
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ReaderCount = 3;
+constexpr uint64_t UpdateCount = 100'000;
+
+static std::atomic<int64_t> LiveNodes{0};
+
+struct Node
+{
+    uint64_t version;
+    uint64_t checksum;      // always equals to 'version * 2'
+
+    explicit Node(uint64_t v) : version(v), checksum(v * 2)
+    {
+        LiveNodes.fetch_add(1, std::memory_order_relaxed);
+    }
+
+    ~Node()
+    {
+        // a destroyed node must not be read again.
+        version = checksum = 1;
+        LiveNodes.fetch_sub(1, std::memory_order_relaxed);
+    }
+};
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    std::atomic<Node *> shared{new Node(0)};
+    std::atomic_flag barrier = ATOMIC_FLAG_INIT;
+    std::atomic<bool> stop{false};
+    std::atomic<uint64_t> invalid_reads{0};
+
+    std::vector<std::thread> readers;
+    readers.reserve(ReaderCount);
+    for (uint32_t i = 0; i < ReaderCount; ++i) {
+        readers.emplace_back([&] {
+            barrier.wait(false);
+            sc::hazard::HazardPointer hazard;
+            while (!stop.load(std::memory_order_relaxed)) {
+                auto *node = hazard.protect(shared);
+                if (node->checksum != node->version * 2) {
+                    invalid_reads.fetch_add(1, std::memory_order_relaxed);
+                }
+                hazard.reset();
+            }
+        });
+    }
+
+    barrier.test_and_set();
+    barrier.notify_all();
+
+    auto begin = get_current_time();
+    uint64_t max_live = 0;
+    for (uint64_t i = 1; i <= UpdateCount; ++i) {
+        auto *old = shared.exchange(new Node(i), std::memory_order_acq_rel);
+        sc::hazard::retire(old);
+        max_live = std::max(max_live, (uint64_t)LiveNodes.load(std::memory_order_relaxed));
+    }
+    auto elapsed = get_current_time() - begin;
+
+    stop.store(true, std::memory_order_relaxed);
+    for (auto &t: readers) {
+        t.join();
+    }
+
+    std::cout << "Updates: " << UpdateCount << ", time: " << elapsed << "ns, invalid reads: "
+              << invalid_reads.load() << ", max live nodes: " << max_live << std::endl;
+
+    // a protected node is kept after flush.
+    {
+        sc::hazard::HazardPointer hazard;
+        auto *node = hazard.protect(shared);
+        shared.store(new Node(0), std::memory_order_release);
+        sc::hazard::retire(node);
+        sc::hazard::flush();
+        std::cout << "Live nodes with a protected node: " << LiveNodes.load() << ", expected: 2" << std::endl;
+    }
+
+    // no node is protected now, all retired nodes can be destroyed.
+    sc::hazard::flush();
+    std::cout << "Live nodes after flush: " << LiveNodes.load() << ", expected: 1" << std::endl;
+
+    delete shared.load();
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
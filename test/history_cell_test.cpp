@@ -0,0 +1,67 @@
+///
+/// @file  history_cell_test.cpp
+/// @brief Test for sc::HistoryCell.
+///
+
+#include "cell/history_cell.hpp"
+
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        sc::HistoryCell<std::string, 3> cell("initial");
+        std::cout << "Empty history: " << cell.history().empty() << ", value: " << cell.load() << std::endl;
+        for (auto *s: {"a", "b", "c", "d"}) {
+            cell.store(s);
+        }
+        auto history = cell.history();
+        std::cout << "Value: " << cell.load() << ", writes: " << cell.write_count() << ", history:";
+        for (const auto &entry: history) {
+            std::cout << " " << entry.sequence << "=" << entry.value;
+        }
+        std::cout << ", written by this thread: " << (history[0].thread == std::this_thread::get_id())
+                  << ", ordered times: " << (history[0].at <= history[2].at) << std::endl;
+    }
+
+    // Each thread writes its own values in order, so a snapshot keeps the order of each thread.
+    {
+        constexpr size_t Kept = 64;
+        sc::HistoryCell<uint64_t, Kept, std::chrono::steady_clock> cell(0);
+        std::vector<std::thread> threads;
+        for (uint32_t t = 0; t < ThreadCount; ++t) {
+            threads.emplace_back([&, t] {
+                for (uint64_t i = 0; i < LoopCount / 100; ++i) {
+                    cell.store(i * ThreadCount + t);
+                }
+            });
+        }
+        bool ordered = true;
+        for (int i = 0; i < 1000; ++i) {
+            auto history = cell.history();
+            for (size_t k = 1; k < history.size(); ++k) {
+                ordered &= history[k].sequence == history[k - 1].sequence + 1 && history[k - 1].at <= history[k].at;
+            }
+        }
+        for (auto &t: threads) {
+            t.join();
+        }
+        auto history = cell.history();
+        std::cout << "Concurrent: writes: " << cell.write_count() << ", expected: " << ThreadCount * (LoopCount / 100)
+                  << ", kept: " << history.size() << ", ordered: " << ordered
+                  << ", latest is the value: " << (history[Kept - 1].value == cell.load()) << std::endl;
+    }
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,258 @@
+///
+/// @file  interleaving_test.cpp
+/// @brief The small concurrent cases of the cells, queues and locks, explored under the
+/// sc::testing::Scheduler and run on the free threads for the ThreadSanitizer build.
+///
+
+#include "testing/scheduler.hpp"
+
+#include "cell/sync_cell.hpp"
+#include "lock/rw_spin_lock.hpp"
+#include "lock/ticket_lock.hpp"
+#include "queue/mpmc_bounded_queue.hpp"
+#include "queue/mpmc_list_queue.hpp"
+
+#include <atomic>
+#include <string>
+#include <thread>
+#include <utility>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+/// @brief The count of the interleavings explored for each case.
+constexpr uint64_t SeedCount = 200;
+/// @brief The count of the runs on the free threads for each case, kept small for the sanitizer.
+constexpr uint64_t RoundCount = 200;
+
+/// @brief Runs a case under the scheduler with each seed, then on the free threads, and reports the
+/// failed runs.
+///
+/// A case is a default constructible type with a 'TaskCount', a 'task(index)' run by each task,
+/// and a 'check()' called after all tasks have completed. The spinning in the tasks must go through
+/// the yield points, i.e. @c sc::testing::yield_now or the @c sc::testing::Backoff.
+template<typename Case>
+void explore(const std::string &name)
+{
+    uint64_t scheduled = 0;
+    for (uint64_t seed = 0; seed < SeedCount; ++seed) {
+        Case c;
+        sc::testing::Scheduler scheduler(seed);
+        for (size_t i = 0; i < Case::TaskCount; ++i) {
+            scheduler.spawn([&c, i] { c.task(i); });
+        }
+        scheduler.run();
+        scheduled += !c.check();
+    }
+
+    uint64_t threaded = 0;
+    for (uint64_t round = 0; round < RoundCount; ++round) {
+        Case c;
+        std::vector<std::thread> threads;
+        for (size_t i = 0; i < Case::TaskCount; ++i) {
+            threads.emplace_back([&c, i] { c.task(i); });
+        }
+        for (auto &t: threads) {
+            t.join();
+        }
+        threaded += !c.check();
+    }
+    std::cout << name << ": failed schedules: " << scheduled << " of " << SeedCount << ", failed rounds: "
+              << threaded << " of " << RoundCount << std::endl;
+}
+
+/// @brief The CAS loops of the atomic cell, retried after another task has won.
+struct AtomicCellCase
+{
+    static constexpr size_t TaskCount = 3;
+
+    sc::SyncCell<uint64_t> cell{0};
+
+    void task(size_t)
+    {
+        for (uint32_t i = 0; i < 2; ++i) {
+            auto v = cell.load();
+            sc::testing::yield_now();
+            while (!cell.compare_exchange(v, v + 1)) {
+                sc::testing::yield_now();
+            }
+        }
+    }
+
+    bool check()
+    {
+        return cell.load() == TaskCount * 2;
+    }
+};
+
+/// @brief The updates of the lock-based cell, each task appends its own letters in order.
+struct LockedCellCase
+{
+    static constexpr size_t TaskCount = 3;
+
+    sc::SyncCell<std::string, sc::ForceLock<sc::testing::Backoff>> cell;
+
+    void task(size_t index)
+    {
+        cell.with_mut([index](std::string &s) { s.push_back(char('a' + index)); });
+        sc::testing::yield_now();
+        cell.with_mut([index](std::string &s) { s.push_back(char('A' + index)); });
+    }
+
+    bool check()
+    {
+        auto s = cell.load();
+        bool ordered = s.size() == TaskCount * 2;
+        for (size_t i = 0; i < TaskCount; ++i) {
+            ordered = ordered && s.find(char('a' + i)) < s.find(char('A' + i));
+        }
+        return ordered;
+    }
+};
+
+/// @brief Two producers and one consumer of the list queue, the items of each producer are kept in
+/// order.
+struct ListQueueCase
+{
+    static constexpr size_t TaskCount = 3;
+    static constexpr uint32_t ItemCount = 2;
+
+    sc::mpmc::LinkedListQueue<uint32_t> queue;
+    std::vector<uint32_t> received;
+
+    void task(size_t index)
+    {
+        if (index > 0) {
+            for (uint32_t i = 0; i < ItemCount; ++i) {
+                queue.enqueue(index * 10 + i);
+                sc::testing::yield_now();
+            }
+            return;
+        }
+        while (received.size() < ItemCount * 2) {
+            if (auto v = queue.try_dequeue()) {
+                received.push_back(*v);
+            } else {
+                sc::testing::yield_now();
+            }
+        }
+    }
+
+    bool check()
+    {
+        std::vector<uint32_t> next{10, 20};
+        for (auto v: received) {
+            auto &expected = next[v / 10 - 1];
+            if (v != expected) {
+                return false;
+            }
+            ++expected;
+        }
+        return queue.is_empty();
+    }
+};
+
+/// @brief The producers blocked on a full bounded queue, woken up by the consumer.
+struct BoundedQueueCase
+{
+    static constexpr size_t TaskCount = 3;
+
+    sc::mpmc::BoundedQueue<uint32_t, sc::testing::Backoff> queue{1};
+    uint32_t sum = 0;
+
+    void task(size_t index)
+    {
+        if (index > 0) {
+            queue.enqueue(uint32_t(index));
+            queue.enqueue(uint32_t(index * 10));
+            return;
+        }
+        for (uint32_t n = 0; n < 4;) {
+            if (auto v = queue.try_dequeue()) {
+                sum += *v;
+                ++n;
+            } else {
+                sc::testing::yield_now();
+            }
+        }
+    }
+
+    bool check()
+    {
+        return sum == 33 && queue.is_empty();
+    }
+};
+
+/// @brief The read-modify-writes under the ticket lock, whose holder yields inside.
+struct TicketLockCase
+{
+    static constexpr size_t TaskCount = 3;
+
+    sc::lock::TicketLock<uint32_t, sc::testing::Backoff> lock{0};
+
+    void task(size_t)
+    {
+        for (uint32_t i = 0; i < 2; ++i) {
+            auto guard = lock.lock();
+            auto v = *guard;
+            sc::testing::yield_now();
+            *guard = v + 1;
+        }
+    }
+
+    bool check()
+    {
+        return *lock.lock() == TaskCount * 2;
+    }
+};
+
+/// @brief A writer keeping two fields equal, which the readers never see torn.
+struct RwLockCase
+{
+    static constexpr size_t TaskCount = 3;
+
+    sc::lock::RwSpinLock<std::pair<uint32_t, uint32_t>, sc::testing::Backoff> lock{std::pair<uint32_t, uint32_t>{}};
+    std::atomic<bool> torn{false};
+
+    void task(size_t index)
+    {
+        for (uint32_t i = 0; i < 2; ++i) {
+            if (index == 0) {
+                auto guard = lock.write();
+                ++guard->first;
+                sc::testing::yield_now();
+                ++guard->second;
+            } else {
+                auto guard = lock.read();
+                auto first = guard->first;
+                sc::testing::yield_now();
+                if (first != guard->second) {
+                    torn.store(true);
+                }
+            }
+        }
+    }
+
+    bool check()
+    {
+        auto guard = lock.read();
+        return !torn.load() && guard->first == 2 && guard->second == 2;
+    }
+};
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    explore<AtomicCellCase>("SyncCell atomic");
+    explore<LockedCellCase>("SyncCell lock");
+    explore<ListQueueCase>("mpmc::LinkedListQueue");
+    explore<BoundedQueueCase>("mpmc::BoundedQueue");
+    explore<TicketLockCase>("TicketLock");
+    explore<RwLockCase>("RwSpinLock");
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
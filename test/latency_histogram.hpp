@@ -0,0 +1,159 @@
+///
+/// @file  latency_histogram.hpp
+/// @brief An HDR-style latency histogram for the stress tests and benchmarks.
+///
+
+#ifndef SYNC_CELL_LATENCY_HISTOGRAM_HPP
+#define SYNC_CELL_LATENCY_HISTOGRAM_HPP
+
+#include <algorithm>
+#include <bit>
+#include <cstdint>
+#include <limits>
+#include <ostream>
+#include <string_view>
+#include <vector>
+
+
+/// @brief Records the latencies in nanoseconds into the log-linear buckets: each power of two range
+/// is split into 'SubBucketCount / 2' linear sub-buckets, so the relative error of a reported value
+/// is below 2 / SubBucketCount (about 1.6%) for the whole 64-bit range, with a fixed memory cost.
+class LatencyHistogram
+{
+    static constexpr uint32_t SubBucketBits = 7;
+    static constexpr uint64_t SubBucketCount = uint64_t{1} << SubBucketBits;
+    static constexpr uint64_t SubBucketHalf = SubBucketCount / 2;
+    static constexpr size_t BucketCount = (64 - SubBucketBits + 2) * SubBucketHalf;
+
+public:
+    LatencyHistogram() : counts_(BucketCount, 0) { }
+
+    /// @brief Records a latency, the negative values (caused by the clock skew) are recorded as 0.
+    void record(int64_t ns)
+    {
+        auto v = static_cast<uint64_t>(std::max<int64_t>(ns, 0));
+        ++counts_[index_of(v)];
+        ++count_;
+        sum_ += static_cast<double>(v);
+        min_ = std::min(min_, v);
+        max_ = std::max(max_, v);
+    }
+
+    void merge(const LatencyHistogram &other)
+    {
+        for (size_t i = 0; i < BucketCount; ++i) {
+            counts_[i] += other.counts_[i];
+        }
+        count_ += other.count_;
+        sum_ += other.sum_;
+        min_ = std::min(min_, other.min_);
+        max_ = std::max(max_, other.max_);
+    }
+
+    [[nodiscard]] uint64_t count() const noexcept
+    {
+        return count_;
+    }
+
+    [[nodiscard]] uint64_t min() const noexcept
+    {
+        return count_ == 0 ? 0 : min_;
+    }
+
+    [[nodiscard]] uint64_t max() const noexcept
+    {
+        return max_;
+    }
+
+    [[nodiscard]] double mean() const noexcept
+    {
+        return count_ == 0 ? 0 : sum_ / static_cast<double>(count_);
+    }
+
+    /// @brief Returns the value at the 'percentile' (in [0, 100]), which is the highest value
+    /// equivalent to the bucket it falls in, but never above the recorded maximum.
+    [[nodiscard]] uint64_t percentile(double percentile) const noexcept
+    {
+        if (count_ == 0) {
+            return 0;
+        }
+
+        auto rank = static_cast<uint64_t>(percentile / 100.0 * static_cast<double>(count_) + 0.5);
+        rank = std::clamp<uint64_t>(rank, 1, count_);
+        uint64_t seen = 0;
+        for (size_t i = 0; i < BucketCount; ++i) {
+            seen += counts_[i];
+            if (seen >= rank) {
+                return std::min(highest_value_of(i), max_);
+            }
+        }
+        return max_;
+    }
+
+    /// @brief Prints the count and the p50/p99/p999 latencies in one line.
+    void print_summary(std::ostream &os, std::string_view name) const
+    {
+        os << "[Latency] " << name << " count: " << count() << ", min: " << min() << "ns, p50: "
+           << percentile(50) << "ns, p99: " << percentile(99) << "ns, p999: " << percentile(99.9)
+           << "ns, max: " << max() << "ns, mean: " << static_cast<uint64_t>(mean()) << "ns" << std::endl;
+    }
+
+    static void write_csv_header(std::ostream &os)
+    {
+        os << "name,count,min_ns,p50_ns,p99_ns,p999_ns,max_ns,mean_ns\n";
+    }
+
+    /// @brief Writes the summary as a CSV row, see @c write_csv_header for the columns.
+    void write_csv_row(std::ostream &os, std::string_view name) const
+    {
+        os << name << ',' << count() << ',' << min() << ',' << percentile(50) << ',' << percentile(99) << ','
+           << percentile(99.9) << ',' << max() << ',' << static_cast<uint64_t>(mean()) << '\n';
+    }
+
+    /// @brief Writes the summary and the non-empty buckets (as the [highest value, count] pairs) as
+    /// a JSON object.
+    void write_json(std::ostream &os, std::string_view name) const
+    {
+        os << "{\"name\":\"" << name << "\",\"count\":" << count() << ",\"min_ns\":" << min()
+           << ",\"p50_ns\":" << percentile(50) << ",\"p99_ns\":" << percentile(99) << ",\"p999_ns\":"
+           << percentile(99.9) << ",\"max_ns\":" << max() << ",\"mean_ns\":" << static_cast<uint64_t>(mean())
+           << ",\"buckets\":[";
+        bool first = true;
+        for (size_t i = 0; i < BucketCount; ++i) {
+            if (counts_[i] == 0) {
+                continue;
+            }
+            os << (first ? "" : ",") << '[' << highest_value_of(i) << ',' << counts_[i] << ']';
+            first = false;
+        }
+        os << "]}\n";
+    }
+
+private:
+    static size_t index_of(uint64_t v) noexcept
+    {
+        if (v < SubBucketCount) {
+            return v;
+        }
+        auto shift = static_cast<uint32_t>(std::bit_width(v)) - SubBucketBits;
+        return shift * SubBucketHalf + (v >> shift);
+    }
+
+    static uint64_t highest_value_of(size_t index) noexcept
+    {
+        if (index < SubBucketCount) {
+            return index;
+        }
+        auto shift = index / SubBucketHalf - 1;
+        auto sub = index - shift * SubBucketHalf;
+        return (sub << shift) + ((uint64_t{1} << shift) - 1);
+    }
+
+    std::vector<uint64_t> counts_;
+    uint64_t count_ = 0;
+    double sum_ = 0;
+    uint64_t min_ = std::numeric_limits<uint64_t>::max();
+    uint64_t max_ = 0;
+};
+
+#endif //SYNC_CELL_LATENCY_HISTOGRAM_HPP
@@ -0,0 +1,45 @@
+///
+/// @file  latency_histogram_test.cpp
+/// @brief Test for LatencyHistogram.
+///
+
+#include "latency_histogram.hpp"
+
+#include <iostream>
+#include <sstream>
+
+
+int main()
+{
+    // Uniform values 1..100000: the percentiles are within the bucket precision of the exact ones.
+    LatencyHistogram histogram;
+    for (int64_t v = 1; v <= 100000; ++v) {
+        histogram.record(v);
+    }
+    std::cout << "count: " << histogram.count() << ", min: " << histogram.min() << ", max: " << histogram.max()
+              << ", mean: " << histogram.mean() << std::endl;
+    std::cout << "p50: " << histogram.percentile(50) << " (exact 50000), p99: " << histogram.percentile(99)
+              << " (exact 99000), p999: " << histogram.percentile(99.9) << " (exact 99900)" << std::endl;
+
+    // The small values are exact, and the negative ones are clamped to 0.
+    LatencyHistogram small;
+    small.record(-5);
+    small.record(3);
+    small.record(3);
+    small.record(100);
+    std::cout << "small min: " << small.min() << ", p50: " << small.percentile(50) << ", p100: "
+              << small.percentile(100) << std::endl;
+
+    histogram.merge(small);
+    std::cout << "merged count: " << histogram.count() << ", min: " << histogram.min() << std::endl;
+
+    std::ostringstream csv;
+    LatencyHistogram::write_csv_header(csv);
+    small.write_csv_row(csv, "small");
+    std::cout << csv.str();
+    small.write_json(std::cout, "small");
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
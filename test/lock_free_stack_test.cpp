@@ -0,0 +1,113 @@
+///
+/// @file  lock_free_stack_test.cpp
+/// @brief Test for sc::stack::LockFreeStack.
+///
+
+#include "stack/lock_free_stack.hpp"
+#include "hazard/hazard.hpp"
+
+#include "queue_thread_run.hpp"
+
+
+constexpr uint32_t PusherCount = 2;
+constexpr uint32_t PopperCount = 2;
+
+template<typename Stack>
+void push_all(Stack &stack, std::atomic_flag &barrier, uint32_t index)
+{
+    barrier.wait(false);
+
+    Task task{};
+    task.tid = index;
+    for (uint64_t i = 0; i < LoopCount; ++i) {
+        task.task_id = i;
+        task.in_time = get_current_time();
+        stack.push(task);
+    }
+}
+
+template<typename Stack>
+void pop_some(Stack &stack, std::atomic_flag &barrier, std::atomic<uint64_t> &counter, std::vector<Task> &result)
+{
+    barrier.wait(false);
+
+    constexpr uint64_t Total = PusherCount * LoopCount;
+    uint64_t n = 0;
+    while (counter.load(std::memory_order_acquire) < Total) {
+        // drain the stack sometimes.
+        if (++n % 1024 == 0) {
+            auto tasks = stack.pop_all();
+            counter.fetch_add(tasks.size(), std::memory_order_acq_rel);
+            result.insert(result.end(), tasks.begin(), tasks.end());
+        } else if (auto task = stack.pop(); task) {
+            counter.fetch_add(1, std::memory_order_acq_rel);
+            result.push_back(*task);
+        }
+    }
+}
+
+template<typename Reclaimer>
+void run()
+{
+    sc::stack::LockFreeStack<Task, Reclaimer> stack;
+    using Stack = decltype(stack);
+    std::atomic_flag barrier = ATOMIC_FLAG_INIT;
+    std::atomic<uint64_t> counter{0};
+
+    std::cout << "Stack is lock free: " << stack.is_lock_free() << std::endl;
+
+    std::vector<std::thread> threads;
+    std::vector<std::vector<Task>> result(PopperCount);
+    for (uint32_t i = 0; i < PusherCount; ++i) {
+        threads.emplace_back(push_all<Stack>, std::ref(stack), std::ref(barrier), i);
+    }
+    for (uint32_t i = 0; i < PopperCount; ++i) {
+        threads.emplace_back(pop_some<Stack>, std::ref(stack), std::ref(barrier), std::ref(counter),
+                             std::ref(result[i]));
+    }
+
+    auto begin = get_current_time();
+    barrier.test_and_set();
+    barrier.notify_all();
+    for (auto &t: threads) {
+        t.join();
+    }
+    auto elapsed = get_current_time() - begin;
+
+    // every task must be popped exactly once.
+    std::vector<uint32_t> taken(PusherCount * LoopCount, 0);
+    for (auto &r: result) {
+        for (auto &t: r) {
+            ++taken[t.tid * LoopCount + t.task_id];
+        }
+    }
+    bool exactly_once = true;
+    for (auto c: taken) {
+        exactly_once = exactly_once && c == 1;
+    }
+    std::cout << "Time: " << elapsed << "ns, every task popped exactly once: " << exactly_once
+              << ", is empty: " << stack.is_empty() << std::endl;
+
+    Task task{};
+    task.task_id = 1;
+    stack.push(task);
+    task.task_id = 2;
+    stack.push(task);
+    auto tasks = stack.pop_all();
+    std::cout << "Pop all: " << tasks.size() << ", expected: 2, top task id: " << tasks.front().task_id
+              << ", expected: 2" << std::endl;
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    std::cout << "Run with the epoch-based reclamation:" << std::endl;
+    run<sc::epoch::Reclaimer>();
+    std::cout << "Run with the hazard pointers:" << std::endl;
+    run<sc::hazard::Reclaimer>();
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
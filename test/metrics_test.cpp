@@ -0,0 +1,119 @@
+///
+/// @file  metrics_test.cpp
+/// @brief Test for the contention counters enabled by SYNC_CELL_METRICS.
+///
+
+#define SYNC_CELL_METRICS
+
+#include "cell/sync_cell.hpp"
+#include "deque/work_stealing_deque.hpp"
+#include "lock/spin_lock.hpp"
+#include "queue/blocking_queue.hpp"
+#include "queue/mpmc_bounded_queue.hpp"
+
+#include <optional>
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+constexpr uint64_t OpCount = LoopCount / 100;
+
+void print_stats(const char *name, const sc::metrics::Stats &stats)
+{
+    std::cout << name << " CAS failures: " << stats.cas_failures << ", spins: " << stats.spin_iterations
+              << ", parks: " << stats.park_events << ", failed steals: " << stats.failed_steals << std::endl;
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    // No contention, no event.
+    sc::lock::SpinLock<uint64_t> idle_lock(0);
+    *idle_lock.lock() += 1;
+    std::cout << "Uncontended lock has no event: " << (idle_lock.stats() == sc::metrics::Stats{}) << std::endl;
+
+    // A thread waits for the lock held by the main thread.
+    sc::lock::SpinLock<uint64_t> held_lock(0);
+    std::optional guard{held_lock.lock()};
+    std::thread waiter([&held_lock] { *held_lock.lock() += 1; });
+    std::this_thread::sleep_for(std::chrono::milliseconds(50));
+    guard.reset();
+    waiter.join();
+    std::cout << "Waiting for the held lock spins: " << (held_lock.stats().spin_iterations > 0) << std::endl;
+
+    sc::lock::SpinLock<uint64_t> lock(0);
+    run_threads(ThreadCount, [&lock] {
+        for (uint64_t i = 0; i < OpCount; ++i) {
+            auto guard = lock.lock();
+            // Hold the lock for a while to let the others wait.
+            for (int n = 0; n < 10; ++n) {
+                *guard += 1;
+            }
+        }
+    });
+    print_stats("SpinLock", lock.stats());
+
+    sc::SyncCell<std::string> cell("hello");
+    run_threads(ThreadCount, [&cell] {
+        for (uint64_t i = 0; i < OpCount; ++i) {
+            cell.store(std::to_string(i));
+        }
+    });
+    print_stats("SyncCell<std::string>", cell.stats());
+
+    // The producers wait on the full queue.
+    sc::mpmc::BoundedQueue<uint64_t> bounded(1);
+    std::thread consumer([&bounded] {
+        for (uint64_t n = 0; n < OpCount * ThreadCount;) {
+            if (bounded.try_dequeue()) {
+                ++n;
+            }
+        }
+    });
+    run_threads(ThreadCount, [&bounded] {
+        for (uint64_t i = 0; i < OpCount; ++i) {
+            bounded.enqueue(i);
+        }
+    });
+    consumer.join();
+    print_stats("BoundedQueue", bounded.stats());
+
+    // The consumer parks on the empty queue until the producer enqueues.
+    sc::BlockingQueue<sc::mpmc::BoundedQueue<uint64_t>> blocking(4);
+    std::thread consumer_thread([&blocking] { (void) blocking.dequeue(); });
+    std::this_thread::sleep_for(std::chrono::milliseconds(100));
+    blocking.enqueue(1);
+    consumer_thread.join();
+    std::cout << "BlockingQueue parked: " << (blocking.stats().park_events > 0) << std::endl;
+
+    // The stealers race for the last tasks.
+    sc::deque::Worker<uint64_t> worker;
+    std::atomic<uint64_t> stolen{0};
+    std::vector<std::thread> stealers;
+    for (uint32_t i = 0; i < ThreadCount; ++i) {
+        stealers.emplace_back([&stolen, s = worker.stealer()] {
+            while (stolen.load(std::memory_order_relaxed) < OpCount) {
+                if (s.steal().success()) {
+                    stolen.fetch_add(1, std::memory_order_relaxed);
+                }
+            }
+        });
+    }
+    for (uint64_t i = 0; i < OpCount; ++i) {
+        worker.push(i);
+    }
+    for (auto &t: stealers) {
+        t.join();
+    }
+    print_stats("Worker", worker.stats());
+    std::cout << "Stealer shares the counters: " << (worker.stealer().stats() == worker.stats()) << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -4,15 +4,79 @@
 ///
 
 #include "queue/mpmc_array_queue.hpp"
+#include "util/sharded_counter.hpp"
 
 #include "queue_thread_run.hpp"
 
 
 constexpr uint32_t ProducerCount = 4;
 constexpr uint32_t ConsumerCount = 2;
+constexpr uint32_t BatchSize = 100;
 
-int main()
+/// @brief Pushes and pops the tasks in batches. Checks that every task is dequeued exactly once,
+/// and the tasks of one producer are dequeued in the FIFO order by each consumer.
+void run_batch()
 {
+    sc::mpmc::ArrayListQueue<Task> queue;
+    std::atomic_flag barrier = ATOMIC_FLAG_INIT;
+    sc::util::ShardedCounter<> counter;
+    constexpr uint64_t Total = ProducerCount * LoopCount;
+
+    std::vector<std::thread> threads;
+    for (uint32_t p = 0; p < ProducerCount; ++p) {
+        threads.emplace_back([&queue, &barrier, p] {
+            barrier.wait(false);
+            std::vector<Task> batch(BatchSize);
+            for (uint64_t i = 0; i < LoopCount; i += BatchSize) {
+                for (uint32_t j = 0; j < BatchSize; ++j) {
+                    batch[j].tid = p;
+                    batch[j].task_id = i + j;
+                }
+                queue.enqueue_batch(batch.begin(), batch.end());
+            }
+        });
+    }
+
+    std::vector<std::vector<Task>> result(ConsumerCount);
+    for (uint32_t c = 0; c < ConsumerCount; ++c) {
+        threads.emplace_back([&queue, &barrier, &counter, &r = result[c]] {
+            barrier.wait(false);
+            while (counter.sum() < Total) {
+                counter.add(queue.try_dequeue_batch(r, BatchSize));
+            }
+        });
+    }
+
+    auto begin = get_current_time();
+    barrier.test_and_set();
+    barrier.notify_all();
+    for (auto &t: threads) {
+        t.join();
+    }
+    auto elapsed = get_current_time() - begin;
+
+    std::vector<uint32_t> taken(Total, 0);
+    bool fifo = true;
+    for (auto &r: result) {
+        std::vector<int64_t> last(ProducerCount, -1);
+        for (auto &t: r) {
+            ++taken[t.tid * LoopCount + t.task_id];
+            fifo = fifo && (int64_t)t.task_id > last[t.tid];
+            last[t.tid] = (int64_t)t.task_id;
+        }
+    }
+    bool exactly_once = true;
+    for (auto c: taken) {
+        exactly_once = exactly_once && c == 1;
+    }
+    std::cout << "[Batch] time: " << elapsed << "ns, every task dequeued exactly once: " << exactly_once
+              << ", fifo: " << fifo << std::endl;
+}
+
+int main(int argc, char **argv)
+{
+    std::cout << std::boolalpha;
+
     sc::mpmc::ArrayListQueue<Task> mpmc_queue;
     std::atomic_flag barrier = ATOMIC_FLAG_INIT;
 
@@ -52,6 +116,9 @@ int main()
     for (auto &t: mpmc_consumer_threads) {
         t.join();
     }
+    report_latency("mpmc::ArrayListQueue", mpmc_result, argc, argv);
+
+    run_batch();
 
     std::cout << "hello world" << std::endl;
 
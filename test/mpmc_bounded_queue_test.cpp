@@ -0,0 +1,234 @@
+///
+/// @file  mpmc_bounded_queue_test.cpp
+/// @brief Test for sc::mpmc::BoundedQueue.
+///
+
+#include "queue/mpmc_bounded_queue.hpp"
+
+#include "queue_thread_run.hpp"
+
+
+constexpr uint32_t ProducerCount = 4;
+constexpr uint32_t ConsumerCount = 2;
+constexpr size_t QueueCapacity = 1024;
+
+template<typename Queue>
+concept Resizable = requires(Queue &queue) {
+    queue.reserve(1);
+    queue.shrink_to_fit();
+};
+
+void run_resize()
+{
+    // Only the resizable queue pays for the gate of the migration.
+    static_assert(!Resizable<sc::mpmc::BoundedQueue<uint64_t>>);
+    static_assert(Resizable<sc::mpmc::ResizableBoundedQueue<uint64_t>>);
+
+    // Wrap around the buffer, then grow and shrink it: the order of the values is kept.
+    sc::mpmc::ResizableBoundedQueue<uint64_t> queue(4);
+    for (uint64_t i = 0; i < 3; ++i) {
+        queue.try_enqueue(i);
+    }
+    queue.try_dequeue();
+    queue.try_enqueue(3);
+    queue.try_enqueue(4);
+    auto full = !queue.try_enqueue(5);
+    queue.reserve(4);
+    auto grown = queue.capacity();
+    for (uint64_t i = 5; i < 9; ++i) {
+        queue.try_enqueue(i);
+    }
+    queue.try_dequeue();
+    queue.shrink_to_fit();
+    auto shrunk = queue.capacity();
+    auto full_after_shrink = !queue.try_enqueue(9);
+    bool ordered = true;
+    uint64_t expected = 2;
+    while (auto v = queue.try_dequeue()) {
+        ordered = ordered && *v == expected++;
+    }
+    queue.shrink_to_fit();
+    std::cout << "Resize: full: " << full << ", grown: " << grown << ", shrunk: " << shrunk << ", full after shrink: "
+              << full_after_shrink << ", ordered: " << ordered << ", empty shrunk to: " << queue.capacity()
+              << std::endl;
+
+    // Resize while the producers and consumers are running, no value is lost.
+    constexpr uint32_t ThreadCount = 2;
+    constexpr uint64_t Count = LoopCount / 10;
+    sc::mpmc::ResizableBoundedQueue<uint64_t> shared(16);
+    std::atomic<uint64_t> sum{0};
+    std::atomic<uint64_t> received{0};
+    std::vector<std::thread> threads;
+    for (uint32_t i = 0; i < ThreadCount; ++i) {
+        threads.emplace_back([&, i] {
+            for (uint64_t n = i; n < Count; n += ThreadCount) {
+                shared.enqueue(n);
+            }
+        });
+        threads.emplace_back([&] {
+            while (received.load(std::memory_order_relaxed) < Count) {
+                if (auto v = shared.try_dequeue()) {
+                    sum.fetch_add(*v, std::memory_order_relaxed);
+                    received.fetch_add(1, std::memory_order_relaxed);
+                }
+            }
+        });
+    }
+    uint64_t resizes = 0;
+    while (received.load(std::memory_order_relaxed) < Count) {
+        if (resizes % 2 == 0) {
+            shared.reserve(64);
+        } else {
+            shared.shrink_to_fit();
+        }
+        ++resizes;
+        std::this_thread::yield();
+    }
+    for (auto &t: threads) {
+        t.join();
+    }
+    std::cout << "Concurrent resize: sum: " << sum.load() << ", expected: " << Count * (Count - 1) / 2
+              << ", resizes > 0: " << (resizes > 0) << std::endl;
+}
+
+void run_overflow()
+{
+    using Queue = sc::mpmc::BoundedQueue<uint64_t>;
+    std::vector<uint64_t> dropped;
+    auto on_drop = [&dropped](uint64_t &&v) { dropped.push_back(v); };
+
+    Queue reject(2, sc::mpmc::Overflow::Reject, on_drop);
+    bool results[3];
+    for (uint64_t i = 0; i < 3; ++i) {
+        results[i] = reject.enqueue(i);
+    }
+    std::cout << "Reject: " << results[0] << " " << results[1] << " " << results[2] << ", dropped: "
+              << dropped.size() << std::endl;
+
+    Queue oldest(2, sc::mpmc::Overflow::DropOldest, on_drop);
+    for (uint64_t i = 0; i < 5; ++i) {
+        oldest.enqueue(i);
+    }
+    std::cout << "DropOldest: dropped:";
+    for (auto v: dropped) {
+        std::cout << " " << v;
+    }
+    std::cout << ", kept: " << *oldest.try_dequeue() << " " << *oldest.try_dequeue() << std::endl;
+
+    dropped.clear();
+    Queue newest(2, sc::mpmc::Overflow::DropNewest, on_drop);
+    for (uint64_t i = 0; i < 5; ++i) {
+        newest.enqueue(i);
+    }
+    std::cout << "DropNewest: dropped:";
+    for (auto v: dropped) {
+        std::cout << " " << v;
+    }
+    std::cout << ", kept: " << *newest.try_dequeue() << " " << *newest.try_dequeue() << std::endl;
+
+    // The producers never block, the consumed and the dropped values are all values.
+    constexpr uint64_t Count = LoopCount / 10;
+    std::atomic<uint64_t> drop_count{0};
+    Queue shared(16, sc::mpmc::Overflow::DropOldest, [&drop_count](uint64_t &&) {
+        drop_count.fetch_add(1, std::memory_order_relaxed);
+    });
+    std::atomic<bool> done{false};
+    uint64_t consumed = 0;
+    std::thread consumer([&] {
+        while (!done.load(std::memory_order_acquire)) {
+            consumed += shared.try_dequeue().has_value();
+        }
+        while (shared.try_dequeue()) {
+            ++consumed;
+        }
+    });
+    std::vector<std::thread> producers;
+    for (uint32_t i = 0; i < ProducerCount; ++i) {
+        producers.emplace_back([&] {
+            for (uint64_t n = 0; n < Count; ++n) {
+                shared.enqueue(n);
+            }
+        });
+    }
+    for (auto &t: producers) {
+        t.join();
+    }
+    done.store(true, std::memory_order_release);
+    consumer.join();
+    std::cout << "Concurrent DropOldest: consumed + dropped: " << consumed + drop_count.load() << ", expected: "
+              << ProducerCount * Count << std::endl;
+
+    sc::mpmc::FixedBoundedQueue<uint64_t, 2> fixed(sc::mpmc::Overflow::DropNewest);
+    for (uint64_t i = 0; i < 3; ++i) {
+        fixed.enqueue(i);
+    }
+    std::cout << "Fixed DropNewest: len: " << fixed.len() << std::endl;
+}
+
+int main(int argc, char **argv)
+{
+    sc::mpmc::BoundedQueue<Task> mpmc_queue(QueueCapacity);
+    std::atomic_flag barrier = ATOMIC_FLAG_INIT;
+
+    std::cout << "Queue is lock free: " << std::boolalpha <<
+              mpmc_queue.is_lock_free() << std::endl;
+
+    // fill the queue and check the 'Full' state.
+    size_t full_count = 0;
+    while (mpmc_queue.try_enqueue(Task{})) {
+        ++full_count;
+    }
+    std::cout << "Queue full after enqueue " << full_count << " items, capacity: "
+              << mpmc_queue.capacity() << std::endl;
+    while (mpmc_queue.try_dequeue()) { }
+
+    std::vector<std::thread> mpmc_produce_threads;
+    mpmc_produce_threads.reserve(ProducerCount);
+    for (uint32_t i = 0; i < ProducerCount; ++i) {
+        mpmc_produce_threads.emplace_back(
+                produce<sc::mpmc::BoundedQueue<Task>>,
+                std::ref(mpmc_queue), std::ref(barrier));
+    }
+
+    constexpr uint64_t Total = ProducerCount * LoopCount;
+    constexpr uint64_t ConsumerResultSize = Total / ConsumerCount;
+    static_assert(ConsumerResultSize * ConsumerCount == Total);
+
+    std::vector<std::thread> mpmc_consumer_threads;
+    mpmc_consumer_threads.reserve(ConsumerCount);
+    std::vector<std::vector<Task>> mpmc_result(ConsumerCount);
+    for (auto &r : mpmc_result) {
+        r.reserve(ConsumerResultSize);
+    }
+    for (uint32_t i = 0; i < ConsumerCount; ++i) {
+        mpmc_consumer_threads.emplace_back(
+                consume<sc::mpmc::BoundedQueue<Task>>,
+                std::ref(mpmc_queue), std::ref(barrier),
+                std::ref(mpmc_result[i]), ConsumerResultSize);
+    }
+
+    std::this_thread::sleep_for(std::chrono::seconds(2));
+    // begin
+    barrier.test_and_set();
+    barrier.notify_all();
+
+    for (auto &t: mpmc_produce_threads) {
+        t.join();
+    }
+    for (auto &t: mpmc_consumer_threads) {
+        t.join();
+    }
+    report_latency("mpmc::BoundedQueue", mpmc_result, argc, argv);
+
+    // The buffer of the fixed queue is inline.
+    sc::mpmc::FixedBoundedQueue<Task, 8> fixed_queue;
+    std::cout << "Fixed queue capacity: " << fixed_queue.capacity() << ", size: " << sizeof(fixed_queue)
+              << std::endl;
+
+    run_resize();
+    run_overflow();
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
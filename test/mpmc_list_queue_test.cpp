@@ -15,7 +15,7 @@ constexpr uint32_t ProducerCount = 4;
 constexpr uint32_t ConsumerCount = 2;
 
 template<typename Queue>
-void run()
+void run(std::string_view name, int argc, char **argv)
 {
     Queue task_queue;
     std::atomic_flag barrier = ATOMIC_FLAG_INIT;
@@ -59,18 +59,19 @@ void run()
     for (auto &t: consume_threads) {
         t.join();
     }
+    report_latency(name, result, argc, argv);
 }
 
 int main(int argc, char **argv)
 {
     if (argc > 1 && strcmp(argv[1], "-v2") == 0) {
 #if __cpp_lib_atomic_shared_ptr
-        run<sc::mpmc::LinkedListQueueV2<Task>>();
+        run<sc::mpmc::LinkedListQueueV2<Task>>("mpmc::LinkedListQueueV2", argc, argv);
 #else
         std::cout << "Error: Built the test without cpp atomic_shared_ptr support." << std::endl;
 #endif
     } else {
-        run<sc::mpmc::LinkedListQueue<Task>>();
+        run<sc::mpmc::LinkedListQueue<Task>>("mpmc::LinkedListQueue", argc, argv);
     }
 
     std::cout << "hello world" << std::endl;
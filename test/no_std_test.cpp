@@ -0,0 +1,67 @@
+///
+/// @file  no_std_test.cpp
+/// @brief Test for the build with SYNC_CELL_NO_STD: the core headers do not include the OS-dependent
+/// headers of the standard library.
+///
+
+#define SYNC_CELL_NO_STD
+
+#include "cell/lazy_sync_cell.hpp"
+#include "cell/once_sync_cell.hpp"
+#include "cell/seq_lock_cell.hpp"
+#include "cell/sync_cell.hpp"
+#include "lock/mcs_lock.hpp"
+#include "lock/rw_spin_lock.hpp"
+#include "lock/spin_lock.hpp"
+#include "lock/ticket_lock.hpp"
+#include "queue/mpmc_array_queue.hpp"
+#include "queue/mpmc_bounded_queue.hpp"
+#include "queue/spsc_ring_buffer.hpp"
+
+// The include guards of libstdc++.
+#if defined(_GLIBCXX_THREAD) || defined(_GLIBCXX_MUTEX) || defined(_GLIBCXX_CONDITION_VARIABLE) || defined(_GLIBCXX_CHRONO)
+#error "An OS-dependent header is included with SYNC_CELL_NO_STD."
+#endif
+
+#include <string>
+
+#include "test_util.hpp"
+
+
+// The statically allocated queue, no heap allocation.
+constinit sc::mpmc::FixedBoundedQueue<int, 4> StaticQueue;
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    sc::SyncCell<std::string> cell("hello");
+    cell.store("world");
+    sc::SeqLockCell<uint64_t> seq_cell(1);
+    seq_cell.update([](uint64_t v) { return v + 1; });
+    sc::OnceSyncCell<int> once;
+    once.get_or_init([] { return 42; });
+    std::cout << "Cells: " << cell.load() << ", " << seq_cell.read() << ", " << *once.get() << std::endl;
+
+    sc::lock::SpinLock<int> spin_lock(1);
+    sc::lock::RwSpinLock<int> rw_lock(2);
+    sc::lock::TicketLock<int> ticket_lock(3);
+    sc::lock::McsLock<int> mcs_lock(4);
+    std::cout << "Locks: " << *spin_lock.lock() << ", " << *rw_lock.read() << ", " << *ticket_lock.lock()
+              << ", " << *mcs_lock.lock() << std::endl;
+
+    sc::mpmc::ArrayListQueue<int> array_queue;
+    array_queue.enqueue(5);
+    sc::mpmc::BoundedQueue<int> bounded_queue(4);
+    bounded_queue.enqueue(6);
+    sc::spsc::RingBuffer<int> ring_buffer(4);
+    auto [producer, consumer] = ring_buffer.split();
+    producer.enqueue(7);
+    StaticQueue.enqueue(8);
+    std::cout << "Queues: " << *array_queue.try_dequeue() << ", " << *bounded_queue.try_dequeue() << ", "
+              << *consumer.try_dequeue() << ", " << *StaticQueue.try_dequeue() << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,88 @@
+///
+/// @file  numa_test.cpp
+/// @brief Test for the NUMA node hints enabled by SYNC_CELL_NUMA.
+///
+
+#define SYNC_CELL_NUMA
+
+#include "deque/work_stealing_deque.hpp"
+#include "pool/thread_pool.hpp"
+#include "queue/mpmc_bounded_queue.hpp"
+#include "queue/spsc_ring_buffer.hpp"
+#include "shared/numa.hpp"
+
+#include <atomic>
+#include <cstdint>
+#include <cstring>
+
+#include "test_util.hpp"
+
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    auto node = sc::numa::current_node();
+    std::cout << "SC_HAS_NUMA: " << (SC_HAS_NUMA == 1) << ", current node known: "
+              << (node != sc::numa::AnyNode) << std::endl;
+
+    // The memory is usable whether the node exists or not, the binding is only a preference.
+    for (int hint: {sc::numa::AnyNode, node, 63}) {
+        auto *p = static_cast<unsigned char *>(sc::numa::allocate(10000, 64, hint));
+        std::memset(p, 0xab, 10000);
+        std::cout << "allocate on " << hint << ": aligned: " << (reinterpret_cast<uintptr_t>(p) % 64 == 0)
+                  << ", last byte: " << (int) p[9999] << std::endl;
+        sc::numa::deallocate(p, 10000, 64, hint);
+    }
+
+    {
+        using Alloc = sc::numa::NodeAllocator<uint64_t>;
+        sc::mpmc::ResizableBoundedQueue<uint64_t, sc::util::Backoff, Alloc> queue(
+                4, sc::mpmc::Overflow::Block, {}, Alloc(node));
+        for (uint64_t i = 0; i < 4; ++i) {
+            queue.try_enqueue(i);
+        }
+        queue.reserve(64);
+        uint64_t sum = 0;
+        while (auto v = queue.try_dequeue()) {
+            sum += *v;
+        }
+        std::cout << "BoundedQueue: sum: " << sum << ", node kept: "
+                  << (queue.get_allocator().node() == node) << std::endl;
+
+        sc::spsc::RingBuffer<uint64_t, sc::util::Backoff, Alloc> buffer(16, Alloc(node));
+        auto [producer, consumer] = buffer.split();
+        producer.try_enqueue(7);
+        std::cout << "RingBuffer: value: " << *consumer.try_dequeue() << std::endl;
+    }
+
+    // The resized buffers of the deque stay on the node.
+    {
+        sc::deque::Worker<uint64_t> worker(sc::deque::Flavor::Fifo, node);
+        auto stealer = worker.stealer();
+        for (uint64_t i = 0; i < 1000; ++i) {
+            worker.push(i);
+        }
+        uint64_t sum = 0;
+        while (auto v = std::move(stealer.steal()).success()) {
+            sum += *v;
+        }
+        std::cout << "Worker: sum: " << sum << ", expected: " << 1000 * 999 / 2 << std::endl;
+    }
+
+    {
+        std::atomic<uint64_t> count{0};
+        {
+            sc::pool::ThreadPool pool(2, {node});
+            for (int i = 0; i < 100; ++i) {
+                pool.spawn([&] { count.fetch_add(1, std::memory_order_relaxed); });
+            }
+            pool.join();
+        }
+        std::cout << "ThreadPool: jobs run: " << count.load() << std::endl;
+    }
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,146 @@
+///
+/// @file  object_pool_test.cpp
+/// @brief Test for sc::pool::ObjectPool and sc::pool::PoolAllocator.
+///
+
+#include "pool/object_pool.hpp"
+#include "queue/mpmc_list_queue.hpp"
+#include "stack/lock_free_stack.hpp"
+
+#include <atomic>
+#include <memory>
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+/// @brief The bookkeeping of the blocks allocated by a @c CountingAllocator.
+struct Counts
+{
+    std::atomic<int64_t> allocations{0};
+    std::atomic<int64_t> live{0};
+};
+
+template<typename T>
+struct CountingAllocator
+{
+    using value_type = T;
+
+    Counts *counts;
+
+    explicit CountingAllocator(Counts *counts) noexcept : counts(counts) { }
+
+    template<typename U>
+    CountingAllocator(const CountingAllocator<U> &other) noexcept : counts(other.counts) { }
+
+    T *allocate(size_t n)
+    {
+        counts->allocations.fetch_add(1, std::memory_order_relaxed);
+        counts->live.fetch_add(1, std::memory_order_relaxed);
+        return std::allocator<T>().allocate(n);
+    }
+
+    void deallocate(T *p, size_t n) noexcept
+    {
+        counts->live.fetch_sub(1, std::memory_order_relaxed);
+        std::allocator<T>().deallocate(p, n);
+    }
+
+    template<typename U>
+    bool operator==(const CountingAllocator<U> &other) const noexcept
+    {
+        return counts == other.counts;
+    }
+};
+
+struct Payload
+{
+    uint64_t id;
+    std::string name;
+};
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    // A released block is reused by the next allocation of the thread.
+    {
+        Counts counts;
+        {
+            sc::pool::ObjectPool<Payload, CountingAllocator<Payload>> pool{CountingAllocator<Payload>(&counts)};
+            auto *first = pool.alloc(1, "first");
+            pool.dealloc(first);
+            auto second = pool.make(2, "second");
+            std::cout << "Reused: " << (second.get() == first) << ", name: " << second->name
+                      << ", allocations: " << counts.allocations.load() << std::endl;
+        }
+        std::cout << "Live after the pool destroyed: " << counts.live.load() << std::endl;
+    }
+
+    // A producer allocates and a consumer releases, the blocks go back by batches.
+    {
+        constexpr uint64_t Count = LoopCount / 10;
+        constexpr uint32_t InFlight = 256;
+        Counts counts;
+        {
+            sc::pool::ObjectPool<Payload, CountingAllocator<Payload>> pool{CountingAllocator<Payload>(&counts)};
+            sc::mpmc::LinkedListQueue<Payload *> queue;
+            std::atomic<uint64_t> sum{0};
+            std::thread consumer([&] {
+                for (uint64_t n = 0; n < Count;) {
+                    if (auto p = queue.try_dequeue()) {
+                        sum.fetch_add((*p)->id, std::memory_order_relaxed);
+                        pool.dealloc(*p);
+                        ++n;
+                    } else {
+                        std::this_thread::yield();
+                    }
+                }
+            });
+            for (uint64_t i = 0; i < Count; ++i) {
+                while (queue.len() >= InFlight) {
+                    std::this_thread::yield();
+                }
+                queue.enqueue(pool.alloc(i, "payload"));
+            }
+            consumer.join();
+            std::cout << "Producer / consumer: sum: " << sum.load() << ", expected: " << Count * (Count - 1) / 2
+                      << ", recycled: " << (counts.allocations.load() < (int64_t)Count / 2) << std::endl;
+        }
+        std::cout << "Live after the pool destroyed: " << counts.live.load() << std::endl;
+    }
+
+    // The containers take the nodes from the process-wide pools.
+    {
+        sc::mpmc::LinkedListQueue<uint64_t, 0, sc::pool::PoolAllocator<uint64_t>> queue;
+        sc::stack::LockFreeStack<uint64_t, sc::epoch::Reclaimer, sc::pool::PoolAllocator<uint64_t>> stack;
+        std::vector<std::thread> threads;
+        std::atomic<uint64_t> sum{0};
+        for (uint32_t t = 0; t < 2; ++t) {
+            threads.emplace_back([&] {
+                for (uint64_t i = 0; i < LoopCount / 10; ++i) {
+                    queue.enqueue(i);
+                    stack.push(i);
+                    // The enqueue of the other thread may be linked later, the queue looks empty until then.
+                    auto v = queue.try_dequeue();
+                    while (!v) {
+                        std::this_thread::yield();
+                        v = queue.try_dequeue();
+                    }
+                    sum.fetch_add(*v + *stack.pop(), std::memory_order_relaxed);
+                }
+            });
+        }
+        for (auto &t: threads) {
+            t.join();
+        }
+        std::cout << "PoolAllocator: sum: " << sum.load() << ", expected: "
+                  << 2 * (LoopCount / 10) * (LoopCount / 10 - 1) << std::endl;
+    }
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
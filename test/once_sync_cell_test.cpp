@@ -0,0 +1,120 @@
+///
+/// @file  once_sync_cell_test.cpp
+/// @brief Test for sc::OnceSyncCell and sc::LazySyncCell.
+///
+
+#include "cell/lazy_sync_cell.hpp"
+#include "cell/once_sync_cell.hpp"
+
+#include <stdexcept>
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 8;
+
+static std::atomic<uint32_t> LazyInitCount{0};
+
+static sc::LazySyncCell<std::vector<uint64_t>> LazyValue([] {
+    LazyInitCount.fetch_add(1, std::memory_order_relaxed);
+    std::this_thread::sleep_for(std::chrono::milliseconds(100));
+    return std::vector<uint64_t>(LoopCount, 1);
+});
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        sc::OnceSyncCell<std::string> cell;
+        std::atomic<uint32_t> init_count{0};
+        std::atomic_flag barrier = ATOMIC_FLAG_INIT;
+
+        std::vector<std::thread> threads;
+        std::vector<const std::string *> result(ThreadCount);
+        for (uint32_t i = 0; i < ThreadCount; ++i) {
+            threads.emplace_back([&, i] {
+                barrier.wait(false);
+                result[i] = &cell.get_or_init([&init_count, i] {
+                    init_count.fetch_add(1, std::memory_order_relaxed);
+                    // make the initialization slow to let the other threads wait.
+                    std::this_thread::sleep_for(std::chrono::milliseconds(100));
+                    return "thread-" + std::to_string(i);
+                });
+            });
+        }
+
+        barrier.test_and_set();
+        barrier.notify_all();
+        for (auto &t: threads) {
+            t.join();
+        }
+
+        bool same = true;
+        for (auto *p: result) {
+            same = same && p == cell.get();
+        }
+        std::cout << "Init count: " << init_count.load() << ", value: " << *cell.get()
+                  << ", all threads get the same value: " << same << std::endl;
+        std::cout << "Set an initialized cell: " << cell.set("value") << std::endl;
+    }
+
+    {
+        sc::OnceSyncCell<int> cell;
+        auto *failed = cell.get_or_try_init([] { return std::optional<int>(); });
+        std::cout << "Try init failed: " << (failed == nullptr) << ", initialized: " << cell.is_initialized() << std::endl;
+
+        try {
+            cell.get_or_init([]() -> int { throw std::runtime_error("init error"); });
+        } catch (const std::runtime_error &e) {
+            std::cout << "Init throws: " << e.what() << ", initialized: " << cell.is_initialized() << std::endl;
+        }
+
+        std::cout << "Set an empty cell: " << cell.set(42) << ", value: " << *cell.get() << std::endl;
+    }
+
+    {
+        std::cout << "Lazy value initialized before access: " << LazyValue.is_initialized() << std::endl;
+
+        std::atomic_flag barrier = ATOMIC_FLAG_INIT;
+        std::vector<std::thread> threads;
+        std::vector<size_t> result(ThreadCount);
+        for (uint32_t i = 0; i < ThreadCount; ++i) {
+            threads.emplace_back([&, i] {
+                barrier.wait(false);
+                result[i] = LazyValue->size();
+            });
+        }
+
+        barrier.test_and_set();
+        barrier.notify_all();
+        for (auto &t: threads) {
+            t.join();
+        }
+
+        bool same = true;
+        for (auto r: result) {
+            same = same && r == LoopCount;
+        }
+        std::cout << "Lazy init count: " << LazyInitCount.load() << ", all threads see the value: " << same
+                  << std::endl;
+    }
+
+    {
+        sc::OnceSyncCell<std::string> cell;
+        std::cout << "Empty cell pointer: " << (cell.get_unsafe() == nullptr);
+        cell.set("a");
+        *cell.get_unsafe() += "b";
+        std::cout << ", into inner: " << *std::move(cell).into_inner();
+
+        sc::LazySyncCell<std::string> lazy([] { return std::string("lazy"); });
+        std::cout << ", lazy into inner: " << std::move(lazy).into_inner() << std::endl;
+    }
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,96 @@
+///
+/// @file  oneshot_cell_test.cpp
+/// @brief Test for sc::OneshotCell.
+///
+
+#include "cell/oneshot_cell.hpp"
+
+#include <memory>
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "coro_util.hpp"
+#include "test_util.hpp"
+
+
+constexpr uint64_t RoundCount = LoopCount / 1000;
+
+#if __cpp_impl_coroutine
+
+DetachedTask async_recv(sc::OneshotCell<std::string> &cell, std::string &received)
+{
+    received = co_await cell.recv_async();
+}
+
+void run_async()
+{
+    sc::OneshotCell<std::string> cell;
+    std::string received;
+    async_recv(cell, received);
+    auto suspended = received.empty();
+    cell.send("async");
+
+    // The value sent before the 'co_await' is received without suspending.
+    sc::OneshotCell<std::string> ready;
+    ready.send("ready");
+    std::string received_ready;
+    async_recv(ready, received_ready);
+    std::cout << "Async suspended: " << suspended << ", received: " << received << ", received ready: "
+              << received_ready << std::endl;
+}
+
+#else
+
+void run_async()
+{
+    std::cout << "Skip the async test: built without cpp coroutine support." << std::endl;
+}
+
+#endif
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        sc::OneshotCell<std::unique_ptr<int>> cell;
+        std::cout << "Try recv empty: " << !cell.try_recv().has_value() << ", send: "
+                  << cell.send(std::make_unique<int>(1)) << ", send again: " << cell.send(std::make_unique<int>(2))
+                  << ", ready: " << cell.is_ready();
+        auto value = cell.try_recv();
+        std::cout << ", received: " << **value << ", ready after: " << cell.is_ready() << ", sent: "
+                  << cell.is_sent() << std::endl;
+    }
+
+    // The rounds of request-response between the main thread and a worker thread.
+    std::vector<std::unique_ptr<sc::OneshotCell<uint64_t>>> requests;
+    std::vector<std::unique_ptr<sc::OneshotCell<uint64_t>>> responses;
+    for (uint64_t n = 0; n < RoundCount; ++n) {
+        requests.push_back(std::make_unique<sc::OneshotCell<uint64_t>>());
+        responses.push_back(std::make_unique<sc::OneshotCell<uint64_t>>());
+    }
+    std::thread worker([&] {
+        for (uint64_t n = 0; n < RoundCount; ++n) {
+            responses[n]->send(requests[n]->recv() * 2);
+        }
+    });
+    auto begin = get_current_time();
+    uint64_t bad = 0;
+    for (uint64_t n = 0; n < RoundCount; ++n) {
+        requests[n]->send(n);
+        if (responses[n]->recv() != n * 2) {
+            ++bad;
+        }
+    }
+    auto elapsed = get_current_time() - begin;
+    worker.join();
+    std::cout << "Rounds: " << RoundCount << ", bad responses: " << bad << ", time: " << elapsed << "ns"
+              << std::endl;
+
+    run_async();
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,134 @@
+///
+/// @file  pool_builder_test.cpp
+/// @brief Test for sc::pool::Builder and the pinning of the workers enabled by SYNC_CELL_AFFINITY.
+///
+
+#define SYNC_CELL_AFFINITY
+
+#include "pool/builder.hpp"
+
+#include <algorithm>
+#include <atomic>
+#include <mutex>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+/// @brief Runs the jobs on all workers, and returns the allowed cores seen by each job.
+template<typename Pool>
+std::vector<std::vector<int>> observe(Pool &pool, size_t jobs)
+{
+    std::mutex mtx;
+    std::vector<std::vector<int>> seen;
+    pool.scope([&](sc::pool::Scope &s) {
+        for (size_t i = 0; i < jobs; ++i) {
+            s.spawn([&] {
+                auto cores = sc::affinity::allowed_cores();
+                std::lock_guard guard(mtx);
+                seen.push_back(std::move(cores));
+            });
+        }
+    });
+    return seen;
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    auto allowed = sc::affinity::allowed_cores();
+    std::cout << "SC_HAS_AFFINITY: " << (SC_HAS_AFFINITY == 1) << ", allowed cores known: " << !allowed.empty()
+              << ", current core allowed: "
+              << (std::find(allowed.begin(), allowed.end(), sc::affinity::current_core()) != allowed.end())
+              << std::endl;
+
+    {
+        auto cores = sc::affinity::impl::parse_cpu_list("0-3,8,10-11\n");
+        std::cout << "Parsed:";
+        for (auto core: cores) {
+            std::cout << " " << core;
+        }
+        std::cout << std::endl;
+
+        // The malformed items are skipped, and a huge range stops at the max core count.
+        auto bad = sc::affinity::impl::parse_cpu_list("0-,x,2-1,3a,-4,5,99999999999,6-4000000000\n");
+        auto huge = sc::affinity::impl::parse_cpu_list("0-2147483647");
+        std::cout << "Parsed bad:";
+        for (auto core: bad) {
+            std::cout << " " << core;
+        }
+        std::cout << ", huge range size: " << huge.size() << std::endl;
+    }
+
+    {
+        // Each worker is pinned to one allowed core.
+        auto pool = sc::pool::Builder().threads(4).pin_workers(sc::pool::CoreSelection::spread()).build();
+        bool single = true;
+        bool inside = true;
+        for (auto &cores: observe(*pool, 64)) {
+            single = single && cores.size() == 1;
+            inside = inside && std::find(allowed.begin(), allowed.end(), cores.front()) != allowed.end();
+        }
+        std::cout << "Spread: threads: " << pool->thread_count() << ", pinned to one core: " << single
+                  << ", allowed: " << inside << std::endl;
+    }
+
+    {
+        // The workers of a node may run on all of its cores.
+        auto node_cores = sc::affinity::node_cores(0);
+        auto pool = sc::pool::Builder().threads(2).pin_workers(sc::pool::CoreSelection::numa_nodes({0})).build();
+        bool matched = true;
+        for (auto &cores: observe(*pool, 16)) {
+            matched = matched && (node_cores.empty() || cores == node_cores);
+        }
+        std::cout << "Node 0: pinned to its cores: " << matched << std::endl;
+    }
+
+    {
+        // A core not allowed leaves the workers unpinned, and the default pool is not pinned.
+        auto pool = sc::pool::Builder().threads(2).lifo_slot(false).pin_workers(
+                sc::pool::CoreSelection::cores({-1})).build();
+        auto unpinned = sc::pool::Builder().threads(2).build();
+        bool all_allowed = true;
+        for (auto &cores: observe(*pool, 16)) {
+            all_allowed = all_allowed && cores == allowed;
+        }
+        for (auto &cores: observe(*unpinned, 16)) {
+            all_allowed = all_allowed && cores == allowed;
+        }
+        std::cout << "Unpinned: all cores allowed: " << all_allowed << std::endl;
+    }
+
+    {
+        using Pool = sc::pool::BasicThreadPool<sc::deque::Worker<sc::pool::JobPtr>,
+                sc::mpmc::ArrayListQueue<sc::pool::JobPtr>, sc::pool::LocalitySteal>;
+        std::atomic<uint64_t> sum{0};
+        auto pool = sc::pool::BasicBuilder<Pool>().threads(3).steal_policy(sc::pool::LocalitySteal({0, 1, 2})).build();
+        for (uint64_t i = 1; i <= 100; ++i) {
+            pool->spawn([&sum, i] { sum.fetch_add(i, std::memory_order_relaxed); });
+        }
+        pool->join();
+        std::cout << "Locality pool sum: " << sum.load() << std::endl;
+
+        // Without a policy, the locality follows the cores of the pinning: the worker 0 on the core 0
+        // visits the worker 2 on the core 1 first, then the workers on the cores 8 and 9.
+        std::vector<int> nodes(4, sc::numa::AnyNode);
+        auto builder = sc::pool::BasicBuilder<Pool>().threads(4).pin_workers(
+                sc::pool::CoreSelection::cores({0, 8, 1, 9}));
+        auto derived = builder.resolved_steal_policy().victims(0, nodes);
+        auto given = builder.steal_policy(sc::pool::LocalitySteal()).resolved_steal_policy().victims(0, nodes);
+        std::cout << "Derived locality victims:";
+        for (auto victim: derived) {
+            std::cout << " " << victim;
+        }
+        std::cout << ", given policy victims:";
+        for (auto victim: given) {
+            std::cout << " " << victim;
+        }
+        std::cout << std::endl;
+    }
+
+    std::cout << "hello world" << std::endl;
+    return 0;
+}
@@ -0,0 +1,96 @@
+///
+/// @file  priority_queue_test.cpp
+/// @brief Test for sc::mpmc::PriorityQueue.
+///
+
+#include "queue/priority_queue.hpp"
+
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+constexpr uint64_t PushCount = LoopCount / 100;
+
+struct Job
+{
+    uint32_t priority;
+    uint32_t id;
+};
+
+struct ByPriority
+{
+    bool operator()(const Job &a, const Job &b) const noexcept
+    {
+        return a.priority < b.priority;
+    }
+};
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        sc::mpmc::PriorityQueue<Job, ByPriority> jobs;
+        std::cout << "Empty: " << jobs.is_empty() << ", pop empty: " << !jobs.pop_max().has_value() << std::endl;
+        uint32_t id = 0;
+        for (auto priority: {1u, 3u, 2u, 3u, 1u}) {
+            jobs.push(Job{priority, id++});
+        }
+        std::cout << "Popped (priority:id):";
+        while (auto job = jobs.pop_max()) {
+            std::cout << " " << job->priority << ":" << job->id;
+        }
+        std::cout << ", expected: 3:1 3:3 2:2 1:0 1:4" << std::endl;
+    }
+
+    // The threads push and pop concurrently, then pop the rest concurrently, when each thread pops
+    // in the descending order.
+    sc::mpmc::PriorityQueue<uint64_t> queue;
+    std::atomic<uint64_t> popped_sum{0};
+    std::atomic<uint64_t> popped_count{0};
+    auto elapsed = run_threads(ThreadCount, [&](uint32_t i) {
+        uint64_t sum = 0;
+        uint64_t count = 0;
+        for (uint64_t n = 0; n < PushCount; ++n) {
+            // A scrambled priority, each value is unique.
+            queue.push((n * 7919 % PushCount) * ThreadCount + i);
+            if (n % 2 == 0) {
+                if (auto v = queue.pop_max()) {
+                    sum += *v;
+                    ++count;
+                }
+            }
+        }
+        popped_sum.fetch_add(sum);
+        popped_count.fetch_add(count);
+    });
+
+    std::atomic<uint64_t> unordered{0};
+    run_threads(ThreadCount, [&](uint32_t) {
+        uint64_t sum = 0;
+        uint64_t count = 0;
+        auto last = UINT64_MAX;
+        while (auto v = queue.pop_max()) {
+            if (*v > last) {
+                unordered.fetch_add(1);
+            }
+            last = *v;
+            sum += *v;
+            ++count;
+        }
+        popped_sum.fetch_add(sum);
+        popped_count.fetch_add(count);
+    });
+
+    auto total = ThreadCount * PushCount;
+    std::cout << "Popped: " << popped_count.load() << ", expected: " << total << ", sum matched: "
+              << (popped_sum.load() == total * (total - 1) / 2) << ", unordered pops: " << unordered.load()
+              << ", empty: " << queue.is_empty() << ", time: " << elapsed << "ns" << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,192 @@
+///
+/// @file  queue_backend_test.cpp
+/// @brief Test for sc::QueueBackend and the thread pool over the other backends.
+///
+
+#include "queue/queue_backend.hpp"
+
+#include <atomic>
+#include <deque>
+#include <mutex>
+#include <optional>
+#include <vector>
+
+#include "pool/thread_pool.hpp"
+#include "queue/mpmc_bounded_queue.hpp"
+#include "queue/mpmc_list_queue.hpp"
+#include "test_util.hpp"
+
+
+/// @brief A queue of another library, with the same shape as the queues of the project.
+template<typename T>
+class LockedQueue
+{
+public:
+    using value_type = T;
+
+    void enqueue(T value)
+    {
+        std::lock_guard guard(mtx_);
+        items_.push_back(std::move(value));
+    }
+
+    std::optional<T> try_dequeue()
+    {
+        std::lock_guard guard(mtx_);
+        if (items_.empty()) {
+            return {};
+        }
+        auto value = std::move(items_.front());
+        items_.pop_front();
+        return value;
+    }
+
+private:
+    std::mutex mtx_;
+    std::deque<T> items_;
+};
+
+/// @brief A queue with other names, adapted by a specialization of the traits.
+struct Mailbox
+{
+    std::vector<int> letters;
+};
+
+template<>
+struct sc::QueueBackendTraits<Mailbox>
+{
+    using value_type = int;
+    using stealer_type = Mailbox *;
+
+    static bool push(Mailbox &q, int &&value)
+    {
+        q.letters.push_back(value);
+        return true;
+    }
+
+    static std::optional<int> pop(Mailbox &q)
+    {
+        if (q.letters.empty()) {
+            return {};
+        }
+        auto value = q.letters.back();
+        q.letters.pop_back();
+        return value;
+    }
+
+    static stealer_type stealer(Mailbox &q) noexcept
+    {
+        return &q;
+    }
+
+    static sc::deque::Steal<int> steal(const stealer_type &s)
+    {
+        auto value = pop(*s);
+        return value ? sc::deque::Steal<int>::success(*value) : sc::deque::Steal<int>::empty();
+    }
+};
+
+static_assert(sc::QueueBackend<sc::mpmc::ArrayListQueue<int>>);
+static_assert(sc::QueueBackend<sc::mpmc::LinkedListQueue<int>>);
+static_assert(sc::QueueBackend<sc::mpmc::BoundedQueue<int>>);
+static_assert(sc::QueueBackend<sc::deque::Worker<int>>);
+static_assert(sc::QueueBackend<LockedQueue<int>>);
+static_assert(sc::QueueBackend<Mailbox>);
+static_assert(!sc::QueueBackend<std::vector<int>>);
+static_assert(sc::PoolBackend<sc::mpmc::FixedBoundedQueue<int, 16>>);
+static_assert(!sc::PoolBackend<sc::mpmc::BoundedQueue<int>>);
+static_assert(!sc::PoolBackend<Mailbox>);
+
+/// @brief The generic code: pushes 'count' items, steals one, then pops the rest.
+template<sc::QueueBackend Q>
+void exercise(const char *name, Q &q, int count)
+{
+    using Backend = sc::QueueBackendTraits<Q>;
+    int pushed = 0;
+    for (int i = 1; i <= count; ++i) {
+        pushed += Backend::push(q, int(i));
+    }
+    auto stealer = Backend::stealer(q);
+    auto stolen = Backend::steal(stealer);
+    int sum = stolen.is_success() ? *std::move(stolen).success() : 0;
+    while (auto value = Backend::pop(q)) {
+        sum += *value;
+    }
+    std::cout << name << ": pushed " << pushed << ", sum " << sum << ", empty steal: "
+              << Backend::steal(stealer).is_empty() << std::endl;
+}
+
+template<typename Pool>
+uint64_t run_pool(size_t threads)
+{
+    std::atomic<uint64_t> sum{0};
+    Pool pool(threads);
+    for (uint64_t i = 1; i <= 100; ++i) {
+        pool.spawn([&sum, i] { sum.fetch_add(i, std::memory_order_relaxed); });
+    }
+    pool.scope([&](typename Pool::Scope &s) {
+        for (uint64_t i = 0; i < LoopCount / 10000; ++i) {
+            s.spawn([&sum](typename Pool::Scope &inner) {
+                inner.spawn([&sum] { sum.fetch_add(1, std::memory_order_relaxed); });
+            });
+        }
+    });
+    pool.join();
+    return sum.load();
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        sc::mpmc::ArrayListQueue<int> array_list;
+        sc::mpmc::LinkedListQueue<int> linked_list;
+        sc::mpmc::BoundedQueue<int> bounded(4);
+        sc::deque::Worker<int> worker;
+        LockedQueue<int> locked;
+        Mailbox mailbox;
+        exercise("ArrayListQueue", array_list, 10);
+        exercise("LinkedListQueue", linked_list, 10);
+        // The push to the full bounded queue fails instead of blocking.
+        exercise("BoundedQueue", bounded, 10);
+        exercise("Worker", worker, 10);
+        exercise("LockedQueue", locked, 10);
+        exercise("Mailbox", mailbox, 10);
+    }
+
+    {
+        auto expected = 5050 + LoopCount / 10000;
+        auto standard = run_pool<sc::pool::ThreadPool>(2);
+        auto shared_locals = run_pool<sc::pool::BasicThreadPool<sc::mpmc::LinkedListQueue<sc::pool::JobPtr>>>(2);
+        auto third_party = run_pool<sc::pool::BasicThreadPool<sc::deque::Worker<sc::pool::JobPtr>,
+                LockedQueue<sc::pool::JobPtr>>>(2);
+        std::cout << "Pool sums matched: " << (standard == expected) << " " << (shared_locals == expected) << " "
+                  << (third_party == expected) << std::endl;
+    }
+
+    {
+        // The spawns beyond the bounded injector are rejected while the only worker is blocked.
+        std::atomic<bool> release{false};
+        std::atomic<uint64_t> run{0};
+        sc::pool::BasicThreadPool<sc::deque::Worker<sc::pool::JobPtr>,
+                sc::mpmc::FixedBoundedQueue<sc::pool::JobPtr, 4>> pool(1);
+        pool.spawn([&] {
+            while (!release.load(std::memory_order_acquire)) {
+                std::this_thread::yield();
+            }
+            run.fetch_add(1);
+        });
+        uint64_t accepted = 1;
+        while (pool.spawn([&] { run.fetch_add(1); })) {
+            ++accepted;
+        }
+        release.store(true, std::memory_order_release);
+        pool.join();
+        std::cout << "Bounded injector: accepted at most the capacity: " << (accepted <= 5)
+                  << ", all accepted run: " << (run.load() == accepted) << std::endl;
+    }
+
+    std::cout << "hello world" << std::endl;
+    return 0;
+}
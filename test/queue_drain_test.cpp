@@ -0,0 +1,122 @@
+///
+/// @file  queue_drain_test.cpp
+/// @brief Test for the drain of the queues and the stack.
+///
+
+#include "queue/blocking_queue.hpp"
+#include "queue/mpmc_array_queue.hpp"
+#include "queue/mpmc_list_queue.hpp"
+#include "queue/mpsc_list_queue.hpp"
+#include "queue/spsc_ring_buffer.hpp"
+#include "stack/lock_free_stack.hpp"
+
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+/// @brief Drains the items 0..count enqueued by 'push' by chunks of 3, and checks the order.
+template<typename Queue, typename Push>
+void run_fifo(const std::string &name, Queue &queue, Push push, uint64_t count)
+{
+    for (uint64_t i = 0; i < count; ++i) {
+        push(i);
+    }
+    uint64_t expected = 0;
+    bool ordered = true;
+    for (auto &v: queue.drain(3)) {
+        ordered = ordered && v == expected++;
+    }
+    std::cout << name << ": drained: " << expected << ", ordered: " << ordered << ", empty: " << queue.is_empty()
+              << std::endl;
+}
+
+void run_concurrent()
+{
+    // The drain stops after the items in the queue when it is created, although the producer
+    // keeps enqueuing.
+    sc::mpmc::LinkedListQueue<uint64_t> queue;
+    constexpr uint64_t Count = 1000;
+    for (uint64_t n = 0; n < Count; ++n) {
+        queue.enqueue(n);
+    }
+    auto drain = queue.drain();
+    std::atomic<bool> stop{false};
+    std::thread producer([&] {
+        while (!stop.load(std::memory_order_relaxed)) {
+            queue.enqueue(Count);
+        }
+    });
+    uint64_t drained = 0;
+    bool old_items = true;
+    for (auto &v: drain) {
+        old_items = old_items && v < Count;
+        ++drained;
+    }
+    stop.store(true, std::memory_order_relaxed);
+    producer.join();
+    std::cout << "Concurrent drain: drained: " << drained << ", only old items: " << old_items << std::endl;
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    sc::mpmc::LinkedListQueue<uint64_t> mpmc;
+    run_fifo("mpmc::LinkedListQueue", mpmc, [&](uint64_t v) { mpmc.enqueue(v); }, 10);
+    sc::mpsc::LinkedListQueue<uint64_t> mpsc;
+    run_fifo("mpsc::LinkedListQueue", mpsc, [&](uint64_t v) { mpsc.enqueue(v); }, 10);
+    // More than one block of 63 items.
+    sc::mpmc::ArrayListQueue<uint64_t> array;
+    run_fifo("ArrayListQueue", array, [&](uint64_t v) { array.enqueue(v); }, 200);
+    sc::BlockingQueue<sc::mpmc::LinkedListQueue<uint64_t>> blocking;
+    run_fifo("BlockingQueue", blocking, [&](uint64_t v) { blocking.enqueue(v); }, 10);
+
+    sc::spsc::RingBuffer<uint64_t> buffer(8);
+    auto [producer, consumer] = buffer.split();
+    for (uint64_t i = 0; i < 5; ++i) {
+        producer.try_enqueue(i);
+    }
+    consumer.try_dequeue();
+    for (uint64_t i = 5; i < 9; ++i) {
+        producer.try_enqueue(i);
+    }
+    uint64_t expected = 1;
+    bool ordered = true;
+    for (auto &v: consumer.drain(3)) {
+        ordered = ordered && v == expected++;
+    }
+    std::cout << "spsc::RingBuffer: drained to: " << expected << ", ordered: " << ordered << ", empty: "
+              << buffer.is_empty() << std::endl;
+
+    // The stack is drained from the top to the bottom.
+    sc::stack::LockFreeStack<std::string> stack;
+    stack.push("a");
+    stack.push("b");
+    stack.push("c");
+    std::string lifo;
+    for (auto &v: stack.drain()) {
+        lifo += v;
+    }
+    std::cout << "LockFreeStack: drained: " << lifo << ", empty: " << stack.is_empty() << std::endl;
+
+    // Stop early: the rest of the chunk is dropped, the other chunks are still in the queue.
+    sc::mpmc::LinkedListQueue<uint64_t> partial;
+    for (uint64_t i = 0; i < 10; ++i) {
+        partial.enqueue(i);
+    }
+    for (auto &v: partial.drain(4)) {
+        if (v == 1) {
+            break;
+        }
+    }
+    std::cout << "Stop early: len: " << partial.len() << ", next: " << *partial.try_dequeue() << std::endl;
+
+    run_concurrent();
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
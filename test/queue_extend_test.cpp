@@ -0,0 +1,111 @@
+///
+/// @file  queue_extend_test.cpp
+/// @brief Test for the extend and the iterator constructors of the queues and the stack.
+///
+
+#include "queue/blocking_queue.hpp"
+#include "queue/channel.hpp"
+#include "queue/mpmc_array_queue.hpp"
+#include "queue/mpmc_bounded_queue.hpp"
+#include "queue/mpmc_list_queue.hpp"
+#include "queue/mpmc_list_queue_v2.hpp"
+#include "queue/mpsc_list_queue.hpp"
+#include "queue/priority_queue.hpp"
+#include "queue/spsc_ring_buffer.hpp"
+#include "stack/lock_free_stack.hpp"
+
+#include <list>
+#include <ranges>
+#include <string>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+/// @brief Dequeues all items, and prints them in one line.
+template<typename Queue>
+void print_all(const std::string &name, Queue &queue)
+{
+    std::cout << name << ":";
+    while (auto v = queue.try_dequeue()) {
+        std::cout << " " << *v;
+    }
+    std::cout << std::endl;
+}
+
+void run_unbounded()
+{
+    std::vector<uint64_t> jobs{1, 2, 3};
+
+    sc::mpmc::LinkedListQueue<uint64_t> mpmc(jobs.begin(), jobs.end());
+    mpmc.extend(std::views::iota(4) | std::views::take(2));
+    print_all("mpmc::LinkedListQueue", mpmc);
+
+    auto mpsc = sc::collect<sc::mpsc::LinkedListQueue<uint64_t>>(jobs);
+    mpsc.extend(std::list<uint64_t>{4, 5});
+    print_all("mpsc::LinkedListQueue", mpsc);
+
+    auto v2 = sc::collect<sc::mpmc::LinkedListQueueV2<uint64_t>>(jobs | std::views::reverse);
+    print_all("LinkedListQueueV2", v2);
+
+    // A forward range is enqueued by 'enqueue_batch', the others item by item.
+    sc::mpmc::ArrayListQueue<uint64_t> array(jobs.begin(), jobs.end());
+    array.extend(std::views::iota(4) | std::views::take(2));
+    print_all("ArrayListQueue", array);
+
+    // Move the strings out of the vector.
+    std::vector<std::string> words{"a", "b", "c"};
+    sc::mpmc::LinkedListQueue<std::string> strings;
+    strings.extend(std::make_move_iterator(words.begin()), std::make_move_iterator(words.end()));
+    std::cout << "Moved: " << words[0].empty() << ", len: " << strings.len() << std::endl;
+
+    auto priority = sc::collect<sc::mpmc::PriorityQueue<uint64_t>>(std::vector<uint64_t>{3, 9, 1});
+    priority.extend(jobs);
+    std::cout << "PriorityQueue: max: " << *priority.pop_max() << ", len: " << priority.len() << std::endl;
+
+    sc::stack::LockFreeStack<uint64_t> stack(jobs.begin(), jobs.end());
+    stack.extend(std::views::iota(4) | std::views::take(2));
+    std::cout << "LockFreeStack:";
+    for (auto v: stack.pop_all()) {
+        std::cout << " " << v;
+    }
+    std::cout << std::endl;
+}
+
+void run_producers()
+{
+    std::vector<uint64_t> jobs{1, 2, 3, 4, 5};
+
+    sc::mpmc::BoundedQueue<uint64_t> reject(3, sc::mpmc::Overflow::Reject);
+    std::cout << "BoundedQueue Reject: enqueued: " << reject.extend(jobs) << std::endl;
+    sc::mpmc::BoundedQueue<uint64_t> oldest(3, sc::mpmc::Overflow::DropOldest);
+    std::cout << "BoundedQueue DropOldest: enqueued: " << oldest.extend(jobs) << std::endl;
+    print_all("BoundedQueue DropOldest", oldest);
+
+    sc::spsc::RingBuffer<uint64_t> buffer(8);
+    auto [producer, consumer] = buffer.split();
+    producer.extend(jobs);
+    print_all("spsc::RingBuffer", consumer);
+
+    sc::BlockingQueue<sc::mpmc::LinkedListQueue<uint64_t>> blocking;
+    auto enqueued = blocking.extend(jobs);
+    blocking.close();
+    std::cout << "BlockingQueue: enqueued: " << enqueued << ", after close: " << blocking.extend(jobs)
+              << std::endl;
+
+    auto [tx, rx] = sc::make_channel<sc::mpmc::LinkedListQueue<uint64_t>>();
+    std::cout << "Sender: sent: " << tx.extend(jobs | std::views::filter([](uint64_t v) { return v % 2 == 1; }))
+              << ", len: " << rx.len() << std::endl;
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    run_unbounded();
+    run_producers();
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
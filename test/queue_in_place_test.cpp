@@ -0,0 +1,116 @@
+///
+/// @file  queue_in_place_test.cpp
+/// @brief Test for the in-place construction and consumption of the values in the ring slots.
+///
+
+#include "queue/mpmc_bounded_queue.hpp"
+#include "queue/spsc_ring_buffer.hpp"
+
+#include <array>
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+/// @brief A large payload counting its copies and moves.
+struct Payload
+{
+    inline static std::atomic<uint64_t> Transfers{0};
+
+    std::array<uint64_t, 512> data{};
+    uint64_t id = 0;
+
+    Payload() = default;
+
+    explicit Payload(uint64_t id) : id(id) { }
+
+    Payload(const Payload &other) : data(other.data), id(other.id)
+    {
+        Transfers.fetch_add(1, std::memory_order_relaxed);
+    }
+
+    Payload(Payload &&other) noexcept : data(other.data), id(other.id)
+    {
+        Transfers.fetch_add(1, std::memory_order_relaxed);
+    }
+
+    Payload &operator=(const Payload &) = delete;
+};
+
+/// @brief Fills the payload in the slot, and reads it back in the slot.
+template<typename Producer, typename Consumer>
+void run_queue(const std::string &name, Producer &producer, Consumer &consumer)
+{
+    Payload::Transfers.store(0);
+    producer.try_emplace(1);
+    producer.try_enqueue_with([](std::optional<Payload> &slot) {
+        slot.emplace(2);
+        slot->data.fill(2);
+    });
+    uint64_t sum = 0;
+    while (consumer.try_dequeue_with([&sum](Payload &p) { sum += p.id + p.data[511]; })) { }
+    auto empty = !consumer.try_dequeue_with([](Payload &) { });
+    std::cout << name << ": sum: " << sum << ", transfers: " << Payload::Transfers.load() << ", empty: " << empty
+              << std::endl;
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    sc::mpmc::BoundedQueue<Payload> bounded(4);
+    run_queue("BoundedQueue", bounded, bounded);
+
+    sc::spsc::RingBuffer<Payload> buffer(4);
+    auto [producer, consumer] = buffer.split();
+    run_queue("RingBuffer", producer, consumer);
+
+    // Full queue: the function is not called.
+    sc::mpmc::BoundedQueue<Payload> full(1);
+    full.try_emplace(1);
+    bool called = false;
+    auto enqueued = full.try_enqueue_with([&called](std::optional<Payload> &slot) {
+        called = true;
+        slot.emplace(2);
+    });
+    std::cout << "Full: enqueued: " << enqueued << ", called: " << called << std::endl;
+
+    // The producers and the consumers construct and consume the values in place concurrently.
+    constexpr uint32_t ThreadCount = 2;
+    constexpr uint64_t Count = LoopCount / 100;
+    sc::mpmc::BoundedQueue<Payload> shared(16);
+    Payload::Transfers.store(0);
+    std::atomic<uint64_t> sum{0};
+    std::atomic<uint64_t> received{0};
+    std::vector<std::thread> threads;
+    for (uint32_t i = 0; i < ThreadCount; ++i) {
+        threads.emplace_back([&, i] {
+            for (uint64_t n = i; n < Count; n += ThreadCount) {
+                while (!shared.try_emplace(n)) {
+                    std::this_thread::yield();
+                }
+            }
+        });
+        threads.emplace_back([&] {
+            while (received.load(std::memory_order_relaxed) < Count) {
+                if (!shared.try_dequeue_with([&](Payload &p) {
+                    sum.fetch_add(p.id, std::memory_order_relaxed);
+                    received.fetch_add(1, std::memory_order_relaxed);
+                })) {
+                    std::this_thread::yield();
+                }
+            }
+        });
+    }
+    for (auto &t: threads) {
+        t.join();
+    }
+    std::cout << "Concurrent: sum: " << sum.load() << ", expected: " << Count * (Count - 1) / 2 << ", transfers: "
+              << Payload::Transfers.load() << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
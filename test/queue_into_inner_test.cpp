@@ -0,0 +1,91 @@
+///
+/// @file  queue_into_inner_test.cpp
+/// @brief Test for the into_inner of the queues, which moves the remaining items out in order.
+///
+
+#include "queue/mpmc_array_queue.hpp"
+#include "queue/mpmc_bounded_queue.hpp"
+#include "queue/mpmc_list_queue.hpp"
+
+#include <string>
+#include <utility>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ProducerCount = 4;
+constexpr uint64_t ItemCount = 100;
+
+/// @brief Enqueues the items 'producer * ItemCount + i' on the producer threads, dequeues 'skip'
+/// items, then checks the rest moved out by @c into_inner are in the order of each producer.
+template<typename Queue>
+void run_into_inner(const std::string &name, Queue queue, uint64_t skip)
+{
+    run_threads(ProducerCount, [&queue](uint32_t producer) {
+        for (uint64_t i = 0; i < ItemCount; ++i) {
+            queue.enqueue(std::to_string(producer * ItemCount + i));
+        }
+    });
+    std::vector<uint64_t> next(ProducerCount);
+    bool ordered = true;
+    auto check = [&](const std::string &item) {
+        auto v = std::stoull(item);
+        auto &expected = next[v / ItemCount];
+        ordered = ordered && v == expected + v / ItemCount * ItemCount;
+        ++expected;
+    };
+    for (uint64_t i = 0; i < skip; ++i) {
+        check(*queue.try_dequeue());
+    }
+
+    auto items = std::move(queue).into_inner();
+    for (auto &item: items) {
+        check(item);
+    }
+    bool complete = true;
+    for (auto n: next) {
+        complete = complete && n == ItemCount;
+    }
+    std::cout << name << ": moved out: " << items.size() << ", ordered: " << ordered << ", complete: "
+              << complete << std::endl;
+}
+
+/// @brief The head of the bounded queue of 4 slots wraps around the end of the buffer.
+template<typename Queue>
+void run_bounded_wrap(const std::string &name, Queue queue)
+{
+    for (uint32_t i = 0; i < 7; ++i) {
+        queue.enqueue(std::to_string(i));
+        if (i >= 3) {
+            queue.try_dequeue();
+        }
+    }
+    std::string items;
+    for (auto &item: std::move(queue).into_inner()) {
+        items += item;
+    }
+    std::cout << name << " wrap: " << items << std::endl;
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    run_into_inner("mpmc::LinkedListQueue", sc::mpmc::LinkedListQueue<std::string>(), 0);
+    run_into_inner("mpmc::LinkedListQueue skip", sc::mpmc::LinkedListQueue<std::string>(), 10);
+    // More than one block of 63 items, and the head in the middle of a block.
+    run_into_inner("ArrayListQueue", sc::mpmc::ArrayListQueue<std::string>(), 0);
+    run_into_inner("ArrayListQueue skip", sc::mpmc::ArrayListQueue<std::string>(), 70);
+    run_into_inner("BoundedQueue", sc::mpmc::BoundedQueue<std::string>(ProducerCount * ItemCount), 0);
+    run_into_inner("BoundedQueue skip", sc::mpmc::BoundedQueue<std::string>(ProducerCount * ItemCount), 10);
+    run_bounded_wrap("BoundedQueue", sc::mpmc::BoundedQueue<std::string>(4));
+    run_bounded_wrap("FixedBoundedQueue", sc::mpmc::FixedBoundedQueue<std::string, 4>());
+
+    sc::mpmc::LinkedListQueue<std::string> empty;
+    std::cout << "Empty: " << std::move(empty).into_inner().size() << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
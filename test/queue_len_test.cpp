@@ -0,0 +1,180 @@
+///
+/// @file  queue_len_test.cpp
+/// @brief Test for the len, is_empty, capacity and remaining of the queues.
+///
+
+#include "queue/blocking_queue.hpp"
+#include "queue/broadcast_ring.hpp"
+#include "queue/channel.hpp"
+#include "queue/delay_queue.hpp"
+#include "queue/mpmc_array_queue.hpp"
+#include "queue/mpmc_bounded_queue.hpp"
+#include "queue/mpmc_list_queue.hpp"
+#include "queue/mpsc_list_queue.hpp"
+#include "queue/priority_queue.hpp"
+#include "queue/spsc_ring_buffer.hpp"
+
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+
+/// @brief Enqueues 'push' items, dequeues 'pop' items, and prints the lengths on the way.
+template<typename Queue>
+void run_fifo(const std::string &name, Queue &queue, uint64_t push, uint64_t pop)
+{
+    auto empty = queue.is_empty() && queue.len() == 0;
+    for (uint64_t i = 0; i < push; ++i) {
+        queue.enqueue(i);
+    }
+    auto pushed = queue.len();
+    for (uint64_t i = 0; i < pop; ++i) {
+        queue.try_dequeue();
+    }
+    std::cout << name << ": empty: " << empty << ", len: " << pushed << " -> " << queue.len() << ", unbounded: "
+              << (queue.capacity() == sc::UnboundedCapacity && queue.remaining() == sc::UnboundedCapacity)
+              << std::endl;
+}
+
+void run_bounded()
+{
+    // Wrap around the buffer, so the tail index is behind the head index.
+    sc::mpmc::BoundedQueue<uint64_t> queue(10);
+    for (uint64_t i = 0; i < 7; ++i) {
+        queue.try_enqueue(i);
+    }
+    for (uint64_t i = 0; i < 5; ++i) {
+        queue.try_dequeue();
+    }
+    for (uint64_t i = 0; i < 6; ++i) {
+        queue.try_enqueue(i);
+    }
+    auto wrapped = queue.len();
+    auto remaining = queue.remaining();
+    while (queue.try_enqueue(0)) { }
+    std::cout << "BoundedQueue: len: " << wrapped << ", remaining: " << remaining << ", full len: " << queue.len()
+              << ", full remaining: " << queue.remaining() << ", empty: " << queue.is_empty() << std::endl;
+
+    sc::mpmc::FixedBoundedQueue<uint64_t, 4> fixed;
+    fixed.try_enqueue(1);
+    std::cout << "FixedBoundedQueue: len: " << fixed.len() << ", capacity: " << fixed.capacity()
+              << ", remaining: " << fixed.remaining() << std::endl;
+
+    // The snapshots stay in the bounds while other threads are enqueuing and dequeuing.
+    sc::mpmc::BoundedQueue<uint64_t> shared(64);
+    std::atomic<bool> stop{false};
+    std::vector<std::thread> threads;
+    for (uint32_t i = 0; i < ThreadCount; ++i) {
+        threads.emplace_back([&, i] {
+            while (!stop.load(std::memory_order_relaxed)) {
+                if (i % 2 == 0) {
+                    shared.try_enqueue(i);
+                } else {
+                    shared.try_dequeue();
+                }
+            }
+        });
+    }
+    bool in_bounds = true;
+    for (uint64_t n = 0; n < LoopCount / 100; ++n) {
+        auto len = shared.len();
+        in_bounds = in_bounds && len <= shared.capacity() && shared.remaining() <= shared.capacity();
+    }
+    stop.store(true, std::memory_order_relaxed);
+    for (auto &t: threads) {
+        t.join();
+    }
+    std::cout << "BoundedQueue concurrent len in bounds: " << in_bounds << std::endl;
+}
+
+void run_ring_buffer()
+{
+    sc::spsc::RingBuffer<uint64_t> buffer(4);
+    auto [producer, consumer] = buffer.split();
+    auto empty = buffer.is_empty();
+    for (uint64_t i = 0; i < 3; ++i) {
+        producer.try_enqueue(i);
+    }
+    consumer.try_dequeue();
+    producer.try_enqueue(3);
+    producer.try_enqueue(4);
+    std::cout << "RingBuffer: empty: " << empty << ", len: " << buffer.len() << ", remaining: " << buffer.remaining()
+              << std::endl;
+}
+
+void run_unbounded()
+{
+    // More than one block of 63 items.
+    sc::mpmc::ArrayListQueue<uint64_t> array;
+    run_fifo("ArrayListQueue", array, 200, 70);
+    std::vector<uint64_t> batch(100, 0);
+    array.enqueue_batch(batch.begin(), batch.end());
+    std::vector<uint64_t> out;
+    array.try_dequeue_batch(out, 10);
+    std::cout << "ArrayListQueue batch: len: " << array.len() << ", expected: " << 200 - 70 + 100 - out.size()
+              << std::endl;
+
+    sc::mpmc::LinkedListQueue<uint64_t> mpmc;
+    run_fifo("mpmc::LinkedListQueue", mpmc, 20, 5);
+    sc::mpsc::LinkedListQueue<uint64_t> mpsc;
+    run_fifo("mpsc::LinkedListQueue", mpsc, 20, 25);
+    sc::BlockingQueue<sc::mpmc::ArrayListQueue<uint64_t>> blocking;
+    run_fifo("BlockingQueue", blocking, 10, 3);
+
+    sc::mpmc::PriorityQueue<uint64_t> priority;
+    for (uint64_t i = 0; i < 10; ++i) {
+        priority.push(i);
+    }
+    priority.pop_max();
+    std::cout << "PriorityQueue: len: " << priority.len() << ", unbounded: "
+              << (priority.remaining() == sc::UnboundedCapacity) << std::endl;
+
+    sc::DelayQueue<uint64_t> delay;
+    delay.push_after(1, std::chrono::hours(1));
+    delay.push_after(2, std::chrono::milliseconds(0));
+    std::cout << "DelayQueue: len: " << delay.len() << ", size: " << delay.size() << std::endl;
+}
+
+void run_broadcast()
+{
+    sc::broadcast::Ring<uint64_t> ring(8, 2);
+    auto no_consumer = ring.len();
+    auto fast = *ring.subscribe();
+    auto slow = *ring.subscribe();
+    for (uint64_t i = 0; i < 5; ++i) {
+        ring.send(i);
+    }
+    fast.try_recv();
+    fast.try_recv();
+    slow.try_recv();
+    std::cout << "Ring: no consumer len: " << no_consumer << ", len: " << ring.len() << ", remaining: "
+              << ring.remaining() << ", fast len: " << fast.len() << ", slow len: " << slow.len() << std::endl;
+}
+
+void run_channel()
+{
+    auto [tx, rx] = sc::make_channel<sc::mpmc::BoundedQueue<uint64_t>>(8);
+    tx.send(1);
+    tx.send(2);
+    std::cout << "Channel: len: " << rx.len() << ", remaining: " << tx.remaining() << ", capacity: "
+              << tx.capacity() << ", empty: " << rx.is_empty() << std::endl;
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    run_bounded();
+    run_ring_buffer();
+    run_unbounded();
+    run_broadcast();
+    run_channel();
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
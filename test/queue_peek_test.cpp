@@ -0,0 +1,124 @@
+///
+/// @file  queue_peek_test.cpp
+/// @brief Test for the peek and pop_if of the queues.
+///
+
+#include "queue/blocking_queue.hpp"
+#include "queue/mpmc_list_queue.hpp"
+#include "queue/mpsc_list_queue.hpp"
+#include "queue/priority_queue.hpp"
+#include "queue/spsc_ring_buffer.hpp"
+
+#include <optional>
+#include <stdexcept>
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+
+/// @brief Peeks and conditionally pops the items 0, 1, 2 enqueued by 'push'.
+template<typename Queue, typename Push>
+void run_fifo(const std::string &name, Queue &queue, Push push)
+{
+    uint64_t peeked = 100;
+    auto empty_peek = queue.peek([&](const uint64_t &v) { peeked = v; });
+    for (uint64_t i = 0; i < 3; ++i) {
+        push(i);
+    }
+    queue.peek([&](const uint64_t &v) { peeked = v; });
+    auto odd = queue.pop_if([](const uint64_t &v) { return v % 2 == 1; });
+    auto even = queue.pop_if([](const uint64_t &v) { return v % 2 == 0; });
+    auto next = queue.pop_if([](const uint64_t &v) { return v % 2 == 1; });
+    std::cout << name << ": empty peek: " << empty_peek << ", peeked: " << peeked << ", pop odd: " << odd.has_value()
+              << ", pop even: " << *even << ", pop odd: " << *next << std::endl;
+}
+
+void run_throwing()
+{
+    // A throwing callback unlocks the head, and the item stays in the queue.
+    sc::mpmc::LinkedListQueue<uint64_t> queue;
+    queue.enqueue(1);
+    queue.enqueue(2);
+    uint32_t caught = 0;
+    try {
+        queue.pop_if([](const uint64_t &) -> bool { throw std::runtime_error("pred"); });
+    } catch (const std::runtime_error &) {
+        ++caught;
+    }
+    try {
+        queue.peek([](const uint64_t &) { throw std::runtime_error("peek"); });
+    } catch (const std::runtime_error &) {
+        ++caught;
+    }
+    // Another consumer would spin forever on a head left locked.
+    std::optional<uint64_t> first;
+    std::thread other([&] { first = queue.try_dequeue(); });
+    other.join();
+    auto second = queue.pop_if([](const uint64_t &v) { return v == 2; });
+    std::cout << "Throwing callback: caught: " << caught << ", first: " << *first << ", second: " << *second
+              << ", len: " << queue.len() << std::endl;
+}
+
+void run_consumers()
+{
+    // Each consumer only takes an item it can handle, all items are taken exactly once.
+    constexpr uint64_t Count = LoopCount / 10;
+    sc::mpmc::LinkedListQueue<uint64_t> queue;
+    for (uint64_t n = 0; n < Count; ++n) {
+        queue.enqueue(n);
+    }
+    std::atomic<uint64_t> taken{0};
+    std::atomic<uint64_t> sum{0};
+    run_threads(ThreadCount, [&](uint32_t i) {
+        while (taken.load(std::memory_order_relaxed) < Count) {
+            auto v = queue.pop_if([i](const uint64_t &v) { return v % ThreadCount == i; });
+            if (v) {
+                sum.fetch_add(*v, std::memory_order_relaxed);
+                taken.fetch_add(1, std::memory_order_relaxed);
+            } else {
+                // The head belongs to another consumer, let it run.
+                std::this_thread::yield();
+            }
+        }
+    });
+    std::cout << "Concurrent pop_if: sum: " << sum.load() << ", expected: " << Count * (Count - 1) / 2
+              << ", empty: " << queue.is_empty() << std::endl;
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    sc::mpmc::LinkedListQueue<uint64_t> mpmc;
+    run_fifo("mpmc::LinkedListQueue", mpmc, [&](uint64_t v) { mpmc.enqueue(v); });
+    sc::mpsc::LinkedListQueue<uint64_t> mpsc;
+    run_fifo("mpsc::LinkedListQueue", mpsc, [&](uint64_t v) { mpsc.enqueue(v); });
+    sc::BlockingQueue<sc::mpmc::LinkedListQueue<uint64_t>> blocking;
+    run_fifo("BlockingQueue", blocking, [&](uint64_t v) { blocking.enqueue(v); });
+    sc::spsc::RingBuffer<uint64_t> buffer(4);
+    auto [producer, consumer] = buffer.split();
+    run_fifo("spsc::RingBuffer", consumer, [&](uint64_t v) { producer.try_enqueue(v); });
+
+    // The head of the priority queue is the max value.
+    sc::mpmc::PriorityQueue<uint64_t> priority;
+    for (uint64_t i = 0; i < 5; ++i) {
+        priority.push(i);
+    }
+    uint64_t max = 0;
+    priority.peek([&](const uint64_t &v) { max = v; });
+    auto small = priority.pop_if([](const uint64_t &v) { return v < 3; });
+    auto big = priority.pop_if([](const uint64_t &v) { return v >= 3; });
+    std::cout << "PriorityQueue: peeked: " << max << ", pop small: " << small.has_value() << ", pop big: " << *big
+              << ", len: " << priority.len() << std::endl;
+
+    run_throwing();
+    run_consumers();
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
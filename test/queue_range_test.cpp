@@ -0,0 +1,48 @@
+///
+/// @file  queue_range_test.cpp
+/// @brief Test for sc::DequeueView and sc::EnqueueIterator.
+///
+
+#include "queue/queue_range.hpp"
+#include "queue/blocking_queue.hpp"
+#include "queue/mpmc_array_queue.hpp"
+
+#include <algorithm>
+#include <ranges>
+#include <thread>
+
+#include "test_util.hpp"
+
+
+int main()
+{
+    static_assert(std::ranges::input_range<sc::DequeueView<sc::BlockingQueue<sc::mpmc::ArrayListQueue<uint64_t>>>>);
+    static_assert(std::output_iterator<sc::EnqueueIterator<sc::BlockingQueue<sc::mpmc::ArrayListQueue<uint64_t>>>, uint64_t>);
+
+    sc::BlockingQueue<sc::mpmc::ArrayListQueue<uint64_t>> queue;
+
+    auto begin = get_current_time();
+    std::thread producer([&queue] {
+        std::ranges::copy(std::views::iota(uint64_t(0), LoopCount) | std::views::transform([](uint64_t v) { return v * 2; }),
+                          sc::enqueue_iterator(queue));
+        queue.close();
+    });
+
+    // the loop exits after the queue is closed and drained.
+    uint64_t count = 0;
+    uint64_t sum = 0;
+    for (auto v: sc::dequeue_view(queue) | std::views::filter([](uint64_t v) { return v % 4 == 0; })) {
+        ++count;
+        sum += v;
+    }
+    producer.join();
+
+    auto half = LoopCount / 2;
+    std::cout << "Count: " << count << ", expected: " << half << ", sum: " << sum
+              << ", expected: " << 2 * half * (half - 1) << ", time: " << get_current_time() - begin
+              << "ns" << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -6,9 +6,13 @@
 #ifndef SYNC_CELL_QUEUE_THREAD_RUN_HPP
 #define SYNC_CELL_QUEUE_THREAD_RUN_HPP
 
+#include <fstream>
+#include <string>
+#include <string_view>
 #include <thread>
 #include <vector>
 
+#include "latency_histogram.hpp"
 #include "test_util.hpp"
 
 
@@ -81,5 +85,42 @@ void consume(
     });
 }
 
+/// @brief Collects the enqueue-to-dequeue latency of the consumed tasks and prints the percentiles.
+///
+/// The summary is also appended as a CSV row to the file given by '--latency-csv=<path>', and
+/// written as JSON to the file given by '--latency-json=<path>', for the regression tracking.
+inline void report_latency(
+        std::string_view name,
+        const std::vector<std::vector<Task>> &results,
+        int argc,
+        char **argv)
+{
+    LatencyHistogram histogram;
+    for (auto &result: results) {
+        for (auto &task: result) {
+            histogram.record(task.out_time - task.in_time);
+        }
+    }
+    histogram.print_summary(std::cout, name);
+
+    constexpr std::string_view CsvArg = "--latency-csv=";
+    constexpr std::string_view JsonArg = "--latency-json=";
+    for (int i = 1; i < argc; ++i) {
+        std::string_view arg(argv[i]);
+        if (arg.starts_with(CsvArg)) {
+            std::string path(arg.substr(CsvArg.size()));
+            bool empty = !std::ifstream(path).good();
+            std::ofstream file(path, std::ios::app);
+            if (empty) {
+                LatencyHistogram::write_csv_header(file);
+            }
+            histogram.write_csv_row(file, name);
+        } else if (arg.starts_with(JsonArg)) {
+            std::ofstream file(std::string(arg.substr(JsonArg.size())));
+            histogram.write_json(file, name);
+        }
+    }
+}
+
 
 #endif //SYNC_CELL_QUEUE_THREAD_RUN_HPP
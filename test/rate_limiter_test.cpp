@@ -0,0 +1,103 @@
+///
+/// @file  rate_limiter_test.cpp
+/// @brief Test for sc::sync::RateLimiter.
+///
+
+#include "sync/rate_limiter.hpp"
+
+#include <atomic>
+#include <chrono>
+#include <thread>
+#include <vector>
+
+#include "coro_util.hpp"
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+
+#if __cpp_impl_coroutine
+
+DetachedTask async_acquire(sc::sync::RateLimiter &limiter, int &acquired)
+{
+    for (int i = 0; i < 3; ++i) {
+        co_await limiter.acquire_async(5);
+        ++acquired;
+    }
+}
+
+void run_async()
+{
+    // 100 tokens per second, so each 5 tokens after the burst take 50 ms.
+    sc::sync::RateLimiter limiter(100, std::chrono::seconds(1), 5);
+    int acquired = 0;
+    auto start = std::chrono::steady_clock::now();
+    async_acquire(limiter, acquired);
+    auto first = acquired;
+
+    size_t resumed = 0;
+    while (acquired < 3) {
+        resumed += limiter.poll();
+        std::this_thread::sleep_for(std::chrono::milliseconds(1));
+    }
+    auto elapsed = std::chrono::steady_clock::now() - start;
+    std::cout << "Async: acquired at first: " << first << ", resumed: " << resumed << ", waited the refills: "
+              << (elapsed >= std::chrono::milliseconds(100)) << std::endl;
+}
+
+#else
+
+void run_async()
+{
+    std::cout << "Skip the async test: built without cpp coroutine support." << std::endl;
+}
+
+#endif
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        sc::sync::RateLimiter limiter(10, std::chrono::milliseconds(100), 4);
+        std::cout << "Burst: " << limiter.burst() << ", available: " << limiter.available()
+                  << ", try 3: " << limiter.try_acquire(3) << ", try 2: " << limiter.try_acquire(2)
+                  << ", try 1: " << limiter.try_acquire(1) << ", available: " << limiter.available() << std::endl;
+        std::this_thread::sleep_for(std::chrono::milliseconds(25));
+        std::cout << "Refilled after 25 ms: " << (limiter.available() >= 2) << std::endl;
+        std::this_thread::sleep_for(std::chrono::milliseconds(100));
+        std::cout << "Full after the burst refilled: " << limiter.available() << std::endl;
+    }
+
+    // The blocking acquisitions of all threads are spread at the rate after the burst.
+    {
+        sc::sync::RateLimiter limiter(1000, std::chrono::seconds(1), 10);
+        constexpr uint64_t PerThread = 25;
+        auto start = std::chrono::steady_clock::now();
+        std::vector<std::thread> threads;
+        for (uint32_t t = 0; t < ThreadCount; ++t) {
+            threads.emplace_back([&] {
+                for (uint64_t i = 0; i < PerThread; ++i) {
+                    limiter.acquire();
+                }
+            });
+        }
+        for (auto &t: threads) {
+            t.join();
+        }
+        auto elapsed = std::chrono::steady_clock::now() - start;
+        // 100 tokens, 10 of them in the burst, the others refilled in 90 ms.
+        std::cout << "Blocking: throttled: " << (elapsed >= std::chrono::milliseconds(85))
+                  << ", try after the reservations: " << limiter.try_acquire(20) << std::endl;
+
+        sc::sync::RateLimiter bigger(1000, std::chrono::seconds(1), 10);
+        bigger.acquire(20);
+        std::cout << "More than the burst acquired, available after: " << bigger.available() << std::endl;
+    }
+
+    run_async();
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,140 @@
+///
+/// @file  rcu_cell_test.cpp
+/// @brief Test for sc::RcuCell.
+///
+
+#include "cell/rcu_cell.hpp"
+
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ReaderCount = 4;
+constexpr uint64_t UpdateCount = 10'000;
+
+static std::atomic<int64_t> LiveConfigs{0};
+
+struct Config
+{
+    uint64_t version;
+    uint64_t checksum;      // always equals to 'version * 2'
+
+    explicit Config(uint64_t v) : version(v), checksum(v * 2)
+    {
+        LiveConfigs.fetch_add(1, std::memory_order_relaxed);
+    }
+
+    Config(const Config &other) : version(other.version), checksum(other.checksum)
+    {
+        LiveConfigs.fetch_add(1, std::memory_order_relaxed);
+    }
+
+    ~Config()
+    {
+        // a reclaimed value must not be read again.
+        version = checksum = 1;
+        LiveConfigs.fetch_sub(1, std::memory_order_relaxed);
+    }
+};
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        sc::RcuCell<Config> cell(0u);
+        std::atomic_flag barrier = ATOMIC_FLAG_INIT;
+        std::atomic<bool> stop{false};
+        std::atomic<uint64_t> torn_reads{0};
+
+        std::vector<std::thread> readers;
+        readers.reserve(ReaderCount);
+        for (uint32_t i = 0; i < ReaderCount; ++i) {
+            readers.emplace_back([&] {
+                barrier.wait(false);
+                uint64_t last = 0;
+                while (!stop.load(std::memory_order_relaxed)) {
+                    auto guard = cell.read();
+                    if (guard->checksum != guard->version * 2 || guard->version < last) {
+                        torn_reads.fetch_add(1, std::memory_order_relaxed);
+                    }
+                    last = guard->version;
+                }
+            });
+        }
+
+        barrier.test_and_set();
+        barrier.notify_all();
+
+        auto begin = get_current_time();
+        for (uint64_t i = 0; i < UpdateCount; ++i) {
+            cell.update([](const Config &c) { return Config(c.version + 1); });
+        }
+        auto elapsed = get_current_time() - begin;
+
+        stop.store(true, std::memory_order_relaxed);
+        for (auto &t: readers) {
+            t.join();
+        }
+
+        std::cout << "Updates: " << UpdateCount << ", time: " << elapsed << "ns, final version: "
+                  << cell.read()->version << ", invalid reads: " << torn_reads.load() << std::endl;
+
+        // no reader now, the old values can be reclaimed by the next updates.
+        cell.store(Config(0));
+        cell.store(Config(0));
+        std::cout << "Live values after reclamation: " << LiveConfigs.load() << std::endl;
+    }
+
+    std::cout << "Live values after the cell destroyed: " << LiveConfigs.load() << std::endl;
+
+    // The small value is stored inline, its guard holds a copy.
+    {
+        struct Small
+        {
+            uint32_t version;
+            uint32_t checksum;
+        };
+        static_assert(sc::impl::RcuInline<Small> && !sc::impl::RcuInline<Config>);
+
+        sc::RcuCell<Small> cell(Small{0, 0});
+        std::atomic<bool> stop{false};
+        std::atomic<uint64_t> torn_reads{0};
+
+        std::vector<std::thread> readers;
+        readers.reserve(ReaderCount);
+        for (uint32_t i = 0; i < ReaderCount; ++i) {
+            readers.emplace_back([&] {
+                uint32_t last = 0;
+                while (!stop.load(std::memory_order_relaxed)) {
+                    auto guard = cell.read();
+                    if (guard->checksum != guard->version * 2 || guard->version < last) {
+                        torn_reads.fetch_add(1, std::memory_order_relaxed);
+                    }
+                    last = guard->version;
+                    std::this_thread::yield();
+                }
+            });
+        }
+
+        auto begin = get_current_time();
+        for (uint32_t i = 0; i < UpdateCount; ++i) {
+            cell.update([](const Small &s) { return Small{s.version + 1, (s.version + 1) * 2}; });
+        }
+        auto elapsed = get_current_time() - begin;
+
+        stop.store(true, std::memory_order_relaxed);
+        for (auto &t: readers) {
+            t.join();
+        }
+
+        std::cout << "Inline updates: " << UpdateCount << ", time: " << elapsed << "ns, final version: "
+                  << cell.read()->version << ", invalid reads: " << torn_reads.load() << std::endl;
+    }
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
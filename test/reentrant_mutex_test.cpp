@@ -0,0 +1,97 @@
+///
+/// @file  reentrant_mutex_test.cpp
+/// @brief Test for sc::lock::ReentrantMutex.
+///
+
+#include "lock/reentrant_mutex.hpp"
+
+#include <algorithm>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+
+struct Counter
+{
+    uint64_t value = 0;
+    uint64_t max_depth = 0;
+};
+
+constinit sc::lock::ReentrantMutex<Counter> Shared;
+
+/// @brief Re-enters the lock 'depth' times, as a callback calling back into the locked object.
+uint64_t reenter(uint32_t depth)
+{
+    auto guard = Shared.lock();
+    if (depth == 0) {
+        return guard->value;
+    }
+    return reenter(depth - 1);
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        sc::lock::ReentrantMutex<int> lock(1);
+        auto outer = lock.lock();
+        auto inner = lock.lock();
+        auto tried = lock.try_lock();
+        bool other_failed = false;
+        std::thread([&] { other_failed = !lock.try_lock(); }).join();
+        outer.get_mut() = 2;
+        std::cout << "Outer nested: " << outer.is_nested() << ", inner nested: " << inner.is_nested()
+                  << ", try lock nested: " << (tried && tried->is_nested()) << ", other thread failed: "
+                  << other_failed << ", inner sees: " << *inner << std::endl;
+    }
+
+    {
+        sc::lock::ReentrantMutex<int> lock(0);
+        {
+            auto outer = lock.lock();
+            {
+                auto inner = lock.lock();
+            }
+            std::cout << "Locked after the inner guard: " << lock.is_locked() << ", owned: "
+                      << lock.is_owned_by_current_thread() << std::endl;
+        }
+        bool other_owned = false;
+        std::thread([&] {
+            auto guard = lock.lock();
+            other_owned = lock.is_owned_by_current_thread();
+        }).join();
+        std::cout << "Locked after the outer guard: " << lock.is_locked() << ", other thread owned: " << other_owned
+                  << ", mapped: " << *lock.lock().map([](const int &v) -> const int & { return v; }) << std::endl;
+    }
+
+    // The threads increment the value under the outermost guard, and re-enter it while holding it.
+    std::vector<std::thread> threads;
+    for (uint32_t i = 0; i < ThreadCount; ++i) {
+        threads.emplace_back([i] {
+            for (uint64_t n = 0; n < LoopCount / 100; ++n) {
+                auto guard = Shared.lock();
+                auto before = guard->value;
+                auto seen = reenter(i + 1);
+                guard.get_mut().value = seen + 1;
+                guard.get_mut().max_depth = std::max<uint64_t>(guard->max_depth, i + 1);
+                if (seen != before) {
+                    std::cout << "The value changed while holding the lock." << std::endl;
+                }
+            }
+        });
+    }
+    for (auto &t: threads) {
+        t.join();
+    }
+    auto guard = Shared.lock();
+    std::cout << "Value: " << guard->value << ", expected: " << ThreadCount * (LoopCount / 100) << ", max depth: "
+              << guard->max_depth << ", locked: " << Shared.is_locked() << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,120 @@
+///
+/// @file  scheduler_test.cpp
+/// @brief Test for sc::testing::Scheduler.
+///
+
+#include "testing/scheduler.hpp"
+
+#include "lock/spin_lock.hpp"
+#include "queue/mpmc_bounded_queue.hpp"
+
+#include "test_util.hpp"
+
+
+constexpr uint64_t SeedCount = 100;
+constexpr uint32_t TaskCount = 3;
+
+/// @brief An unsynchronized read-modify-write, which loses the updates in some interleavings.
+uint32_t racy_increment(sc::testing::Scheduler &scheduler)
+{
+    uint32_t value = 0;
+    for (uint32_t i = 0; i < TaskCount; ++i) {
+        scheduler.spawn([&value] {
+            auto v = value;
+            sc::testing::yield_now();
+            value = v + 1;
+        });
+    }
+    scheduler.run();
+    return value;
+}
+
+void run_reproducible()
+{
+    sc::testing::Scheduler first(42);
+    auto first_value = racy_increment(first);
+    sc::testing::Scheduler second(42);
+    auto second_value = racy_increment(second);
+    std::cout << "Same seed, same value: " << (first_value == second_value)
+              << ", same trace: " << (first.trace() == second.trace()) << std::endl;
+
+    uint64_t lost = 0;
+    uint64_t first_lost_seed = SeedCount;
+    for (uint64_t seed = 0; seed < SeedCount; ++seed) {
+        sc::testing::Scheduler scheduler(seed);
+        if (racy_increment(scheduler) != TaskCount) {
+            ++lost;
+            first_lost_seed = std::min(first_lost_seed, seed);
+        }
+    }
+    std::cout << "Lost update found by " << lost << " of " << SeedCount << " seeds, first seed: "
+              << first_lost_seed << std::endl;
+}
+
+void run_spin_lock()
+{
+    uint64_t wrong = 0;
+    for (uint64_t seed = 0; seed < SeedCount; ++seed) {
+        sc::testing::Scheduler scheduler(seed);
+        sc::lock::SpinLock<uint32_t, sc::testing::Backoff> lock(0);
+        for (uint32_t i = 0; i < TaskCount; ++i) {
+            scheduler.spawn([&lock] {
+                auto guard = lock.lock();
+                auto v = *guard;
+                // The other tasks spin on the lock and yield back.
+                sc::testing::yield_now();
+                *guard = v + 1;
+            });
+        }
+        scheduler.run();
+        if (*lock.lock() != TaskCount) {
+            ++wrong;
+        }
+    }
+    std::cout << "SpinLock lost updates: " << wrong << " of " << SeedCount << " seeds" << std::endl;
+}
+
+void run_queue()
+{
+    uint64_t wrong = 0;
+    for (uint64_t seed = 0; seed < SeedCount; ++seed) {
+        sc::testing::Scheduler scheduler(seed);
+        sc::mpmc::BoundedQueue<uint32_t> queue(2);
+        uint32_t sum = 0;
+        scheduler.spawn([&queue] {
+            for (uint32_t i = 1; i <= 4; ++i) {
+                while (!queue.try_enqueue(i)) {
+                    sc::testing::yield_now();
+                }
+            }
+        });
+        scheduler.spawn([&queue, &sum] {
+            for (uint32_t n = 0; n < 4;) {
+                if (auto v = queue.try_dequeue()) {
+                    sum += *v;
+                    ++n;
+                } else {
+                    sc::testing::yield_now();
+                }
+            }
+        });
+        scheduler.run();
+        if (sum != 10) {
+            ++wrong;
+        }
+    }
+    std::cout << "BoundedQueue wrong sums: " << wrong << " of " << SeedCount << " seeds" << std::endl;
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    run_reproducible();
+    run_spin_lock();
+    run_queue();
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,167 @@
+///
+/// @file  select_test.cpp
+/// @brief Test for sc::sync::Select.
+///
+
+#include "queue/mpmc_array_queue.hpp"
+#include "sync/select.hpp"
+
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "coro_util.hpp"
+#include "test_util.hpp"
+
+
+constexpr uint32_t ProducerCount = 4;
+constexpr uint64_t ItemCount = LoopCount / 10;
+
+using Queue = sc::BlockingQueue<sc::mpmc::ArrayListQueue<uint64_t>>;
+
+void run_blocking()
+{
+    Queue even;
+    Queue odd;
+    sc::OneshotCell<std::string> done;
+    sc::sync::Event stop;
+
+    uint64_t sum = 0;
+    uint64_t count = 0;
+    bool drained[2] = {false, false};
+    std::string result;
+    bool stopped = false;
+    sc::sync::Select select;
+    auto on_item = [&](uint32_t queue) {
+        return [&, queue](std::optional<uint64_t> v) {
+            if (v) {
+                sum += *v;
+                ++count;
+            } else {
+                drained[queue] = true;
+            }
+        };
+    };
+    select.recv(even, on_item(0))
+          .recv(odd, on_item(1))
+          .recv(done, [&](std::string s) { result = std::move(s); })
+          .wait(stop, [&] { stopped = true; });
+
+    auto begin = get_current_time();
+    std::vector<std::thread> threads;
+    for (uint32_t i = 0; i < ProducerCount; ++i) {
+        threads.emplace_back([&, i] {
+            for (uint64_t n = i; n < ItemCount; n += ProducerCount) {
+                (n % 2 == 0 ? even : odd).enqueue(n);
+            }
+        });
+    }
+    std::thread closer([&] {
+        for (uint32_t i = 0; i < ProducerCount; ++i) {
+            threads[i].join();
+        }
+        done.send("sent");
+        even.close();
+        odd.close();
+    });
+
+    // A drained queue is always ready from then on.
+    while (!drained[0] || !drained[1] || result.empty()) {
+        select.run();
+    }
+    closer.join();
+    std::cout << "Sum: " << sum << ", expected: " << ItemCount * (ItemCount - 1) / 2 << ", count: " << count
+              << ", oneshot: " << result << ", time: " << get_current_time() - begin << "ns" << std::endl;
+
+    std::thread setter([&] {
+        std::this_thread::sleep_for(std::chrono::milliseconds(10));
+        stop.set();
+    });
+    sc::sync::Select stop_select;
+    stop_select.wait(stop, [&] { stopped = true; });
+    auto index = stop_select.run();
+    setter.join();
+    std::cout << "Stopped: " << stopped << ", index: " << index << std::endl;
+}
+
+void run_timeout()
+{
+    Queue queue;
+    sc::sync::Event event(sc::sync::ResetMode::Auto);
+    sc::sync::Select select;
+    select.recv(queue, [](std::optional<uint64_t>) { }).wait(event, [] { });
+
+    auto none = select.try_run();
+    auto begin = get_current_time();
+    auto timeout = select.run_for(std::chrono::milliseconds(50));
+    auto elapsed = get_current_time() - begin;
+    event.set();
+    auto ready = select.run_for(std::chrono::milliseconds(50));
+    std::cout << "Try run: " << none.has_value() << ", timeout: " << !timeout.has_value() << ", waited >= 50ms: "
+              << (elapsed >= 50'000'000) << ", event index: " << ready.value_or(-1) << ", auto reset: "
+              << !event.is_set() << std::endl;
+}
+
+#if __cpp_impl_coroutine
+
+DetachedTask async_select(sc::sync::Select &select, const uint64_t &count, std::atomic<bool> &finished)
+{
+    while (count < ItemCount) {
+        co_await select.run_async();
+    }
+    finished.store(true, std::memory_order_release);
+}
+
+void run_async()
+{
+    Queue first;
+    Queue second;
+    uint64_t sum = 0;
+    uint64_t count = 0;
+    sc::sync::Select select;
+    auto on_item = [&](std::optional<uint64_t> v) {
+        sum += *v;
+        ++count;
+    };
+    select.recv(first, on_item).recv(second, on_item);
+
+    std::atomic<bool> finished{false};
+    async_select(select, count, finished);
+    std::vector<std::thread> threads;
+    for (uint32_t i = 0; i < ProducerCount; ++i) {
+        threads.emplace_back([&, i] {
+            for (uint64_t n = i; n < ItemCount; n += ProducerCount) {
+                (i % 2 == 0 ? first : second).enqueue(n);
+            }
+        });
+    }
+    for (auto &t: threads) {
+        t.join();
+    }
+    while (!finished.load(std::memory_order_acquire)) {
+        std::this_thread::yield();
+    }
+    std::cout << "Async sum: " << sum << ", expected: " << ItemCount * (ItemCount - 1) / 2 << std::endl;
+}
+
+#else
+
+void run_async()
+{
+    std::cout << "Skip the async test: built without cpp coroutine support." << std::endl;
+}
+
+#endif
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    run_timeout();
+    run_blocking();
+    run_async();
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
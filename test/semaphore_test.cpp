@@ -0,0 +1,139 @@
+///
+/// @file  semaphore_test.cpp
+/// @brief Test for sc::sync::Semaphore.
+///
+
+#include "sync/semaphore.hpp"
+
+#include <algorithm>
+#include <thread>
+#include <vector>
+
+#include "coro_util.hpp"
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 8;
+constexpr size_t Permits = 3;
+constexpr uint64_t AcquireCount = LoopCount / 100;
+
+/// @brief Records the max count of the threads holding the permits at the same time.
+struct Concurrency
+{
+    std::atomic<size_t> current{0};
+    std::atomic<size_t> max{0};
+
+    void enter(size_t count)
+    {
+        auto v = current.fetch_add(count, std::memory_order_relaxed) + count;
+        auto m = max.load(std::memory_order_relaxed);
+        while (v > m && !max.compare_exchange_weak(m, v, std::memory_order_relaxed)) { }
+    }
+
+    void leave(size_t count)
+    {
+        current.fetch_sub(count, std::memory_order_relaxed);
+    }
+};
+
+void acquire_loop(sc::sync::Semaphore &semaphore, Concurrency &concurrency, uint32_t index)
+{
+    for (uint64_t i = 0; i < AcquireCount; ++i) {
+        // acquire one or two permits, by blocking or with timeout.
+        size_t count = (i + index) % 2 + 1;
+        if (i % 3 == 0) {
+            auto permit = semaphore.acquire_timeout(std::chrono::seconds(10), count);
+            if (!permit) {
+                sync_io([] { std::cout << "Error: acquire timeout." << std::endl; });
+                continue;
+            }
+            concurrency.enter(count);
+            concurrency.leave(count);
+        } else {
+            auto permit = semaphore.acquire(count);
+            concurrency.enter(count);
+            concurrency.leave(count);
+        }
+    }
+}
+
+#if __cpp_impl_coroutine
+
+DetachedTask async_acquire(sc::sync::Semaphore &semaphore, std::vector<uint32_t> &order, uint32_t index)
+{
+    auto permit = co_await semaphore.acquire_async();
+    order.push_back(index);
+}
+
+void run_async()
+{
+    sc::sync::Semaphore semaphore(1);
+    std::vector<uint32_t> order;
+
+    bool suspended;
+    {
+        auto permit = semaphore.acquire();
+        // all coroutines are suspended, and resumed in order one by one when the permit is released.
+        for (uint32_t i = 0; i < 4; ++i) {
+            async_acquire(semaphore, order, i);
+        }
+        suspended = order.empty();
+    }
+
+    std::cout << "Async suspended: " << suspended << ", resumed order:";
+    for (auto i: order) {
+        std::cout << " " << i;
+    }
+    std::cout << ", available permits: " << semaphore.available_permits() << std::endl;
+}
+
+#else
+
+void run_async()
+{
+    std::cout << "Skip the async test: built without cpp coroutine support." << std::endl;
+}
+
+#endif
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    sc::sync::Semaphore semaphore(Permits);
+    {
+        auto permit = semaphore.acquire(Permits);
+        auto begin = get_current_time();
+        auto timeout = semaphore.acquire_timeout(std::chrono::milliseconds(100));
+        std::cout << "Try acquire when exhausted: " << semaphore.try_acquire().has_value()
+                  << ", acquire timeout: " << !timeout.has_value() << ", wait time: "
+                  << get_current_time() - begin << "ns" << std::endl;
+    }
+    {
+        auto permit = semaphore.try_acquire(2);
+        permit->forget();
+        std::cout << "Available after forget: " << semaphore.available_permits() << std::endl;
+        semaphore.release(2);
+    }
+
+    Concurrency concurrency;
+    auto begin = get_current_time();
+    std::vector<std::thread> threads;
+    threads.reserve(ThreadCount);
+    for (uint32_t i = 0; i < ThreadCount; ++i) {
+        threads.emplace_back(acquire_loop, std::ref(semaphore), std::ref(concurrency), i);
+    }
+    for (auto &t: threads) {
+        t.join();
+    }
+
+    std::cout << "Max concurrency: " << concurrency.max.load() << ", permits: " << Permits
+              << ", available after all: " << semaphore.available_permits() << ", time: "
+              << get_current_time() - begin << "ns" << std::endl;
+
+    run_async();
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,106 @@
+///
+/// @file  seq_lock_cell_test.cpp
+/// @brief Test for sc::SeqLockCell.
+///
+
+#include "cell/seq_lock_cell.hpp"
+
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ReaderCount = 3;
+constexpr uint32_t WriterCount = 2;
+
+/// @brief A 24-byte stats struct, all fields are always the same value.
+struct Stats
+{
+    uint64_t a;
+    uint64_t b;
+    uint64_t c;
+};
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    sc::SeqLockCell<Stats> cell(Stats{0, 0, 0});
+    std::atomic_flag barrier = ATOMIC_FLAG_INIT;
+    std::atomic<uint32_t> writer_done{0};
+    std::atomic<uint64_t> torn_reads{0};
+    std::atomic<uint64_t> total_reads{0};
+    std::atomic<uint64_t> validated_torn{0};
+    std::atomic<uint64_t> conflicts{0};
+
+    std::vector<std::thread> threads;
+    for (uint32_t i = 0; i < ReaderCount; ++i) {
+        threads.emplace_back([&] {
+            barrier.wait(false);
+            uint64_t reads = 0;
+            while (writer_done.load(std::memory_order_relaxed) != WriterCount) {
+                auto s = cell.read();
+                if (s.a != s.b || s.b != s.c) {
+                    torn_reads.fetch_add(1, std::memory_order_relaxed);
+                }
+                ++reads;
+            }
+            total_reads.fetch_add(reads, std::memory_order_relaxed);
+        });
+    }
+    // The optimistic reader validates once after using the value, and only retries on a conflict.
+    threads.emplace_back([&] {
+        barrier.wait(false);
+        uint64_t torn = 0;
+        uint64_t failed = 0;
+        while (writer_done.load(std::memory_order_relaxed) != WriterCount) {
+            auto r = cell.read_optimistic();
+            auto v = r.value();
+            if (!cell.validate(r)) {
+                ++failed;
+                continue;
+            }
+            if (v.a != v.b || v.b != v.c) {
+                ++torn;
+            }
+        }
+        validated_torn.fetch_add(torn, std::memory_order_relaxed);
+        conflicts.fetch_add(failed, std::memory_order_relaxed);
+    });
+    for (uint32_t i = 0; i < WriterCount; ++i) {
+        threads.emplace_back([&] {
+            barrier.wait(false);
+            for (uint64_t n = 0; n < LoopCount; ++n) {
+                cell.update([](Stats s) { return Stats{s.a + 1, s.b + 1, s.c + 1}; });
+            }
+            writer_done.fetch_add(1, std::memory_order_relaxed);
+        });
+    }
+
+    auto begin = get_current_time();
+    barrier.test_and_set();
+    barrier.notify_all();
+    for (auto &t: threads) {
+        t.join();
+    }
+    auto elapsed = get_current_time() - begin;
+
+    auto s = cell.read();
+    std::cout << "Final value: " << s.a << ", expected: " << WriterCount * LoopCount
+              << ", reads: " << total_reads.load() << ", torn reads: " << torn_reads.load()
+              << ", time: " << elapsed << "ns" << std::endl;
+
+    auto r = cell.read_optimistic();
+    std::cout << "Optimistic torn reads: " << validated_torn.load() << ", conflicts: " << conflicts.load()
+              << ", validated after writes: " << cell.validate(r) << ", value: " << r.value().a << std::endl;
+
+    cell.write(Stats{1, 1, 1});
+    std::cout << "Validated after a write: " << cell.validate(r) << std::endl;
+    std::cout << "Value after write: " << cell.read().a << std::endl;
+    std::cout << "Into inner: " << std::move(cell).into_inner().a << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
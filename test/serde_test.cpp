@@ -0,0 +1,185 @@
+///
+/// @file  serde_test.cpp
+/// @brief Test for the serialization hooks enabled by SYNC_CELL_SERDE.
+///
+
+#define SYNC_CELL_SERDE
+
+#include "cell/once_sync_cell.hpp"
+#include "cell/sync_cell.hpp"
+#include "lock/mcs_lock.hpp"
+#include "lock/rw_spin_lock.hpp"
+#include "lock/spin_lock.hpp"
+#include "lock/ticket_lock.hpp"
+
+#include <optional>
+#include <sstream>
+#include <string>
+#include <type_traits>
+#include <variant>
+
+#include "test_util.hpp"
+
+
+template<typename T>
+constexpr bool IsOptional = false;
+
+template<typename T>
+constexpr bool IsOptional<std::optional<T>> = true;
+
+/// @brief A minimal archive called as 'ar(value)' like the cereal ones, which writes the values as
+/// the text separated by spaces.
+struct OutArchive
+{
+    std::ostringstream out;
+
+    template<typename T>
+    void operator()(const T &value)
+    {
+        if constexpr(std::is_arithmetic_v<T> || std::is_same_v<T, std::string>) {
+            out << value << ' ';
+        } else if constexpr(IsOptional<T>) {
+            out << value.has_value() << ' ';
+            if (value) {
+                (*this)(*value);
+            }
+        } else {
+            save(*this, value);
+        }
+    }
+};
+
+struct InArchive
+{
+    std::istringstream in;
+
+    template<typename T>
+    void operator()(T &value)
+    {
+        if constexpr(std::is_arithmetic_v<T> || std::is_same_v<T, std::string>) {
+            in >> value;
+        } else if constexpr(IsOptional<T>) {
+            bool has_value;
+            in >> has_value;
+            if (has_value) {
+                (*this)(value.emplace());
+            }
+        } else {
+            load(*this, value);
+        }
+    }
+};
+
+/// @brief A minimal json value with the interface used by the hooks, like the nlohmann::json.
+struct Json
+{
+    std::variant<std::nullptr_t, double, std::string> value;
+
+    template<typename T>
+    Json &operator=(T v)
+    {
+        if constexpr(std::is_arithmetic_v<T>) {
+            value = (double)v;
+        } else {
+            value = std::move(v);
+        }
+        return *this;
+    }
+
+    [[nodiscard]] bool is_null() const
+    {
+        return std::holds_alternative<std::nullptr_t>(value);
+    }
+
+    template<typename T>
+    [[nodiscard]] T get() const
+    {
+        if constexpr(std::is_arithmetic_v<T>) {
+            return (T)std::get<double>(value);
+        } else {
+            return std::get<T>(value);
+        }
+    }
+};
+
+struct State
+{
+    sc::SyncCell<uint64_t> limit;
+    sc::OnceSyncCell<std::string> name;
+    sc::OnceSyncCell<std::string> unset;
+    sc::lock::SpinLock<std::string> owner;
+    sc::lock::RwSpinLock<double> ratio;
+    sc::lock::TicketLock<int> ticket;
+    sc::lock::McsLock<int> mcs;
+};
+
+template<typename Archive, typename S>
+void serialize_state(Archive &ar, S &state)
+{
+    ar(state.limit);
+    ar(state.name);
+    ar(state.unset);
+    ar(state.owner);
+    ar(state.ratio);
+    ar(state.ticket);
+    ar(state.mcs);
+}
+
+void save(OutArchive &ar, const State &state)
+{
+    serialize_state(ar, state);
+}
+
+void load(InArchive &ar, State &state)
+{
+    serialize_state(ar, state);
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    State state;
+    state.limit.store(42);
+    state.name.set("worker");
+    *state.owner.lock() = "main";
+    *state.ratio.write() = 0.5;
+    *state.ticket.lock() = 7;
+    *state.mcs.lock() = -3;
+
+    OutArchive out;
+    out(std::as_const(state));
+    std::cout << "Archive: " << out.out.str() << std::endl;
+
+    State restored;
+    InArchive in{std::istringstream(out.out.str())};
+    in(restored);
+    std::cout << "Restored: limit: " << restored.limit.load() << ", name: " << *restored.name.get() << ", unset: "
+              << (restored.unset.get() == nullptr) << ", owner: " << *restored.owner.lock() << ", ratio: "
+              << *restored.ratio.read() << ", ticket: " << *restored.ticket.lock() << ", mcs: "
+              << *restored.mcs.lock() << std::endl;
+
+    // The initialized once cell keeps its value.
+    InArchive again{std::istringstream("1 other")};
+    sc::OnceSyncCell<std::string> kept;
+    kept.set("first");
+    again(kept);
+    std::cout << "Once cell kept: " << *kept.get() << std::endl;
+
+    Json j;
+    to_json(j, state.limit);
+    sc::SyncCell<uint64_t> limit;
+    from_json(j, limit);
+    Json empty;
+    to_json(empty, state.unset);
+    sc::OnceSyncCell<std::string> name;
+    Json text;
+    to_json(text, state.owner);
+    from_json(text, name);
+    std::cout << "Json: limit: " << limit.load() << ", unset is null: " << empty.is_null() << ", name: "
+              << *name.get() << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
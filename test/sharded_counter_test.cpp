@@ -0,0 +1,56 @@
+///
+/// @file  sharded_counter_test.cpp
+/// @brief Test for sc::util::ShardedCounter.
+///
+
+#include "util/sharded_counter.hpp"
+
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+
+constinit sc::util::ShardedCounter<> StaticCounter;
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    sc::util::ShardedCounter<> counter;
+    auto sharded_time = run_threads(ThreadCount, [&counter] {
+        for (uint64_t n = 0; n < LoopCount; ++n) {
+            counter.add();
+        }
+    });
+    std::cout << "[ShardedCounter] sum: " << counter.sum() << ", expected: " << ThreadCount * LoopCount
+              << ", time: " << sharded_time << "ns" << std::endl;
+
+    std::atomic<uint64_t> atomic{0};
+    auto atomic_time = run_threads(ThreadCount, [&atomic] {
+        for (uint64_t n = 0; n < LoopCount; ++n) {
+            atomic.fetch_add(1, std::memory_order_relaxed);
+        }
+    });
+    std::cout << "[std::atomic] sum: " << atomic.load() << ", time: " << atomic_time << "ns" << std::endl;
+
+    // More threads than the shards.
+    sc::util::ShardedCounter<2> small;
+    run_threads(ThreadCount, [&small] {
+        for (uint64_t n = 0; n < LoopCount; ++n) {
+            small.add(2);
+        }
+    });
+    std::cout << "[ShardedCounter<2>] sum: " << small.sum() << ", expected: " << 2 * ThreadCount * LoopCount
+              << std::endl;
+
+    small.reset();
+    StaticCounter.add(3);
+    std::cout << "Sum after reset: " << small.sum() << ", static counter: " << StaticCounter.sum() << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
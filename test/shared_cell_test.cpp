@@ -0,0 +1,122 @@
+///
+/// @file  shared_cell_test.cpp
+/// @brief Test for sc::SharedCell over the cell flavors.
+///
+
+#include "cell/shared_cell.hpp"
+
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+struct Counter
+{
+    uint64_t hits;
+    uint64_t misses;
+};
+
+struct Config
+{
+    std::string name;
+    int version;
+};
+
+static_assert(sc::SharedCell<sc::SyncCell<Counter>>);
+static_assert(sc::SharedCell<sc::SyncCell<Config>>);
+static_assert(sc::SharedCell<sc::SyncCell<Counter, sc::ForceLock<>>>);
+static_assert(sc::SharedCell<sc::RcuCell<Config>>);
+static_assert(sc::SharedCell<sc::RcuCell<uint64_t>>);
+static_assert(sc::SharedCell<sc::SeqLockCell<Counter>>);
+static_assert(sc::SharedCell<sc::ArcCell<Config>>);
+static_assert(!sc::SharedCell<std::atomic<int>>);
+
+/// @brief A library type written once over any cell.
+template<sc::SharedCell Cell>
+class HitTracker
+{
+    using Traits = sc::SharedCellTraits<Cell>;
+
+public:
+    explicit HitTracker(Cell &cell) noexcept : cell_(cell) { }
+
+    void record(bool hit)
+    {
+        Traits::update(cell_, [hit](const Counter &c) {
+            return hit ? Counter{c.hits + 1, c.misses} : Counter{c.hits, c.misses + 1};
+        });
+    }
+
+    [[nodiscard]] uint64_t total() const
+    {
+        auto snapshot = Traits::load_snapshot(cell_);
+        return snapshot->hits + (*snapshot).misses;
+    }
+
+private:
+    Cell &cell_;
+};
+
+template<sc::SharedCell Cell>
+void track(const char *name, Cell &cell)
+{
+    constexpr uint32_t ThreadCount = 4;
+    constexpr uint64_t PerThread = LoopCount / 1000;
+
+    HitTracker<Cell> tracker(cell);
+    std::vector<std::thread> threads;
+    for (uint32_t t = 0; t < ThreadCount; ++t) {
+        threads.emplace_back([&tracker, t] {
+            for (uint64_t i = 0; i < PerThread; ++i) {
+                tracker.record((i + t) % 2 == 0);
+            }
+        });
+    }
+    for (auto &thread: threads) {
+        thread.join();
+    }
+    auto snapshot = sc::SharedCellTraits<Cell>::load_snapshot(cell);
+    std::cout << name << ": total matched: " << (tracker.total() == ThreadCount * PerThread) << ", hits: "
+              << snapshot->hits << ", misses: " << snapshot->misses << std::endl;
+}
+
+template<sc::SharedCell Cell>
+std::string rename(Cell &cell)
+{
+    using Traits = sc::SharedCellTraits<Cell>;
+    Traits::store(cell, Config{"initial", 1});
+    Traits::update(cell, [](const Config &c) { return Config{c.name + "-renamed", c.version + 1}; });
+    auto snapshot = Traits::load_snapshot(cell);
+    return snapshot->name + "/" + std::to_string(snapshot->version);
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        sc::SyncCell<Counter> sync_cell(Counter{0, 0});
+        sc::SyncCell<Counter, sc::ForceLock<>> locked_cell(Counter{0, 0});
+        sc::SeqLockCell<Counter> seq_lock_cell(Counter{0, 0});
+        sc::RcuCell<Counter> rcu_cell(Counter{0, 0});
+        sc::ArcCell<Counter> arc_cell(std::make_shared<Counter>());
+        track("SyncCell", sync_cell);
+        track("SyncCell<ForceLock>", locked_cell);
+        track("SeqLockCell", seq_lock_cell);
+        track("RcuCell", rcu_cell);
+        track("ArcCell", arc_cell);
+    }
+
+    {
+        sc::SyncCell<Config> sync_cell;
+        sc::RcuCell<Config> rcu_cell;
+        sc::ArcCell<Config> arc_cell(std::make_shared<Config>());
+        std::cout << "Renamed: " << rename(sync_cell) << " " << rename(rcu_cell) << " " << rename(arc_cell)
+                  << std::endl;
+    }
+
+    std::cout << "hello world" << std::endl;
+    return 0;
+}
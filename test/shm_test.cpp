@@ -0,0 +1,149 @@
+///
+/// @file  shm_test.cpp
+/// @brief Test for sc::shm::ShmCell and sc::shm::ShmQueue.
+///
+
+#define SYNC_CELL_SHM
+
+#include "cell/shm_cell.hpp"
+#include "queue/shm_queue.hpp"
+
+#include <chrono>
+#include <string>
+#include <thread>
+#include <vector>
+
+#include <sys/wait.h>
+
+#include "test_util.hpp"
+
+
+struct Status
+{
+    uint64_t produced;
+    uint32_t pid;
+    bool done;
+};
+
+struct Shared
+{
+    alignas(sc::util::CacheLineSize) std::byte cell[sc::shm::ShmCell<Status>::required_size()];
+    alignas(sc::util::CacheLineSize) std::byte queue[sc::shm::ShmQueue<uint64_t>::required_size(64)];
+};
+
+constexpr uint64_t ItemCount = 100000;
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        // The memory is zeroed, nothing can be attached before it is created.
+        auto *memory = ::mmap(nullptr, sizeof(Shared), PROT_READ | PROT_WRITE, MAP_SHARED | MAP_ANONYMOUS, -1, 0);
+        auto *shared = static_cast<Shared *>(memory);
+        std::cout << "Attached before created: " << (sc::shm::ShmCell<Status>::attach(shared->cell) != nullptr)
+                  << std::endl;
+
+        auto *cell = sc::shm::ShmCell<Status>::create(shared->cell);
+        auto *queue = sc::shm::ShmQueue<uint64_t>::create(shared->queue, 50);
+        std::cout << "Capacity: " << queue->capacity() << ", wrong type attached: "
+                  << (sc::shm::ShmQueue<uint32_t>::attach(shared->queue) != nullptr) << ", cell as queue: "
+                  << (sc::shm::ShmQueue<Status>::attach(shared->cell) != nullptr) << std::endl;
+
+        auto pid = ::fork();
+        if (pid == 0) {
+            // The child attaches to the objects created by the parent, as an unrelated process would.
+            auto *status = sc::shm::ShmCell<Status>::attach(shared->cell);
+            auto *items = sc::shm::ShmQueue<uint64_t>::attach(shared->queue);
+            if (status == nullptr || items == nullptr) {
+                ::_exit(1);
+            }
+            for (uint64_t i = 1; i <= ItemCount; ++i) {
+                items->enqueue(i);
+                if (i % 1000 == 0) {
+                    status->store(Status{i, static_cast<uint32_t>(::getpid()), false});
+                }
+            }
+            status->update([](Status s) {
+                s.done = true;
+                return s;
+            });
+            ::_exit(0);
+        }
+
+        uint64_t sum = 0;
+        for (uint64_t i = 0; i < ItemCount; ++i) {
+            sum += queue->dequeue();
+        }
+        auto version = cell->version();
+        while (!cell->load().done) {
+            version = cell->wait_changed(version);
+        }
+        int exit_status = 0;
+        ::waitpid(pid, &exit_status, 0);
+        auto status = cell->load();
+        std::cout << "Child exit: " << WEXITSTATUS(exit_status) << ", sum: " << sum << " (expected "
+                  << ItemCount * (ItemCount + 1) / 2 << "), produced: " << status.produced << ", pid: "
+                  << (status.pid == static_cast<uint32_t>(pid)) << ", version: " << cell->version() << ", empty: "
+                  << queue->is_empty() << std::endl;
+        ::munmap(memory, sizeof(Shared));
+    }
+
+    {
+        // The threads of one process share the objects in the same way.
+        alignas(sc::util::CacheLineSize) static std::byte cell_memory[sc::shm::ShmCell<Status>::required_size()];
+        alignas(sc::util::CacheLineSize) static std::byte queue_memory[sc::shm::ShmQueue<uint64_t>::required_size(4)];
+        auto *cell = sc::shm::ShmCell<Status>::create(cell_memory);
+        auto *queue = sc::shm::ShmQueue<uint64_t>::create(queue_memory, 4);
+        std::cout << "Timed out version: " << cell->wait_changed_for(0, std::chrono::milliseconds(10))
+                  << ", empty dequeue: " << queue->try_dequeue().has_value() << std::endl;
+
+        constexpr uint64_t PerThread = LoopCount / 1000;
+        std::vector<std::thread> threads;
+        for (uint32_t t = 0; t < 2; ++t) {
+            threads.emplace_back([=] {
+                for (uint64_t i = 0; i < PerThread; ++i) {
+                    queue->enqueue(1);
+                }
+            });
+        }
+        uint64_t sum = 0;
+        for (uint64_t i = 0; i < 2 * PerThread; ++i) {
+            sum += queue->dequeue();
+            cell->update([](Status s) {
+                ++s.produced;
+                return s;
+            });
+        }
+        for (auto &thread: threads) {
+            thread.join();
+        }
+        std::cout << "Sum: " << sum << ", updates: " << cell->load().produced << ", full: "
+                  << queue->try_enqueue(1) << queue->try_enqueue(2) << queue->try_enqueue(3)
+                  << queue->try_enqueue(4) << queue->try_enqueue(5) << ", len: " << queue->len() << std::endl;
+    }
+
+    {
+        auto name = "/sc_shm_test_" + std::to_string(::getpid());
+        try {
+            auto region = sc::shm::Region::create(name, sc::shm::ShmCell<Status>::required_size());
+            sc::shm::ShmCell<Status>::create(region.data(), Status{7, 0, false});
+            auto opened = sc::shm::Region::open(name);
+            auto *cell = sc::shm::ShmCell<Status>::attach(opened.data());
+            bool exists = false;
+            try {
+                sc::shm::Region::create(name, 64);
+            } catch (const std::system_error &) {
+                exists = true;
+            }
+            std::cout << "Region value: " << (cell != nullptr ? cell->load().produced : 0) << ", created twice: "
+                      << !exists << ", unlinked: " << sc::shm::Region::unlink(name) << ", unlinked twice: "
+                      << sc::shm::Region::unlink(name) << std::endl;
+        } catch (const std::system_error &e) {
+            std::cout << "Skip the region test, no shared memory: " << e.what() << std::endl;
+        }
+    }
+
+    std::cout << "hello world" << std::endl;
+    return 0;
+}
@@ -0,0 +1,55 @@
+///
+/// @file  single_thread_test.cpp
+/// @brief Test for the build with SYNC_CELL_SINGLE_THREADED: the try operations work, and a wait
+/// which can never complete is reported.
+///
+
+#define SYNC_CELL_SINGLE_THREADED
+
+#include "cell/once_sync_cell.hpp"
+#include "cell/sync_cell.hpp"
+#include "lock/spin_lock.hpp"
+#include "queue/mpmc_bounded_queue.hpp"
+#include "queue/spsc_ring_buffer.hpp"
+#include "shared/single_thread.hpp"
+
+#include <cstdlib>
+
+#include "test_util.hpp"
+
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    std::cout << "SC_HAS_THREADS: " << (SC_HAS_THREADS == 1) << ", SC_HAS_STD: " << (SC_HAS_STD == 1) << std::endl;
+
+    sc::SyncCell<int> cell(1);
+    cell.store(cell.load() + 1);
+    sc::OnceSyncCell<int> once;
+    std::cout << "Cells: " << cell.load() << ", " << once.get_or_init([] { return 42; }) << std::endl;
+
+    sc::lock::SpinLock<int> lock(3);
+    auto guard = lock.try_lock();
+    std::cout << "Lock: " << **guard << ", try again: " << lock.try_lock().has_value() << std::endl;
+
+    sc::mpmc::BoundedQueue<int> queue(2);
+    auto first = queue.try_enqueue(1) && queue.try_enqueue(2);
+    std::cout << "BoundedQueue: enqueued: " << first << ", full: " << !queue.try_enqueue(3) << std::endl;
+
+    sc::spsc::RingBuffer<int> ring_buffer(2);
+    auto [producer, consumer] = ring_buffer.split();
+    std::cout << "RingBuffer: empty: " << !consumer.try_dequeue().has_value() << std::endl;
+
+    // The blocking enqueue to the full queue waits for a consumer thread, which does not exist.
+    sc::single_thread::set_handler([](const char *what) noexcept {
+        std::cout << "Would block: " << what << std::endl;
+        std::cout << "hello world" << std::endl;
+        std::_Exit(0);
+    });
+    queue.enqueue(3);
+
+    std::cout << "The blocking enqueue returned" << std::endl;
+
+    return 1;
+}
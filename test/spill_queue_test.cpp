@@ -0,0 +1,135 @@
+///
+/// @file  spill_queue_test.cpp
+/// @brief Test for sc::mpmc::SpillQueue.
+///
+
+#include "queue/spill_queue.hpp"
+
+#include <atomic>
+#include <filesystem>
+#include <fstream>
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+struct Order
+{
+    std::string symbol;
+    uint32_t quantity;
+};
+
+template<>
+struct sc::SpillCodec<Order>
+{
+    static void encode(const Order &order, std::string &out)
+    {
+        out.append(reinterpret_cast<const char *>(&order.quantity), sizeof(order.quantity));
+        out.append(order.symbol);
+    }
+
+    static Order decode(std::string_view bytes)
+    {
+        Order order{std::string(bytes.substr(sizeof(uint32_t))), 0};
+        std::memcpy(&order.quantity, bytes.data(), sizeof(uint32_t));
+        return order;
+    }
+};
+
+constexpr uint32_t ThreadCount = 2;
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    auto dir = std::filesystem::temp_directory_path();
+    auto path = dir / ("sc_spill_test_" + std::to_string(std::hash<std::thread::id>()(std::this_thread::get_id())));
+
+    {
+        sc::mpmc::SpillQueue<uint64_t> queue(path, 4);
+        for (uint64_t i = 0; i < 10; ++i) {
+            queue.enqueue(i);
+        }
+        std::cout << "Len: " << queue.len() << ", spilled: " << queue.spilled() << std::endl;
+        std::cout << "Dequeued:";
+        while (auto value = queue.try_dequeue()) {
+            std::cout << " " << *value;
+        }
+        std::cout << std::endl << "Empty: " << queue.is_empty() << ", file size: "
+                  << std::filesystem::file_size(path) << std::endl;
+    }
+
+    {
+        // The queue of a crashed run, whose items in the memory are lost.
+        {
+            sc::mpmc::SpillQueue<Order> queue(path, 2);
+            for (uint32_t i = 0; i < 8; ++i) {
+                queue.enqueue(Order{"SYM" + std::to_string(i), i * 10});
+            }
+            // Takes the 2 items in the memory, then refills 2 others from the file.
+            std::cout << "Before the crash: " << queue.try_dequeue()->symbol << " " << queue.try_dequeue()->symbol
+                      << " " << queue.try_dequeue()->symbol << ", spilled: " << queue.spilled() << std::endl;
+        }
+        // A record torn by the crash.
+        {
+            std::ofstream out(path, std::ios::binary | std::ios::app);
+            out.write("\x20\0\0\0\1\2", 6);
+        }
+        auto queue = sc::mpmc::SpillQueue<Order>::recover(path, 2);
+        std::cout << "Recovered: " << queue.len() << ":";
+        queue.enqueue(Order{"NEW", 1});
+        while (auto order = queue.try_dequeue()) {
+            std::cout << " " << order->symbol << "/" << order->quantity;
+        }
+        std::cout << std::endl;
+    }
+
+    {
+        std::filesystem::remove(path);
+        auto queue = sc::mpmc::SpillQueue<std::string>::recover(path, 1);
+        std::cout << "Recovered a missing file: " << queue.len() << ", created: " << std::filesystem::exists(path)
+                  << std::endl;
+
+        std::ofstream(path, std::ios::binary | std::ios::trunc) << "not a spill file";
+        try {
+            auto broken = sc::mpmc::SpillQueue<std::string>::recover(path, 1);
+            std::cout << "Recovered a broken file" << std::endl;
+        } catch (const std::system_error &e) {
+            std::cout << "Broken file: " << e.what() << std::endl;
+        }
+    }
+
+    {
+        sc::mpmc::SpillQueue<uint64_t> queue(path, 64);
+        constexpr uint64_t PerThread = LoopCount / 1000;
+        std::atomic<uint64_t> sum{0};
+        std::atomic<uint64_t> count{0};
+        std::vector<std::thread> threads;
+        for (uint32_t t = 0; t < ThreadCount; ++t) {
+            threads.emplace_back([&] {
+                for (uint64_t i = 1; i <= PerThread; ++i) {
+                    queue.enqueue(i);
+                }
+            });
+            threads.emplace_back([&] {
+                while (count.load(std::memory_order_relaxed) < ThreadCount * PerThread) {
+                    if (auto value = queue.try_dequeue()) {
+                        sum.fetch_add(*value, std::memory_order_relaxed);
+                        count.fetch_add(1, std::memory_order_relaxed);
+                    }
+                }
+            });
+        }
+        for (auto &thread: threads) {
+            thread.join();
+        }
+        std::cout << "Sum: " << sum.load() << " (expected " << ThreadCount * PerThread * (PerThread + 1) / 2
+                  << "), empty: " << queue.is_empty() << std::endl;
+    }
+
+    std::filesystem::remove(path);
+    std::cout << "hello world" << std::endl;
+    return 0;
+}
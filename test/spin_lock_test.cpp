@@ -0,0 +1,259 @@
+///
+/// @file  spin_lock_test.cpp
+/// @brief Test for sc::lock::SpinLock and sc::lock::RwSpinLock.
+///
+
+#include "lock/rw_spin_lock.hpp"
+#include "lock/spin_lock.hpp"
+
+#include <stdexcept>
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+constexpr uint32_t ReaderCount = 3;
+constexpr uint32_t WriterCount = 2;
+
+/// @brief Both fields are always the same value when the lock is not held by a writer.
+struct Pair
+{
+    uint64_t a = 0;
+    uint64_t b = 0;
+};
+
+void run_spin_lock()
+{
+    sc::lock::SpinLock<Pair> lock;
+    {
+        auto guard = lock.lock();
+        std::cout << "SpinLock try lock when locked: " << lock.try_lock().has_value() << std::endl;
+    }
+
+    auto begin = get_current_time();
+    std::vector<std::thread> threads;
+    threads.reserve(ThreadCount);
+    for (uint32_t i = 0; i < ThreadCount; ++i) {
+        threads.emplace_back([&lock] {
+            for (uint64_t n = 0; n < LoopCount; ++n) {
+                auto guard = lock.lock();
+                ++guard->a;
+                ++guard->b;
+            }
+        });
+    }
+    for (auto &t: threads) {
+        t.join();
+    }
+
+    auto guard = lock.try_lock();
+    std::cout << "SpinLock value: " << (*guard)->a << " / " << (*guard)->b << ", expected: "
+              << ThreadCount * LoopCount << ", time: " << get_current_time() - begin << "ns" << std::endl;
+}
+
+void run_rw_spin_lock()
+{
+    sc::lock::RwSpinLock<Pair> lock;
+    {
+        auto r1 = lock.read();
+        auto r2 = lock.try_read();
+        std::cout << "RwSpinLock readers: " << lock.reader_count() << ", try write when read locked: "
+                  << lock.try_write().has_value() << std::endl;
+    }
+    {
+        auto w = lock.write();
+        std::cout << "RwSpinLock try read when write locked: " << lock.try_read().has_value()
+                  << ", try write: " << lock.try_write().has_value() << std::endl;
+    }
+
+    std::atomic<uint32_t> writer_done{0};
+    std::atomic<uint64_t> torn_reads{0};
+    std::atomic<uint64_t> total_reads{0};
+
+    auto begin = get_current_time();
+    std::vector<std::thread> threads;
+    for (uint32_t i = 0; i < ReaderCount; ++i) {
+        threads.emplace_back([&] {
+            uint64_t reads = 0;
+            while (writer_done.load(std::memory_order_relaxed) != WriterCount) {
+                auto guard = lock.read();
+                if (guard->a != guard->b) {
+                    torn_reads.fetch_add(1, std::memory_order_relaxed);
+                }
+                ++reads;
+            }
+            total_reads.fetch_add(reads, std::memory_order_relaxed);
+        });
+    }
+    for (uint32_t i = 0; i < WriterCount; ++i) {
+        threads.emplace_back([&] {
+            for (uint64_t n = 0; n < LoopCount / 10; ++n) {
+                auto guard = lock.write();
+                ++guard->a;
+                ++guard->b;
+            }
+            writer_done.fetch_add(1, std::memory_order_relaxed);
+        });
+    }
+    for (auto &t: threads) {
+        t.join();
+    }
+
+    auto guard = lock.read();
+    std::cout << "RwSpinLock value: " << guard->a << ", expected: " << WriterCount * (LoopCount / 10)
+              << ", reads: " << total_reads.load() << ", torn reads: " << torn_reads.load()
+              << ", time: " << get_current_time() - begin << "ns" << std::endl;
+}
+
+/// @brief Check-then-modify by the upgradeable readers: the value never exceeds the limit.
+void run_upgradeable()
+{
+    sc::lock::RwSpinLock<Pair> lock;
+    {
+        auto upgradeable = lock.read_upgradeable();
+        auto reader = lock.try_read();
+        std::cout << "Upgradeable with reader: " << reader.has_value() << ", another upgradeable: "
+                  << lock.try_read_upgradeable().has_value() << ", writer: " << lock.try_write().has_value()
+                  << ", try upgrade with reader: " << upgradeable.try_upgrade().has_value() << std::endl;
+    }
+
+    constexpr uint64_t Limit = LoopCount / 10;
+    std::atomic<uint64_t> upgrades{0};
+
+    auto begin = get_current_time();
+    std::vector<std::thread> threads;
+    threads.reserve(ThreadCount);
+    for (uint32_t i = 0; i < ThreadCount; ++i) {
+        threads.emplace_back([&] {
+            while (true) {
+                auto guard = lock.read_upgradeable();
+                if (guard->a >= Limit) {
+                    break;
+                }
+                auto writer = std::move(guard).upgrade();
+                ++writer->a;
+                ++writer->b;
+                upgrades.fetch_add(1, std::memory_order_relaxed);
+            }
+        });
+    }
+    for (auto &t: threads) {
+        t.join();
+    }
+
+    auto guard = lock.read();
+    std::cout << "Upgradeable value: " << guard->a << ", expected: " << Limit << ", upgrades: "
+              << upgrades.load() << ", time: " << get_current_time() - begin << "ns" << std::endl;
+}
+
+/// @brief The mapped guards keep holding the lock.
+void run_mapped()
+{
+    sc::lock::SpinLock<std::pair<uint64_t, std::string>> lock(1, "a");
+    {
+        auto name = lock.lock().map_mut([](auto &p) -> std::string & { return p.second; });
+        name->append("b");
+        std::cout << "Mapped guard holds the lock: " << lock.is_locked() << ", value: " << *name << std::endl;
+        auto first = std::move(name).map([](const std::string &n) -> const char & { return n[0]; });
+        std::cout << "Mapped again: " << *first << ", still locked: " << lock.is_locked() << std::endl;
+    }
+    std::cout << "Released after the mapped guard: " << !lock.is_locked() << std::endl;
+
+    sc::lock::RwSpinLock<Pair> rw_lock;
+    {
+        auto a = rw_lock.write().map_mut([](Pair &p) -> uint64_t & { return p.a; });
+        *a = 2;
+        std::cout << "Mapped write guard blocks the readers: " << !rw_lock.try_read().has_value() << std::endl;
+    }
+    {
+        auto a = rw_lock.read().map([](const Pair &p) -> const uint64_t & { return p.a; });
+        auto b = rw_lock.read_upgradeable().map([](const Pair &p) -> const uint64_t & { return p.b; });
+        std::cout << "Mapped readers: " << rw_lock.reader_count() << ", a: " << *a << ", b: " << *b << std::endl;
+    }
+}
+
+/// @brief Returns true if the 'f' throws the PoisonError.
+template<typename F>
+bool throws_poison(F f)
+{
+    try {
+        f();
+    } catch (const sc::lock::PoisonError &) {
+        return true;
+    }
+    return false;
+}
+
+void run_poison()
+{
+    using PoisonSpinLock = sc::lock::SpinLock<Pair, sc::util::Backoff, sc::lock::Poison>;
+    using PoisonRwLock = sc::lock::RwSpinLock<Pair, sc::util::Backoff, sc::lock::Poison>;
+
+    // A writer throws in the middle of the update of the pair.
+    auto half_update = [](auto guard) {
+        guard->a = 1;
+        throw std::runtime_error("failed");
+    };
+
+    PoisonSpinLock spin_lock(Pair{0, 0});
+    try {
+        half_update(spin_lock.lock());
+    } catch (const std::runtime_error &) { }
+    std::cout << "Spin lock poisoned: " << spin_lock.is_poisoned() << ", lock throws: "
+              << throws_poison([&] { (void) spin_lock.lock(); }) << ", try lock throws: "
+              << throws_poison([&] { (void) spin_lock.try_lock(); }) << ", unlocked: " << !spin_lock.is_locked();
+    {
+        // Recover the value.
+        auto guard = spin_lock.lock_ignoring_poison();
+        guard->b = guard->a;
+        spin_lock.clear_poison();
+    }
+    auto pair = *spin_lock.lock();
+    std::cout << ", recovered: " << pair.a << " / " << pair.b << std::endl;
+
+    PoisonRwLock rw_lock(Pair{0, 0});
+    // A read guard destroyed by an exception does not poison the lock.
+    try {
+        auto guard = rw_lock.read();
+        throw std::runtime_error("failed");
+    } catch (const std::runtime_error &) { }
+    auto read_poisoned = rw_lock.is_poisoned();
+    try {
+        half_update(std::move(rw_lock.read_upgradeable()).upgrade());
+    } catch (const std::runtime_error &) { }
+    std::cout << "Rw lock poisoned by a reader: " << read_poisoned << ", by a writer: " << rw_lock.is_poisoned()
+              << ", read throws: " << throws_poison([&] { (void) rw_lock.read(); }) << ", write throws: "
+              << throws_poison([&] { (void) rw_lock.try_write(); }) << ", unlocked: "
+              << (rw_lock.reader_count() == 0 && !rw_lock.is_write_locked()) << std::endl;
+
+    // The default policy does not poison.
+    sc::lock::SpinLock<Pair> no_poison(Pair{0, 0});
+    try {
+        half_update(no_poison.lock());
+    } catch (const std::runtime_error &) { }
+    std::cout << "Default policy poisoned: " << no_poison.is_poisoned() << ", value: " << no_poison.lock()->a
+              << ", lock is noexcept: " << noexcept(no_poison.lock()) << std::endl;
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    run_spin_lock();
+    run_rw_spin_lock();
+    run_upgradeable();
+    run_mapped();
+    run_poison();
+
+    sc::lock::SpinLock<std::string> spin_lock("a");
+    sc::lock::RwSpinLock<std::string> rw_lock("b");
+    std::cout << "Into inner: " << std::move(spin_lock).into_inner() << " / " << std::move(rw_lock).into_inner()
+              << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
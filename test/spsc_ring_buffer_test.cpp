@@ -0,0 +1,55 @@
+///
+/// @file  spsc_ring_buffer_test.cpp
+/// @brief Test for sc::spsc::RingBuffer.
+///
+
+#include "queue/spsc_ring_buffer.hpp"
+
+#include "queue_thread_run.hpp"
+
+
+constexpr size_t BufferCapacity = 1024;
+
+int main()
+{
+    using Buffer = sc::spsc::RingBuffer<Task>;
+
+    Buffer ring_buffer(BufferCapacity);
+    std::atomic_flag barrier = ATOMIC_FLAG_INIT;
+
+    std::cout << "Buffer is lock free: " << std::boolalpha <<
+              ring_buffer.is_lock_free() << std::endl;
+
+    auto [producer, consumer] = ring_buffer.split();
+
+    std::thread produce_thread(produce<Buffer::Producer>, std::ref(producer), std::ref(barrier));
+
+    std::vector<Task> result;
+    result.reserve(LoopCount);
+    std::thread consume_thread(
+            consume<Buffer::Consumer>,
+            std::ref(consumer), std::ref(barrier),
+            std::ref(result), LoopCount);
+
+    std::this_thread::sleep_for(std::chrono::seconds(2));
+    // begin
+    barrier.test_and_set();
+    barrier.notify_all();
+
+    produce_thread.join();
+    consume_thread.join();
+
+    // the single consumer must see the values with the same order of the single producer.
+    bool ordered = true;
+    for (uint64_t i = 0; i < result.size(); ++i) {
+        if (result[i].task_id != i) {
+            ordered = false;
+            break;
+        }
+    }
+    std::cout << "Result size: " << result.size() << ", in order: " << ordered << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,118 @@
+///
+/// @file  steal_policy_test.cpp
+/// @brief Test for sc::pool::StealPolicy and the thread pool over the policies.
+///
+
+#include "pool/steal_policy.hpp"
+
+#include <atomic>
+#include <set>
+#include <vector>
+
+#include "pool/thread_pool.hpp"
+#include "test_util.hpp"
+
+
+/// @brief A policy of the user, which visits the workers in the index order, from the worker 0.
+struct FirstWorkerSteal
+{
+    std::vector<size_t> victims(size_t index, const std::vector<int> &numa_nodes)
+    {
+        std::vector<size_t> victims;
+        for (size_t i = 0; i < numa_nodes.size(); ++i) {
+            if (i != index) {
+                victims.push_back(i);
+            }
+        }
+        return victims;
+    }
+
+    size_t start(size_t) noexcept
+    {
+        return 0;
+    }
+};
+
+static_assert(sc::pool::StealPolicy<sc::pool::RoundRobinSteal>);
+static_assert(sc::pool::StealPolicy<sc::pool::RandomSteal>);
+static_assert(sc::pool::StealPolicy<sc::pool::LocalitySteal>);
+static_assert(sc::pool::StealPolicy<FirstWorkerSteal>);
+static_assert(!sc::pool::StealPolicy<int>);
+
+void print(const char *name, const std::vector<size_t> &victims)
+{
+    std::cout << name << ":";
+    for (auto v: victims) {
+        std::cout << " " << v;
+    }
+    std::cout << std::endl;
+}
+
+template<typename Pool>
+void spawn_tree(Pool &pool, std::atomic<uint64_t> &counter, uint32_t depth)
+{
+    counter.fetch_add(1, std::memory_order_relaxed);
+    if (depth == 0) {
+        return;
+    }
+    for (int i = 0; i < 2; ++i) {
+        pool.spawn([&pool, &counter, depth] { spawn_tree(pool, counter, depth - 1); });
+    }
+}
+
+template<sc::pool::StealPolicy Policy>
+bool run_tree(const std::vector<int> &nodes, const Policy &policy = Policy())
+{
+    using Pool = sc::pool::BasicThreadPool<sc::deque::Worker<sc::pool::JobPtr>,
+            sc::mpmc::ArrayListQueue<sc::pool::JobPtr>, Policy>;
+    std::atomic<uint64_t> counter{0};
+    {
+        Pool pool(4, nodes, true, policy);
+        for (int i = 0; i < 4; ++i) {
+            pool.spawn([&pool, &counter] { spawn_tree(pool, counter, 10); });
+        }
+    }
+    return counter.load() == 4 * ((1u << 11) - 1);
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        std::vector<int> nodes = {0, 0, 0, 0, 1, 1, 1, 1};
+        sc::pool::RoundRobinSteal round_robin;
+        print("RoundRobin 5", round_robin.victims(5, nodes));
+
+        // The same node first, the nearer cores first on it, and the ring order by the same distance.
+        sc::pool::LocalitySteal locality({0, 1, 2, 3, 32, 33, 34, 35});
+        print("Locality 1", locality.victims(1, nodes));
+        print("Locality 6", locality.victims(6, nodes));
+        sc::pool::LocalitySteal no_cores;
+        print("Locality 2 without the cores", no_cores.victims(2, nodes));
+        print("Locality 2 without the nodes", no_cores.victims(2, std::vector<int>(8, sc::numa::AnyNode)));
+
+        sc::pool::RandomSteal random;
+        auto victims = random.victims(3, nodes);
+        std::set<size_t> starts;
+        bool in_range = true;
+        for (int i = 0; i < 100; ++i) {
+            auto start = random.start(victims.size());
+            in_range = in_range && start < victims.size();
+            starts.insert(start);
+        }
+        print("Random 3", victims);
+        std::cout << "Random starts in range: " << in_range << ", varied: " << (starts.size() > 1) << std::endl;
+    }
+
+    {
+        std::vector<int> nodes = {0, 0, 1, 1};
+        std::cout << "Pools ran all jobs: " << run_tree<sc::pool::RoundRobinSteal>(nodes) << " "
+                  << run_tree<sc::pool::RandomSteal>(nodes) << " "
+                  << run_tree(nodes, sc::pool::LocalitySteal({0, 1, 32, 33})) << " "
+                  << run_tree<FirstWorkerSteal>({}) << std::endl;
+    }
+
+    std::cout << "hello world" << std::endl;
+    return 0;
+}
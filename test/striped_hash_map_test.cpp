@@ -0,0 +1,70 @@
+///
+/// @file  striped_hash_map_test.cpp
+/// @brief Test for sc::map::StripedHashMap.
+///
+
+#include "map/striped_hash_map.hpp"
+
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+constexpr uint64_t KeyCount = 1000;
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    sc::map::StripedHashMap<std::string, uint64_t> map(10);
+    std::cout << "Shard count: " << map.shard_count() << ", insert: " << map.insert("a", 1)
+              << ", insert again: " << map.insert("a", 2) << ", insert or assign: " << map.insert_or_assign("a", 3)
+              << ", value: " << *map.get("a") << ", missing: " << !map.get("b").has_value() << std::endl;
+    std::cout << "with_mut missing: " << map.with_mut("b", [](uint64_t &v) { ++v; }) << ", erase: "
+              << *map.erase("a") << ", erase again: " << !map.erase("a").has_value() << ", empty: " << map.empty()
+              << std::endl;
+
+    // All threads count the same keys concurrently.
+    sc::map::StripedHashMap<uint64_t, uint64_t> counts;
+    auto elapsed = run_threads(ThreadCount, [&counts](uint32_t) {
+        for (uint64_t n = 0; n < LoopCount; ++n) {
+            counts.upsert(n % KeyCount, [](uint64_t &v) { ++v; });
+        }
+    });
+    uint64_t total = 0;
+    bool even = true;
+    counts.for_each([&](uint64_t, uint64_t v) {
+        total += v;
+        even = even && v == ThreadCount * LoopCount / KeyCount;
+    });
+    std::cout << "Keys: " << counts.size() << ", expected: " << KeyCount << ", total: " << total << ", expected: "
+              << ThreadCount * LoopCount << ", evenly counted: " << even << ", time: " << elapsed << "ns"
+              << std::endl;
+
+    // The threads insert and erase their own keys while reading the others.
+    sc::map::StripedHashMap<uint64_t, uint64_t> owned;
+    std::atomic<uint64_t> bad_reads{0};
+    run_threads(ThreadCount, [&owned, &bad_reads](uint32_t i) {
+        for (uint64_t n = 0; n < LoopCount / 10; ++n) {
+            auto key = n * ThreadCount + i;
+            owned.insert(key, key * 2);
+            owned.with(key, [&bad_reads, key](uint64_t v) {
+                if (v != key * 2) {
+                    bad_reads.fetch_add(1, std::memory_order_relaxed);
+                }
+            });
+            if (n % 2 == 0) {
+                owned.erase(key);
+            }
+        }
+    });
+    std::cout << "Owned keys: " << owned.size() << ", expected: " << ThreadCount * (LoopCount / 10 / 2)
+              << ", bad reads: " << bad_reads.load() << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
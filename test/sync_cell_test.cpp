@@ -0,0 +1,394 @@
+///
+/// @file  sync_cell_test.cpp
+/// @brief Test for sc::SyncCell.
+///
+
+#include "cell/sync_cell.hpp"
+
+#include <cmath>
+#include <limits>
+#include <stdexcept>
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+
+struct Stats
+{
+    uint64_t count;
+    uint64_t sum;
+    uint64_t max;
+};
+
+/// @brief Whether the arithmetic operations are available for the cell.
+template<typename Cell>
+constexpr bool HasFetchAdd = requires(Cell &c, typename Cell::value_type v) { c.fetch_add(v); };
+
+/// @brief Whether the unsynchronized reference is available for the cell.
+template<typename Cell>
+constexpr bool HasGetMut = requires(Cell &c) { c.get_mut(); };
+
+/// @brief Two fields and a padding, too big for the atomic struct.
+struct WidePair
+{
+    uint64_t first;
+    uint64_t second;
+    uint64_t padding[2];
+};
+
+/// @brief The writers update two fields together by @c update, the readers check they never tear.
+template<typename Cell>
+void run_atomic_struct(const char *name)
+{
+    using Pair = typename Cell::value_type;
+    constexpr uint64_t UpdateCount = LoopCount / 10;
+    Cell cell(Pair{0, 1});
+    std::atomic<bool> torn{false};
+    std::atomic<bool> returned_torn{false};
+    run_threads(ThreadCount, [&](uint32_t index) {
+        for (uint64_t n = 0; n < UpdateCount; ++n) {
+            if (index % 2 == 0) {
+                auto next = cell.update([](Pair &p) {
+                    ++p.first;
+                    ++p.second;
+                });
+                if (next.second != next.first + 1) {
+                    returned_torn.store(true);
+                }
+            } else if (auto p = cell.load(); p.second != p.first + 1) {
+                torn.store(true);
+            }
+        }
+    });
+    auto p = cell.load();
+    std::cout << "[atomic struct] " << name << ": first: " << p.first << ", second: " << p.second << ", expected: "
+              << ThreadCount / 2 * UpdateCount << " / " << ThreadCount / 2 * UpdateCount + 1 << ", torn: "
+              << torn.load() << ", returned torn: " << returned_torn.load() << std::endl;
+}
+
+template<typename Cell, typename Func>
+void run_concurrent_update(Cell &cell, Func update)
+{
+    run_threads(ThreadCount, [&cell, &update](uint32_t i) {
+        for (uint64_t n = 0; n < LoopCount; ++n) {
+            cell.fetch_update(std::memory_order_acq_rel, std::memory_order_acquire,
+                              [&update, i, n](const auto &v) { return update(v, i, n); });
+        }
+    });
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        sc::SyncCell<uint64_t> cell(0);
+        auto begin = get_current_time();
+        run_concurrent_update(cell, [](uint64_t v, uint32_t, uint64_t) { return std::optional(v + 1); });
+        std::cout << "[u64] value: " << cell.load() << ", expected: " << ThreadCount * LoopCount
+                  << ", time: " << get_current_time() - begin << "ns" << std::endl;
+
+        // the function rejects the update.
+        auto r = cell.fetch_update(std::memory_order_seq_cst, std::memory_order_seq_cst,
+                                   [](uint64_t) { return std::optional<uint64_t>(); });
+        std::cout << "[u64] rejected update: " << !r.has_value() << ", value: " << cell.load() << std::endl;
+    }
+
+    {
+        sc::SyncCell<Stats> cell(Stats{0, 0, 0});
+        auto begin = get_current_time();
+        run_concurrent_update(cell, [](Stats s, uint32_t, uint64_t n) {
+            s.count += 1;
+            s.sum += n;
+            s.max = std::max(s.max, n);
+            return std::optional(s);
+        });
+        auto s = cell.load();
+        std::cout << "[Stats] count: " << s.count << ", sum: " << s.sum << ", max: " << s.max
+                  << ", expected count: " << ThreadCount * LoopCount
+                  << ", expected sum: " << ThreadCount * (LoopCount * (LoopCount - 1) / 2)
+                  << ", time: " << get_current_time() - begin << "ns" << std::endl;
+    }
+
+    {
+        sc::SyncCell<std::string> cell;
+        auto begin = get_current_time();
+        run_concurrent_update(cell, [](const std::string &s, uint32_t i, uint64_t n) {
+            return n % 1000 == 0 ? std::optional(s + std::to_string(i)) : std::nullopt;
+        });
+        std::cout << "[string] size: " << cell.load().size() << ", expected: " << ThreadCount * LoopCount / 1000
+                  << ", time: " << get_current_time() - begin << "ns" << std::endl;
+
+        std::string expected = "a";
+        std::cout << "[string] compare exchange with a wrong value: " << cell.compare_exchange(expected, "b")
+                  << std::endl;
+    }
+
+    {
+        sc::SyncCell<uint64_t> cell(0);
+        run_threads(ThreadCount, [&cell] {
+            for (uint64_t n = 0; n < LoopCount; ++n) {
+                cell.with_mut([](uint64_t &v) { v += 1; });
+            }
+        });
+        std::cout << "[u64] with_mut value: " << cell.with([](uint64_t v) { return v; })
+                  << ", expected: " << ThreadCount * LoopCount << std::endl;
+    }
+
+    {
+        sc::SyncCell<std::string> cell;
+        run_threads(ThreadCount, [&cell] {
+            for (uint64_t n = 0; n < LoopCount / 100; ++n) {
+                cell.with_mut([](std::string &s) { s.push_back('a'); });
+            }
+        });
+        auto size = cell.with([](const std::string &s) { return s.size(); });
+        auto prev_size = cell.with_mut([](std::string &s) { return std::exchange(s, "b").size(); });
+        std::cout << "[string] with_mut size: " << size << ", expected: " << ThreadCount * (LoopCount / 100)
+                  << ", exchanged size: " << prev_size << ", value: " << cell.load() << std::endl;
+    }
+
+    {
+        sc::SyncCell<int64_t> cell(0);
+        sc::SyncCell<int64_t> max(INT64_MIN);
+        sc::SyncCell<int64_t> min(INT64_MAX);
+        run_threads(ThreadCount, [&] {
+            for (int64_t n = 0; n < static_cast<int64_t>(LoopCount); ++n) {
+                cell.fetch_add(2);
+                cell.fetch_sub(1);
+                max.fetch_max(n);
+                min.fetch_min(-n);
+            }
+        });
+        std::cout << "[i64] fetch_add value: " << cell.load() << ", expected: " << ThreadCount * LoopCount
+                  << ", max / min: " << max.load() << " / " << min.load() << ", expected: " << LoopCount - 1
+                  << " / -" << LoopCount - 1 << std::endl;
+
+        sc::SyncCell<uint8_t> wrapping(255);
+        auto prev = wrapping.fetch_add(2);
+        sc::SyncCell<double> real(0.5);
+        real.fetch_add(1.0);
+        auto nan_prev = real.fetch_max(std::numeric_limits<double>::quiet_NaN());
+        std::cout << "[u8] wrapped: " << +prev << " -> " << +wrapping.load() << ", [f64] fetch_add value: "
+                  << real.load() << ", NaN max ignored: " << (nan_prev == real.load()) << ", string arith: "
+                  << HasFetchAdd<sc::SyncCell<std::string>> << std::endl;
+    }
+
+    {
+        // The sums of the halves are exact, so the concurrent sum can be checked exactly.
+        sc::F64Cell sum(0.0);
+        sc::F32Cell nan(std::numeric_limits<float>::quiet_NaN());
+        run_threads(ThreadCount, [&] {
+            for (uint64_t n = 0; n < LoopCount / 10; ++n) {
+                sum.fetch_add(0.5);
+            }
+            nan.fetch_add(1.0f);
+        });
+        sum.fetch_sub(1.0);
+        std::cout << "[f64] sum: " << sum.load() << ", expected: " << ThreadCount * (LoopCount / 10) / 2 - 1
+                  << ", [f32] NaN stays NaN: " << std::isnan(nan.load()) << std::endl;
+    }
+
+    {
+        // The relaxed counter, and the data published by a release store and an acquire load.
+        sc::SyncCell<uint64_t> counter(0);
+        sc::SyncCell<uint64_t> data(0);
+        sc::SyncCell<bool> ready(false);
+        std::thread consumer([&] {
+            while (!ready.load_with(std::memory_order_acquire)) {
+                std::this_thread::yield();
+            }
+            std::cout << "[ordering] published data: " << data.load_with(std::memory_order_relaxed);
+        });
+        data.store_with(42, std::memory_order_relaxed);
+        ready.store_with(true, std::memory_order_release);
+        consumer.join();
+
+        run_threads(ThreadCount, [&counter] {
+            for (uint64_t n = 0; n < LoopCount; ++n) {
+                counter.fetch_add_with(1, std::memory_order_relaxed);
+            }
+        });
+        uint64_t expected = 0;
+        auto replaced = counter.compare_exchange_with(expected, 0, std::memory_order_acq_rel,
+                                                      std::memory_order_relaxed);
+        std::cout << ", relaxed counter: " << expected << ", expected: " << ThreadCount * LoopCount << ", replaced: "
+                  << replaced << std::endl;
+    }
+
+    {
+        // A small struct updated by one CAS, its fields are always consistent.
+        struct Range
+        {
+            uint32_t begin;
+            uint32_t end;
+        };
+        sc::SyncCell<Range> range(Range{0, 1});
+        std::atomic<bool> torn{false};
+        run_threads(ThreadCount, [&] {
+            for (uint64_t n = 0; n < LoopCount / 10; ++n) {
+                range.with_mut([](Range &r) {
+                    ++r.begin;
+                    ++r.end;
+                });
+                auto r = range.load();
+                if (r.end != r.begin + 1) {
+                    torn.store(true);
+                }
+            }
+        });
+        std::cout << "[struct] lock free: " << range.is_lock_free() << ", begin: " << range.get(&Range::begin)
+                  << ", end: " << range.get(&Range::end) << ", expected: " << ThreadCount * (LoopCount / 10)
+                  << " / " << ThreadCount * (LoopCount / 10) + 1 << ", torn: " << torn.load() << std::endl;
+
+        // Only the 'end' is compared, the 'begin' is replaced with whatever the last one has been.
+        std::atomic<uint64_t> claimed{0};
+        run_threads(ThreadCount, [&] {
+            for (uint64_t n = 0; n < LoopCount / 10; ++n) {
+                auto end = range.get(&Range::end);
+                auto next = Range{static_cast<uint32_t>(n), end + 1};
+                if (range.compare_exchange_if([end](const Range &r) { return r.end == end; }, next)) {
+                    claimed.fetch_add(1, std::memory_order_relaxed);
+                }
+            }
+        });
+        std::cout << "[struct] compare_exchange_if claimed: " << claimed.load() << ", end moved by: "
+                  << range.get(&Range::end) - (ThreadCount * (LoopCount / 10) + 1) << std::endl;
+    }
+
+    {
+        struct Pair32
+        {
+            uint32_t first;
+            uint32_t second;
+        };
+        struct Pair64
+        {
+            uint64_t first;
+            uint64_t second;
+        };
+        run_atomic_struct<sc::SyncCell<Pair32>>("8 bytes");
+        run_atomic_struct<sc::SyncCell<Pair64>>("16 bytes");
+        run_atomic_struct<sc::SyncCell<WidePair, sc::ForceLock<>>>("32 bytes, lock");
+        run_atomic_struct<sc::SyncCell<WidePair, sc::FlatCombining<>>>("32 bytes, flat combining");
+        static_assert(sc::SyncCell<Pair64>::IsAtomic && !sc::SyncCell<WidePair, sc::ForceLock<>>::IsAtomic);
+    }
+
+    {
+        // The lock-based backend is forced for a value which fits an atomic.
+        static_assert(sc::SyncCell<uint64_t>::IsAtomic && !sc::SyncCell<std::string>::IsAtomic);
+        static_assert(!sc::SyncCell<uint64_t, sc::ForceLock<>>::IsAtomic);
+        static_assert(!sc::SyncCell<uint64_t, sc::ForceLock<>>::IsAlwaysLockFree);
+        sc::SyncCell<uint64_t, sc::ForceLock<>> locked(0);
+        run_threads(ThreadCount, [&] {
+            for (uint64_t n = 0; n < LoopCount / 10; ++n) {
+                locked.fetch_add(1);
+            }
+        });
+        std::cout << "[force lock] lock free: " << locked.is_lock_free() << ", value: " << locked.load()
+                  << ", expected: " << ThreadCount * (LoopCount / 10) << std::endl;
+    }
+
+    {
+        // The updates of all threads are applied by the combiners, in the order of publication.
+        struct Range
+        {
+            uint64_t begin;
+            uint64_t end;
+        };
+        static_assert(!sc::SyncCell<Range, sc::FlatCombining<>>::IsAtomic);
+        sc::SyncCell<Range, sc::FlatCombining<>> range(Range{0, 1});
+        std::atomic<uint64_t> result_sum{0};
+        run_threads(ThreadCount, [&] {
+            for (uint64_t n = 0; n < LoopCount / 10; ++n) {
+                auto prev = range.with_mut([](Range &r) { return ++r.begin; });
+                range.fetch_update(std::memory_order_seq_cst, std::memory_order_seq_cst, [](const Range &r) {
+                    return std::optional<Range>(Range{r.begin, r.end + 1});
+                });
+                result_sum.fetch_add(prev, std::memory_order_relaxed);
+            }
+        });
+        bool rethrown = false;
+        try {
+            range.with_mut([](Range &) { throw std::runtime_error("update failed"); });
+        } catch (const std::runtime_error &) {
+            rethrown = true;
+        }
+        // Each update returns the new 'begin', so the results are 1 to the count of the updates.
+        auto r = range.load();
+        constexpr uint64_t UpdateCount = ThreadCount * (LoopCount / 10);
+        std::cout << "[flat combining] begin: " << r.begin << ", end: " << r.end << ", expected: " << UpdateCount
+                  << " / " << UpdateCount + 1 << ", results matched: "
+                  << (result_sum.load() == UpdateCount * (UpdateCount + 1) / 2) << ", rethrown: " << rethrown
+                  << std::endl;
+    }
+
+    // Each swapped-in value is returned by exactly one later swap, or stays in the cell.
+    {
+        sc::SyncCell<uint64_t> cell(0);
+        std::atomic<uint64_t> returned{0};
+        std::vector<std::thread> threads;
+        for (uint32_t t = 0; t < ThreadCount; ++t) {
+            threads.emplace_back([&, t] {
+                uint64_t sum = 0;
+                for (uint64_t i = 1; i <= LoopCount / 10; ++i) {
+                    sum += cell.swap(i * ThreadCount + t);
+                }
+                returned.fetch_add(sum, std::memory_order_relaxed);
+            });
+        }
+        for (auto &t: threads) {
+            t.join();
+        }
+        constexpr uint64_t N = ThreadCount * (LoopCount / 10);
+        std::cout << "[swap] returned and left: " << returned.load() + cell.load() << ", expected: "
+                  << (N + ThreadCount) * (N + ThreadCount - 1) / 2 - ThreadCount * (ThreadCount - 1) / 2
+                  << std::endl;
+
+        sc::SyncCell<std::string> string("a");
+        auto replaced = string.replace_with([](const std::string &s) { return s + "b"; });
+        auto taken = string.take();
+        // A struct without the operator==, compared by one field only.
+        struct Entry
+        {
+            std::string name;
+            uint64_t generation;
+        };
+        sc::SyncCell<Entry> entry(Entry{"old", 1});
+        auto stale = entry.compare_exchange_if([](const Entry &e) { return e.generation == 0; }, Entry{"stale", 1});
+        auto fresh = entry.compare_exchange_if([](const Entry &e) { return e.generation == 1; }, Entry{"new", 2});
+        std::cout << "[compare_exchange_if] stale: " << stale.has_value() << ", fresh previous: " << fresh->name
+                  << ", value: " << entry.load().name << std::endl;
+
+        bool left_empty = string.load().empty();
+        auto swapped = string.swap("c");
+        std::cout << "[string] replaced: " << replaced << ", taken: " << taken << ", left empty: " << left_empty
+                  << ", swapped out: \"" << swapped << "\", value: " << string.load() << std::endl;
+    }
+
+    {
+        sc::SyncCell<uint64_t> u64(1);
+        sc::SyncCell<std::string> string("a");
+        std::cout << "Into inner: " << std::move(u64).into_inner() << " / " << std::move(string).into_inner()
+                  << std::endl;
+    }
+
+    {
+        sc::SyncCell<std::string> string("a");
+        sc::SyncCell<uint64_t, sc::ForceLock<>> locked(1);
+        sc::SyncCell<std::string, sc::FlatCombining<>> combining("c");
+        string.get_mut() += "b";
+        ++locked.get_mut();
+        combining.get_mut().push_back('d');
+        static_assert(!HasGetMut<sc::SyncCell<uint64_t>> && HasGetMut<sc::SyncCell<std::string>>);
+        std::cout << "Get mut: " << string.load() << " / " << locked.load() << " / " << combining.load() << std::endl;
+    }
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,80 @@
+///
+/// @file  tagged_ptr_cell_test.cpp
+/// @brief Test for sc::TaggedPtrCell.
+///
+
+#include "cell/tagged_ptr_cell.hpp"
+
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+
+void run_aba()
+{
+    int a = 1;
+    int b = 2;
+    sc::TaggedPtrCell<int> cell(&a);
+
+    // The stale snapshot which is taken before the pointer changes A -> B -> A.
+    auto stale = cell.load();
+    cell.store(&b);
+    cell.store(&a);
+
+    auto current = cell.load();
+    std::cout << "ABA: pointer is same: " << (current.ptr == stale.ptr) << ", tag: " << stale.tag << " -> "
+              << current.tag << std::endl;
+
+    auto expected = stale;
+    auto replaced = cell.compare_exchange(expected, &b);
+    std::cout << "CAS with the stale value: " << replaced << ", reloaded: " << (expected == current) << std::endl;
+    replaced = cell.compare_exchange(expected, &b);
+    std::cout << "CAS with the reloaded value: " << replaced << ", value: " << *cell.load().ptr
+              << ", tag: " << cell.load().tag << std::endl;
+}
+
+void run_concurrent()
+{
+    std::vector<int> values(ThreadCount);
+    sc::TaggedPtrCell<int> cell;
+    std::atomic<uint64_t> success{0};
+
+    auto begin = get_current_time();
+    std::vector<std::thread> threads;
+    threads.reserve(ThreadCount);
+    for (uint32_t i = 0; i < ThreadCount; ++i) {
+        threads.emplace_back([&, i] {
+            uint64_t n = 0;
+            auto expected = cell.load();
+            for (uint64_t k = 0; k < LoopCount / 10; ++k) {
+                if (cell.compare_exchange(expected, &values[i])) {
+                    ++n;
+                    expected = {&values[i], expected.tag + 1};
+                }
+            }
+            success.fetch_add(n, std::memory_order_relaxed);
+        });
+    }
+    for (auto &t: threads) {
+        t.join();
+    }
+
+    std::cout << "Concurrent CAS succeeded: " << success.load() << ", tag: " << cell.load().tag
+              << ", time: " << get_current_time() - begin << "ns" << std::endl;
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+    std::cout << "TaggedPtrCell is lock free: " << sc::TaggedPtrCell<int>::is_lock_free() << std::endl;
+
+    run_aba();
+    run_concurrent();
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,66 @@
+///
+/// @file  take_cell_test.cpp
+/// @brief Test for sc::TakeCell.
+///
+
+#include "cell/take_cell.hpp"
+
+#include <atomic>
+#include <memory>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        sc::TakeCell<std::unique_ptr<int>> cell(std::make_unique<int>(7));
+        std::cout << "Has value: " << cell.has_value() << std::endl;
+        auto first = cell.take();
+        auto second = cell.take();
+        std::cout << "First take: " << **first << ", second take: " << second.has_value()
+                  << ", taken: " << cell.is_taken() << ", refilled: " << cell.try_put(std::make_unique<int>(8))
+                  << std::endl;
+    }
+
+    // Exactly one thread takes each value, and exactly one put fills each cell.
+    {
+        constexpr uint64_t Rounds = LoopCount / 1000;
+        std::atomic<uint64_t> takes{0};
+        std::atomic<uint64_t> puts{0};
+        std::atomic<uint64_t> sum{0};
+        for (uint64_t round = 0; round < Rounds; ++round) {
+            sc::TakeCell<std::unique_ptr<uint64_t>> cell;
+            std::vector<std::thread> threads;
+            for (uint32_t t = 0; t < ThreadCount; ++t) {
+                threads.emplace_back([&, t] {
+                    if (t % 2 == 0 && cell.try_put(std::make_unique<uint64_t>(round))) {
+                        puts.fetch_add(1, std::memory_order_relaxed);
+                    }
+                    while (!cell.is_taken()) {
+                        if (auto value = cell.take()) {
+                            takes.fetch_add(1, std::memory_order_relaxed);
+                            sum.fetch_add(**value, std::memory_order_relaxed);
+                        }
+                        std::this_thread::yield();
+                    }
+                });
+            }
+            for (auto &t: threads) {
+                t.join();
+            }
+        }
+        std::cout << "Puts: " << puts.load() << ", takes: " << takes.load() << ", expected: " << Rounds
+                  << ", sum: " << sum.load() << ", expected: " << Rounds * (Rounds - 1) / 2 << std::endl;
+    }
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
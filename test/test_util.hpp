@@ -6,10 +6,14 @@
 #ifndef SYNC_CELL_TEST_UTIL_HPP
 #define SYNC_CELL_TEST_UTIL_HPP
 
+#include <atomic>
 #include <chrono>
 #include <cstdint>
 #include <iostream>
 #include <mutex>
+#include <thread>
+#include <type_traits>
+#include <vector>
 
 
 constexpr uint64_t LoopCount = 10'000'000;
@@ -38,4 +42,39 @@ void sync_io(Func f)
     f();
 }
 
+/// @brief Runs 'f' on 'count' threads started together, called with the index of the thread if it
+/// takes one. No thread exits before all of them have finished 'f', so the running threads have
+/// distinct ids.
+/// @return The time in nanoseconds from the start of the threads to their join.
+template<typename F>
+int64_t run_threads(uint32_t count, F f)
+{
+    std::atomic_flag barrier = ATOMIC_FLAG_INIT;
+    std::atomic<uint32_t> finished{0};
+    std::vector<std::thread> threads;
+    threads.reserve(count);
+    for (uint32_t i = 0; i < count; ++i) {
+        threads.emplace_back([&barrier, &finished, &f, count, i] {
+            barrier.wait(false);
+            if constexpr(std::is_invocable_v<F &, uint32_t>) {
+                f(i);
+            } else {
+                f();
+            }
+            finished.fetch_add(1, std::memory_order_relaxed);
+            while (finished.load(std::memory_order_relaxed) < count) {
+                std::this_thread::yield();
+            }
+        });
+    }
+
+    auto begin = get_current_time();
+    barrier.test_and_set();
+    barrier.notify_all();
+    for (auto &t: threads) {
+        t.join();
+    }
+    return get_current_time() - begin;
+}
+
 #endif //SYNC_CELL_TEST_UTIL_HPP
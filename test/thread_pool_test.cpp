@@ -0,0 +1,247 @@
+///
+/// @file  thread_pool_test.cpp
+/// @brief Test for sc::pool::ThreadPool.
+///
+
+#include "pool/thread_pool.hpp"
+
+#include <chrono>
+#include <mutex>
+#include <thread>
+#include <vector>
+
+#include "queue/mpmc_list_queue.hpp"
+#include "test_util.hpp"
+
+
+constexpr uint32_t SpawnerCount = 4;
+constexpr uint64_t SpawnCount = LoopCount / 100;
+
+/// @brief Spawns a binary tree of jobs from a running job, each node counts itself.
+void spawn_tree(sc::pool::ThreadPool &pool, std::atomic<uint64_t> &counter, uint32_t depth)
+{
+    counter.fetch_add(1, std::memory_order_relaxed);
+    if (depth == 0) {
+        return;
+    }
+    for (int i = 0; i < 2; ++i) {
+        pool.spawn([&pool, &counter, depth] { spawn_tree(pool, counter, depth - 1); });
+    }
+}
+
+/// @brief Spawns a chain of jobs, each one spawns the next as its last action.
+template<typename Pool, typename F>
+void spawn_chain(Pool &pool, F &record, int id)
+{
+    pool.spawn([&pool, &record, id] {
+        record(id);
+        if (id < 106) {
+            spawn_chain(pool, record, id + 1);
+        }
+    });
+}
+
+/// @brief Sums the range by splitting it in the scoped jobs, which borrow the range.
+uint64_t scoped_sum(sc::pool::ThreadPool &pool, const uint64_t *first, size_t count)
+{
+    if (count <= 1024) {
+        uint64_t sum = 0;
+        for (size_t i = 0; i < count; ++i) {
+            sum += first[i];
+        }
+        return sum;
+    }
+    uint64_t left = 0;
+    uint64_t right = 0;
+    pool.scope([&](sc::pool::Scope &s) {
+        s.spawn([&] { left = scoped_sum(pool, first, count / 2); });
+        s.spawn([&] { right = scoped_sum(pool, first + count / 2, count - count / 2); });
+    });
+    return left + right;
+}
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        // The graceful shutdown runs all jobs spawned before it.
+        sc::pool::ThreadPool pool(2);
+        std::atomic<uint32_t> done{0};
+        for (int i = 0; i < 20; ++i) {
+            pool.spawn([&done] {
+                std::this_thread::sleep_for(std::chrono::milliseconds(1));
+                done.fetch_add(1);
+            });
+        }
+        pool.shutdown();
+        auto rejected = !pool.spawn([&done] { done.fetch_add(100); });
+        pool.join();
+        std::cout << "Threads: " << pool.thread_count() << ", shutdown: " << pool.is_shutdown() << ", rejected: "
+                  << rejected << ", done: " << done.load() << ", expected: 20" << std::endl;
+    }
+
+    {
+        // The nested jobs are pushed to the worker deques and stolen by the idle workers, they are
+        // still accepted while shutting down.
+        std::atomic<uint64_t> counter{0};
+        {
+            sc::pool::ThreadPool pool(4);
+            pool.spawn([&pool, &counter] { spawn_tree(pool, counter, 12); });
+        }
+        std::cout << "Tree jobs: " << counter.load() << ", expected: " << (1u << 13) - 1 << std::endl;
+    }
+
+    {
+        // The scoped jobs borrow the stack data, and the nested scopes are waited by the workers,
+        // which run the other jobs meanwhile.
+        sc::pool::ThreadPool pool(4);
+        std::vector<uint64_t> values(SpawnCount);
+        for (uint64_t i = 0; i < SpawnCount; ++i) {
+            values[i] = i;
+        }
+        uint64_t sum = 0;
+        auto spawned = pool.scope([&](sc::pool::Scope &s) {
+            s.spawn([&] { sum = scoped_sum(pool, values.data(), values.size()); });
+            return 1;
+        });
+        std::atomic<uint32_t> nested{0};
+        pool.scope([&nested](sc::pool::Scope &s) {
+            s.spawn([&nested](sc::pool::Scope &inner) {
+                nested.fetch_add(1);
+                inner.spawn([&nested] { nested.fetch_add(1); });
+            });
+        });
+        pool.shutdown();
+        // The scope still completes the jobs rejected after shutdown on the calling thread.
+        uint32_t inline_done = 0;
+        pool.scope([&inline_done](sc::pool::Scope &s) { s.spawn([&inline_done] { ++inline_done; }); });
+        std::cout << "Scoped sum matched: " << (sum == SpawnCount * (SpawnCount - 1) / 2) << ", returned: " << spawned
+                  << ", nested: "
+                  << nested.load() << ", after shutdown: " << inline_done << std::endl;
+    }
+
+    {
+        // The job spawned last runs next from the LIFO slot, before the ones in the FIFO queue, and
+        // the slot yields to the queue after a few runs in a row.
+        using FifoPool = sc::pool::BasicThreadPool<sc::mpmc::LinkedListQueue<sc::pool::JobPtr>>;
+        for (bool lifo_slot: {true, false}) {
+            std::mutex mtx;
+            std::vector<int> order;
+            auto record = [&](int id) {
+                std::lock_guard guard(mtx);
+                order.push_back(id);
+            };
+            FifoPool pool(1, {}, lifo_slot);
+            pool.spawn([&] {
+                for (int i = 1; i <= 3; ++i) {
+                    pool.spawn([&record, i] { record(i); });
+                }
+            });
+            pool.spawn([&] {
+                pool.spawn([&record] { record(0); });
+                spawn_chain(pool, record, 101);
+            });
+            pool.join();
+            std::cout << "LIFO slot " << lifo_slot << ", order:";
+            for (auto id: order) {
+                std::cout << " " << id;
+            }
+            std::cout << std::endl;
+        }
+
+        std::atomic<uint64_t> counter{0};
+        {
+            sc::pool::ThreadPool pool(4, {}, false);
+            pool.spawn([&pool, &counter] { spawn_tree(pool, counter, 12); });
+        }
+        std::cout << "Tree jobs without the LIFO slot: " << counter.load() << ", expected: " << (1u << 13) - 1
+                  << std::endl;
+    }
+
+    {
+        // The draining shutdown runs all jobs, the aborting one drops the jobs not started.
+        sc::pool::ThreadPool drained(2);
+        for (int i = 0; i < 20; ++i) {
+            drained.spawn([] { std::this_thread::sleep_for(std::chrono::microseconds(100)); });
+        }
+        auto drain = drained.shutdown(sc::pool::Grace::Drain);
+
+        sc::pool::ThreadPool aborted(1);
+        std::atomic<bool> started{false};
+        aborted.spawn([&] {
+            started.store(true);
+            while (!aborted.is_shutdown()) {
+                std::this_thread::yield();
+            }
+        });
+        while (!started.load()) {
+            std::this_thread::yield();
+        }
+        for (int i = 0; i < 50; ++i) {
+            aborted.spawn([] { });
+        }
+        auto abort = aborted.shutdown(sc::pool::Grace::Abort);
+        std::cout << "Drain: completed " << drain.completed << ", dropped " << drain.dropped << "; abort: completed "
+                  << abort.completed << ", dropped " << abort.dropped << std::endl;
+    }
+
+    {
+        // The drain stops at the deadline, and the dropped jobs of a scope still complete it.
+        sc::pool::ThreadPool pool(1);
+        std::atomic<bool> started{false};
+        std::atomic<bool> spawned{false};
+        sc::pool::ShutdownReport report{};
+        std::thread closer([&] {
+            while (!spawned.load()) {
+                std::this_thread::yield();
+            }
+            report = pool.shutdown_for(sc::pool::Grace::Drain, std::chrono::milliseconds(10));
+        });
+        std::atomic<uint32_t> ran{0};
+        pool.scope([&](sc::pool::Scope &s) {
+            s.spawn([&] {
+                started.store(true);
+                std::this_thread::sleep_for(std::chrono::milliseconds(100));
+                ran.fetch_add(1);
+            });
+            while (!started.load()) {
+                std::this_thread::yield();
+            }
+            for (int i = 0; i < 20; ++i) {
+                s.spawn([&ran] { ran.fetch_add(1); });
+            }
+            spawned.store(true);
+        });
+        closer.join();
+        std::cout << "Deadline: scope returned, ran " << ran.load() << ", completed " << report.completed
+                  << ", dropped " << report.dropped << std::endl;
+    }
+
+    // Many threads spawn the small jobs concurrently.
+    std::atomic<uint64_t> sum{0};
+    auto begin = get_current_time();
+    {
+        sc::pool::ThreadPool pool(4);
+        std::vector<std::thread> spawners;
+        for (uint32_t i = 0; i < SpawnerCount; ++i) {
+            spawners.emplace_back([&pool, &sum, i] {
+                for (uint64_t n = 0; n < SpawnCount; ++n) {
+                    auto value = n * SpawnerCount + i;
+                    pool.spawn([&sum, value] { sum.fetch_add(value, std::memory_order_relaxed); });
+                }
+            });
+        }
+        for (auto &t: spawners) {
+            t.join();
+        }
+        pool.join();
+    }
+    auto total = SpawnerCount * SpawnCount;
+    std::cout << "Spawned sum matched: " << (sum.load() == total * (total - 1) / 2) << ", time: "
+              << get_current_time() - begin << "ns" << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,94 @@
+///
+/// @file  thread_sharded_cell_test.cpp
+/// @brief Test for sc::ThreadShardedCell.
+///
+
+#include "cell/thread_sharded_cell.hpp"
+
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        // The plain values, only read after the threads are joined.
+        sc::ThreadShardedCell<uint64_t> cell;
+        std::cout << "Main thread has no value: " << (cell.get() == nullptr) << std::endl;
+        auto begin = get_current_time();
+        run_threads(ThreadCount, [&cell](uint32_t) {
+            for (uint64_t n = 0; n < LoopCount; ++n) {
+                ++cell.get_or_default();
+            }
+        });
+        auto elapsed = get_current_time() - begin;
+
+        size_t count = 0;
+        cell.for_each([&count](uint64_t) { ++count; });
+        auto sum = cell.fold(uint64_t{0}, [](uint64_t s, uint64_t v) { return s + v; });
+        std::cout << "Values: " << count << ", expected: " << ThreadCount << ", sum: " << sum << ", expected: "
+                  << ThreadCount * LoopCount << ", time: " << elapsed << "ns" << std::endl;
+
+        // The id of an exited thread is reused, with its value.
+        bool reused = false;
+        std::thread([&cell, &reused] { reused = cell.get() != nullptr; }).join();
+        std::cout << "New thread reuses a value: " << reused << std::endl;
+    }
+
+    {
+        // The atomic values are visited while the owners are adding.
+        sc::ThreadShardedCell<std::atomic<uint64_t>> cell;
+        auto total = [&cell] {
+            return cell.fold(uint64_t{0}, [](uint64_t s, const std::atomic<uint64_t> &v) {
+                return s + v.load(std::memory_order_relaxed);
+            });
+        };
+
+        std::atomic<bool> done{false};
+        bool monotonic = true;
+        std::thread visitor([&] {
+            uint64_t last = 0;
+            while (!done.load(std::memory_order_acquire)) {
+                auto now = total();
+                monotonic = monotonic && now >= last;
+                last = now;
+            }
+        });
+        run_threads(ThreadCount, [&cell](uint32_t) {
+            for (uint64_t n = 0; n < LoopCount; ++n) {
+                cell.get_or_init([] { return 0; }).fetch_add(1, std::memory_order_relaxed);
+            }
+        });
+        done.store(true, std::memory_order_release);
+        visitor.join();
+        std::cout << "Atomic sum: " << total() << ", expected: " << ThreadCount * LoopCount << ", monotonic: "
+                  << monotonic << std::endl;
+    }
+
+    {
+        // The per-thread free lists, drained by one thread after the workers are joined.
+        sc::ThreadShardedCell<std::vector<uint32_t>> free_lists;
+        run_threads(ThreadCount, [&free_lists](uint32_t i) {
+            for (uint32_t n = 0; n <= i; ++n) {
+                free_lists.get_or_default().push_back(i);
+            }
+        });
+        std::vector<uint32_t> drained;
+        free_lists.for_each([&drained](std::vector<uint32_t> &list) {
+            drained.insert(drained.end(), list.begin(), list.end());
+            list.clear();
+        });
+        std::cout << "Drained: " << drained.size() << ", expected: " << ThreadCount * (ThreadCount + 1) / 2
+                  << std::endl;
+    }
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,68 @@
+///
+/// @file  time_cell_test.cpp
+/// @brief Test for sc::DurationCell and sc::InstantCell.
+///
+
+#include "cell/time_cell.hpp"
+
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+constexpr uint64_t UpdateCount = LoopCount / 10;
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        sc::DurationCell<std::chrono::microseconds> cell(std::chrono::microseconds(5));
+        auto prev = cell.fetch_add(std::chrono::microseconds(10));
+        cell.fetch_sub(std::chrono::microseconds(3));
+        auto min_prev = cell.fetch_min(std::chrono::microseconds(2));
+        std::cout << "Duration is lock free: " << cell.is_lock_free() << ", previous: " << prev.count()
+                  << ", before min: " << min_prev.count() << ", value: " << cell.load().count()
+                  << ", expected: 5 / 12 / 2" << std::endl;
+    }
+
+    {
+        sc::InstantCell<> cell;
+        auto epoch = cell.load() == std::chrono::steady_clock::time_point();
+        auto stored = cell.store_now();
+        std::this_thread::sleep_for(std::chrono::milliseconds(2));
+        auto elapsed = cell.elapsed();
+        auto earlier = stored - std::chrono::seconds(1);
+        cell.fetch_max(earlier);
+        std::cout << "Instant is epoch: " << epoch << ", elapsed >= 2ms: " << (elapsed >= std::chrono::milliseconds(2))
+                  << ", earlier ignored: " << (cell.load() == stored) << ", later stored: "
+                  << (cell.fetch_max_now() == stored && cell.load() > stored) << std::endl;
+    }
+
+    // The threads record the max latency and the last finish time, as the latency bookkeeping of the
+    // queue tests does by hand.
+    sc::DurationCell<> max_latency;
+    sc::InstantCell<> last_finish;
+    std::vector<std::thread> threads;
+    auto begin = get_current_time();
+    for (uint32_t i = 0; i < ThreadCount; ++i) {
+        threads.emplace_back([&, i] {
+            for (uint64_t n = 0; n < UpdateCount; ++n) {
+                max_latency.fetch_max(std::chrono::nanoseconds(n * ThreadCount + i));
+            }
+            last_finish.fetch_max_now();
+        });
+    }
+    for (auto &t: threads) {
+        t.join();
+    }
+    std::cout << "Max latency: " << max_latency.load().count() << ", expected: " << ThreadCount * UpdateCount - 1
+              << ", last finish in the past: " << (last_finish.elapsed() >= std::chrono::nanoseconds(0))
+              << ", time: " << get_current_time() - begin << "ns" << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,104 @@
+///
+/// @file  tracing_test.cpp
+/// @brief Test for the tracing hooks enabled by SYNC_CELL_TRACING.
+///
+
+#define SYNC_CELL_TRACING
+
+#include "cell/once_sync_cell.hpp"
+#include "lock/spin_lock.hpp"
+#include "lock/ticket_lock.hpp"
+#include "queue/blocking_queue.hpp"
+#include "queue/mpmc_bounded_queue.hpp"
+
+#include <mutex>
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+struct Event
+{
+    bool enter;
+    sc::tracing::SpanKind kind;
+    std::string name;
+    const void *object;
+};
+
+std::mutex EventsMtx;
+std::vector<Event> Events;
+
+void sc::tracing::span_enter(SpanKind kind, const char *name, const void *object) noexcept
+{
+    std::lock_guard guard(EventsMtx);
+    Events.push_back({true, kind, name, object});
+}
+
+void sc::tracing::span_exit(SpanKind kind, const char *name, const void *object) noexcept
+{
+    std::lock_guard guard(EventsMtx);
+    Events.push_back({false, kind, name, object});
+}
+
+/// @brief Prints and clears the recorded events.
+void print_events(const char *title)
+{
+    std::lock_guard guard(EventsMtx);
+    std::cout << title << ": " << Events.size() << " events" << std::endl;
+    for (auto &e: Events) {
+        std::cout << "  " << (e.enter ? "enter " : "exit  ") << e.name << " kind: " << (int) e.kind << std::endl;
+    }
+    Events.clear();
+}
+
+template<typename Lock>
+void hold_and_wait(Lock &lock)
+{
+    std::thread waiter;
+    {
+        auto guard = lock.lock();
+        waiter = std::thread([&lock] { (void) lock.lock(); });
+        std::this_thread::sleep_for(std::chrono::milliseconds(50));
+    }
+    waiter.join();
+}
+
+int main()
+{
+    sc::lock::SpinLock<int> spin_lock(0);
+    *spin_lock.lock() += 1;
+    print_events("Uncontended SpinLock");
+
+    hold_and_wait(spin_lock);
+    print_events("Contended SpinLock");
+
+    sc::lock::TicketLock<int> ticket_lock(0);
+    hold_and_wait(ticket_lock);
+    print_events("Contended TicketLock");
+
+    sc::BlockingQueue<sc::mpmc::BoundedQueue<int>> queue(4);
+    std::thread consumer([&queue] { (void) queue.dequeue(); });
+    std::this_thread::sleep_for(std::chrono::milliseconds(100));
+    queue.enqueue(1);
+    consumer.join();
+    print_events("Blocked BlockingQueue");
+
+    sc::OnceSyncCell<int> once;
+    std::thread initializer([&once] {
+        once.get_or_init([] {
+            std::this_thread::sleep_for(std::chrono::milliseconds(100));
+            return 42;
+        });
+    });
+    std::this_thread::sleep_for(std::chrono::milliseconds(20));
+    auto value = once.get_or_init([] { return 0; });
+    initializer.join();
+    std::cout << "Once value: " << value << std::endl;
+    print_events("OnceSyncCell init and wait");
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
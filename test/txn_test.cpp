@@ -0,0 +1,111 @@
+///
+/// @file  txn_test.cpp
+/// @brief Test for sc::txn::atomically and sc::txn::TxCell.
+///
+
+#include "txn/txn.hpp"
+
+#include <algorithm>
+#include <atomic>
+#include <memory>
+#include <stdexcept>
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t AccountCount = 4;
+constexpr uint32_t ThreadCount = 4;
+constexpr int64_t InitialBalance = 1000;
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        sc::txn::TxCell<int64_t> a(1);
+        sc::txn::TxCell<std::string> b("x");
+        auto seen = sc::txn::atomically([&](sc::txn::Transaction &tx) {
+            tx.write(a, tx.read(a) + 1);
+            tx.update(b, [](const std::string &s) { return s + "y"; });
+            // A nested transaction joins the outer one, so it sees the buffered writes.
+            return sc::txn::atomically([&](sc::txn::Transaction &inner) { return inner.read(a); });
+        });
+        std::cout << "Read own write: " << seen << ", a: " << a.load() << ", b: " << b.load() << std::endl;
+
+        try {
+            sc::txn::atomically([&](sc::txn::Transaction &tx) {
+                tx.write(a, 100);
+                throw std::runtime_error("rolled back");
+            });
+        } catch (const std::runtime_error &e) {
+            std::cout << "Exception: " << e.what() << ", a after: " << a.load() << std::endl;
+        }
+
+        b.store("z");
+        std::cout << "Stored: " << b.load() << std::endl;
+    }
+
+    // The transfers keep the total balance, which every reading transaction sees.
+    {
+        std::vector<std::unique_ptr<sc::txn::TxCell<int64_t>>> cells;
+        for (uint32_t i = 0; i < AccountCount; ++i) {
+            cells.push_back(std::make_unique<sc::txn::TxCell<int64_t>>(InitialBalance));
+        }
+        std::atomic<bool> done{false};
+        std::atomic<uint64_t> bad_totals{0};
+        std::thread auditor([&] {
+            while (!done.load(std::memory_order_acquire)) {
+                auto total = sc::txn::atomically([&](sc::txn::Transaction &tx) {
+                    int64_t sum = 0;
+                    for (auto &cell: cells) {
+                        sum += tx.read(*cell);
+                    }
+                    return sum;
+                });
+                if (total != InitialBalance * AccountCount) {
+                    bad_totals.fetch_add(1, std::memory_order_relaxed);
+                }
+            }
+        });
+        std::vector<std::thread> threads;
+        for (uint32_t t = 0; t < ThreadCount; ++t) {
+            threads.emplace_back([&, t] {
+                for (uint64_t i = 0; i < LoopCount / 100; ++i) {
+                    auto &from = *cells[(t + i) % AccountCount];
+                    auto &to = *cells[(t + i * 3 + 1) % AccountCount];
+                    if (&from == &to) {
+                        continue;
+                    }
+                    sc::txn::atomically([&](sc::txn::Transaction &tx) {
+                        auto amount = std::min<int64_t>(tx.read(from), static_cast<int64_t>(i % 50));
+                        tx.write(from, tx.read(from) - amount);
+                        tx.write(to, tx.read(to) + amount);
+                    });
+                }
+            });
+        }
+        for (auto &t: threads) {
+            t.join();
+        }
+        done.store(true, std::memory_order_release);
+        auditor.join();
+
+        int64_t total = 0;
+        bool negative = false;
+        for (auto &cell: cells) {
+            auto balance = cell->load();
+            total += balance;
+            negative |= balance < 0;
+        }
+        std::cout << "Transfers: total: " << total << ", expected: " << InitialBalance * AccountCount
+                  << ", negative balance: " << negative << ", inconsistent audits: " << bad_totals.load()
+                  << std::endl;
+    }
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
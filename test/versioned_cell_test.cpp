@@ -0,0 +1,59 @@
+///
+/// @file  versioned_cell_test.cpp
+/// @brief Test for sc::VersionedCell.
+///
+
+#include "cell/versioned_cell.hpp"
+
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    {
+        sc::VersionedCell<std::string> config("a");
+        auto [value, version] = config.load();
+        std::cout << "Initial: " << value << ", version: " << version << std::endl;
+        std::cout << "Store: version: " << config.store("b") << std::endl;
+        std::cout << "Store if the old version: " << config.store_if_version(version, "stale")
+                  << ", store if the current version: " << config.store_if_version(1, "c") << std::endl;
+        auto latest = config.load();
+        std::cout << "Latest: " << latest.value << ", version: " << latest.version << ", version(): "
+                  << config.version() << std::endl;
+    }
+
+    // The optimistic increments by store_if_version are not lost, and each one takes a revision.
+    {
+        sc::VersionedCell<uint64_t> counter;
+        std::cout << "uint64_t cell is lock-free: " << counter.is_lock_free() << std::endl;
+        std::vector<std::thread> threads;
+        for (uint32_t t = 0; t < ThreadCount; ++t) {
+            threads.emplace_back([&] {
+                for (uint64_t i = 0; i < LoopCount / 100; ++i) {
+                    auto current = counter.load();
+                    while (!counter.store_if_version(current.version, current.value + 1)) {
+                        current = counter.load();
+                    }
+                }
+            });
+        }
+        for (auto &t: threads) {
+            t.join();
+        }
+        auto [value, version] = counter.load();
+        std::cout << "Counter: " << value << ", version: " << version << ", expected: "
+                  << ThreadCount * (LoopCount / 100) << std::endl;
+    }
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,71 @@
+///
+/// @file  wait_group_test.cpp
+/// @brief Test for sc::sync::WaitGroup and sc::sync::CountdownLatch.
+///
+
+#include "sync/wait_group.hpp"
+
+#include <thread>
+#include <vector>
+
+#include "test_util.hpp"
+
+
+constexpr uint32_t WorkerCount = 8;
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    // The workers are detached, the coordinator only waits for the group.
+    sc::sync::WaitGroup group;
+    std::vector<uint64_t> sums(WorkerCount, 0);
+    auto begin = get_current_time();
+    for (uint32_t round = 0; round < 2; ++round) {
+        group.add(WorkerCount);
+        for (uint32_t i = 0; i < WorkerCount; ++i) {
+            std::thread([&group, &sums, i] {
+                for (uint64_t v = 0; v < LoopCount; ++v) {
+                    sums[i] += v;
+                }
+                group.done();
+            }).detach();
+        }
+        group.wait();
+    }
+
+    uint64_t sum = 0;
+    for (auto s: sums) {
+        sum += s;
+    }
+    std::cout << "WaitGroup sum: " << sum << ", expected: " << WorkerCount * LoopCount * (LoopCount - 1)
+              << ", count after wait: " << group.count() << ", time: " << get_current_time() - begin
+              << "ns" << std::endl;
+
+    // All workers start at the same time once the latch is opened by the coordinator, and the
+    // coordinator waits for them by another latch.
+    sc::sync::CountdownLatch start(1);
+    sc::sync::CountdownLatch finished(WorkerCount);
+    std::atomic<uint32_t> started{0};
+    for (uint32_t i = 0; i < WorkerCount; ++i) {
+        std::thread([&start, &finished, &started] {
+            start.wait();
+            started.fetch_add(1, std::memory_order_relaxed);
+            finished.count_down();
+        }).detach();
+    }
+
+    std::this_thread::sleep_for(std::chrono::milliseconds(100));
+    auto started_before_open = started.load();
+    auto opened_before = start.try_wait();
+    start.count_down();
+    finished.wait();
+
+    std::cout << "Latch opened before count down: " << opened_before << ", started before open: "
+              << started_before_open << ", started after wait: " << started.load() << ", expected: "
+              << WorkerCount << ", opened: " << finished.try_wait() << std::endl;
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
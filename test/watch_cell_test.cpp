@@ -0,0 +1,107 @@
+///
+/// @file  watch_cell_test.cpp
+/// @brief Test for sc::WatchCell.
+///
+
+#include "cell/watch_cell.hpp"
+
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "coro_util.hpp"
+#include "test_util.hpp"
+
+
+constexpr uint32_t ThreadCount = 4;
+constexpr uint64_t SendCount = LoopCount / 1000;
+
+struct Config
+{
+    uint64_t version;
+    /// @brief Always 'version * 2', to check a borrowed value is never torn.
+    uint64_t check;
+};
+
+#if __cpp_impl_coroutine
+
+DetachedTask async_watch(sc::WatchCell<std::string> &cell, std::vector<std::string> &seen)
+{
+    auto sub = cell.subscribe();
+    for (int i = 0; i < 2; ++i) {
+        co_await sub.changed();
+        seen.push_back(*sub.borrow());
+    }
+}
+
+void run_async()
+{
+    sc::WatchCell<std::string> cell("initial");
+    std::vector<std::string> seen;
+    async_watch(cell, seen);
+    auto suspended = seen.empty();
+    cell.send("first");
+    cell.send("second");
+    std::cout << "Async suspended: " << suspended << ", seen:";
+    for (auto &s: seen) {
+        std::cout << " " << s;
+    }
+    std::cout << std::endl;
+}
+
+#else
+
+void run_async()
+{
+    std::cout << "Skip the async test: built without cpp coroutine support." << std::endl;
+}
+
+#endif
+
+int main()
+{
+    std::cout << std::boolalpha;
+
+    sc::WatchCell<std::string> names("a");
+    auto sub = names.subscribe();
+    std::cout << "Initial: " << *sub.borrow() << ", changed: " << sub.has_changed() << ", wait timeout: "
+              << !sub.wait_changed_timeout(std::chrono::milliseconds(10)) << std::endl;
+    names.send("b");
+    names.send_modify([](std::string &s) { s += "c"; });
+    std::cout << "Changed: " << sub.has_changed() << ", value: " << *sub.borrow_and_update() << ", changed after: "
+              << sub.has_changed() << ", version: " << names.version() << std::endl;
+
+    // The subscribers wait for the changes until the last value, and never see a torn or older value.
+    sc::WatchCell<Config> config(Config{0, 0});
+    std::atomic<uint64_t> bad_reads{0};
+    std::vector<std::thread> threads;
+    threads.reserve(ThreadCount);
+    for (uint32_t i = 0; i < ThreadCount; ++i) {
+        threads.emplace_back([&config, &bad_reads, sub = config.subscribe()]() mutable {
+            uint64_t last = 0;
+            while (last < SendCount) {
+                sub.wait_changed();
+                auto value = *sub.borrow();
+                if (value.check != value.version * 2 || value.version < last) {
+                    bad_reads.fetch_add(1, std::memory_order_relaxed);
+                }
+                last = value.version;
+            }
+        });
+    }
+    auto begin = get_current_time();
+    for (uint64_t n = 1; n <= SendCount; ++n) {
+        config.send(Config{n, n * 2});
+    }
+    for (auto &t: threads) {
+        t.join();
+    }
+    std::cout << "Subscribers done: " << ThreadCount << ", bad reads: " << bad_reads.load() << ", time: "
+              << get_current_time() - begin << "ns" << std::endl;
+
+    run_async();
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
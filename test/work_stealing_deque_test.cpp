@@ -0,0 +1,221 @@
+///
+/// @file  work_stealing_deque_test.cpp
+/// @brief Test for sc::deque::Worker and sc::deque::Stealer.
+///
+
+#include "deque/work_stealing_deque.hpp"
+#include "hazard/hazard.hpp"
+
+#include "queue_thread_run.hpp"
+
+
+constexpr uint32_t StealerCount = 2;
+
+template<typename Stealer>
+void steal(
+        const Stealer &stealer,
+        std::atomic_flag &barrier,
+        std::atomic<uint64_t> &counter,
+        std::vector<Task> &result)
+{
+    auto tid = std::this_thread::get_id();
+
+    sync_io([&tid] { std::cout << "[Steal] Thread [" << tid << "] waiting..." << std::endl; });
+    barrier.wait(false);
+
+    auto begin = get_current_time();
+
+    auto c_tid = (int64_t)*(ThreadIdType *)(&tid);     // hack
+    while (counter.load(std::memory_order_acquire) < LoopCount) {
+        auto task = stealer.steal().success();
+        if (task) {
+            counter.fetch_add(1, std::memory_order_acq_rel);
+            task->consume_tid = c_tid;
+            task->out_time = get_current_time();
+            result.push_back(*task);
+        }
+    }
+
+    auto end = get_current_time();
+    auto elapsed = end - begin;
+    sync_io([&tid, elapsed, &result] {
+        std::cout << "[Steal] Thread [" << tid << "] finished. count time: "
+                  << elapsed << "ns, result size: " << result.size() << std::endl;
+    });
+}
+
+template<typename Reclaimer>
+void run(sc::deque::Flavor flavor)
+{
+    sc::deque::Worker<Task, Reclaimer> worker(flavor);
+    std::atomic_flag barrier = ATOMIC_FLAG_INIT;
+    std::atomic<uint64_t> counter{0};
+
+    std::vector<std::thread> steal_threads;
+    steal_threads.reserve(StealerCount);
+    std::vector<std::vector<Task>> result(StealerCount + 1);
+    for (uint32_t i = 0; i < StealerCount; ++i) {
+        steal_threads.emplace_back(
+                steal<sc::deque::Stealer<Task, Reclaimer>>,
+                worker.stealer(), std::ref(barrier),
+                std::ref(counter), std::ref(result[i]));
+    }
+
+    std::this_thread::sleep_for(std::chrono::seconds(1));
+    // begin
+    barrier.test_and_set();
+    barrier.notify_all();
+
+    // the owner thread pushes all tasks, and pops one task every 4 push.
+    auto &local = result[StealerCount];
+    Task task{};
+    for (uint64_t i = 0; i < LoopCount; ++i) {
+        task.task_id = i;
+        task.in_time = get_current_time();
+        worker.push(task);
+
+        if (i % 4 == 0) {
+            if (auto t = worker.pop(); t) {
+                counter.fetch_add(1, std::memory_order_acq_rel);
+                local.push_back(*t);
+            }
+        }
+    }
+    while (auto t = worker.pop()) {
+        counter.fetch_add(1, std::memory_order_acq_rel);
+        local.push_back(*t);
+    }
+
+    for (auto &t: steal_threads) {
+        t.join();
+    }
+
+    // every task must be taken exactly once.
+    std::vector<uint32_t> taken(LoopCount, 0);
+    for (auto &r: result) {
+        for (auto &t: r) {
+            ++taken[t.task_id];
+        }
+    }
+    bool exactly_once = true;
+    for (auto c: taken) {
+        exactly_once = exactly_once && c == 1;
+    }
+    std::cout << "Owner popped: " << local.size() << ", every task taken exactly once: "
+              << std::boolalpha << exactly_once << std::endl;
+}
+
+/// @brief The thieves steal the batches into their own workers, and pop them locally.
+template<typename Reclaimer>
+void run_batch(sc::deque::Flavor flavor)
+{
+    constexpr uint64_t Count = LoopCount / 10;
+    sc::deque::Worker<uint64_t, Reclaimer> worker(flavor);
+    std::atomic<uint64_t> counter{0};
+    std::vector<std::vector<uint64_t>> result(StealerCount + 1);
+    std::vector<uint64_t> batches(StealerCount, 0);
+
+    std::vector<std::thread> steal_threads;
+    for (uint32_t i = 0; i < StealerCount; ++i) {
+        steal_threads.emplace_back([&, i, stealer = worker.stealer()] {
+            sc::deque::Worker<uint64_t, Reclaimer> local(flavor);
+            while (counter.load(std::memory_order_acquire) < Count) {
+                auto steal = stealer.steal_batch_and_pop(local);
+                if (!steal.is_success()) {
+                    continue;
+                }
+                ++batches[i];
+                result[i].push_back(*std::move(steal).success());
+                counter.fetch_add(1, std::memory_order_acq_rel);
+                while (auto t = local.pop()) {
+                    result[i].push_back(*t);
+                    counter.fetch_add(1, std::memory_order_acq_rel);
+                }
+            }
+        });
+    }
+
+    auto &owned = result[StealerCount];
+    for (uint64_t i = 0; i < Count; ++i) {
+        worker.push(i);
+        if (i % 8 == 0) {
+            if (auto t = worker.pop(); t) {
+                owned.push_back(*t);
+                counter.fetch_add(1, std::memory_order_acq_rel);
+            }
+        }
+    }
+    while (auto t = worker.pop()) {
+        owned.push_back(*t);
+        counter.fetch_add(1, std::memory_order_acq_rel);
+    }
+    for (auto &t: steal_threads) {
+        t.join();
+    }
+
+    std::vector<uint32_t> taken(Count, 0);
+    uint64_t stolen = 0;
+    for (uint32_t i = 0; i <= StealerCount; ++i) {
+        stolen += i < StealerCount ? result[i].size() : 0;
+        for (auto id: result[i]) {
+            ++taken[id];
+        }
+    }
+    bool exactly_once = true;
+    for (auto c: taken) {
+        exactly_once = exactly_once && c == 1;
+    }
+    uint64_t batch_count = 0;
+    for (auto c: batches) {
+        batch_count += c;
+    }
+    std::cout << "Batch steals: every task taken exactly once: " << std::boolalpha << exactly_once
+              << ", tasks per batch >= 1: " << (batch_count == 0 || stolen >= batch_count) << std::endl;
+}
+
+void batch_order()
+{
+    for (auto source: {sc::deque::Flavor::Fifo, sc::deque::Flavor::Lifo}) {
+        for (auto target: {sc::deque::Flavor::Fifo, sc::deque::Flavor::Lifo}) {
+            sc::deque::Worker<int> worker(source);
+            sc::deque::Worker<int> dest(target);
+            for (int i = 0; i < 10; ++i) {
+                worker.push(i);
+            }
+            auto stealer = worker.stealer();
+            auto count = stealer.steal_batch(dest, 3).success();
+            auto first = stealer.steal_batch_and_pop(dest).success();
+            std::cout << (source == sc::deque::Flavor::Fifo ? "Fifo" : "Lifo") << " -> "
+                      << (target == sc::deque::Flavor::Fifo ? "Fifo" : "Lifo") << ": batch of " << *count
+                      << ", then popped " << *first << ", dest:";
+            while (auto t = dest.pop()) {
+                std::cout << " " << *t;
+            }
+            std::cout << ", left " << worker.len() << ", empty batch: "
+                      << sc::deque::Worker<int>().stealer().steal_batch(dest).is_empty() << std::endl;
+        }
+    }
+}
+
+int main()
+{
+    std::cout << "Batch order:" << std::endl;
+    batch_order();
+    std::cout << "Run Lifo worker with the batch thieves:" << std::endl;
+    run_batch<sc::epoch::Reclaimer>(sc::deque::Flavor::Lifo);
+    std::cout << "Run Fifo worker with the batch thieves:" << std::endl;
+    run_batch<sc::epoch::Reclaimer>(sc::deque::Flavor::Fifo);
+
+    std::cout << "Run Lifo worker:" << std::endl;
+    run<sc::epoch::Reclaimer>(sc::deque::Flavor::Lifo);
+    std::cout << "Run Fifo worker:" << std::endl;
+    run<sc::epoch::Reclaimer>(sc::deque::Flavor::Fifo);
+    std::cout << "Run Lifo worker with the hazard pointers:" << std::endl;
+    run<sc::hazard::Reclaimer>(sc::deque::Flavor::Lifo);
+    std::cout << "Run Fifo worker with the hazard pointers:" << std::endl;
+    run<sc::hazard::Reclaimer>(sc::deque::Flavor::Fifo);
+
+    std::cout << "hello world" << std::endl;
+
+    return 0;
+}
@@ -0,0 +1,229 @@
+///
+/// @file  scheduler.hpp
+/// @brief A deterministic scheduler for the reproducible tests of the code built on the cells,
+/// locks and queues. Inspired by [shuttle](https://github.com/awslabs/shuttle).
+///
+
+#ifndef SYNC_CELL_SCHEDULER_HPP
+#define SYNC_CELL_SCHEDULER_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The scheduler.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <condition_variable>
+#include <cstddef>
+#include <cstdint>
+#include <exception>
+#include <functional>
+#include <mutex>
+#include <random>
+#include <thread>
+#include <utility>
+#include <vector>
+
+#include "util/back_off.hpp"
+
+
+namespace sc::testing {
+
+class Scheduler;
+
+namespace impl {
+
+inline thread_local Scheduler *CurrentScheduler = nullptr;
+inline thread_local size_t CurrentTask = 0;
+
+}
+
+/// @brief Runs the tasks one at a time, and switches between them only at the yield points. The
+/// next task at each yield point is picked by a random generator with the given seed, so the same
+/// seed always produces the same interleaving, and a failure found by a random seed can be replayed.
+///
+/// Each task runs on its own thread, but only one of them is running at any time. The switch hands
+/// over through a mutex, so it explores the interleavings of the tasks, not the weak memory
+/// behaviors (use the ThreadSanitizer build for them).
+///
+/// A yield point is a call of @c yield_now, or a snooze of the spinning primitives which use the
+/// @c sc::testing::Backoff, e.g. @c sc::lock::SpinLock<T, sc::testing::Backoff>.
+/// @note A task must not block on anything other than the yield points (e.g. a @c std::mutex held
+/// by another task, or the parking primitives in @c sc::sync), otherwise the whole run deadlocks.
+///
+/// @example
+/// ``` cpp
+/// for (uint64_t seed = 0; seed < 100; ++seed) {
+///     sc::testing::Scheduler scheduler(seed);
+///     int value = 0;
+///     for (int i = 0; i < 2; ++i) {
+///         scheduler.spawn([&] {
+///             auto v = value;
+///             sc::testing::yield_now();
+///             value = v + 1;
+///         });
+///     }
+///     scheduler.run();
+///     if (value != 2) { std::cout << "lost update with seed: " << seed << std::endl; }
+/// }
+/// ```
+class Scheduler
+{
+    static constexpr size_t NoTask = SIZE_MAX;
+
+public:
+    explicit Scheduler(uint64_t seed) noexcept : seed_(seed), rng_(seed) { }
+
+    Scheduler(const Scheduler &) = delete;
+
+    Scheduler &operator=(const Scheduler &) = delete;
+
+    [[nodiscard]] uint64_t seed() const noexcept
+    {
+        return seed_;
+    }
+
+    /// @brief Adds a task, which is started by the next @c run.
+    void spawn(std::function<void()> task)
+    {
+        tasks_.push_back(std::move(task));
+    }
+
+    /// @brief Runs all spawned tasks to completion, and rethrows the first exception thrown by them.
+    void run()
+    {
+        runnable_.clear();
+        trace_.clear();
+        for (size_t i = 0; i < tasks_.size(); ++i) {
+            runnable_.push_back(i);
+        }
+        if (!runnable_.empty()) {
+            std::lock_guard guard(mtx_);
+            pick_next();
+        }
+
+        std::vector<std::thread> threads;
+        threads.reserve(tasks_.size());
+        for (size_t i = 0; i < tasks_.size(); ++i) {
+            threads.emplace_back([this, i] { run_task(i); });
+        }
+        for (auto &t: threads) {
+            t.join();
+        }
+        tasks_.clear();
+
+        if (auto e = std::exchange(exception_, nullptr)) {
+            std::rethrow_exception(e);
+        }
+    }
+
+    /// @brief Returns the indices of the tasks picked at each switch of the last @c run, which is
+    /// the same for the same seed.
+    [[nodiscard]] const std::vector<size_t> &trace() const noexcept
+    {
+        return trace_;
+    }
+
+    /// @brief Returns the scheduler running the current thread, or nullptr if it is not a task.
+    [[nodiscard]] static Scheduler *current() noexcept
+    {
+        return impl::CurrentScheduler;
+    }
+
+    /// @brief Switches to a task picked by the random generator, which may be the current task.
+    void yield_current()
+    {
+        std::unique_lock lock(mtx_);
+        pick_next();
+        wait_turn(lock, impl::CurrentTask);
+    }
+
+private:
+    void run_task(size_t index)
+    {
+        impl::CurrentScheduler = this;
+        impl::CurrentTask = index;
+        {
+            std::unique_lock lock(mtx_);
+            wait_turn(lock, index);
+        }
+
+        try {
+            tasks_[index]();
+        } catch (...) {
+            std::lock_guard guard(mtx_);
+            if (!exception_) {
+                exception_ = std::current_exception();
+            }
+        }
+
+        std::lock_guard guard(mtx_);
+        std::erase(runnable_, index);
+        pick_next();
+        impl::CurrentScheduler = nullptr;
+    }
+
+    /// @brief Picks the next task and wakes it up, requires the lock is held.
+    void pick_next()
+    {
+        if (runnable_.empty()) {
+            current_ = NoTask;
+            return;
+        }
+
+        // Not the std distributions: their results are not specified by the standard, so the trace
+        // of a seed would differ between the standard libraries.
+        current_ = runnable_[rng_() % runnable_.size()];
+        trace_.push_back(current_);
+        cond_var_.notify_all();
+    }
+
+    void wait_turn(std::unique_lock<std::mutex> &lock, size_t index)
+    {
+        cond_var_.wait(lock, [this, index] { return current_ == index; });
+    }
+
+    const uint64_t seed_;
+    std::mt19937_64 rng_;
+
+    std::vector<std::function<void()>> tasks_;
+    std::vector<size_t> runnable_;
+    std::vector<size_t> trace_;
+    size_t current_ = NoTask;
+    std::exception_ptr exception_;
+
+    std::mutex mtx_;
+    std::condition_variable cond_var_;
+};
+
+/// @brief A yield point: switches to another task if the current thread is run by a @c Scheduler,
+/// otherwise yields the thread to the OS.
+inline void yield_now()
+{
+    if (auto scheduler = Scheduler::current()) {
+        scheduler->yield_current();
+    } else {
+        std::this_thread::yield();
+    }
+}
+
+/// @brief The backoff strategy which makes each snooze a yield point of the @c Scheduler, and never
+/// advises to park the thread.
+struct Scheduled
+{
+    static constexpr uint32_t SpinLimit = 0;
+    static constexpr uint32_t YieldSteps = 1;
+    static constexpr bool Completable = false;
+
+    static void yield()
+    {
+        yield_now();
+    }
+};
+
+/// @brief The backoff for the spinning primitives under the @c Scheduler.
+using Backoff = util::BasicBackoff<Scheduled>;
+
+}
+
+#endif //SYNC_CELL_SCHEDULER_HPP
@@ -0,0 +1,367 @@
+///
+/// @file  txn.hpp
+/// @brief A software transactional memory for the updates of several cells together, based on
+/// [TL2](https://doi.org/10.1007/11864219_14) (Dice, Shalev and Shavit, DISC 2006).
+///
+
+#ifndef SYNC_CELL_TXN_HPP
+#define SYNC_CELL_TXN_HPP
+
+#include "shared/config.hpp"
+
+#if !SC_HAS_STD
+#error "The txn.hpp requires the std threads, it can not be used with SYNC_CELL_NO_STD."
+#endif
+
+#include <algorithm>
+#include <atomic>
+#include <cstdint>
+#include <type_traits>
+#include <utility>
+#include <vector>
+
+#include "epoch/epoch.hpp"
+#include "util/back_off.hpp"
+
+
+namespace sc::txn {
+
+template<typename T>
+class TxCell;
+
+class Transaction;
+
+namespace impl {
+
+/// @brief The lowest bit of the version word of a cell marks it locked by a committing transaction.
+constexpr uint64_t LockedBit = 1;
+
+/// @brief The global version clock, advanced by each committed transaction which writes.
+inline std::atomic<uint64_t> &version_clock() noexcept
+{
+    static std::atomic<uint64_t> clock{0};
+    return clock;
+}
+
+/// @brief Thrown by a read which sees a newer or locked cell, caught by @c atomically to retry.
+struct Conflict { };
+
+/// @brief The version word shared by all the typed cells.
+struct CellBase
+{
+    /// @brief The version of the last commit written to the cell shifted left by one, and the
+    /// @c LockedBit.
+    std::atomic<uint64_t> meta{0};
+};
+
+/// @brief A buffered write of a transaction, installed into the cell on the commit.
+struct WriteEntry
+{
+    CellBase *cell;
+    /// @brief The new value, owned by the entry until installed.
+    void *value;
+    void (*install)(CellBase *, void *, const epoch::Guard &) noexcept;
+    void (*destroy)(void *) noexcept;
+};
+
+/// @brief Creates and commits the transactions for @c atomically and @c TxCell::store.
+struct TxAccess;
+
+inline Transaction *&current() noexcept
+{
+    thread_local Transaction *tx = nullptr;
+    return tx;
+}
+
+}
+
+/// @brief The handle of a running transaction, passed to the function of @c atomically.
+///
+/// The reads see a consistent snapshot of all cells at the start of the transaction, and the writes
+/// are buffered until the commit, so they are visible to the reads of the same transaction only.
+/// A read of a cell committed by another transaction after the start aborts the attempt by an
+/// internal exception, so the function must not catch all exceptions around the reads.
+class Transaction
+{
+    friend struct impl::TxAccess;
+
+    Transaction() : read_version_(impl::version_clock().load(std::memory_order_acquire)) { }
+
+public:
+    Transaction(const Transaction &) = delete;
+
+    Transaction &operator=(const Transaction &) = delete;
+
+    ~Transaction()
+    {
+        for (auto &entry: writes_) {
+            entry.destroy(entry.value);
+        }
+    }
+
+    /// @brief Returns the value of the cell in the snapshot of the transaction, or the value written
+    /// by the transaction. The reference keeps valid until the transaction ends or writes the cell.
+    template<typename T>
+    [[nodiscard]] const T &read(const TxCell<T> &cell)
+    {
+        if (auto *entry = find_write(&cell)) {
+            return *static_cast<const T *>(entry->value);
+        }
+
+        auto pre = cell.meta.load(std::memory_order_acquire);
+        if ((pre & impl::LockedBit) != 0 || pre >> 1 > read_version_) {
+            throw impl::Conflict();
+        }
+        auto *value = cell.value_.load(std::memory_order_acquire);
+        if (cell.meta.load(std::memory_order_acquire) != pre) {
+            throw impl::Conflict();
+        }
+        reads_.push_back(&cell);
+        return *value;
+    }
+
+    /// @brief Buffers a write of the cell, which is visible to the other threads after the commit.
+    template<typename T, typename U>
+    void write(TxCell<T> &cell, U &&value)
+    {
+        if (auto *entry = find_write(&cell)) {
+            *static_cast<T *>(entry->value) = std::forward<U>(value);
+            return;
+        }
+
+        auto *buffered = new T(std::forward<U>(value));
+        try {
+            writes_.push_back(impl::WriteEntry{&cell, buffered, &TxCell<T>::install, &TxCell<T>::destroy});
+        } catch (...) {
+            delete buffered;
+            throw;
+        }
+    }
+
+    /// @brief Writes the result of 'f' called with the current value of the cell.
+    template<typename T, typename F>
+    void update(TxCell<T> &cell, F &&f)
+    {
+        write(cell, std::forward<F>(f)(read(cell)));
+    }
+
+private:
+    [[nodiscard]] impl::WriteEntry *find_write(const impl::CellBase *cell) noexcept
+    {
+        for (auto &entry: writes_) {
+            if (entry.cell == cell) {
+                return &entry;
+            }
+        }
+        return nullptr;
+    }
+
+    /// @brief Locks the written cells, validates the read ones and installs the writes.
+    /// @return false if another transaction has conflicted, the caller retries then.
+    bool commit(const epoch::Guard &guard) noexcept
+    {
+        // A read-only transaction has been validated by each read.
+        if (writes_.empty()) {
+            return true;
+        }
+
+        // The cells are locked in the address order, so two committing transactions do not wait for
+        // each other in a cycle.
+        std::sort(writes_.begin(), writes_.end(), [](const auto &a, const auto &b) { return a.cell < b.cell; });
+        size_t locked = 0;
+        for (; locked < writes_.size(); ++locked) {
+            auto &meta = writes_[locked].cell->meta;
+            auto current = meta.load(std::memory_order_relaxed);
+            if ((current & impl::LockedBit) != 0 ||
+                !meta.compare_exchange_strong(current, current | impl::LockedBit, std::memory_order_acquire,
+                                              std::memory_order_relaxed)) {
+                unlock(locked);
+                return false;
+            }
+        }
+
+        auto write_version = impl::version_clock().fetch_add(1, std::memory_order_acq_rel) + 1;
+        // No transaction has committed since the start, so the reads are still the latest values.
+        if (write_version != read_version_ + 1) {
+            for (const auto *cell: reads_) {
+                auto meta = cell->meta.load(std::memory_order_acquire);
+                bool locked_by_other = (meta & impl::LockedBit) != 0 && find_write(cell) == nullptr;
+                if (locked_by_other || meta >> 1 > read_version_) {
+                    unlock(writes_.size());
+                    return false;
+                }
+            }
+        }
+
+        for (auto &entry: writes_) {
+            entry.install(entry.cell, std::exchange(entry.value, nullptr), guard);
+            entry.cell->meta.store(write_version << 1, std::memory_order_release);
+        }
+        writes_.clear();
+        return true;
+    }
+
+    void unlock(size_t count) noexcept
+    {
+        for (size_t i = 0; i < count; ++i) {
+            writes_[i].cell->meta.fetch_and(~impl::LockedBit, std::memory_order_release);
+        }
+    }
+
+    uint64_t read_version_;
+    std::vector<const impl::CellBase *> reads_;
+    std::vector<impl::WriteEntry> writes_;
+};
+
+namespace impl {
+
+struct TxAccess
+{
+    static Transaction begin()
+    {
+        return Transaction();
+    }
+
+    static bool commit(Transaction &tx, const epoch::Guard &guard) noexcept
+    {
+        return tx.commit(guard);
+    }
+};
+
+}
+
+/// @brief A versioned cell which is read and written by the transactions of @c atomically, so the
+/// invariants between several cells hold without one lock around all of them.
+///
+/// The value is kept in an immutable heap block replaced by each commit, and the old blocks are
+/// destroyed by the epoch-based reclamation after the readers unpin.
+/// @tparam T The value type, which is copy or move constructible.
+template<typename T>
+class TxCell : impl::CellBase
+{
+    friend class Transaction;
+
+public:
+    using value_type = T;
+
+    template<typename... Args>
+    explicit TxCell(Args &&... args) : value_(new T(std::forward<Args>(args)...)) { }
+
+    TxCell(const TxCell &) = delete;
+
+    TxCell &operator=(const TxCell &) = delete;
+
+    ~TxCell()
+    {
+        delete value_.load(std::memory_order_relaxed);
+    }
+
+    /// @brief Returns a copy of the latest committed value, as a transaction reading only this cell.
+    [[nodiscard]] T load() const;
+
+    /// @brief Replaces the value, as a transaction writing only this cell.
+    template<typename U>
+    void store(U &&value);
+
+private:
+    static void install(impl::CellBase *cell, void *value, const epoch::Guard &guard) noexcept
+    {
+        auto *self = static_cast<TxCell *>(cell);
+        auto *old = self->value_.exchange(static_cast<T *>(value), std::memory_order_acq_rel);
+        guard.defer_destroy(old);
+    }
+
+    static void destroy(void *value) noexcept
+    {
+        delete static_cast<T *>(value);
+    }
+
+    std::atomic<T *> value_;
+};
+
+/// @brief Runs 'f' as a transaction, and retries it until it commits without a conflict:
+/// ``` cpp
+/// sc::txn::TxCell<int64_t> from(100), to(0);
+/// sc::txn::atomically([&](sc::txn::Transaction &tx) {
+///     auto amount = std::min<int64_t>(tx.read(from), 30);
+///     tx.write(from, tx.read(from) - amount);
+///     tx.write(to, tx.read(to) + amount);
+/// });
+/// ```
+///
+/// So 'f' may run several times, and must not have the side effects other than the writes of the
+/// cells. An exception thrown by 'f' discards the writes of the transaction and is propagated. A
+/// nested @c atomically joins the transaction running on the thread.
+/// @tparam Backoff The backoff between the retries.
+/// @param f The function with the signature of 'R(Transaction &)'.
+/// @return The result of 'f' in the committed attempt.
+template<typename Backoff = util::Backoff, typename F>
+auto atomically(F &&f) -> std::invoke_result_t<F &, Transaction &>
+{
+    using R = std::invoke_result_t<F &, Transaction &>;
+
+    if (auto *outer = impl::current()) {
+        return f(*outer);
+    }
+
+    Backoff backoff;
+    while (true) {
+        // The blocks read by the transaction are not destroyed until it ends.
+        auto guard = epoch::pin();
+        auto tx = impl::TxAccess::begin();
+        impl::current() = &tx;
+        struct Reset
+        {
+            ~Reset()
+            {
+                impl::current() = nullptr;
+            }
+        } reset;
+
+        try {
+            if constexpr (std::is_void_v<R>) {
+                f(tx);
+                if (impl::TxAccess::commit(tx, guard)) {
+                    return;
+                }
+            } else {
+                R result = f(tx);
+                if (impl::TxAccess::commit(tx, guard)) {
+                    return result;
+                }
+            }
+        } catch (const impl::Conflict &) {
+        }
+        backoff.snooze();
+    }
+}
+
+template<typename T>
+T TxCell<T>::load() const
+{
+    return atomically([this](Transaction &tx) { return tx.read(*this); });
+}
+
+template<typename T>
+template<typename U>
+void TxCell<T>::store(U &&value)
+{
+    if (auto *outer = impl::current()) {
+        outer->write(*this, std::forward<U>(value));
+        return;
+    }
+
+    // A blind write only fails to commit while another commit holds the cell, so the commit is
+    // retried with the value written once.
+    auto guard = epoch::pin();
+    auto tx = impl::TxAccess::begin();
+    tx.write(*this, std::forward<U>(value));
+    util::Backoff backoff;
+    while (!impl::TxAccess::commit(tx, guard)) {
+        backoff.snooze();
+    }
+}
+
+}
+
+#endif //SYNC_CELL_TXN_HPP
@@ -9,13 +9,61 @@
 
 #include <algorithm>
 #include <cstdint>
-#include <thread>
 
+#include "shared/config.hpp"
 #include "intrin_wrapper.hpp"
 
+#if SC_HAS_STD
+#include <thread>
+#endif
+
+#if !SC_HAS_THREADS
+#include "shared/single_thread.hpp"
+#endif
+
 
 namespace sc::util {
 
+namespace backoff {
+
+/// @brief The default strategy: spins, then yields the thread for a while, and then advises to
+/// park the thread. Ported from crossbeam.
+struct Exponential
+{
+    static constexpr uint32_t SpinLimit = 6;
+    static constexpr uint32_t YieldSteps = 4;
+    static constexpr bool Completable = true;
+};
+
+/// @brief Only spins, never yields the thread or advises to park the thread. Suitable for the
+/// short waits on the dedicated cores.
+struct SpinOnly
+{
+    static constexpr uint32_t SpinLimit = 6;
+    static constexpr uint32_t YieldSteps = 0;
+    static constexpr bool Completable = false;
+};
+
+/// @brief Spins, and then always yields the thread, never advises to park the thread.
+struct SpinThenYield
+{
+    static constexpr uint32_t SpinLimit = 6;
+    static constexpr uint32_t YieldSteps = 1;
+    static constexpr bool Completable = false;
+};
+
+/// @brief Spins for a short while, and then advises to park the thread without yielding. Suitable
+/// for the oversubscribed machines, where the spinning and yielding threads steal the time slices
+/// of the threads making progress.
+struct SpinThenPark
+{
+    static constexpr uint32_t SpinLimit = 4;
+    static constexpr uint32_t YieldSteps = 0;
+    static constexpr bool Completable = true;
+};
+
+}
+
 /// @brief Performs exponential backoff in spin loops.
 ///
 /// Backing off in spin loops reduces contention and improves overall performance.
@@ -62,13 +110,19 @@ namespace sc::util {
 ///     }
 /// }
 /// ```
-class Backoff
+/// @tparam Strategy The backoff strategy, see the @c sc::util::backoff namespace. If the strategy
+/// has a static @c yield() function, it is called instead of yielding the thread to the OS.
+template<typename Strategy>
+class BasicBackoff
 {
-    static constexpr uint32_t SpinLimit = 6;
-    static constexpr uint32_t YieldLimit = 10;
+    static constexpr uint32_t SpinLimit = Strategy::SpinLimit;
+    static constexpr uint32_t YieldLimit = Strategy::SpinLimit + Strategy::YieldSteps;
+    static constexpr bool CustomYield = requires { Strategy::yield(); };
 
 public:
-    constexpr Backoff() noexcept = default;
+    using strategy_type = Strategy;
+
+    constexpr BasicBackoff() noexcept = default;
 
     void reset() noexcept
     {
@@ -101,15 +155,35 @@ public:
     ///
     /// @note If possible, use @c is_completed to check when it is advised to stop using backoff
     /// and block the current thread using a different synchronization mechanism instead.
+    /// @note On a single-threaded target, no other thread can make the progress: the wait is
+    /// reported by @c sc::single_thread::would_block, unless the strategy yields to the tasks of
+    /// the caller, e.g. the coroutines of a cooperative scheduler.
     void snooze() noexcept
     {
-        if (step_ <= SpinLimit) {
-            auto s = 1u << step_;
+#if !SC_HAS_THREADS
+        if constexpr (CustomYield) {
+            Strategy::yield();
+            return;
+        } else {
+            single_thread::would_block("sc::util::Backoff::snooze: waiting for another thread");
+        }
+#endif
+        if (step_ <= SpinLimit || YieldLimit == SpinLimit) {
+            auto s = 1u << std::min(step_, SpinLimit);
             for (uint32_t i = 0; i < s; ++i) {
                 spin_loop_hint();
             }
+        } else if constexpr (CustomYield) {
+            Strategy::yield();
         } else {
+#if SC_HAS_STD
             std::this_thread::yield();
+#else
+            // No scheduler to yield to, keep spinning.
+            for (uint32_t i = 0; i < (1u << SpinLimit); ++i) {
+                spin_loop_hint();
+            }
+#endif
         }
 
         if (step_ <= YieldLimit) {
@@ -118,15 +192,20 @@ public:
     }
 
     /// @brief Returns true if exponential backoff has completed and blocking the thread is advised.
+    ///
+    /// A strategy which is not completable never advises blocking, so the caller keeps backing off.
     [[nodiscard]] bool is_completed() const noexcept
     {
-        return step_ > YieldLimit;
+        return Strategy::Completable && step_ > YieldLimit;
     }
 
 private:
     uint32_t step_ = 0;
 };
 
+/// @brief The backoff with the default strategy.
+using Backoff = BasicBackoff<backoff::Exponential>;
+
 }
 
 #endif //SYNC_CELL_BACK_OFF_HPP
@@ -27,8 +27,31 @@ constexpr std::size_t hardware_destructive_interference_size = 64;
 
 }
 
+/// @brief The alignment of the @c CachePadded. The same as crossbeam-rs, 128 bytes on x86-64 and
+/// PowerPC 64, where the spatial prefetcher pulls the cache lines in pairs, and on AArch64, where
+/// some cores (e.g. Apple M series) have 128-byte cache lines. Otherwise, it is the destructive
+/// interference size of the compiler.
+#if defined(__x86_64__) || defined(_M_X64) || defined(__aarch64__) || defined(_M_ARM64) || defined(__powerpc64__)
+inline constexpr std::size_t CacheLineSize = 128;
+#else
+inline constexpr std::size_t CacheLineSize = impl::hardware_destructive_interference_size;
+#endif
+
+/// @brief Pads and aligns a value to @c CacheLineSize, so it does not share the cache line with
+/// the other data, which avoids the false sharing between the values written by different threads,
+/// e.g. the head and the tail of a queue.
+///
+/// @example
+/// ``` cpp
+/// struct Counters
+/// {
+///     sc::util::CachePadded<std::atomic<uint64_t>> produced;
+///     sc::util::CachePadded<std::atomic<uint64_t>> consumed;
+/// };
+/// counters.produced->fetch_add(1, std::memory_order_relaxed);
+/// ```
 template<typename T>
-class alignas(impl::hardware_destructive_interference_size) CachePadded
+class alignas(CacheLineSize) CachePadded
 {
     static_assert(!std::is_reference_v<T>);
 
@@ -0,0 +1,230 @@
+///
+/// @file  drop_queue.hpp
+/// @brief A queue of the replaced values whose destruction is deferred out of the writers, to be
+/// done by an explicit collection or a background collector.
+///
+
+#ifndef SYNC_CELL_DROP_QUEUE_HPP
+#define SYNC_CELL_DROP_QUEUE_HPP
+
+#include <atomic>
+#include <cstddef>
+#include <type_traits>
+#include <utility>
+
+#include "shared/config.hpp"
+
+#if SC_HAS_STD
+#include <chrono>
+#include <condition_variable>
+#include <mutex>
+#include <thread>
+#endif
+
+
+namespace sc::util {
+
+/// @brief A lock-free queue of the values waiting for their destruction, e.g. the old values
+/// replaced in a @c SyncCell with the @c DeferredDrop backend, whose destructors free a large
+/// amount of memory and would stall the writer:
+/// ``` cpp
+/// sc::SyncCell<RoutingTable, sc::DeferredDrop<>> table;
+/// table.store(build_table());   // The old table is parked in the global queue.
+/// // Later, on a thread where the latency does not matter.
+/// sc::util::DropQueue::global().collect();
+/// ```
+///
+/// @c defer moves the value into a heap node pushed by one CAS, or pushes the node of a
+/// @c Reservation allocated ahead, and @c collect destroys all parked values on the calling thread.
+/// The destructors must not throw.
+class DropQueue
+{
+    struct Node
+    {
+        Node *next;
+        void (*drop)(Node *) noexcept;
+    };
+
+    template<typename T>
+    struct TypedNode : Node
+    {
+        explicit TypedNode(T &&v) : Node{nullptr, &TypedNode::drop_node}, value(std::move(v)) { }
+
+        static void drop_node(Node *node) noexcept
+        {
+            delete static_cast<TypedNode *>(node);
+        }
+
+        T value;
+    };
+
+public:
+    /// @brief A value in a node allocated ahead of the @c defer, so a writer can allocate before it
+    /// replaces the value of the cell, and park the replaced value without failing:
+    /// ``` cpp
+    /// sc::util::DropQueue::Reservation<Table> reservation(std::move(table));   // May throw.
+    /// std::swap(current, reservation.value());
+    /// queue.defer(std::move(reservation));                                      // Never throws.
+    /// ```
+    /// The value not parked is destroyed with the reservation.
+    template<typename T>
+    class Reservation
+    {
+        friend class DropQueue;
+
+    public:
+        explicit Reservation(T value) : node_(new TypedNode<T>(std::move(value))) { }
+
+        Reservation(const Reservation &) = delete;
+
+        Reservation &operator=(const Reservation &) = delete;
+
+        ~Reservation()
+        {
+            if (node_ != nullptr) {
+                TypedNode<T>::drop_node(node_);
+            }
+        }
+
+        [[nodiscard]] T &value() noexcept
+        {
+            return node_->value;
+        }
+
+    private:
+        TypedNode<T> *node_;
+    };
+
+    constexpr DropQueue() noexcept = default;
+
+    DropQueue(const DropQueue &) = delete;
+
+    DropQueue &operator=(const DropQueue &) = delete;
+
+    /// @brief Destroys the values still parked.
+    ~DropQueue()
+    {
+        collect();
+    }
+
+    /// @brief Returns the queue used by the @c DeferredDrop cells, which is collected at the exit of
+    /// the program at the latest.
+    static DropQueue &global() noexcept
+    {
+        static DropQueue queue;
+        return queue;
+    }
+
+    /// @brief Parks the 'value' to be destroyed by a later @c collect.
+    /// @throw std::bad_alloc If the node can not be allocated, the 'value' is destroyed by the
+    /// caller then.
+    template<typename T>
+    void defer(T &&value)
+    {
+        using Value = std::remove_cvref_t<T>;
+        static_assert(std::is_nothrow_destructible_v<Value>);
+
+        push(new TypedNode<Value>(std::forward<T>(value)));
+    }
+
+    /// @brief Parks the value of the 'reservation', without any allocation.
+    template<typename T>
+    void defer(Reservation<T> &&reservation) noexcept
+    {
+        static_assert(std::is_nothrow_destructible_v<T>);
+
+        push(std::exchange(reservation.node_, nullptr));
+    }
+
+    /// @brief Destroys all values parked before the call, on the calling thread.
+    /// @return The count of the destroyed values.
+    size_t collect() noexcept
+    {
+        auto *node = head_.exchange(nullptr, std::memory_order_acquire);
+        size_t count = 0;
+        while (node != nullptr) {
+            auto *next = node->next;
+            node->drop(node);
+            node = next;
+            ++count;
+        }
+        parked_.fetch_sub(count, std::memory_order_relaxed);
+        return count;
+    }
+
+    /// @brief Returns the count of the parked values, which may be outdated by the concurrent calls.
+    [[nodiscard]] size_t parked() const noexcept
+    {
+        return parked_.load(std::memory_order_relaxed);
+    }
+
+private:
+    void push(Node *node) noexcept
+    {
+        node->next = head_.load(std::memory_order_relaxed);
+        while (!head_.compare_exchange_weak(node->next, node, std::memory_order_release,
+                                            std::memory_order_relaxed)) {
+        }
+        parked_.fetch_add(1, std::memory_order_relaxed);
+    }
+
+    std::atomic<Node *> head_{nullptr};
+    std::atomic<size_t> parked_{0};
+};
+
+#if SC_HAS_STD
+
+/// @brief A background thread collecting a @c DropQueue periodically, until it is destroyed.
+/// ``` cpp
+/// // In the main function, before the cells are written.
+/// sc::util::DropCollector collector(std::chrono::milliseconds(50));
+/// ```
+class DropCollector
+{
+public:
+    /// @param interval The time between two collections.
+    /// @param queue The queue to collect, which must outlive the collector.
+    explicit DropCollector(std::chrono::milliseconds interval = std::chrono::milliseconds(10),
+                           DropQueue &queue = DropQueue::global())
+            : queue_(queue), interval_(interval), thread_([this] { run(); }) { }
+
+    DropCollector(const DropCollector &) = delete;
+
+    DropCollector &operator=(const DropCollector &) = delete;
+
+    /// @brief Stops the thread after a last collection.
+    ~DropCollector()
+    {
+        {
+            std::lock_guard guard(mtx_);
+            stopped_ = true;
+        }
+        cond_var_.notify_one();
+        thread_.join();
+    }
+
+private:
+    void run()
+    {
+        std::unique_lock lock(mtx_);
+        while (!stopped_) {
+            cond_var_.wait_for(lock, interval_, [this] { return stopped_; });
+            lock.unlock();
+            queue_.collect();
+            lock.lock();
+        }
+    }
+
+    DropQueue &queue_;
+    std::chrono::milliseconds interval_;
+    std::mutex mtx_;
+    std::condition_variable cond_var_;
+    bool stopped_ = false;
+    std::thread thread_;
+};
+
+#endif
+
+}
+
+#endif //SYNC_CELL_DROP_QUEUE_HPP
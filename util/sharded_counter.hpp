@@ -0,0 +1,85 @@
+///
+/// @file  sharded_counter.hpp
+/// @brief A counter split into the cache-padded shards, for the high-throughput counting from many
+/// threads.
+///
+
+#ifndef SYNC_CELL_SHARDED_COUNTER_HPP
+#define SYNC_CELL_SHARDED_COUNTER_HPP
+
+#include <array>
+#include <atomic>
+#include <bit>
+#include <cstddef>
+#include <cstdint>
+
+#include "util/cache_padded.hpp"
+#include "util/thread_id.hpp"
+
+
+namespace sc::util {
+
+/// @brief A counter which is split into 'ShardCount' shards, each in its own cache line. A thread
+/// always adds to the same shard, so the threads do not contend on one atomic variable, unlike the
+/// @c std::atomic<uint64_t>. The reads are slower instead, which aggregate all shards.
+///
+/// The @c sum is not a snapshot: the additions running concurrently with it may or may not be
+/// counted. It is exact when no thread is adding, e.g. after the threads are joined.
+///
+/// @example
+/// ``` cpp
+/// sc::util::ShardedCounter<> processed;
+/// // In the worker threads.
+/// processed.add();
+/// // After the workers are joined.
+/// auto total = processed.sum();
+/// ```
+/// @tparam ShardCount The count of shards, a power of 2. The threads more than it share the shards.
+template<size_t ShardCount = 16>
+class ShardedCounter
+{
+    static_assert(std::has_single_bit(ShardCount), "The shard count must be a power of 2.");
+
+public:
+    constexpr ShardedCounter() noexcept = default;
+
+    ShardedCounter(const ShardedCounter &) = delete;
+
+    ShardedCounter &operator=(const ShardedCounter &) = delete;
+
+    /// @brief Adds 'n' to the shard of the current thread.
+    void add(uint64_t n = 1)
+    {
+        shards_[thread_id() & (ShardCount - 1)]->fetch_add(n, std::memory_order_relaxed);
+    }
+
+    /// @brief Returns the sum of all shards.
+    [[nodiscard]] uint64_t sum() const noexcept
+    {
+        uint64_t sum = 0;
+        for (auto &shard: shards_) {
+            sum += shard->load(std::memory_order_relaxed);
+        }
+        return sum;
+    }
+
+    /// @brief Resets all shards to 0. The additions running concurrently with it may be lost.
+    void reset() noexcept
+    {
+        for (auto &shard: shards_) {
+            shard->store(0, std::memory_order_relaxed);
+        }
+    }
+
+    [[nodiscard]] static constexpr size_t shard_count() noexcept
+    {
+        return ShardCount;
+    }
+
+private:
+    std::array<CachePadded<std::atomic<uint64_t>>, ShardCount> shards_{};
+};
+
+}
+
+#endif //SYNC_CELL_SHARDED_COUNTER_HPP
@@ -0,0 +1,106 @@
+///
+/// @file  thread_id.hpp
+/// @brief The small, dense and reusable thread ids, to index the per-thread slots.
+///
+
+#ifndef SYNC_CELL_THREAD_ID_HPP
+#define SYNC_CELL_THREAD_ID_HPP
+
+#include <algorithm>
+#include <atomic>
+#include <cstddef>
+#include <functional>
+#include <vector>
+
+#include "util/back_off.hpp"
+
+
+namespace sc::util {
+
+namespace impl {
+
+/// @brief Hands out the thread ids, the ids of the exited threads are reused from the lowest one,
+/// so the ids stay as small as the count of the live threads.
+class ThreadIdRegistry
+{
+public:
+    static ThreadIdRegistry &instance() noexcept
+    {
+        static ThreadIdRegistry registry;
+        return registry;
+    }
+
+    size_t acquire()
+    {
+        lock();
+        size_t id;
+        if (free_.empty()) {
+            id = next_++;
+        } else {
+            std::pop_heap(free_.begin(), free_.end(), std::greater<>());
+            id = free_.back();
+            free_.pop_back();
+        }
+        unlock();
+        return id;
+    }
+
+    void release(size_t id)
+    {
+        lock();
+        free_.push_back(id);
+        std::push_heap(free_.begin(), free_.end(), std::greater<>());
+        unlock();
+    }
+
+private:
+    /// @brief Only taken when a thread starts or exits, so a spin lock is enough.
+    void lock() noexcept
+    {
+        Backoff backoff;
+        while (locked_.test_and_set(std::memory_order_acquire)) {
+            backoff.snooze();
+        }
+    }
+
+    void unlock() noexcept
+    {
+        locked_.clear(std::memory_order_release);
+    }
+
+    std::atomic_flag locked_ = ATOMIC_FLAG_INIT;
+    size_t next_ = 0;
+    /// @brief The min-heap of the released ids.
+    std::vector<size_t> free_;
+};
+
+/// @brief Holds the id of a thread, and releases it when the thread exits.
+struct ThreadIdHolder
+{
+    ThreadIdHolder() : id(ThreadIdRegistry::instance().acquire()) { }
+
+    ~ThreadIdHolder()
+    {
+        ThreadIdRegistry::instance().release(id);
+    }
+
+    ThreadIdHolder(const ThreadIdHolder &) = delete;
+
+    ThreadIdHolder &operator=(const ThreadIdHolder &) = delete;
+
+    const size_t id;
+};
+
+}
+
+/// @brief Returns the id of the current thread. The live threads have distinct ids, and the id of
+/// an exited thread is given to a later thread, so the ids are dense from 0.
+inline size_t thread_id()
+{
+    thread_local const impl::ThreadIdHolder holder;
+    return holder.id;
+}
+
+}
+
+#endif //SYNC_CELL_THREAD_ID_HPP